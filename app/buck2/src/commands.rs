@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+pub(crate) mod daemon;
 #[cfg(not(client_only))]
 pub(crate) mod forkserver;
 #[cfg(not(client_only))]
@@ -0,0 +1,40 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use buck2_client::commands::daemon::DaemonSubcommand;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::exit_result::ExitResult;
+
+/// Inspect the running buck daemon.
+///
+/// Without a subcommand, this starts or runs the daemon. That is an internal command: the buck
+/// client invokes it to spawn a server process.
+#[derive(Debug, clap::Parser)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub(crate) struct DaemonCommand {
+    #[clap(subcommand)]
+    pub(crate) subcommand: Option<DaemonSubcommand>,
+    #[cfg(not(client_only))]
+    #[clap(flatten)]
+    pub(crate) start: buck2_daemon::daemon::DaemonCommand,
+}
+
+impl DaemonCommand {
+    pub(crate) fn exec(
+        self,
+        matches: &clap::ArgMatches,
+        ctx: ClientCommandContext<'_>,
+    ) -> ExitResult {
+        let matches = matches.subcommand().expect("subcommand not found").1;
+        match self.subcommand {
+            Some(cmd) => cmd.exec(matches, ctx),
+            None => ExitResult::bail("`buck2 daemon` requires a subcommand"),
+        }
+    }
+}
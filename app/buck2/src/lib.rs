@@ -27,6 +27,8 @@ use buck2_client::commands::kill::KillCommand;
 use buck2_client::commands::killall::KillallCommand;
 use buck2_client::commands::log::LogCommand;
 use buck2_client::commands::lsp::LspCommand;
+use buck2_client::commands::pick::PickCommand;
+use buck2_client::commands::prelude::PreludeCommand;
 use buck2_client::commands::profile::ProfileCommand;
 use buck2_client::commands::query::aquery::AqueryCommand;
 use buck2_client::commands::query::cquery::CqueryCommand;
@@ -40,8 +42,11 @@ use buck2_client::commands::subscribe::SubscribeCommand;
 use buck2_client::commands::targets::TargetsCommand;
 use buck2_client::commands::test::TestCommand;
 use buck2_client_ctx::argfiles::expand_argfiles_with_context;
+use buck2_client_ctx::argfiles::format_resolved_args;
+use buck2_client_ctx::argfiles::take_emit_resolved_args;
 use buck2_client_ctx::client_ctx::ClientCommandContext;
 use buck2_client_ctx::client_metadata::ClientMetadata;
+use buck2_client_ctx::command_aliases::CommandAliases;
 use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::immediate_config::ImmediateConfigContext;
 use buck2_client_ctx::streaming::BuckSubcommand;
@@ -50,7 +55,6 @@ use buck2_client_ctx::version::BuckVersion;
 use buck2_cmd_starlark_client::StarlarkCommand;
 use buck2_common::argv::Argv;
 use buck2_common::invocation_paths_result::InvocationPathsResult;
-use buck2_common::invocation_roots::get_invocation_paths_result;
 use buck2_core::buck2_env_anyhow;
 use buck2_core::fs::paths::file_name::FileNameBuf;
 use buck2_error::buck2_error;
@@ -63,6 +67,7 @@ use clap::FromArgMatches;
 use dupe::Dupe;
 
 use crate::check_user_allowed::check_user_allowed;
+use crate::commands::daemon::DaemonCommand;
 use crate::process_context::ProcessContext;
 
 mod check_user_allowed;
@@ -124,6 +129,11 @@ struct BeforeSubcommandOptions {
     #[clap(long, global = true)]
     client_metadata: Vec<ClientMetadata>,
 
+    /// Metadata key-value pairs like `--client-metadata`, which are also readable from BXL with
+    /// `ctx.build_metadata`, e.g. to pass a CI run identifier through to generated artifacts.
+    #[clap(long, global = true, value_parser = ClientMetadata::parse_build_visible)]
+    build_metadata: Vec<ClientMetadata>,
+
     /// Do not launch a daemon process, run buck server in client process.
     ///
     /// Note even when running in no-buckd mode, it still writes state files.
@@ -204,7 +214,34 @@ pub fn exec(process: ProcessContext<'_>) -> ExitResult {
         expanded_args[0] = arg0.to_owned();
     }
 
-    let clap = Opt::command();
+    let mut clap = Opt::command();
+    let subcommand = CommandAliases::subcommand(&expanded_args, &clap).map(str::to_owned);
+    match subcommand.as_deref() {
+        Some(name) if !CommandAliases::is_builtin(&clap, name) => {
+            // Only unknown subcommands need the config, so don't fail outside of a project.
+            if let Ok(aliases) = immediate_config.command_aliases() {
+                expanded_args = aliases
+                    .expand(expanded_args, &clap)
+                    .buck_error_context("Error expanding command alias")?;
+            }
+        }
+        None | Some("help") => {
+            if let Some(help) = immediate_config
+                .command_aliases()
+                .ok()
+                .and_then(|aliases| aliases.help())
+            {
+                clap = clap.after_help(help);
+            }
+        }
+        Some(_) => {}
+    }
+
+    if take_emit_resolved_args(&mut expanded_args) {
+        buck2_client_ctx::println!("{}", format_resolved_args(&expanded_args))?;
+        return ExitResult::success();
+    }
+
     let matches = clap.get_matches_from(&expanded_args);
     let opt: Opt = Opt::from_arg_matches(&matches)?;
 
@@ -217,7 +254,10 @@ pub fn exec(process: ProcessContext<'_>) -> ExitResult {
 
     match &opt.cmd {
         #[cfg(not(client_only))]
-        CommandKind::Daemon(..) | CommandKind::Forkserver(..) => {}
+        CommandKind::Daemon(DaemonCommand {
+            subcommand: None, ..
+        })
+        | CommandKind::Forkserver(..) => {}
         CommandKind::Clean(..) => {}
         _ => {
             check_user_allowed()?;
@@ -234,9 +274,6 @@ pub fn exec(process: ProcessContext<'_>) -> ExitResult {
 
 #[derive(Debug, clap::Subcommand)]
 pub(crate) enum CommandKind {
-    #[cfg(not(client_only))]
-    #[clap(hide = true)]
-    Daemon(buck2_daemon::daemon::DaemonCommand),
     #[cfg(not(client_only))]
     #[clap(hide = true)]
     Forkserver(crate::commands::forkserver::ForkserverCommand),
@@ -248,6 +285,7 @@ pub(crate) enum CommandKind {
     Aquery(AqueryCommand),
     Build(BuildCommand),
     Bxl(BxlCommand),
+    Daemon(DaemonCommand),
     // TODO(nga): implement `buck2 help-buckconfig` too
     //   https://www.internalfb.com/tasks/?t=183528129
     HelpEnv(HelpEnvCommand),
@@ -287,6 +325,9 @@ pub(crate) enum CommandKind {
     #[clap(subcommand)]
     Log(LogCommand),
     Lsp(LspCommand),
+    Pick(PickCommand),
+    #[clap(subcommand)]
+    Prelude(PreludeCommand),
     Subscribe(SubscribeCommand),
 }
 
@@ -300,13 +341,17 @@ impl CommandKind {
         common_opts: BeforeSubcommandOptions,
     ) -> ExitResult {
         let paths_result =
-            get_invocation_paths_result(process.working_dir, common_opts.isolation_dir.clone());
+            immediate_config.invocation_paths_result(common_opts.isolation_dir.clone());
 
         // Handle the daemon command earlier: it wants to fork, but the things we do below might
         // want to create threads.
         #[cfg(not(client_only))]
-        if let CommandKind::Daemon(cmd) = self {
-            return cmd
+        if let CommandKind::Daemon(DaemonCommand {
+            subcommand: None,
+            start,
+        }) = self
+        {
+            return start
                 .exec(
                     process.log_reload_handle.dupe(),
                     paths_result.get_result()?,
@@ -387,13 +432,15 @@ impl CommandKind {
             process.restarted_trace_id.dupe(),
             &runtime,
             common_opts.oncall,
-            common_opts.client_metadata,
+            common_opts
+                .client_metadata
+                .into_iter()
+                .chain(common_opts.build_metadata)
+                .collect(),
             common_opts.isolation_dir,
         );
 
         match self {
-            #[cfg(not(client_only))]
-            CommandKind::Daemon(..) => unreachable!("Checked earlier"),
             #[cfg(not(client_only))]
             CommandKind::Forkserver(cmd) => cmd
                 .exec(matches, command_ctx, process.log_reload_handle.dupe())
@@ -405,6 +452,7 @@ impl CommandKind {
             CommandKind::Bxl(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Test(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Cquery(cmd) => cmd.exec(matches, command_ctx),
+            CommandKind::Daemon(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::HelpEnv(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Kill(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Killall(cmd) => cmd.exec(matches, command_ctx),
@@ -436,6 +484,8 @@ impl CommandKind {
             CommandKind::Install(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Log(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Lsp(cmd) => cmd.exec(matches, command_ctx),
+            CommandKind::Pick(cmd) => cmd.exec(matches, command_ctx),
+            CommandKind::Prelude(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::Subscribe(cmd) => cmd.exec(matches, command_ctx),
             CommandKind::ExpandExternalCell(cmd) => cmd.exec(matches, command_ctx),
         }
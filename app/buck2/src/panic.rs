@@ -63,6 +63,7 @@ mod imp {
     use buck2_data::Location;
     use buck2_events::metadata;
     use buck2_events::sink::remote::new_remote_event_sink_if_enabled;
+    use buck2_events::sink::remote::RemoteEventSink;
     use buck2_events::BuckEvent;
     use buck2_util::threads::thread_spawn;
     use fbinit::FacebookInit;
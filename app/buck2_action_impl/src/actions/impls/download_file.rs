@@ -31,6 +31,7 @@ use buck2_common::io::trace::TracingIoProvider;
 use buck2_core::category::CategoryRef;
 use buck2_error::BuckErrorContext;
 use buck2_error::ErrorTag;
+use buck2_execute::artifact::fs::ExecutorFs;
 use buck2_execute::artifact_value::ArtifactValue;
 use buck2_execute::digest_config::DigestConfig;
 use buck2_execute::execute::command_executor::ActionExecutionTimingData;
@@ -40,6 +41,8 @@ use buck2_execute::materialize::http::Checksum;
 use buck2_execute::materialize::materializer::HttpDownloadInfo;
 use buck2_http::HttpClient;
 use dupe::Dupe;
+use indexmap::indexmap;
+use indexmap::IndexMap;
 use indexmap::IndexSet;
 use starlark::values::OwnedFrozenValue;
 
@@ -254,6 +257,19 @@ impl Action for DownloadFileAction {
             .next()
             .map(|o| o.get_path().path().as_str())
     }
+
+    fn aquery_attributes(&self, _fs: &ExecutorFs) -> IndexMap<String, String> {
+        let mut attrs = indexmap! {
+            "url".to_owned() => self.inner.url.to_string(),
+        };
+        if let Some(sha1) = self.inner.checksum.sha1() {
+            attrs.insert("sha1".to_owned(), sha1.to_owned());
+        }
+        if let Some(sha256) = self.inner.checksum.sha256() {
+            attrs.insert("sha256".to_owned(), sha256.to_owned());
+        }
+        attrs
+    }
 }
 
 #[async_trait]
@@ -9,6 +9,8 @@
 
 use std::borrow::Cow;
 use std::ops::ControlFlow;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 
 use allocative::Allocative;
 use async_trait::async_trait;
@@ -25,6 +27,7 @@ use buck2_build_api::actions::IncrementalActionExecutable;
 use buck2_build_api::actions::UnregisteredAction;
 use buck2_build_api::artifact_groups::ArtifactGroup;
 use buck2_build_api::artifact_groups::ArtifactGroupValues;
+use buck2_build_api::audit_dep_files::DepFileInputs;
 use buck2_build_api::interpreter::rule_defs::cmd_args::space_separated::SpaceSeparatedCommandLineBuilder;
 use buck2_build_api::interpreter::rule_defs::cmd_args::value_as::ValueAsCommandLineLike;
 use buck2_build_api::interpreter::rule_defs::cmd_args::CommandLineArgLike;
@@ -36,6 +39,7 @@ use buck2_build_api::interpreter::rule_defs::cmd_args::SimpleCommandLineArtifact
 use buck2_build_api::interpreter::rule_defs::cmd_args::StarlarkCmdArgs;
 use buck2_build_api::interpreter::rule_defs::provider::builtin::worker_info::FrozenWorkerInfo;
 use buck2_build_api::interpreter::rule_defs::provider::builtin::worker_info::WorkerInfo;
+use buck2_common::file_ops::TrackedFileDigest;
 use buck2_core::category::CategoryRef;
 use buck2_core::execution_types::executor_config::RemoteExecutorCustomImage;
 use buck2_core::execution_types::executor_config::RemoteExecutorDependency;
@@ -43,14 +47,16 @@ use buck2_core::fs::buck_out_path::BuildArtifactPath;
 use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
 use buck2_error::starlark_error::from_starlark;
 use buck2_error::BuckErrorContext;
-use buck2_events::dispatch::span_async_simple;
+use buck2_events::dispatch::span_async;
 use buck2_execute::artifact::fs::ExecutorFs;
 use buck2_execute::execute::action_digest::ActionDigest;
 use buck2_execute::execute::cache_uploader::force_cache_upload;
 use buck2_execute::execute::environment_inheritance::EnvironmentInheritance;
 use buck2_execute::execute::manager::CommandExecutionManager;
+use buck2_execute::execute::paths_with_digest::PathsWithDigestBlobData;
 use buck2_execute::execute::prepared::PreparedAction;
 use buck2_execute::execute::request::ActionMetadataBlob;
+use buck2_execute::execute::request::ActionMetadataBlobData;
 use buck2_execute::execute::request::CommandExecutionInput;
 use buck2_execute::execute::request::CommandExecutionOutput;
 use buck2_execute::execute::request::CommandExecutionPaths;
@@ -59,6 +65,7 @@ use buck2_execute::execute::request::ExecutorPreference;
 use buck2_execute::execute::request::WorkerId;
 use buck2_execute::execute::request::WorkerSpec;
 use buck2_execute::execute::result::CommandExecutionResult;
+use buck2_execute::execute::target::CommandExecutionTarget;
 use derive_more::Display;
 use dupe::Dupe;
 use gazebo::prelude::*;
@@ -92,6 +99,7 @@ use starlark::values::ValueOfUnchecked;
 use starlark::values::ValueTyped;
 use starlark::values::ValueTypedComplex;
 
+use self::argfile::ArgfileParameter;
 use self::dep_files::DepFileBundle;
 use crate::actions::impls::run::dep_files::make_dep_file_bundle;
 use crate::actions::impls::run::dep_files::populate_dep_files;
@@ -100,6 +108,7 @@ use crate::actions::impls::run::dep_files::RunActionDepFiles;
 use crate::actions::impls::run::metadata::metadata_content;
 use crate::context::run::RunActionError;
 
+pub(crate) mod argfile;
 pub(crate) mod audit_dep_files;
 pub(crate) mod dep_files;
 mod metadata;
@@ -164,11 +173,15 @@ pub(crate) struct UnregisteredRunAction {
     pub(crate) low_pass_filter: bool,
     pub(crate) dep_files: RunActionDepFiles,
     pub(crate) metadata_param: Option<MetadataParameter>,
+    pub(crate) argfile: Option<ArgfileParameter>,
     pub(crate) no_outputs_cleanup: bool,
     pub(crate) allow_cache_upload: bool,
     pub(crate) allow_dep_file_cache_upload: bool,
     pub(crate) force_full_hybrid_if_capable: bool,
     pub(crate) unique_input_inodes: bool,
+    /// `None` if the action did not declare whether it needs network access.
+    pub(crate) allow_network: Option<bool>,
+    pub(crate) stream_output: bool,
     pub(crate) remote_execution_dependencies: Vec<RemoteExecutorDependency>,
     pub(crate) remote_execution_custom_image: Option<RemoteExecutorCustomImage>,
 }
@@ -292,6 +305,14 @@ enum ExecuteResult {
     },
 }
 
+/// Remote dep file cache divergences seen by this daemon, to report one in `sampling` of them.
+static REMOTE_DEP_FILE_DIVERGENCES: AtomicU64 = AtomicU64::new(0);
+
+fn should_sample_divergence(sampling: u32) -> bool {
+    sampling != 0
+        && REMOTE_DEP_FILE_DIVERGENCES.fetch_add(1, Ordering::Relaxed) % (sampling as u64) == 0
+}
+
 impl RunAction {
     fn unpack(
         values: &OwnedFrozenValueTyped<FrozenStarlarkRunActionValues>,
@@ -329,6 +350,23 @@ impl RunAction {
         })
     }
 
+    /// Visit the artifacts of this RunAction without expanding its command line.
+    fn visit_artifacts(
+        &self,
+        artifact_visitor: &mut impl CommandLineArtifactVisitor,
+    ) -> buck2_error::Result<()> {
+        let values = Self::unpack(&self.starlark_values)?;
+        values.args.visit_artifacts(artifact_visitor)?;
+        values.exe.visit_artifacts(artifact_visitor)?;
+        if let Some(worker) = values.worker {
+            worker.exe.visit_artifacts(artifact_visitor)?;
+        }
+        for (_, v) in values.env.iter() {
+            v.visit_artifacts(artifact_visitor)?;
+        }
+        Ok(())
+    }
+
     /// Get the command line expansion for this RunAction.
     fn expand_command_line_and_worker(
         &self,
@@ -461,6 +499,31 @@ impl RunAction {
             extra_env.push((metadata_param.env_var.to_owned(), env));
         }
 
+        // Move the arguments to an argfile if the command line is too long to run. The command
+        // line fingerprint used for dep files is still computed from the arguments themselves.
+        let argfile = match &self.inner.argfile {
+            Some(argfile) if argfile.is_needed(&expanded, executor_fs.path_separator()) => {
+                let path =
+                    BuildArtifactPath::new(ctx.target().owner().dupe(), argfile.path.clone());
+                let arg = format!(
+                    "@{}",
+                    cli_ctx
+                        .resolve_project_path(fs.buck_out_path_resolver().resolve_gen(&path))?
+                        .into_string()
+                );
+                let data = argfile.format.contents(&expanded.args).into_bytes();
+                let digest =
+                    TrackedFileDigest::from_content(&data, ctx.digest_config().cas_digest_config());
+                inputs.push(CommandExecutionInput::ActionMetadata(ActionMetadataBlob {
+                    data: PathsWithDigestBlobData(ActionMetadataBlobData(data)),
+                    digest,
+                    path,
+                }));
+                Some(arg)
+            }
+            _ => None,
+        };
+
         let scratch = ctx.target().scratch_path();
         let scratch_path = fs.buck_out_path_resolver().resolve_scratch(&scratch);
         extra_env.push((
@@ -484,6 +547,7 @@ impl RunAction {
 
         Ok(PreparedRunAction {
             expanded,
+            argfile,
             extra_env,
             paths,
             worker,
@@ -505,22 +569,32 @@ impl RunAction {
 
         if let Some(bundle) = dep_file_bundle {
             if let Some(found_dep_file_entry) = &result.dep_file_metadata {
-                let can_use = span_async_simple(
+                let check = span_async(
                     buck2_data::MatchDepFilesStart {
                         checking_filtered_inputs: true,
                         remote_cache: true,
                     },
-                    bundle.check_remote_dep_file_entry(
-                        ctx.digest_config(),
-                        ctx.fs(),
-                        ctx.materializer(),
-                        found_dep_file_entry,
-                    ),
-                    buck2_data::MatchDepFilesEnd {},
+                    async {
+                        let check = bundle
+                            .check_remote_dep_file_entry(
+                                ctx.digest_config(),
+                                ctx.fs(),
+                                ctx.materializer(),
+                                found_dep_file_entry,
+                            )
+                            .await;
+                        let end = buck2_data::MatchDepFilesEnd {
+                            remote_verification: check
+                                .as_ref()
+                                .ok()
+                                .map(|check| check.verification as i32),
+                        };
+                        (check, end)
+                    },
                 )
                 .await?;
 
-                if can_use {
+                if check.is_verified() {
                     tracing::info!(
                         "Action result is cached via remote dep file cache, skipping execution of :\n```\n$ {}\n```\n for action `{}` with remote dep file key `{}`",
                         request.all_args_str(),
@@ -529,6 +603,19 @@ impl RunAction {
                     );
                     return Ok(ControlFlow::Break(result));
                 }
+
+                let sampling = ctx.run_action_knobs().remote_dep_file_divergence_sampling;
+                if should_sample_divergence(sampling) {
+                    let target = ctx.target();
+                    ctx.events()
+                        .instant_event(buck2_data::RemoteDepFileDivergence {
+                            key: Some(target.as_proto_action_key()),
+                            name: Some(target.as_proto_action_name()),
+                            remote_dep_file_key: bundle.remote_dep_file_action.action.to_string(),
+                            verification: check.verification as i32,
+                            dep_files: check.diverging_dep_files,
+                        });
+                }
             } else {
                 // This should not happen as we check for the metadata on the cache querier side.
                 tracing::debug!(
@@ -561,6 +648,9 @@ impl RunAction {
         let input_files_bytes = prepared_run_action.paths.input_files_bytes();
         // Run actions are assumed to be shared
         let host_sharing_requirements = HostSharingRequirements::Shared(self.inner.weight);
+        // Actions predicted to be on the critical path run first, and race local and remote
+        // execution when possible, since any delay to them delays the whole build.
+        let prioritized = ctx.predicted_on_critical_path();
 
         let req = prepared_run_action
             .into_command_execution_request()
@@ -570,8 +660,13 @@ impl RunAction {
             .with_low_pass_filter(self.inner.low_pass_filter)
             .with_outputs_cleanup(!self.inner.no_outputs_cleanup)
             .with_local_environment_inheritance(EnvironmentInheritance::local_command_exclusions())
-            .with_force_full_hybrid_if_capable(self.inner.force_full_hybrid_if_capable)
+            .with_force_full_hybrid_if_capable(
+                self.inner.force_full_hybrid_if_capable || prioritized,
+            )
+            .with_prioritized(prioritized)
             .with_unique_input_inodes(self.inner.unique_input_inodes)
+            .with_allow_network(knobs.network_policy.resolve(self.inner.allow_network)?)
+            .with_stream_output(self.inner.stream_output)
             .with_remote_execution_dependencies(self.inner.remote_execution_dependencies.clone())
             .with_remote_execution_custom_image(self.inner.remote_execution_custom_image.clone());
 
@@ -661,6 +756,8 @@ impl RunAction {
 
 pub(crate) struct PreparedRunAction {
     expanded: ExpandedCommandLine,
+    /// The argument referencing the argfile, if the arguments were moved to one.
+    argfile: Option<String>,
     extra_env: Vec<(String, String)>,
     paths: CommandExecutionPaths,
     worker: Option<WorkerSpec>,
@@ -670,11 +767,17 @@ impl PreparedRunAction {
     fn into_command_execution_request(self) -> CommandExecutionRequest {
         let Self {
             expanded: ExpandedCommandLine { exe, args, mut env },
+            argfile,
             extra_env,
             paths,
             worker,
         } = self;
 
+        let args = match argfile {
+            Some(argfile) => vec![argfile],
+            None => args,
+        };
+
         for (k, v) in extra_env {
             env.insert(k, v);
         }
@@ -714,16 +817,8 @@ impl Action for RunAction {
     }
 
     fn inputs(&self) -> buck2_error::Result<Cow<'_, [ArtifactGroup]>> {
-        let values = Self::unpack(&self.starlark_values)?;
         let mut artifact_visitor = SimpleCommandLineArtifactVisitor::new();
-        values.args.visit_artifacts(&mut artifact_visitor)?;
-        values.exe.visit_artifacts(&mut artifact_visitor)?;
-        if let Some(worker) = values.worker {
-            worker.exe.visit_artifacts(&mut artifact_visitor)?;
-        }
-        for (_, v) in values.env.iter() {
-            v.visit_artifacts(&mut artifact_visitor)?;
-        }
+        self.visit_artifacts(&mut artifact_visitor)?;
         Ok(Cow::Owned(artifact_visitor.inputs.into_iter().collect()))
     }
 
@@ -778,7 +873,24 @@ impl Action for RunAction {
             "no_outputs_cleanup".to_owned() => self.inner.no_outputs_cleanup.to_string(),
             "allow_cache_upload".to_owned() => self.inner.allow_cache_upload.to_string(),
             "allow_dep_file_cache_upload".to_owned() => self.inner.allow_dep_file_cache_upload.to_string(),
+            "allow_network".to_owned() => match self.inner.allow_network {
+                None => "None".to_owned(),
+                Some(x) => x.to_string(),
+            },
+            "stream_output".to_owned() => self.inner.stream_output.to_string(),
+        }
+    }
+
+    fn dep_file_inputs(&self) -> buck2_error::Result<Option<DepFileInputs>> {
+        if self.inner.dep_files.labels.is_empty() {
+            return Ok(None);
         }
+        let mut visitor = DepFilesCommandLineVisitor::new(&self.inner.dep_files);
+        self.visit_artifacts(&mut visitor)?;
+        Ok(Some(DepFileInputs {
+            untagged: visitor.inputs.untagged,
+            tagged: visitor.inputs.tagged.into_iter().collect(),
+        }))
     }
 
     fn error_handler(&self) -> Option<OwnedFrozenValue> {
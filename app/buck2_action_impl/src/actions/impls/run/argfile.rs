@@ -0,0 +1,211 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Argfiles (aka response files) that `run` actions with an `argfile_format` move their
+//! arguments to when the command line is too long for the execution platform.
+
+use std::borrow::Cow;
+
+use allocative::Allocative;
+use buck2_build_api::actions::impls::expanded_command_line::ExpandedCommandLine;
+use buck2_core::execution_types::executor_config::PathSeparatorKind;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
+use dupe::Dupe;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum ArgfileError {
+    #[error("Unknown `argfile_format` `{0}`, expected `gnu` or `msvc`")]
+    UnknownFormat(String),
+}
+
+/// Windows limits command lines to 32767 characters. Leave room for quoting.
+const WINDOWS_COMMAND_LINE_LIMIT: usize = 30_000;
+
+/// Linux limits arguments and environment to a quarter of the stack size, 2 MiB by default, and
+/// macOS to 1 MiB. Leave room for the environment.
+const UNIX_COMMAND_LINE_LIMIT: usize = 512 * 1024;
+
+/// How the tool run by the action parses its argfiles.
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq, Allocative)]
+pub(crate) enum ArgfileFormat {
+    /// GCC, Clang and most other Unix tools: whitespace separates arguments, which may be
+    /// double-quoted, and backslash escapes the next character.
+    Gnu,
+    /// MSVC tools such as `cl.exe` and `link.exe`: arguments are quoted like on the Windows
+    /// command line.
+    Msvc,
+}
+
+impl ArgfileFormat {
+    pub(crate) fn parse(format: &str) -> buck2_error::Result<Self> {
+        match format {
+            "gnu" => Ok(Self::Gnu),
+            "msvc" => Ok(Self::Msvc),
+            _ => Err(ArgfileError::UnknownFormat(format.to_owned()).into()),
+        }
+    }
+
+    fn quote(self, arg: &str) -> Cow<str> {
+        match self {
+            Self::Gnu => quote_gnu(arg),
+            Self::Msvc => quote_msvc(arg),
+        }
+    }
+
+    /// Contents of an argfile with `args`, one per line.
+    pub(crate) fn contents(self, args: &[String]) -> String {
+        let mut contents = String::new();
+        for arg in args {
+            contents.push_str(&self.quote(arg));
+            contents.push('\n');
+        }
+        contents
+    }
+}
+
+fn quote_gnu(arg: &str) -> Cow<str> {
+    if !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|c| c.is_ascii_whitespace() || matches!(c, '"' | '\'' | '\\'))
+    {
+        return arg.into();
+    }
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    for c in arg.chars() {
+        if matches!(c, '"' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted.into()
+}
+
+/// Quoting understood by `CommandLineToArgvW`: backslashes are only special before a double
+/// quote, where they have to be doubled.
+fn quote_msvc(arg: &str) -> Cow<str> {
+    if !arg.is_empty() && !arg.chars().any(|c| matches!(c, ' ' | '\t' | '\n' | '"')) {
+        return arg.into();
+    }
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.extend(std::iter::repeat('\\').take(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                quoted.extend(std::iter::repeat('\\').take(backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.extend(std::iter::repeat('\\').take(backslashes * 2));
+    quoted.push('"');
+    quoted.into()
+}
+
+#[derive(Debug, Allocative)]
+pub(crate) struct ArgfileParameter {
+    pub(crate) format: ArgfileFormat,
+    /// Path of the argfile in the output directory of the target.
+    pub(crate) path: ForwardRelativePathBuf,
+}
+
+impl ArgfileParameter {
+    /// Argfiles live in `__argfiles__/<category>/<identifier>`, which is unique per action.
+    pub(crate) fn path(
+        category: &str,
+        identifier: Option<&str>,
+    ) -> buck2_error::Result<ForwardRelativePathBuf> {
+        let path =
+            ForwardRelativePath::new("__argfiles__")?.join(ForwardRelativePath::new(category)?);
+        match identifier {
+            Some(identifier) => path.join_normalized(identifier),
+            None => Ok(path),
+        }
+    }
+
+    /// Whether the arguments have to move to the argfile for the command to run on a platform
+    /// with the given path separator.
+    pub(crate) fn is_needed(
+        &self,
+        expanded: &ExpandedCommandLine,
+        path_separator: PathSeparatorKind,
+    ) -> bool {
+        let limit = match path_separator {
+            PathSeparatorKind::Windows => WINDOWS_COMMAND_LINE_LIMIT,
+            PathSeparatorKind::Unix => UNIX_COMMAND_LINE_LIMIT,
+        };
+        let length: usize = expanded
+            .exe
+            .iter()
+            .chain(&expanded.args)
+            .map(|arg| arg.len() + 1)
+            .sum();
+        length > limit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_gnu() {
+        assert_eq!(quote_gnu("-DFOO=1"), "-DFOO=1");
+        assert_eq!(quote_gnu(""), "\"\"");
+        assert_eq!(quote_gnu("a b"), "\"a b\"");
+        assert_eq!(quote_gnu("it's"), "\"it's\"");
+        assert_eq!(quote_gnu(r#"-DX="y""#), r#""-DX=\"y\"""#);
+        assert_eq!(quote_gnu(r"C:\dir"), r#""C:\\dir""#);
+    }
+
+    #[test]
+    fn test_quote_msvc() {
+        assert_eq!(quote_msvc("/Fo:out.obj"), "/Fo:out.obj");
+        assert_eq!(quote_msvc(r"C:\dir\file.c"), r"C:\dir\file.c");
+        assert_eq!(quote_msvc(""), "\"\"");
+        assert_eq!(quote_msvc(r"C:\Program Files\"), r#""C:\Program Files\\""#);
+        assert_eq!(quote_msvc(r#"/DX="y""#), r#""/DX=\"y\"""#);
+        assert_eq!(quote_msvc(r#"a\"b"#), r#""a\\\"b""#);
+    }
+
+    #[test]
+    fn test_contents() {
+        let args = vec!["-c".to_owned(), "a b.c".to_owned()];
+        assert_eq!(ArgfileFormat::Gnu.contents(&args), "-c\n\"a b.c\"\n");
+        assert_eq!(ArgfileFormat::Msvc.contents(&args), "-c\n\"a b.c\"\n");
+    }
+
+    #[test]
+    fn test_is_needed() {
+        let argfile = ArgfileParameter {
+            format: ArgfileFormat::Msvc,
+            path: ArgfileParameter::path("cxx_compile", Some("foo.cpp")).unwrap(),
+        };
+        assert_eq!(argfile.path.as_str(), "__argfiles__/cxx_compile/foo.cpp");
+        let expanded = ExpandedCommandLine {
+            exe: vec!["cl.exe".to_owned()],
+            args: vec!["x".repeat(40_000)],
+            env: Default::default(),
+        };
+        assert!(argfile.is_needed(&expanded, PathSeparatorKind::Windows));
+        assert!(!argfile.is_needed(&expanded, PathSeparatorKind::Unix));
+    }
+}
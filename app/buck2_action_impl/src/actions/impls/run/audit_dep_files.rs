@@ -8,20 +8,26 @@
  */
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::io::Write;
+use std::sync::Arc;
 
 use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
+use buck2_build_api::actions::RegisteredAction;
 use buck2_build_api::audit_dep_files::AUDIT_DEP_FILES;
+use buck2_build_api::audit_dep_files::READ_ACTION_DEP_FILES;
 use buck2_core::category::Category;
 use buck2_core::deferred::base_deferred_key::BaseDeferredKey;
 use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
 use buck2_directory::directory::directory::Directory;
 use buck2_directory::directory::directory_iterator::DirectoryIterator;
+use buck2_directory::directory::directory_selector::DirectorySelector;
 use buck2_error::buck2_error;
 use buck2_error::BuckErrorContext;
 use buck2_execute::digest_config::HasDigestConfig;
 use buck2_execute::materialize::materializer::HasMaterializer;
 use dice::DiceTransaction;
+use dupe::Dupe;
 
 use crate::actions::impls::run::dep_files::get_dep_files;
 use crate::actions::impls::run::dep_files::read_dep_files;
@@ -32,6 +38,33 @@ pub(crate) fn init_audit_dep_files() {
     AUDIT_DEP_FILES.init(|ctx, label, category, identifier, stdout| {
         Box::pin(audit_dep_files(ctx, label, category, identifier, stdout))
     });
+    READ_ACTION_DEP_FILES.init(|ctx, action| Box::pin(read_action_dep_files(ctx, action)));
+}
+
+async fn read_action_dep_files(
+    ctx: &DiceTransaction,
+    action: &RegisteredAction,
+) -> buck2_error::Result<Option<HashMap<Arc<str>, DirectorySelector>>> {
+    let key = DepFilesKey::new(
+        action.owner().dupe(),
+        action.category().to_owned(),
+        action.identifier().map(|i| i.to_owned()),
+    );
+
+    let Some(state) = get_dep_files(&key) else {
+        return Ok(None);
+    };
+
+    let dep_files = read_dep_files(
+        state.has_signatures(),
+        state.declared_dep_files(),
+        &ctx.clone().get_artifact_fs().await?,
+        ctx.per_transaction_data().get_materializer().as_ref(),
+    )
+    .await
+    .with_buck_error_context(|| format!("Failed to read dep files for key `{}`", key))?;
+
+    Ok(dep_files.map(|d| d.into_contents()))
 }
 
 async fn audit_dep_files(
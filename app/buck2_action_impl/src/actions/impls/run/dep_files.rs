@@ -466,7 +466,9 @@ impl DepFileBundle {
                 declared_outputs,
                 &self.declared_dep_files,
             ),
-            buck2_data::MatchDepFilesEnd {},
+            buck2_data::MatchDepFilesEnd {
+                remote_verification: None,
+            },
         )
         .await?;
         let outputs = outputs.map(|o| {
@@ -501,7 +503,9 @@ impl DepFileBundle {
                 declared_outputs,
                 &self.declared_dep_files,
             ),
-            buck2_data::MatchDepFilesEnd {},
+            buck2_data::MatchDepFilesEnd {
+                remote_verification: None,
+            },
         )
         .await?;
 
@@ -524,18 +528,22 @@ impl DepFileBundle {
         fs: &ArtifactFs,
         materializer: &dyn Materializer,
         found: &RemoteDepFile,
-    ) -> buck2_error::Result<bool> {
+    ) -> buck2_error::Result<RemoteDepFileCheck> {
         // Everything in the common digest structure is included in the remote dep file key,
         // so they should be the same but it's good to double check.
         let common = &self.common_digests;
         if common.commandline_cli_digest.as_bytes().to_vec() != found.commandline_cli_digest {
             tracing::debug!("Remote dep files miss: command cli digests are different");
-            return Ok(false);
+            return Ok(RemoteDepFileCheck::mismatch(
+                buck2_data::RemoteDepFileVerification::RemoteDepFileMismatchCommandLine,
+            ));
         }
         if common.output_paths_digest.raw_digest().as_bytes().to_vec() != found.output_paths_digest
         {
             tracing::debug!("Remote dep files miss: output paths digest are different");
-            return Ok(false);
+            return Ok(RemoteDepFileCheck::mismatch(
+                buck2_data::RemoteDepFileVerification::RemoteDepFileMismatchOutputPaths,
+            ));
         }
         if common
             .untagged_inputs_digest
@@ -545,13 +553,17 @@ impl DepFileBundle {
             != found.untagged_inputs_digest
         {
             tracing::debug!("Remote dep files miss: untagged inputs digest are different");
-            return Ok(false);
+            return Ok(RemoteDepFileCheck::mismatch(
+                buck2_data::RemoteDepFileVerification::RemoteDepFileMismatchUntaggedInputs,
+            ));
         }
 
         // Ensure the declared dep files are the same
         if self.declared_dep_files.tagged.len() != found.dep_file_inputs.len() {
             tracing::debug!("Remote dep files miss: declared dep file counts are different");
-            return Ok(false);
+            return Ok(RemoteDepFileCheck::mismatch(
+                buck2_data::RemoteDepFileVerification::RemoteDepFileMismatchDeclaredDepFiles,
+            ));
         }
         let different_dep_files_count = self
             .declared_dep_files
@@ -562,7 +574,9 @@ impl DepFileBundle {
             .count();
         if different_dep_files_count != 0 {
             tracing::debug!("Remote dep files miss: declared dep files are different");
-            return Ok(false);
+            return Ok(RemoteDepFileCheck::mismatch(
+                buck2_data::RemoteDepFileVerification::RemoteDepFileMismatchDeclaredDepFiles,
+            ));
         }
 
         // Now we compare the filtered input digests.
@@ -584,7 +598,9 @@ impl DepFileBundle {
             Some(dep_files) => dep_files,
             None => {
                 tracing::debug!("Remote dep files miss: Dep files cannot be materialized");
-                return Ok(false);
+                return Ok(RemoteDepFileCheck::mismatch(
+                    buck2_data::RemoteDepFileVerification::RemoteDepFileMismatchDepFilesNotMaterialized,
+                ));
             }
         };
 
@@ -595,22 +611,50 @@ impl DepFileBundle {
             .filter(dep_files)
             .fingerprint(digest_config);
 
-        let different_digest_count = computed_filtered_fingerprints
+        let diverging_dep_files: Vec<String> = computed_filtered_fingerprints
             .tagged
             .iter()
             .zip(found.dep_file_inputs.iter())
             .filter(|((_, f1), found)| {
                 f1.fingerprint().raw_digest().as_bytes().to_vec() != found.filtered_fingerprint
             })
-            .count();
+            .map(|(_, found)| found.dep_file_path.clone())
+            .collect();
 
-        if different_digest_count != 0 {
+        if !diverging_dep_files.is_empty() {
             tracing::debug!("Remote dep files miss: Filtered input digests are different");
-            return Ok(false);
+            return Ok(RemoteDepFileCheck {
+                verification:
+                    buck2_data::RemoteDepFileVerification::RemoteDepFileMismatchFilteredInputs,
+                diverging_dep_files,
+            });
         }
 
         // Everything matches! The action from cache can be used.
-        Ok(true)
+        Ok(RemoteDepFileCheck {
+            verification: buck2_data::RemoteDepFileVerification::RemoteDepFileVerified,
+            diverging_dep_files: Vec::new(),
+        })
+    }
+}
+
+/// Outcome of checking an entry of the remote dep file cache against the inputs of the action.
+pub(crate) struct RemoteDepFileCheck {
+    pub(crate) verification: buck2_data::RemoteDepFileVerification,
+    /// Dep files listing inputs whose digests differ from the cache entry.
+    pub(crate) diverging_dep_files: Vec<String>,
+}
+
+impl RemoteDepFileCheck {
+    fn mismatch(verification: buck2_data::RemoteDepFileVerification) -> Self {
+        RemoteDepFileCheck {
+            verification,
+            diverging_dep_files: Vec::new(),
+        }
+    }
+
+    pub(crate) fn is_verified(&self) -> bool {
+        self.verification == buck2_data::RemoteDepFileVerification::RemoteDepFileVerified
     }
 }
 
@@ -1352,6 +1396,12 @@ pub(crate) struct ConcreteDepFiles {
     contents: HashMap<Arc<str>, DirectorySelector>,
 }
 
+impl ConcreteDepFiles {
+    pub(crate) fn into_contents(self) -> HashMap<Arc<str>, DirectorySelector> {
+        self.contents
+    }
+}
+
 /// A command line visitor to collect inputs and outputs in a form relevant for dep files
 /// computations.
 pub(crate) struct DepFilesCommandLineVisitor<'a> {
@@ -8,6 +8,7 @@
  */
 
 use std::borrow::Cow;
+use std::collections::HashMap;
 
 use allocative::Allocative;
 use async_trait::async_trait;
@@ -28,15 +29,20 @@ use buck2_build_api::interpreter::rule_defs::artifact::starlark_artifact_like::V
 use buck2_core::category::CategoryRef;
 use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
 use buck2_core::fs::paths::forward_rel_path::ForwardRelativePathBuf;
+use buck2_directory::directory::entry::DirectoryEntry;
 use buck2_error::BuckErrorContext;
 use buck2_execute::artifact::artifact_dyn::ArtifactDyn;
 use buck2_execute::artifact_utils::ArtifactValueBuilder;
+use buck2_execute::directory::new_symlink;
 use buck2_execute::execute::command_executor::ActionExecutionTimingData;
 use buck2_execute::materialize::materializer::CopiedArtifact;
 use dupe::Dupe;
 use gazebo::prelude::*;
 use indexmap::IndexSet;
 use itertools::Itertools;
+use relative_path::Component;
+use relative_path::RelativePath;
+use relative_path::RelativePathBuf;
 use starlark::values::dict::UnpackDictEntries;
 use starlark::values::OwnedFrozenValue;
 use starlark::values::ValueError;
@@ -50,12 +56,26 @@ enum SymlinkedDirError {
     EmptyPath,
     #[error("Only artifact inputs are supported in symlink_dir actions, got {0}")]
     UnsupportedInput(ArtifactGroup),
+    #[error("Symlink targets must be relative paths, but `{0}` points to `{1}`")]
+    AbsoluteSymlinkTarget(Box<ForwardRelativePath>, String),
+}
+
+/// A symlink declared in a runtime tree, which is not backed by an input artifact.
+#[derive(Debug, Allocative)]
+struct DeclaredSymlink {
+    path: Box<ForwardRelativePath>,
+    #[allocative(skip)]
+    target: RelativePathBuf,
+    /// The entry of the tree the symlink points to, if it points inside the tree. Used to copy
+    /// that entry instead when the tree is copied (i.e. on Windows).
+    resolved: Option<Box<ForwardRelativePath>>,
 }
 
 #[derive(Allocative)]
 pub(crate) struct UnregisteredSymlinkedDirAction {
     copy: bool,
     args: Vec<(ArtifactGroup, Box<ForwardRelativePath>)>,
+    symlinks: Vec<DeclaredSymlink>,
     // All associated artifacts of inputs unioned together
     unioned_associated_artifacts: AssociatedArtifacts,
 }
@@ -147,10 +167,58 @@ impl UnregisteredSymlinkedDirAction {
         Ok(Self {
             copy,
             args,
+            symlinks: Vec::new(),
             unioned_associated_artifacts: AssociatedArtifacts::from(unioned_associated_artifacts),
         })
     }
 
+    /// A tree laid out for use at runtime, containing `srcs` as well as the `symlinks`, which
+    /// map paths in the tree to (relative) symlink targets.
+    pub(crate) fn new_runtime_tree<'v>(
+        copy: bool,
+        srcs: UnpackDictEntries<&'v str, ValueAsArtifactLike<'v>>,
+        symlinks: UnpackDictEntries<&'v str, &'v str>,
+    ) -> buck2_error::Result<Self> {
+        let mut action = Self::new(false, srcs)?;
+        action.copy = copy;
+
+        for (path, target) in symlinks.entries {
+            let path = ForwardRelativePathBuf::try_from(path.to_owned())
+                .buck_error_context("symlinks key must be a forward relative path")?
+                .into_box();
+            let target = RelativePathBuf::from_path(target).map_err(|_| {
+                SymlinkedDirError::AbsoluteSymlinkTarget(path.clone(), target.to_owned())
+            })?;
+            let resolved = resolve_symlink_target(&path, &target)
+                .filter(|resolved| action.args.iter().any(|(_, p)| p == resolved));
+            action.symlinks.push(DeclaredSymlink {
+                path,
+                target,
+                resolved,
+            });
+        }
+
+        // Declared symlinks must not overlap with each other or with the artifacts either.
+        let mut paths = action
+            .args
+            .iter()
+            .map(|(_, p)| &**p)
+            .chain(action.symlinks.iter().map(|s| &*s.path))
+            .collect::<Vec<_>>();
+        paths.sort();
+        for (x, y) in paths.iter().zip(paths.iter().skip(1)) {
+            if y.starts_with(x) {
+                return Err(SymlinkedDirError::OverlappingPaths(
+                    (*x).to_buf().into_box(),
+                    (*y).to_buf().into_box(),
+                )
+                .into());
+            }
+        }
+
+        Ok(action)
+    }
+
     pub(crate) fn inputs(&self) -> IndexSet<ArtifactGroup> {
         self.args.iter().map(|x| x.0.dupe()).collect()
     }
@@ -171,6 +239,7 @@ impl UnregisteredAction for UnregisteredSymlinkedDirAction {
         Ok(Box::new(SymlinkedDirAction {
             copy: self.copy,
             args: self.args,
+            symlinks: self.symlinks,
             inputs: BoxSliceSet::from(inputs),
             outputs: BoxSliceSet::from(outputs),
         }))
@@ -181,6 +250,7 @@ impl UnregisteredAction for UnregisteredSymlinkedDirAction {
 struct SymlinkedDirAction {
     copy: bool,
     args: Vec<(ArtifactGroup, Box<ForwardRelativePath>)>,
+    symlinks: Vec<DeclaredSymlink>,
     inputs: BoxSliceSet<ArtifactGroup>,
     outputs: BoxSliceSet<BuildArtifact>,
 }
@@ -235,6 +305,7 @@ impl IncrementalActionExecutable for SymlinkedDirAction {
         let output = ctx.fs().resolve_build(self.output().get_path());
         let mut builder = ArtifactValueBuilder::new(fs, ctx.digest_config());
         let mut srcs = Vec::new();
+        let mut by_path = HashMap::new();
 
         for (group, dest) in &self.args {
             let (src_artifact, value) = ctx
@@ -244,6 +315,9 @@ impl IncrementalActionExecutable for SymlinkedDirAction {
                 .buck_error_context("Input did not dereference to exactly one artifact")?;
 
             let src = src_artifact.resolve_path(ctx.fs())?;
+            if !self.symlinks.is_empty() {
+                by_path.insert(&**dest, (src.clone(), value.dupe()));
+            }
             let dest = output.join(dest);
 
             if self.copy {
@@ -258,6 +332,31 @@ impl IncrementalActionExecutable for SymlinkedDirAction {
             }
         }
 
+        for symlink in &self.symlinks {
+            let dest = output.join(&symlink.path);
+            match symlink
+                .resolved
+                .as_ref()
+                .filter(|_| self.copy)
+                .and_then(|resolved| by_path.get(&**resolved))
+            {
+                Some((src, value)) => {
+                    let dest_entry = builder.add_copied(value, src.as_ref(), dest.as_ref())?;
+                    srcs.push(CopiedArtifact::new(
+                        src.clone(),
+                        dest,
+                        dest_entry.map_dir(|d| d.as_immutable()),
+                    ));
+                }
+                None => {
+                    builder.add_entry(
+                        dest.as_ref(),
+                        DirectoryEntry::Leaf(new_symlink(symlink.target.as_str())?),
+                    )?;
+                }
+            }
+        }
+
         let value = builder.build(output.as_ref())?;
         ctx.materializer()
             .declare_copy(output, value.dupe(), srcs, ctx.cancellation_context())
@@ -273,6 +372,29 @@ impl IncrementalActionExecutable for SymlinkedDirAction {
     }
 }
 
+/// Resolves `target`, relative to the directory containing `link`, to a path in the tree, if it
+/// does not escape it.
+fn resolve_symlink_target(
+    link: &ForwardRelativePath,
+    target: &RelativePath,
+) -> Option<Box<ForwardRelativePath>> {
+    let mut parts: Vec<&str> = link.as_str().split('/').collect();
+    parts.pop();
+    for component in target.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                parts.pop()?;
+            }
+            Component::Normal(name) => parts.push(name),
+        }
+    }
+    ForwardRelativePathBuf::new(parts.join("/"))
+        .ok()
+        .filter(|p| !p.is_empty())
+        .map(|p| p.into_box())
+}
+
 #[cfg(test)]
 mod tests {
     use buck2_artifact::artifact::artifact_type::Artifact;
@@ -310,4 +432,27 @@ mod tests {
         assert!(validate(&["test", "other", "test"]).is_err());
         assert!(validate(&["test", "test/child"]).is_err());
     }
+
+    #[test]
+    fn test_resolve_symlink_target() {
+        fn resolve(link: &str, target: &str) -> Option<String> {
+            resolve_symlink_target(
+                ForwardRelativePath::new(link).unwrap(),
+                RelativePath::new(target),
+            )
+            .map(|p| p.as_str().to_owned())
+        }
+
+        assert_eq!(
+            resolve("lib/libfoo.so", "libfoo.so.1"),
+            Some("lib/libfoo.so.1".to_owned())
+        );
+        assert_eq!(
+            resolve("bin/tool", "../lib/tool"),
+            Some("lib/tool".to_owned())
+        );
+        assert_eq!(resolve("data", "./other/./x"), Some("other/x".to_owned()));
+        assert_eq!(resolve("bin/tool", "../../outside"), None);
+        assert_eq!(resolve("bin/tool", ".."), None);
+    }
 }
@@ -45,6 +45,26 @@ fn create_dir_tree<'v>(
     Ok(declaration.into_declared_artifact(unioned_associated_artifacts))
 }
 
+fn create_runtime_tree<'v>(
+    eval: &mut Evaluator<'v, '_, '_>,
+    this: &AnalysisActions<'v>,
+    output: OutputArtifactArg<'v>,
+    srcs: UnpackDictEntries<&'v str, ValueAsArtifactLike<'v>>,
+    symlinks: UnpackDictEntries<&'v str, &'v str>,
+    copy: bool,
+) -> buck2_error::Result<ValueTyped<'v, StarlarkDeclaredArtifact>> {
+    let action = UnregisteredSymlinkedDirAction::new_runtime_tree(copy, srcs, symlinks)?;
+    let inputs = action.inputs();
+    let unioned_associated_artifacts = action.unioned_associated_artifacts();
+
+    let mut this = this.state()?;
+    let (declaration, output_artifact) =
+        this.get_or_declare_output(eval, output, OutputType::Directory)?;
+    this.register_action(inputs, indexset![output_artifact], action, None, None)?;
+
+    Ok(declaration.into_declared_artifact(unioned_associated_artifacts))
+}
+
 fn copy_file_impl<'v>(
     eval: &mut Evaluator<'v, '_, '_>,
     this: &AnalysisActions<'v>,
@@ -157,4 +177,29 @@ pub(crate) fn analysis_actions_methods_copy(methods: &mut MethodsBuilder) {
     ) -> starlark::Result<ValueTyped<'v, StarlarkDeclaredArtifact>> {
         Ok(create_dir_tree(eval, this, output, srcs, true)?)
     }
+
+    /// Returns an `artifact` which is a directory laid out for use at runtime, e.g. as the
+    /// runfiles of a test. It is constructed by the materializer, so no command runs to create it.
+    ///
+    /// * `srcs` is a dictionary of path (relative to the result directory) to the bound `artifact`
+    ///   to place there.
+    /// * `symlinks` is a dictionary of path (relative to the result directory) to the relative
+    ///   target of a symlink to create there, e.g. `{"lib/libfoo.so": "libfoo.so.1"}`.
+    /// * `copy` controls whether `srcs` are copied rather than symlinked. It defaults to copying
+    ///   on Windows, where symlinks are not reliably available. When copying, `symlinks` that point
+    ///   to one of the `srcs` are replaced by a copy of it as well.
+    fn runtime_tree<'v>(
+        this: &AnalysisActions<'v>,
+        #[starlark(require = pos)] output: OutputArtifactArg<'v>,
+        #[starlark(require = pos)] srcs: UnpackDictEntries<&'v str, ValueAsArtifactLike<'v>>,
+        #[starlark(require = named, default = UnpackDictEntries::default())]
+        symlinks: UnpackDictEntries<&'v str, &'v str>,
+        #[starlark(require = named)] copy: Option<bool>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<ValueTyped<'v, StarlarkDeclaredArtifact>> {
+        let copy = copy.unwrap_or(cfg!(windows));
+        Ok(create_runtime_tree(
+            eval, this, output, srcs, symlinks, copy,
+        )?)
+    }
 }
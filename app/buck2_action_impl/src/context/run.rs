@@ -46,6 +46,8 @@ use starlark::values::ValueOf;
 use starlark_map::small_map;
 use starlark_map::small_map::SmallMap;
 
+use crate::actions::impls::run::argfile::ArgfileFormat;
+use crate::actions::impls::run::argfile::ArgfileParameter;
 use crate::actions::impls::run::dep_files::RunActionDepFiles;
 use crate::actions::impls::run::new_executor_preference;
 use crate::actions::impls::run::MetadataParameter;
@@ -104,6 +106,12 @@ pub(crate) fn analysis_actions_methods_run(methods: &mut MethodsBuilder) {
     ///     * Both `metadata_env_var` and `metadata_path` are useful when making actions behave in
     ///       an incremental manner (for details, see [Incremental
     ///       Actions](https://buck2.build/docs/rule_authors/incremental_actions/))
+    /// * `argfile_format`: if the command line is too long for the platform the action executes
+    ///   on, write the `arguments` to an argfile and pass `@<path>` to the executable instead.
+    ///   This is the syntax the tool uses to parse argfiles: `"gnu"` (GCC, Clang and most Unix
+    ///   tools) or `"msvc"` (`cl.exe`, `link.exe` and other MSVC tools). The argfile is written
+    ///   to `__argfiles__/<category>/<identifier>` in the output directory of the target. Without
+    ///   this, arguments are always passed on the command line.
     /// * The `prefer_local`, `prefer_remote` and `local_only` options allow selecting where the
     /// action should run if the executor selected for this target is a hybrid executor.
     ///     * All those options disable concurrent execution: the action will run on the preferred
@@ -117,6 +125,17 @@ pub(crate) fn analysis_actions_methods_run(methods: &mut MethodsBuilder) {
     ///     and `--local-only` CLI flags. The CLI flags take precedence.
     ///     * The `force_full_hybrid_if_capable` option overrides the `use_limited_hybrid` hybrid.
     ///     The options listed above take precedence if set.
+    /// * `allow_network`: whether the action needs network access. `False` runs the action
+    ///   without network access: locally in a network namespace (see
+    ///   `buck2.network_isolation_command`), and on RE with the `dockerNetwork=off` platform
+    ///   property. When unset, `buck2.network_policy` decides: `allow` (the default) gives network
+    ///   access, `deny` and `forbid` don't. Under `forbid`, `allow_network = True` is an error.
+    /// * `stream_output`: when the action runs locally, print its stdout and stderr lines to the
+    ///   console while it runs, prefixed by the action, instead of only after it finished. This is
+    ///   meant for long running actions like tests or code generation. Lines are printed in
+    ///   batches every `buck2.stream_output_flush_interval_ms` (200 by default), and at most
+    ///   `buck2.stream_output_max_lines_per_second` (100 by default) lines per second are
+    ///   printed: the others are only counted.
     /// * `remote_execution_dependencies`: list of dependencies which is passed to Remote Execution.
     ///   Each dependency is dictionary with the following keys:
     ///     * `smc_tier`: name of the SMC tier to call by RE Scheduler.
@@ -177,6 +196,7 @@ pub(crate) fn analysis_actions_methods_run(methods: &mut MethodsBuilder) {
         #[starlark(require = named)] dep_files: Option<SmallMap<&'v str, &'v ArtifactTag>>,
         #[starlark(require = named)] metadata_env_var: Option<String>,
         #[starlark(require = named)] metadata_path: Option<String>,
+        #[starlark(require = named)] argfile_format: Option<&str>,
         // TODO(scottcao): Refactor `no_outputs_cleanup` to `outputs_cleanup`
         #[starlark(require = named, default = false)] no_outputs_cleanup: bool,
         #[starlark(require = named, default = false)] allow_cache_upload: bool,
@@ -186,6 +206,8 @@ pub(crate) fn analysis_actions_methods_run(methods: &mut MethodsBuilder) {
             Either<ValueOf<'v, &'v WorkerRunInfo<'v>>, ValueOf<'v, &'v RunInfo<'v>>>,
         >,
         #[starlark(require = named, default = false)] unique_input_inodes: bool,
+        #[starlark(require = named)] allow_network: Option<bool>,
+        #[starlark(require = named, default = false)] stream_output: bool,
         #[starlark(require = named)] error_handler: Option<StarlarkCallable<'v>>,
         eval: &mut Evaluator<'v, '_, '_>,
         #[starlark(require = named, default=UnpackList::default())]
@@ -346,6 +368,20 @@ pub(crate) fn analysis_actions_methods_run(methods: &mut MethodsBuilder) {
             (None, None) => Ok(None),
         }?;
 
+        let argfile = match argfile_format {
+            Some(format) => {
+                let format = ArgfileFormat::parse(format)?;
+                let identifier = match &identifier {
+                    NoneOr::Other(identifier) => Some(identifier.as_str()),
+                    NoneOr::None => None,
+                };
+                let path = ArgfileParameter::path(category.as_str(), identifier)?;
+                this.state()?.claim_output_path(eval, &path)?;
+                Some(ArgfileParameter { format, path })
+            }
+            None => None,
+        };
+
         if artifacts.outputs.is_empty() {
             return Err(buck2_error::Error::from(RunActionError::NoOutputsSpecified).into());
         }
@@ -381,11 +417,14 @@ pub(crate) fn analysis_actions_methods_run(methods: &mut MethodsBuilder) {
             low_pass_filter,
             dep_files: dep_files_configuration,
             metadata_param,
+            argfile,
             no_outputs_cleanup,
             allow_cache_upload,
             allow_dep_file_cache_upload,
             force_full_hybrid_if_capable,
             unique_input_inodes,
+            allow_network,
+            stream_output,
             remote_execution_dependencies: re_dependencies,
             remote_execution_custom_image: re_custom_image,
         };
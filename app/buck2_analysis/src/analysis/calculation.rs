@@ -14,6 +14,7 @@ use std::time::Instant;
 
 use allocative::Allocative;
 use async_trait::async_trait;
+use buck2_build_api::analysis::calculation::DepAnalysisFailed;
 use buck2_build_api::analysis::calculation::RuleAnalsysisCalculationImpl;
 use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
 use buck2_build_api::analysis::calculation::EVAL_ANALYSIS_QUERY;
@@ -32,6 +33,7 @@ use buck2_events::dispatch::record_root_spans;
 use buck2_events::dispatch::span_async;
 use buck2_events::dispatch::span_async_simple;
 use buck2_events::span::SpanId;
+use buck2_futures::cpu_attribution::CpuSubsystem;
 use buck2_interpreter::file_loader::LoadedModule;
 use buck2_interpreter::load_module::InterpreterCalculation;
 use buck2_interpreter::paths::module::StarlarkModulePath;
@@ -200,7 +202,12 @@ pub async fn get_dep_analysis<'v>(
             let res = ctx
                 .get_analysis_result(dep.label())
                 .await
-                .and_then(|v| v.require_compatible());
+                .and_then(|v| v.require_compatible())
+                .map_err(|e| {
+                    e.context(DepAnalysisFailed {
+                        dep: dep.label().dupe(),
+                    })
+                });
             res.map(|x| (dep.label(), x))
         }
         .boxed()
@@ -236,7 +243,8 @@ async fn get_analysis_result(
     ctx: &mut DiceComputations<'_>,
     target: &ConfiguredTargetLabel,
 ) -> buck2_error::Result<MaybeCompatible<AnalysisResult>> {
-    get_analysis_result_inner(ctx, target)
+    CpuSubsystem::Analysis
+        .attribute(get_analysis_result_inner(ctx, target))
         .await
         .tag(ErrorTag::Analysis)
 }
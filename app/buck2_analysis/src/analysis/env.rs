@@ -333,6 +333,7 @@ async fn run_analysis_with_env_underlying(
 
     // Pull the ctx object back out, and steal ctx.action's state back
     let analysis_registry = ctx.take_state();
+    let annotations = ctx.take_annotations();
 
     // TODO: Convert the ValueError from `try_from_value` better than just printing its Debug
     let res_typed = ProviderCollection::try_from_value(list_res)?;
@@ -368,7 +369,8 @@ async fn run_analysis_with_env_underlying(
         declared_actions,
         declared_artifacts,
         validations,
-    ))
+    )
+    .with_annotations(annotations))
 }
 
 pub fn transitive_validations(
@@ -0,0 +1,53 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use async_trait::async_trait;
+use buck2_client_ctx::common::target_cfg::TargetCfgOptions;
+use buck2_client_ctx::common::CommonCommandOptions;
+
+use crate::AuditSubcommand;
+
+#[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
+#[clap(
+    name = "audit-action-inputs",
+    about = "prints out the input files of the actions of a target, with transitive set projections expanded. \
+    For actions with dep files, inputs that the dep files of their last execution in this daemon \
+    did not list are reported as pruned. This does not run the build, so actions that have not \
+    run since the daemon started list all their inputs."
+)]
+pub struct AuditActionInputsCommand {
+    #[clap(help = "Target to query action inputs for")]
+    pub pattern: String,
+
+    #[clap(long, help = "Only show actions with this category")]
+    pub action: Option<String>,
+
+    #[clap(
+        long,
+        requires = "action",
+        help = "Only show actions with this identifier"
+    )]
+    pub identifier: Option<String>,
+
+    #[clap(long, help = "Output in JSON format")]
+    pub json: bool,
+
+    #[clap(flatten)]
+    pub target_cfg: TargetCfgOptions,
+
+    #[clap(flatten)]
+    pub common_opts: CommonCommandOptions,
+}
+
+#[async_trait]
+impl AuditSubcommand for AuditActionInputsCommand {
+    fn common_opts(&self) -> &CommonCommandOptions {
+        &self.common_opts
+    }
+}
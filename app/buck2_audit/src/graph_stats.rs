@@ -0,0 +1,46 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use async_trait::async_trait;
+use buck2_client_ctx::common::target_cfg::TargetCfgOptions;
+use buck2_client_ctx::common::CommonCommandOptions;
+
+use crate::AuditSubcommand;
+
+#[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
+#[clap(
+    name = "audit-graph-stats",
+    about = "prints statistics about the configured target graph reachable from the given patterns \
+    (node counts by rule type, edge count, max depth, widest fan-out and configuration counts)"
+)]
+pub struct AuditGraphStatsCommand {
+    #[clap(name = "TARGET_PATTERNS", help = "Target patterns to audit")]
+    pub patterns: Vec<String>,
+
+    /// Number of targets with the widest fan-out to report.
+    #[clap(long, default_value = "10")]
+    pub top: usize,
+
+    /// Output in JSON format
+    #[clap(long)]
+    pub json: bool,
+
+    #[clap(flatten)]
+    pub target_cfg: TargetCfgOptions,
+
+    #[clap(flatten)]
+    pub common_opts: CommonCommandOptions,
+}
+
+#[async_trait]
+impl AuditSubcommand for AuditGraphStatsCommand {
+    fn common_opts(&self) -> &CommonCommandOptions {
+        &self.common_opts
+    }
+}
@@ -24,36 +24,48 @@ use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::streaming::StreamingCommand;
 use classpath::AuditClasspathCommand;
 
+use crate::action_inputs::AuditActionInputsCommand;
 use crate::analysis_queries::AuditAnalysisQueriesCommand;
+use crate::audit_tests::AuditTestsCommand;
 use crate::cell::AuditCellCommand;
 use crate::config::AuditConfigCommand;
 use crate::configurations::AuditConfigurationsCommand;
+use crate::daemon_env::AuditDaemonEnvCommand;
 use crate::deferred_materializer::DeferredMaterializerCommand;
 use crate::dep_files::AuditDepFilesCommand;
 use crate::execution_platform_resolution::AuditExecutionPlatformResolutionCommand;
+use crate::graph_stats::AuditGraphStatsCommand;
 use crate::includes::AuditIncludesCommand;
+use crate::licenses::AuditLicensesCommand;
 use crate::output::command::AuditOutputCommand;
 use crate::output::parse::AuditParseCommand;
 use crate::package_values::PackageValuesCommand;
 use crate::prelude::AuditPreludeCommand;
 use crate::providers::AuditProvidersCommand;
+use crate::re_conformance::AuditReConformanceCommand;
 use crate::starlark::StarlarkCommand;
 use crate::subtargets::AuditSubtargetsCommand;
 use crate::visibility::AuditVisibilityCommand;
 
+pub mod action_inputs;
 pub mod analysis_queries;
+pub mod audit_tests;
 pub mod cell;
 pub mod classpath;
 pub mod config;
 pub mod configurations;
+pub mod daemon_env;
 pub mod deferred_materializer;
 pub mod dep_files;
 pub mod execution_platform_resolution;
+pub mod graph_stats;
 pub mod includes;
+pub mod licenses;
 pub mod output;
 pub mod package_values;
 pub mod prelude;
 pub mod providers;
+pub mod re_conformance;
 pub mod starlark;
 pub mod subtargets;
 pub mod visibility;
@@ -65,9 +77,12 @@ pub enum AuditCommand {
     Classpath(AuditClasspathCommand),
     Config(AuditConfigCommand),
     Configurations(AuditConfigurationsCommand),
+    DaemonEnv(AuditDaemonEnvCommand),
     Includes(AuditIncludesCommand),
+    Licenses(AuditLicensesCommand),
     Prelude(AuditPreludeCommand),
     Providers(AuditProvidersCommand),
+    ReConformance(AuditReConformanceCommand),
     Subtargets(AuditSubtargetsCommand),
     AnalysisQueries(AuditAnalysisQueriesCommand),
     ExecutionPlatformResolution(AuditExecutionPlatformResolutionCommand),
@@ -75,6 +90,9 @@ pub enum AuditCommand {
     #[clap(subcommand)]
     Starlark(StarlarkCommand),
     DepFiles(AuditDepFilesCommand),
+    ActionInputs(AuditActionInputsCommand),
+    GraphStats(AuditGraphStatsCommand),
+    Tests(AuditTestsCommand),
     DeferredMaterializer(DeferredMaterializerCommand),
     Output(AuditOutputCommand),
     Parse(AuditParseCommand),
@@ -101,14 +119,20 @@ impl AuditCommand {
             AuditCommand::Classpath(cmd) => cmd,
             AuditCommand::Config(cmd) => cmd,
             AuditCommand::Configurations(cmd) => cmd,
+            AuditCommand::DaemonEnv(cmd) => cmd,
             AuditCommand::Includes(cmd) => cmd,
+            AuditCommand::Licenses(cmd) => cmd,
             AuditCommand::Prelude(cmd) => cmd,
             AuditCommand::Providers(cmd) => cmd,
+            AuditCommand::ReConformance(cmd) => cmd,
             AuditCommand::Subtargets(cmd) => cmd,
             AuditCommand::AnalysisQueries(cmd) => cmd,
             AuditCommand::ExecutionPlatformResolution(cmd) => cmd,
             AuditCommand::Starlark(cmd) => cmd,
             AuditCommand::DepFiles(cmd) => cmd,
+            AuditCommand::ActionInputs(cmd) => cmd,
+            AuditCommand::GraphStats(cmd) => cmd,
+            AuditCommand::Tests(cmd) => cmd,
             AuditCommand::DeferredMaterializer(cmd) => cmd,
             AuditCommand::Visibility(cmd) => cmd,
             AuditCommand::Output(cmd) => cmd,
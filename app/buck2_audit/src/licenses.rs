@@ -0,0 +1,51 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use async_trait::async_trait;
+use buck2_client_ctx::common::target_cfg::TargetCfgOptions;
+use buck2_client_ctx::common::CommonCommandOptions;
+
+use crate::AuditSubcommand;
+
+#[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
+#[clap(
+    name = "audit-licenses",
+    about = "prints the licenses of the given targets and their transitive target deps, as \
+    declared with the `license.spdx` metadata key, and fails if any license is denied by the \
+    `licenses.denylist` buckconfig"
+)]
+pub struct AuditLicensesCommand {
+    #[clap(
+        name = "TARGET_PATTERNS",
+        help = "Patterns of the targets to audit",
+        required = true
+    )]
+    pub patterns: Vec<String>,
+
+    /// Also list the targets that declare each license.
+    #[clap(long)]
+    pub show_targets: bool,
+
+    /// Output in JSON format
+    #[clap(long)]
+    pub json: bool,
+
+    #[clap(flatten)]
+    pub target_cfg: TargetCfgOptions,
+
+    #[clap(flatten)]
+    pub common_opts: CommonCommandOptions,
+}
+
+#[async_trait]
+impl AuditSubcommand for AuditLicensesCommand {
+    fn common_opts(&self) -> &CommonCommandOptions {
+        &self.common_opts
+    }
+}
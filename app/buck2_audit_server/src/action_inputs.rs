@@ -0,0 +1,283 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Write;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use buck2_audit::action_inputs::AuditActionInputsCommand;
+use buck2_build_api::actions::artifact::get_artifact_fs::GetArtifactFs;
+use buck2_build_api::actions::RegisteredAction;
+use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
+use buck2_build_api::artifact_groups::ArtifactGroup;
+use buck2_build_api::artifact_groups::ResolvedArtifactGroup;
+use buck2_build_api::artifact_groups::TransitiveSetProjectionKey;
+use buck2_build_api::audit_dep_files::READ_ACTION_DEP_FILES;
+use buck2_cli_proto::ClientContext;
+use buck2_common::pattern::parse_from_cli::parse_patterns_from_cli_args;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use buck2_directory::directory::directory_selector::DirectorySelector;
+use buck2_error::BuckErrorContext;
+use buck2_node::target_calculation::ConfiguredTargetCalculation;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::global_cfg_options::global_cfg_options_from_client_context;
+use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+use dice::DiceComputations;
+use dice::DiceTransaction;
+use dupe::Dupe;
+
+use crate::ServerAuditSubcommand;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum AuditActionInputsError {
+    #[error("No action matching category `{0}`{1} in `{2}`")]
+    NoMatchingAction(String, String, String),
+}
+
+#[derive(serde::Serialize)]
+struct ActionInputs {
+    category: String,
+    identifier: Option<String>,
+    inputs: Vec<String>,
+    /// Inputs that the dep files of the last execution of the action did not list, so changes to
+    /// them do not rerun it.
+    pruned: Vec<String>,
+}
+
+/// Collects the paths of the given inputs of an action, expanding transitive set projections.
+async fn input_paths(
+    ctx: &mut DiceComputations<'_>,
+    artifact_fs: &ArtifactFs,
+    inputs: Vec<ArtifactGroup>,
+) -> buck2_error::Result<BTreeSet<ProjectRelativePathBuf>> {
+    let mut paths = BTreeSet::new();
+    let mut visited: HashSet<TransitiveSetProjectionKey> = HashSet::new();
+    let mut queue = inputs;
+
+    while let Some(input) = queue.pop() {
+        match input.resolved_artifact(ctx).await? {
+            ResolvedArtifactGroup::Artifact(artifact) => {
+                paths.insert(artifact.get_path().resolve(artifact_fs)?);
+            }
+            ResolvedArtifactGroup::TransitiveSetProjection(key) => {
+                if !visited.insert(key.dupe()) {
+                    continue;
+                }
+                let set = key.key.lookup(ctx).await?;
+                queue.extend(set.get_projection_sub_inputs(key.projection)?);
+            }
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Splits the inputs of an action into the ones it used and the ones its dep files pruned. Tagged
+/// inputs are used if the dep file with their label lists them (or, for directories, anything
+/// in them), or if there are no contents for that label. Untagged inputs are always used.
+fn apply_dep_files(
+    untagged: BTreeSet<ProjectRelativePathBuf>,
+    tagged: Vec<(Arc<str>, BTreeSet<ProjectRelativePathBuf>)>,
+    dep_files: &HashMap<Arc<str>, DirectorySelector>,
+) -> (
+    BTreeSet<ProjectRelativePathBuf>,
+    BTreeSet<ProjectRelativePathBuf>,
+) {
+    let mut used = untagged;
+    let mut pruned = BTreeSet::new();
+
+    for (label, paths) in tagged {
+        for path in paths {
+            match dep_files.get(&label) {
+                Some(selector) if !selector.selects(&path) => {
+                    pruned.insert(path);
+                }
+                _ => {
+                    used.insert(path);
+                }
+            }
+        }
+    }
+
+    pruned.retain(|p| !used.contains(p));
+    (used, pruned)
+}
+
+/// Collects the paths of all the inputs of an action, split into the ones it used and the ones
+/// its dep files pruned. Nothing is pruned if the action has no dep files or if they are not
+/// available in this daemon.
+async fn action_inputs(
+    ctx: &mut DiceTransaction,
+    artifact_fs: &ArtifactFs,
+    action: &RegisteredAction,
+) -> buck2_error::Result<(
+    BTreeSet<ProjectRelativePathBuf>,
+    BTreeSet<ProjectRelativePathBuf>,
+)> {
+    if let Some(dep_file_inputs) = action.dep_file_inputs()? {
+        if let Some(dep_files) = (READ_ACTION_DEP_FILES.get()?)(ctx, action).await? {
+            let untagged = input_paths(ctx, artifact_fs, dep_file_inputs.untagged).await?;
+            let mut tagged = Vec::with_capacity(dep_file_inputs.tagged.len());
+            for (label, inputs) in dep_file_inputs.tagged {
+                tagged.push((label, input_paths(ctx, artifact_fs, inputs).await?));
+            }
+            return Ok(apply_dep_files(untagged, tagged, &dep_files));
+        }
+    }
+
+    let inputs = input_paths(ctx, artifact_fs, action.inputs()?.into_owned()).await?;
+    Ok((inputs, BTreeSet::new()))
+}
+
+#[async_trait]
+impl ServerAuditSubcommand for AuditActionInputsCommand {
+    async fn server_execute(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+        _client_ctx: ClientContext,
+    ) -> buck2_error::Result<()> {
+        Ok(server_ctx
+            .with_dice_ctx(|server_ctx, mut ctx| async move {
+                let global_cfg_options = global_cfg_options_from_client_context(
+                    &self.target_cfg.target_cfg(),
+                    server_ctx,
+                    &mut ctx,
+                )
+                .await?;
+
+                let label = parse_patterns_from_cli_args::<TargetPatternExtra>(
+                    &mut ctx,
+                    &[self.pattern.clone()],
+                    server_ctx.working_dir(),
+                )
+                .await?
+                .into_iter()
+                .next()
+                .buck_error_context("Parsing patterns returned nothing")?
+                .as_target_label(&self.pattern)?;
+
+                let label = ctx
+                    .get_configured_target_post_transition(&label, &global_cfg_options)
+                    .await?;
+
+                let analysis = ctx
+                    .get_analysis_result(&label)
+                    .await?
+                    .require_compatible()?;
+                let artifact_fs = ctx.get_artifact_fs().await?;
+
+                let mut results = Vec::new();
+                for action in analysis.analysis_values().iter_actions() {
+                    if let Some(category) = &self.action {
+                        if action.category().as_str() != category {
+                            continue;
+                        }
+                    }
+                    if self.identifier.is_some()
+                        && action.identifier() != self.identifier.as_deref()
+                    {
+                        continue;
+                    }
+
+                    let (inputs, pruned) = action_inputs(&mut ctx, &artifact_fs, action).await?;
+                    results.push(ActionInputs {
+                        category: action.category().as_str().to_owned(),
+                        identifier: action.identifier().map(|i| i.to_owned()),
+                        inputs: inputs.into_iter().map(|p| p.to_string()).collect(),
+                        pruned: pruned.into_iter().map(|p| p.to_string()).collect(),
+                    });
+                }
+
+                if let Some(category) = &self.action {
+                    if results.is_empty() {
+                        return Err(AuditActionInputsError::NoMatchingAction(
+                            category.clone(),
+                            self.identifier
+                                .as_ref()
+                                .map(|i| format!(" and identifier `{}`", i))
+                                .unwrap_or_default(),
+                            label.to_string(),
+                        )
+                        .into());
+                    }
+                }
+
+                let mut stdout = stdout.as_writer();
+                if self.json {
+                    writeln!(stdout, "{}", serde_json::to_string_pretty(&results)?)?;
+                } else {
+                    for action in results {
+                        match &action.identifier {
+                            Some(identifier) => {
+                                writeln!(stdout, "{} {}", action.category, identifier)?
+                            }
+                            None => writeln!(stdout, "{}", action.category)?,
+                        }
+                        for input in &action.inputs {
+                            writeln!(stdout, "  {}", input)?;
+                        }
+                        for input in &action.pruned {
+                            writeln!(stdout, "  {} (pruned by dep file)", input)?;
+                        }
+                    }
+                }
+
+                Ok(())
+            })
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+
+    use super::*;
+
+    fn paths(paths: &[&str]) -> BTreeSet<ProjectRelativePathBuf> {
+        paths
+            .iter()
+            .map(|p| ProjectRelativePathBuf::unchecked_new((*p).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_dep_files() {
+        let mut headers = DirectorySelector::empty();
+        headers.select(ForwardRelativePath::new("inc/a.h").unwrap());
+        headers.select(ForwardRelativePath::new("gen").unwrap());
+        let dep_files = HashMap::from_iter([(Arc::from("headers"), headers)]);
+
+        let (used, pruned) = apply_dep_files(
+            paths(&["main.c", "inc/common.h"]),
+            vec![
+                (
+                    Arc::from("headers"),
+                    paths(&["inc/a.h", "inc/b.h", "inc/common.h", "gen", "other"]),
+                ),
+                (Arc::from("modules"), paths(&["mod/x.pcm"])),
+            ],
+            &dep_files,
+        );
+
+        assert_eq!(
+            paths(&["gen", "inc/a.h", "inc/common.h", "main.c", "mod/x.pcm"]),
+            used
+        );
+        assert_eq!(paths(&["inc/b.h", "other"]), pruned);
+    }
+}
@@ -0,0 +1,125 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::io::Write;
+
+use async_trait::async_trait;
+use buck2_audit::audit_tests::AuditTestsCommand;
+use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
+use buck2_build_api::interpreter::rule_defs::provider::builtin::external_runner_test_info::FrozenExternalRunnerTestInfo;
+use buck2_cli_proto::ClientContext;
+use buck2_core::configuration::compatibility::MaybeCompatible;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+use buck2_server_ctx::pattern_parse_and_resolve::parse_and_resolve_provider_labels_from_cli_args;
+use dice::DiceComputations;
+use dice::DiceTransaction;
+use futures::future;
+use futures::FutureExt;
+
+use crate::common::target_resolution_config::audit_command_target_resolution_config;
+use crate::ServerAuditSubcommand;
+
+#[derive(serde::Serialize)]
+struct TestEntry {
+    target: String,
+    test_type: String,
+    labels: Vec<String>,
+    contacts: Vec<String>,
+}
+
+#[async_trait]
+impl ServerAuditSubcommand for AuditTestsCommand {
+    async fn server_execute(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+        _client_ctx: ClientContext,
+    ) -> buck2_error::Result<()> {
+        server_ctx
+            .with_dice_ctx(move |server_ctx, ctx| {
+                server_execute_with_dice(self, server_ctx, stdout, ctx)
+            })
+            .await
+    }
+}
+
+async fn server_execute_with_dice(
+    command: &AuditTestsCommand,
+    server_ctx: &dyn ServerCommandContextTrait,
+    mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+    mut ctx: DiceTransaction,
+) -> buck2_error::Result<()> {
+    let target_resolution_config =
+        audit_command_target_resolution_config(&mut ctx, &command.target_cfg, server_ctx).await?;
+
+    let provider_labels = parse_and_resolve_provider_labels_from_cli_args(
+        &mut ctx,
+        &command.patterns,
+        server_ctx.working_dir(),
+    )
+    .await?;
+
+    let mut futs = Vec::new();
+    for label in provider_labels {
+        for providers_label in target_resolution_config
+            .get_configured_provider_label(&mut ctx, &label)
+            .await?
+        {
+            futs.push(DiceComputations::declare_closure(|ctx| {
+                async move {
+                    let providers = ctx.get_providers(&providers_label).await?;
+                    // Incompatible targets are skipped, as they would be by `buck2 test`.
+                    let providers = match providers {
+                        MaybeCompatible::Compatible(providers) => providers,
+                        MaybeCompatible::Incompatible(_) => return buck2_error::Ok(None),
+                    };
+                    let Some(info) = providers
+                        .provider_collection()
+                        .builtin_provider::<FrozenExternalRunnerTestInfo>()
+                    else {
+                        return Ok(None);
+                    };
+                    Ok(Some(TestEntry {
+                        target: providers_label.to_string(),
+                        test_type: info.test_type().to_owned(),
+                        labels: info.labels().map(str::to_owned).collect(),
+                        contacts: info.contacts().map(str::to_owned).collect(),
+                    }))
+                }
+                .boxed()
+            }));
+        }
+    }
+
+    let mut tests: Vec<TestEntry> = future::try_join_all(ctx.compute_many(futs))
+        .await?
+        .into_iter()
+        .flatten()
+        .collect();
+    tests.sort_by(|a, b| a.target.cmp(&b.target));
+
+    let mut stdout = stdout.as_writer();
+    if command.json {
+        writeln!(&mut stdout, "{}", serde_json::to_string_pretty(&tests)?)?;
+    } else {
+        for test in &tests {
+            writeln!(
+                &mut stdout,
+                "{} {} [{}]",
+                test.target,
+                test.test_type,
+                test.labels.join(", ")
+            )?;
+        }
+    }
+    stdout.flush()?;
+    Ok(())
+}
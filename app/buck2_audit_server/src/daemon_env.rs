@@ -0,0 +1,89 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::Write;
+
+use async_trait::async_trait;
+use buck2_audit::daemon_env::AuditDaemonEnvCommand;
+use buck2_cli_proto::ClientContext;
+use buck2_common::daemon_env::original_daemon_env;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+use serde::Serialize;
+
+use crate::ServerAuditSubcommand;
+
+#[derive(Serialize)]
+struct DaemonEnv {
+    hermetic: bool,
+    env: BTreeMap<String, String>,
+    /// Set when the daemon replaced the environment it was started in.
+    original_env: Option<BTreeMap<String, String>>,
+}
+
+fn lossy(env: impl IntoIterator<Item = (OsString, OsString)>) -> BTreeMap<String, String> {
+    env.into_iter()
+        .map(|(k, v)| {
+            (
+                k.to_string_lossy().into_owned(),
+                v.to_string_lossy().into_owned(),
+            )
+        })
+        .collect()
+}
+
+#[async_trait]
+impl ServerAuditSubcommand for AuditDaemonEnvCommand {
+    async fn server_execute(
+        &self,
+        _server_ctx: &dyn ServerCommandContextTrait,
+        mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+        _client_ctx: ClientContext,
+    ) -> buck2_error::Result<()> {
+        let original_env = original_daemon_env().map(|env| lossy(env.clone()));
+        let daemon_env = DaemonEnv {
+            hermetic: original_env.is_some(),
+            env: lossy(std::env::vars_os()),
+            original_env,
+        };
+
+        let mut stdout = stdout.as_writer();
+        if self.json {
+            writeln!(stdout, "{}", serde_json::to_string_pretty(&daemon_env)?)?;
+            return Ok(());
+        }
+
+        writeln!(stdout, "hermetic: {}", daemon_env.hermetic)?;
+        for (k, v) in &daemon_env.env {
+            match daemon_env.original_env.as_ref().map(|o| o.get(k)) {
+                Some(Some(original)) if original != v => {
+                    writeln!(stdout, "{}={} (originally {})", k, v, original)?
+                }
+                Some(None) => writeln!(stdout, "{}={} (not originally set)", k, v)?,
+                _ => writeln!(stdout, "{}={}", k, v)?,
+            }
+        }
+        if let Some(original_env) = &daemon_env.original_env {
+            let removed: Vec<_> = original_env
+                .keys()
+                .filter(|k| !daemon_env.env.contains_key(*k))
+                .collect();
+            if !removed.is_empty() {
+                writeln!(stdout)?;
+                writeln!(stdout, "Removed from the original environment:")?;
+                for k in removed {
+                    writeln!(stdout, "  {}", k)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io::Write;
+
+use async_trait::async_trait;
+use buck2_audit::graph_stats::AuditGraphStatsCommand;
+use buck2_build_api::configure_targets::load_compatible_patterns;
+use buck2_cli_proto::ClientContext;
+use buck2_common::pattern::parse_from_cli::parse_patterns_from_cli_args;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+use buck2_node::load_patterns::MissingTargetBehavior;
+use buck2_node::nodes::configured::ConfiguredTargetNode;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::global_cfg_options::global_cfg_options_from_client_context;
+use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+
+use crate::ServerAuditSubcommand;
+
+#[derive(serde::Serialize)]
+struct FanOut {
+    target: String,
+    deps: usize,
+}
+
+#[derive(serde::Serialize)]
+struct GraphStats {
+    targets: usize,
+    edges: usize,
+    /// Length of the longest dependency chain, in edges.
+    max_depth: usize,
+    rule_types: BTreeMap<String, usize>,
+    configurations: BTreeMap<String, usize>,
+    widest_fan_out: Vec<FanOut>,
+}
+
+impl GraphStats {
+    fn compute<'a>(roots: impl IntoIterator<Item = &'a ConfiguredTargetNode>, top: usize) -> Self {
+        let mut nodes: HashMap<&ConfiguredTargetLabel, &ConfiguredTargetNode> = HashMap::new();
+        let mut queue: Vec<&ConfiguredTargetNode> = roots.into_iter().collect();
+        while let Some(node) = queue.pop() {
+            if nodes.insert(node.label(), node).is_none() {
+                queue.extend(node.deps());
+            }
+        }
+
+        let mut edges = 0;
+        let mut rule_types = BTreeMap::new();
+        let mut configurations = BTreeMap::new();
+        let mut fan_out = Vec::with_capacity(nodes.len());
+        for node in nodes.values() {
+            let deps = node.deps().count();
+            edges += deps;
+            *rule_types.entry(node.rule_type().to_string()).or_insert(0) += 1;
+            *configurations
+                .entry(node.label().cfg().to_string())
+                .or_insert(0) += 1;
+            fan_out.push((node.label(), deps));
+        }
+        fan_out.sort_by(|(a_label, a), (b_label, b)| b.cmp(a).then_with(|| a_label.cmp(b_label)));
+        let widest_fan_out = fan_out
+            .into_iter()
+            .take(top)
+            .map(|(label, deps)| FanOut {
+                target: label.to_string(),
+                deps,
+            })
+            .collect();
+
+        GraphStats {
+            targets: nodes.len(),
+            edges,
+            max_depth: max_depth(&nodes),
+            rule_types,
+            configurations,
+            widest_fan_out,
+        }
+    }
+}
+
+/// Longest path in the graph, computed iteratively since target graphs can be too deep to
+/// recurse over.
+fn max_depth(nodes: &HashMap<&ConfiguredTargetLabel, &ConfiguredTargetNode>) -> usize {
+    let mut depths: HashMap<&ConfiguredTargetLabel, usize> = HashMap::with_capacity(nodes.len());
+    let mut max = 0;
+    for node in nodes.values() {
+        let mut stack = vec![(*node, false)];
+        while let Some((node, visited_deps)) = stack.pop() {
+            if depths.contains_key(node.label()) {
+                continue;
+            }
+            if visited_deps {
+                let depth = node
+                    .deps()
+                    .filter_map(|d| depths.get(d.label()))
+                    .map(|d| d + 1)
+                    .max()
+                    .unwrap_or(0);
+                max = max.max(depth);
+                depths.insert(node.label(), depth);
+            } else {
+                stack.push((node, true));
+                stack.extend(
+                    node.deps()
+                        .filter(|d| !depths.contains_key(d.label()))
+                        .map(|d| (d, false)),
+                );
+            }
+        }
+    }
+    max
+}
+
+#[async_trait]
+impl ServerAuditSubcommand for AuditGraphStatsCommand {
+    async fn server_execute(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+        _client_ctx: ClientContext,
+    ) -> buck2_error::Result<()> {
+        Ok(server_ctx
+            .with_dice_ctx(|server_ctx, mut ctx| async move {
+                let parsed_patterns = parse_patterns_from_cli_args::<TargetPatternExtra>(
+                    &mut ctx,
+                    &self.patterns,
+                    server_ctx.working_dir(),
+                )
+                .await?;
+                let global_cfg_options = global_cfg_options_from_client_context(
+                    &self.target_cfg.target_cfg(),
+                    server_ctx,
+                    &mut ctx,
+                )
+                .await?;
+                // Incompatible targets are skipped because this is an audit command
+                let targets = load_compatible_patterns(
+                    &mut ctx,
+                    parsed_patterns,
+                    &global_cfg_options,
+                    MissingTargetBehavior::Fail,
+                )
+                .await?;
+
+                let stats = GraphStats::compute(targets.iter(), self.top);
+
+                let mut stdout = stdout.as_writer();
+                if self.json {
+                    writeln!(stdout, "{}", serde_json::to_string_pretty(&stats)?)?;
+                } else {
+                    writeln!(stdout, "targets: {}", stats.targets)?;
+                    writeln!(stdout, "edges: {}", stats.edges)?;
+                    writeln!(stdout, "max depth: {}", stats.max_depth)?;
+                    writeln!(stdout, "configurations: {}", stats.configurations.len())?;
+                    for (cfg, count) in &stats.configurations {
+                        writeln!(stdout, "  {}: {}", cfg, count)?;
+                    }
+                    writeln!(stdout, "rule types: {}", stats.rule_types.len())?;
+                    for (rule_type, count) in &stats.rule_types {
+                        writeln!(stdout, "  {}: {}", rule_type, count)?;
+                    }
+                    writeln!(stdout, "widest fan-out:")?;
+                    for fan_out in &stats.widest_fan_out {
+                        writeln!(stdout, "  {}: {}", fan_out.target, fan_out.deps)?;
+                    }
+                }
+
+                Ok(())
+            })
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::configuration::data::ConfigurationData;
+    use dupe::Dupe;
+
+    use super::*;
+
+    fn node(name: &str, rule_type: &str, deps: &[&ConfiguredTargetNode]) -> ConfiguredTargetNode {
+        ConfiguredTargetNode::testing_with_deps(
+            ConfiguredTargetLabel::testing_parse(name, ConfigurationData::testing_new()),
+            rule_type,
+            deps.iter().map(|d| (*d).dupe()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_compute() {
+        let d = node("root//:d", "cxx_library", &[]);
+        let b = node("root//:b", "cxx_library", &[&d]);
+        let c = node("root//:c", "cxx_library", &[&d]);
+        let a = node("root//:a", "cxx_binary", &[&b, &c, &d]);
+
+        let stats = GraphStats::compute([&a, &b], 2);
+
+        assert_eq!(4, stats.targets);
+        assert_eq!(5, stats.edges);
+        assert_eq!(2, stats.max_depth);
+        assert_eq!(
+            BTreeMap::from_iter([
+                (a.rule_type().to_string(), 1),
+                (d.rule_type().to_string(), 3),
+            ]),
+            stats.rule_types
+        );
+        assert_eq!(
+            BTreeMap::from_iter([(a.label().cfg().to_string(), 4)]),
+            stats.configurations
+        );
+        assert_eq!(
+            vec![(a.label().to_string(), 3), (b.label().to_string(), 1)],
+            stats
+                .widest_fan_out
+                .iter()
+                .map(|f| (f.target.clone(), f.deps))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_compute_single_target() {
+        let a = node("root//:a", "cxx_binary", &[]);
+
+        let stats = GraphStats::compute([&a], 10);
+
+        assert_eq!(1, stats.targets);
+        assert_eq!(0, stats.edges);
+        assert_eq!(0, stats.max_depth);
+        assert_eq!(1, stats.widest_fan_out.len());
+        assert_eq!(0, stats.widest_fan_out[0].deps);
+    }
+}
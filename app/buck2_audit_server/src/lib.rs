@@ -19,20 +19,26 @@ use buck2_cli_proto::ClientContext;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 
+mod action_inputs;
 mod analysis_queries;
+mod audit_tests;
 mod cell;
 mod classpath;
 mod common;
 mod config;
 mod configurations;
+mod daemon_env;
 pub mod deferred_materializer;
 mod dep_files;
 mod execution_platform_resolution;
+mod graph_stats;
 mod includes;
+mod licenses;
 pub mod output;
 mod package_values;
 mod prelude;
 mod providers;
+mod re_conformance;
 mod server;
 mod starlark;
 mod subtargets;
@@ -85,14 +91,20 @@ impl AuditCommandExt for AuditCommand {
             AuditCommand::Classpath(cmd) => cmd,
             AuditCommand::Config(cmd) => cmd,
             AuditCommand::Configurations(cmd) => cmd,
+            AuditCommand::DaemonEnv(cmd) => cmd,
             AuditCommand::Includes(cmd) => cmd,
+            AuditCommand::Licenses(cmd) => cmd,
             AuditCommand::Prelude(cmd) => cmd,
             AuditCommand::Providers(cmd) => cmd,
+            AuditCommand::ReConformance(cmd) => cmd,
             AuditCommand::Subtargets(cmd) => cmd,
             AuditCommand::AnalysisQueries(cmd) => cmd,
             AuditCommand::ExecutionPlatformResolution(cmd) => cmd,
             AuditCommand::Starlark(cmd) => cmd,
             AuditCommand::DepFiles(cmd) => cmd,
+            AuditCommand::ActionInputs(cmd) => cmd,
+            AuditCommand::GraphStats(cmd) => cmd,
+            AuditCommand::Tests(cmd) => cmd,
             AuditCommand::DeferredMaterializer(cmd) => cmd,
             AuditCommand::Visibility(cmd) => cmd,
             AuditCommand::Output(cmd) => cmd,
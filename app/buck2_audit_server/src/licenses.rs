@@ -0,0 +1,269 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+use std::io::Write;
+
+use async_trait::async_trait;
+use buck2_audit::licenses::AuditLicensesCommand;
+use buck2_build_api::configure_targets::load_compatible_patterns;
+use buck2_cli_proto::ClientContext;
+use buck2_common::dice::cells::HasCellResolver;
+use buck2_common::legacy_configs::dice::HasLegacyConfigs;
+use buck2_common::legacy_configs::key::BuckconfigKeyRef;
+use buck2_common::pattern::parse_from_cli::parse_patterns_from_cli_args;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use buck2_node::attrs::configured_attr::ConfiguredAttr;
+use buck2_node::attrs::inspect_options::AttrInspectOptions;
+use buck2_node::attrs::internal::METADATA_ATTRIBUTE_FIELD;
+use buck2_node::load_patterns::MissingTargetBehavior;
+use buck2_node::metadata::key::MetadataKeyRef;
+use buck2_node::nodes::configured::ConfiguredTargetNode;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::global_cfg_options::global_cfg_options_from_client_context;
+use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+
+use crate::ServerAuditSubcommand;
+
+/// Metadata key under which targets declare their licenses, as an SPDX license expression or a
+/// list of them.
+const LICENSE_METADATA_KEY: &str = "license.spdx";
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum AuditLicensesError {
+    #[error(
+        "`{0}` has an invalid `{LICENSE_METADATA_KEY}` metadata value, expected a string or a list \
+        of strings: {1}"
+    )]
+    InvalidLicense(String, String),
+    #[error("Denied licenses (per `licenses.denylist`): {0}")]
+    Denied(String),
+}
+
+#[derive(Debug, Default, PartialEq, serde::Serialize)]
+struct LicenseReport {
+    /// License expression to the targets that declare it.
+    licenses: BTreeMap<String, BTreeSet<String>>,
+    /// Targets that declare no license.
+    unlicensed: BTreeSet<String>,
+}
+
+/// The licenses declared by a target in its metadata.
+fn declared_licenses(node: &ConfiguredTargetNode) -> buck2_error::Result<Vec<String>> {
+    let Some(attr) = node.get(METADATA_ATTRIBUTE_FIELD, AttrInspectOptions::All) else {
+        return Ok(Vec::new());
+    };
+    let ConfiguredAttr::Metadata(metadata) = attr.value else {
+        return Ok(Vec::new());
+    };
+    let Some(value) = metadata.get(MetadataKeyRef::unchecked_new(LICENSE_METADATA_KEY)) else {
+        return Ok(Vec::new());
+    };
+    parse_licenses(value.as_json()).ok_or_else(|| {
+        AuditLicensesError::InvalidLicense(node.label().to_string(), value.as_json().to_string())
+            .into()
+    })
+}
+
+fn parse_licenses(value: &serde_json::Value) -> Option<Vec<String>> {
+    match value {
+        serde_json::Value::String(license) => Some(vec![license.clone()]),
+        serde_json::Value::Array(licenses) => licenses
+            .iter()
+            .map(|l| l.as_str().map(str::to_owned))
+            .collect(),
+        _ => None,
+    }
+}
+
+/// License identifiers in an SPDX expression such as `(MIT OR Apache-2.0) AND BSD-3-Clause`.
+fn license_ids(expression: &str) -> impl Iterator<Item = &str> {
+    expression
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .filter(|id| !id.is_empty() && !matches!(*id, "AND" | "OR" | "WITH"))
+}
+
+impl LicenseReport {
+    /// Licenses of the roots and everything they link with, i.e. their transitive target deps.
+    /// Execution deps such as compilers are not part of the output, so their licenses are not
+    /// included.
+    fn compute<'a>(
+        roots: impl IntoIterator<Item = &'a ConfiguredTargetNode>,
+    ) -> buck2_error::Result<Self> {
+        let mut report = LicenseReport::default();
+        let mut visited = HashSet::new();
+        let mut queue: Vec<&ConfiguredTargetNode> = roots.into_iter().collect();
+        while let Some(node) = queue.pop() {
+            if !visited.insert(node.label()) {
+                continue;
+            }
+            let licenses = declared_licenses(node)?;
+            if licenses.is_empty() {
+                report
+                    .unlicensed
+                    .insert(node.label().unconfigured().to_string());
+            }
+            for license in licenses {
+                report
+                    .licenses
+                    .entry(license)
+                    .or_default()
+                    .insert(node.label().unconfigured().to_string());
+            }
+            queue.extend(node.target_deps());
+        }
+        Ok(report)
+    }
+
+    /// License expressions that mention a denied license identifier. This errs on the side of
+    /// caution: `MIT OR GPL-3.0` is denied if `GPL-3.0` is.
+    fn denied(&self, denylist: &[&str]) -> Vec<&str> {
+        self.licenses
+            .keys()
+            .filter(|expression| license_ids(expression).any(|id| denylist.contains(&id)))
+            .map(|expression| expression.as_str())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ServerAuditSubcommand for AuditLicensesCommand {
+    async fn server_execute(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+        _client_ctx: ClientContext,
+    ) -> buck2_error::Result<()> {
+        Ok(server_ctx
+            .with_dice_ctx(|server_ctx, mut ctx| async move {
+                let parsed_patterns = parse_patterns_from_cli_args::<TargetPatternExtra>(
+                    &mut ctx,
+                    &self.patterns,
+                    server_ctx.working_dir(),
+                )
+                .await?;
+                let global_cfg_options = global_cfg_options_from_client_context(
+                    &self.target_cfg.target_cfg(),
+                    server_ctx,
+                    &mut ctx,
+                )
+                .await?;
+                // Incompatible targets are skipped because this is an audit command
+                let targets = load_compatible_patterns(
+                    &mut ctx,
+                    parsed_patterns,
+                    &global_cfg_options,
+                    MissingTargetBehavior::Fail,
+                )
+                .await?;
+
+                let root_cell = ctx.get_cell_resolver().await?.root_cell();
+                let denylist = ctx
+                    .get_legacy_config_property(
+                        root_cell,
+                        BuckconfigKeyRef {
+                            section: "licenses",
+                            property: "denylist",
+                        },
+                    )
+                    .await?;
+                let denylist: Vec<&str> = denylist
+                    .as_deref()
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|id| !id.is_empty())
+                    .collect();
+
+                let report = LicenseReport::compute(targets.iter())?;
+
+                let mut stdout = stdout.as_writer();
+                if self.json {
+                    writeln!(stdout, "{}", serde_json::to_string_pretty(&report)?)?;
+                } else {
+                    for (license, targets) in &report.licenses {
+                        writeln!(stdout, "{}: {} targets", license, targets.len())?;
+                        if self.show_targets {
+                            for target in targets {
+                                writeln!(stdout, "  {}", target)?;
+                            }
+                        }
+                    }
+                    writeln!(stdout, "no license: {} targets", report.unlicensed.len())?;
+                    if self.show_targets {
+                        for target in &report.unlicensed {
+                            writeln!(stdout, "  {}", target)?;
+                        }
+                    }
+                }
+                stdout.flush()?;
+
+                let denied = report.denied(&denylist);
+                if !denied.is_empty() {
+                    let denied = denied
+                        .iter()
+                        .map(|license| {
+                            let targets = &report.licenses[*license];
+                            format!(
+                                "`{}` (declared by {})",
+                                license,
+                                targets.iter().cloned().collect::<Vec<_>>().join(", ")
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("; ");
+                    return Err(AuditLicensesError::Denied(denied).into());
+                }
+
+                Ok(())
+            })
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_licenses() {
+        assert_eq!(
+            parse_licenses(&serde_json::json!("MIT")),
+            Some(vec!["MIT".to_owned()])
+        );
+        assert_eq!(
+            parse_licenses(&serde_json::json!(["MIT", "BSD-3-Clause"])),
+            Some(vec!["MIT".to_owned(), "BSD-3-Clause".to_owned()])
+        );
+        assert_eq!(parse_licenses(&serde_json::json!(["MIT", 1])), None);
+        assert_eq!(parse_licenses(&serde_json::json!({"id": "MIT"})), None);
+    }
+
+    #[test]
+    fn test_denied() {
+        assert_eq!(
+            license_ids("(MIT OR Apache-2.0) AND GPL-2.0 WITH Classpath-exception-2.0")
+                .collect::<Vec<_>>(),
+            vec!["MIT", "Apache-2.0", "GPL-2.0", "Classpath-exception-2.0"]
+        );
+
+        let mut report = LicenseReport::default();
+        for license in ["MIT", "MIT OR GPL-3.0", "Apache-2.0"] {
+            report
+                .licenses
+                .insert(license.to_owned(), BTreeSet::from(["root//:a".to_owned()]));
+        }
+        assert_eq!(report.denied(&["GPL-3.0"]), vec!["MIT OR GPL-3.0"]);
+        assert_eq!(report.denied(&["GPL-3.0-only"]), Vec::<&str>::new());
+    }
+}
@@ -16,6 +16,7 @@ use buck2_build_api::actions::query::PRINT_ACTION_NODE;
 use buck2_build_api::analysis::calculation::RuleAnalysisCalculation;
 use buck2_build_api::audit_output::AuditOutputResult;
 use buck2_build_api::audit_output::AUDIT_OUTPUT;
+use buck2_build_api::context::HasBuildContextData;
 use buck2_cli_proto::ClientContext;
 use buck2_common::dice::cells::HasCellResolver;
 use buck2_core::cells::CellResolver;
@@ -72,8 +73,12 @@ async fn audit_output<'v>(
         .await?;
 
     let command_config = configured_target_label.cfg();
-    let command_config_hash = command_config.output_hash();
-    if command_config_hash.as_str() != config_hash {
+    let buck_out_path_resolver = dice_ctx.get_buck_out_path().await?;
+    if buck_out_path_resolver
+        .scheme()
+        .configuration_component(command_config)
+        != config_hash
+    {
         return Ok(Some(AuditOutputResult::MaybeRelevant(target_label)));
     }
 
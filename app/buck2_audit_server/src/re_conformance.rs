@@ -0,0 +1,103 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::io::Write;
+
+use async_trait::async_trait;
+use buck2_audit::re_conformance::AuditReConformanceCommand;
+use buck2_build_api::actions::execute::dice_data::GetReClient;
+use buck2_cli_proto::ClientContext;
+use buck2_execute::digest_config::HasDigestConfig;
+use buck2_execute::re::conformance::run_conformance_checks;
+use buck2_execute::re::conformance::ConformanceOutcome;
+use buck2_server_ctx::ctx::ServerCommandContextTrait;
+use buck2_server_ctx::ctx::ServerCommandDiceContext;
+use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
+use serde::Serialize;
+
+use crate::ServerAuditSubcommand;
+
+#[derive(buck2_error::Error, Debug)]
+#[buck2(environment)]
+#[error("{0} of {1} RE conformance checks failed")]
+struct ReConformanceFailed(usize, usize);
+
+#[derive(Serialize)]
+struct CheckJson<'a> {
+    name: &'a str,
+    description: &'a str,
+    outcome: &'a str,
+    message: Option<&'a str>,
+}
+
+#[async_trait]
+impl ServerAuditSubcommand for AuditReConformanceCommand {
+    async fn server_execute(
+        &self,
+        server_ctx: &dyn ServerCommandContextTrait,
+        mut stdout: PartialResultDispatcher<buck2_cli_proto::StdoutBytes>,
+        _client_ctx: ClientContext,
+    ) -> buck2_error::Result<()> {
+        let checks = server_ctx
+            .with_dice_ctx(|_server_ctx, ctx| async move {
+                let re_client = ctx.per_transaction_data().get_re_client();
+                let digest_config = ctx.global_data().get_digest_config();
+                Ok(run_conformance_checks(&re_client, digest_config).await)
+            })
+            .await?;
+
+        let mut stdout = stdout.as_writer();
+        if self.json {
+            let json: Vec<_> = checks
+                .iter()
+                .map(|check| {
+                    let (outcome, message) = match &check.outcome {
+                        ConformanceOutcome::Passed => ("passed", None),
+                        ConformanceOutcome::Failed(message) => ("failed", Some(message.as_str())),
+                        ConformanceOutcome::Skipped(message) => ("skipped", Some(message.as_str())),
+                    };
+                    CheckJson {
+                        name: check.name,
+                        description: check.description,
+                        outcome,
+                        message,
+                    }
+                })
+                .collect();
+            writeln!(stdout, "{}", serde_json::to_string_pretty(&json)?)?;
+        } else {
+            for check in &checks {
+                match &check.outcome {
+                    ConformanceOutcome::Passed => {
+                        writeln!(stdout, "PASS {}: {}", check.name, check.description)?
+                    }
+                    ConformanceOutcome::Failed(message) => writeln!(
+                        stdout,
+                        "FAIL {}: {}\n     {}",
+                        check.name, check.description, message
+                    )?,
+                    ConformanceOutcome::Skipped(message) => writeln!(
+                        stdout,
+                        "SKIP {}: {}\n     {}",
+                        check.name, check.description, message
+                    )?,
+                }
+            }
+        }
+
+        let failed = checks
+            .iter()
+            .filter(|check| matches!(check.outcome, ConformanceOutcome::Failed(_)))
+            .count();
+        if failed > 0 {
+            return Err(ReConformanceFailed(failed, checks.len()).into());
+        }
+        Ok(())
+    }
+}
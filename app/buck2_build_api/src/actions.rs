@@ -72,6 +72,7 @@ use crate::actions::execute::error::ExecuteError;
 use crate::actions::impls::run_action_knobs::RunActionKnobs;
 use crate::artifact_groups::ArtifactGroup;
 use crate::artifact_groups::ArtifactGroupValues;
+use crate::audit_dep_files::DepFileInputs;
 
 pub mod artifact;
 pub mod box_slice_set;
@@ -156,6 +157,12 @@ pub trait Action: Allocative + Debug + Send + Sync + 'static {
         indexmap! {}
     }
 
+    /// The inputs of this action split by the dep file that decides whether they are used, or
+    /// `None` if this action does not produce dep files.
+    fn dep_file_inputs(&self) -> buck2_error::Result<Option<DepFileInputs>> {
+        Ok(None)
+    }
+
     /// error handler
     fn error_handler(&self) -> Option<OwnedFrozenValue> {
         None
@@ -209,6 +216,10 @@ pub trait ActionExecutionCtx: Send + Sync {
 
     fn mergebase(&self) -> &Mergebase;
 
+    /// Whether the action was on the critical path of a previous build, and should be scheduled
+    /// first. Always false if critical path scheduling is disabled.
+    fn predicted_on_critical_path(&self) -> bool;
+
     fn prepare_action(
         &mut self,
         request: &CommandExecutionRequest,
@@ -18,6 +18,7 @@ use buck2_artifact::actions::key::ActionKey;
 use buck2_artifact::artifact::build_artifact::BuildArtifact;
 use buck2_build_signals::env::NodeDuration;
 use buck2_common::events::HasEvents;
+use buck2_common::legacy_configs::dice::HasLegacyConfigs;
 use buck2_core::deferred::base_deferred_key::BaseDeferredKey;
 use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
 use buck2_data::ActionErrorDiagnostics;
@@ -30,12 +31,17 @@ use buck2_events::dispatch::async_record_root_spans;
 use buck2_events::dispatch::get_dispatcher;
 use buck2_events::dispatch::span_async;
 use buck2_events::span::SpanId;
+use buck2_execute::artifact_signing::ArtifactSigners;
 use buck2_execute::execute::result::CommandExecutionReport;
 use buck2_execute::execute::result::CommandExecutionStatus;
+use buck2_execute::materialize::materializer::HasMaterializer;
 use buck2_execute::output_size::OutputSize;
 use buck2_futures::cancellation::CancellationContext;
 use buck2_interpreter::print_handler::EventDispatcherPrintHandler;
 use buck2_interpreter::soft_error::Buck2StarlarkSoftErrorHandler;
+use buck2_node::attrs::configured_attr::ConfiguredAttr;
+use buck2_node::attrs::inspect_options::AttrInspectOptions;
+use buck2_node::nodes::configured::ConfiguredTargetNode;
 use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
 use derive_more::Display;
 use dice::DiceComputations;
@@ -52,6 +58,7 @@ use starlark::environment::Module;
 use starlark::eval::Evaluator;
 use tracing::debug;
 
+use crate::actions::artifact::get_artifact_fs::GetArtifactFs;
 use crate::actions::error::ActionError;
 use crate::actions::error_handler::ActionErrorHandlerError;
 use crate::actions::error_handler::ActionSubErrorResult;
@@ -59,6 +66,7 @@ use crate::actions::error_handler::StarlarkActionErrorContext;
 use crate::actions::execute::action_executor::ActionOutputs;
 use crate::actions::execute::action_executor::BuckActionExecutor;
 use crate::actions::execute::action_executor::HasActionExecutor;
+use crate::actions::execute::lost_inputs::execute_reexecuting_lost_inputs;
 use crate::actions::RegisteredAction;
 use crate::artifact_groups::calculation::ensure_artifact_group_staged;
 use crate::artifact_groups::ArtifactGroup;
@@ -100,32 +108,7 @@ async fn build_action_no_redirect(
     cancellation: &CancellationContext<'_>,
     action: Arc<RegisteredAction>,
 ) -> buck2_error::Result<ActionOutputs> {
-    let materialized_inputs = {
-        let inputs = action.inputs()?;
-
-        let ready_inputs: Vec<_> = tokio::task::unconstrained(KeepGoing::try_compute_join_all(
-            ctx,
-            inputs.iter(),
-            |ctx, v| {
-                async move {
-                    let resolved = v.resolved_artifact(ctx).await?;
-                    buck2_error::Ok(
-                        ensure_artifact_group_staged(ctx, resolved.clone())
-                            .await?
-                            .to_group_values(&resolved)?,
-                    )
-                }
-                .boxed()
-            },
-        ))
-        .await?;
-
-        let mut results = IndexMap::with_capacity(inputs.len());
-        for (artifact, ready) in zip(inputs.iter(), ready_inputs) {
-            results.insert(artifact.clone(), ready);
-        }
-        results
-    };
+    let materialized_inputs = materialize_inputs(ctx, &action).await?;
 
     let start_event = buck2_data::ActionExecutionStart {
         key: Some(action.key().as_proto()),
@@ -149,8 +132,8 @@ async fn build_action_no_redirect(
         _ => None,
     };
 
-    let target_rule_type_name = match target {
-        Some(label) => Some(get_target_rule_type_name(ctx, &label).await?),
+    let target_rule_type_name = match &target {
+        Some(label) => Some(get_target_rule_type_name(ctx, label).await?),
         None => None,
     };
 
@@ -180,7 +163,103 @@ async fn build_action_no_redirect(
         spans,
     })?;
 
-    action_execution_data.action_result
+    let outputs = action_execution_data.action_result?;
+    if let Some(target) = &target {
+        sign_action_outputs(ctx, target, &outputs)
+            .await
+            .buck_error_context(format!("signing outputs of action `{}`", action))?;
+    }
+    Ok(outputs)
+}
+
+/// Signs the outputs of an action owned by `target` with the signers from `[artifact_signing]`
+/// that apply to it. Unchanged outputs are not signed again.
+async fn sign_action_outputs(
+    ctx: &mut DiceComputations<'_>,
+    target: &ConfiguredTargetLabel,
+    outputs: &ActionOutputs,
+) -> buck2_error::Result<()> {
+    let config = ctx.get_legacy_root_config_on_dice().await?;
+    let signers = ArtifactSigners::parse(config.view(ctx))?;
+    if signers.is_empty() {
+        return Ok(());
+    }
+
+    let node = ctx
+        .get_configured_target_node(target)
+        .await?
+        .require_compatible()?;
+    let labels = target_labels(&node);
+    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+    let matching = signers.matching(node.rule_type().name(), &labels);
+    if matching.is_empty() {
+        return Ok(());
+    }
+
+    let artifact_fs = ctx.get_artifact_fs().await?;
+    let materializer = ctx.per_transaction_data().get_materializer();
+    for (path, value) in outputs.iter() {
+        let Some(digest) = value.digest() else {
+            // Symlinks have nothing to sign.
+            continue;
+        };
+        let digest = digest.to_string();
+        let path = artifact_fs.resolve_build(path);
+        materializer.ensure_materialized(vec![path.clone()]).await?;
+        let path = artifact_fs.fs().resolve(&path);
+        for signer in &matching {
+            signer.sign(&path, &digest, artifact_fs.fs()).await?;
+        }
+    }
+    Ok(())
+}
+
+fn target_labels(node: &ConfiguredTargetNode) -> Vec<String> {
+    // Like `TargetLabelFilter`, this relies on `labels` being a plain list of strings.
+    match node
+        .get("labels", AttrInspectOptions::All)
+        .map(|attr| attr.value)
+    {
+        Some(ConfiguredAttr::List(list)) => list
+            .iter()
+            .filter_map(|label| match label {
+                ConfiguredAttr::String(label) => Some(label.to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Stage the inputs of the action, and return their values.
+pub(crate) async fn materialize_inputs(
+    ctx: &mut DiceComputations<'_>,
+    action: &RegisteredAction,
+) -> buck2_error::Result<IndexMap<ArtifactGroup, ArtifactGroupValues>> {
+    let inputs = action.inputs()?;
+
+    let ready_inputs: Vec<_> = tokio::task::unconstrained(KeepGoing::try_compute_join_all(
+        ctx,
+        inputs.iter(),
+        |ctx, v| {
+            async move {
+                let resolved = v.resolved_artifact(ctx).await?;
+                buck2_error::Ok(
+                    ensure_artifact_group_staged(ctx, resolved.clone())
+                        .await?
+                        .to_group_values(&resolved)?,
+                )
+            }
+            .boxed()
+        },
+    ))
+    .await?;
+
+    let mut results = IndexMap::with_capacity(inputs.len());
+    for (artifact, ready) in zip(inputs.iter(), ready_inputs) {
+        results.insert(artifact.clone(), ready);
+    }
+    Ok(results)
 }
 
 async fn build_action_inner(
@@ -191,9 +270,9 @@ async fn build_action_inner(
     action: &Arc<RegisteredAction>,
     target_rule_type_name: Option<String>,
 ) -> (ActionExecutionData, Box<buck2_data::ActionExecutionEnd>) {
-    let (execute_result, command_reports) = executor
-        .execute(materialized_inputs, action, cancellation)
-        .await;
+    let (execute_result, command_reports) =
+        execute_reexecuting_lost_inputs(ctx, cancellation, executor, materialized_inputs, action)
+            .await;
 
     let allow_omit_details = execute_result.is_ok();
 
@@ -11,3 +11,4 @@ pub mod action_execution_target;
 pub mod action_executor;
 pub mod dice_data;
 pub mod error;
+pub(crate) mod lost_inputs;
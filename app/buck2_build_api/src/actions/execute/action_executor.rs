@@ -14,6 +14,9 @@ use std::sync::Arc;
 use allocative::Allocative;
 use async_trait::async_trait;
 use buck2_artifact::artifact::build_artifact::BuildArtifact;
+use buck2_build_signals::critical_path_history::CriticalPathActionId;
+use buck2_build_signals::critical_path_history::CriticalPathHistory;
+use buck2_build_signals::critical_path_history::HasCriticalPathScheduling;
 use buck2_common::dice::data::HasIoProvider;
 use buck2_common::events::HasEvents;
 use buck2_common::http::HasHttpClient;
@@ -57,6 +60,7 @@ use buck2_execute::re::manager::ManagedRemoteExecutionClient;
 use buck2_file_watcher::mergebase::GetMergebase;
 use buck2_file_watcher::mergebase::Mergebase;
 use buck2_futures::cancellation::CancellationContext;
+use buck2_futures::cpu_attribution::CpuSubsystem;
 use buck2_http::HttpClient;
 use derivative::Derivative;
 use derive_more::Display;
@@ -253,6 +257,10 @@ impl HasActionExecutor for DiceComputations<'_> {
         let http_client = self.per_transaction_data().get_http_client();
         let mergebase = self.per_transaction_data().get_mergebase();
         let invalidation_tracking_enabled = self.get_invalidation_tracking_config().enabled;
+        let critical_path_scheduling = self.per_transaction_data().get_critical_path_scheduling();
+        let critical_path_history = critical_path_scheduling
+            .enabled
+            .then_some(critical_path_scheduling.history);
 
         Ok(Arc::new(BuckActionExecutor::new(
             CommandExecutor::new(
@@ -273,6 +281,7 @@ impl HasActionExecutor for DiceComputations<'_> {
             http_client,
             mergebase,
             invalidation_tracking_enabled,
+            critical_path_history,
         )))
     }
 }
@@ -289,6 +298,8 @@ pub struct BuckActionExecutor {
     http_client: HttpClient,
     mergebase: Mergebase,
     invalidation_tracking_enabled: bool,
+    /// Set if critical path scheduling is enabled.
+    critical_path_history: Option<CriticalPathHistory>,
 }
 
 impl BuckActionExecutor {
@@ -304,6 +315,7 @@ impl BuckActionExecutor {
         http_client: HttpClient,
         mergebase: Mergebase,
         invalidation_tracking_enabled: bool,
+        critical_path_history: Option<CriticalPathHistory>,
     ) -> Self {
         BuckActionExecutor {
             command_executor,
@@ -317,6 +329,7 @@ impl BuckActionExecutor {
             http_client,
             mergebase,
             invalidation_tracking_enabled,
+            critical_path_history,
         }
     }
 }
@@ -328,6 +341,9 @@ struct BuckActionExecutionContext<'a> {
     outputs: &'a [BuildArtifact],
     command_reports: &'a mut Vec<CommandExecutionReport>,
     cancellations: &'a CancellationContext<'a>,
+    /// Set when re-executing an action whose outputs were lost from the CAS, whose action cache
+    /// entry would refer to the lost outputs.
+    skip_action_cache: bool,
 }
 
 #[async_trait]
@@ -392,6 +408,19 @@ impl ActionExecutionCtx for BuckActionExecutionContext<'_> {
         &self.executor.mergebase
     }
 
+    fn predicted_on_critical_path(&self) -> bool {
+        match &self.executor.critical_path_history {
+            Some(history) => history
+                .predicted_duration(&CriticalPathActionId::new(
+                    &self.action.owner().to_string(),
+                    self.action.category().as_str(),
+                    self.action.identifier(),
+                ))
+                .is_some(),
+            None => false,
+        }
+    }
+
     fn prepare_action(
         &mut self,
         request: &CommandExecutionRequest,
@@ -407,6 +436,9 @@ impl ActionExecutionCtx for BuckActionExecutionContext<'_> {
         request: &CommandExecutionRequest,
         prepared_action: &PreparedAction,
     ) -> ControlFlow<CommandExecutionResult, CommandExecutionManager> {
+        if self.skip_action_cache {
+            return ControlFlow::Continue(manager);
+        }
         let action = self.target();
         self.executor
             .command_executor
@@ -578,89 +610,92 @@ impl BuckActionExecutor {
         inputs: IndexMap<ArtifactGroup, ArtifactGroupValues>,
         action: &RegisteredAction,
         cancellations: &CancellationContext<'_>,
+        skip_action_cache: bool,
     ) -> (
         Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError>,
         Vec<CommandExecutionReport>,
     ) {
         let mut command_reports = Vec::new();
 
-        let res = async {
-            let outputs = action.outputs();
-
-            let mut ctx = BuckActionExecutionContext {
-                executor: self,
-                action,
-                inputs,
-                outputs: outputs.as_ref(),
-                command_reports: &mut command_reports,
-                cancellations,
-            };
-
-            let (result, metadata) = match action.as_executable() {
-                ActionExecutable::Pristine(exe) => {
-                    ctx.cleanup_outputs().await?;
-                    exe.execute(&mut ctx).await?
-                }
-                ActionExecutable::Incremental(exe) => {
-                    // Let the action perform clean up in this case.
-                    exe.execute(&mut ctx).await?
-                }
-            };
-
-            // Check that all the outputs are the right output_type
-            for x in outputs.iter() {
-                let declared = x.output_type();
-                // FIXME: One day we should treat FileOrDirectory as a File, and soft_error if it is a directory
-                if declared != OutputType::FileOrDirectory {
-                    if let Some(t) = result.0.outputs.get(x.get_path()) {
-                        let real = if t.is_dir() {
-                            OutputType::Directory
-                        } else {
-                            OutputType::File
-                        };
-                        if real != declared {
-                            return Err(ExecuteError::WrongOutputType {
-                                path: self.command_executor.fs().resolve_build(x.get_path()),
-                                declared,
-                                real,
-                            });
+        let res = CpuSubsystem::ActionExecution
+            .attribute(async {
+                let outputs = action.outputs();
+
+                let mut ctx = BuckActionExecutionContext {
+                    executor: self,
+                    action,
+                    inputs,
+                    outputs: outputs.as_ref(),
+                    command_reports: &mut command_reports,
+                    cancellations,
+                    skip_action_cache,
+                };
+
+                let (result, metadata) = match action.as_executable() {
+                    ActionExecutable::Pristine(exe) => {
+                        ctx.cleanup_outputs().await?;
+                        exe.execute(&mut ctx).await?
+                    }
+                    ActionExecutable::Incremental(exe) => {
+                        // Let the action perform clean up in this case.
+                        exe.execute(&mut ctx).await?
+                    }
+                };
+
+                // Check that all the outputs are the right output_type
+                for x in outputs.iter() {
+                    let declared = x.output_type();
+                    // FIXME: One day we should treat FileOrDirectory as a File, and soft_error if it is a directory
+                    if declared != OutputType::FileOrDirectory {
+                        if let Some(t) = result.0.outputs.get(x.get_path()) {
+                            let real = if t.is_dir() {
+                                OutputType::Directory
+                            } else {
+                                OutputType::File
+                            };
+                            if real != declared {
+                                return Err(ExecuteError::WrongOutputType {
+                                    path: self.command_executor.fs().resolve_build(x.get_path()),
+                                    declared,
+                                    real,
+                                });
+                            }
                         }
                     }
                 }
-            }
 
-            // Check all the outputs were returned, and no additional outputs
-            // TODO (T122966509): Check projections here as well
-            if !outputs
-                .iter()
-                .map(|b| b.get_path())
-                .eq(result.0.outputs.keys())
-            {
-                let declared = outputs
+                // Check all the outputs were returned, and no additional outputs
+                // TODO (T122966509): Check projections here as well
+                if !outputs
                     .iter()
-                    .filter(|x| !result.0.outputs.contains_key(x.get_path()))
-                    .map(|x| self.command_executor.fs().resolve_build(x.get_path()))
-                    .collect();
-                let real = result
-                    .0
-                    .outputs
-                    .keys()
-                    .filter(|x| {
-                        // This is error message, linear search is fine.
-                        !outputs.iter().map(|b| b.get_path()).contains(x)
-                    })
-                    .map(|x| self.command_executor.fs().resolve_build(x))
-                    .collect::<Vec<_>>();
-                if real.is_empty() {
-                    Err(ExecuteError::MissingOutputs { declared })
+                    .map(|b| b.get_path())
+                    .eq(result.0.outputs.keys())
+                {
+                    let declared = outputs
+                        .iter()
+                        .filter(|x| !result.0.outputs.contains_key(x.get_path()))
+                        .map(|x| self.command_executor.fs().resolve_build(x.get_path()))
+                        .collect();
+                    let real = result
+                        .0
+                        .outputs
+                        .keys()
+                        .filter(|x| {
+                            // This is error message, linear search is fine.
+                            !outputs.iter().map(|b| b.get_path()).contains(x)
+                        })
+                        .map(|x| self.command_executor.fs().resolve_build(x))
+                        .collect::<Vec<_>>();
+                    if real.is_empty() {
+                        Err(ExecuteError::MissingOutputs { declared })
+                    } else {
+                        Err(ExecuteError::MismatchedOutputs { declared, real })
+                    }
                 } else {
-                    Err(ExecuteError::MismatchedOutputs { declared, real })
+                    Ok((result, metadata))
                 }
-            } else {
-                Ok((result, metadata))
-            }
-        }
-        .await;
+            })
+            .await;
 
         (res, command_reports)
     }
@@ -794,6 +829,7 @@ mod tests {
                 .build(),
             Default::default(),
             true,
+            None,
         );
 
         #[derive(Debug, Allocative)]
@@ -935,7 +971,12 @@ mod tests {
         );
         let res = with_dispatcher_async(
             EventDispatcher::null(),
-            executor.execute(Default::default(), &action, CancellationContext::testing()),
+            executor.execute(
+                Default::default(),
+                &action,
+                CancellationContext::testing(),
+                false,
+            ),
         )
         .await
         .0
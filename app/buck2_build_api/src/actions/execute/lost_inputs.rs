@@ -0,0 +1,253 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Re-execution of the actions whose outputs were lost from the CAS.
+//!
+//! The outputs of actions executed remotely are often only stored in the CAS, and expire from it
+//! when the daemon lives long enough. When an action requires such an input, the action producing
+//! it is executed again (skipping the action cache, whose entry refers to the lost outputs), and
+//! the action is retried. Producing actions may themselves have lost inputs, which are recreated
+//! the same way, up to `buck2.lost_inputs_max_reexecution_depth` levels.
+
+use std::collections::HashSet;
+
+use buck2_artifact::actions::key::ActionKey;
+use buck2_common::events::HasEvents;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_data::ToProtoMessage;
+use buck2_error::BuckErrorContext;
+use buck2_events::dispatch::span_async;
+use buck2_execute::execute::result::CommandExecutionReport;
+use buck2_execute::materialize::materializer::CasArtifactMissing;
+use buck2_futures::cancellation::CancellationContext;
+use dice::DiceComputations;
+use dupe::Dupe;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use indexmap::IndexMap;
+
+use crate::actions::artifact::get_artifact_fs::GetArtifactFs;
+use crate::actions::calculation::materialize_inputs;
+use crate::actions::calculation::ActionCalculation;
+use crate::actions::error::ActionError;
+use crate::actions::execute::action_executor::ActionExecutionMetadata;
+use crate::actions::execute::action_executor::ActionOutputs;
+use crate::actions::execute::action_executor::BuckActionExecutor;
+use crate::actions::execute::action_executor::HasActionExecutor;
+use crate::actions::execute::error::ExecuteError;
+use crate::actions::impls::run_action_knobs::HasRunActionKnobs;
+use crate::actions::RegisteredAction;
+use crate::artifact_groups::ArtifactGroup;
+use crate::artifact_groups::ArtifactGroupValues;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum LostInputsError {
+    #[error(
+        "Action `{0}` re-executed to recreate outputs lost from the CAS produced different outputs, it is not deterministic"
+    )]
+    NonDeterministic(String),
+}
+
+type ExecuteResult = (
+    Result<(ActionOutputs, ActionExecutionMetadata), ExecuteError>,
+    Vec<CommandExecutionReport>,
+);
+
+/// Execute the action, re-executing the actions producing its inputs when they are missing from
+/// the CAS.
+pub(crate) async fn execute_reexecuting_lost_inputs(
+    ctx: &mut DiceComputations<'_>,
+    cancellation: &CancellationContext<'_>,
+    executor: &BuckActionExecutor,
+    inputs: IndexMap<ArtifactGroup, ArtifactGroupValues>,
+    action: &RegisteredAction,
+) -> ExecuteResult {
+    let mut reexecuted = HashSet::new();
+    execute_at_depth(
+        ctx,
+        cancellation,
+        executor,
+        inputs,
+        action,
+        0,
+        &mut reexecuted,
+    )
+    .await
+}
+
+fn execute_at_depth<'a>(
+    ctx: &'a mut DiceComputations<'_>,
+    cancellation: &'a CancellationContext<'_>,
+    executor: &'a BuckActionExecutor,
+    inputs: IndexMap<ArtifactGroup, ArtifactGroupValues>,
+    action: &'a RegisteredAction,
+    depth: u32,
+    reexecuted: &'a mut HashSet<ActionKey>,
+) -> BoxFuture<'a, ExecuteResult> {
+    async move {
+        let max_depth = ctx
+            .per_transaction_data()
+            .get_run_action_knobs()
+            .lost_inputs_max_reexecution_depth;
+        let mut command_reports = Vec::new();
+        loop {
+            // Re-executed actions skip the action cache, which refers to their lost outputs.
+            let (result, reports) = executor
+                .execute(inputs.clone(), action, cancellation, depth > 0)
+                .await;
+            command_reports.extend(reports);
+
+            let missing = match &result {
+                Err(e) => missing_from_cas(e),
+                Ok(_) => None,
+            };
+            let Some(missing) = missing else {
+                return (result, command_reports);
+            };
+            if depth >= max_depth {
+                return (result, command_reports);
+            }
+            let producer = match ctx.get_artifact_fs().await {
+                Ok(fs) => producer_of(&inputs, &missing.path, &fs),
+                Err(_) => None,
+            };
+            // Each action is re-executed at most once, so that a non-deterministic failure to
+            // produce the input cannot loop.
+            let Some(producer) = producer.filter(|p| reexecuted.insert(p.dupe())) else {
+                return (result, command_reports);
+            };
+
+            if let Err(error) = reexecute(
+                ctx,
+                cancellation,
+                action,
+                &producer,
+                &missing.path,
+                depth + 1,
+                reexecuted,
+            )
+            .await
+            {
+                return (Err(ExecuteError::Error { error }), command_reports);
+            }
+        }
+    }
+    .boxed()
+}
+
+/// Execute the action producing `path`, and check that it produced the same outputs as before.
+async fn reexecute(
+    ctx: &mut DiceComputations<'_>,
+    cancellation: &CancellationContext<'_>,
+    consumer: &RegisteredAction,
+    producer: &ActionKey,
+    path: &ProjectRelativePath,
+    depth: u32,
+    reexecuted: &mut HashSet<ActionKey>,
+) -> buck2_error::Result<()> {
+    let action = ActionCalculation::get_action(ctx, producer).await?;
+    let name = buck2_data::ActionName {
+        category: action.category().as_str().to_owned(),
+        identifier: action.identifier().unwrap_or("").to_owned(),
+    };
+
+    let dispatcher = ctx.per_transaction_data().get_dispatcher().dupe();
+    dispatcher.instant_event(buck2_data::LostInputReexecution {
+        key: Some(action.key().as_proto()),
+        name: Some(name.clone()),
+        consumer: Some(consumer.key().as_proto()),
+        path: path.to_string(),
+        depth,
+    });
+    dispatcher.console_warning(format!(
+        "Re-executing action `{}` (depth {}): its output `{}` is missing from the CAS",
+        action, depth, path
+    ));
+
+    let result: buck2_error::Result<()> = async {
+        // The outputs the build already depends on, computed before they were lost.
+        let expected = ActionCalculation::build_action(ctx, action.key()).await?;
+        let inputs = materialize_inputs(ctx, &action).await?;
+        let executor = ctx.get_action_executor(action.execution_config()).await?;
+
+        let start_event = buck2_data::ActionExecutionStart {
+            key: Some(action.key().as_proto()),
+            kind: action.kind().into(),
+            name: Some(name.clone()),
+        };
+        let result = span_async(start_event, async {
+            let (result, _) = execute_at_depth(
+                ctx,
+                cancellation,
+                &executor,
+                inputs,
+                &action,
+                depth,
+                reexecuted,
+            )
+            .await;
+            let end = buck2_data::ActionExecutionEnd {
+                key: Some(action.key().as_proto()),
+                kind: action.kind().into(),
+                name: Some(name.clone()),
+                failed: result.is_err(),
+                execution_kind: result
+                    .as_ref()
+                    .map_or(buck2_data::ActionExecutionKind::NotSet, |(_, meta)| {
+                        meta.execution_kind.as_enum()
+                    }) as i32,
+                ..Default::default()
+            };
+            (result, Box::new(end))
+        })
+        .await;
+
+        match result {
+            Ok((outputs, _)) if outputs == expected => Ok(()),
+            Ok(_) => Err(LostInputsError::NonDeterministic(action.to_string()).into()),
+            Err(e) => Err(ActionError::new(e, name, action.key().as_proto(), None, None).into()),
+        }
+    }
+    .await;
+    result.with_buck_error_context(|| {
+        format!(
+            "Failed to re-execute action `{}` producing `{}`, which is missing from the CAS",
+            action, path
+        )
+    })
+}
+
+/// The CAS artifact whose absence failed the execution, if any.
+fn missing_from_cas(error: &ExecuteError) -> Option<std::sync::Arc<CasArtifactMissing>> {
+    match error {
+        ExecuteError::Error { error }
+        | ExecuteError::CommandExecutionError { error: Some(error) } => {
+            error.find_typed_context::<CasArtifactMissing>()
+        }
+        _ => None,
+    }
+}
+
+/// The action producing the input `path` is in, if it is an output of an action.
+fn producer_of(
+    inputs: &IndexMap<ArtifactGroup, ArtifactGroupValues>,
+    path: &ProjectRelativePath,
+    fs: &ArtifactFs,
+) -> Option<ActionKey> {
+    inputs
+        .values()
+        .flat_map(|values| values.iter())
+        .find_map(|(artifact, _)| {
+            let key = artifact.action_key()?;
+            let artifact_path = artifact.get_path().resolve(fs).ok()?;
+            path.starts_with(&artifact_path).then(|| key.dupe())
+        })
+}
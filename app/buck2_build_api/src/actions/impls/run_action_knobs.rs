@@ -7,9 +7,63 @@
  * of this source tree.
  */
 
+use std::str::FromStr;
+
 use dice::UserComputationData;
 use dupe::Dupe;
 
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum NetworkPolicyError {
+    #[error(
+        "Action declares `allow_network = True`, but network access is forbidden for all actions \
+        (per `buck2.network_policy = forbid`)"
+    )]
+    Forbidden,
+    #[error("Invalid network policy: `{0}`, expected `allow`, `deny` or `forbid`")]
+    Invalid(String),
+}
+
+/// Whether actions get network access, from `buck2.network_policy`. Run actions can declare
+/// whether they need the network with `allow_network`; this decides what happens to actions
+/// that don't, and whether the declaration is honored.
+#[derive(Copy, Clone, Dupe, Debug, Default, PartialEq, Eq)]
+pub enum NetworkPolicy {
+    /// Actions have network access unless they declare `allow_network = False`.
+    #[default]
+    Allow,
+    /// Actions have no network access unless they declare `allow_network = True`.
+    Deny,
+    /// No action has network access, and declaring `allow_network = True` is an error.
+    Forbid,
+}
+
+impl NetworkPolicy {
+    /// Whether an action which declared `allow_network` (if at all) gets network access.
+    pub fn resolve(self, allow_network: Option<bool>) -> buck2_error::Result<bool> {
+        match (self, allow_network) {
+            (_, Some(false)) => Ok(false),
+            (NetworkPolicy::Forbid, Some(true)) => Err(NetworkPolicyError::Forbidden.into()),
+            (_, Some(true)) => Ok(true),
+            (NetworkPolicy::Allow, None) => Ok(true),
+            (NetworkPolicy::Deny | NetworkPolicy::Forbid, None) => Ok(false),
+        }
+    }
+}
+
+impl FromStr for NetworkPolicy {
+    type Err = buck2_error::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(NetworkPolicy::Allow),
+            "deny" => Ok(NetworkPolicy::Deny),
+            "forbid" => Ok(NetworkPolicy::Forbid),
+            _ => Err(NetworkPolicyError::Invalid(s.to_owned()).into()),
+        }
+    }
+}
+
 /// Knobs controlling how RunAction works.
 #[derive(Copy, Clone, Dupe, Default)]
 pub struct RunActionKnobs {
@@ -24,6 +78,17 @@ pub struct RunActionKnobs {
     /// for network actions (download_file, cas_artifact). Used to support offline
     /// builds.
     pub use_network_action_output_cache: bool,
+
+    /// Whether run actions get network access.
+    pub network_policy: NetworkPolicy,
+
+    /// Report details of one in this many remote dep file cache entries which don't match
+    /// the inputs observed locally. Zero disables reporting.
+    pub remote_dep_file_divergence_sampling: u32,
+
+    /// When an input of an action is missing from the CAS, how many levels of producing actions
+    /// may be re-executed to recreate it, instead of failing. Zero disables re-execution.
+    pub lost_inputs_max_reexecution_depth: u32,
 }
 
 pub trait HasRunActionKnobs {
@@ -44,3 +109,25 @@ impl HasRunActionKnobs for UserComputationData {
             .expect("RunActionKnobs should be set")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_policy() {
+        assert_eq!(
+            NetworkPolicy::from_str("deny").unwrap(),
+            NetworkPolicy::Deny
+        );
+        assert!(NetworkPolicy::from_str("off").is_err());
+
+        assert!(NetworkPolicy::Allow.resolve(None).unwrap());
+        assert!(!NetworkPolicy::Allow.resolve(Some(false)).unwrap());
+        assert!(!NetworkPolicy::Deny.resolve(None).unwrap());
+        assert!(NetworkPolicy::Deny.resolve(Some(true)).unwrap());
+        assert!(!NetworkPolicy::Forbid.resolve(None).unwrap());
+        assert!(!NetworkPolicy::Forbid.resolve(Some(false)).unwrap());
+        assert!(NetworkPolicy::Forbid.resolve(Some(true)).is_err());
+    }
+}
@@ -15,9 +15,11 @@ use buck2_artifact::artifact::artifact_type::Artifact;
 use buck2_core::provider::label::ConfiguredProvidersLabel;
 use buck2_interpreter::starlark_profiler::data::StarlarkProfileDataAndStats;
 
+use crate::analysis::annotations::TargetAnnotation;
 use crate::analysis::registry::RecordedAnalysisValues;
 use crate::artifact_groups::promise::PromiseArtifactId;
 
+pub mod annotations;
 // TODO(@wendyy) move into `buck2_node`
 pub mod anon_promises_dyn;
 // TODO(@wendyy) move into `buck2_interpreter_for_build`
@@ -45,6 +47,8 @@ pub struct AnalysisResult {
     pub num_declared_artifacts: u64,
     /// `None` means there are no `ValidationInfo` providers in transitive dependencies.
     pub validations: Option<TransitiveValidations>,
+    /// Annotations the rule attached to the target with `ctx.annotate`.
+    annotations: Arc<[TargetAnnotation]>,
 }
 
 impl AnalysisResult {
@@ -64,9 +68,21 @@ impl AnalysisResult {
             num_declared_actions,
             num_declared_artifacts,
             validations,
+            annotations: Arc::new([]),
         }
     }
 
+    pub fn with_annotations(self, annotations: Vec<TargetAnnotation>) -> Self {
+        Self {
+            annotations: annotations.into(),
+            ..self
+        }
+    }
+
+    pub fn annotations(&self) -> &Arc<[TargetAnnotation]> {
+        &self.annotations
+    }
+
     pub fn providers(&self) -> buck2_error::Result<FrozenProviderCollectionValueRef<'_>> {
         self.analysis_values.provider_collection()
     }
@@ -0,0 +1,23 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use allocative::Allocative;
+use serde::Serialize;
+
+/// A non-fatal message a rule attached to the target it analyzed with `ctx.annotate`, e.g. that
+/// the target uses a deprecated attribute. Unlike soft errors, which are about the daemon,
+/// annotations are about targets and are reported to whoever builds them.
+///
+/// DO NOT UPDATE WITHOUT UPDATING `docs/users/build_observability/build_report.md`!
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Allocative, Serialize)]
+pub struct TargetAnnotation {
+    /// Free-form category chosen by the rule, e.g. `deprecation`. Empty if none was given.
+    pub category: String,
+    pub message: String,
+}
@@ -12,6 +12,7 @@ use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 
+use allocative::Allocative;
 use async_trait::async_trait;
 use buck2_core::configuration::compatibility::MaybeCompatible;
 use buck2_core::configuration::pair::ConfigurationNoExec;
@@ -120,3 +121,25 @@ impl RuleAnalysisCalculation for DiceComputations<'_> {
         Ok(analysis.map(|x| x.validations))
     }
 }
+
+/// Context of the analysis errors caused by the failed analysis of a dependency, so that targets
+/// which were not analyzed because of a dependency can be told apart from targets which failed.
+/// It is not displayed: the error already says which target failed analysis.
+#[derive(Debug, Allocative, Eq, PartialEq, derive_more::Display)]
+#[display("Analysis of dependency `{}` failed", dep)]
+pub struct DepAnalysisFailed {
+    pub dep: ConfiguredTargetLabel,
+}
+
+impl buck2_error::TypedContext for DepAnalysisFailed {
+    fn eq(&self, other: &dyn buck2_error::TypedContext) -> bool {
+        match (other as &dyn std::any::Any).downcast_ref::<Self>() {
+            Some(v) => self == v,
+            None => false,
+        }
+    }
+
+    fn should_display(&self) -> bool {
+        false
+    }
+}
@@ -7,15 +7,21 @@
  * of this source tree.
  */
 
+use std::collections::HashMap;
 use std::future::Future;
 use std::io::Write;
 use std::pin::Pin;
+use std::sync::Arc;
 
 use buck2_core::category::Category;
 use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+use buck2_directory::directory::directory_selector::DirectorySelector;
 use buck2_util::late_binding::LateBinding;
 use dice::DiceTransaction;
 
+use crate::actions::RegisteredAction;
+use crate::artifact_groups::ArtifactGroup;
+
 /// Implementation of `audit dep-files`.
 pub static AUDIT_DEP_FILES: LateBinding<
     for<'a> fn(
@@ -26,3 +32,27 @@ pub static AUDIT_DEP_FILES: LateBinding<
         &'a mut (dyn Write + Send),
     ) -> Pin<Box<dyn Future<Output = buck2_error::Result<()>> + Send + 'a>>,
 > = LateBinding::new("AUDIT_DEP_FILES");
+
+/// The inputs of an action that produces dep files, see `Action::dep_file_inputs`.
+pub struct DepFileInputs {
+    /// Inputs that are always used by the action.
+    pub untagged: Vec<ArtifactGroup>,
+    /// Inputs that are only used if they are listed in the dep file with this label.
+    pub tagged: Vec<(Arc<str>, Vec<ArtifactGroup>)>,
+}
+
+/// Reads the dep files produced by the last execution of an action in this daemon, returning the
+/// paths listed in each of them by label. Returns `None` if they are not available, e.g. because
+/// the action has not run since the daemon started.
+pub static READ_ACTION_DEP_FILES: LateBinding<
+    for<'a> fn(
+        ctx: &'a DiceTransaction,
+        action: &'a RegisteredAction,
+    ) -> Pin<
+        Box<
+            dyn Future<Output = buck2_error::Result<Option<HashMap<Arc<str>, DirectorySelector>>>>
+                + Send
+                + 'a,
+        >,
+    >,
+> = LateBinding::new("READ_ACTION_DEP_FILES");
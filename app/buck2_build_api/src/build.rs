@@ -8,18 +8,25 @@
  */
 
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fmt::Formatter;
+use std::fmt::Write;
 use std::sync::Arc;
 
 use allocative::Allocative;
 use buck2_core::configuration::compatibility::MaybeCompatible;
 use buck2_core::execution_types::executor_config::PathSeparatorKind;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
 use buck2_core::provider::label::ConfiguredProvidersLabel;
 use buck2_core::provider::label::ProvidersLabel;
+use buck2_core::target::label::label::TargetLabel;
+use buck2_data::ToProtoMessage;
 use buck2_error::BuckErrorContext;
 use buck2_events::dispatch::console_message;
+use buck2_events::dispatch::instant_event;
 use buck2_execute::artifact::fs::ExecutorFs;
 use dice::LinearRecomputeDiceComputations;
 use dice::UserComputationData;
@@ -32,11 +39,14 @@ use futures::stream::Stream;
 use futures::stream::StreamExt;
 use futures::FutureExt;
 use itertools::Itertools;
+use serde::Serialize;
 use tokio::sync::Mutex;
 
 use crate::actions::artifact::get_artifact_fs::GetArtifactFs;
 use crate::actions::calculation::get_target_rule_type_name;
 use crate::actions::calculation::BuildKey;
+use crate::analysis::annotations::TargetAnnotation;
+use crate::analysis::calculation::DepAnalysisFailed;
 use crate::analysis::calculation::RuleAnalysisCalculation;
 use crate::artifact_groups::calculation::EnsureTransitiveSetProjectionKey;
 use crate::artifact_groups::ArtifactGroup;
@@ -74,6 +84,8 @@ pub struct ConfiguredBuildTargetResultGen<T> {
     pub target_rule_type_name: Option<String>,
     pub configured_graph_size: Option<buck2_error::Result<MaybeCompatible<u64>>>,
     pub errors: Vec<buck2_error::Error>,
+    /// Annotations the rule attached to the target with `ctx.annotate`.
+    pub annotations: Arc<[TargetAnnotation]>,
 }
 
 pub type ConfiguredBuildTargetResult =
@@ -84,14 +96,54 @@ pub struct BuildTargetResult {
     /// Errors that could not be associated with a specific configured target. These errors may be
     /// associated with a providers label, or might not be associated with any target at all.
     pub other_errors: BTreeMap<Option<ProvidersLabel>, Vec<buck2_error::Error>>,
+    /// Requested targets that do not exist, skipped because of `--skip-missing-targets`.
+    pub skipped_missing: BTreeSet<TargetLabel>,
     pub build_failed: bool,
 }
 
+/// Why a requested target was not built.
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The target does not exist and `--skip-missing-targets` was passed.
+    Missing,
+    /// The target is incompatible with the target platform.
+    Incompatible,
+}
+
+/// A requested target that was skipped rather than built.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct SkippedTarget {
+    /// The target label, including the configuration if the target was configured.
+    pub target: String,
+    pub reason: SkipReason,
+}
+
+impl SkippedTarget {
+    /// Console summary of all skipped targets, or `None` if nothing was skipped.
+    pub fn summary_message(skipped: &[SkippedTarget]) -> Option<String> {
+        if skipped.is_empty() {
+            return None;
+        }
+        let mut message = String::new();
+        writeln!(message, "Skipped {} targets:", skipped.len()).unwrap();
+        for s in skipped {
+            let reason = match s.reason {
+                SkipReason::Missing => "missing",
+                SkipReason::Incompatible => "incompatible",
+            };
+            writeln!(message, "  {}: {}", s.target, reason).unwrap();
+        }
+        Some(message)
+    }
+}
+
 impl BuildTargetResult {
     pub fn new() -> Self {
         Self {
             configured: BTreeMap::new(),
             other_errors: BTreeMap::new(),
+            skipped_missing: BTreeSet::new(),
             build_failed: false,
         }
     }
@@ -99,6 +151,54 @@ impl BuildTargetResult {
     pub fn extend(&mut self, other: BuildTargetResult) {
         self.configured.extend(other.configured);
         self.other_errors.extend(other.other_errors);
+        self.skipped_missing.extend(other.skipped_missing);
+    }
+
+    /// All requested targets that were skipped, sorted by target.
+    pub fn skipped_targets(&self) -> Vec<SkippedTarget> {
+        let missing = self.skipped_missing.iter().map(|t| SkippedTarget {
+            target: t.to_string(),
+            reason: SkipReason::Missing,
+        });
+        let incompatible = self
+            .configured
+            .iter()
+            .filter(|(_, result)| result.is_none())
+            .map(|(label, _)| SkippedTarget {
+                target: label.to_string(),
+                reason: SkipReason::Incompatible,
+            });
+        missing.chain(incompatible).sorted().collect()
+    }
+
+    /// Console summary of the annotations rules attached to the built targets, grouped by
+    /// configured target, or `None` if there are none.
+    pub fn annotations_message(&self) -> Option<String> {
+        let mut seen = HashSet::new();
+        let mut targets = 0;
+        let mut body = String::new();
+        for (label, result) in &self.configured {
+            let Some(result) = result else {
+                continue;
+            };
+            // Annotations are per target, not per requested subtarget.
+            if result.annotations.is_empty() || !seen.insert(label.target()) {
+                continue;
+            }
+            targets += 1;
+            writeln!(body, "  {}:", label.target()).unwrap();
+            for annotation in result.annotations.iter() {
+                if annotation.category.is_empty() {
+                    writeln!(body, "    {}", annotation.message).unwrap();
+                } else {
+                    writeln!(body, "    [{}] {}", annotation.category, annotation.message).unwrap();
+                }
+            }
+        }
+        if targets == 0 {
+            return None;
+        }
+        Some(format!("Annotations on {} targets:\n{}", targets, body))
     }
 
     pub fn is_empty(&self) -> bool {
@@ -115,6 +215,7 @@ impl BuildTargetResult {
             Option<ConfiguredBuildTargetResultGen<(usize, buck2_error::Result<ProviderArtifacts>)>>,
         >::new();
         let mut other_errors = BTreeMap::<_, Vec<_>>::new();
+        let mut skipped_missing = BTreeSet::new();
         let mut build_failed = false;
 
         while let Some(event) = stream.next().await {
@@ -125,6 +226,10 @@ impl BuildTargetResult {
                     build_failed = true;
                     continue;
                 }
+                BuildEvent::SkippedMissing { label } => {
+                    skipped_missing.insert(label);
+                    continue;
+                }
             };
             match variant {
                 ConfiguredBuildEventVariant::SkippedIncompatible => {
@@ -133,6 +238,7 @@ impl BuildTargetResult {
                 ConfiguredBuildEventVariant::Prepared {
                     run_args,
                     target_rule_type_name,
+                    annotations,
                 } => {
                     res.entry((*label).dupe())
                         .or_insert(Some(ConfiguredBuildTargetResultGen {
@@ -141,6 +247,7 @@ impl BuildTargetResult {
                             target_rule_type_name: Some(target_rule_type_name),
                             configured_graph_size: None,
                             errors: Vec::new(),
+                            annotations,
                         }));
                 }
                 ConfiguredBuildEventVariant::Execution(execution_variant) => {
@@ -190,6 +297,7 @@ impl BuildTargetResult {
                             target_rule_type_name: None,
                             configured_graph_size: None,
                             errors: Vec::new(),
+                            annotations: Arc::new([]),
                         }))
                         .as_mut()
                         .unwrap()
@@ -214,6 +322,7 @@ impl BuildTargetResult {
                         target_rule_type_name,
                         configured_graph_size,
                         errors,
+                        annotations,
                     } = result;
 
                     // No need for a stable sort: the indices are unique (see below).
@@ -233,6 +342,7 @@ impl BuildTargetResult {
                         target_rule_type_name,
                         configured_graph_size,
                         errors,
+                        annotations,
                     }
                 });
 
@@ -243,6 +353,7 @@ impl BuildTargetResult {
         Ok(Self {
             configured: res,
             other_errors,
+            skipped_missing,
             build_failed,
         })
     }
@@ -264,6 +375,7 @@ pub enum ConfiguredBuildEventVariant {
     Prepared {
         run_args: Option<Vec<String>>,
         target_rule_type_name: String,
+        annotations: Arc<[TargetAnnotation]>,
     },
     Execution(ConfiguredBuildEventExecutionVariant),
     GraphSize {
@@ -288,6 +400,10 @@ pub enum BuildEvent {
         label: Option<ProvidersLabel>,
         err: buck2_error::Error,
     },
+    /// A requested target that does not exist, skipped because of `--skip-missing-targets`.
+    SkippedMissing {
+        label: TargetLabel,
+    },
 }
 
 impl BuildEvent {
@@ -316,7 +432,7 @@ pub async fn build_configured_label<'a>(
     opts: BuildConfiguredLabelOptions,
 ) -> BoxStream<'a, ConfiguredBuildEvent> {
     let providers_label = Arc::new(providers_label);
-    build_configured_label_inner(
+    let stream = build_configured_label_inner(
         ctx,
         materialization,
         providers_label.dupe(),
@@ -326,11 +442,127 @@ pub async fn build_configured_label<'a>(
     .await
     .unwrap_or_else(|e| {
         futures::stream::once(futures::future::ready(ConfiguredBuildEvent {
-            label: providers_label,
+            label: providers_label.dupe(),
             variant: ConfiguredBuildEventVariant::Error { err: e.into() },
         }))
         .boxed()
-    })
+    });
+    // Outputs are only reported for convenience, don't fail the build over them.
+    let artifact_fs = ctx.get().get_artifact_fs().await.ok();
+    report_target_build_end(providers_label, artifact_fs, stream)
+}
+
+#[derive(Default)]
+struct TargetBuildEndState {
+    status: buck2_data::TargetBuildStatus,
+    outputs: Vec<String>,
+}
+
+/// Emits a `TargetBuildEnd` event once the stream of events for a configured target is exhausted,
+/// so subscribers learn about each target as soon as it is done rather than at the end of the build.
+fn report_target_build_end<'a>(
+    providers_label: Arc<ConfiguredProvidersLabel>,
+    artifact_fs: Option<ArtifactFs>,
+    stream: BoxStream<'a, ConfiguredBuildEvent>,
+) -> BoxStream<'a, ConfiguredBuildEvent> {
+    let target = providers_label.target().as_proto();
+    let state = Arc::new(std::sync::Mutex::new(TargetBuildEndState {
+        status: buck2_data::TargetBuildStatus::Success,
+        outputs: Vec::new(),
+    }));
+    let end = {
+        let state = state.dupe();
+        let target = target.clone();
+        futures::stream::once(async move {
+            let state = std::mem::take(&mut *state.lock().unwrap());
+            instant_event(buck2_data::TargetBuildEnd {
+                target: Some(target),
+                status: state.status as i32,
+                providers_label: providers_label.unconfigured().to_string(),
+                outputs: state.outputs,
+            });
+        })
+        .filter_map(|()| futures::future::ready(None))
+    };
+    stream
+        .inspect(move |event| {
+            let mut state = state.lock().unwrap();
+            let new_status = match &event.variant {
+                ConfiguredBuildEventVariant::SkippedIncompatible => {
+                    buck2_data::TargetBuildStatus::Skipped
+                }
+                ConfiguredBuildEventVariant::Error { err }
+                | ConfiguredBuildEventVariant::Execution(
+                    ConfiguredBuildEventExecutionVariant::BuildOutput {
+                        output: Err(err), ..
+                    }
+                    | ConfiguredBuildEventExecutionVariant::Validation { result: Err(err) },
+                ) => failure_status(err, &target),
+                ConfiguredBuildEventVariant::Execution(
+                    ConfiguredBuildEventExecutionVariant::BuildOutput {
+                        output: Ok(output), ..
+                    },
+                ) => {
+                    // Same as the outputs of `BuildTarget` in the build response.
+                    if let (Some(artifact_fs), false) = (
+                        &artifact_fs,
+                        matches!(output.provider_type, BuildProviderType::DefaultOther),
+                    ) {
+                        for (artifact, _value) in output.values.iter() {
+                            if let Ok(path) = artifact.resolve_path(artifact_fs) {
+                                let path = path.to_string();
+                                if !state.outputs.contains(&path) {
+                                    state.outputs.push(path);
+                                }
+                            }
+                        }
+                    }
+                    return;
+                }
+                _ => return,
+            };
+            if status_rank(new_status) > status_rank(state.status) {
+                state.status = new_status;
+            }
+        })
+        .chain(end)
+        .boxed()
+}
+
+/// A target whose errors come from the analysis or the actions of other targets was not attempted
+/// because a dependency failed. Anything else counts against the target itself, including the
+/// failed actions of anonymous targets and BXL, which are not owned by a dependency of the target.
+fn failure_status(
+    err: &buck2_error::Error,
+    target: &buck2_data::ConfiguredTargetLabel,
+) -> buck2_data::TargetBuildStatus {
+    use buck2_data::action_key::Owner;
+
+    if err.find_typed_context::<DepAnalysisFailed>().is_some() {
+        return buck2_data::TargetBuildStatus::DepFailed;
+    }
+
+    let owner = err
+        .action_error()
+        .and_then(|e| e.key.as_ref())
+        .and_then(|k| k.owner.as_ref());
+    match owner {
+        Some(
+            Owner::TargetLabel(owner)
+            | Owner::TestTargetLabel(owner)
+            | Owner::LocalResourceSetup(owner),
+        ) if owner != target => buck2_data::TargetBuildStatus::DepFailed,
+        _ => buck2_data::TargetBuildStatus::Failure,
+    }
+}
+
+fn status_rank(status: buck2_data::TargetBuildStatus) -> u8 {
+    match status {
+        buck2_data::TargetBuildStatus::Unknown | buck2_data::TargetBuildStatus::Success => 0,
+        buck2_data::TargetBuildStatus::Skipped => 1,
+        buck2_data::TargetBuildStatus::DepFailed => 2,
+        buck2_data::TargetBuildStatus::Failure => 3,
+    }
 }
 
 async fn build_configured_label_inner<'a>(
@@ -342,7 +574,7 @@ async fn build_configured_label_inner<'a>(
 ) -> buck2_error::Result<BoxStream<'a, ConfiguredBuildEvent>> {
     let artifact_fs = ctx.get().get_artifact_fs().await?;
 
-    let (outputs, run_args, target_rule_type_name) = {
+    let (outputs, run_args, target_rule_type_name, annotations) = {
         // A couple of these objects aren't Send and so scope them here so async transform doesn't get concerned.
         let providers = match ctx.get().get_providers(providers_label.as_ref()).await? {
             MaybeCompatible::Incompatible(reason) => {
@@ -424,7 +656,17 @@ async fn build_configured_label_inner<'a>(
         let target_rule_type_name =
             get_target_rule_type_name(&mut ctx.get(), providers_label.target()).await?;
 
-        (outputs, run_args, target_rule_type_name)
+        // Already computed to get the providers above.
+        let annotations: Arc<[TargetAnnotation]> = match ctx
+            .get()
+            .get_analysis_result(providers_label.target())
+            .await?
+        {
+            MaybeCompatible::Compatible(analysis) => analysis.annotations().dupe(),
+            MaybeCompatible::Incompatible(_) => Arc::new([]),
+        };
+
+        (outputs, run_args, target_rule_type_name, annotations)
     };
 
     if let Some(signals) = ctx
@@ -519,6 +761,7 @@ async fn build_configured_label_inner<'a>(
         variant: ConfiguredBuildEventVariant::Prepared {
             run_args,
             target_rule_type_name,
+            annotations,
         },
     }))
     .chain(outputs);
@@ -586,3 +829,141 @@ impl HasCreateUnhashedSymlinkLock for UserComputationData {
             .dupe()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::configuration::data::ConfigurationData;
+    use buck2_core::provider::label::ProvidersName;
+    use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+    use buck2_data::action_key::Owner;
+    use buck2_data::TargetBuildStatus;
+
+    use super::*;
+
+    fn label(target: &str) -> ConfiguredTargetLabel {
+        ConfiguredTargetLabel::testing_parse(target, ConfigurationData::testing_new())
+    }
+
+    fn action_error(owner: Owner) -> buck2_error::Error {
+        buck2_error::Error::new(
+            "action failed".to_owned(),
+            None,
+            Some(buck2_data::ActionError {
+                key: Some(buck2_data::ActionKey {
+                    owner: Some(owner),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        )
+    }
+
+    #[test]
+    fn test_failure_status() {
+        let target = label("cell//pkg:target");
+        let dep = label("cell//pkg:dep");
+        let target_proto = target.as_proto();
+
+        assert_eq!(
+            failure_status(
+                &action_error(Owner::TargetLabel(target.as_proto())),
+                &target_proto
+            ),
+            TargetBuildStatus::Failure
+        );
+        assert_eq!(
+            failure_status(
+                &action_error(Owner::TargetLabel(dep.as_proto())),
+                &target_proto
+            ),
+            TargetBuildStatus::DepFailed
+        );
+        assert_eq!(
+            failure_status(
+                &action_error(Owner::TestTargetLabel(dep.as_proto())),
+                &target_proto
+            ),
+            TargetBuildStatus::DepFailed
+        );
+        assert_eq!(
+            failure_status(
+                &action_error(Owner::AnonTarget(Default::default())),
+                &target_proto
+            ),
+            TargetBuildStatus::Failure
+        );
+        assert_eq!(
+            failure_status(
+                &action_error(Owner::BxlKey(Default::default())),
+                &target_proto
+            ),
+            TargetBuildStatus::Failure
+        );
+
+        let analysis_error = buck2_error::Error::new("analysis failed".to_owned(), None, None);
+        assert_eq!(
+            failure_status(&analysis_error, &target_proto),
+            TargetBuildStatus::Failure
+        );
+        assert_eq!(
+            failure_status(
+                &analysis_error.context(DepAnalysisFailed { dep: dep.dupe() }),
+                &target_proto
+            ),
+            TargetBuildStatus::DepFailed
+        );
+    }
+
+    #[test]
+    fn test_status_rank() {
+        let statuses = [
+            TargetBuildStatus::Success,
+            TargetBuildStatus::Skipped,
+            TargetBuildStatus::DepFailed,
+            TargetBuildStatus::Failure,
+        ];
+        for w in statuses.windows(2) {
+            assert!(status_rank(w[0]) < status_rank(w[1]), "{:?}", w);
+        }
+        assert_eq!(
+            status_rank(TargetBuildStatus::Unknown),
+            status_rank(TargetBuildStatus::Success)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_collect_stream_isolates_failed_targets() -> buck2_error::Result<()> {
+        let failed =
+            ConfiguredProvidersLabel::new(label("cell//pkg:failed"), ProvidersName::Default);
+        let sibling =
+            ConfiguredProvidersLabel::new(label("cell//pkg:sibling"), ProvidersName::Default);
+        let events = || {
+            futures::stream::iter([
+                BuildEvent::new_configured(
+                    failed.dupe(),
+                    ConfiguredBuildEventVariant::Error {
+                        err: buck2_error::Error::new("analysis failed".to_owned(), None, None),
+                    },
+                ),
+                BuildEvent::new_configured(
+                    sibling.dupe(),
+                    ConfiguredBuildEventVariant::Prepared {
+                        run_args: None,
+                        target_rule_type_name: "rule".to_owned(),
+                        annotations: Arc::new([]),
+                    },
+                ),
+            ])
+        };
+
+        let res = BuildTargetResult::collect_stream(events(), false).await?;
+        assert!(res.build_failed);
+        assert_eq!(1, res.configured[&failed].as_ref().unwrap().errors.len());
+        assert!(res.configured[&sibling].as_ref().unwrap().errors.is_empty());
+
+        let res = BuildTargetResult::collect_stream(events(), true).await?;
+        assert!(res.build_failed);
+        assert!(!res.configured.contains_key(&sibling));
+        Ok(())
+    }
+}
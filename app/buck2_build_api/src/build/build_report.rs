@@ -49,9 +49,11 @@ use itertools::Itertools;
 use serde::Serialize;
 use starlark_map::small_set::SmallSet;
 
+use crate::analysis::annotations::TargetAnnotation;
 use crate::build::action_error::BuildReportActionError;
 use crate::build::BuildProviderType;
 use crate::build::ConfiguredBuildTargetResult;
+use crate::build::SkippedTarget;
 
 #[derive(Debug, Serialize)]
 #[allow(clippy::upper_case_acronyms)] // We care about how they serialise
@@ -79,6 +81,8 @@ pub struct BuildReport {
     project_root: AbsNormPathBuf,
     truncated: bool,
     strings: BTreeMap<String, String>,
+    /// Requested targets that were not built because they are missing or incompatible
+    skipped: Vec<SkippedTarget>,
 }
 
 /// The fields that stored in the unconfigured `BuildReportEntry` for buck1 backcompat.
@@ -108,6 +112,9 @@ struct MaybeConfiguredBuildReportEntry {
 pub(crate) struct ConfiguredBuildReportEntry {
     /// A list of errors that occurred while building this target
     errors: Vec<BuildReportError>,
+    /// Non-fatal messages the rule attached to this target with `ctx.annotate`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    annotations: Vec<TargetAnnotation>,
     #[serde(flatten)]
     inner: MaybeConfiguredBuildReportEntry,
 }
@@ -192,6 +199,7 @@ impl<'a> BuildReportCollector<'a> {
         include_package_project_relative_paths: bool,
         configured: &BTreeMap<ConfiguredProvidersLabel, Option<ConfiguredBuildTargetResult>>,
         other_errors: &BTreeMap<Option<ProvidersLabel>, Vec<buck2_error::Error>>,
+        skipped: Vec<SkippedTarget>,
     ) -> BuildReport {
         let mut this: BuildReportCollector<'_> = Self {
             artifact_fs,
@@ -250,6 +258,7 @@ impl<'a> BuildReportCollector<'a> {
             // Setting this to false since we don't currently truncate buck2's build report.
             truncated: false,
             strings: this.strings,
+            skipped,
         }
     }
 
@@ -406,6 +415,13 @@ impl<'a> BuildReportCollector<'a> {
 
             errors.extend(result.errors.iter().cloned());
 
+            // All subtargets of a target share its annotations.
+            if configured_report.annotations.is_empty() {
+                configured_report
+                    .annotations
+                    .extend(result.annotations.iter().cloned());
+            }
+
             if let Some(Ok(MaybeCompatible::Compatible(configured_graph_size))) =
                 result.configured_graph_size
             {
@@ -583,6 +599,7 @@ pub fn generate_build_report(
     trace_id: &TraceId,
     configured: &BTreeMap<ConfiguredProvidersLabel, Option<ConfiguredBuildTargetResult>>,
     other_errors: &BTreeMap<Option<ProvidersLabel>, Vec<buck2_error::Error>>,
+    skipped: Vec<SkippedTarget>,
 ) -> Result<Option<String>, buck2_error::Error> {
     let build_report = BuildReportCollector::convert(
         trace_id,
@@ -595,6 +612,7 @@ pub fn generate_build_report(
         opts.unstable_include_package_project_relative_paths,
         configured,
         other_errors,
+        skipped,
     );
 
     let mut serialized_build_report = None;
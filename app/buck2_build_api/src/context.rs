@@ -14,6 +14,7 @@ use std::sync::Arc;
 use allocative::Allocative;
 use async_trait::async_trait;
 use buck2_core::fs::buck_out_path::BuckOutPathResolver;
+use buck2_core::fs::output_path_scheme::OutputPathScheme;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use derive_more::Display;
 use dice::DiceComputations;
@@ -30,12 +31,21 @@ pub trait SetBuildContextData {
     fn set_buck_out_path(
         &mut self,
         path: Option<ProjectRelativePathBuf>,
+    ) -> buck2_error::Result<()> {
+        self.set_build_context_data(path, OutputPathScheme::default())
+    }
+
+    fn set_build_context_data(
+        &mut self,
+        path: Option<ProjectRelativePathBuf>,
+        output_path_scheme: OutputPathScheme,
     ) -> buck2_error::Result<()>;
 }
 
 #[derive(PartialEq, Eq, Allocative)]
 pub struct BuildData {
     buck_out_path: ProjectRelativePathBuf,
+    output_path_scheme: Arc<OutputPathScheme>,
 }
 
 #[derive(Clone, Dupe, Display, Debug, Eq, Hash, PartialEq, Allocative)]
@@ -54,14 +64,18 @@ impl InjectedKey for BuildDataKey {
 impl HasBuildContextData for DiceComputations<'_> {
     async fn get_buck_out_path(&mut self) -> buck2_error::Result<BuckOutPathResolver> {
         let data = self.compute(&BuildDataKey).await?;
-        Ok(BuckOutPathResolver::new(data.buck_out_path.to_buf()))
+        Ok(BuckOutPathResolver::with_scheme(
+            data.buck_out_path.to_buf(),
+            data.output_path_scheme.dupe(),
+        ))
     }
 }
 
 impl SetBuildContextData for DiceTransactionUpdater {
-    fn set_buck_out_path(
+    fn set_build_context_data(
         &mut self,
         path: Option<ProjectRelativePathBuf>,
+        output_path_scheme: OutputPathScheme,
     ) -> buck2_error::Result<()> {
         Ok(self.changed_to(vec![(
             BuildDataKey,
@@ -69,6 +83,7 @@ impl SetBuildContextData for DiceTransactionUpdater {
                 buck_out_path: path.unwrap_or_else(|| {
                     ProjectRelativePathBuf::unchecked_new("buck-out/v2".to_owned())
                 }),
+                output_path_scheme: Arc::new(output_path_scheme),
             }),
         )])?)
     }
@@ -32,6 +32,7 @@ use starlark::environment::MethodsStatic;
 use starlark::eval::Evaluator;
 use starlark::typing::Ty;
 use starlark::values::none::NoneOr;
+use starlark::values::none::NoneType;
 use starlark::values::starlark_value;
 use starlark::values::starlark_value_as_type::StarlarkValueAsType;
 use starlark::values::structs::StructRef;
@@ -48,6 +49,7 @@ use starlark::values::ValueOfUnchecked;
 use starlark::values::ValueTyped;
 use starlark::values::ValueTypedComplex;
 
+use crate::analysis::annotations::TargetAnnotation;
 use crate::analysis::registry::AnalysisRegistry;
 use crate::deferred::calculation::GET_PROMISED_ARTIFACT;
 use crate::interpreter::rule_defs::plugins::AnalysisPlugins;
@@ -171,6 +173,8 @@ pub struct AnalysisContext<'v> {
     /// Only `None` when running a `dynamic_output` action from Bxl.
     label: Option<ValueTyped<'v, StarlarkConfiguredProvidersLabel>>,
     plugins: Option<ValueTypedComplex<'v, AnalysisPlugins<'v>>>,
+    #[trace(unsafe_ignore)]
+    annotations: RefCell<Vec<TargetAnnotation>>,
 }
 
 impl<'v> Display for AnalysisContext<'v> {
@@ -206,6 +210,7 @@ impl<'v> AnalysisContext<'v> {
             }),
             label,
             plugins,
+            annotations: RefCell::new(Vec::new()),
         }
     }
 
@@ -231,6 +236,11 @@ impl<'v> AnalysisContext<'v> {
         self.actions.state()?.assert_no_promises()
     }
 
+    /// The annotations made with `ctx.annotate`, in the order they were made.
+    pub fn take_annotations(&self) -> Vec<TargetAnnotation> {
+        self.annotations.take()
+    }
+
     /// Must take an `AnalysisContext` which has never had `take_state` called on it before.
     pub fn take_state(&self) -> AnalysisRegistry<'v> {
         self.actions
@@ -329,6 +339,33 @@ fn analysis_context_methods(builder: &mut MethodsBuilder) {
             .plugins
             .buck_error_context("`plugins` is not available for `dynamic_output` or BXL")?)
     }
+
+    /// Attaches a non-fatal message to the target being analyzed, for example to tell its owners
+    /// that it uses a deprecated attribute. Annotations of the requested targets are printed,
+    /// grouped by target, at the end of `buck2 build` and included in the build report.
+    ///
+    /// `category` is a free-form string that lets tools filter annotations, e.g. `deprecation`.
+    ///
+    /// Annotations are only reported for rule analysis: they are ignored in anon targets,
+    /// `dynamic_output` and BXL.
+    ///
+    /// ```python
+    /// def _impl(ctx: AnalysisContext) -> list[Provider]:
+    ///     if ctx.attrs.legacy_flags:
+    ///         ctx.annotate("`legacy_flags` is deprecated, use `flags`", category = "deprecation")
+    ///     ...
+    /// ```
+    fn annotate(
+        this: RefAnalysisContext,
+        #[starlark(require = pos)] message: &str,
+        #[starlark(require = named, default = "")] category: &str,
+    ) -> starlark::Result<NoneType> {
+        this.0.annotations.borrow_mut().push(TargetAnnotation {
+            category: category.to_owned(),
+            message: message.to_owned(),
+        });
+        Ok(NoneType)
+    }
 }
 
 #[starlark_module]
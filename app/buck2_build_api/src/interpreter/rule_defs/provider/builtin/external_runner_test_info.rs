@@ -106,6 +106,22 @@ pub struct ExternalRunnerTestInfoGen<V: ValueLifetimeless> {
     /// Configuration needed to spawn a new worker. This worker will be used to run every single
     /// command related to test execution, including listing.
     worker: ValueOfUncheckedGeneric<V, FrozenWorkerInfo>,
+
+    /// Resources this test needs while it runs, as a mapping from resource name to amount.
+    /// `cpu` and `memory_mb` are well known, any other key names a device class (e.g. `gpu`).
+    /// Used to schedule the test locally and mapped to platform properties when it runs on RE.
+    resource_requirements: ValueOfUncheckedGeneric<V, DictType<String, i32>>,
+
+    /// Variables to inherit from the environment when running locally, on top of the built-in
+    /// allowlist and `test.env_allowlist`.
+    env_allowlist: ValueOfUncheckedGeneric<V, Vec<String>>,
+
+    /// Variables never to inherit from the environment when running locally.
+    env_denylist: ValueOfUncheckedGeneric<V, Vec<String>>,
+
+    /// Variables to point to a directory private to the test when running locally, as `VAR`, or
+    /// to set from a template referring to other sandboxed variables, as `VAR=$HOME/.cache`.
+    env_sandbox: ValueOfUncheckedGeneric<V, Vec<String>>,
 }
 
 // NOTE: All the methods here unwrap because we validate at freeze time.
@@ -187,6 +203,33 @@ impl FrozenExternalRunnerTestInfo {
         unpack_opt_worker(self.worker.get().to_value()).unwrap()
     }
 
+    pub fn resource_requirements(&self) -> impl Iterator<Item = (&str, u32)> {
+        unwrap_all(iter_resource_requirements(
+            self.resource_requirements.get().to_value(),
+        ))
+    }
+
+    pub fn env_allowlist(&self) -> impl Iterator<Item = &str> {
+        unwrap_all(iter_opt_str_list(
+            self.env_allowlist.get().to_value(),
+            "env_allowlist",
+        ))
+    }
+
+    pub fn env_denylist(&self) -> impl Iterator<Item = &str> {
+        unwrap_all(iter_opt_str_list(
+            self.env_denylist.get().to_value(),
+            "env_denylist",
+        ))
+    }
+
+    pub fn env_sandbox(&self) -> impl Iterator<Item = &str> {
+        unwrap_all(iter_opt_str_list(
+            self.env_sandbox.get().to_value(),
+            "env_sandbox",
+        ))
+    }
+
     pub fn visit_artifacts(
         &self,
         visitor: &mut dyn CommandLineArtifactVisitor,
@@ -412,6 +455,48 @@ fn iter_local_resources<'v>(
     }))
 }
 
+fn iter_resource_requirements<'v>(
+    resource_requirements: Value<'v>,
+) -> impl Iterator<Item = buck2_error::Result<(&'v str, u32)>> {
+    if resource_requirements.is_none() {
+        return Either::Left(Either::Left(empty()));
+    }
+
+    let resource_requirements = match DictRef::from_value(resource_requirements) {
+        Some(resource_requirements) => resource_requirements,
+        None => {
+            return Either::Left(Either::Right(once(Err(buck2_error!(
+                [],
+                "Invalid `resource_requirements`: Expected a dict, got: `{}`",
+                resource_requirements
+            )))));
+        }
+    };
+
+    let resource_requirements = resource_requirements.iter().collect::<Vec<_>>();
+
+    Either::Right(resource_requirements.into_iter().map(|(key, value)| {
+        let key = key.unpack_str().with_buck_error_context(|| {
+            format!(
+                "Invalid key in `resource_requirements`: Expected a str, got: `{}`",
+                key
+            )
+        })?;
+
+        let amount = value
+            .unpack_i32()
+            .and_then(|v| u32::try_from(v).ok())
+            .with_buck_error_context(|| {
+                format!(
+                    "Invalid value in `resource_requirements` for key `{}`: Expected a non-negative int, got: `{}`",
+                    key, value
+                )
+            })?;
+
+        Ok((key, amount))
+    }))
+}
+
 fn unpack_opt_executor<'v>(
     executor: Value<'v>,
 ) -> buck2_error::Result<Option<&'v StarlarkCommandExecutorConfig>> {
@@ -469,6 +554,21 @@ where
     check_all(iter_executor_overrides(
         info.executor_overrides.get().to_value(),
     ))?;
+    check_all(iter_resource_requirements(
+        info.resource_requirements.get().to_value(),
+    ))?;
+    check_all(iter_opt_str_list(
+        info.env_allowlist.get().to_value(),
+        "env_allowlist",
+    ))?;
+    check_all(iter_opt_str_list(
+        info.env_denylist.get().to_value(),
+        "env_denylist",
+    ))?;
+    check_all(iter_opt_str_list(
+        info.env_sandbox.get().to_value(),
+        "env_sandbox",
+    ))?;
 
     let provided_local_resources =
         iter_local_resources(info.local_resources.get().to_value())
@@ -524,6 +624,10 @@ fn external_runner_test_info_creator(globals: &mut GlobalsBuilder) {
         #[starlark(default = NoneType)] local_resources: Value<'v>,
         #[starlark(default = NoneType)] required_local_resources: Value<'v>,
         #[starlark(default = NoneType)] worker: Value<'v>,
+        #[starlark(default = NoneType)] resource_requirements: Value<'v>,
+        #[starlark(default = NoneType)] env_allowlist: Value<'v>,
+        #[starlark(default = NoneType)] env_denylist: Value<'v>,
+        #[starlark(default = NoneType)] env_sandbox: Value<'v>,
     ) -> starlark::Result<ExternalRunnerTestInfo<'v>> {
         let res = ExternalRunnerTestInfo {
             test_type: ValueOfUnchecked::new(r#type),
@@ -538,6 +642,10 @@ fn external_runner_test_info_creator(globals: &mut GlobalsBuilder) {
             local_resources: ValueOfUnchecked::new(local_resources),
             required_local_resources: ValueOfUnchecked::new(required_local_resources),
             worker: ValueOfUnchecked::new(worker),
+            resource_requirements: ValueOfUnchecked::new(resource_requirements),
+            env_allowlist: ValueOfUnchecked::new(env_allowlist),
+            env_denylist: ValueOfUnchecked::new(env_denylist),
+            env_sandbox: ValueOfUnchecked::new(env_sandbox),
         };
         validate_external_runner_test_info(&res)?;
         Ok(res)
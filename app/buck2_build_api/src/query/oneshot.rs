@@ -29,6 +29,7 @@ pub trait QueryFrontend: Send + Sync + 'static {
         working_dir: &ProjectRelativePath,
         query: &str,
         query_args: &[String],
+        profile: bool,
     ) -> buck2_error::Result<QueryEvaluationResult<TargetNode>>;
 
     async fn eval_cquery(
@@ -40,6 +41,7 @@ pub trait QueryFrontend: Send + Sync + 'static {
         global_cfg_options: GlobalCfgOptions,
         target_universe: Option<&[String]>,
         collect_universes: bool,
+        profile: bool,
     ) -> buck2_error::Result<(
         QueryEvaluationResult<ConfiguredTargetNode>,
         Option<Vec<Arc<CqueryUniverse>>>,
@@ -52,6 +54,7 @@ pub trait QueryFrontend: Send + Sync + 'static {
         query: &str,
         query_args: &[String],
         global_cfg_options: GlobalCfgOptions,
+        profile: bool,
     ) -> buck2_error::Result<QueryEvaluationResult<ActionQueryNode>>;
 }
 
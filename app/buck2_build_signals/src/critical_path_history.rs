@@ -0,0 +1,226 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Actions which were on the critical path of previous builds, used to predict the critical path
+//! of the next one and schedule it first.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use buck2_core::fs::async_fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::file_name::FileName;
+use buck2_error::BuckErrorContext;
+use dice::UserComputationData;
+use dupe::Dupe;
+
+/// How many actions to remember. Each build adds its critical path, so this is only reached by
+/// daemons building many different things, in which case the history starts over.
+const MAX_ACTIONS: usize = 20_000;
+
+/// Version of the history on disk. Update when changing its format.
+const CRITICAL_PATH_ACTIONS_VERSION: u64 = 1;
+
+/// Identifies an action across builds: unlike action keys, this does not depend on the DICE
+/// state, but only on the configured target and the name of the action.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CriticalPathActionId(String);
+
+impl CriticalPathActionId {
+    pub fn new(owner: &str, category: &str, identifier: Option<&str>) -> Self {
+        Self(format!(
+            "{} {} {}",
+            owner,
+            category,
+            identifier.unwrap_or_default()
+        ))
+    }
+}
+
+/// How the actions on the critical path of a build compare to the previous builds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CriticalPathPrediction {
+    /// Actions on the critical path which were on the critical path of a previous build.
+    pub predicted_actions: u64,
+    /// How long these actions took the last time they were on the critical path.
+    pub previous_duration: Duration,
+    /// How long these actions took in this build.
+    pub duration: Duration,
+}
+
+/// Actions which were on the critical path of previous builds, with how long they took the last
+/// time. The history is kept in the build count directory, so that it survives daemon restarts.
+#[derive(Clone, Dupe, Default)]
+pub struct CriticalPathHistory {
+    actions: Arc<Mutex<HashMap<CriticalPathActionId, Duration>>>,
+    /// Where the history is written after each build, if anywhere.
+    path: Option<Arc<AbsNormPathBuf>>,
+}
+
+impl CriticalPathHistory {
+    fn path(build_count_dir: &AbsNormPathBuf) -> AbsNormPathBuf {
+        build_count_dir.join(FileName::unchecked_new(&format!(
+            "critical_path_actions-{}",
+            CRITICAL_PATH_ACTIONS_VERSION
+        )))
+    }
+
+    /// An empty history, written to `build_count_dir`.
+    pub fn new(build_count_dir: &AbsNormPathBuf) -> Self {
+        Self {
+            actions: Default::default(),
+            path: Some(Arc::new(Self::path(build_count_dir))),
+        }
+    }
+
+    /// Reads the history written to `build_count_dir` by previous daemons, if any.
+    pub async fn load(build_count_dir: &AbsNormPathBuf) -> buck2_error::Result<Self> {
+        let history = Self::new(build_count_dir);
+        let path = Self::path(build_count_dir);
+        if let Some(buffer) = async_fs_util::read_to_string_if_exists(&path).await? {
+            let actions: HashMap<String, u64> = serde_json::from_str(&buffer)
+                .with_buck_error_context(|| format!("Parsing JSON from {}", path.display()))?;
+            *history.actions.lock().unwrap() = actions
+                .into_iter()
+                .map(|(action, millis)| {
+                    (CriticalPathActionId(action), Duration::from_millis(millis))
+                })
+                .collect();
+        }
+        Ok(history)
+    }
+
+    /// Records the actions on the critical path of a build.
+    pub fn record(
+        &self,
+        actions: impl IntoIterator<Item = (CriticalPathActionId, Duration)>,
+    ) -> CriticalPathPrediction {
+        let mut history = self.actions.lock().unwrap();
+        if history.len() >= MAX_ACTIONS {
+            history.clear();
+        }
+        let mut prediction = CriticalPathPrediction::default();
+        for (action, duration) in actions {
+            if let Some(previous) = history.insert(action, duration) {
+                prediction.predicted_actions += 1;
+                prediction.previous_duration += previous;
+                prediction.duration += duration;
+            }
+        }
+        prediction
+    }
+
+    /// Writes the history, if it has a path.
+    pub async fn save(&self) -> buck2_error::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let actions: HashMap<String, u64> = {
+            let history = self.actions.lock().unwrap();
+            history
+                .iter()
+                .map(|(action, duration)| {
+                    (
+                        action.0.clone(),
+                        duration.as_millis().try_into().unwrap_or(u64::MAX),
+                    )
+                })
+                .collect()
+        };
+        if let Some(dir) = path.parent() {
+            async_fs_util::create_dir_all(dir).await?;
+        }
+        async_fs_util::write(&**path, &serde_json::to_vec(&actions)?).await
+    }
+
+    /// How long the action took the last time it was on the critical path, if it was.
+    pub fn predicted_duration(&self, action: &CriticalPathActionId) -> Option<Duration> {
+        self.actions.lock().unwrap().get(action).copied()
+    }
+}
+
+/// The history of the daemon, and whether a command uses it to schedule actions.
+#[derive(Clone, Dupe)]
+pub struct CriticalPathScheduling {
+    pub history: CriticalPathHistory,
+    /// Whether actions predicted to be on the critical path run first (per
+    /// `buck2.critical_path_scheduling`). The history is recorded either way.
+    pub enabled: bool,
+}
+
+pub trait HasCriticalPathScheduling {
+    fn set_critical_path_scheduling(&mut self, scheduling: CriticalPathScheduling);
+
+    fn get_critical_path_scheduling(&self) -> CriticalPathScheduling;
+}
+
+impl HasCriticalPathScheduling for UserComputationData {
+    fn set_critical_path_scheduling(&mut self, scheduling: CriticalPathScheduling) {
+        self.data.set(scheduling);
+    }
+
+    fn get_critical_path_scheduling(&self) -> CriticalPathScheduling {
+        self.data
+            .get::<CriticalPathScheduling>()
+            .expect("CriticalPathScheduling should be set")
+            .dupe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let history = CriticalPathHistory::default();
+        let a = CriticalPathActionId::new("root//:a (cfg)", "cxx_compile", Some("a.cpp"));
+        let b = CriticalPathActionId::new("root//:b (cfg)", "cxx_link", None);
+
+        assert_eq!(
+            CriticalPathPrediction::default(),
+            history.record([(a.clone(), Duration::from_secs(3))])
+        );
+        assert_eq!(Some(Duration::from_secs(3)), history.predicted_duration(&a));
+        assert_eq!(None, history.predicted_duration(&b));
+
+        assert_eq!(
+            CriticalPathPrediction {
+                predicted_actions: 1,
+                previous_duration: Duration::from_secs(3),
+                duration: Duration::from_secs(2),
+            },
+            history.record([
+                (a.clone(), Duration::from_secs(2)),
+                (b.clone(), Duration::from_secs(1)),
+            ])
+        );
+        assert_eq!(Some(Duration::from_secs(2)), history.predicted_duration(&a));
+        assert_eq!(Some(Duration::from_secs(1)), history.predicted_duration(&b));
+    }
+
+    #[tokio::test]
+    async fn test_load() -> buck2_error::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let dir = AbsNormPathBuf::new(tempdir.path().join("build_count"))?;
+        let a = CriticalPathActionId::new("root//:a (cfg)", "cxx_compile", Some("a.cpp"));
+
+        let history = CriticalPathHistory::load(&dir).await?;
+        assert_eq!(None, history.predicted_duration(&a));
+        history.record([(a.clone(), Duration::from_secs(3))]);
+        history.save().await?;
+
+        // As after a daemon restart.
+        let history = CriticalPathHistory::load(&dir).await?;
+        assert_eq!(Some(Duration::from_secs(3)), history.predicted_duration(&a));
+        Ok(())
+    }
+}
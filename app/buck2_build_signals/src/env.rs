@@ -21,6 +21,8 @@ use buck2_events::dispatch::EventDispatcher;
 use dice::UserComputationData;
 use dupe::Dupe;
 
+use crate::critical_path_history::CriticalPathScheduling;
+
 #[derive(Copy, Clone, Dupe)]
 pub struct NodeDuration {
     /// The amount of time for this node that corresponds to something the user might be able to
@@ -87,6 +89,7 @@ pub struct BuildSignalsContext {
     pub command_name: String,
     pub metadata: HashMap<String, String>,
     pub isolation_prefix: FileNameBuf,
+    pub critical_path_scheduling: CriticalPathScheduling,
 }
 
 /// Created along with the BuildSignalsInstaller (ideally, BuildSignalsInstaller's definition would
@@ -9,5 +9,6 @@
 
 #![feature(error_generic_member_access)]
 
+pub mod critical_path_history;
 pub mod env;
 pub mod node_key;
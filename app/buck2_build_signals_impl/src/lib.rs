@@ -30,6 +30,7 @@ use buck2_build_api::artifact_groups::ResolvedArtifactGroupBuildSignalsKey;
 use buck2_build_api::build_signals::BuildSignals;
 use buck2_build_api::build_signals::BuildSignalsInstaller;
 use buck2_build_api::build_signals::CREATE_BUILD_SIGNALS;
+use buck2_build_signals::critical_path_history::CriticalPathActionId;
 use buck2_build_signals::env::BuildSignalsContext;
 use buck2_build_signals::env::CriticalPathBackendName;
 use buck2_build_signals::env::DeferredBuildSignals;
@@ -39,6 +40,7 @@ use buck2_build_signals::node_key::BuildSignalsNodeKey;
 use buck2_common::package_listing::dice::PackageListingKey;
 use buck2_common::package_listing::dice::PackageListingKeyActivationData;
 use buck2_core::package::PackageLabel;
+use buck2_core::soft_error;
 use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
 use buck2_data::ToProtoMessage;
 use buck2_error::BuckErrorContext;
@@ -413,6 +415,27 @@ where
 
         let compute_elapsed = now.elapsed();
 
+        let history = &ctx.critical_path_scheduling.history;
+        let prediction =
+            history.record(critical_path.iter().filter_map(|(key, data, _)| match key {
+                NodeKey::BuildKey(key) => {
+                    let ActionWithExtraData { action, .. } =
+                        data.action_with_extra_data.as_ref()?;
+                    Some((
+                        CriticalPathActionId::new(
+                            &key.0.owner().to_string(),
+                            action.category().as_str(),
+                            action.identifier(),
+                        ),
+                        data.duration.critical_path_duration(),
+                    ))
+                }
+                _ => None,
+            }));
+        if let Err(e) = history.save().await {
+            soft_error!("critical_path_history_save", e, quiet: true)?;
+        }
+
         let meta_entry_data = NodeData {
             action_with_extra_data: None,
             duration: NodeDuration {
@@ -513,6 +536,12 @@ where
             num_edges,
             uses_total_duration: true,
             backend_name: Some(T::name().to_string()),
+            critical_path_scheduling: ctx.critical_path_scheduling.enabled,
+            predicted_critical_path_actions: prediction.predicted_actions,
+            predicted_critical_path_actions_previous_duration: Some(
+                prediction.previous_duration.try_into()?,
+            ),
+            predicted_critical_path_actions_duration: Some(prediction.duration.try_into()?),
         });
         Ok(())
     }
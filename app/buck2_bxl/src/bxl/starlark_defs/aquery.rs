@@ -331,6 +331,7 @@ fn aquery_methods(builder: &mut MethodsBuilder) {
                                 query,
                                 &query_args,
                                 this.global_cfg_options_override.clone(),
+                                false,
                             )
                             .await?,
                         eval.heap(),
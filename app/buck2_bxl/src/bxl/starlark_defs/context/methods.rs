@@ -12,6 +12,7 @@ use std::sync::Arc;
 
 use buck2_build_api::interpreter::rule_defs::context::AnalysisActions;
 use buck2_cli_proto::build_request::Materializations;
+use buck2_common::dice::build_metadata::HasBuildMetadata;
 use buck2_common::dice::cells::HasCellResolver;
 use buck2_common::dice::data::HasIoProvider;
 use buck2_common::events::HasEvents;
@@ -663,6 +664,22 @@ pub(crate) fn bxl_context_methods(builder: &mut MethodsBuilder) {
         Ok(cli_args)
     }
 
+    /// Metadata passed to the command with `--build-metadata key=value`, as a dict.
+    ///
+    /// This is meant to pass identifiers of the invocation, such as a CI run id, through to the
+    /// artifacts written by the bxl script. Scripts reading it are re-evaluated when the metadata
+    /// changes.
+    #[starlark(attribute)]
+    fn build_metadata<'v>(this: &'v BxlContext<'v>) -> starlark::Result<SmallMap<String, String>> {
+        let metadata = this.via_dice(|ctx, _: &BxlContextNoDice<'_>| {
+            ctx.via(|ctx| async move { ctx.get_build_metadata().await }.boxed_local())
+        })?;
+        Ok(metadata
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect())
+    }
+
     /// Returns the `bxl.Filesystem` for performing a basic set of filesystem operations within bxl
     #[starlark(attribute)]
     fn fs<'v>(this: ValueTyped<'v, BxlContext<'v>>) -> starlark::Result<BxlFilesystem<'v>> {
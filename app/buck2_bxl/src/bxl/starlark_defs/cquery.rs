@@ -547,6 +547,7 @@ fn cquery_methods(builder: &mut MethodsBuilder) {
                                 this.global_cfg_options_override.clone(),
                                 target_universe.into_option().as_ref().map(|v| &v.items[..]),
                                 false,
+                                false,
                             )
                             .await?
                             .0,
@@ -143,6 +143,7 @@ impl LazyCqueryOperation {
                         self.global_cfg_options.clone(),
                         target_universe.as_ref().map(|items| &items[..]),
                         false,
+                        false,
                     )
                     .await?
                     .0;
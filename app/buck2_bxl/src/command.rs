@@ -239,6 +239,7 @@ async fn bxl(
                 .map(|(k, v)| (k.to_owned(), Some(v.to_owned())))
                 .collect::<BTreeMap<_, _>>(),
             &BTreeMap::default(),
+            Vec::new(),
         )?
     } else {
         None
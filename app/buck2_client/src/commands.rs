@@ -12,6 +12,7 @@ pub mod bxl;
 pub mod clean;
 pub mod clean_stale;
 pub mod ctargets;
+pub mod daemon;
 pub mod debug;
 pub mod expand_external_cell;
 pub mod explain;
@@ -22,6 +23,8 @@ pub mod kill;
 pub mod killall;
 pub mod log;
 pub mod lsp;
+pub mod pick;
+pub mod prelude;
 pub mod profile;
 pub mod query;
 pub mod rage;
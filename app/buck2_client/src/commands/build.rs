@@ -22,6 +22,7 @@ use buck2_client_ctx::command_outcome::CommandOutcome;
 use buck2_client_ctx::common::build::CommonBuildOptions;
 use buck2_client_ctx::common::build::CommonOutputOptions;
 use buck2_client_ctx::common::target_cfg::TargetCfgWithUniverseOptions;
+use buck2_client_ctx::common::target_label_filter::TargetLabelFilterOptions;
 use buck2_client_ctx::common::ui::CommonConsoleOptions;
 use buck2_client_ctx::common::CommonBuildConfigurationOptions;
 use buck2_client_ctx::common::CommonCommandOptions;
@@ -36,6 +37,8 @@ use buck2_client_ctx::final_console::FinalConsole;
 use buck2_client_ctx::output_destination_arg::OutputDestinationArg;
 use buck2_client_ctx::path_arg::PathArg;
 use buck2_client_ctx::streaming::StreamingCommand;
+use buck2_client_ctx::subscribers::subscriber::EventSubscriber;
+use buck2_client_ctx::subscribers::target_report::TargetReportWriter;
 use buck2_core::buck2_env;
 use buck2_error::buck2_error;
 use buck2_error::BuckErrorContext;
@@ -55,7 +58,8 @@ pub struct BuildCommand {
     #[clap(
         long = "materializations",
         short = 'M',
-        help = "Materialize (or skip) the final artifacts, bypassing buckconfig.",
+        help = "Materialize (or skip) the final artifacts, bypassing buckconfig. Defaults to `all` \
+                with `--out` or `--show-output`.",
         ignore_case = true,
         value_enum
     )]
@@ -121,6 +125,17 @@ pub struct BuildCommand {
     )]
     output_hashes_file: Option<PathArg>,
 
+    /// Write a provenance document to this file, listing for each requested output the source
+    /// files, toolchains and downloads it was built from and how its actions were executed.
+    #[clap(long = "provenance", value_name = "PATH")]
+    provenance_file: Option<PathArg>,
+
+    /// Write a JSON report with the status (success, failure, skipped or cached) and outputs of
+    /// each requested target to this file. Useful with `--keep-going` to find out which targets
+    /// built.
+    #[clap(long, value_name = "PATH")]
+    report_file: Option<PathArg>,
+
     /// This option does nothing. It is here to keep compatibility with Buck1 and ci
     #[clap(long = "deep", hide = true)]
     _deep: bool,
@@ -131,6 +146,9 @@ pub struct BuildCommand {
     #[clap(flatten)]
     target_cfg: TargetCfgWithUniverseOptions,
 
+    #[clap(flatten)]
+    target_label_filter: TargetLabelFilterOptions,
+
     #[clap(flatten)]
     common_opts: CommonCommandOptions,
 }
@@ -157,6 +175,19 @@ impl BuildCommand {
         build_providers::Action::Skip
     }
 
+    /// Outputs copied with `--out` or printed with `--show-output` are needed at the end of the
+    /// command, even if final artifacts are not materialized by default. They are materialized
+    /// as soon as they are built, rather than after the build, and even if other targets fail.
+    fn final_artifact_materializations(&self) -> Option<FinalArtifactMaterializations> {
+        match &self.materializations {
+            Some(materializations) => Some(materializations.dupe()),
+            None if self.output_path.is_some() || self.show_output.format().is_some() => {
+                Some(FinalArtifactMaterializations::All)
+            }
+            None => None,
+        }
+    }
+
     pub(crate) fn patterns(&self) -> &Vec<String> {
         &self.patterns
     }
@@ -231,12 +262,16 @@ impl StreamingCommand for BuildCommand {
                     }),
                     response_options: Some(ResponseOptions {
                         return_outputs: self.show_output.format().is_some()
-                            || self.output_path.is_some(),
+                            || self.output_path.is_some()
+                            || self.report_file.is_some(),
                         return_default_other_outputs: show_default_other_outputs,
                     }),
                     build_opts: Some(self.build_opts.to_proto()),
-                    final_artifact_materializations: self.materializations.to_proto() as i32,
+                    final_artifact_materializations: self
+                        .final_artifact_materializations()
+                        .to_proto() as i32,
                     target_universe: self.target_cfg.target_universe,
+                    target_label_filter: Some(self.target_label_filter.target_label_filter()),
                     output_hashes_file: self
                         .output_hashes_file
                         .map(|p| {
@@ -250,6 +285,19 @@ impl StreamingCommand for BuildCommand {
                                 })
                         })
                         .transpose()?,
+                    provenance_file: self
+                        .provenance_file
+                        .map(|p| {
+                            p.resolve(&ctx.working_dir)
+                                .into_string()
+                                .with_buck_error_context(|| {
+                                    format!(
+                                        "Failed to convert provenance file path ({}) to string",
+                                        p.display()
+                                    )
+                                })
+                        })
+                        .transpose()?,
                 },
                 ctx.stdin()
                     .console_interaction_stream(&self.common_opts.console_opts),
@@ -321,6 +369,10 @@ impl StreamingCommand for BuildCommand {
         res.with_stdout(stdout)
     }
 
+    fn prefetch_target_patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
     fn console_opts(&self) -> &CommonConsoleOptions {
         &self.common_opts.console_opts
     }
@@ -336,6 +388,13 @@ impl StreamingCommand for BuildCommand {
     fn starlark_opts(&self) -> &CommonStarlarkOptions {
         &self.common_opts.starlark_opts
     }
+
+    fn extra_subscribers(&self) -> Vec<Box<dyn EventSubscriber>> {
+        match &self.report_file {
+            Some(path) => vec![Box::new(TargetReportWriter::new(path.clone()))],
+            None => vec![],
+        }
+    }
 }
 
 pub(crate) fn print_build_succeeded(
@@ -464,4 +523,24 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn final_artifact_materializations() -> buck2_error::Result<()> {
+        assert_matches!(parse(&[])?.final_artifact_materializations(), None);
+        assert_matches!(
+            parse(&["--show-output"])?.final_artifact_materializations(),
+            Some(FinalArtifactMaterializations::All)
+        );
+        assert_matches!(
+            parse(&["--out", "-"])?.final_artifact_materializations(),
+            Some(FinalArtifactMaterializations::All)
+        );
+        assert_matches!(
+            parse(&["--show-output", "--materializations", "none"])?
+                .final_artifact_materializations(),
+            Some(FinalArtifactMaterializations::None)
+        );
+
+        Ok(())
+    }
 }
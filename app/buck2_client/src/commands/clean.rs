@@ -14,16 +14,20 @@ use std::time::Duration;
 use buck2_client_ctx::client_ctx::ClientCommandContext;
 use buck2_client_ctx::common::target_cfg::TargetCfgUnusedOptions;
 use buck2_client_ctx::common::CommonCommandOptions;
+use buck2_client_ctx::daemon::client::connect::establish_connection_existing;
 use buck2_client_ctx::daemon::client::kill::kill_command_impl;
 use buck2_client_ctx::daemon::client::BuckdLifecycleLock;
 use buck2_client_ctx::exit_result::ExitResult;
 use buck2_client_ctx::final_console::FinalConsole;
 use buck2_client_ctx::startup_deadline::StartupDeadline;
 use buck2_client_ctx::streaming::BuckSubcommand;
+use buck2_client_ctx::subscribers::stdout_stderr_forwarder::StdoutStderrForwarder;
+use buck2_client_ctx::subscribers::subscribers::EventSubscribers;
 use buck2_common::argv::Argv;
 use buck2_common::argv::SanitizedArgv;
 use buck2_common::daemon_dir::DaemonDir;
 use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::abs_path::AbsPath;
 use buck2_core::fs::paths::abs_path::AbsPathBuf;
@@ -35,6 +39,8 @@ use walkdir::WalkDir;
 
 use crate::commands::clean_stale::parse_clean_stale_args;
 use crate::commands::clean_stale::CleanStaleCommand;
+use crate::commands::status::find_all_daemon_dirs;
+use crate::commands::status::proto_duration_to_duration;
 
 /// Delete generated files and caches.
 ///
@@ -68,6 +74,15 @@ the specified duration, without killing the daemon",
     #[clap(long = "tracked-only", requires = "stale")]
     tracked_only: bool,
 
+    /// Kill every buck2 daemon on this machine, across all projects and isolation dirs, that
+    /// is not running a command. Does not delete buck-out.
+    #[clap(long, conflicts_with_all = &["stale", "keep_since_time"])]
+    all_daemons: bool,
+
+    /// With `--all-daemons`, only kill daemons which have been idle for at least this long.
+    #[clap(long, requires = "all_daemons", value_name = "DURATION")]
+    idle_for: Option<humantime::Duration>,
+
     /// Command doesn't need these flags, but they are used in mode files, so we need to keep them.
     #[clap(flatten)]
     _target_cfg: TargetCfgUnusedOptions,
@@ -88,6 +103,21 @@ impl CleanCommand {
             return cmd.exec(matches, ctx);
         }
 
+        if self.all_daemons {
+            let idle_for = self.idle_for.map_or(Duration::ZERO, |d| d.into());
+            return ctx
+                .instant_command(
+                    "clean",
+                    &self.common_opts.event_log_opts,
+                    |ctx| async move {
+                        let root = ctx.paths()?.roots.common_buckd_dir()?;
+                        let console = &self.common_opts.console_opts.final_console();
+                        clean_idle_daemons(&root, idle_for, console, self.dry_run).await
+                    },
+                )
+                .into();
+        }
+
         ctx.instant_command(
             "clean",
             &self.common_opts.event_log_opts,
@@ -153,6 +183,78 @@ async fn clean(
     Ok(())
 }
 
+/// Kill all daemons on this machine which have been idle for at least `idle_for`.
+async fn clean_idle_daemons(
+    root: &AbsNormPath,
+    idle_for: Duration,
+    console: &FinalConsole,
+    dry_run: bool,
+) -> buck2_error::Result<()> {
+    for daemon_dir in find_all_daemon_dirs(root)? {
+        let status = match establish_connection_existing(&daemon_dir).await {
+            Ok(client) => {
+                client
+                    .with_subscribers(EventSubscribers::new(vec![Box::new(StdoutStderrForwarder)]))
+                    .with_flushing()
+                    .status(false)
+                    .await
+            }
+            // Not running, nothing to reclaim.
+            Err(_) => continue,
+        };
+        let idle = match status {
+            Ok(status) => status
+                .idle_duration
+                .as_ref()
+                .map(proto_duration_to_duration),
+            Err(e) => {
+                console.print_warning(&format!(
+                    "Failed to get status of buckd in `{}`: {:#}",
+                    daemon_dir, e
+                ))?;
+                continue;
+            }
+        };
+        // Older daemons do not report idle time, leave them alone.
+        let Some(idle) = idle else {
+            continue;
+        };
+        // Zero idle time means a command is running.
+        if idle.is_zero() || idle < idle_for {
+            continue;
+        }
+
+        console.print_stderr(&format!(
+            "{} (idle for {})",
+            daemon_dir,
+            humantime::format_duration(Duration::from_secs(idle.as_secs()))
+        ))?;
+        if dry_run {
+            continue;
+        }
+
+        let _lifecycle_lock = BuckdLifecycleLock::lock_with_timeout(
+            daemon_dir.clone(),
+            StartupDeadline::duration_from_now(Duration::from_secs(10))?,
+        )
+        .await
+        .with_buck_error_context(|| "Error locking buckd lifecycle.lock")?;
+        let Ok(mut client) = establish_connection_existing(&daemon_dir).await else {
+            // Exited since we checked its status.
+            continue;
+        };
+        // The daemon checks again that it is idle, so that no command can start between this
+        // check and the kill.
+        if !client
+            .kill_if_idle("`buck2 clean --all-daemons` was invoked", idle_for)
+            .await?
+        {
+            console.print_stderr(&format!("{} is no longer idle, not killed", daemon_dir))?;
+        }
+    }
+    Ok(())
+}
+
 fn collect_paths_to_clean(
     buck_out_path: &AbsNormPathBuf,
 ) -> buck2_error::Result<Vec<AbsNormPathBuf>> {
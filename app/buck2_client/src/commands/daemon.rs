@@ -0,0 +1,38 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_common::argv::Argv;
+use buck2_common::argv::SanitizedArgv;
+
+use crate::commands::daemon::healthcheck::HealthcheckCommand;
+
+pub mod healthcheck;
+
+/// Inspect the running buck daemon.
+#[derive(Debug, clap::Subcommand)]
+pub enum DaemonSubcommand {
+    Healthcheck(HealthcheckCommand),
+}
+
+impl DaemonSubcommand {
+    pub fn exec(self, matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        let matches = matches.subcommand().expect("subcommand not found").1;
+        match self {
+            DaemonSubcommand::Healthcheck(cmd) => cmd.exec(matches, ctx),
+        }
+    }
+
+    pub fn sanitize_argv(&self, argv: Argv) -> SanitizedArgv {
+        match self {
+            DaemonSubcommand::Healthcheck(cmd) => cmd.sanitize_argv(argv),
+        }
+    }
+}
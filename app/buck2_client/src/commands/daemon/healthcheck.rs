@@ -0,0 +1,199 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::time::Duration;
+
+use buck2_cli_proto::StatusResponse;
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::daemon::client::connect::BuckdConnectOptions;
+use buck2_client_ctx::exit_result::ExitCode;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_common::argv::Argv;
+use buck2_common::argv::SanitizedArgv;
+
+/// Report the health of the running buck daemon.
+///
+/// Intended to be scraped by automation deciding when to recycle build hosts. Does not start a
+/// daemon if none is running. Exits with a non-zero status if the daemon is not running or is
+/// unhealthy.
+#[derive(Debug, clap::Parser)]
+pub struct HealthcheckCommand {
+    /// Print the report as JSON.
+    #[clap(long)]
+    json: bool,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Healthcheck {
+    healthy: bool,
+    running: bool,
+    /// Reasons why the daemon is considered unhealthy.
+    problems: Vec<String>,
+    pid: Option<i64>,
+    version: Option<String>,
+    isolation_dir: Option<String>,
+    uptime_secs: Option<u64>,
+    active_commands: Option<u64>,
+    dice_key_count: Option<u64>,
+    materializer_queue_size: Option<u64>,
+    re_connection_status: Option<String>,
+}
+
+impl Healthcheck {
+    fn not_running() -> Healthcheck {
+        Healthcheck {
+            healthy: false,
+            running: false,
+            problems: vec!["no buckd running".to_owned()],
+            pid: None,
+            version: None,
+            isolation_dir: None,
+            uptime_secs: None,
+            active_commands: None,
+            dice_key_count: None,
+            materializer_queue_size: None,
+            re_connection_status: None,
+        }
+    }
+
+    fn from_status(status: StatusResponse) -> Healthcheck {
+        let mut problems = Vec::new();
+        if status.valid_working_directory == Some(false) {
+            problems.push("daemon working directory is no longer valid".to_owned());
+        }
+        if status.valid_buck_out_mount == Some(false) {
+            problems.push("buck-out mount is no longer valid".to_owned());
+        }
+        if let Some(e) = &status.re_connection_error {
+            problems.push(format!("RE connection failed: {}", e));
+        }
+
+        let uptime_secs = status
+            .uptime
+            .map(|d| Duration::new(d.seconds as u64, d.nanos as u32).as_secs());
+
+        Healthcheck {
+            healthy: problems.is_empty(),
+            running: true,
+            problems,
+            pid: status.process_info.as_ref().map(|p| p.pid),
+            version: status.process_info.map(|p| p.version),
+            isolation_dir: Some(status.isolation_dir),
+            uptime_secs,
+            active_commands: status.active_commands,
+            dice_key_count: status.snapshot.as_ref().map(|s| s.dice_key_count),
+            materializer_queue_size: status
+                .snapshot
+                .as_ref()
+                .map(|s| s.deferred_materializer_queue_size),
+            re_connection_status: status.re_connection_status,
+        }
+    }
+
+    fn print_human(&self) -> buck2_error::Result<()> {
+        fn field<T: std::fmt::Display>(v: &Option<T>) -> String {
+            match v {
+                Some(v) => v.to_string(),
+                None => "unknown".to_owned(),
+            }
+        }
+
+        buck2_client_ctx::println!(
+            "status: {}",
+            if self.healthy { "healthy" } else { "unhealthy" }
+        )?;
+        for problem in &self.problems {
+            buck2_client_ctx::println!("problem: {}", problem)?;
+        }
+        if !self.running {
+            return Ok(());
+        }
+        buck2_client_ctx::println!("pid: {}", field(&self.pid))?;
+        buck2_client_ctx::println!("version: {}", field(&self.version))?;
+        buck2_client_ctx::println!("isolation dir: {}", field(&self.isolation_dir))?;
+        buck2_client_ctx::println!("uptime (s): {}", field(&self.uptime_secs))?;
+        buck2_client_ctx::println!("active commands: {}", field(&self.active_commands))?;
+        buck2_client_ctx::println!("DICE keys: {}", field(&self.dice_key_count))?;
+        buck2_client_ctx::println!(
+            "materializer queue: {}",
+            field(&self.materializer_queue_size)
+        )?;
+        buck2_client_ctx::println!("RE connection: {}", field(&self.re_connection_status))?;
+        Ok(())
+    }
+}
+
+impl HealthcheckCommand {
+    pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        let res = ctx.with_runtime(|ctx| async move {
+            let healthcheck = match ctx
+                .connect_buckd(BuckdConnectOptions::existing_only_no_console())
+                .await
+            {
+                Err(_) => Healthcheck::not_running(),
+                Ok(mut client) => {
+                    Healthcheck::from_status(client.with_flushing().status(true).await?)
+                }
+            };
+
+            if self.json {
+                buck2_client_ctx::println!("{}", serde_json::to_string_pretty(&healthcheck)?)?;
+            } else {
+                healthcheck.print_human()?;
+            }
+
+            Ok::<_, buck2_error::Error>(healthcheck.healthy)
+        });
+
+        match res {
+            Ok(true) => ExitResult::success(),
+            Ok(false) => ExitResult::status(ExitCode::UserError),
+            Err(e) => ExitResult::err(e),
+        }
+    }
+
+    pub fn sanitize_argv(&self, argv: Argv) -> SanitizedArgv {
+        argv.no_need_to_sanitize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_cli_proto::StatusResponse;
+
+    use crate::commands::daemon::healthcheck::Healthcheck;
+
+    #[test]
+    fn test_unhealthy_on_re_failure() {
+        let status = StatusResponse {
+            valid_working_directory: Some(true),
+            valid_buck_out_mount: Some(true),
+            re_connection_status: Some("failed".to_owned()),
+            re_connection_error: Some("timeout".to_owned()),
+            ..Default::default()
+        };
+        let healthcheck = Healthcheck::from_status(status);
+        assert!(healthcheck.running);
+        assert!(!healthcheck.healthy);
+        assert_eq!(vec!["RE connection failed: timeout"], healthcheck.problems);
+    }
+
+    #[test]
+    fn test_healthy() {
+        let status = StatusResponse {
+            valid_working_directory: Some(true),
+            valid_buck_out_mount: Some(true),
+            active_commands: Some(2),
+            ..Default::default()
+        };
+        let healthcheck = Healthcheck::from_status(status);
+        assert!(healthcheck.healthy);
+        assert_eq!(Some(2), healthcheck.active_commands);
+    }
+}
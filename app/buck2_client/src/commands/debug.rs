@@ -26,6 +26,7 @@ use crate::commands::debug::allocative::AllocativeCommand;
 use crate::commands::debug::daemon_dir::DaemonDirCommand;
 use crate::commands::debug::eval::EvalCommand;
 use crate::commands::debug::exe::ExeCommand;
+use crate::commands::debug::io_trace::IoTraceCommand;
 use crate::commands::debug::log_perf::LogPerfCommand;
 use crate::commands::debug::paranoid::ParanoidCommand;
 use crate::commands::debug::persist_event_logs::PersistEventLogsCommand;
@@ -48,6 +49,7 @@ mod file_status;
 mod flush_dep_files;
 mod heap_dump;
 mod internal_version;
+mod io_trace;
 mod log_perf;
 mod materialize;
 mod paranoid;
@@ -94,6 +96,8 @@ pub enum DebugCommand {
     LogPerf(LogPerfCommand),
     /// Interact with I/O tracing of the daemon.
     TraceIo(TraceIoCommand),
+    /// Export the filesystem reads of a command traced with `trace-io`.
+    IoTrace(IoTraceCommand),
     #[doc(hidden)]
     PersistEventLogs(PersistEventLogsCommand),
     #[clap(subcommand)]
@@ -124,6 +128,7 @@ impl DebugCommand {
             DebugCommand::FileStatus(cmd) => cmd.exec(matches, ctx),
             DebugCommand::LogPerf(cmd) => cmd.exec(matches, ctx),
             DebugCommand::TraceIo(cmd) => cmd.exec(matches, ctx),
+            DebugCommand::IoTrace(cmd) => cmd.exec(matches, ctx),
             DebugCommand::PersistEventLogs(cmd) => cmd.exec(matches, ctx),
             DebugCommand::Paranoid(cmd) => cmd.exec(matches, ctx),
             DebugCommand::Eval(cmd) => cmd.exec(matches, ctx),
@@ -0,0 +1,150 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::io::Write;
+
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::exit_result::ClientIoError;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_event_log::stream_value::StreamValue;
+use serde::Serialize;
+use tokio_stream::StreamExt;
+
+use crate::commands::log::options::EventLogOptions;
+use crate::commands::log::transform_format;
+use crate::commands::log::LogCommandOutputFormat;
+use crate::commands::log::LogCommandOutputFormatWithWriter;
+
+/// Export the filesystem reads the daemon performed for a command.
+///
+/// Reads are only recorded while I/O tracing is enabled with `buck2 debug trace-io enable`, and
+/// only reads not cached by the daemon are performed, so run the command to trace after enabling
+/// it (which restarts the daemon) to trace a cold build.
+///
+/// This produces tab-delimited output listing the path, the kind of read (`file`, `dir` or
+/// `metadata`), the size (bytes for files, entries for directories) and the duration in
+/// microseconds of every read, in the order they completed. A summary is printed to stderr.
+#[derive(Debug, clap::Parser)]
+pub struct IoTraceCommand {
+    #[clap(flatten)]
+    event_log: EventLogOptions,
+    #[clap(
+        long,
+        help = "Which output format to use for this command",
+        default_value = "tabulated",
+        ignore_case = true,
+        value_enum
+    )]
+    format: LogCommandOutputFormat,
+}
+
+#[derive(Debug, Serialize)]
+struct IoTraceRead {
+    path: String,
+    kind: &'static str,
+    size: u64,
+    duration_us: u64,
+}
+
+impl IoTraceRead {
+    fn from_proto(read: &buck2_data::IoTraceRead) -> Self {
+        Self {
+            path: read.path.clone(),
+            kind: match read.kind() {
+                buck2_data::IoTraceReadKind::IoTraceReadFile => "file",
+                buck2_data::IoTraceReadKind::IoTraceReadDir => "dir",
+                buck2_data::IoTraceReadKind::IoTraceReadMetadata => "metadata",
+            },
+            size: read.size,
+            duration_us: read.duration_us,
+        }
+    }
+}
+
+impl IoTraceCommand {
+    pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        let Self { event_log, format } = self;
+
+        ctx.instant_command_no_log("debug-io-trace", |ctx| async move {
+            let log_path = event_log.get(&ctx).await?;
+
+            let (invocation, mut events) = log_path.unpack_stream().await?;
+            buck2_client_ctx::eprintln!(
+                "Showing filesystem reads from: {}",
+                invocation.display_command_line()
+            )?;
+
+            let mut reads = Vec::new();
+            while let Some(event) = events.try_next().await? {
+                let StreamValue::Event(event) = event else {
+                    continue;
+                };
+                if let Some(buck2_data::buck_event::Data::Instant(instant)) = &event.data {
+                    if let Some(buck2_data::instant_event::Data::IoTraceRead(read)) = &instant.data
+                    {
+                        reads.push(IoTraceRead::from_proto(read));
+                    }
+                }
+            }
+
+            if reads.is_empty() {
+                buck2_client_ctx::eprintln!(
+                    "No reads recorded, enable I/O tracing with `buck2 debug trace-io enable`"
+                )?;
+            } else {
+                let (bytes, duration_us) = reads.iter().fold((0, 0), |(b, d), r| {
+                    let bytes = if r.kind == "file" { r.size } else { 0 };
+                    (b + bytes, d + r.duration_us)
+                });
+                buck2_client_ctx::eprintln!(
+                    "{} reads, {} bytes of files, {:.3}s reading",
+                    reads.len(),
+                    bytes,
+                    duration_us as f64 / 1_000_000.0
+                )?;
+            }
+
+            log_reads(reads, format)
+        })
+        .into()
+    }
+}
+
+fn log_reads(reads: Vec<IoTraceRead>, format: LogCommandOutputFormat) -> buck2_error::Result<()> {
+    Ok(buck2_client_ctx::stdio::print_with_writer::<
+        buck2_error::Error,
+        _,
+    >(|w| {
+        let mut log_writer = transform_format(format, w);
+
+        for read in reads {
+            let res: Result<(), ClientIoError> = {
+                match &mut log_writer {
+                    LogCommandOutputFormatWithWriter::Tabulated(writer) => {
+                        writeln!(
+                            writer,
+                            "{}\t{}\t{}\t{}",
+                            read.path, read.kind, read.size, read.duration_us,
+                        )?;
+                    }
+                    LogCommandOutputFormatWithWriter::Json(writer) => {
+                        serde_json::to_writer(writer.by_ref(), &read)?;
+                        writer.write_all("\n".as_bytes())?;
+                    }
+                    LogCommandOutputFormatWithWriter::Csv(writer) => {
+                        writer.serialize(&read)?;
+                    }
+                }
+                Ok(())
+            };
+            res?;
+        }
+        Ok(())
+    })?)
+}
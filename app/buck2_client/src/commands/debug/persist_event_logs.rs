@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use buck2_client_ctx::client_ctx::ClientCommandContext;
@@ -87,7 +88,7 @@ impl PersistEventLogsCommand {
                 remote_success,
                 metadata: buck2_events::metadata::collect(),
             };
-            dispatch_event_to_scribe(sink.as_ref(), &trace_id, event_to_send).await;
+            dispatch_event_to_scribe(sink.as_deref(), &trace_id, event_to_send).await;
         });
         ExitResult::success()
     }
@@ -323,7 +324,7 @@ fn categorize_error(err: &buck2_error::Error) -> &'static str {
 }
 
 async fn dispatch_event_to_scribe(
-    sink: Option<&RemoteEventSink>,
+    sink: Option<&dyn RemoteEventSink>,
     invocation_id: &TraceId,
     result: PersistEventLogSubprocess,
 ) {
@@ -343,7 +344,9 @@ async fn dispatch_event_to_scribe(
     };
 }
 
-fn create_scribe_sink(ctx: &ClientCommandContext) -> buck2_error::Result<Option<RemoteEventSink>> {
+fn create_scribe_sink(
+    ctx: &ClientCommandContext,
+) -> buck2_error::Result<Option<Arc<dyn RemoteEventSink>>> {
     new_remote_event_sink_if_enabled(
         ctx.fbinit(),
         /* buffer size */ 100,
@@ -212,6 +212,10 @@ impl StreamingCommand for InstallCommand {
         }
     }
 
+    fn prefetch_target_patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
     fn console_opts(&self) -> &CommonConsoleOptions {
         &self.common_opts.console_opts
     }
@@ -7,10 +7,13 @@
  * of this source tree.
  */
 
+mod bottlenecks;
 mod critical_path;
 pub(crate) mod debug_replay;
 pub(crate) mod debug_what_ran;
+mod dep_file_divergences;
 mod diff;
+mod export;
 pub(crate) mod options;
 pub(crate) mod path_log;
 mod replay;
@@ -89,11 +92,14 @@ pub enum LogCommand {
     WhatMaterialized(what_materialized::WhatMaterializedCommand),
     WhatUploaded(what_uploaded::WhatUploadedCommand),
     CriticalPath(critical_path::CriticalPathCommand),
+    Bottlenecks(bottlenecks::BottlenecksCommand),
+    DepFileDivergences(dep_file_divergences::DepFileDivergencesCommand),
     Replay(replay::ReplayCommand),
     ShowUser(show_user_log::ShowUserLogCommand),
     Summary(summary::SummaryCommand),
     #[clap(subcommand)]
     Diff(diff::DiffCommand),
+    Export(export::ExportCommand),
 }
 
 impl LogCommand {
@@ -108,10 +114,13 @@ impl LogCommand {
             Self::WhatMaterialized(cmd) => cmd.exec(matches, ctx),
             Self::WhatUploaded(cmd) => cmd.exec(matches, ctx),
             Self::CriticalPath(cmd) => cmd.exec(matches, ctx),
+            Self::Bottlenecks(cmd) => cmd.exec(matches, ctx),
+            Self::DepFileDivergences(cmd) => cmd.exec(matches, ctx),
             Self::Replay(cmd) => cmd.exec(matches, ctx),
             Self::ShowUser(cmd) => cmd.exec(matches, ctx),
             Self::Summary(cmd) => cmd.exec(matches, ctx),
             Self::Diff(cmd) => cmd.exec(matches, ctx),
+            Self::Export(cmd) => cmd.exec(matches, ctx),
         }
     }
 
@@ -0,0 +1,442 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::exit_result::ClientIoError;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_event_log::stream_value::StreamValue;
+use buck2_event_observer::display;
+use buck2_event_observer::display::TargetDisplayOptions;
+use serde::Serialize;
+use tokio_stream::StreamExt;
+
+use crate::commands::log::options::EventLogOptions;
+use crate::commands::log::transform_format;
+use crate::commands::log::LogCommandOutputFormat;
+use crate::commands::log::LogCommandOutputFormatWithWriter;
+
+/// Show the actions that held back a selected build the most.
+///
+/// Actions on the critical path are ranked by their drag: how much shorter the build would have
+/// been if the action took no time, i.e. how much it can be improved before it stops being on the
+/// critical path. For each action, this shows how long it was queued vs executing, how many
+/// actions were running alongside it on average vs the peak for the build, and a suggestion.
+///
+/// This produces tab-delimited output listing the kind of action execution, owner, category,
+/// identifier, drag, queue duration, execution duration, average parallelism, peak parallelism
+/// and suggestion.
+///
+/// All durations are in microseconds.
+#[derive(Debug, clap::Parser)]
+pub struct BottlenecksCommand {
+    #[clap(flatten)]
+    event_log: EventLogOptions,
+    #[clap(
+        long,
+        help = "Which output format to use for this command",
+        default_value = "tabulated",
+        ignore_case = true,
+        value_enum
+    )]
+    format: LogCommandOutputFormat,
+    /// Maximum number of actions to show.
+    #[clap(long, default_value = "10")]
+    limit: usize,
+}
+
+/// Identifies an action across its execution span and the critical path.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct ActionId {
+    owner: String,
+    category: String,
+    identifier: String,
+}
+
+/// When an action ran, from its execution span.
+#[derive(Clone, Debug)]
+struct ActionRun {
+    start: SystemTime,
+    end: SystemTime,
+    queue: Duration,
+    exec: Duration,
+}
+
+struct CriticalAction {
+    id: ActionId,
+    execution_kind: &'static str,
+    drag: Duration,
+}
+
+#[derive(Serialize)]
+struct Bottleneck {
+    execution_kind: &'static str,
+    owner: String,
+    category: String,
+    identifier: String,
+    #[serde(serialize_with = "serialize_micros")]
+    drag: Duration,
+    #[serde(serialize_with = "serialize_micros")]
+    queue_duration: Duration,
+    #[serde(serialize_with = "serialize_micros")]
+    exec_duration: Duration,
+    /// Average number of actions running while this one ran, including itself.
+    average_parallelism: f64,
+    /// Maximum number of actions running at once during the build.
+    peak_parallelism: usize,
+    suggestion: &'static str,
+}
+
+fn serialize_micros<S: serde::Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u128(d.as_micros())
+}
+
+impl BottlenecksCommand {
+    pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        let Self {
+            event_log,
+            format,
+            limit,
+        } = self;
+
+        ctx.instant_command_no_log("log-bottlenecks", |ctx| async move {
+            let log_path = event_log.get(&ctx).await?;
+
+            let (invocation, mut events) = log_path.unpack_stream().await?;
+            buck2_client_ctx::eprintln!(
+                "Showing bottlenecks from: {}",
+                invocation.display_command_line()
+            )?;
+
+            let target_display_options = TargetDisplayOptions::for_log();
+            let mut runs = HashMap::new();
+            let mut critical_path = Vec::new();
+
+            while let Some(event) = events.try_next().await? {
+                let StreamValue::Event(event) = event else {
+                    continue;
+                };
+                match &event.data {
+                    Some(buck2_data::buck_event::Data::SpanEnd(end)) => {
+                        if let Some(buck2_data::span_end_event::Data::ActionExecution(action)) =
+                            &end.data
+                        {
+                            if let Some((id, run)) =
+                                action_run(&event, end, action, target_display_options)?
+                            {
+                                runs.insert(id, run);
+                            }
+                        }
+                    }
+                    Some(buck2_data::buck_event::Data::Instant(instant)) => {
+                        if let Some(buck2_data::instant_event::Data::BuildGraphInfo(build_graph)) =
+                            &instant.data
+                        {
+                            critical_path = critical_actions(build_graph, target_display_options)?;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            log_bottlenecks(bottlenecks(critical_path, &runs, limit), format)
+        })
+        .into()
+    }
+}
+
+fn action_run(
+    event: &buck2_data::BuckEvent,
+    end: &buck2_data::SpanEndEvent,
+    action: &buck2_data::ActionExecutionEnd,
+    target_display_options: TargetDisplayOptions,
+) -> buck2_error::Result<Option<(ActionId, ActionRun)>> {
+    let (Some(key), Some(name), Some(timestamp), Some(duration)) =
+        (&action.key, &action.name, &event.timestamp, &end.duration)
+    else {
+        return Ok(None);
+    };
+    let end_time = SystemTime::try_from(timestamp.clone())?;
+    let duration = Duration::try_from(duration.clone())?;
+    let id = ActionId {
+        owner: display::display_action_key(key, target_display_options)?,
+        category: name.category.clone(),
+        identifier: name.identifier.clone(),
+    };
+
+    // The last command is the one that produced the action's result.
+    let metadata = action
+        .commands
+        .last()
+        .and_then(|c| c.details.as_ref())
+        .and_then(|d| d.metadata.as_ref());
+    let queue = match metadata.and_then(|m| m.queue_duration.clone()) {
+        Some(queue) => Duration::try_from(queue)?,
+        None => Duration::ZERO,
+    };
+    let exec = match metadata.and_then(|m| m.execution_time.clone()) {
+        Some(exec) => Duration::try_from(exec)?,
+        None => duration.saturating_sub(queue),
+    };
+
+    Ok(Some((
+        id,
+        ActionRun {
+            start: end_time.checked_sub(duration).unwrap_or(end_time),
+            end: end_time,
+            queue,
+            exec,
+        },
+    )))
+}
+
+fn critical_actions(
+    build_graph: &buck2_data::BuildGraphExecutionInfo,
+    target_display_options: TargetDisplayOptions,
+) -> buck2_error::Result<Vec<CriticalAction>> {
+    use buck2_data::critical_path_entry2::action_execution::Owner;
+    use buck2_data::critical_path_entry2::Entry;
+
+    let mut actions = Vec::new();
+    for entry in &build_graph.critical_path2 {
+        let Some(Entry::ActionExecution(action)) = &entry.entry else {
+            continue;
+        };
+        let owner = match &action.owner {
+            Some(Owner::TargetLabel(t)) => {
+                display::display_configured_target_label(t, target_display_options)?
+            }
+            Some(Owner::BxlKey(t)) => display::display_bxl_key(t)?,
+            Some(Owner::AnonTarget(t)) => display::display_anon_target(t)?,
+            None => continue,
+        };
+        let Some(name) = &action.name else {
+            continue;
+        };
+        let drag = match entry
+            .potential_improvement_duration
+            .clone()
+            .or_else(|| entry.user_duration.clone())
+        {
+            Some(drag) => Duration::try_from(drag)?,
+            None => continue,
+        };
+        actions.push(CriticalAction {
+            id: ActionId {
+                owner,
+                category: name.category.clone(),
+                identifier: name.identifier.clone(),
+            },
+            execution_kind: buck2_data::ActionExecutionKind::from_i32(action.execution_kind)
+                .unwrap_or(buck2_data::ActionExecutionKind::NotSet)
+                .as_str_name(),
+            drag,
+        });
+    }
+    Ok(actions)
+}
+
+/// Maximum number of actions running at the same time.
+fn peak_parallelism<'a>(runs: impl IntoIterator<Item = &'a ActionRun>) -> usize {
+    let mut edges = Vec::new();
+    for run in runs {
+        edges.push((run.start, 1));
+        edges.push((run.end, -1));
+    }
+    // Ends sort before starts at the same instant, so back-to-back actions don't overlap.
+    edges.sort();
+    let mut running: i64 = 0;
+    let mut peak = 0;
+    for (_, delta) in edges {
+        running += delta;
+        peak = peak.max(running);
+    }
+    peak as usize
+}
+
+/// Average number of actions running during `run`, including `run` itself.
+fn average_parallelism<'a>(run: &ActionRun, runs: impl IntoIterator<Item = &'a ActionRun>) -> f64 {
+    let window = run.end.duration_since(run.start).unwrap_or_default();
+    if window.is_zero() {
+        return 1.0;
+    }
+    let busy: Duration = runs
+        .into_iter()
+        .map(|other| {
+            let start = other.start.max(run.start);
+            let end = other.end.min(run.end);
+            end.duration_since(start).unwrap_or_default()
+        })
+        .sum();
+    (busy.as_secs_f64() / window.as_secs_f64()).max(1.0)
+}
+
+fn suggestion(run: &ActionRun, average: f64, peak: usize) -> &'static str {
+    if run.queue > run.exec {
+        "mostly queued: raise executor concurrency or free up resources for this action"
+    } else if peak > 1 && average * 2.0 <= peak as f64 {
+        "most parallelism was idle: split this target so its work can run in parallel"
+    } else {
+        "make this action faster, or remove it from the critical path by cutting dependencies"
+    }
+}
+
+fn bottlenecks(
+    mut critical_path: Vec<CriticalAction>,
+    runs: &HashMap<ActionId, ActionRun>,
+    limit: usize,
+) -> Vec<Bottleneck> {
+    let peak = peak_parallelism(runs.values());
+    critical_path.sort_by(|a, b| b.drag.cmp(&a.drag));
+    critical_path
+        .into_iter()
+        .filter(|action| !action.drag.is_zero())
+        .filter_map(|action| {
+            // Actions that were not executed in this build, e.g. in a previous build of the same
+            // daemon, have no span to analyze.
+            let run = runs.get(&action.id)?;
+            let average = average_parallelism(run, runs.values());
+            Some(Bottleneck {
+                execution_kind: action.execution_kind,
+                drag: action.drag,
+                queue_duration: run.queue,
+                exec_duration: run.exec,
+                average_parallelism: average,
+                peak_parallelism: peak,
+                suggestion: suggestion(run, average, peak),
+                owner: action.id.owner,
+                category: action.id.category,
+                identifier: action.id.identifier,
+            })
+        })
+        .take(limit)
+        .collect()
+}
+
+fn log_bottlenecks(
+    bottlenecks: Vec<Bottleneck>,
+    format: LogCommandOutputFormat,
+) -> buck2_error::Result<()> {
+    Ok(buck2_client_ctx::stdio::print_with_writer::<
+        buck2_error::Error,
+        _,
+    >(|w| {
+        let mut log_writer = transform_format(format, w);
+
+        for bottleneck in bottlenecks {
+            let res: Result<(), ClientIoError> = {
+                match &mut log_writer {
+                    LogCommandOutputFormatWithWriter::Tabulated(writer) => {
+                        writeln!(
+                            writer,
+                            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.1}\t{}\t{}",
+                            bottleneck.execution_kind,
+                            bottleneck.owner,
+                            bottleneck.category,
+                            bottleneck.identifier,
+                            bottleneck.drag.as_micros(),
+                            bottleneck.queue_duration.as_micros(),
+                            bottleneck.exec_duration.as_micros(),
+                            bottleneck.average_parallelism,
+                            bottleneck.peak_parallelism,
+                            bottleneck.suggestion,
+                        )?;
+                    }
+                    LogCommandOutputFormatWithWriter::Json(writer) => {
+                        serde_json::to_writer(writer.by_ref(), &bottleneck)?;
+                        writer.write_all("\n".as_bytes())?;
+                    }
+                    LogCommandOutputFormatWithWriter::Csv(writer) => {
+                        writer.serialize(bottleneck)?;
+                    }
+                }
+                Ok(())
+            };
+            res?;
+        }
+        Ok(())
+    })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(name: &str) -> ActionId {
+        ActionId {
+            owner: "root//:t".to_owned(),
+            category: name.to_owned(),
+            identifier: String::new(),
+        }
+    }
+
+    fn run(start: u64, end: u64, queue: u64) -> ActionRun {
+        let at = |s| SystemTime::UNIX_EPOCH + Duration::from_secs(s);
+        ActionRun {
+            start: at(start),
+            end: at(end),
+            queue: Duration::from_secs(queue),
+            exec: Duration::from_secs(end - start - queue),
+        }
+    }
+
+    fn critical(name: &str, drag: u64) -> CriticalAction {
+        CriticalAction {
+            id: id(name),
+            execution_kind: "LOCAL",
+            drag: Duration::from_secs(drag),
+        }
+    }
+
+    #[test]
+    fn test_parallelism() {
+        let runs = [run(0, 10, 0), run(0, 5, 0), run(5, 10, 0), run(10, 20, 0)];
+        assert_eq!(peak_parallelism(&runs), 2);
+        assert_eq!(average_parallelism(&runs[0], &runs), 2.0);
+        assert_eq!(average_parallelism(&runs[3], &runs), 1.0);
+    }
+
+    #[test]
+    fn test_bottlenecks() {
+        let runs = HashMap::from([
+            (id("a"), run(0, 10, 0)),
+            (id("b"), run(0, 10, 0)),
+            (id("c"), run(0, 10, 0)),
+            (id("d"), run(0, 10, 0)),
+            (id("link"), run(10, 40, 0)),
+            (id("queued"), run(40, 50, 8)),
+        ]);
+        let critical_path = vec![
+            critical("a", 2),
+            critical("link", 30),
+            critical("queued", 10),
+            critical("missing", 100),
+        ];
+
+        let result = bottlenecks(critical_path, &runs, 10);
+        assert_eq!(
+            result
+                .iter()
+                .map(|b| (b.category.as_str(), b.drag.as_secs()))
+                .collect::<Vec<_>>(),
+            vec![("link", 30), ("queued", 10), ("a", 2)]
+        );
+        assert_eq!(result[0].peak_parallelism, 4);
+        assert_eq!(result[0].average_parallelism, 1.0);
+        assert!(result[0].suggestion.contains("split this target"));
+        assert!(result[1].suggestion.contains("queued"));
+        assert_eq!(result[2].average_parallelism, 4.0);
+
+        assert_eq!(bottlenecks(Vec::new(), &runs, 10).len(), 0);
+    }
+}
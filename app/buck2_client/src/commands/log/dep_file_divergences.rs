@@ -0,0 +1,273 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::exit_result::ClientIoError;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_event_log::stream_value::StreamValue;
+use buck2_event_observer::display;
+use buck2_event_observer::display::TargetDisplayOptions;
+use serde::Serialize;
+use tokio_stream::StreamExt;
+
+use crate::commands::log::options::EventLogOptions;
+use crate::commands::log::transform_format;
+use crate::commands::log::LogCommandOutputFormat;
+use crate::commands::log::LogCommandOutputFormatWithWriter;
+
+/// Show the remote dep file cache hits that did not match the inputs observed locally.
+///
+/// Only a sample of divergences is recorded, per `buck2.remote_dep_file_divergence_sampling`.
+/// Divergences are grouped by action category, owner and reason, most frequent first.
+///
+/// This produces tab-delimited output listing the count, category, owner, reason and the dep
+/// files which diverged.
+#[derive(Debug, clap::Parser)]
+pub struct DepFileDivergencesCommand {
+    #[clap(flatten)]
+    event_log: EventLogOptions,
+    #[clap(
+        long,
+        help = "Which output format to use for this command",
+        default_value = "tabulated",
+        ignore_case = true,
+        value_enum
+    )]
+    format: LogCommandOutputFormat,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct DivergenceKey {
+    category: String,
+    owner: String,
+    reason: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct DivergenceSummary {
+    count: u64,
+    category: String,
+    owner: String,
+    reason: &'static str,
+    dep_files: Vec<String>,
+}
+
+impl DepFileDivergencesCommand {
+    pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        let Self { event_log, format } = self;
+
+        ctx.instant_command_no_log("log-dep-file-divergences", |ctx| async move {
+            let log_path = event_log.get(&ctx).await?;
+
+            let (invocation, mut events) = log_path.unpack_stream().await?;
+            buck2_client_ctx::eprintln!(
+                "Showing remote dep file divergences from: {}",
+                invocation.display_command_line()
+            )?;
+
+            let target_display_options = TargetDisplayOptions::for_log();
+            let mut divergences = Vec::new();
+
+            while let Some(event) = events.try_next().await? {
+                let StreamValue::Event(event) = event else {
+                    continue;
+                };
+                if let Some(buck2_data::buck_event::Data::Instant(instant)) = &event.data {
+                    if let Some(buck2_data::instant_event::Data::RemoteDepFileDivergence(d)) =
+                        &instant.data
+                    {
+                        let owner = match &d.key {
+                            Some(key) => display::display_action_key(key, target_display_options)?,
+                            None => String::new(),
+                        };
+                        divergences.push((owner, d.clone()));
+                    }
+                }
+            }
+
+            log_divergences(summarize(divergences), format)
+        })
+        .into()
+    }
+}
+
+fn summarize(
+    divergences: Vec<(String, buck2_data::RemoteDepFileDivergence)>,
+) -> Vec<DivergenceSummary> {
+    let mut groups: BTreeMap<DivergenceKey, (u64, Vec<String>)> = BTreeMap::new();
+    for (owner, divergence) in divergences {
+        let key = DivergenceKey {
+            category: divergence
+                .name
+                .as_ref()
+                .map(|n| n.category.clone())
+                .unwrap_or_default(),
+            owner,
+            reason: divergence.verification().as_str_name(),
+        };
+        let (count, dep_files) = groups.entry(key).or_default();
+        *count += 1;
+        for dep_file in divergence.dep_files {
+            if !dep_files.contains(&dep_file) {
+                dep_files.push(dep_file);
+            }
+        }
+    }
+
+    let mut summaries: Vec<_> = groups
+        .into_iter()
+        .map(|(key, (count, dep_files))| DivergenceSummary {
+            count,
+            category: key.category,
+            owner: key.owner,
+            reason: key.reason,
+            dep_files,
+        })
+        .collect();
+    // Stable, so groups with the same count stay ordered by key.
+    summaries.sort_by(|a, b| b.count.cmp(&a.count));
+    summaries
+}
+
+fn log_divergences(
+    summaries: Vec<DivergenceSummary>,
+    format: LogCommandOutputFormat,
+) -> buck2_error::Result<()> {
+    Ok(buck2_client_ctx::stdio::print_with_writer::<
+        buck2_error::Error,
+        _,
+    >(|w| {
+        let mut log_writer = transform_format(format, w);
+
+        for summary in summaries {
+            let res: Result<(), ClientIoError> = {
+                match &mut log_writer {
+                    LogCommandOutputFormatWithWriter::Tabulated(writer) => {
+                        writeln!(
+                            writer,
+                            "{}\t{}\t{}\t{}\t{}",
+                            summary.count,
+                            summary.category,
+                            summary.owner,
+                            summary.reason,
+                            summary.dep_files.join(","),
+                        )?;
+                    }
+                    LogCommandOutputFormatWithWriter::Json(writer) => {
+                        serde_json::to_writer(writer.by_ref(), &summary)?;
+                        writer.write_all("\n".as_bytes())?;
+                    }
+                    LogCommandOutputFormatWithWriter::Csv(writer) => {
+                        writer.serialize(DivergenceSummaryCsv {
+                            count: summary.count,
+                            category: summary.category,
+                            owner: summary.owner,
+                            reason: summary.reason,
+                            dep_files: summary.dep_files.join(","),
+                        })?;
+                    }
+                }
+                Ok(())
+            };
+            res?;
+        }
+        Ok(())
+    })?)
+}
+
+/// CSV can't serialize sequences as a single field.
+#[derive(Serialize)]
+struct DivergenceSummaryCsv {
+    count: u64,
+    category: String,
+    owner: String,
+    reason: &'static str,
+    dep_files: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn divergence(
+        owner: &str,
+        category: &str,
+        verification: buck2_data::RemoteDepFileVerification,
+        dep_files: &[&str],
+    ) -> (String, buck2_data::RemoteDepFileDivergence) {
+        (
+            owner.to_owned(),
+            buck2_data::RemoteDepFileDivergence {
+                name: Some(buck2_data::ActionName {
+                    category: category.to_owned(),
+                    identifier: String::new(),
+                }),
+                verification: verification as i32,
+                dep_files: dep_files.iter().map(|s| (*s).to_owned()).collect(),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_summarize() {
+        use buck2_data::RemoteDepFileVerification::*;
+
+        let summaries = summarize(vec![
+            divergence(
+                "root//:a",
+                "cxx_compile",
+                RemoteDepFileMismatchFilteredInputs,
+                &["headers"],
+            ),
+            divergence(
+                "root//:b",
+                "cxx_compile",
+                RemoteDepFileMismatchCommandLine,
+                &[],
+            ),
+            divergence(
+                "root//:a",
+                "cxx_compile",
+                RemoteDepFileMismatchFilteredInputs,
+                &["headers"],
+            ),
+            divergence(
+                "root//:a",
+                "cxx_compile",
+                RemoteDepFileMismatchFilteredInputs,
+                &["other"],
+            ),
+        ]);
+
+        assert_eq!(
+            summaries
+                .iter()
+                .map(|s| (s.count, s.owner.as_str(), s.reason, s.dep_files.join(",")))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    3,
+                    "root//:a",
+                    "REMOTE_DEP_FILE_MISMATCH_FILTERED_INPUTS",
+                    "headers,other".to_owned()
+                ),
+                (
+                    1,
+                    "root//:b",
+                    "REMOTE_DEP_FILE_MISMATCH_COMMAND_LINE",
+                    String::new()
+                ),
+            ]
+        );
+    }
+}
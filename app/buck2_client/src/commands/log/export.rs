@@ -0,0 +1,195 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! `buck2 log export` streams the decoded events of an invocation to the stdin of an exporter
+//! process, one JSON object per line:
+//!
+//! - The first line is `{"version": 1, "invocation": ...}`, where `invocation` is the invocation
+//!   header of the event log. `version` is bumped on incompatible changes of this protocol.
+//! - Each following line is an event, in the same format as `buck2 log show` prints it.
+//! - Stdin is closed after the last event.
+//!
+//! The exporter inherits stdout and stderr and runs in the current directory. The command fails
+//! if the exporter exits with a non-zero status. An exporter may close stdin before the last
+//! event if it has seen everything it needs.
+
+use std::io;
+use std::process::Stdio;
+
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_error::BuckErrorContext;
+use buck2_event_log::stream_value::StreamValue;
+use buck2_event_log::utils::Invocation;
+use buck2_util::process::async_background_command;
+use futures::Stream;
+use futures::TryStreamExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+
+use crate::commands::log::options::EventLogOptions;
+
+/// Version of the protocol spoken on the stdin of exporters.
+const PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum LogExportError {
+    #[error("Log exporter `{0}` failed: {1}")]
+    ExporterFailed(String, std::process::ExitStatus),
+}
+
+#[derive(serde::Serialize)]
+struct Header<'a> {
+    version: u32,
+    invocation: &'a Invocation,
+}
+
+/// Streams the events of an invocation to an exporter process.
+///
+/// The exporter reads the events from stdin as JSON lines, see `buck2 log show` for their format.
+/// The first line is a header with the protocol version and the invocation.
+#[derive(Debug, clap::Parser)]
+pub struct ExportCommand {
+    #[clap(flatten)]
+    event_log: EventLogOptions,
+
+    /// Name of an exporter from the `[log_exporters]` section of `.buckconfig`.
+    #[clap(
+        long,
+        value_name = "NAME",
+        conflicts_with = "command",
+        required_unless_present = "command"
+    )]
+    exporter: Option<String>,
+
+    /// Command line of the exporter, instead of a configured one.
+    #[clap(last = true, value_name = "COMMAND")]
+    command: Vec<String>,
+}
+
+impl ExportCommand {
+    pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        let Self {
+            event_log,
+            exporter,
+            command,
+        } = self;
+
+        ctx.instant_command_no_log("log-export", |ctx| async move {
+            let command = match exporter {
+                Some(name) => ctx.immediate_config.log_exporters()?.command(&name)?,
+                None => command,
+            };
+            let program = &command[0];
+
+            let log_path = event_log.get(&ctx).await?;
+            let (invocation, events) = log_path.unpack_stream().await?;
+
+            let mut child = async_background_command(program)
+                .args(&command[1..])
+                .current_dir(ctx.working_dir.path())
+                .stdin(Stdio::piped())
+                .spawn()
+                .with_buck_error_context(|| format!("Error spawning log exporter `{}`", program))?;
+            let stdin = child.stdin.take().buck_error_context("Missing stdin")?;
+
+            let written = write_events(tokio::io::BufWriter::new(stdin), &invocation, events).await;
+            if written.is_err() {
+                // Don't let the exporter mistake a truncated stream for a complete one.
+                let _ignored = child.start_kill();
+            }
+            let status = child.wait().await.with_buck_error_context(|| {
+                format!("Error waiting for log exporter `{}`", program)
+            })?;
+            written?;
+
+            if !status.success() {
+                return Err(LogExportError::ExporterFailed(program.clone(), status).into());
+            }
+            buck2_error::Ok(())
+        })
+        .into()
+    }
+}
+
+/// Writes the header and the events, then closes `out`. Stops early without an error if the
+/// reader goes away.
+async fn write_events<W: AsyncWrite + Unpin>(
+    mut out: W,
+    invocation: &Invocation,
+    events: impl Stream<Item = buck2_error::Result<StreamValue>>,
+) -> buck2_error::Result<()> {
+    let mut events = std::pin::pin!(events);
+
+    let mut buf = serde_json::to_vec(&Header {
+        version: PROTOCOL_VERSION,
+        invocation,
+    })?;
+    buf.push(b'\n');
+    if !write_unless_closed(out.write_all(&buf).await)? {
+        return Ok(());
+    }
+
+    while let Some(event) = events.try_next().await? {
+        buf.clear();
+        serde_json::to_writer(&mut buf, &event)?;
+        buf.push(b'\n');
+        if !write_unless_closed(out.write_all(&buf).await)? {
+            return Ok(());
+        }
+    }
+
+    write_unless_closed(out.shutdown().await)?;
+    Ok(())
+}
+
+/// Whether the reader is still there after a write.
+fn write_unless_closed(res: io::Result<()>) -> buck2_error::Result<bool> {
+    match res {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(false),
+        Err(e) => Err(e).buck_error_context("Error writing to log exporter"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_cli_proto::CommandResult;
+    use buck2_wrapper_common::invocation_id::TraceId;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_events() -> buck2_error::Result<()> {
+        let invocation = Invocation {
+            command_line_args: vec!["buck2".to_owned(), "build".to_owned()],
+            expanded_command_line_args: vec!["buck2".to_owned(), "build".to_owned()],
+            working_dir: "/repo".to_owned(),
+            trace_id: TraceId::null(),
+        };
+        let events = futures::stream::iter(vec![Ok(StreamValue::Result(Box::new(
+            CommandResult::default(),
+        )))]);
+
+        let mut out = Vec::new();
+        write_events(&mut out, &invocation, events).await?;
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["version"], 1);
+        assert_eq!(lines[0]["invocation"]["working_dir"], "/repo");
+        assert!(lines[1].get("Result").is_some());
+        Ok(())
+    }
+}
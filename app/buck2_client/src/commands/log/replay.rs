@@ -74,6 +74,9 @@ impl ReplayCommand {
                     "(replay)", // Could be better
                     console_opts.superconsole_config(),
                     build_count_dir,
+                    // The output of failed actions was written by the command being replayed.
+                    None,
+                    None,
                 )?;
 
                 let res = EventsCtx::new(EventSubscribers::new(vec![console]))
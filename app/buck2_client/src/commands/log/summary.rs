@@ -49,7 +49,9 @@ struct Stats {
     re_max_download_speeds: Vec<SlidingWindow>,
     re_max_upload_speeds: Vec<SlidingWindow>,
     hg_revision: Option<String>,
+    git_revision: Option<String>,
     has_local_changes: Option<bool>,
+    invalidation_storms: Vec<buck2_data::DiceInvalidationStorm>,
 }
 
 impl Stats {
@@ -104,6 +106,9 @@ impl Stats {
                             }
                         }
                     }
+                    Some(buck2_data::instant_event::Data::DiceInvalidationStorm(storm)) => {
+                        self.invalidation_storms.push(storm.clone());
+                    }
                     Some(buck2_data::instant_event::Data::SystemInfo(system_info)) => {
                         self.total_disk_space_bytes = system_info.total_disk_space_bytes;
                         self.system_total_memory_bytes = system_info.system_total_memory_bytes;
@@ -115,6 +120,12 @@ impl Stats {
                             }
                             None => {}
                         }
+                        match vcs.git_revision {
+                            Some(ref revision) => {
+                                self.git_revision = Some(revision.clone());
+                            }
+                            None => {}
+                        }
                         match vcs.has_local_changes {
                             Some(ref has_local_changes) => {
                                 self.has_local_changes = Some(*has_local_changes);
@@ -224,12 +235,29 @@ impl Display for Stats {
         if let Some(hg_revision) = &self.hg_revision {
             writeln!(f, "hg revision: {}", hg_revision)?;
         }
+        if let Some(git_revision) = &self.git_revision {
+            writeln!(f, "git revision: {}", git_revision)?;
+        }
 
         if let Some(has_local_changes) = self.has_local_changes {
             writeln!(f, "has local changes: {}", has_local_changes)?;
         } else {
             writeln!(f, "has local changes: unknown")?;
         }
+        for storm in &self.invalidation_storms {
+            writeln!(
+                f,
+                "invalidation storm: {} changed keys invalidated {} out of {} keys",
+                storm.changed_keys, storm.invalidated_keys, storm.graph_keys
+            )?;
+            for hotspot in &storm.hotspots {
+                writeln!(
+                    f,
+                    "  {} invalidated {} keys",
+                    hotspot.key, hotspot.invalidated_keys
+                )?;
+            }
+        }
         Ok(())
     }
 }
@@ -21,6 +21,7 @@ use buck2_client_ctx::subscribers::superconsole::SuperConsoleConfig;
 use buck2_client_ctx::subscribers::superconsole::SuperConsoleState;
 use buck2_client_ctx::subscribers::superconsole::CUTOFFS;
 use buck2_event_log::stream_value::StreamValue;
+use buck2_event_observer::console_theme::ConsoleTheme;
 use buck2_event_observer::verbosity::Verbosity;
 use buck2_events::BuckEvent;
 use superconsole::components::DrawVertical;
@@ -100,7 +101,10 @@ impl WhatUpCommand {
                     }
                     StreamValue::PartialResult(..) => {}
                     StreamValue::Result(result) => {
-                        let result = StatefulSuperConsole::render_result_errors(&result);
+                        let result = StatefulSuperConsole::render_result_errors(
+                            &result,
+                            &ConsoleTheme::default(),
+                        );
                         super_console.emit(result);
                         super_console.finalize(&Self::component(&super_console_state))?;
                         buck2_client_ctx::eprintln!("No open spans to render when log ended")?;
@@ -44,6 +44,12 @@ pub struct WhatUploadedCommand {
         help = "Aggregates the output by file extension"
     )]
     aggregate_by_extension: bool,
+    #[clap(
+        long = "cache-uploads",
+        help = "Summarizes the uploads of action results to the action cache by rule type instead, and lists the uploads which failed",
+        conflicts_with = "aggregate_by_extension"
+    )]
+    cache_uploads: bool,
 }
 
 #[derive(serde::Serialize)]
@@ -80,6 +86,110 @@ impl Display for ExtensionRecord {
     }
 }
 
+#[derive(serde::Serialize)]
+struct RuleTypeRecord {
+    rule_type: String,
+    actions_uploaded: u64,
+    artifacts_uploaded: u64,
+    bytes_uploaded: u64,
+    failures: u64,
+}
+
+impl Display for RuleTypeRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}",
+            self.rule_type,
+            self.actions_uploaded,
+            self.artifacts_uploaded,
+            self.bytes_uploaded,
+            self.failures
+        )
+    }
+}
+
+/// Uploads to the action cache, by rule type of the target owning the action.
+#[derive(Default)]
+struct CacheUploadStats {
+    /// Rule types of the targets analyzed, by configured target label. Targets whose analysis
+    /// was not in the invocation have an unknown rule type.
+    rule_types: HashMap<String, String>,
+    by_rule_type: HashMap<String, RuleTypeRecord>,
+    /// Actions whose upload failed, and why.
+    failures: Vec<(String, String)>,
+}
+
+impl CacheUploadStats {
+    fn analysis_end(&mut self, analysis: &buck2_data::AnalysisEnd) {
+        if let Some(buck2_data::analysis_end::Target::StandardTarget(label)) = &analysis.target {
+            if let Ok(label) =
+                display::display_configured_target_label(label, TargetDisplayOptions::for_log())
+            {
+                self.rule_types.insert(label, analysis.rule.clone());
+            }
+        }
+    }
+
+    fn cache_upload_end(&mut self, upload: &buck2_data::CacheUploadEnd) {
+        let rule_type = upload
+            .key
+            .as_ref()
+            .and_then(|key| key.owner.as_ref())
+            .and_then(|owner| {
+                display::display_action_owner(owner, TargetDisplayOptions::for_log()).ok()
+            })
+            .and_then(|owner| self.rule_types.get(&owner))
+            .map_or("unknown", |rule_type| rule_type.as_str())
+            .to_owned();
+        let record = self
+            .by_rule_type
+            .entry(rule_type.clone())
+            .or_insert_with(|| RuleTypeRecord {
+                rule_type,
+                actions_uploaded: 0,
+                artifacts_uploaded: 0,
+                bytes_uploaded: 0,
+                failures: 0,
+            });
+        if upload.success {
+            record.actions_uploaded += 1;
+            record.artifacts_uploaded +=
+                (upload.file_digests.len() + upload.tree_digests.len()) as u64;
+            record.bytes_uploaded += upload.output_bytes.unwrap_or_default();
+        } else if !upload.error.is_empty() {
+            // Uploads without an error were skipped, e.g. because the result can't be cached.
+            record.failures += 1;
+            let action = display::display_action_identity(
+                upload.key.as_ref(),
+                upload.name.as_ref(),
+                TargetDisplayOptions::for_log(),
+            )
+            .unwrap_or_else(|_| "unknown action".to_owned());
+            let reason = match &upload.re_error_code {
+                Some(code) => format!("{}: {}", code, upload.error),
+                None => upload.error.clone(),
+            };
+            self.failures.push((action, reason));
+        }
+    }
+
+    /// Rule types with the most bytes uploaded first.
+    fn records(&mut self) -> Vec<RuleTypeRecord> {
+        let mut records: Vec<RuleTypeRecord> = self
+            .by_rule_type
+            .drain()
+            .map(|(_, record)| record)
+            .collect();
+        records.sort_by(|a, b| {
+            b.bytes_uploaded
+                .cmp(&a.bytes_uploaded)
+                .then_with(|| a.rule_type.cmp(&b.rule_type))
+        });
+        records
+    }
+}
+
 fn get_action_record(
     state: &HashMap<u64, buck2_data::ActionExecutionStart>,
     upload: &ReUploadEvent,
@@ -106,7 +216,7 @@ fn get_action_record(
 
 fn print_uploads(
     output: &mut LogCommandOutputFormatWithWriter,
-    record: &ActionRecord,
+    record: &(impl Display + serde::Serialize),
 ) -> Result<(), ClientIoError> {
     match output {
         LogCommandOutputFormatWithWriter::Tabulated(w) => Ok(writeln!(w, "{}", record)?),
@@ -158,6 +268,7 @@ impl WhatUploadedCommand {
             event_log,
             output,
             aggregate_by_extension,
+            cache_uploads,
         } = self;
 
         buck2_client_ctx::stdio::print_with_writer::<buck2_error::Error, _>(|w| {
@@ -175,6 +286,7 @@ impl WhatUploadedCommand {
                 let mut total_bytes_uploaded = 0;
                 let mut state = HashMap::new();
                 let mut stats_by_extension: HashMap<String, ReUploadMetrics> = HashMap::new();
+                let mut cache_upload_stats = CacheUploadStats::default();
                 while let Some(event) = events.try_next().await? {
                     match event {
                         // Insert parent span information so we can refer back to it later.
@@ -192,7 +304,19 @@ impl WhatUploadedCommand {
 
                             Some(buck2_data::buck_event::Data::SpanEnd(end)) => {
                                 match end.data.as_ref() {
-                                    Some(buck2_data::span_end_event::Data::ReUpload(ref u)) => {
+                                    Some(buck2_data::span_end_event::Data::Analysis(analysis))
+                                        if cache_uploads =>
+                                    {
+                                        cache_upload_stats.analysis_end(analysis);
+                                    }
+                                    Some(buck2_data::span_end_event::Data::CacheUpload(upload))
+                                        if cache_uploads =>
+                                    {
+                                        cache_upload_stats.cache_upload_end(upload);
+                                    }
+                                    Some(buck2_data::span_end_event::Data::ReUpload(ref u))
+                                        if !cache_uploads =>
+                                    {
                                         let upload = ReUploadEvent {
                                             parent_span_id: event.parent_id,
                                             inner: u,
@@ -222,7 +346,14 @@ impl WhatUploadedCommand {
                         StreamValue::Result(..) | StreamValue::PartialResult(..) => {}
                     }
                 }
-                if aggregate_by_extension {
+                if cache_uploads {
+                    for record in cache_upload_stats.records() {
+                        print_uploads(&mut output, &record)?;
+                    }
+                    for (action, reason) in &cache_upload_stats.failures {
+                        buck2_client_ctx::eprintln!("failed: {}: {}", action, reason)?;
+                    }
+                } else if aggregate_by_extension {
                     print_extension_stats(&mut output, &stats_by_extension)?;
                 } else {
                     buck2_client_ctx::eprintln!(
@@ -0,0 +1,124 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+mod picker;
+
+use std::env;
+use std::ffi::OsString;
+use std::process::Stdio;
+
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::exit_result::ExitCode;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_util::process::background_command;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum PickError {
+    #[error("No targets matching `{0}`")]
+    NoTargets(String),
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum PickAction {
+    Build,
+    Run,
+    Test,
+}
+
+impl PickAction {
+    fn command(self) -> &'static str {
+        match self {
+            PickAction::Build => "build",
+            PickAction::Run => "run",
+            PickAction::Test => "test",
+        }
+    }
+}
+
+/// Fuzzy-find targets and build, run or test the selection.
+///
+/// Lists the targets matching the patterns, lets you filter them by typing and select several
+/// with tab (only one for `--action run`), then runs the command on the selected targets.
+#[derive(Debug, clap::Parser)]
+#[clap(name = "pick")]
+pub struct PickCommand {
+    /// Patterns of the targets to pick from. Defaults to `...`, all targets under the current
+    /// directory.
+    #[clap(value_name = "TARGET_PATTERNS")]
+    patterns: Vec<String>,
+
+    /// Command to run on the selected targets.
+    #[clap(long, value_enum, default_value = "build")]
+    action: PickAction,
+
+    /// Arguments passed after `--` to the command, e.g. to the binary for `--action run`.
+    #[clap(name = "EXTRA_ARGS", last = true)]
+    extra_args: Vec<String>,
+}
+
+impl PickCommand {
+    pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        let patterns = if self.patterns.is_empty() {
+            vec!["...".to_owned()]
+        } else {
+            self.patterns
+        };
+        let exe = env::current_exe()?;
+        let isolation_dir = ctx.paths()?.isolation.clone();
+
+        let output = background_command(&exe)
+            .arg("--isolation-dir")
+            .arg(isolation_dir.as_str())
+            .arg("targets")
+            .args(&patterns)
+            .current_dir(ctx.working_dir.path())
+            .stdin(Stdio::null())
+            .stderr(Stdio::inherit())
+            .output()?;
+        if !output.status.success() {
+            // `buck2 targets` already printed the error.
+            return match output.status.code() {
+                Some(code) => ExitResult::status_extended(code),
+                None => ExitResult::status(ExitCode::UnknownFailure),
+            };
+        }
+        let targets: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_owned)
+            .collect();
+        if targets.is_empty() {
+            return ExitResult::err(PickError::NoTargets(patterns.join(" ")).into());
+        }
+
+        let multi = !matches!(self.action, PickAction::Run);
+        let selected = picker::pick(targets, multi)?;
+        if selected.is_empty() {
+            return ExitResult::success();
+        }
+
+        let mut argv: Vec<OsString> = vec![
+            exe.clone().into(),
+            "--isolation-dir".into(),
+            isolation_dir.as_str().into(),
+            self.action.command().into(),
+        ];
+        argv.extend(selected.into_iter().map(OsString::from));
+        if !self.extra_args.is_empty() {
+            argv.push("--".into());
+            argv.extend(self.extra_args.into_iter().map(OsString::from));
+        }
+        ExitResult::exec(
+            exe.into_os_string(),
+            argv,
+            Some(ctx.working_dir.path().to_buf().into_abs_path_buf()),
+            Vec::new(),
+        )
+    }
+}
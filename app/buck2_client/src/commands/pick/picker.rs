@@ -0,0 +1,304 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! A minimal fuzzy finder drawn on stderr.
+
+use std::collections::BTreeSet;
+use std::io;
+use std::io::IsTerminal;
+use std::io::Write;
+
+use crossterm::cursor::MoveTo;
+use crossterm::event;
+use crossterm::event::Event;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
+use crossterm::queue;
+use crossterm::style::Attribute;
+use crossterm::style::Print;
+use crossterm::style::SetAttribute;
+use crossterm::terminal;
+use crossterm::terminal::Clear;
+use crossterm::terminal::ClearType;
+use crossterm::terminal::EnterAlternateScreen;
+use crossterm::terminal::LeaveAlternateScreen;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum PickerError {
+    #[error("`buck2 pick` needs an interactive terminal")]
+    NotATerminal,
+}
+
+/// Score of `candidate` for `query`, higher is better. `None` if the characters of the query do
+/// not appear in order in the candidate. Matching is case-insensitive and ignores whitespace in
+/// the query. Consecutive characters and characters at the start of a path component or target
+/// name score higher.
+pub(crate) fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last = None;
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let found = (pos..candidate.len()).find(|&i| candidate[i].eq_ignore_ascii_case(&q))?;
+        score += 1;
+        if found == 0 || matches!(candidate[found - 1], '/' | ':' | '_' | '-' | '.') {
+            score += 3;
+        }
+        match last {
+            Some(last) if last + 1 == found => score += 5,
+            Some(_) => score -= (found - pos) as i64,
+            None => {}
+        }
+        last = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+pub(crate) struct Picker {
+    candidates: Vec<String>,
+    multi: bool,
+    query: String,
+    /// Indices of the candidates matching the query, best first.
+    matches: Vec<usize>,
+    /// Index into `matches`.
+    cursor: usize,
+    selected: BTreeSet<usize>,
+}
+
+impl Picker {
+    pub(crate) fn new(candidates: Vec<String>, multi: bool) -> Self {
+        let mut picker = Self {
+            candidates,
+            multi,
+            query: String::new(),
+            matches: Vec::new(),
+            cursor: 0,
+            selected: BTreeSet::new(),
+        };
+        picker.refilter();
+        picker
+    }
+
+    fn refilter(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .candidates
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| Some((fuzzy_score(&self.query, c)?, i)))
+            .collect();
+        scored.sort_by_key(|&(score, i)| (-score, i));
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.cursor = 0;
+    }
+
+    pub(crate) fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.refilter();
+    }
+
+    pub(crate) fn pop(&mut self) {
+        if self.query.pop().is_some() {
+            self.refilter();
+        }
+    }
+
+    pub(crate) fn up(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub(crate) fn down(&mut self) {
+        if self.cursor + 1 < self.matches.len() {
+            self.cursor += 1;
+        }
+    }
+
+    /// Toggles the selection of the candidate under the cursor and moves to the next one.
+    pub(crate) fn toggle(&mut self) {
+        if !self.multi {
+            return;
+        }
+        if let Some(&i) = self.matches.get(self.cursor) {
+            if !self.selected.remove(&i) {
+                self.selected.insert(i);
+            }
+            self.down();
+        }
+    }
+
+    /// The selected candidates, or the one under the cursor if none were selected.
+    pub(crate) fn accept(mut self) -> Vec<String> {
+        if self.selected.is_empty() {
+            match self.matches.get(self.cursor) {
+                Some(&i) => vec![self.candidates.swap_remove(i)],
+                None => Vec::new(),
+            }
+        } else {
+            self.selected
+                .iter()
+                .map(|&i| self.candidates[i].clone())
+                .collect()
+        }
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let width = width as usize;
+        let rows = (height as usize).saturating_sub(1).max(1);
+        let offset = self.cursor.saturating_sub(rows - 1);
+
+        queue!(out, Clear(ClearType::All))?;
+        for (row, (pos, &i)) in self
+            .matches
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(rows)
+            .enumerate()
+        {
+            let marker = if self.selected.contains(&i) { '*' } else { ' ' };
+            let line: String = format!(" {} {}", marker, self.candidates[i])
+                .chars()
+                .take(width)
+                .collect();
+            queue!(out, MoveTo(0, row as u16 + 1))?;
+            if pos == self.cursor {
+                queue!(
+                    out,
+                    SetAttribute(Attribute::Reverse),
+                    Print(line),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            } else {
+                queue!(out, Print(line))?;
+            }
+        }
+        let prompt = format!(
+            "{}/{} > {}",
+            self.matches.len(),
+            self.candidates.len(),
+            self.query
+        );
+        let prompt_width = prompt.chars().count().min(width);
+        queue!(
+            out,
+            MoveTo(0, 0),
+            Print(prompt.chars().take(width).collect::<String>()),
+            MoveTo(prompt_width as u16, 0)
+        )?;
+        out.flush()
+    }
+}
+
+/// Restores the terminal even if the picker fails.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        if let Err(e) = crossterm::execute!(io::stderr(), EnterAlternateScreen) {
+            drop(terminal::disable_raw_mode());
+            return Err(e);
+        }
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        drop(crossterm::execute!(io::stderr(), LeaveAlternateScreen));
+        drop(terminal::disable_raw_mode());
+    }
+}
+
+/// Lets the user pick from `candidates`. Type to filter, up and down to move, tab to select
+/// several candidates when `multi` is set, enter to accept and escape to cancel. Returns nothing
+/// if cancelled.
+pub(crate) fn pick(candidates: Vec<String>, multi: bool) -> buck2_error::Result<Vec<String>> {
+    if !io::stderr().is_terminal() {
+        return Err(PickerError::NotATerminal.into());
+    }
+    let mut picker = Picker::new(candidates, multi);
+    let _guard = TerminalGuard::enter()?;
+    let mut stderr = io::stderr();
+    loop {
+        picker.draw(&mut stderr)?;
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = event::read()?
+        else {
+            continue;
+        };
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        match code {
+            KeyCode::Esc => return Ok(Vec::new()),
+            KeyCode::Char('c') if ctrl => return Ok(Vec::new()),
+            KeyCode::Enter => return Ok(picker.accept()),
+            KeyCode::Up => picker.up(),
+            KeyCode::Char('p' | 'k') if ctrl => picker.up(),
+            KeyCode::Down => picker.down(),
+            KeyCode::Char('n' | 'j') if ctrl => picker.down(),
+            KeyCode::Tab => picker.toggle(),
+            KeyCode::Backspace => picker.pop(),
+            KeyCode::Char(c) if !ctrl => picker.push(c),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "root//foo:bar"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "root//foo:bar"), None);
+        assert_eq!(fuzzy_score("rab", "root//foo:bar"), None);
+        assert!(fuzzy_score("FooBar", "root//foo:bar").is_some());
+        assert!(
+            fuzzy_score("bar", "root//foo:bar").unwrap()
+                > fuzzy_score("bar", "root//b/a/r:x").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_picker() {
+        let candidates = vec![
+            "root//foo:lib".to_owned(),
+            "root//foo:test".to_owned(),
+            "root//bar:lib".to_owned(),
+        ];
+
+        let mut picker = Picker::new(candidates.clone(), false);
+        picker.push('b');
+        picker.push('a');
+        picker.push('r');
+        assert_eq!(picker.accept(), vec!["root//bar:lib".to_owned()]);
+
+        let mut picker = Picker::new(candidates.clone(), true);
+        picker.toggle();
+        picker.down();
+        picker.toggle();
+        assert_eq!(
+            picker.accept(),
+            vec!["root//foo:lib".to_owned(), "root//bar:lib".to_owned()]
+        );
+
+        let mut picker = Picker::new(candidates, false);
+        picker.push('z');
+        assert_eq!(picker.accept(), Vec::<String>::new());
+    }
+}
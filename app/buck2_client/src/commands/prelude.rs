@@ -0,0 +1,181 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use buck2_client_ctx::client_ctx::ClientCommandContext;
+use buck2_client_ctx::exit_result::ExitResult;
+use buck2_common::prelude_lock::PreludeLock;
+use buck2_common::prelude_lock::DEFAULT_PRELUDE_GIT_ORIGIN;
+use buck2_common::prelude_lock::PRELUDE_LOCKFILE;
+use buck2_common::temp_path::TempPath;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_error::BuckErrorContext;
+use buck2_util::process::async_background_command;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum PreludeUpdateError {
+    #[error("`git {0}` failed:\n{1}")]
+    GitFailed(String, String),
+    #[error("Revision `{0}` not found in `{1}`")]
+    RevisionNotFound(String, String),
+    #[error("Commit `{0}` of `{1}` has no `prelude.bzl`, it is not a prelude")]
+    NotAPrelude(String, String),
+}
+
+#[derive(Debug, clap::Subcommand)]
+#[clap(about = "Manage the prelude pinned in `prelude.lock`")]
+pub enum PreludeCommand {
+    Update(PreludeUpdateCommand),
+}
+
+impl PreludeCommand {
+    pub fn exec(self, matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        match self {
+            Self::Update(cmd) => cmd.exec(matches, ctx),
+        }
+    }
+}
+
+/// Pin the prelude to a commit of its git repository.
+///
+/// Resolves the revision, checks that the commit can be fetched and contains a prelude, and
+/// writes it to `prelude.lock` in the project root. Include that file from `.buckconfig` and set
+/// `prelude = git` in `[external_cells]` to use the pinned prelude.
+#[derive(Debug, clap::Parser)]
+pub struct PreludeUpdateCommand {
+    /// Git repository of the prelude. Defaults to the one in `prelude.lock`, or to the upstream
+    /// prelude repository.
+    #[clap(long, value_name = "URL")]
+    git_origin: Option<String>,
+
+    /// Branch, tag or commit hash to pin.
+    #[clap(long, value_name = "REV", default_value = "main")]
+    rev: String,
+}
+
+impl PreludeUpdateCommand {
+    pub fn exec(self, _matches: &clap::ArgMatches, ctx: ClientCommandContext<'_>) -> ExitResult {
+        let Self { git_origin, rev } = self;
+
+        ctx.instant_command_no_log("prelude-update", |ctx| async move {
+            let paths = ctx.paths()?;
+            let lockfile = paths
+                .project_root()
+                .root()
+                .join(ForwardRelativePath::new(PRELUDE_LOCKFILE)?);
+            let current = fs_util::read_to_string_if_exists(&lockfile)?
+                .and_then(|contents| PreludeLock::parse(&contents));
+
+            let git_origin = git_origin
+                .or_else(|| current.as_ref().map(|l| l.git_origin.clone()))
+                .unwrap_or_else(|| DEFAULT_PRELUDE_GIT_ORIGIN.to_owned());
+
+            let commit_hash = resolve_rev(&git_origin, &rev).await?;
+
+            let tmp_dir = paths.tmp_dir();
+            fs_util::create_dir_all(&tmp_dir)?;
+            let checkout = TempPath::new_in(&tmp_dir)?;
+            fs_util::create_dir_all(checkout.path())?;
+            verify_prelude(checkout.path(), &git_origin, &commit_hash).await?;
+            checkout.close()?;
+
+            let lock = PreludeLock {
+                git_origin,
+                commit_hash,
+                buck2_revision: buck2_build_info::revision().map(str::to_owned),
+            };
+            fs_util::write(&lockfile, lock.render())?;
+
+            match current {
+                Some(current) if current.commit_hash == lock.commit_hash => {
+                    buck2_client_ctx::println!("Prelude is up to date at {}", lock.commit_hash)?;
+                }
+                Some(current) => buck2_client_ctx::println!(
+                    "Updated prelude from {} to {}",
+                    current.commit_hash,
+                    lock.commit_hash
+                )?,
+                None => {
+                    buck2_client_ctx::println!("Pinned prelude to {}", lock.commit_hash)?;
+                    buck2_client_ctx::println!(
+                        "Add `<file:{}>` to `.buckconfig` and set `prelude = git` in \
+                        `[external_cells]` to use it.",
+                        PRELUDE_LOCKFILE
+                    )?;
+                }
+            }
+            buck2_error::Ok(())
+        })
+        .into()
+    }
+}
+
+async fn git(cwd: Option<&AbsNormPath>, args: &[&str]) -> buck2_error::Result<String> {
+    let mut command = async_background_command("git");
+    command.args(args);
+    if let Some(cwd) = cwd {
+        command.current_dir(cwd);
+    }
+    let output = command
+        .output()
+        .await
+        .buck_error_context("Could not run git")?;
+    if !output.status.success() {
+        return Err(PreludeUpdateError::GitFailed(
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        )
+        .into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The commit hash `rev` refers to in the repo at `git_origin`.
+async fn resolve_rev(git_origin: &str, rev: &str) -> buck2_error::Result<String> {
+    if rev.len() == 40 && rev.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Ok(rev.to_ascii_lowercase());
+    }
+    let refs = git(None, &["ls-remote", git_origin, rev]).await?;
+    match refs
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().next())
+    {
+        Some(commit_hash) => Ok(commit_hash.to_owned()),
+        None => {
+            Err(PreludeUpdateError::RevisionNotFound(rev.to_owned(), git_origin.to_owned()).into())
+        }
+    }
+}
+
+/// Fetches the commit into an empty directory and checks that it has a prelude at its root.
+async fn verify_prelude(
+    dir: &AbsNormPath,
+    git_origin: &str,
+    commit_hash: &str,
+) -> buck2_error::Result<()> {
+    git(Some(dir), &["init", "-q"]).await?;
+    git(
+        Some(dir),
+        &["fetch", "-q", "--depth", "1", git_origin, commit_hash],
+    )
+    .await?;
+    let prelude_bzl = format!("{}:prelude.bzl", commit_hash);
+    if git(Some(dir), &["cat-file", "-e", &prelude_bzl])
+        .await
+        .is_err()
+    {
+        return Err(
+            PreludeUpdateError::NotAPrelude(commit_hash.to_owned(), git_origin.to_owned()).into(),
+        );
+    }
+    Ok(())
+}
@@ -103,6 +103,7 @@ impl StreamingCommand for AqueryCommand {
                     target_cfg: Some(self.target_cfg.target_cfg()),
                     context: Some(context),
                     output_attributes,
+                    profile_query: self.query_common.profile_query,
                     unstable_output_format,
                 },
                 ctx.stdin()
@@ -67,6 +67,10 @@ pub(crate) struct CommonQueryOptions {
         help = "list of literals for a multi-query (one containing `%s` or `%Ss`)"
     )]
     query_args: Vec<String>,
+
+    /// Print the time spent evaluating each subexpression of the query to stderr.
+    #[clap(long)]
+    pub profile_query: bool,
 }
 
 impl CommonQueryOptions {
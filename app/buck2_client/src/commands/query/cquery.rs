@@ -120,6 +120,7 @@ impl StreamingCommand for CqueryCommand {
                     target_universe: self.target_cfg.target_universe,
                     target_cfg: Some(self.target_cfg.target_cfg.target_cfg()),
                     show_providers: self.show_providers,
+                    profile_query: self.query_common.profile_query,
                     unstable_output_format,
                     profile_mode: self.profile_options.profile_mode_proto().map(|m| m as i32),
                     profile_output: self
@@ -111,6 +111,7 @@ impl StreamingCommand for UqueryCommand {
                     query_args,
                     context: Some(context),
                     output_attributes,
+                    profile_query: self.query_common.profile_query,
                     unstable_output_format,
                 },
                 ctx.stdin()
@@ -19,6 +19,7 @@ use std::collections::HashMap;
 use std::fmt;
 use std::future::Future;
 use std::process::Stdio;
+use std::sync::Arc;
 use std::time::Duration;
 use std::time::SystemTime;
 
@@ -181,7 +182,9 @@ impl RageCommand {
         let daemon_stderr_command = self.section("Daemon stderr", || {
             upload_daemon_stderr(stderr_path, &manifold, &manifold_id)
         });
-        let hg_snapshot_id_command = self.section("Source control", source_control::get_info);
+        let working_dir = ctx.working_dir.path().to_buf();
+        let hg_snapshot_id_command =
+            self.section("Source control", || source_control::get_info(&working_dir));
         let dice_dump_command = self.section("Dice dump", || async {
             dice::upload_dice_dump(buckd.clone().await?, dice_dump_dir, &manifold, &manifold_id)
                 .await
@@ -271,7 +274,7 @@ impl RageCommand {
 
     async fn send_to_scuba(
         &self,
-        sink: Option<RemoteEventSink>,
+        sink: Option<Arc<dyn RemoteEventSink>>,
         invocation_id: Option<TraceId>,
         system_info: RageSection<system_info::SystemInfo>,
         daemon_stderr_dump: RageSection<String>,
@@ -334,7 +337,7 @@ impl RageCommand {
         // We store in Ent via Ingress that rage was run for specific invocation
         if let Some(invocation_id) = invocation_id {
             dispatch_result_event(
-                sink.as_ref(),
+                sink.as_deref(),
                 &invocation_id,
                 RageResult {
                     string_data,
@@ -521,7 +524,7 @@ async fn upload_re_logs_impl(
 }
 
 async fn dispatch_result_event(
-    sink: Option<&RemoteEventSink>,
+    sink: Option<&dyn RemoteEventSink>,
     rage_id: &TraceId,
     result: RageResult,
 ) -> buck2_error::Result<()> {
@@ -531,7 +534,7 @@ async fn dispatch_result_event(
 }
 
 async fn dispatch_event_to_scribe(
-    sink: Option<&RemoteEventSink>,
+    sink: Option<&dyn RemoteEventSink>,
     trace_id: &TraceId,
     event: InstantEvent,
 ) -> buck2_error::Result<()> {
@@ -554,7 +557,9 @@ async fn dispatch_event_to_scribe(
 }
 
 #[allow(unused_variables)] // Conditional compilation
-fn create_scribe_sink(ctx: &ClientCommandContext) -> buck2_error::Result<Option<RemoteEventSink>> {
+fn create_scribe_sink(
+    ctx: &ClientCommandContext,
+) -> buck2_error::Result<Option<Arc<dyn RemoteEventSink>>> {
     // TODO(swgiillespie) scribe_logging is likely the right feature for this, but we should be able to inject a sink
     // without using configurations at the call site
     new_remote_event_sink_if_enabled(
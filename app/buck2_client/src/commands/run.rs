@@ -67,6 +67,12 @@ pub struct RunCommand {
     #[clap(long, group = "exec_options")]
     emit_shell: bool,
 
+    /// Run the target under this command, e.g. `--wrapper "gdb --args"` or
+    /// `--wrapper "perf record"`. The wrapper is split using shell quoting rules and the target's
+    /// command line is appended to it. Also applies to `--emit-shell` and `--command-args-file`.
+    #[clap(long, value_name = "COMMAND")]
+    wrapper: Option<String>,
+
     #[clap(name = "TARGET", help = "Target to build and run")]
     target: String,
 
@@ -96,6 +102,11 @@ impl StreamingCommand for RunCommand {
         matches: &clap::ArgMatches,
         ctx: &mut ClientCommandContext<'_>,
     ) -> ExitResult {
+        // Parse the wrapper before building so that a typo doesn't cost a build.
+        let mut run_args = match &self.wrapper {
+            Some(wrapper) => parse_wrapper(wrapper)?,
+            None => Vec::new(),
+        };
         let context = ctx.client_context(matches, &self)?;
         // TODO(rafaelc): fail fast on the daemon if the target doesn't have RunInfo
         let response = buckd
@@ -116,6 +127,8 @@ impl StreamingCommand for RunCommand {
                     final_artifact_materializations: Materializations::Materialize as i32,
                     target_universe: Vec::new(),
                     output_hashes_file: None,
+                    provenance_file: None,
+                    target_label_filter: None,
                 },
                 ctx.stdin()
                     .console_interaction_stream(&self.common_opts.console_opts),
@@ -148,7 +161,7 @@ impl StreamingCommand for RunCommand {
         if response.build_targets.is_empty() || response.build_targets[0].run_args.is_empty() {
             return ExitResult::err(RunCommandError::NonBinaryRule(self.target).into());
         }
-        let mut run_args = response.build_targets[0].run_args.clone();
+        run_args.extend(response.build_targets[0].run_args.iter().cloned());
         run_args.extend(self.extra_run_args);
 
         print_build_succeeded(&console, ctx)?;
@@ -201,6 +214,10 @@ impl StreamingCommand for RunCommand {
         )
     }
 
+    fn prefetch_target_patterns(&self) -> &[String] {
+        std::slice::from_ref(&self.target)
+    }
+
     fn console_opts(&self) -> &CommonConsoleOptions {
         &self.common_opts.console_opts
     }
@@ -236,6 +253,13 @@ impl StreamingCommand for RunCommand {
     }
 }
 
+fn parse_wrapper(wrapper: &str) -> buck2_error::Result<Vec<String>> {
+    match shlex::split(wrapper) {
+        Some(args) if !args.is_empty() => Ok(args),
+        _ => Err(RunCommandError::InvalidWrapper(wrapper.to_owned()).into()),
+    }
+}
+
 #[derive(Serialize)]
 struct CommandArgsFile {
     path: String,
@@ -257,4 +281,22 @@ pub enum RunCommandError {
         "`buck2 run` only supports a single target, but multiple targets were requested. Only executing the first one built."
     )]
     MultipleTargets,
+    #[error("Invalid `--wrapper`: `{0}` (expected a non-empty, shell-quoted command)")]
+    InvalidWrapper(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_wrapper;
+
+    #[test]
+    fn test_parse_wrapper() {
+        assert_eq!(parse_wrapper("gdb --args").unwrap(), vec!["gdb", "--args"]);
+        assert_eq!(
+            parse_wrapper("perf record -o 'my perf.data'").unwrap(),
+            vec!["perf", "record", "-o", "my perf.data"]
+        );
+        assert!(parse_wrapper("").is_err());
+        assert!(parse_wrapper("gdb 'unterminated").is_err());
+    }
 }
@@ -18,6 +18,7 @@ use buck2_client_ctx::subscribers::subscribers::EventSubscribers;
 use buck2_common::argv::Argv;
 use buck2_common::argv::SanitizedArgv;
 use buck2_common::daemon_dir::DaemonDir;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_error::BuckErrorContext;
 use chrono::DateTime;
 use humantime::format_duration;
@@ -36,6 +37,10 @@ pub struct StatusCommand {
     snapshot: bool,
     #[clap(long, help = "Enable printing status for all running buckd")]
     all: bool,
+    /// Print a compact summary of every running buckd on this machine, across all projects and
+    /// isolation dirs, including resource usage and how long each has been idle.
+    #[clap(long, conflicts_with_all = &["all", "snapshot"])]
+    all_isolation_dirs: bool,
 }
 
 impl StatusCommand {
@@ -45,35 +50,25 @@ impl StatusCommand {
         ctx: ClientCommandContext<'_>,
     ) -> buck2_error::Result<()> {
         ctx.with_runtime(|ctx| async move {
-            if self.all {
-                let mut daemon_dirs = Vec::new();
+            if self.all || self.all_isolation_dirs {
                 let root = ctx.paths()?.roots.common_buckd_dir()?;
-                let walker = WalkDir::new(&root).follow_links(false).into_iter();
-                for entry in walker {
-                    let entry = entry?;
-                    if entry.file_type().is_dir() {
-                        let dir = DaemonDir {
-                            path: entry.into_path().try_into()?,
-                        };
-
-                        if dir.buckd_info().exists() {
-                            daemon_dirs.push(dir);
-                        }
-                    }
-                }
+                let daemon_dirs = find_all_daemon_dirs(&root)?;
 
                 let mut statuses = Vec::new();
                 for dir in daemon_dirs {
                     if let Ok(bootstrap_client) = establish_connection_existing(&dir).await {
-                        statuses.push(process_status(
-                            bootstrap_client
-                                .with_subscribers(EventSubscribers::new(vec![Box::new(
-                                    StdoutStderrForwarder,
-                                )]))
-                                .with_flushing()
-                                .status(self.snapshot)
-                                .await?,
-                        )?);
+                        let status = bootstrap_client
+                            .with_subscribers(EventSubscribers::new(vec![Box::new(
+                                StdoutStderrForwarder,
+                            )]))
+                            .with_flushing()
+                            .status(self.snapshot || self.all_isolation_dirs)
+                            .await?;
+                        statuses.push(if self.all_isolation_dirs {
+                            process_status_summary(&dir, status)?
+                        } else {
+                            process_status(status)?
+                        });
                     }
                 }
 
@@ -107,6 +102,30 @@ impl StatusCommand {
     }
 }
 
+/// Find the daemon dirs of all buckd that ever ran on this machine, for any project root and
+/// isolation dir.
+pub(crate) fn find_all_daemon_dirs(root: &AbsNormPath) -> buck2_error::Result<Vec<DaemonDir>> {
+    let mut daemon_dirs = Vec::new();
+    let walker = WalkDir::new(root).follow_links(false).into_iter();
+    for entry in walker {
+        let entry = entry?;
+        if entry.file_type().is_dir() {
+            let dir = DaemonDir {
+                path: entry.into_path().try_into()?,
+            };
+
+            if dir.buckd_info().exists() {
+                daemon_dirs.push(dir);
+            }
+        }
+    }
+    Ok(daemon_dirs)
+}
+
+pub(crate) fn proto_duration_to_duration(duration: &prost_types::Duration) -> Duration {
+    Duration::new(duration.seconds as u64, duration.nanos as u32)
+}
+
 fn timestamp_to_string(seconds: u64, nanos: u32) -> buck2_error::Result<String> {
     Ok(DateTime::from_timestamp(seconds as i64, nanos)
         .buck_error_context(StatusError::NativeDateTime)?
@@ -126,10 +145,7 @@ fn process_status(status: StatusResponse) -> buck2_error::Result<serde_json::Val
     };
     let uptime = match status.uptime {
         None => "unknown".to_owned(),
-        Some(uptime) => {
-            let uptime = Duration::new(uptime.seconds as u64, uptime.nanos as u32);
-            duration_to_string(uptime)
-        }
+        Some(uptime) => duration_to_string(proto_duration_to_duration(&uptime)),
     };
 
     let mut value = serde_json::json!({
@@ -144,6 +160,10 @@ fn process_status(status: StatusResponse) -> buck2_error::Result<serde_json::Val
         "supports_vpnless": status.supports_vpnless.unwrap_or_default(),
         "http2": status.http2,
         "io_provider": status.io_provider,
+        "active_commands": status.active_commands,
+        "re_connection_status": status.re_connection_status,
+        "idle_for": status.idle_duration.map(|d| duration_to_string(proto_duration_to_duration(&d))),
+        "thin_client_socket": status.thin_client_socket,
     });
 
     if let Some(valid_working_directory) = status.valid_working_directory {
@@ -157,6 +177,29 @@ fn process_status(status: StatusResponse) -> buck2_error::Result<serde_json::Val
     Ok(value)
 }
 
+/// Compact, machine-readable status used by `--all-isolation-dirs`.
+fn process_status_summary(
+    daemon_dir: &DaemonDir,
+    status: StatusResponse,
+) -> buck2_error::Result<serde_json::Value> {
+    let snapshot = status.snapshot.as_ref();
+    Ok(serde_json::json!({
+        "daemon_dir": daemon_dir.to_string(),
+        "project_root": status.project_root,
+        "isolation_dir": status.isolation_dir,
+        "pid": status.process_info.as_ref().map(|p| p.pid),
+        "version": status.process_info.as_ref().map(|p| &p.version),
+        "uptime_secs": status.uptime.map(|d| proto_duration_to_duration(&d).as_secs()),
+        "idle_secs": status.idle_duration.map(|d| proto_duration_to_duration(&d).as_secs()),
+        "active_commands": status.active_commands,
+        "rss_bytes": snapshot.and_then(|s| s.buck2_rss),
+        "max_rss_bytes": snapshot.map(|s| s.buck2_max_rss),
+        "user_cpu_us": snapshot.map(|s| s.buck2_user_cpu_us),
+        "system_cpu_us": snapshot.map(|s| s.buck2_system_cpu_us),
+        "forkserver_pid": status.forkserver_pid,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
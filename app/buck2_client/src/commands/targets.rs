@@ -379,6 +379,10 @@ impl StreamingCommand for TargetsCommand {
         }
     }
 
+    fn prefetch_target_patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
     fn console_opts(&self) -> &CommonConsoleOptions {
         &self.common_opts.console_opts
     }
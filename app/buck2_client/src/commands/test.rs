@@ -14,6 +14,7 @@ use buck2_cli_proto::TestSessionOptions;
 use buck2_client_ctx::client_ctx::ClientCommandContext;
 use buck2_client_ctx::common::build::CommonBuildOptions;
 use buck2_client_ctx::common::target_cfg::TargetCfgOptions;
+use buck2_client_ctx::common::target_label_filter::TargetLabelFilterOptions;
 use buck2_client_ctx::common::ui::CommonConsoleOptions;
 use buck2_client_ctx::common::CommonBuildConfigurationOptions;
 use buck2_client_ctx::common::CommonCommandOptions;
@@ -142,6 +143,12 @@ If include patterns are present, regardless of whether exclude patterns are pres
     #[clap(long)]
     test_executor_stdout: Option<OutputDestinationArg>,
 
+    /// Only discover the test cases, without running them, and print them with their targets,
+    /// suites and labels in JSON. The tests are still built, since the test executor lists the
+    /// test cases by running the test binaries.
+    #[clap(long)]
+    list: bool,
+
     /// Normally testing will follow the `tests` attribute of all targets, to find their associated tests.
     /// When passed, this flag will disable that, and only run the directly supplied targets.
     #[clap(long)]
@@ -181,6 +188,9 @@ If include patterns are present, regardless of whether exclude patterns are pres
     #[clap(flatten)]
     target_cfg: TargetCfgOptions,
 
+    #[clap(flatten)]
+    target_label_filter: TargetLabelFilterOptions,
+
     #[clap(flatten)]
     common_opts: CommonCommandOptions,
 }
@@ -216,6 +226,7 @@ impl StreamingCommand for TestCommand {
                             || self.unstable_allow_all_tests_on_re,
                         force_use_project_relative_paths: self.unstable_allow_all_tests_on_re,
                         force_run_from_project_root: self.unstable_allow_all_tests_on_re,
+                        list_only: self.list,
                     }),
                     timeout: self
                         .timeout
@@ -226,6 +237,7 @@ impl StreamingCommand for TestCommand {
                         .transpose()
                         .buck_error_context("Invalid `timeout`")?,
                     ignore_tests_attribute: self.ignore_tests_attribute,
+                    target_label_filter: Some(self.target_label_filter.target_label_filter()),
                 },
                 ctx.stdin()
                     .console_interaction_stream(&self.common_opts.console_opts),
@@ -276,6 +288,21 @@ impl StreamingCommand for TestCommand {
             console.print_error(&format!("{} BUILDS FAILED", build_errors.len()))?;
         }
 
+        if self.list {
+            print_error_counter(&console, listing_failed, "LISTINGS FAILED", "⚠")?;
+            buck2_client_ctx::println!(
+                "{}",
+                serde_json::to_string_pretty(&response.listed_tests)?
+            )?;
+            return if !build_errors.is_empty() {
+                ExitResult::from_errors(&build_errors)
+            } else if listing_failed.count > 0 {
+                ExitResult::bail("Listing tests failed")
+            } else {
+                ExitResult::success()
+            };
+        }
+
         let mut line = Line::default();
         line.push(Span::new_unstyled_lossy("Tests finished: "));
         if listing_failed.count > 0 {
@@ -345,6 +372,10 @@ impl StreamingCommand for TestCommand {
         }
     }
 
+    fn prefetch_target_patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
     fn console_opts(&self) -> &CommonConsoleOptions {
         &self.common_opts.console_opts
     }
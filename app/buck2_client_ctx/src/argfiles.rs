@@ -52,6 +52,32 @@ enum ArgExpansionError {
     PythonExecutionFailed { source: io::Error, cmd: Command },
     #[error("Unable to read line from stdin")]
     StdinReadError { source: buck2_error::Error },
+    #[error("Flag file `{path}` includes itself: {}", .chain.join(" -> "))]
+    CyclicInclusion { path: String, chain: Vec<String> },
+    #[error(
+        "Invalid host section `{line}` in flag file `{path}`, expected `[host=<os>]`, `[host=<os>-<arch>]` or `[host=*]`"
+    )]
+    InvalidHostSection { line: String, path: String },
+}
+
+/// Flag to print the arguments after all argfiles and aliases are expanded, and exit.
+const EMIT_RESOLVED_ARGS: &str = "--emit-resolved-args";
+
+/// Removes `--emit-resolved-args` from `args`, returning whether it was passed.
+pub fn take_emit_resolved_args(args: &mut Vec<String>) -> bool {
+    let end = args.iter().position(|a| a == "--").unwrap_or(args.len());
+    let found = args[..end].iter().any(|a| a == EMIT_RESOLVED_ARGS);
+    if found {
+        let rest = args.split_off(end);
+        args.retain(|a| a != EMIT_RESOLVED_ARGS);
+        args.extend(rest);
+    }
+    found
+}
+
+/// The arguments as a single shell-quoted line, for `--emit-resolved-args`.
+pub fn format_resolved_args(args: &[String]) -> String {
+    shlex::try_join(args.iter().map(|s| s.as_str())).expect("Null byte unexpected")
 }
 
 /// Log that a relative flag file was not found in CWD, but was found, and used, from the cell root
@@ -88,6 +114,9 @@ enum ArgFile {
 // Expands any argfiles passed as command line parameters. There are
 // two ways to do: `@argfile` or `--flagfile PATH`.
 //
+// Argfiles may include other argfiles, and plain text argfiles may have
+// sections that only apply on some hosts (see `select_host_sections`).
+//
 // Caveats:
 //  - `--` and `--flagfile` cannot be values of other options
 //  - `--flagfile=X` is _not_ supported, you need to pass
@@ -101,6 +130,16 @@ pub fn expand_argfiles_with_context(
     args: Vec<String>,
     context: &mut ImmediateConfigContext,
     cwd: &AbsWorkingDir,
+) -> buck2_error::Result<Vec<String>> {
+    expand_argfiles(args, context, cwd, &mut Vec::new())
+}
+
+/// `including` is the chain of argfiles currently being expanded, to detect cycles.
+fn expand_argfiles(
+    args: Vec<String>,
+    context: &mut ImmediateConfigContext,
+    cwd: &AbsWorkingDir,
+    including: &mut Vec<String>,
 ) -> buck2_error::Result<Vec<String>> {
     let mut expanded_args = Vec::new();
     let mut arg_iterator = args.into_iter();
@@ -119,8 +158,8 @@ pub fn expand_argfiles_with_context(
                         return Err(ArgExpansionError::MissingFlagFilePath.into());
                     }
                 };
-                // TODO: We want to detect cyclic inclusion
-                let expanded_flagfile_args = resolve_and_expand_argfile(&flagfile, context, cwd)?;
+                let expanded_flagfile_args =
+                    resolve_and_expand_argfile(&flagfile, context, cwd, including)?;
                 expanded_args.extend(expanded_flagfile_args);
             }
             next_arg if next_arg.starts_with('@') => {
@@ -128,8 +167,8 @@ pub fn expand_argfiles_with_context(
                 if flagfile.is_empty() {
                     return Err(ArgExpansionError::MissingFlagFilePathInArgfile.into());
                 }
-                // TODO: We want to detect cyclic inclusion
-                let expanded_flagfile_args = resolve_and_expand_argfile(flagfile, context, cwd)?;
+                let expanded_flagfile_args =
+                    resolve_and_expand_argfile(flagfile, context, cwd, including)?;
                 expanded_args.extend(expanded_flagfile_args);
             }
             _ => expanded_args.push(next_arg),
@@ -145,11 +184,65 @@ fn resolve_and_expand_argfile(
     path: &str,
     context: &mut ImmediateConfigContext,
     cwd: &AbsWorkingDir,
+    including: &mut Vec<String>,
 ) -> buck2_error::Result<Vec<String>> {
     let flagfile = resolve_flagfile(path, context, cwd)
         .with_buck_error_context(|| format!("Error resolving flagfile `{}`", path))?;
+    let key = match &flagfile {
+        ArgFile::Path(p) => fs_util::canonicalize(p)?.to_string(),
+        ArgFile::PythonExecutable(p, flag) => {
+            let p = fs_util::canonicalize(p)?;
+            match flag {
+                Some(flag) => format!("{}#{}", p, flag),
+                None => p.to_string(),
+            }
+        }
+        ArgFile::Stdin => "-".to_owned(),
+    };
+    if including.contains(&key) {
+        let mut chain = including.clone();
+        chain.push(key);
+        return Err(ArgExpansionError::CyclicInclusion {
+            path: path.to_owned(),
+            chain,
+        }
+        .into());
+    }
+
     let flagfile_lines = expand_argfile_contents(&flagfile)?;
-    expand_argfiles_with_context(flagfile_lines, context, cwd)
+    including.push(key);
+    let res = expand_argfiles(flagfile_lines, context, cwd, including);
+    including.pop();
+    res
+}
+
+/// In plain text argfiles, a `[host=<os>]` or `[host=<os>-<arch>]` line starts a section whose
+/// lines only apply on matching hosts, and `[host=*]` starts a section for all hosts again. Names
+/// are those of `std::env::consts`, e.g. `linux`, `macos`, `windows`, `x86_64` and `aarch64`.
+fn select_host_sections(
+    lines: Vec<String>,
+    path: &str,
+    os: &str,
+    arch: &str,
+) -> buck2_error::Result<Vec<String>> {
+    let mut selected = Vec::new();
+    let mut enabled = true;
+    for line in lines {
+        if let Some(host) = line.strip_prefix("[host=") {
+            let host =
+                host.strip_suffix(']')
+                    .ok_or_else(|| ArgExpansionError::InvalidHostSection {
+                        line: line.clone(),
+                        path: path.to_owned(),
+                    })?;
+            enabled = host == "*" || host == os || host.split_once('-') == Some((os, arch));
+            continue;
+        }
+        if enabled {
+            selected.push(line);
+        }
+    }
+    Ok(selected)
 }
 
 fn expand_argfile_contents(flagfile: &ArgFile) -> buck2_error::Result<Vec<String>> {
@@ -173,7 +266,12 @@ fn expand_argfile_contents(flagfile: &ArgFile) -> buck2_error::Result<Vec<String
                 }
                 lines.push(line);
             }
-            Ok(lines)
+            select_host_sections(
+                lines,
+                &path.to_string_lossy(),
+                std::env::consts::OS,
+                std::env::consts::ARCH,
+            )
         }
         ArgFile::PythonExecutable(path, flag) => {
             let mut cmd = background_command(if is_open_source() {
@@ -206,18 +304,23 @@ fn expand_argfile_contents(flagfile: &ArgFile) -> buck2_error::Result<Vec<String
                 .into())
             }
         }
-        ArgFile::Stdin => io::stdin()
-            .lock()
-            .lines()
-            .filter_map(|line| match line {
-                Ok(x) if x.is_empty() => None,
-                Ok(x) => Some(Ok(x)),
-                Err(err) => Some(Err(ArgExpansionError::StdinReadError {
-                    source: err.into(),
-                }
-                .into())),
-            })
-            .collect(),
+        ArgFile::Stdin => select_host_sections(
+            io::stdin()
+                .lock()
+                .lines()
+                .filter_map(|line| match line {
+                    Ok(x) if x.is_empty() => None,
+                    Ok(x) => Some(Ok(x)),
+                    Err(err) => Some(Err(ArgExpansionError::StdinReadError {
+                        source: err.into(),
+                    }
+                    .into())),
+                })
+                .collect::<buck2_error::Result<_>>()?,
+            "-",
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+        ),
     }
 }
 
@@ -328,4 +431,58 @@ mod tests {
                 .unwrap();
         assert_eq!(res, vec!["--magic".to_owned()]);
     }
+
+    #[test]
+    fn test_cyclic_inclusion() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = AbsPath::new(tempdir.path()).unwrap();
+        fs_util::write(root.join("arg1.txt"), "--a\n@arg2.txt").unwrap();
+        fs_util::write(root.join("arg2.txt"), "@arg1.txt").unwrap();
+        fs_util::write(root.join(".buckconfig"), "[cells]\nroot = .").unwrap();
+        let cwd = AbsWorkingDir::unchecked_new(
+            AbsNormPathBuf::new(root.canonicalize().unwrap()).unwrap(),
+        );
+        let mut context = ImmediateConfigContext::new(&cwd);
+        let err = expand_argfiles_with_context(vec!["@arg1.txt".to_owned()], &mut context, &cwd)
+            .unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("includes itself"),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_select_host_sections() {
+        let lines = |s: &str| s.lines().map(str::to_owned).collect::<Vec<_>>();
+        let content =
+            lines("--all\n[host=linux]\n--linux\n[host=macos-aarch64]\n--mac-arm\n[host=*]\n--end");
+        assert_eq!(
+            select_host_sections(content.clone(), "mode", "linux", "x86_64").unwrap(),
+            lines("--all\n--linux\n--end")
+        );
+        assert_eq!(
+            select_host_sections(content.clone(), "mode", "macos", "aarch64").unwrap(),
+            lines("--all\n--mac-arm\n--end")
+        );
+        assert_eq!(
+            select_host_sections(content, "mode", "macos", "x86_64").unwrap(),
+            lines("--all\n--end")
+        );
+        assert!(select_host_sections(lines("[host=linux"), "mode", "linux", "x86_64").is_err());
+    }
+
+    #[test]
+    fn test_take_emit_resolved_args() {
+        let mut args = vec![
+            "buck2".to_owned(),
+            "build".to_owned(),
+            "--emit-resolved-args".to_owned(),
+            "--".to_owned(),
+            "--emit-resolved-args".to_owned(),
+        ];
+        assert!(take_emit_resolved_args(&mut args));
+        assert_eq!(args, vec!["buck2", "build", "--", "--emit-resolved-args"]);
+        assert!(!take_emit_resolved_args(&mut args));
+    }
 }
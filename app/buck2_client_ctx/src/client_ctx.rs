@@ -16,6 +16,7 @@ use buck2_cli_proto::ClientContext;
 use buck2_common::argv::Argv;
 use buck2_common::invocation_paths::InvocationPaths;
 use buck2_common::invocation_paths_result::InvocationPathsResult;
+use buck2_core::buck2_env;
 use buck2_core::error::buck2_hard_error_env;
 use buck2_core::fs::paths::file_name::FileNameBuf;
 use buck2_core::fs::working_dir::AbsWorkingDir;
@@ -147,6 +148,7 @@ impl<'a> ClientCommandContext<'a> {
             command_name,
             std::env::args().collect(),
             None,
+            None,
         )?;
 
         recorder.update_metadata_from_client_metadata(&self.client_metadata);
@@ -280,6 +282,7 @@ impl<'a> ClientCommandContext<'a> {
                 .map(ClientMetadata::to_proto)
                 .collect(),
             preemptible: Default::default(),
+            parent_trace_id: buck2_env!("BUCK2_PARENT_TRACE_ID")?.map(|s| s.to_owned()),
         })
     }
 
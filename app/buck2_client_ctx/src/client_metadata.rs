@@ -18,6 +18,8 @@ use regex::Regex;
 pub struct ClientMetadata {
     pub key: String,
     pub value: String,
+    /// Whether the pair is also readable from Starlark (passed with `--build-metadata`).
+    pub build_visible: bool,
 }
 
 impl ClientMetadata {
@@ -25,8 +27,17 @@ impl ClientMetadata {
         buck2_data::ClientMetadata {
             key: self.key.clone(),
             value: self.value.clone(),
+            build_visible: self.build_visible,
         }
     }
+
+    /// Parse a `key=value` pair which is readable from Starlark.
+    pub fn parse_build_visible(value: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            build_visible: true,
+            ..Self::from_str(value)?
+        })
+    }
 }
 
 impl FromStr for ClientMetadata {
@@ -47,6 +58,7 @@ impl FromStr for ClientMetadata {
         Ok(Self {
             key: key.to_owned(),
             value: value.to_owned(),
+            build_visible: false,
         })
     }
 }
@@ -74,10 +86,17 @@ mod tests {
             ClientMetadata::from_str("foo=bar").unwrap(),
             ClientMetadata {
                 key: "foo".to_owned(),
-                value: "bar".to_owned()
+                value: "bar".to_owned(),
+                build_visible: false,
             }
         );
         assert!(ClientMetadata::from_str("foo").is_err());
         assert!(ClientMetadata::from_str("=foo").is_err());
+        assert!(
+            ClientMetadata::parse_build_visible("run_id=1234")
+                .unwrap()
+                .build_visible
+        );
+        assert!(ClientMetadata::parse_build_visible("run-id=1234").is_err());
     }
 }
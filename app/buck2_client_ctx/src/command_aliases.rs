@@ -0,0 +1,254 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Command aliases from the `[command_alias]` section of the root `.buckconfig`:
+//!
+//! ```ini
+//! [command_alias]
+//! ci-build = build --keep-going --console=simple
+//! ```
+//!
+//! With this, `buck2 ci-build //foo:bar` runs `buck2 build --keep-going --console=simple //foo:bar`.
+//! The `[alias]` section is not used because it holds target aliases.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use buck2_common::legacy_configs::configs::LegacyBuckConfig;
+
+#[derive(buck2_error::Error, Debug)]
+#[buck2(input)]
+enum CommandAliasError {
+    #[error("Command alias `{0}` is empty")]
+    Empty(String),
+    #[error("Command alias `{0}` has unbalanced quotes: `{1}`")]
+    InvalidQuoting(String, String),
+    #[error("Cycle detected in command aliases [{}]", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+/// Whether `arg` is an option of `command` which takes its value as a separate argument.
+fn takes_separate_value(command: &clap::Command, arg: &str) -> bool {
+    let option = if let Some(long) = arg.strip_prefix("--") {
+        command.get_arguments().find(|a| a.get_long() == Some(long))
+    } else {
+        let mut chars = arg.chars().skip(1);
+        match (chars.next(), chars.next()) {
+            (Some(short), None) => command
+                .get_arguments()
+                .find(|a| a.get_short() == Some(short)),
+            _ => None,
+        }
+    };
+    option.is_some_and(|a| a.get_action().takes_values())
+}
+
+#[derive(Default, Debug)]
+pub struct CommandAliases {
+    /// Alias name to the unsplit command line it expands to.
+    aliases: BTreeMap<String, String>,
+}
+
+impl CommandAliases {
+    const SECTION: &'static str = "command_alias";
+
+    pub fn from_config(config: &LegacyBuckConfig) -> Self {
+        let aliases = match config.get_section(Self::SECTION) {
+            Some(section) => section
+                .iter()
+                .map(|(name, value)| (name.to_owned(), value.as_str().to_owned()))
+                .collect(),
+            None => BTreeMap::new(),
+        };
+        Self { aliases }
+    }
+
+    /// The position of the subcommand of `command` in `args`, skipping `argv[0]` and options
+    /// before it.
+    fn subcommand_index(args: &[String], command: &clap::Command) -> Option<usize> {
+        let mut i = 1;
+        while let Some(arg) = args.get(i) {
+            if arg == "--" {
+                return None;
+            }
+            if !arg.starts_with('-') {
+                return Some(i);
+            }
+            i += if takes_separate_value(command, arg) {
+                2
+            } else {
+                1
+            };
+        }
+        None
+    }
+
+    /// The subcommand `args` invoke, if any.
+    pub fn subcommand<'a>(args: &'a [String], command: &clap::Command) -> Option<&'a str> {
+        Some(args[Self::subcommand_index(args, command)?].as_str())
+    }
+
+    /// Whether `name` is a built-in subcommand of `command` rather than a potential alias.
+    pub fn is_builtin(command: &clap::Command, name: &str) -> bool {
+        name == "help" || command.find_subcommand(name).is_some()
+    }
+
+    /// Replaces an alias used as the subcommand of `args` by what it expands to. Aliases may
+    /// refer to other aliases. Built-in subcommands of `command` are never expanded.
+    pub fn expand(
+        &self,
+        mut args: Vec<String>,
+        command: &clap::Command,
+    ) -> buck2_error::Result<Vec<String>> {
+        let Some(index) = Self::subcommand_index(&args, command) else {
+            return Ok(args);
+        };
+
+        let mut chain: Vec<String> = Vec::new();
+        let mut replacement = vec![args[index].clone()];
+        loop {
+            let name = &replacement[0];
+            if Self::is_builtin(command, name) {
+                break;
+            }
+            let Some(value) = self.aliases.get(name) else {
+                break;
+            };
+            if chain.contains(name) {
+                chain.push(name.clone());
+                return Err(CommandAliasError::Cycle(chain).into());
+            }
+            chain.push(name.clone());
+
+            let expanded = shlex::split(value)
+                .ok_or_else(|| CommandAliasError::InvalidQuoting(name.clone(), value.clone()))?;
+            if expanded.is_empty() {
+                return Err(CommandAliasError::Empty(name.clone()).into());
+            }
+            replacement = expanded
+                .into_iter()
+                .chain(replacement.into_iter().skip(1))
+                .collect();
+        }
+
+        args.splice(index..=index, replacement);
+        Ok(args)
+    }
+
+    /// Text listing the aliases, appended to `buck2 --help`.
+    pub fn help(&self) -> Option<String> {
+        if self.aliases.is_empty() {
+            return None;
+        }
+        let mut help = String::from("Command aliases (from [command_alias] in .buckconfig):\n");
+        for (name, value) in &self.aliases {
+            writeln!(help, "  {} = {}", name, value).unwrap();
+        }
+        Some(help)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases(aliases: &[(&str, &str)]) -> CommandAliases {
+        CommandAliases {
+            aliases: aliases
+                .iter()
+                .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+                .collect(),
+        }
+    }
+
+    fn args(args: &str) -> Vec<String> {
+        args.split(' ').map(str::to_owned).collect()
+    }
+
+    fn command() -> clap::Command {
+        clap::Command::new("buck2")
+            .arg(clap::Arg::new("isolation_dir").long("isolation-dir"))
+            .arg(clap::Arg::new("verbose").short('v').long("verbose"))
+            .arg(
+                clap::Arg::new("help_wrapper")
+                    .long("help-wrapper")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .subcommand(clap::Command::new("build"))
+            .subcommand(clap::Command::new("test"))
+    }
+
+    #[test]
+    fn test_expand() -> buck2_error::Result<()> {
+        let aliases = aliases(&[
+            ("ci-build", "build --keep-going --console=simple"),
+            ("ci", "ci-build -c a.b='x y'"),
+            ("build", "test"),
+        ]);
+        let command = command();
+        assert_eq!(
+            aliases.expand(args("buck2 -v 2 ci-build //:a"), &command)?,
+            args("buck2 -v 2 build --keep-going --console=simple //:a")
+        );
+        assert_eq!(
+            aliases.expand(args("buck2 ci //:a"), &command)?,
+            vec![
+                "buck2",
+                "build",
+                "--keep-going",
+                "--console=simple",
+                "-c",
+                "a.b=x y",
+                "//:a"
+            ]
+        );
+        // Built-ins cannot be overridden.
+        assert_eq!(
+            aliases.expand(args("buck2 build //:a"), &command)?,
+            args("buck2 build //:a")
+        );
+        // Unknown subcommands are left for clap to report.
+        assert_eq!(
+            aliases.expand(args("buck2 unknown"), &command)?,
+            args("buck2 unknown")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cycle() {
+        let aliases = aliases(&[("a", "b --x"), ("b", "a --y")]);
+        let err = aliases
+            .expand(args("buck2 a"), &command())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("a -> b -> a"), "{}", err);
+    }
+
+    #[test]
+    fn test_subcommand() {
+        let command = command();
+        assert_eq!(
+            CommandAliases::subcommand(&args("buck2 --isolation-dir x build"), &command),
+            Some("build")
+        );
+        assert_eq!(
+            CommandAliases::subcommand(&args("buck2 -v 2 --isolation-dir=x build"), &command),
+            Some("build")
+        );
+        assert_eq!(
+            CommandAliases::subcommand(&args("buck2 --help-wrapper ci-build"), &command),
+            Some("ci-build")
+        );
+        assert_eq!(
+            CommandAliases::subcommand(&args("buck2 --help"), &command),
+            None
+        );
+    }
+}
@@ -153,17 +153,22 @@ pub struct CommonBuildOptions {
 
     /// If Buck hits an error, continue doing as much work as possible before exiting.
     ///
+    /// Use `build --report-file` to tell targets that failed apart from targets that were not
+    /// built because one of their dependencies failed.
+    ///
     /// See `--fail-fast` for more details.
     #[clap(long, group = "fail-when")]
     keep_going: bool,
 
     /// If target is missing, then skip building instead of throwing error.
+    /// Skipped targets are listed at the end of the build and in the build report.
     #[clap(long)]
     skip_missing_targets: bool,
 
     /// If target is incompatible with the specified configuration, skip building instead of throwing error.
     /// This does not apply to targets specified with glob patterns `/...` or `:`
     /// which are skipped unconditionally.
+    /// Skipped targets are listed at the end of the build and in the build report.
     #[clap(long)]
     skip_incompatible_targets: bool,
 
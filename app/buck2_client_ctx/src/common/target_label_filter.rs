@@ -0,0 +1,36 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use buck2_cli_proto::TargetLabelFilter;
+
+/// Options to filter the targets matched by the command line patterns by their `labels`.
+#[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize, Default)]
+#[clap(next_help_heading = "Target Label Filtering Options")]
+pub struct TargetLabelFilterOptions {
+    /// Only keep targets that have at least one of these labels. Comma separated.
+    ///
+    /// Applied to the targets the patterns expand to (including explicitly named targets), before
+    /// they are configured.
+    #[clap(long, value_name = "LABEL", use_value_delimiter = true)]
+    pub include_labels: Vec<String>,
+
+    /// Drop targets that have any of these labels. Comma separated. Takes precedence over
+    /// `--include-labels`.
+    #[clap(long, value_name = "LABEL", use_value_delimiter = true)]
+    pub exclude_labels: Vec<String>,
+}
+
+impl TargetLabelFilterOptions {
+    pub fn target_label_filter(&self) -> TargetLabelFilter {
+        TargetLabelFilter {
+            include_labels: self.include_labels.clone(),
+            exclude_labels: self.exclude_labels.clone(),
+        }
+    }
+}
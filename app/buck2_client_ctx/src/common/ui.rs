@@ -34,6 +34,9 @@ pub enum ConsoleType {
     SimpleNoTty,
     SimpleTty,
     Super,
+    /// Print a documented subset of events as JSON lines on stderr.
+    #[value(name = "json-events")]
+    JsonEvents,
 }
 
 #[derive(
@@ -140,7 +143,7 @@ impl CommonConsoleOptions {
             ConsoleType::Super => true,
             ConsoleType::SimpleNoTty => false,
             ConsoleType::SimpleTty => true,
-            ConsoleType::None => false,
+            ConsoleType::None | ConsoleType::JsonEvents => false,
         };
         if is_tty {
             FinalConsole::new_with_tty()
@@ -14,6 +14,7 @@ use std::io::BufReader;
 use std::net::Ipv4Addr;
 use std::path::PathBuf;
 use std::time::Duration;
+use std::time::Instant;
 
 use buck2_cli_proto::daemon_api_client::DaemonApiClient;
 use buck2_cli_proto::DaemonProcessInfo;
@@ -75,6 +76,17 @@ pub struct DaemonConstraintsRequest {
     pub reject_daemon: Option<String>,
     pub reject_materializer_state: Option<String>,
     pub daemon_startup_config: DaemonStartupConfig,
+    /// Not a constraint: passed to a daemon started for this request.
+    pub prefetch_target_patterns: Option<PrefetchTargetPatterns>,
+}
+
+/// Target patterns of the command a daemon is started for. The daemon prefetches the files needed
+/// to load them while it starts.
+#[derive(Clone, Debug)]
+pub struct PrefetchTargetPatterns {
+    /// Absolute working directory the patterns are relative to.
+    pub working_dir: String,
+    pub patterns: Vec<String>,
 }
 
 #[derive(Debug, derive_more::Display)]
@@ -131,9 +143,22 @@ impl DaemonConstraintsRequest {
             reject_daemon: None,
             reject_materializer_state: None,
             daemon_startup_config: immediate_config.daemon_startup_config()?.clone(),
+            prefetch_target_patterns: None,
         })
     }
 
+    /// The startup config settings that differ from those the daemon was started with.
+    fn startup_config_changes(&self, daemon: &buck2_cli_proto::DaemonConstraints) -> Vec<String> {
+        match daemon
+            .daemon_startup_config
+            .as_deref()
+            .map(DaemonStartupConfig::deserialize)
+        {
+            Some(Ok(daemon_config)) => daemon_config.describe_changes(&self.daemon_startup_config),
+            _ => Vec::new(),
+        }
+    }
+
     fn is_trace_io_requested(&self) -> bool {
         matches!(self.desired_trace_io_state, DesiredTraceIoState::Enabled)
     }
@@ -216,6 +241,10 @@ pub enum BuckdConnectConstraints {
     Constraints(DaemonConstraintsRequest),
 }
 
+/// How long at most to wait for other commands before restarting a daemon whose constraints
+/// don't match.
+const RESTART_WAIT_FOR_COMMANDS: Duration = Duration::from_secs(30);
+
 fn buckd_startup_timeout_var() -> buck2_error::Result<Option<u64>> {
     buck2_env!("BUCKD_STARTUP_TIMEOUT", type=u64)
 }
@@ -293,7 +322,7 @@ impl<'a> BuckdLifecycle<'a> {
         self.lock.clean_daemon_dir()
     }
 
-    async fn start_server(&self) -> buck2_error::Result<()> {
+    async fn start_server(&self, handoff: Option<&DaemonHandoff>) -> buck2_error::Result<()> {
         let mut args = vec!["--isolation-dir", self.paths.isolation.as_str(), "daemon"];
 
         if self.constraints.is_trace_io_requested() {
@@ -305,6 +334,22 @@ impl<'a> BuckdLifecycle<'a> {
             args.push(r);
         }
 
+        if let Some(prefetch) = &self.constraints.prefetch_target_patterns {
+            if !prefetch.patterns.is_empty() {
+                args.push("--prefetch-working-dir");
+                args.push(&prefetch.working_dir);
+                for pattern in &prefetch.patterns {
+                    args.push("--prefetch-target-pattern");
+                    args.push(pattern);
+                }
+            }
+        }
+
+        if let Some(cursor) = handoff.and_then(|h| h.file_watcher_cursor.as_deref()) {
+            args.push("--file-watcher-cursor");
+            args.push(cursor);
+        }
+
         let mut daemon_env_vars = Vec::new();
 
         daemon_env_vars.push((OsStr::new("RUST_BACKTRACE"), OsStr::new("1")));
@@ -592,9 +637,70 @@ impl BootstrapBuckdClient {
         Pid::from_i64(self.info.pid)
     }
 
-    async fn kill_for_constraints_mismatch(&mut self) -> buck2_error::Result<Pid> {
-        self.kill("client expected different buckd constraints")
-            .await
+    /// Kill the daemon if it has not run a command for at least `idle_for`. The daemon checks
+    /// this itself, so no command can start between the check and the kill. Returns whether the
+    /// daemon was killed.
+    pub async fn kill_if_idle(
+        &mut self,
+        reason: &str,
+        idle_for: Duration,
+    ) -> buck2_error::Result<bool> {
+        kill::kill_if_idle(&mut self.client, &self.info, reason, idle_for).await
+    }
+
+    /// Lets commands other clients are running on the daemon finish before it is restarted, for
+    /// up to `max_wait`.
+    async fn wait_for_active_commands(
+        &mut self,
+        max_wait: Duration,
+        event_subscribers: &mut EventSubscribers<'_>,
+    ) -> buck2_error::Result<()> {
+        let start = Instant::now();
+        let mut reported = false;
+        while start.elapsed() < max_wait {
+            let active = match get_status(&mut self.client).await {
+                Ok(status) => status.active_commands.unwrap_or(0),
+                // Not worth failing the command over: we are about to kill the daemon anyway.
+                Err(_) => return Ok(()),
+            };
+            if active == 0 {
+                return Ok(());
+            }
+            if !reported {
+                event_subscribers
+                    .eprintln(&format!(
+                        "Waiting for {} command(s) running on the buck2 daemon to finish...",
+                        active
+                    ))
+                    .await?;
+                reported = true;
+            }
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        Ok(())
+    }
+
+    /// With `handoff`, the daemon saves its state for the daemon replacing it, and this returns
+    /// what the new daemon should pick up.
+    async fn kill_for_constraints_mismatch(
+        &mut self,
+        handoff: bool,
+    ) -> buck2_error::Result<Option<DaemonHandoff>> {
+        let reason = "client expected different buckd constraints";
+        if !handoff {
+            self.kill(reason).await?;
+            return Ok(None);
+        }
+        let file_watcher_cursor =
+            kill::kill_with_handoff(&mut self.client, &self.info, reason).await?;
+        Ok(Some(DaemonHandoff {
+            file_watcher_cursor,
+            materializer_state_identity: self
+                .constraints
+                .extra
+                .as_ref()
+                .and_then(|e| e.materializer_state_identity.clone()),
+        }))
     }
 
     pub fn pid(&self) -> i64 {
@@ -602,6 +708,14 @@ impl BootstrapBuckdClient {
     }
 }
 
+/// What a daemon killed for a constraint mismatch leaves to the daemon replacing it.
+struct DaemonHandoff {
+    file_watcher_cursor: Option<String>,
+    /// Identity of the materializer state of the killed daemon, to tell whether the new daemon
+    /// kept it.
+    materializer_state_identity: Option<String>,
+}
+
 /// The settings prior to connecting to the Buck daemon.
 /// By default, attempts to connect to a daemon that can satisfy specified constraints.
 /// If the daemon does not match constraints (different version or does not enable I/O tracing),
@@ -732,6 +846,8 @@ async fn establish_connection_inner(
         })
         .await?;
 
+    let mut handoff = None;
+
     // Even if we didn't connect before, it's possible that we just raced with another invocation
     // starting the server, so we try to connect again while holding the lock.
     let daemon_was_started_reason = {
@@ -746,6 +862,15 @@ async fn establish_connection_inner(
                             Err(reason) => reason,
                         };
 
+                        let changes = match reason {
+                            ConstraintUnsatisfiedReason::StartupConfig => {
+                                constraints.startup_config_changes(&client.constraints)
+                            }
+                            _ => Vec::new(),
+                        };
+                        let changes: String =
+                            changes.iter().map(|c| format!("\n  {}", c)).collect();
+
                         if is_nested_invocation(
                             get_possibly_nested_invocation_daemon_uuid().as_ref(),
                             &client.constraints,
@@ -755,6 +880,7 @@ async fn establish_connection_inner(
                                 | ConstraintUnsatisfiedReason::StartupConfig => {
                                     return Err(BuckdConnectError::NestedConstraintMismatch {
                                         reason,
+                                        changes,
                                     }
                                     .into());
                                 }
@@ -764,14 +890,32 @@ async fn establish_connection_inner(
 
                         event_subscribers
                             .eprintln(&format!(
-                                "buck2 daemon constraint mismatch: {reason}; killing daemon..."
+                                "buck2 daemon constraint mismatch: {reason}{changes}"
                             ))
                             .await?;
 
-                        deadline
+                        // Leave the rest of the deadline for the kill and the new daemon startup.
+                        let max_wait = deadline
+                            .half()?
+                            .rem_duration("waiting for commands to finish")?
+                            .min(RESTART_WAIT_FOR_COMMANDS);
+                        client
+                            .wait_for_active_commands(max_wait, event_subscribers)
+                            .await?;
+
+                        event_subscribers.eprintln("Killing daemon...").await?;
+
+                        // The new daemon can start from the materializer state and file watcher
+                        // position of this one, unless that state is what we want to get rid of.
+                        let should_handoff = !matches!(
+                            reason,
+                            ConstraintUnsatisfiedReason::RejectDaemonId
+                                | ConstraintUnsatisfiedReason::MaterializerStateIdentity
+                        );
+                        handoff = deadline
                             .run(
                                 "sending kill command to the Buck daemon",
-                                client.kill_for_constraints_mismatch(),
+                                client.kill_for_constraints_mismatch(should_handoff),
                             )
                             .await?;
 
@@ -831,6 +975,7 @@ async fn establish_connection_inner(
                     &constraints,
                     event_subscribers,
                     daemon_was_started_reason,
+                    handoff.as_ref(),
                 )
             },
         )
@@ -844,6 +989,7 @@ async fn start_new_buckd_and_connect(
     constraints: &DaemonConstraintsRequest,
     event_subscribers: &mut EventSubscribers<'_>,
     daemon_was_started_reason: buck2_data::DaemonWasStartedReason,
+    handoff: Option<&DaemonHandoff>,
 ) -> buck2_error::Result<BootstrapBuckdClient> {
     // Daemon dir may be corrupted. Safer to delete it.
     lifecycle_lock
@@ -851,7 +997,7 @@ async fn start_new_buckd_and_connect(
         .buck_error_context("Cleaning daemon dir")?;
 
     // Now there's definitely no server that can be connected to
-    lifecycle_lock.start_server().await?;
+    lifecycle_lock.start_server(handoff).await?;
     // It might take a little bit for the daemon server to start up. We could wait for the buckd.info
     // file to appear, but it's just as easy to just retry the connection itself.
 
@@ -881,6 +1027,23 @@ async fn start_new_buckd_and_connect(
         .eprintln("Connected to new buck2 daemon.")
         .await?;
 
+    if let Some(handoff) = handoff {
+        let kept = handoff.materializer_state_identity.is_some()
+            && client
+                .constraints
+                .extra
+                .as_ref()
+                .and_then(|e| e.materializer_state_identity.as_ref())
+                == handoff.materializer_state_identity.as_ref();
+        event_subscribers
+            .eprintln(if kept {
+                "Kept the materializer state of the previous daemon."
+            } else {
+                "The new buck2 daemon could not keep the materializer state of the previous one."
+            })
+            .await?;
+    }
+
     Ok(client)
 }
 
@@ -1024,6 +1187,15 @@ impl<'a> BuckdProcessInfo<'a> {
 async fn get_constraints(
     client: &mut DaemonApiClient<InterceptedService<Channel, BuckAddAuthTokenInterceptor>>,
 ) -> buck2_error::Result<buck2_cli_proto::DaemonConstraints> {
+    Ok(get_status(client)
+        .await?
+        .daemon_constraints
+        .unwrap_or_default())
+}
+
+async fn get_status(
+    client: &mut DaemonApiClient<InterceptedService<Channel, BuckAddAuthTokenInterceptor>>,
+) -> buck2_error::Result<buck2_cli_proto::StatusResponse> {
     // NOTE: No tailers in bootstrap client, we capture logs if we fail to connect, but
     // otherwise we leave them alone.
     let status = EventsCtx::new(EventSubscribers::new(vec![Box::new(StdoutStderrForwarder)]))
@@ -1034,14 +1206,12 @@ async fn get_constraints(
         })
         .await?;
 
-    let status: buck2_cli_proto::StatusResponse = match status {
+    match status {
         CommandOutcome::Success(r) => Ok(r),
         CommandOutcome::Failure(_) => {
             Err(buck2_error!([], "Unexpected failure message in status()"))
         }
-    }?;
-
-    Ok(status.daemon_constraints.unwrap_or_default())
+    }
 }
 
 pub fn get_daemon_exe() -> buck2_error::Result<PathBuf> {
@@ -1077,8 +1247,12 @@ enum BuckdConnectError {
         expected: DaemonConstraintsRequest,
         actual: buck2_cli_proto::DaemonConstraints,
     },
-    #[error("buck2 daemon constraint mismatch during nested invocation: {reason}")]
-    NestedConstraintMismatch { reason: ConstraintUnsatisfiedReason },
+    #[error("buck2 daemon constraint mismatch during nested invocation: {reason}{changes}")]
+    NestedConstraintMismatch {
+        reason: ConstraintUnsatisfiedReason,
+        /// Changed startup config settings, one per line.
+        changes: String,
+    },
 }
 
 fn daemon_connect_error(error: buck2_error::Error, paths: &InvocationPaths) -> buck2_error::Error {
@@ -1135,6 +1309,7 @@ mod tests {
             reject_daemon: None,
             reject_materializer_state: None,
             daemon_startup_config: DaemonStartupConfig::testing_empty(),
+            prefetch_target_patterns: None,
         }
     }
 
@@ -1178,6 +1353,7 @@ mod tests {
             nested_invocation_daemon_uuid: None,
             reject_materializer_state: None,
             daemon_startup_config: DaemonStartupConfig::testing_empty(),
+            prefetch_target_patterns: None,
         };
 
         let daemon = buck2_cli_proto::DaemonConstraints {
@@ -1207,6 +1383,7 @@ mod tests {
             nested_invocation_daemon_uuid: None,
             reject_materializer_state: None,
             daemon_startup_config: DaemonStartupConfig::testing_empty(),
+            prefetch_target_patterns: None,
         };
 
         let daemon = buck2_cli_proto::DaemonConstraints {
@@ -1239,6 +1416,7 @@ mod tests {
             nested_invocation_daemon_uuid: None,
             reject_materializer_state: None,
             daemon_startup_config: DaemonStartupConfig::testing_empty(),
+            prefetch_target_patterns: None,
         };
 
         let daemon = buck2_cli_proto::DaemonConstraints {
@@ -1269,6 +1447,7 @@ mod tests {
             reject_daemon: None,
             reject_materializer_state: None,
             daemon_startup_config: DaemonStartupConfig::testing_empty(),
+            prefetch_target_patterns: None,
         };
 
         let daemon = buck2_cli_proto::DaemonConstraints {
@@ -105,6 +105,54 @@ pub(crate) async fn kill(
     info: &DaemonProcessInfo,
     reason: &str,
 ) -> buck2_error::Result<()> {
+    kill_impl(client, info, reason, None, false).await?;
+    Ok(())
+}
+
+/// Kill the daemon to replace it with a new one. The daemon saves its materializer state first.
+/// Returns the position of its file watcher, for the new daemon to start from.
+pub(crate) async fn kill_with_handoff(
+    client: &mut DaemonApiClient<InterceptedService<Channel, BuckAddAuthTokenInterceptor>>,
+    info: &DaemonProcessInfo,
+    reason: &str,
+) -> buck2_error::Result<Option<String>> {
+    Ok(kill_impl(client, info, reason, None, true)
+        .await?
+        .file_watcher_cursor)
+}
+
+/// Kill the daemon if it has not run a command for at least `idle_for`. Returns whether it was
+/// killed.
+pub(crate) async fn kill_if_idle(
+    client: &mut DaemonApiClient<InterceptedService<Channel, BuckAddAuthTokenInterceptor>>,
+    info: &DaemonProcessInfo,
+    reason: &str,
+    idle_for: Duration,
+) -> buck2_error::Result<bool> {
+    Ok(kill_impl(client, info, reason, Some(idle_for), false)
+        .await?
+        .killed)
+}
+
+struct KillOutcome {
+    killed: bool,
+    file_watcher_cursor: Option<String>,
+}
+
+impl KillOutcome {
+    const NOT_KILLED: KillOutcome = KillOutcome {
+        killed: false,
+        file_watcher_cursor: None,
+    };
+}
+
+async fn kill_impl(
+    client: &mut DaemonApiClient<InterceptedService<Channel, BuckAddAuthTokenInterceptor>>,
+    info: &DaemonProcessInfo,
+    reason: &str,
+    only_if_idle_for: Option<Duration>,
+    handoff: bool,
+) -> buck2_error::Result<KillOutcome> {
     let pid = Pid::from_i64(info.pid)?;
     let callers = get_callers_for_kill();
 
@@ -114,26 +162,45 @@ pub(crate) async fn kill(
         reason: reason.to_owned(),
         timeout: Some(GRACEFUL_SHUTDOWN_TIMEOUT.try_into()?),
         callers,
+        only_if_idle_for: only_if_idle_for.map(|d| d.try_into()).transpose()?,
+        handoff,
     }));
     let time_to_kill = GRACEFUL_SHUTDOWN_TIMEOUT + FORCE_SHUTDOWN_TIMEOUT;
     let time_req_sent = Instant::now();
+    let mut file_watcher_cursor = None;
     // First we send a Kill request
     match tokio::time::timeout(KILL_REQUEST_TIMEOUT, request_fut).await {
         Ok(inner_result) => {
             match inner_result {
-                Ok(_) => loop {
-                    if !kill::process_exists(pid)? {
-                        return Ok(());
+                Ok(response) => {
+                    let killed = match response.into_inner().result {
+                        Some(command_result::Result::KillResponse(response)) => {
+                            file_watcher_cursor = response.file_watcher_cursor;
+                            response.killed
+                        }
+                        _ => false,
+                    };
+                    if only_if_idle_for.is_some() && !killed {
+                        // The daemon decided it is not idle, leave it running.
+                        return Ok(KillOutcome::NOT_KILLED);
                     }
-                    if time_req_sent.elapsed() > GRACEFUL_SHUTDOWN_TIMEOUT {
-                        crate::eprintln!(
-                            "Timed out waiting for graceful shutdown of buck2 daemon pid {}",
-                            pid
-                        )?;
-                        break;
+                    loop {
+                        if !kill::process_exists(pid)? {
+                            return Ok(KillOutcome {
+                                killed: true,
+                                file_watcher_cursor,
+                            });
+                        }
+                        if time_req_sent.elapsed() > GRACEFUL_SHUTDOWN_TIMEOUT {
+                            crate::eprintln!(
+                                "Timed out waiting for graceful shutdown of buck2 daemon pid {}",
+                                pid
+                            )?;
+                            break;
+                        }
+                        tokio::time::sleep(Duration::from_millis(100)).await;
                     }
-                    tokio::time::sleep(Duration::from_millis(100)).await;
-                },
+                }
                 Err(e) => {
                     // The kill request can fail if the server is in a bad state and we cannot
                     // authenticate to it.
@@ -142,6 +209,10 @@ pub(crate) async fn kill(
                         pid,
                         e
                     )?;
+                    if only_if_idle_for.is_some() {
+                        // We don't know whether the daemon is idle, so don't force it.
+                        return Ok(KillOutcome::NOT_KILLED);
+                    }
                 }
             }
         }
@@ -151,10 +222,17 @@ pub(crate) async fn kill(
                 "Timed out requesting graceful shutdown of buck2 daemon pid {}",
                 pid
             )?;
+            if only_if_idle_for.is_some() {
+                return Ok(KillOutcome::NOT_KILLED);
+            }
         }
     };
 
-    hard_kill_impl(pid, time_req_sent, time_to_kill).await
+    hard_kill_impl(pid, time_req_sent, time_to_kill).await?;
+    Ok(KillOutcome {
+        killed: true,
+        file_watcher_cursor,
+    })
 }
 
 pub(crate) async fn hard_kill(info: &DaemonProcessInfo) -> buck2_error::Result<()> {
@@ -492,6 +492,12 @@ impl ExitCode {
 
 #[cfg(windows)]
 fn do_exec(command: &mut Command) -> buck2_error::Error {
+    // There is no exec on Windows, so we wait for the child instead. Ignore Ctrl-C while doing so:
+    // the child (which shares our console) gets it too, and decides whether to exit (e.g. a
+    // debugger breaks into the target instead). We then exit with its exit code.
+    unsafe {
+        winapi::um::consoleapi::SetConsoleCtrlHandler(None, winapi::shared::minwindef::TRUE);
+    }
     let status = match command.status() {
         Ok(status) => status,
         Err(e) => return e.into(),
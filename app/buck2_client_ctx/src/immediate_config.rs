@@ -11,7 +11,10 @@ use std::sync::OnceLock;
 use std::time::SystemTime;
 
 use buck2_common::init::DaemonStartupConfig;
-use buck2_common::invocation_roots::find_invocation_roots;
+use buck2_common::invocation_paths_result::InvocationPathsResult;
+use buck2_common::invocation_roots::find_invocation_roots_opt;
+use buck2_common::invocation_roots::invocation_paths_result;
+use buck2_common::invocation_roots::no_buck_root_error;
 use buck2_common::invocation_roots::InvocationRoots;
 use buck2_common::legacy_configs::cells::BuckConfigBasedCells;
 use buck2_core::buck2_env;
@@ -22,16 +25,20 @@ use buck2_core::fs::fs_util;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::abs_path::AbsPath;
-use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::paths::file_name::FileNameBuf;
 use buck2_core::fs::working_dir::AbsWorkingDir;
 use buck2_error::BuckErrorContext;
+use dupe::Dupe;
 use prost::Message;
 
+use crate::command_aliases::CommandAliases;
+use crate::log_exporters::LogExporters;
+
 /// Limited view of the root config. This does not follow includes.
 struct ImmediateConfig {
-    cell_resolver: CellResolver,
-    cwd_cell_alias_resolver: CellAliasResolver,
-    daemon_startup_config: DaemonStartupConfig,
+    cells: BuckConfigBasedCells,
+    command_aliases: CommandAliases,
+    log_exporters: LogExporters,
 }
 
 impl ImmediateConfig {
@@ -45,35 +52,30 @@ impl ImmediateConfig {
             &[],
         ))?;
 
-        let cwd_cell_alias_resolver = futures::executor::block_on(
-            cells.get_cell_alias_resolver_for_cwd_fast(&roots.project_root, &roots.cwd),
-        )?;
-
         Ok(ImmediateConfig {
-            cell_resolver: cells.cell_resolver,
-            cwd_cell_alias_resolver,
-            daemon_startup_config: DaemonStartupConfig::new(&cells.root_config)
-                .buck_error_context("Error loading daemon startup config")?,
+            command_aliases: CommandAliases::from_config(&cells.root_config),
+            log_exporters: LogExporters::from_config(&cells.root_config),
+            cells,
         })
     }
 }
 
-/// Lazy-computed immediate config data. This is produced by reading the root buckconfig (but not
-/// processing any includes).
-struct ImmediateConfigContextData {
-    cell_resolver: CellResolver,
-    cwd_cell_alias_resolver: CellAliasResolver,
-    daemon_startup_config: DaemonStartupConfig,
-    project_filesystem: ProjectRoot,
-}
-
+/// Lazy-computed immediate config data, read from the root buckconfig (but not processing any
+/// includes).
+///
+/// Every part is computed on first use, so that commands only pay for what they need: most
+/// commands not talking to the daemon never find the project, and the others only parse the
+/// buckconfig of the cell of the working directory to resolve cell paths in their arguments.
 pub struct ImmediateConfigContext<'a> {
     // Deliberately use `OnceLock` rather than `Lazy` because `Lazy` forces
     // us to have a shared reference to the underlying `buck2_error::Error` which
     // we cannot use to correct chain the errors. Using `OnceLock` means
     // we don't get the result by a shared reference but instead as local
     // value which can be returned.
-    data: OnceLock<ImmediateConfigContextData>,
+    roots: OnceLock<buck2_error::Result<Option<InvocationRoots>>>,
+    config: OnceLock<ImmediateConfig>,
+    daemon_startup_config: OnceLock<DaemonStartupConfig>,
+    cwd_cell_alias_resolver: OnceLock<CellAliasResolver>,
     cwd: &'a AbsWorkingDir,
     trace: Vec<AbsNormPathBuf>,
 }
@@ -81,7 +83,10 @@ pub struct ImmediateConfigContext<'a> {
 impl<'a> ImmediateConfigContext<'a> {
     pub fn new(cwd: &'a AbsWorkingDir) -> Self {
         Self {
-            data: OnceLock::new(),
+            roots: OnceLock::new(),
+            config: OnceLock::new(),
+            daemon_startup_config: OnceLock::new(),
+            cwd_cell_alias_resolver: OnceLock::new(),
             cwd,
             trace: Vec::new(),
         }
@@ -95,8 +100,43 @@ impl<'a> ImmediateConfigContext<'a> {
         &self.trace
     }
 
+    /// The invocation paths, sharing the search for the project root with the config.
+    pub fn invocation_paths_result(&self, isolation: FileNameBuf) -> InvocationPathsResult {
+        invocation_paths_result(self.cwd, self.roots_opt().clone(), isolation)
+    }
+
     pub fn daemon_startup_config(&self) -> buck2_error::Result<&DaemonStartupConfig> {
-        Ok(&self.data()?.daemon_startup_config)
+        self.daemon_startup_config.get_or_try_init(|| {
+            let roots = self.roots()?;
+            let paranoid_info_path = roots.paranoid_info_path()?;
+
+            let mut daemon_startup_config =
+                DaemonStartupConfig::new(&self.config()?.cells.root_config)
+                    .buck_error_context("Error loading daemon startup config")?;
+
+            match is_paranoid_enabled(&paranoid_info_path) {
+                Ok(paranoid) => {
+                    daemon_startup_config.paranoid = paranoid;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to determine whether paranoid is enabled in `{}`: {:#}",
+                        paranoid_info_path,
+                        e
+                    );
+                }
+            };
+
+            buck2_error::Ok(daemon_startup_config)
+        })
+    }
+
+    pub fn command_aliases(&self) -> buck2_error::Result<&CommandAliases> {
+        Ok(&self.config()?.command_aliases)
+    }
+
+    pub fn log_exporters(&self) -> buck2_error::Result<&LogExporters> {
+        Ok(&self.config()?.log_exporters)
     }
 
     /// Resolves a cell path (i.e., contains `//`) into an absolute path. The cell path must have
@@ -109,55 +149,54 @@ impl<'a> ImmediateConfigContext<'a> {
         cell_alias: &str,
         cell_relative_path: &str,
     ) -> buck2_error::Result<AbsNormPathBuf> {
-        let data = self.data()?;
-
-        let cell = data.cwd_cell_alias_resolver.resolve(cell_alias)?;
-        let cell = data.cell_resolver.get(cell)?;
-        let path = cell.path().join_normalized(cell_relative_path)?;
-        Ok(data.project_filesystem.resolve(&path))
+        let path = self
+            .resolve_alias_to_path_in_cwd(cell_alias)?
+            .join_normalized(cell_relative_path)?;
+        Ok(self.roots()?.project_root.resolve(&path))
     }
 
     pub fn resolve_alias_to_path_in_cwd(
         &self,
         alias: &str,
     ) -> buck2_error::Result<CellRootPathBuf> {
-        let data = self.data()?;
-        let cell = data.cwd_cell_alias_resolver.resolve(alias)?;
-        Ok(data.cell_resolver.get(cell)?.path().to_buf())
+        let cell = self.cwd_cell_alias_resolver()?.resolve(alias)?;
+        Ok(self.cell_resolver()?.get(cell)?.path().to_buf())
+    }
+
+    fn roots_opt(&self) -> &buck2_error::Result<Option<InvocationRoots>> {
+        self.roots
+            .get_or_init(|| find_invocation_roots_opt(self.cwd))
+    }
+
+    fn roots(&self) -> buck2_error::Result<&InvocationRoots> {
+        match self.roots_opt() {
+            Ok(Some(roots)) => Ok(roots),
+            Ok(None) => Err(no_buck_root_error(self.cwd)),
+            Err(e) => Err(e.dupe()),
+        }
+    }
+
+    fn config(&self) -> buck2_error::Result<&ImmediateConfig> {
+        self.config
+            .get_or_try_init(|| ImmediateConfig::parse(self.roots()?))
+            .buck_error_context("Error creating cell resolver")
+    }
+
+    fn cell_resolver(&self) -> buck2_error::Result<&CellResolver> {
+        Ok(&self.config()?.cells.cell_resolver)
     }
 
-    fn data(&self) -> buck2_error::Result<&ImmediateConfigContextData> {
-        self.data
+    /// Parses the buckconfig of the cell of the working directory, when it is not the root cell.
+    fn cwd_cell_alias_resolver(&self) -> buck2_error::Result<&CellAliasResolver> {
+        self.cwd_cell_alias_resolver
             .get_or_try_init(|| {
-                let roots = find_invocation_roots(self.cwd)?;
-                let paranoid_info_path = roots.paranoid_info_path()?;
-
-                // See comment in `ImmediateConfig` about why we use `OnceLock` rather than `Lazy`
-                let cfg = ImmediateConfig::parse(&roots)?;
-
-                // It'd be nice to deal with this a little differently by having this be a separate
-                // type.
-                let mut daemon_startup_config = cfg.daemon_startup_config;
-
-                match is_paranoid_enabled(&paranoid_info_path) {
-                    Ok(paranoid) => {
-                        daemon_startup_config.paranoid = paranoid;
-                    }
-                    Err(e) => {
-                        tracing::warn!(
-                            "Failed to determine whether paranoid is enabled in `{}`: {:#}",
-                            paranoid_info_path,
-                            e
-                        );
-                    }
-                };
-
-                buck2_error::Ok(ImmediateConfigContextData {
-                    cell_resolver: cfg.cell_resolver,
-                    cwd_cell_alias_resolver: cfg.cwd_cell_alias_resolver,
-                    daemon_startup_config,
-                    project_filesystem: roots.project_root,
-                })
+                let roots = self.roots()?;
+                // This function is non-reentrant, and blocking for a bit should be ok
+                futures::executor::block_on(
+                    self.config()?
+                        .cells
+                        .get_cell_alias_resolver_for_cwd_fast(&roots.project_root, &roots.cwd),
+                )
             })
             .buck_error_context("Error creating cell resolver")
     }
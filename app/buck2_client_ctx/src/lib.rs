@@ -22,6 +22,7 @@ pub mod argfiles;
 pub mod client_cpu_tracker;
 pub mod client_ctx;
 pub mod client_metadata;
+pub mod command_aliases;
 pub mod command_outcome;
 pub mod common;
 pub mod console_interaction_stream;
@@ -33,6 +34,7 @@ pub mod file_tailers;
 pub mod final_console;
 pub mod ide_support;
 pub mod immediate_config;
+pub mod log_exporters;
 pub mod output_destination_arg;
 pub mod path_arg;
 pub mod query_args;
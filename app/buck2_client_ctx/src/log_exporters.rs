@@ -0,0 +1,92 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Event log exporters from the `[log_exporters]` section of the root `.buckconfig`:
+//!
+//! ```ini
+//! [log_exporters]
+//! datadog = python3 tools/export_to_datadog.py --site=us5
+//! ```
+//!
+//! With this, `buck2 log export --exporter datadog` streams the events of the last command to
+//! that command.
+
+use std::collections::BTreeMap;
+
+use buck2_common::legacy_configs::configs::LegacyBuckConfig;
+
+#[derive(buck2_error::Error, Debug)]
+#[buck2(input)]
+enum LogExporterError {
+    #[error("Unknown log exporter `{0}`, known exporters: [{}]", .1.join(", "))]
+    Unknown(String, Vec<String>),
+    #[error("Log exporter `{0}` is empty")]
+    Empty(String),
+    #[error("Log exporter `{0}` has unbalanced quotes: `{1}`")]
+    InvalidQuoting(String, String),
+}
+
+#[derive(Default, Debug)]
+pub struct LogExporters {
+    /// Exporter name to the unsplit command line running it.
+    exporters: BTreeMap<String, String>,
+}
+
+impl LogExporters {
+    const SECTION: &'static str = "log_exporters";
+
+    pub fn from_config(config: &LegacyBuckConfig) -> Self {
+        let exporters = match config.get_section(Self::SECTION) {
+            Some(section) => section
+                .iter()
+                .map(|(name, value)| (name.to_owned(), value.as_str().to_owned()))
+                .collect(),
+            None => BTreeMap::new(),
+        };
+        Self { exporters }
+    }
+
+    /// The command line of the exporter called `name`.
+    pub fn command(&self, name: &str) -> buck2_error::Result<Vec<String>> {
+        let Some(value) = self.exporters.get(name) else {
+            return Err(LogExporterError::Unknown(
+                name.to_owned(),
+                self.exporters.keys().cloned().collect(),
+            )
+            .into());
+        };
+        let command = shlex::split(value)
+            .ok_or_else(|| LogExporterError::InvalidQuoting(name.to_owned(), value.clone()))?;
+        if command.is_empty() {
+            return Err(LogExporterError::Empty(name.to_owned()).into());
+        }
+        Ok(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command() {
+        let exporters = LogExporters {
+            exporters: [("datadog", "python3 export.py --tag='a b'"), ("empty", "")]
+                .iter()
+                .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+                .collect(),
+        };
+        assert_eq!(
+            exporters.command("datadog").unwrap(),
+            vec!["python3", "export.py", "--tag=a b"]
+        );
+        assert!(exporters.command("empty").is_err());
+        assert!(exporters.command("bigquery").is_err());
+    }
+}
@@ -24,15 +24,18 @@ use crate::daemon::client::connect::BuckdConnectConstraints;
 use crate::daemon::client::connect::BuckdConnectOptions;
 use crate::daemon::client::connect::DaemonConstraintsRequest;
 use crate::daemon::client::connect::DesiredTraceIoState;
+use crate::daemon::client::connect::PrefetchTargetPatterns;
 use crate::daemon::client::BuckdClientConnector;
 use crate::exit_result::ExitCode;
 use crate::exit_result::ExitResult;
 use crate::path_arg::PathArg;
 use crate::signal_handler::with_simple_sigint_handler;
+use crate::subscribers::console_health::ConsoleHealth;
 use crate::subscribers::get::get_console_with_root;
 use crate::subscribers::get::try_get_build_graph_stats;
 use crate::subscribers::get::try_get_build_id_writer;
 use crate::subscribers::get::try_get_event_log_subscriber;
+use crate::subscribers::get::try_get_otlp_traces;
 use crate::subscribers::get::try_get_re_log_subscriber;
 use crate::subscribers::recorder::try_get_invocation_recorder;
 use crate::subscribers::subscriber::EventSubscriber;
@@ -49,10 +52,12 @@ fn default_subscribers<'a, T: StreamingCommand>(
     // Need this to get information from one subscriber (event_log)
     // and log it in another (invocation_recorder)
     let log_size_counter_bytes = Some(Arc::new(AtomicU64::new(0)));
+    // Likewise from the console to the invocation recorder.
+    let console_health = Arc::new(ConsoleHealth::default());
 
-    let build_count_dir = match ctx.paths() {
-        Ok(paths) => Some(paths.build_count_dir()),
-        Err(_) => None,
+    let (build_count_dir, log_dir) = match ctx.paths() {
+        Ok(paths) => (Some(paths.build_count_dir()), Some(paths.log_dir())),
+        Err(_) => (None, None),
     };
     subscribers.push(get_console_with_root(
         ctx.trace_id.dupe(),
@@ -63,6 +68,8 @@ fn default_subscribers<'a, T: StreamingCommand>(
         T::COMMAND_NAME,
         console_opts.superconsole_config(),
         build_count_dir,
+        log_dir,
+        Some(console_health.dupe()),
     )?);
 
     if let Some(event_log) = try_get_event_log_subscriber(cmd, ctx, log_size_counter_bytes.clone())?
@@ -72,6 +79,9 @@ fn default_subscribers<'a, T: StreamingCommand>(
     if let Some(re_log) = try_get_re_log_subscriber(ctx)? {
         subscribers.push(re_log)
     }
+    if let Some(otlp_traces) = try_get_otlp_traces()? {
+        subscribers.push(otlp_traces)
+    }
     if let Some(build_id_writer) = try_get_build_id_writer(cmd.event_log_opts(), ctx)? {
         subscribers.push(build_id_writer)
     }
@@ -84,6 +94,7 @@ fn default_subscribers<'a, T: StreamingCommand>(
         cmd.logging_name(),
         cmd.sanitize_argv(ctx.argv.clone()).argv,
         log_size_counter_bytes,
+        console_health,
     )?;
     subscribers.push(recorder);
 
@@ -119,6 +130,12 @@ pub trait StreamingCommand: Sized + Send + Sync {
         DesiredTraceIoState::Existing
     }
 
+    /// Target patterns the command loads. If a daemon is started for the command, it prefetches
+    /// their build files while it starts.
+    fn prefetch_target_patterns(&self) -> &[String] {
+        &[]
+    }
+
     fn console_opts(&self) -> &CommonConsoleOptions;
 
     fn event_log_opts(&self) -> &CommonEventLogOptions;
@@ -188,6 +205,12 @@ impl<T: StreamingCommand> BuckSubcommand for T {
                 let mut req =
                     DaemonConstraintsRequest::new(ctx.immediate_config, T::trace_io(&self))?;
                 ctx.restarter.apply_to_constraints(&mut req);
+                if let Ok(working_dir) = ctx.working_dir.path().to_str() {
+                    req.prefetch_target_patterns = Some(PrefetchTargetPatterns {
+                        working_dir: working_dir.to_owned(),
+                        patterns: self.prefetch_target_patterns().to_vec(),
+                    });
+                }
                 BuckdConnectConstraints::Constraints(req)
             };
 
@@ -10,10 +10,17 @@
 pub(crate) mod build_graph_stats;
 pub(crate) mod build_id_writer;
 pub(crate) mod classify_server_stderr;
+pub mod console_health;
 pub(crate) mod errorconsole;
 pub mod event_log;
+pub(crate) mod failed_action_output;
 pub mod get;
+pub(crate) mod invocation_records;
+pub(crate) mod json_events_console;
+pub(crate) mod memory_pressure;
+pub(crate) mod network_timeline;
 pub(crate) mod observer;
+pub(crate) mod otlp;
 pub mod re_log;
 pub mod recorder;
 pub(crate) mod simpleconsole;
@@ -22,3 +29,4 @@ pub mod subscriber;
 pub mod subscribers;
 pub mod superconsole;
 pub(crate) mod system_warning;
+pub mod target_report;
@@ -7,12 +7,15 @@
  * of this source tree.
  */
 
+use std::sync::Arc;
 use std::time::Duration;
 use std::time::SystemTime;
 
 use async_trait::async_trait;
 use buck2_cli_proto::command_result;
 use buck2_events::sink::remote::new_remote_event_sink_if_enabled;
+use buck2_events::sink::remote::RemoteEventSink;
+use buck2_events::BuckEvent;
 use buck2_wrapper_common::invocation_id::TraceId;
 use dupe::Dupe;
 use fbinit::FacebookInit;
@@ -22,17 +25,26 @@ use crate::subscribers::subscriber::EventSubscriber;
 pub struct BuildGraphStats {
     fb: FacebookInit,
     trace_id: TraceId,
+    /// Cleared if the command is not sampled by `buck2.telemetry_sample_rate`.
+    sampled: bool,
 }
 
 impl BuildGraphStats {
     pub fn new(fb: FacebookInit, trace_id: TraceId) -> Self {
-        Self { fb, trace_id }
+        Self {
+            fb,
+            trace_id,
+            sampled: true,
+        }
     }
 
     async fn handle_build_response(
         &self,
         res: &buck2_cli_proto::BuildResponse,
     ) -> buck2_error::Result<()> {
+        if !self.sampled {
+            return Ok(());
+        }
         let events = self.build_graph_stats_from_build_response(res);
         self.send_events(events).await;
 
@@ -88,6 +100,19 @@ impl BuildGraphStats {
 
 #[async_trait]
 impl EventSubscriber for BuildGraphStats {
+    async fn handle_events(&mut self, events: &[Arc<BuckEvent>]) -> buck2_error::Result<()> {
+        for event in events {
+            if let buck2_data::buck_event::Data::Instant(instant) = event.data() {
+                if let Some(buck2_data::instant_event::Data::TelemetrySampling(sampling)) =
+                    &instant.data
+                {
+                    self.sampled = sampling.sampled;
+                }
+            }
+        }
+        Ok(())
+    }
+
     async fn handle_command_result(
         &mut self,
         result: &buck2_cli_proto::CommandResult,
@@ -0,0 +1,54 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+/// Tag of commands whose superconsole failed to render or fell back to the simple console.
+pub(crate) const CONSOLE_DEGRADED_TAG: &str = "superconsole_degraded";
+
+/// How well the superconsole rendered, updated by the console and read by the invocation recorder.
+#[derive(Default)]
+pub struct ConsoleHealth {
+    dropped_frames: AtomicU64,
+    render_errors: AtomicU64,
+    fell_back_to_simple_console: AtomicBool,
+}
+
+impl ConsoleHealth {
+    pub(crate) fn set_dropped_frames(&self, dropped_frames: u64) {
+        self.dropped_frames.store(dropped_frames, Ordering::Relaxed);
+    }
+
+    pub(crate) fn render_error(&self) {
+        self.render_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn fall_back_to_simple_console(&self) {
+        self.fell_back_to_simple_console
+            .store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn dropped_frames(&self) -> u64 {
+        self.dropped_frames.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn render_errors(&self) -> u64 {
+        self.render_errors.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn fell_back_to_simple_console(&self) -> bool {
+        self.fell_back_to_simple_console.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn is_degraded(&self) -> bool {
+        self.render_errors() > 0 || self.fell_back_to_simple_console()
+    }
+}
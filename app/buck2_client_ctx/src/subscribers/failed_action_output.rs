@@ -0,0 +1,181 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Files with the output of failed actions.
+//!
+//! Long compiler errors are hard to read from the console, and easily lost to its scrollback.
+//! With `ui.failed_action_output_files`, the stdout and stderr of the last command of every
+//! failed action are written to `buck-out/log/<trace-id>/failed_actions/`, and the console prints
+//! their paths next to the error.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+use buck2_event_observer::display::display_action_key;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_wrapper_common::invocation_id::TraceId;
+
+/// File names longer than this are shortened, and made unique with a hash.
+const MAX_FILE_STEM_LEN: usize = 150;
+
+pub(crate) struct FailedActionOutputFiles {
+    /// `buck-out/log/<trace-id>/failed_actions`.
+    dir: AbsNormPathBuf,
+    /// Set from the console preferences.
+    enabled: bool,
+}
+
+impl FailedActionOutputFiles {
+    pub(crate) fn new(log_dir: &AbsNormPath, trace_id: &TraceId) -> Self {
+        Self {
+            dir: log_dir
+                .join(ForwardRelativePath::unchecked_new(&trace_id.to_string()))
+                .join(ForwardRelativePath::unchecked_new("failed_actions")),
+            enabled: false,
+        }
+    }
+
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// The files the output of the last command of a failed action is written to, along with the
+    /// name of the stream. Empty streams have no file.
+    pub(crate) fn paths(
+        &self,
+        error: &buck2_data::ActionError,
+    ) -> buck2_error::Result<Vec<(&'static str, AbsNormPathBuf)>> {
+        Ok(self
+            .outputs(error)?
+            .into_iter()
+            .map(|(stream, path, _)| (stream, path))
+            .collect())
+    }
+
+    /// Write the files returned by [`Self::paths`].
+    pub(crate) fn write(&self, error: &buck2_data::ActionError) -> buck2_error::Result<()> {
+        let outputs = self.outputs(error)?;
+        if !outputs.is_empty() {
+            fs_util::create_dir_all(&self.dir)?;
+        }
+        for (_, path, output) in outputs {
+            fs_util::write(&path, output)?;
+        }
+        Ok(())
+    }
+
+    fn outputs<'a>(
+        &self,
+        error: &'a buck2_data::ActionError,
+    ) -> buck2_error::Result<Vec<(&'static str, AbsNormPathBuf, &'a str)>> {
+        let Some(details) = error.last_command.as_ref().and_then(|c| c.details.as_ref()) else {
+            return Ok(Vec::new());
+        };
+        if !self.enabled {
+            return Ok(Vec::new());
+        }
+        let stem = file_stem(error)?;
+        Ok([("stdout", &details.stdout), ("stderr", &details.stderr)]
+            .into_iter()
+            .filter(|(_, output)| !output.is_empty())
+            .map(|(stream, output)| {
+                let name = format!("{}.{}", stem, stream);
+                let path = self.dir.join(ForwardRelativePath::unchecked_new(&name));
+                (stream, path, output.as_str())
+            })
+            .collect())
+    }
+}
+
+/// A file name identifying the action, which does not depend on the order actions fail in.
+fn file_stem(error: &buck2_data::ActionError) -> buck2_error::Result<String> {
+    let mut identity = match &error.key {
+        Some(key) => display_action_key(key, TargetDisplayOptions::for_log())?,
+        None => String::new(),
+    };
+    if let Some(name) = &error.name {
+        identity.push('-');
+        identity.push_str(&name.category);
+        if !name.identifier.is_empty() {
+            identity.push('-');
+            identity.push_str(&name.identifier);
+        }
+    }
+
+    let mut stem: String = identity
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if stem.len() > MAX_FILE_STEM_LEN {
+        let mut hasher = DefaultHasher::new();
+        identity.hash(&mut hasher);
+        stem.truncate(MAX_FILE_STEM_LEN - 17);
+        stem = format!("{}-{:016x}", stem, hasher.finish());
+    }
+    Ok(stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::subscribers::failed_action_output::file_stem;
+    use crate::subscribers::failed_action_output::MAX_FILE_STEM_LEN;
+
+    fn action_error(identifier: &str) -> buck2_data::ActionError {
+        buck2_data::ActionError {
+            key: Some(buck2_data::ActionKey {
+                id: vec![],
+                key: "key".to_owned(),
+                owner: Some(buck2_data::action_key::Owner::TargetLabel(
+                    buck2_data::ConfiguredTargetLabel {
+                        label: Some(buck2_data::TargetLabel {
+                            package: "root//foo".to_owned(),
+                            name: "bar".to_owned(),
+                        }),
+                        configuration: Some(buck2_data::Configuration {
+                            full_name: "cfg".to_owned(),
+                        }),
+                        execution_configuration: None,
+                    },
+                )),
+            }),
+            name: Some(buck2_data::ActionName {
+                category: "cxx_compile".to_owned(),
+                identifier: identifier.to_owned(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_file_stem() {
+        let stem = file_stem(&action_error("src/a b.cpp")).unwrap();
+        assert!(stem.ends_with("-cxx_compile-src_a_b.cpp"), "{}", stem);
+        assert!(!stem.contains('/'), "{}", stem);
+    }
+
+    #[test]
+    fn test_long_file_stem() {
+        let a = file_stem(&action_error(&"a".repeat(200))).unwrap();
+        let b = file_stem(&action_error(&format!("{}b", "a".repeat(199)))).unwrap();
+        assert_eq!(MAX_FILE_STEM_LEN, a.len());
+        assert_eq!(MAX_FILE_STEM_LEN, b.len());
+        assert_ne!(a, b);
+    }
+}
@@ -22,8 +22,11 @@ use crate::common::CommonEventLogOptions;
 use crate::streaming::StreamingCommand;
 use crate::subscribers::build_graph_stats::BuildGraphStats;
 use crate::subscribers::build_id_writer::BuildIdWriter;
+use crate::subscribers::console_health::ConsoleHealth;
 use crate::subscribers::errorconsole::ErrorConsole;
 use crate::subscribers::event_log::EventLog;
+use crate::subscribers::json_events_console::JsonEventsConsole;
+use crate::subscribers::otlp::OtlpTraces;
 use crate::subscribers::re_log::ReLog;
 use crate::subscribers::simpleconsole::SimpleConsole;
 use crate::subscribers::subscriber::EventSubscriber;
@@ -31,6 +34,8 @@ use crate::subscribers::superconsole::StatefulSuperConsole;
 use crate::subscribers::superconsole::SuperConsoleConfig;
 
 /// Given a command name and the command arguments, create a default console / superconsole.
+/// `log_dir` is where the output of failed actions is written to, when enabled. The superconsole
+/// reports how well it renders to `console_health`.
 pub fn get_console_with_root(
     trace_id: TraceId,
     console_type: ConsoleType,
@@ -40,6 +45,8 @@ pub fn get_console_with_root(
     command_name: &str,
     config: SuperConsoleConfig,
     build_count_dir: Option<AbsNormPathBuf>,
+    log_dir: Option<AbsNormPathBuf>,
+    console_health: Option<Arc<ConsoleHealth>>,
 ) -> buck2_error::Result<Box<dyn EventSubscriber>> {
     match console_type {
         ConsoleType::Simple => Ok(Box::new(
@@ -48,7 +55,8 @@ pub fn get_console_with_root(
                 verbosity,
                 expect_spans,
                 build_count_dir,
-            ),
+            )
+            .with_failed_action_output_files(log_dir),
         )),
         ConsoleType::SimpleNoTty => Ok(Box::new(
             SimpleConsole::<NoopEventObserverExtra>::without_tty(
@@ -56,24 +64,32 @@ pub fn get_console_with_root(
                 verbosity,
                 expect_spans,
                 build_count_dir,
-            ),
+            )
+            .with_failed_action_output_files(log_dir),
+        )),
+        ConsoleType::SimpleTty => Ok(Box::new(
+            SimpleConsole::<NoopEventObserverExtra>::with_tty(
+                trace_id,
+                verbosity,
+                expect_spans,
+                build_count_dir,
+            )
+            .with_failed_action_output_files(log_dir),
+        )),
+        ConsoleType::Super => Ok(Box::new(
+            StatefulSuperConsole::new_with_root_forced(
+                trace_id,
+                command_name,
+                verbosity,
+                expect_spans,
+                replay_speed,
+                None,
+                config,
+                build_count_dir,
+            )?
+            .with_failed_action_output_files(log_dir)
+            .with_console_health(console_health.unwrap_or_default()),
         )),
-        ConsoleType::SimpleTty => Ok(Box::new(SimpleConsole::<NoopEventObserverExtra>::with_tty(
-            trace_id,
-            verbosity,
-            expect_spans,
-            build_count_dir,
-        ))),
-        ConsoleType::Super => Ok(Box::new(StatefulSuperConsole::new_with_root_forced(
-            trace_id,
-            command_name,
-            verbosity,
-            expect_spans,
-            replay_speed,
-            None,
-            config,
-            build_count_dir,
-        )?)),
         ConsoleType::Auto => {
             match StatefulSuperConsole::new_with_root(
                 trace_id.dupe(),
@@ -84,18 +100,24 @@ pub fn get_console_with_root(
                 config,
                 build_count_dir.clone(),
             )? {
-                Some(super_console) => Ok(Box::new(super_console)),
+                Some(super_console) => Ok(Box::new(
+                    super_console
+                        .with_failed_action_output_files(log_dir)
+                        .with_console_health(console_health.unwrap_or_default()),
+                )),
                 None => Ok(Box::new(
                     SimpleConsole::<NoopEventObserverExtra>::autodetect(
                         trace_id,
                         verbosity,
                         expect_spans,
                         build_count_dir,
-                    ),
+                    )
+                    .with_failed_action_output_files(log_dir),
                 )),
             }
         }
         ConsoleType::None => Ok(Box::new(ErrorConsole)),
+        ConsoleType::JsonEvents => Ok(Box::new(JsonEventsConsole::new())),
     }
 }
 
@@ -139,6 +161,11 @@ pub(crate) fn try_get_re_log_subscriber<'a>(
     Ok(Some(Box::new(log)))
 }
 
+pub(crate) fn try_get_otlp_traces<'a>() -> buck2_error::Result<Option<Box<dyn EventSubscriber + 'a>>>
+{
+    Ok(OtlpTraces::from_env()?.map(|t| Box::new(t) as _))
+}
+
 pub(crate) fn try_get_build_id_writer<'a>(
     opts: &CommonEventLogOptions,
     ctx: &ClientCommandContext<'a>,
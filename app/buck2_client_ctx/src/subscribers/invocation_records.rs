@@ -0,0 +1,162 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Local files of invocation records, written if `buck2.invocation_records` is enabled.
+//!
+//! The record of every command is appended as a line of JSON, in the format of
+//! `--unstable-write-invocation-record`, to the newest file of
+//! `buck-out/<isolation>/invocation_records`. A new file is started when the newest one would
+//! exceed the configured size, and the oldest files are deleted past the configured number of
+//! files. Records are written with a single append, so that concurrent commands don't interleave
+//! their lines.
+
+use std::io::Write;
+
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::file_name::FileNameBuf;
+use buck2_error::BuckErrorContext;
+use buck2_wrapper_common::invocation_id::TraceId;
+use chrono::DateTime;
+use chrono::Utc;
+
+const FILE_PREFIX: &str = "invocation_records_";
+const FILE_EXTENSION: &str = ".jsonl";
+
+/// Append `record`, which is serialized JSON, to the newest file of `dir`.
+pub(crate) fn append_invocation_record(
+    dir: &AbsNormPath,
+    sink: &buck2_data::InvocationRecordSink,
+    now: DateTime<Utc>,
+    trace_id: &TraceId,
+    record: &[u8],
+) -> buck2_error::Result<()> {
+    let mut line = Vec::with_capacity(record.len() + 1);
+    line.extend_from_slice(record);
+    line.push(b'\n');
+
+    fs_util::create_dir_all(dir)?;
+    let mut files = record_files(dir)?;
+    let newest = match files.last() {
+        Some(newest) => {
+            let size = fs_util::metadata(newest)?.len();
+            (size == 0 || size + line.len() as u64 <= sink.max_file_bytes).then(|| newest.clone())
+        }
+        None => None,
+    };
+    let path = match newest {
+        Some(path) => path,
+        None => {
+            // Sort order matters here: the oldest files are lexicographically first.
+            let path = dir.join(FileNameBuf::try_from(format!(
+                "{}{}_{}{}",
+                FILE_PREFIX,
+                now.format("%Y%m%d-%H%M%S"),
+                trace_id,
+                FILE_EXTENSION
+            ))?);
+            files.push(path.clone());
+            path
+        }
+    };
+
+    let mut out = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_buck_error_context(|| format!("Error opening `{}`", path))?;
+    out.write_all(&line)
+        .with_buck_error_context(|| format!("Error writing `{}`", path))?;
+
+    let excess = files.len().saturating_sub(sink.max_files.max(1) as usize);
+    for file in &files[..excess] {
+        // Another command may have removed it already.
+        fs_util::remove_file(file).ok();
+    }
+    Ok(())
+}
+
+/// Files of invocation records in `dir`, from oldest to newest.
+fn record_files(dir: &AbsNormPath) -> buck2_error::Result<Vec<AbsNormPathBuf>> {
+    let Some(entries) = fs_util::read_dir_if_exists(dir)? else {
+        return Ok(Vec::new());
+    };
+    let mut files: Vec<(String, AbsNormPathBuf)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            (name.starts_with(FILE_PREFIX) && name.ends_with(FILE_EXTENSION))
+                .then(|| (name, entry.path()))
+        })
+        .collect();
+    files.sort();
+    Ok(files.into_iter().map(|(_, path)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+    use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
+    use buck2_wrapper_common::invocation_id::TraceId;
+    use chrono::TimeZone;
+    use chrono::Utc;
+
+    use crate::subscribers::invocation_records::append_invocation_record;
+    use crate::subscribers::invocation_records::record_files;
+
+    #[test]
+    fn test_append_invocation_record() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = AbsNormPathBuf::new(tempdir.path().canonicalize().unwrap())
+            .unwrap()
+            .join(ForwardRelativePath::unchecked_new("invocation_records"));
+        let sink = buck2_data::InvocationRecordSink {
+            max_file_bytes: 26,
+            max_files: 2,
+        };
+        let record = |i: i64| {
+            let now = Utc.timestamp_opt(1_700_000_000 + i, 0).unwrap();
+            append_invocation_record(
+                &dir,
+                &sink,
+                now,
+                &TraceId::new(),
+                format!("{{\"record\":{}}}", i).as_bytes(),
+            )
+            .unwrap();
+        };
+
+        // Two records of 13 bytes per file.
+        record(0);
+        record(1);
+        assert_eq!(1, record_files(&dir).unwrap().len());
+        record(2);
+        let files = record_files(&dir).unwrap();
+        assert_eq!(2, files.len());
+        assert_eq!(
+            "{\"record\":0}\n{\"record\":1}\n",
+            std::fs::read_to_string(&files[0]).unwrap()
+        );
+
+        // The oldest file is deleted past 2 files.
+        record(3);
+        record(4);
+        let files = record_files(&dir).unwrap();
+        assert_eq!(2, files.len());
+        assert_eq!(
+            "{\"record\":2}\n{\"record\":3}\n",
+            std::fs::read_to_string(&files[0]).unwrap()
+        );
+        assert_eq!(
+            "{\"record\":4}\n",
+            std::fs::read_to_string(&files[1]).unwrap()
+        );
+    }
+}
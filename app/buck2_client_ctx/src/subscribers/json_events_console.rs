@@ -0,0 +1,228 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use buck2_event_observer::display;
+use buck2_event_observer::display::TargetDisplayOptions;
+use buck2_event_observer::target_status::TargetStatusTracker;
+use buck2_event_observer::unpack_event::unpack_event;
+use buck2_event_observer::unpack_event::UnpackedBuckEvent;
+use buck2_events::BuckEvent;
+use gazebo::variants::VariantName;
+use serde::Serialize;
+
+use crate::subscribers::subscriber::EventSubscriber;
+
+/// Events printed by `--console json-events`, one JSON object per line on stderr. Stdout is left
+/// to the output of the command, which this must not interleave with.
+///
+/// This is a small, stable subset of what happens during a command for tools that embed buck2.
+/// Fields may be added, but existing ones will not be renamed or removed.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum JsonEvent {
+    /// The command was accepted by the daemon.
+    BuildStarted { trace_id: String, command: String },
+    /// A requested configured target finished building.
+    TargetFinished {
+        target: String,
+        configuration: String,
+        /// One of `success`, `failure`, `dep_failed`, `skipped` or `cached`.
+        status: &'static str,
+        /// Paths relative to the project root.
+        outputs: Vec<String>,
+    },
+    /// An action failed. `message` is the same text the other consoles show.
+    ActionFailed { action: String, message: String },
+    /// The command finished. Always the last event.
+    BuildFinished {
+        success: bool,
+        duration_ms: Option<u64>,
+        errors: Vec<String>,
+    },
+}
+
+impl JsonEvent {
+    fn to_json_line(&self) -> buck2_error::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+struct CommandEndInfo {
+    success: bool,
+    duration: Option<Duration>,
+    errors: Vec<String>,
+}
+
+/// This console is what is used for `--console json-events`.
+pub(crate) struct JsonEventsConsole {
+    target_status: TargetStatusTracker,
+    command_end: Option<CommandEndInfo>,
+    finished: bool,
+}
+
+impl JsonEventsConsole {
+    pub(crate) fn new() -> Self {
+        Self {
+            target_status: TargetStatusTracker::default(),
+            command_end: None,
+            finished: false,
+        }
+    }
+
+    fn emit(&mut self, event: JsonEvent) -> buck2_error::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        if matches!(event, JsonEvent::BuildFinished { .. }) {
+            self.finished = true;
+        }
+        // Stderr is unbuffered, so each event reaches consumers as soon as it is emitted. Write the
+        // line in one go so it is not split by other writes to stderr.
+        let mut line = event.to_json_line()?;
+        line.push('\n');
+        crate::eprint!("{}", line)
+    }
+
+    fn handle_event(&mut self, event: &BuckEvent) -> buck2_error::Result<()> {
+        match unpack_event(event)? {
+            UnpackedBuckEvent::SpanStart(_, _, buck2_data::span_start_event::Data::Command(c)) => {
+                self.emit(JsonEvent::BuildStarted {
+                    trace_id: event.trace_id()?.to_string(),
+                    command: c
+                        .data
+                        .as_ref()
+                        .map_or("unknown", |d| d.variant_name())
+                        .to_owned(),
+                })
+            }
+            UnpackedBuckEvent::SpanEnd(_, end, buck2_data::span_end_event::Data::Command(c)) => {
+                self.command_end = Some(CommandEndInfo {
+                    success: c.is_success,
+                    duration: end
+                        .duration
+                        .as_ref()
+                        .and_then(|d| Duration::try_from(d.clone()).ok()),
+                    errors: c.errors.iter().map(|e| e.message.clone()).collect(),
+                });
+                Ok(())
+            }
+            UnpackedBuckEvent::SpanEnd(
+                _,
+                _,
+                buck2_data::span_end_event::Data::ActionExecution(action),
+            ) => self.target_status.action_execution_end(action),
+            UnpackedBuckEvent::Instant(
+                _,
+                _,
+                buck2_data::instant_event::Data::TargetBuildEnd(end),
+            ) => {
+                let completion = self.target_status.target_build_end(end)?;
+                self.emit(JsonEvent::TargetFinished {
+                    target: completion.target,
+                    configuration: completion.configuration,
+                    status: completion.status.as_str(),
+                    outputs: completion.outputs,
+                })
+            }
+            UnpackedBuckEvent::Instant(_, _, buck2_data::instant_event::Data::ActionError(e)) => {
+                let display = display::display_action_error(e, TargetDisplayOptions::for_log())?;
+                self.emit(JsonEvent::ActionFailed {
+                    action: display.action_id,
+                    message: display.reason,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for JsonEventsConsole {
+    async fn handle_events(&mut self, events: &[Arc<BuckEvent>]) -> buck2_error::Result<()> {
+        for event in events {
+            self.handle_event(event)?;
+        }
+        Ok(())
+    }
+
+    async fn handle_command_result(
+        &mut self,
+        result: &buck2_cli_proto::CommandResult,
+    ) -> buck2_error::Result<()> {
+        use buck2_cli_proto::command_result::Result;
+
+        let result_errors = match &result.result {
+            Some(Result::Error(e)) => e.errors.iter().map(|e| e.message.clone()).collect(),
+            _ => Vec::new(),
+        };
+
+        let end = self.command_end.take();
+        let success = end.as_ref().map_or(result_errors.is_empty(), |e| e.success);
+        let duration_ms = end
+            .as_ref()
+            .and_then(|e| e.duration)
+            .map(|d| d.as_millis() as u64);
+        let mut errors = end.map(|e| e.errors).unwrap_or_default();
+        if errors.is_empty() {
+            errors = result_errors;
+        }
+        self.emit(JsonEvent::BuildFinished {
+            success,
+            duration_ms,
+            errors,
+        })
+    }
+
+    async fn handle_error(&mut self, error: &buck2_error::Error) -> buck2_error::Result<()> {
+        self.emit(JsonEvent::BuildFinished {
+            success: false,
+            duration_ms: None,
+            errors: vec![format!("{:#}", error)],
+        })
+    }
+
+    async fn exit(&mut self) -> buck2_error::Result<()> {
+        // Consumers wait for `build_finished`, so make sure there always is one.
+        self.emit(JsonEvent::BuildFinished {
+            success: false,
+            duration_ms: None,
+            errors: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_line() -> buck2_error::Result<()> {
+        assert_eq!(
+            JsonEvent::BuildStarted {
+                trace_id: "abc".to_owned(),
+                command: "Build".to_owned(),
+            }
+            .to_json_line()?,
+            r#"{"event":"build_started","trace_id":"abc","command":"Build"}"#
+        );
+        assert_eq!(
+            JsonEvent::ActionFailed {
+                action: "root//:foo (cp)".to_owned(),
+                message: "Local command returned non-zero exit code 1".to_owned(),
+            }
+            .to_json_line()?,
+            r#"{"event":"action_failed","action":"root//:foo (cp)","message":"Local command returned non-zero exit code 1"}"#
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Local commands running when memory pressure is detected, for
+//! `InvocationRecord::memory_pressure_events`.
+
+use std::collections::HashMap;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use buck2_events::span::SpanId;
+
+use crate::subscribers::system_warning::MemoryPressureHigh;
+
+/// Events recorded per command.
+const MAX_EVENTS: usize = 10;
+
+/// Commands listed per event.
+const MAX_COMMANDS: usize = 5;
+
+struct RunningCommand {
+    action: String,
+    weight: Option<u64>,
+    start: SystemTime,
+}
+
+#[derive(Default)]
+pub(crate) struct MemoryPressureTracker {
+    /// Names of the actions running, by span.
+    actions: HashMap<SpanId, String>,
+    /// Local commands running, by span of their execution stage.
+    commands: HashMap<SpanId, RunningCommand>,
+    /// Whether the last snapshot had memory pressure.
+    under_pressure: bool,
+    events: Vec<buck2_data::MemoryPressureEvent>,
+}
+
+impl MemoryPressureTracker {
+    pub(crate) fn action_start(&mut self, span_id: SpanId, action: String) {
+        self.actions.insert(span_id, action);
+    }
+
+    pub(crate) fn action_end(&mut self, span_id: SpanId) {
+        self.actions.remove(&span_id);
+    }
+
+    /// A local command of the action `parent_id` started.
+    pub(crate) fn local_execute_start(
+        &mut self,
+        span_id: SpanId,
+        parent_id: Option<SpanId>,
+        weight: Option<u64>,
+        timestamp: SystemTime,
+    ) {
+        let action = parent_id
+            .and_then(|parent_id| self.actions.get(&parent_id))
+            .cloned()
+            .unwrap_or_default();
+        self.commands.insert(
+            span_id,
+            RunningCommand {
+                action,
+                weight,
+                start: timestamp,
+            },
+        );
+    }
+
+    pub(crate) fn executor_stage_end(&mut self, span_id: SpanId) {
+        self.commands.remove(&span_id);
+    }
+
+    /// Record an event if memory pressure starts with this snapshot.
+    pub(crate) fn snapshot(
+        &mut self,
+        elapsed: Duration,
+        timestamp: SystemTime,
+        pressure: Option<MemoryPressureHigh>,
+    ) {
+        let was_under_pressure = std::mem::replace(&mut self.under_pressure, pressure.is_some());
+        let Some(pressure) = pressure else {
+            return;
+        };
+        if was_under_pressure || self.events.len() >= MAX_EVENTS {
+            return;
+        }
+
+        let mut commands: Vec<buck2_data::MemoryPressureCommand> = self
+            .commands
+            .values()
+            .map(|c| buck2_data::MemoryPressureCommand {
+                action: c.action.clone(),
+                weight: c.weight,
+                running_ms: timestamp
+                    .duration_since(c.start)
+                    .unwrap_or_default()
+                    .as_millis() as u64,
+            })
+            .collect();
+        commands.sort_by(|a, b| {
+            b.weight
+                .cmp(&a.weight)
+                .then(b.running_ms.cmp(&a.running_ms))
+                .then_with(|| a.action.cmp(&b.action))
+        });
+        commands.truncate(MAX_COMMANDS);
+
+        self.events.push(buck2_data::MemoryPressureEvent {
+            elapsed_ms: elapsed.as_millis() as u64,
+            process_memory_bytes: pressure.process_memory,
+            system_total_memory_bytes: pressure.system_total_memory,
+            commands,
+            running_commands: self.commands.len() as u64,
+        });
+    }
+
+    pub(crate) fn take_events(&mut self) -> Vec<buck2_data::MemoryPressureEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    use buck2_events::span::SpanId;
+
+    use crate::subscribers::memory_pressure::MemoryPressureTracker;
+    use crate::subscribers::system_warning::MemoryPressureHigh;
+
+    fn pressure() -> Option<MemoryPressureHigh> {
+        Some(MemoryPressureHigh {
+            system_total_memory: 100,
+            process_memory: 95,
+        })
+    }
+
+    #[test]
+    fn test_memory_pressure_tracker() {
+        let start = SystemTime::UNIX_EPOCH;
+        let at = |secs| start + Duration::from_secs(secs);
+        let span = |id| SpanId::from_u64(id).unwrap();
+
+        let mut tracker = MemoryPressureTracker::default();
+        for (action, command, name, weight) in [(1, 11, "light", 1), (2, 12, "heavy", 4)] {
+            tracker.action_start(span(action), name.to_owned());
+            tracker.local_execute_start(span(command), Some(span(action)), Some(weight), at(0));
+        }
+        tracker.snapshot(Duration::from_secs(1), at(1), None);
+        tracker.snapshot(Duration::from_secs(2), at(2), pressure());
+        // Only the start of the memory pressure is recorded.
+        tracker.snapshot(Duration::from_secs(3), at(3), pressure());
+        tracker.executor_stage_end(span(12));
+        tracker.action_end(span(2));
+        tracker.snapshot(Duration::from_secs(4), at(4), None);
+        tracker.snapshot(Duration::from_secs(5), at(5), pressure());
+
+        let events = tracker.take_events();
+        assert_eq!(2, events.len());
+        assert_eq!(2000, events[0].elapsed_ms);
+        assert_eq!(2, events[0].running_commands);
+        assert_eq!(
+            vec![("heavy", Some(4), 2000), ("light", Some(1), 2000)],
+            events[0]
+                .commands
+                .iter()
+                .map(|c| (c.action.as_str(), c.weight, c.running_ms))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(1, events[1].running_commands);
+        assert_eq!("light", events[1].commands[0].action);
+        assert_eq!(95, events[1].process_memory_bytes);
+    }
+}
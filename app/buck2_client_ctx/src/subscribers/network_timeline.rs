@@ -0,0 +1,173 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Bytes transferred by periods of the command, recorded if `buck2.network_timeline` is enabled.
+//!
+//! The transfers between two snapshots are attributed to the period of the later one. Periods
+//! start 10 seconds long, and double in length (merging pairs of periods) whenever there would
+//! be more than [`MAX_BUCKETS`] of them, so that long commands still have a compact timeline.
+
+use std::time::Duration;
+use std::time::SystemTime;
+
+const INITIAL_BUCKET_DURATION: Duration = Duration::from_secs(10);
+
+const MAX_BUCKETS: usize = 360;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+struct Bytes {
+    re_download: u64,
+    re_upload: u64,
+    http_download: u64,
+}
+
+impl Bytes {
+    fn new(snapshot: &buck2_data::Snapshot) -> Bytes {
+        Bytes {
+            re_download: snapshot.re_download_bytes,
+            re_upload: snapshot.re_upload_bytes,
+            http_download: snapshot.http_download_bytes,
+        }
+    }
+
+    fn add(&mut self, other: Bytes) {
+        self.re_download += other.re_download;
+        self.re_upload += other.re_upload;
+        self.http_download += other.http_download;
+    }
+
+    fn since(self, earlier: Bytes) -> Bytes {
+        Bytes {
+            re_download: self.re_download.saturating_sub(earlier.re_download),
+            re_upload: self.re_upload.saturating_sub(earlier.re_upload),
+            http_download: self.http_download.saturating_sub(earlier.http_download),
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Bytes::default()
+    }
+}
+
+pub(crate) struct NetworkTimeline {
+    bucket_duration: Duration,
+    /// Time of the first snapshot.
+    start: Option<SystemTime>,
+    /// Totals of the last snapshot.
+    last: Option<Bytes>,
+    buckets: Vec<Bytes>,
+}
+
+impl NetworkTimeline {
+    pub(crate) fn new() -> Self {
+        Self {
+            bucket_duration: INITIAL_BUCKET_DURATION,
+            start: None,
+            last: None,
+            buckets: Vec::new(),
+        }
+    }
+
+    pub(crate) fn update(&mut self, timestamp: SystemTime, snapshot: &buck2_data::Snapshot) {
+        let bytes = Bytes::new(snapshot);
+        let start = *self.start.get_or_insert(timestamp);
+        let Some(last) = self.last.replace(bytes) else {
+            return;
+        };
+        let elapsed = timestamp.duration_since(start).unwrap_or_default();
+        let index = loop {
+            let index = (elapsed.as_millis() / self.bucket_duration.as_millis()) as usize;
+            if index < MAX_BUCKETS {
+                break index;
+            }
+            self.bucket_duration *= 2;
+            self.buckets = self
+                .buckets
+                .chunks(2)
+                .map(|pair| {
+                    let mut merged = Bytes::default();
+                    pair.iter().for_each(|b| merged.add(*b));
+                    merged
+                })
+                .collect();
+        };
+        if self.buckets.len() <= index {
+            self.buckets.resize(index + 1, Bytes::default());
+        }
+        self.buckets[index].add(bytes.since(last));
+    }
+
+    /// The periods with transfers.
+    pub(crate) fn buckets(&self) -> Vec<buck2_data::NetworkTimelineBucket> {
+        let duration_ms = self.bucket_duration.as_millis() as u64;
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, bytes)| !bytes.is_zero())
+            .map(|(i, bytes)| buck2_data::NetworkTimelineBucket {
+                start_ms: i as u64 * duration_ms,
+                duration_ms,
+                re_download_bytes: bytes.re_download,
+                re_upload_bytes: bytes.re_upload,
+                http_download_bytes: bytes.http_download,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    use crate::subscribers::network_timeline::NetworkTimeline;
+
+    fn snapshot(re_download_bytes: u64) -> buck2_data::Snapshot {
+        buck2_data::Snapshot {
+            re_download_bytes,
+            ..Default::default()
+        }
+    }
+
+    fn downloads(timeline: &NetworkTimeline) -> Vec<(u64, u64, u64)> {
+        timeline
+            .buckets()
+            .iter()
+            .map(|b| (b.start_ms, b.duration_ms, b.re_download_bytes))
+            .collect()
+    }
+
+    #[test]
+    fn test_network_timeline() {
+        let start = SystemTime::UNIX_EPOCH;
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut timeline = NetworkTimeline::new();
+        timeline.update(at(0), &snapshot(100));
+        timeline.update(at(1), &snapshot(150));
+        timeline.update(at(9), &snapshot(200));
+        // Nothing downloaded in the second period.
+        timeline.update(at(15), &snapshot(200));
+        timeline.update(at(25), &snapshot(1200));
+        assert_eq!(
+            vec![(0, 10_000, 100), (20_000, 10_000, 1000)],
+            downloads(&timeline)
+        );
+
+        // Past an hour, periods are merged in pairs.
+        timeline.update(at(3600), &snapshot(1300));
+        assert_eq!(
+            vec![
+                (0, 20_000, 100),
+                (20_000, 20_000, 1000),
+                (3_600_000, 20_000, 100)
+            ],
+            downloads(&timeline)
+        );
+    }
+}
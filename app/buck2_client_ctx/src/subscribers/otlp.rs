@@ -0,0 +1,290 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Export of event spans as OpenTelemetry traces.
+//!
+//! Spans are sent in batches to an OTLP/HTTP endpoint using the JSON encoding, so they can be
+//! viewed in any tool accepting OTLP (Jaeger, Honeycomb, ...). The exporter is enabled by setting
+//! the standard `OTEL_EXPORTER_OTLP_ENDPOINT` or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` environment
+//! variables, and `OTEL_EXPORTER_OTLP_HEADERS` can be used to pass e.g. API keys.
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Arc;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use async_trait::async_trait;
+use buck2_core::buck2_env;
+use buck2_events::span::SpanId;
+use buck2_events::BuckEvent;
+use buck2_http::HttpClient;
+use buck2_http::HttpClientBuilder;
+use gazebo::variants::VariantName;
+use tokio::task::JoinHandle;
+
+use crate::subscribers::subscriber::EventSubscriber;
+
+/// Number of finished spans sent in one request.
+const BATCH_SIZE: usize = 1000;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum OtlpError {
+    #[error("Invalid `OTEL_EXPORTER_OTLP_HEADERS` entry `{0}`, expected `key=value`")]
+    InvalidHeader(String),
+}
+
+/// Where and how to send the spans.
+#[derive(Clone)]
+struct OtlpConfig {
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+impl OtlpConfig {
+    fn from_env() -> buck2_error::Result<Option<OtlpConfig>> {
+        let url = match buck2_env!("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT")? {
+            Some(url) => url.to_owned(),
+            None => match buck2_env!("OTEL_EXPORTER_OTLP_ENDPOINT")? {
+                Some(endpoint) => format!("{}/v1/traces", endpoint.trim_end_matches('/')),
+                None => return Ok(None),
+            },
+        };
+        let headers = match buck2_env!("OTEL_EXPORTER_OTLP_HEADERS")? {
+            Some(headers) => parse_headers(headers)?,
+            None => Vec::new(),
+        };
+        Ok(Some(OtlpConfig { url, headers }))
+    }
+}
+
+/// Parse headers in the `key1=value1,key2=value2` format of `OTEL_EXPORTER_OTLP_HEADERS`.
+fn parse_headers(headers: &str) -> buck2_error::Result<Vec<(String, String)>> {
+    headers
+        .split(',')
+        .filter(|h| !h.trim().is_empty())
+        .map(|h| match h.split_once('=') {
+            Some((key, value)) => Ok((key.trim().to_owned(), value.trim().to_owned())),
+            None => Err(OtlpError::InvalidHeader(h.to_owned()).into()),
+        })
+        .collect()
+}
+
+/// A span which started but has not ended yet.
+struct OpenSpan {
+    name: &'static str,
+    parent_id: Option<SpanId>,
+    start: SystemTime,
+}
+
+pub(crate) struct OtlpTraces {
+    config: OtlpConfig,
+    /// Created on the first export.
+    client: Option<HttpClient>,
+    open: HashMap<SpanId, OpenSpan>,
+    /// Finished spans not exported yet, already in the OTLP JSON format.
+    finished: Vec<serde_json::Value>,
+    exports: Vec<JoinHandle<()>>,
+}
+
+impl OtlpTraces {
+    /// Create the exporter if an OTLP endpoint is configured.
+    pub(crate) fn from_env() -> buck2_error::Result<Option<OtlpTraces>> {
+        Ok(OtlpConfig::from_env()?.map(|config| OtlpTraces {
+            config,
+            client: None,
+            open: HashMap::new(),
+            finished: Vec::new(),
+            exports: Vec::new(),
+        }))
+    }
+
+    fn handle_event(&mut self, event: &BuckEvent) -> buck2_error::Result<()> {
+        let Some(span_id) = event.span_id() else {
+            return Ok(());
+        };
+        if let Some(start) = event.span_start_event() {
+            self.open.insert(
+                span_id,
+                OpenSpan {
+                    name: start.data.as_ref().map_or("Unknown", |d| d.variant_name()),
+                    parent_id: event.parent_id(),
+                    start: event.timestamp(),
+                },
+            );
+        } else if let Some(end) = event.span_end_event() {
+            if let Some(open) = self.open.remove(&span_id) {
+                let failed = matches!(
+                    &end.data,
+                    Some(buck2_data::span_end_event::Data::Command(c)) if !c.is_success
+                );
+                self.finished.push(otlp_span(
+                    &event.trace_id()?.to_string(),
+                    span_id,
+                    &open,
+                    event.timestamp(),
+                    failed,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Send the finished spans in the background.
+    async fn export(&mut self) {
+        if self.finished.is_empty() {
+            return;
+        }
+        let client = match self.client.clone() {
+            Some(client) => client,
+            None => match HttpClientBuilder::oss().await {
+                Ok(builder) => self.client.insert(builder.build()).clone(),
+                Err(e) => {
+                    tracing::warn!("Error creating client to export spans: {}", e);
+                    self.finished.clear();
+                    return;
+                }
+            },
+        };
+        let config = self.config.clone();
+        let body = otlp_request(mem::take(&mut self.finished)).to_string();
+        self.exports.push(tokio::spawn(async move {
+            let mut headers = config.headers;
+            headers.push(("Content-Type".to_owned(), "application/json".to_owned()));
+            if let Err(e) = client.post(&config.url, body.into(), headers).await {
+                tracing::warn!("Error exporting spans to `{}`: {}", config.url, e);
+            }
+        }));
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos())
+        .to_string()
+}
+
+/// A span in the OTLP JSON encoding. Buck trace IDs are UUIDs, so they are valid OTLP trace IDs.
+fn otlp_span(
+    trace_id: &str,
+    span_id: SpanId,
+    span: &OpenSpan,
+    end: SystemTime,
+    failed: bool,
+) -> serde_json::Value {
+    // STATUS_CODE_ERROR or STATUS_CODE_UNSET
+    let status = if failed { 2 } else { 0 };
+    let mut json = serde_json::json!({
+        "traceId": trace_id.replace('-', ""),
+        "spanId": format!("{:016x}", span_id.0),
+        "name": span.name,
+        // SPAN_KIND_INTERNAL
+        "kind": 1,
+        "startTimeUnixNano": unix_nanos(span.start),
+        "endTimeUnixNano": unix_nanos(end),
+        "status": { "code": status },
+    });
+    if let Some(parent_id) = span.parent_id {
+        json["parentSpanId"] = format!("{:016x}", parent_id.0).into();
+    }
+    json
+}
+
+fn otlp_request(spans: Vec<serde_json::Value>) -> serde_json::Value {
+    serde_json::json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [{
+                    "key": "service.name",
+                    "value": { "stringValue": "buck2" },
+                }],
+            },
+            "scopeSpans": [{
+                "scope": { "name": "buck2" },
+                "spans": spans,
+            }],
+        }],
+    })
+}
+
+#[async_trait]
+impl EventSubscriber for OtlpTraces {
+    async fn handle_events(&mut self, events: &[Arc<BuckEvent>]) -> buck2_error::Result<()> {
+        for event in events {
+            self.handle_event(event)?;
+        }
+        if self.finished.len() >= BATCH_SIZE {
+            self.export().await;
+        }
+        Ok(())
+    }
+
+    async fn exit(&mut self) -> buck2_error::Result<()> {
+        self.export().await;
+        for export in mem::take(&mut self.exports) {
+            if let Err(e) = export.await {
+                tracing::warn!("Error exporting spans: {}", e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::UNIX_EPOCH;
+
+    use buck2_events::span::SpanId;
+
+    use crate::subscribers::otlp::otlp_span;
+    use crate::subscribers::otlp::parse_headers;
+    use crate::subscribers::otlp::OpenSpan;
+
+    #[test]
+    fn test_parse_headers() {
+        assert_eq!(
+            vec![
+                ("x-honeycomb-team".to_owned(), "abc".to_owned()),
+                ("a".to_owned(), "b=c".to_owned()),
+            ],
+            parse_headers("x-honeycomb-team=abc, a=b=c,").unwrap()
+        );
+        assert!(parse_headers("abc").is_err());
+    }
+
+    #[test]
+    fn test_otlp_span() {
+        let span = OpenSpan {
+            name: "Analysis",
+            parent_id: Some(SpanId::from_u64(1).unwrap()),
+            start: UNIX_EPOCH + Duration::from_secs(1),
+        };
+        assert_eq!(
+            serde_json::json!({
+                "traceId": "0123456789abcdef0123456789abcdef",
+                "spanId": "00000000000000ff",
+                "parentSpanId": "0000000000000001",
+                "name": "Analysis",
+                "kind": 1,
+                "startTimeUnixNano": "1000000000",
+                "endTimeUnixNano": "3000000000",
+                "status": { "code": 2 },
+            }),
+            otlp_span(
+                "01234567-89ab-cdef-0123-456789abcdef",
+                SpanId::from_u64(255).unwrap(),
+                &span,
+                UNIX_EPOCH + Duration::from_secs(3),
+                true,
+            )
+        );
+    }
+}
@@ -25,8 +25,12 @@ use async_trait::async_trait;
 use buck2_cli_proto::command_result;
 use buck2_common::build_count::BuildCount;
 use buck2_common::build_count::BuildCountManager;
+use buck2_common::build_count::CriticalPathContributions;
 use buck2_common::convert::ProstDurationExt;
+use buck2_common::soft_error_budget::SoftErrorBudget;
+use buck2_common::soft_error_budget::SoftErrorHistoryManager;
 use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::abs_path::AbsPathBuf;
 use buck2_core::soft_error;
 use buck2_data::error::ErrorTag;
@@ -45,15 +49,23 @@ use buck2_event_log::ttl::manifold_event_log_ttl;
 use buck2_event_observer::action_stats;
 use buck2_event_observer::action_stats::ActionStats;
 use buck2_event_observer::cache_hit_rate::total_cache_hit_rate;
+use buck2_event_observer::display::display_action_identity;
+use buck2_event_observer::display::display_action_owner;
+use buck2_event_observer::display::display_anon_target;
+use buck2_event_observer::display::display_bxl_key;
+use buck2_event_observer::display::display_configured_target_label;
+use buck2_event_observer::display::TargetDisplayOptions;
 use buck2_event_observer::last_command_execution_kind;
 use buck2_event_observer::last_command_execution_kind::LastCommandExecutionKind;
 use buck2_events::errors::create_error_report;
 use buck2_events::sink::remote::new_remote_event_sink_if_enabled;
+use buck2_events::sink::remote::RemoteEventSink;
 use buck2_events::BuckEvent;
 use buck2_util::cleanup_ctx::AsyncCleanupContext;
 use buck2_util::network_speed_average::NetworkSpeedAverage;
 use buck2_util::sliding_window::SlidingWindow;
 use buck2_wrapper_common::invocation_id::TraceId;
+use chrono::Utc;
 use dupe::Dupe;
 use fbinit::FacebookInit;
 use futures::FutureExt;
@@ -69,6 +81,11 @@ use crate::client_metadata::ClientMetadata;
 use crate::common::CommonEventLogOptions;
 use crate::console_interaction_stream::SuperConsoleToggle;
 use crate::subscribers::classify_server_stderr::classify_server_stderr;
+use crate::subscribers::console_health::ConsoleHealth;
+use crate::subscribers::console_health::CONSOLE_DEGRADED_TAG;
+use crate::subscribers::invocation_records::append_invocation_record;
+use crate::subscribers::memory_pressure::MemoryPressureTracker;
+use crate::subscribers::network_timeline::NetworkTimeline;
 use crate::subscribers::observer::ErrorObserver;
 use crate::subscribers::subscriber::EventSubscriber;
 use crate::subscribers::system_warning::check_cache_misses;
@@ -97,17 +114,26 @@ pub(crate) struct InvocationRecorder<'a> {
     start_time: Instant,
     async_cleanup_context: AsyncCleanupContext<'a>,
     build_count_manager: Option<BuildCountManager>,
+    invocation_records_dir: Option<AbsNormPathBuf>,
+    /// Set if `buck2.invocation_records` is enabled.
+    invocation_record_sink: Option<buck2_data::InvocationRecordSink>,
     trace_id: TraceId,
     command_end: Option<buck2_data::CommandEnd>,
     command_duration: Option<prost_types::Duration>,
     re_session_id: Option<String>,
     re_experiment_name: Option<String>,
     critical_path_duration: Option<Duration>,
+    critical_path_contributions: Option<CriticalPathContributions>,
+    estimated_critical_path_duration: Option<Duration>,
+    critical_path_scheduling: Option<bool>,
+    predicted_critical_path_actions: Option<u64>,
+    predicted_critical_path_actions_error_ms: Option<i64>,
     tags: Vec<String>,
     run_local_count: u64,
     run_remote_count: u64,
     run_action_cache_count: u64,
     run_remote_dep_file_cache_count: u64,
+    cache_stats_by_category: HashMap<String, buck2_data::ActionCategoryCacheStats>,
     run_skipped_count: u64,
     run_fallback_count: u64,
     local_actions_executed_via_worker: u64,
@@ -119,6 +145,8 @@ pub(crate) struct InvocationRecorder<'a> {
     cache_upload_attempt_count: u64,
     dep_file_upload_count: u64,
     dep_file_upload_attempt_count: u64,
+    remote_dep_file_verified_count: u64,
+    remote_dep_file_mismatch_count: u64,
     parsed_target_patterns: Option<buck2_data::ParsedTargetPatterns>,
     filesystem: String,
     watchman_version: Option<String>,
@@ -143,6 +171,7 @@ pub(crate) struct InvocationRecorder<'a> {
     file_watcher_stats: Option<buck2_data::FileWatcherStats>,
     file_watcher_duration: Option<Duration>,
     time_to_last_action_execution_end: Option<Duration>,
+    phase_wall_times: PhaseWallTimes,
     initial_sink_success_count: Option<u64>,
     initial_sink_failure_count: Option<u64>,
     initial_sink_dropped_count: Option<u64>,
@@ -159,6 +188,7 @@ pub(crate) struct InvocationRecorder<'a> {
     has_command_result: bool,
     has_end_of_stream: bool,
     compressed_event_log_size_bytes: Option<Arc<AtomicU64>>,
+    console_health: Option<Arc<ConsoleHealth>>,
     critical_path_backend: Option<String>,
     instant_command_is_success: Option<bool>,
     bxl_ensure_artifacts_duration: Option<prost_types::Duration>,
@@ -204,12 +234,25 @@ pub(crate) struct InvocationRecorder<'a> {
     active_networks_kinds: HashSet<i32>,
     target_cfg: Option<TargetCfg>,
     version_control_revision: Option<buck2_data::VersionControlRevision>,
+    telemetry_sampling: Option<buck2_data::TelemetrySampling>,
+    lost_input_reexecutions: u64,
+    lost_input_reexecution_max_depth: u64,
+    /// Set if `buck2.per_target_telemetry` is enabled, by configured target label.
+    target_stats: Option<HashMap<String, TargetStats>>,
+    /// Set if `buck2.network_timeline` is enabled.
+    network_timeline: Option<NetworkTimeline>,
+    memory_pressure: MemoryPressureTracker,
+    soft_error_history: Option<SoftErrorHistoryManager>,
+    /// Set if `buck2.soft_error_budget` is configured.
+    soft_error_budget: Option<buck2_data::SoftErrorBudget>,
+    soft_error_categories_over_budget: Vec<String>,
     concurrent_commands: bool,
     initial_local_cache_hits_files: Option<i64>,
     initial_local_cache_hits_bytes: Option<i64>,
     initial_local_cache_misses_files: Option<i64>,
     initial_local_cache_misses_bytes: Option<i64>,
     materialization_files: u64,
+    target_build_status_counts: HashMap<buck2_data::TargetBuildStatus, u64>,
 }
 
 struct ErrorsReport {
@@ -230,9 +273,12 @@ impl<'a> InvocationRecorder<'a> {
         trace_id: TraceId,
         isolation_dir: String,
         build_count_manager: Option<BuildCountManager>,
+        invocation_records_dir: Option<AbsNormPathBuf>,
+        soft_error_history: Option<SoftErrorHistoryManager>,
         filesystem: String,
         restarted_trace_id: Option<TraceId>,
         log_size_counter_bytes: Option<Arc<AtomicU64>>,
+        console_health: Option<Arc<ConsoleHealth>>,
         client_metadata: Vec<buck2_data::ClientMetadata>,
     ) -> Self {
         Self {
@@ -244,16 +290,24 @@ impl<'a> InvocationRecorder<'a> {
             start_time: Instant::now(),
             async_cleanup_context,
             build_count_manager,
+            invocation_records_dir,
+            invocation_record_sink: None,
             trace_id,
             command_end: None,
             command_duration: None,
             re_session_id: None,
             re_experiment_name: None,
             critical_path_duration: None,
+            critical_path_contributions: None,
+            estimated_critical_path_duration: None,
+            critical_path_scheduling: None,
+            predicted_critical_path_actions: None,
+            predicted_critical_path_actions_error_ms: None,
             tags: vec![],
             run_local_count: 0,
             run_remote_count: 0,
             run_action_cache_count: 0,
+            cache_stats_by_category: HashMap::new(),
             run_remote_dep_file_cache_count: 0,
             run_skipped_count: 0,
             run_fallback_count: 0,
@@ -266,6 +320,8 @@ impl<'a> InvocationRecorder<'a> {
             cache_upload_attempt_count: 0,
             dep_file_upload_count: 0,
             dep_file_upload_attempt_count: 0,
+            remote_dep_file_verified_count: 0,
+            remote_dep_file_mismatch_count: 0,
             parsed_target_patterns: None,
             filesystem,
             watchman_version: None,
@@ -290,6 +346,7 @@ impl<'a> InvocationRecorder<'a> {
             file_watcher_stats: None,
             file_watcher_duration: None,
             time_to_last_action_execution_end: None,
+            phase_wall_times: PhaseWallTimes::default(),
             initial_sink_success_count: None,
             initial_sink_failure_count: None,
             initial_sink_dropped_count: None,
@@ -306,6 +363,7 @@ impl<'a> InvocationRecorder<'a> {
             has_command_result: false,
             has_end_of_stream: false,
             compressed_event_log_size_bytes: log_size_counter_bytes,
+            console_health,
             critical_path_backend: None,
             instant_command_is_success: None,
             bxl_ensure_artifacts_duration: None,
@@ -357,12 +415,22 @@ impl<'a> InvocationRecorder<'a> {
             active_networks_kinds: HashSet::new(),
             target_cfg: None,
             version_control_revision: None,
+            telemetry_sampling: None,
+            lost_input_reexecutions: 0,
+            lost_input_reexecution_max_depth: 0,
+            target_stats: None,
+            network_timeline: None,
+            memory_pressure: MemoryPressureTracker::default(),
+            soft_error_history,
+            soft_error_budget: None,
+            soft_error_categories_over_budget: Vec::new(),
             concurrent_commands: false,
             initial_local_cache_hits_files: None,
             initial_local_cache_hits_bytes: None,
             initial_local_cache_misses_files: None,
             initial_local_cache_misses_bytes: None,
             materialization_files: 0,
+            target_build_status_counts: HashMap::new(),
         }
     }
 
@@ -408,6 +476,82 @@ impl<'a> InvocationRecorder<'a> {
         Ok(Default::default())
     }
 
+    /// Reads the critical path estimate this build was shown, then records the actual critical path
+    /// of successful builds so later builds of the same targets can be estimated.
+    async fn update_critical_path_history(&mut self, is_success: bool) -> buck2_error::Result<()> {
+        let (Some(manager), Some(patterns)) =
+            (&self.build_count_manager, &self.parsed_target_patterns)
+        else {
+            return Ok(());
+        };
+        self.estimated_critical_path_duration = manager
+            .estimated_critical_path(patterns)
+            .await
+            .buck_error_context("Error reading critical path history")?;
+        if let (true, Some(critical_path)) = (is_success, &self.critical_path_contributions) {
+            manager
+                .record_critical_path(patterns, critical_path)
+                .await
+                .buck_error_context("Error recording critical path history")?;
+        }
+        Ok(())
+    }
+
+    /// Records the soft error categories of this command for `buck2.soft_error_budget`, and which of
+    /// them are now over budget.
+    async fn update_soft_error_history(&mut self) -> buck2_error::Result<()> {
+        let (Some(manager), Some(budget)) = (&self.soft_error_history, &self.soft_error_budget)
+        else {
+            return Ok(());
+        };
+        let history = manager
+            .record(
+                self.soft_error_categories.iter().cloned(),
+                budget.window.try_into().unwrap_or(usize::MAX),
+            )
+            .await
+            .buck_error_context("Error recording soft error history")?;
+        self.soft_error_categories_over_budget =
+            history.over_budget(&SoftErrorBudget::from_proto(&budget.max_rates));
+        Ok(())
+    }
+
+    fn target_build_status_count(&self, status: buck2_data::TargetBuildStatus) -> u64 {
+        self.target_build_status_counts
+            .get(&status)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The targets with the longest critical path contribution, then with the most actions.
+    fn target_telemetry(&mut self) -> Vec<buck2_data::TargetTelemetry> {
+        let Some(target_stats) = self.target_stats.take() else {
+            return Vec::new();
+        };
+        target_stats
+            .into_iter()
+            .sorted_by(|(a_target, a), (b_target, b)| {
+                b.critical_path_duration
+                    .cmp(&a.critical_path_duration)
+                    .then(b.action_count.cmp(&a.action_count))
+                    .then(a_target.cmp(b_target))
+            })
+            .take(MAX_TELEMETRY_TARGETS)
+            .map(|(target, stats)| buck2_data::TargetTelemetry {
+                target,
+                action_count: stats.action_count,
+                cache_hits: stats.cache_hits,
+                cache_misses: stats.cache_misses,
+                cache_hit_rate: if stats.cache_hits + stats.cache_misses == 0 {
+                    0.0
+                } else {
+                    stats.cache_hits as f32 / (stats.cache_hits + stats.cache_misses) as f32
+                },
+                critical_path_duration_ms: stats.critical_path_duration.as_millis() as u64,
+            })
+            .collect()
+    }
+
     fn finalize_errors(&mut self) -> ErrorsReport {
         // Add stderr to GRPC connection errors if available
         let connection_errors: Vec<buck2_error::Error> = self
@@ -643,12 +787,21 @@ impl<'a> InvocationRecorder<'a> {
                 self.tags.push("low_cache_hits".to_owned());
             }
         }
+        if let Some(console_health) = &self.console_health {
+            if console_health.is_degraded() {
+                self.tags.push(CONSOLE_DEGRADED_TAG.to_owned());
+            }
+        }
 
         let mut metadata = Self::default_metadata();
         metadata.strings.extend(std::mem::take(&mut self.metadata));
 
         let errors_report = self.finalize_errors();
 
+        let daemon_cpu_attribution =
+            diff_daemon_cpu_attribution(self.first_snapshot.as_ref(), self.last_snapshot.as_ref());
+
+        let now = SystemTime::now();
         let record = buck2_data::InvocationRecord {
             command_name: Some(self.command_name.to_owned()),
             command_end: self.command_end.take(),
@@ -658,6 +811,18 @@ impl<'a> InvocationRecorder<'a> {
             re_experiment_name: self.re_experiment_name.take().unwrap_or_default(),
             cli_args: self.cli_args.clone(),
             critical_path_duration: self.critical_path_duration.and_then(|x| x.try_into().ok()),
+            estimated_critical_path_duration: self
+                .estimated_critical_path_duration
+                .and_then(|x| x.try_into().ok()),
+            critical_path_estimate_error_ms: match (
+                self.critical_path_duration,
+                self.estimated_critical_path_duration,
+            ) {
+                (Some(actual), Some(estimated)) => {
+                    Some(actual.as_millis() as i64 - estimated.as_millis() as i64)
+                }
+                _ => None,
+            },
             metadata: Some(metadata),
             tags: self.tags.drain(..).collect(),
             run_local_count: self.run_local_count,
@@ -670,6 +835,14 @@ impl<'a> InvocationRecorder<'a> {
                 self.run_action_cache_count,
                 self.run_remote_dep_file_cache_count,
             ) as f32,
+            cache_stats_by_category: std::mem::take(&mut self.cache_stats_by_category)
+                .into_iter()
+                .map(|(category, mut stats)| {
+                    stats.cache_hit_rate =
+                        stats.cache_hits as f32 / (stats.cache_hits + stats.cache_misses) as f32;
+                    (category, stats)
+                })
+                .collect(),
             run_skipped_count: self.run_skipped_count,
             run_fallback_count: Some(self.run_fallback_count),
             local_actions_executed_via_worker: Some(self.local_actions_executed_via_worker),
@@ -681,6 +854,8 @@ impl<'a> InvocationRecorder<'a> {
             cache_upload_attempt_count: self.cache_upload_attempt_count,
             dep_file_upload_count: self.dep_file_upload_count,
             dep_file_upload_attempt_count: self.dep_file_upload_attempt_count,
+            remote_dep_file_verified_count: Some(self.remote_dep_file_verified_count),
+            remote_dep_file_mismatch_count: Some(self.remote_dep_file_mismatch_count),
             parsed_target_patterns: self.parsed_target_patterns.take(),
             filesystem: std::mem::take(&mut self.filesystem),
             watchman_version: self.watchman_version.take(),
@@ -725,6 +900,24 @@ impl<'a> InvocationRecorder<'a> {
             time_to_last_action_execution_end_ms: self
                 .time_to_last_action_execution_end
                 .and_then(|d| u64::try_from(d.as_millis()).ok()),
+            loading_wall_time_ms: self.phase_wall_times.total_ms(Phase::Loading, now),
+            analysis_wall_time_ms: self.phase_wall_times.total_ms(Phase::Analysis, now),
+            action_execution_wall_time_ms: self
+                .phase_wall_times
+                .total_ms(Phase::ActionExecution, now),
+            materialization_wall_time_ms: self
+                .phase_wall_times
+                .total_ms(Phase::Materialization, now),
+            loading_exclusive_wall_time_ms: self.phase_wall_times.exclusive_ms(Phase::Loading, now),
+            analysis_exclusive_wall_time_ms: self
+                .phase_wall_times
+                .exclusive_ms(Phase::Analysis, now),
+            action_execution_exclusive_wall_time_ms: self
+                .phase_wall_times
+                .exclusive_ms(Phase::ActionExecution, now),
+            materialization_exclusive_wall_time_ms: self
+                .phase_wall_times
+                .exclusive_ms(Phase::Materialization, now),
             isolation_dir: Some(self.isolation_dir.clone()),
             sink_success_count,
             sink_failure_count,
@@ -814,18 +1007,63 @@ impl<'a> InvocationRecorder<'a> {
             local_cache_misses_files,
             local_cache_misses_bytes,
             materialization_files: Some(self.materialization_files),
+            targets_succeeded_count: Some(
+                self.target_build_status_count(buck2_data::TargetBuildStatus::Success),
+            ),
+            targets_failed_count: Some(
+                self.target_build_status_count(buck2_data::TargetBuildStatus::Failure),
+            ),
+            targets_dep_failed_count: Some(
+                self.target_build_status_count(buck2_data::TargetBuildStatus::DepFailed),
+            ),
+            targets_skipped_count: Some(
+                self.target_build_status_count(buck2_data::TargetBuildStatus::Skipped),
+            ),
+            daemon_cpu_attribution,
+            telemetry_sample_rate: self.telemetry_sampling.as_ref().map(|s| s.sample_rate),
+            telemetry_sampled: self.telemetry_sampling.as_ref().map(|s| s.sampled),
+            lost_input_reexecutions: Some(self.lost_input_reexecutions),
+            lost_input_reexecution_max_depth: Some(self.lost_input_reexecution_max_depth),
+            target_telemetry: self.target_telemetry(),
+            network_timeline: self
+                .network_timeline
+                .take()
+                .map(|t| t.buckets())
+                .unwrap_or_default(),
+            memory_pressure_events: self.memory_pressure.take_events(),
+            soft_error_categories_over_budget: std::mem::take(
+                &mut self.soft_error_categories_over_budget,
+            ),
+            superconsole_dropped_frames: self.console_health.as_ref().map(|h| h.dropped_frames()),
+            superconsole_render_errors: self.console_health.as_ref().map(|h| h.render_errors()),
+            superconsole_fell_back_to_simple_console: self
+                .console_health
+                .as_ref()
+                .map(|h| h.fell_back_to_simple_console()),
+            critical_path_scheduling: self.critical_path_scheduling,
+            predicted_critical_path_actions: self.predicted_critical_path_actions,
+            predicted_critical_path_actions_error_ms: self.predicted_critical_path_actions_error_ms,
+            soft_error_categories_escalated: self
+                .soft_error_budget
+                .as_mut()
+                .map(|budget| std::mem::take(&mut budget.escalated))
+                .unwrap_or_default(),
         };
 
-        let event = BuckEvent::new(
-            SystemTime::now(),
-            self.trace_id.dupe(),
-            None,
-            None,
-            buck2_data::RecordEvent {
-                data: Some((Box::new(record)).into()),
-            }
-            .into(),
-        );
+        // The local file always has the full record.
+        let remote_record = match &self.telemetry_sampling {
+            Some(sampling) if !sampling.sampled => Some(buck2_data::InvocationRecord {
+                first_snapshot: None,
+                last_snapshot: None,
+                cache_stats_by_category: HashMap::new(),
+                target_telemetry: Vec::new(),
+                network_timeline: Vec::new(),
+                ..record.clone()
+            }),
+            _ => None,
+        };
+
+        let event = self.invocation_record_event(record);
 
         if let Some(path) = &self.write_to_path {
             let res = (|| {
@@ -846,9 +1084,26 @@ impl<'a> InvocationRecorder<'a> {
             }
         }
 
+        if let (Some(dir), Some(sink)) =
+            (&self.invocation_records_dir, &self.invocation_record_sink)
+        {
+            let res = serde_json::to_vec(event.event())
+                .buck_error_context("Error serializing")
+                .and_then(|record| {
+                    append_invocation_record(dir, sink, Utc::now(), &self.trace_id, &record)
+                });
+            if let Err(e) = &res {
+                tracing::warn!("Failed to append InvocationRecord to `{}`: {:#}", dir, e);
+            }
+        }
+
         if let Ok(Some(scribe_sink)) =
             new_remote_event_sink_if_enabled(self.fb, 1, Duration::from_millis(500), 5, None)
         {
+            let event = match remote_record {
+                Some(record) => self.invocation_record_event(record),
+                None => event,
+            };
             tracing::info!("Recording invocation to Scribe: {:?}", &event);
             Some(async move {
                 scribe_sink.send_now(event).await;
@@ -859,6 +1114,19 @@ impl<'a> InvocationRecorder<'a> {
         }
     }
 
+    fn invocation_record_event(&self, record: buck2_data::InvocationRecord) -> BuckEvent {
+        BuckEvent::new(
+            SystemTime::now(),
+            self.trace_id.dupe(),
+            None,
+            None,
+            buck2_data::RecordEvent {
+                data: Some((Box::new(record)).into()),
+            }
+            .into(),
+        )
+    }
+
     // Collects client-side state and data, suitable for telemetry.
     // NOTE: If data is visible from the daemon, put it in cli::metadata::collect()
     fn default_metadata() -> buck2_data::TypedMetadata {
@@ -922,7 +1190,7 @@ impl<'a> InvocationRecorder<'a> {
             buck2_data::command_end::Data::Build(..)
             | buck2_data::command_end::Data::Test(..)
             | buck2_data::command_end::Data::Install(..) => {
-                match self
+                let build_count = match self
                     .build_count(command.is_success, command_data.variant_name())
                     .await
                 {
@@ -932,11 +1200,18 @@ impl<'a> InvocationRecorder<'a> {
                         let _ignored = soft_error!("build_count_error", e.into());
                         Default::default()
                     }
+                };
+                if let Err(e) = self.update_critical_path_history(command.is_success).await {
+                    let _ignored = soft_error!("critical_path_history_error", e.into());
                 }
+                build_count
             }
             // other events don't count builds
             _ => Default::default(),
         };
+        if let Err(e) = self.update_soft_error_history().await {
+            let _ignored = soft_error!("soft_error_history_error", e.into());
+        }
         self.min_attempted_build_count_since_rebase = build_count.attempted_build_count;
         self.min_build_count_since_rebase = build_count.successful_build_count;
 
@@ -963,9 +1238,20 @@ impl<'a> InvocationRecorder<'a> {
 
     fn handle_action_execution_start(
         &mut self,
-        _action: &buck2_data::ActionExecutionStart,
-        _event: &BuckEvent,
+        action: &buck2_data::ActionExecutionStart,
+        event: &BuckEvent,
     ) -> buck2_error::Result<()> {
+        self.phase_wall_times
+            .start(Phase::ActionExecution, event.timestamp());
+        if let Some(span_id) = event.span_id() {
+            let identity = display_action_identity(
+                action.key.as_ref(),
+                action.name.as_ref(),
+                TargetDisplayOptions::for_log(),
+            );
+            self.memory_pressure
+                .action_start(span_id, identity.unwrap_or_default());
+        }
         if self.time_to_first_action_execution.is_none() {
             self.time_to_first_action_execution = Some(self.start_time.elapsed());
         }
@@ -974,32 +1260,75 @@ impl<'a> InvocationRecorder<'a> {
     fn handle_action_execution_end(
         &mut self,
         action: &buck2_data::ActionExecutionEnd,
-        _event: &BuckEvent,
+        event: &BuckEvent,
     ) -> buck2_error::Result<()> {
+        self.phase_wall_times
+            .end(Phase::ActionExecution, event.timestamp());
+        if let Some(span_id) = event.span_id() {
+            self.memory_pressure.action_end(span_id);
+        }
+        let mut cache_hit = None;
         if action.kind == buck2_data::ActionKind::Run as i32 {
             if action_stats::was_fallback_action(action) {
                 self.run_fallback_count += 1;
             }
 
-            match last_command_execution_kind::get_last_command_execution_kind(action) {
+            cache_hit = match last_command_execution_kind::get_last_command_execution_kind(action) {
                 LastCommandExecutionKind::Local => {
                     self.run_local_count += 1;
+                    Some(false)
                 }
                 LastCommandExecutionKind::LocalWorker => {
                     self.run_local_count += 1;
                     self.local_actions_executed_via_worker += 1;
+                    Some(false)
                 }
                 LastCommandExecutionKind::Cached => {
                     self.run_action_cache_count += 1;
+                    Some(true)
                 }
                 LastCommandExecutionKind::RemoteDepFileCached => {
                     self.run_remote_dep_file_cache_count += 1;
+                    Some(true)
                 }
                 LastCommandExecutionKind::Remote => {
                     self.run_remote_count += 1;
+                    Some(false)
                 }
                 LastCommandExecutionKind::NoCommand => {
                     self.run_skipped_count += 1;
+                    None
+                }
+            };
+
+            if let (Some(cache_hit), Some(name)) = (cache_hit, &action.name) {
+                let stats = self
+                    .cache_stats_by_category
+                    .entry(name.category.clone())
+                    .or_default();
+                if cache_hit {
+                    stats.cache_hits += 1;
+                } else {
+                    stats.cache_misses += 1;
+                }
+            }
+        }
+
+        if let Some(target_stats) = &mut self.target_stats {
+            let target = action
+                .key
+                .as_ref()
+                .and_then(|key| key.owner.as_ref())
+                .and_then(|owner| {
+                    display_action_owner(owner, TargetDisplayOptions::for_log()).ok()
+                });
+            if let Some(target) = target {
+                let stats = target_stats.entry(target).or_default();
+                stats.action_count += 1;
+                match cache_hit {
+                    Some(true) => stats.cache_hits += 1,
+                    Some(false) => stats.cache_misses += 1,
+                    None => {}
                 }
             }
         }
@@ -1025,8 +1354,10 @@ impl<'a> InvocationRecorder<'a> {
     fn handle_analysis_start(
         &mut self,
         _analysis: &buck2_data::AnalysisStart,
-        _event: &BuckEvent,
+        event: &BuckEvent,
     ) -> buck2_error::Result<()> {
+        self.phase_wall_times
+            .start(Phase::Analysis, event.timestamp());
         self.time_to_first_analysis
             .get_or_insert_with(|| self.start_time.elapsed());
         Ok(())
@@ -1035,8 +1366,10 @@ impl<'a> InvocationRecorder<'a> {
     fn handle_load_start(
         &mut self,
         _eval: &buck2_data::LoadBuildFileStart,
-        _event: &BuckEvent,
+        event: &BuckEvent,
     ) -> buck2_error::Result<()> {
+        self.phase_wall_times
+            .start(Phase::Loading, event.timestamp());
         self.time_to_load_first_build_file
             .get_or_insert_with(|| self.start_time.elapsed());
         Ok(())
@@ -1045,7 +1378,7 @@ impl<'a> InvocationRecorder<'a> {
     fn handle_executor_stage_start(
         &mut self,
         executor_stage: &buck2_data::ExecutorStageStart,
-        _event: &BuckEvent,
+        event: &BuckEvent,
     ) -> buck2_error::Result<()> {
         match &executor_stage.stage {
             Some(buck2_data::executor_stage_start::Stage::Re(re_stage)) => match &re_stage.stage {
@@ -1057,9 +1390,17 @@ impl<'a> InvocationRecorder<'a> {
             },
             Some(buck2_data::executor_stage_start::Stage::Local(local_stage)) => {
                 match &local_stage.stage {
-                    Some(buck2_data::local_stage::Stage::Execute(_)) => {
+                    Some(buck2_data::local_stage::Stage::Execute(execute)) => {
                         self.time_to_first_command_execution_start
                             .get_or_insert_with(|| self.start_time.elapsed());
+                        if let Some(span_id) = event.span_id() {
+                            self.memory_pressure.local_execute_start(
+                                span_id,
+                                event.parent_id(),
+                                execute.weight,
+                                event.timestamp(),
+                            );
+                        }
                     }
                     _ => {}
                 }
@@ -1093,6 +1434,21 @@ impl<'a> InvocationRecorder<'a> {
         Ok(())
     }
 
+    fn handle_match_dep_files_end(
+        &mut self,
+        match_dep_files: &buck2_data::MatchDepFilesEnd,
+        _event: &BuckEvent,
+    ) -> buck2_error::Result<()> {
+        match match_dep_files.remote_verification {
+            None => {}
+            Some(v) if v == buck2_data::RemoteDepFileVerification::RemoteDepFileVerified as i32 => {
+                self.remote_dep_file_verified_count += 1
+            }
+            Some(_) => self.remote_dep_file_mismatch_count += 1,
+        }
+        Ok(())
+    }
+
     fn handle_re_session_created(
         &mut self,
         session: &buck2_data::RemoteExecutionSessionCreated,
@@ -1106,8 +1462,10 @@ impl<'a> InvocationRecorder<'a> {
     fn handle_materialization_end(
         &mut self,
         materialization: &buck2_data::MaterializationEnd,
-        _event: &BuckEvent,
+        event: &BuckEvent,
     ) -> buck2_error::Result<()> {
+        self.phase_wall_times
+            .end(Phase::Materialization, event.timestamp());
         self.materialization_output_size += materialization.total_bytes;
         self.materialization_files += materialization.file_count;
         Ok(())
@@ -1189,21 +1547,45 @@ impl<'a> InvocationRecorder<'a> {
         _event: &BuckEvent,
     ) -> buck2_error::Result<()> {
         let mut duration = Duration::default();
+        let mut contributions = CriticalPathContributions::default();
 
         for node in &info.critical_path {
             if let Some(d) = &node.duration {
-                duration += d.try_into_duration()?;
+                let d = d.try_into_duration()?;
+                duration += d;
+                contributions.add(None, d);
             }
         }
 
         for node in &info.critical_path2 {
             if let Some(d) = &node.duration {
-                duration += d.try_into_duration()?;
+                let d = d.try_into_duration()?;
+                duration += d;
+                let target = critical_path_entry_target(node);
+                if let (Some(target_stats), Some(target)) = (&mut self.target_stats, &target) {
+                    target_stats
+                        .entry(target.clone())
+                        .or_default()
+                        .critical_path_duration += d;
+                }
+                contributions.add(target, d);
             }
         }
 
         self.critical_path_duration = Some(duration);
+        self.critical_path_contributions = Some(contributions);
         self.critical_path_backend = info.backend_name.clone();
+        self.critical_path_scheduling = Some(info.critical_path_scheduling);
+        self.predicted_critical_path_actions = Some(info.predicted_critical_path_actions);
+        if let (Some(previous), Some(actual)) = (
+            &info.predicted_critical_path_actions_previous_duration,
+            &info.predicted_critical_path_actions_duration,
+        ) {
+            self.predicted_critical_path_actions_error_ms = Some(
+                actual.try_into_duration()?.as_millis() as i64
+                    - previous.try_into_duration()?.as_millis() as i64,
+            );
+        }
         Ok(())
     }
 
@@ -1348,6 +1730,16 @@ impl<'a> InvocationRecorder<'a> {
         self.re_avg_upload_speed
             .update(event.timestamp(), update.re_upload_bytes);
 
+        if let Some(network_timeline) = &mut self.network_timeline {
+            network_timeline.update(event.timestamp(), update);
+        }
+
+        self.memory_pressure.snapshot(
+            self.start_time.elapsed(),
+            event.timestamp(),
+            check_memory_pressure(Some(update), &self.system_info),
+        );
+
         self.peak_process_memory_bytes =
             max(self.peak_process_memory_bytes, process_memory(update));
         self.peak_used_disk_space_bytes =
@@ -1496,6 +1888,11 @@ impl<'a> InvocationRecorder<'a> {
                     buck2_data::span_start_event::Data::TestDiscovery(test_discovery) => {
                         self.handle_test_discovery_start(test_discovery, event)
                     }
+                    buck2_data::span_start_event::Data::Materialization(..) => {
+                        self.phase_wall_times
+                            .start(Phase::Materialization, event.timestamp());
+                        Ok(())
+                    }
                     _ => Ok(()),
                 }
             }
@@ -1510,6 +1907,12 @@ impl<'a> InvocationRecorder<'a> {
                     buck2_data::span_end_event::Data::ActionExecution(action) => {
                         self.handle_action_execution_end(action, event)
                     }
+                    buck2_data::span_end_event::Data::ExecutorStage(..) => {
+                        if let Some(span_id) = event.span_id() {
+                            self.memory_pressure.executor_stage_end(span_id);
+                        }
+                        Ok(())
+                    }
                     buck2_data::span_end_event::Data::FileWatcher(file_watcher) => {
                         self.handle_file_watcher_end(file_watcher, end.duration.as_ref(), event)
                     }
@@ -1522,8 +1925,17 @@ impl<'a> InvocationRecorder<'a> {
                     buck2_data::span_end_event::Data::Materialization(materialization) => {
                         self.handle_materialization_end(materialization, event)
                     }
+                    buck2_data::span_end_event::Data::MatchDepFiles(match_dep_files) => {
+                        self.handle_match_dep_files_end(match_dep_files, event)
+                    }
                     buck2_data::span_end_event::Data::Analysis(..) => {
                         self.analysis_count += 1;
+                        self.phase_wall_times
+                            .end(Phase::Analysis, event.timestamp());
+                        Ok(())
+                    }
+                    buck2_data::span_end_event::Data::Load(..) => {
+                        self.phase_wall_times.end(Phase::Loading, event.timestamp());
                         Ok(())
                     }
                     buck2_data::span_end_event::Data::DiceBlockConcurrentCommand(
@@ -1560,6 +1972,12 @@ impl<'a> InvocationRecorder<'a> {
                     buck2_data::instant_event::Data::TargetPatterns(tag) => {
                         self.handle_parsed_target_patterns(tag)
                     }
+                    buck2_data::instant_event::Data::TargetBuildEnd(end) => {
+                        if let Some(status) = buck2_data::TargetBuildStatus::from_i32(end.status) {
+                            *self.target_build_status_counts.entry(status).or_default() += 1;
+                        }
+                        Ok(())
+                    }
                     buck2_data::instant_event::Data::MaterializerStateInfo(materializer_state) => {
                         self.handle_materializer_state_info(materializer_state)
                     }
@@ -1600,6 +2018,35 @@ impl<'a> InvocationRecorder<'a> {
                         self.version_control_revision = Some(revision.clone());
                         Ok(())
                     }
+                    buck2_data::instant_event::Data::TelemetrySampling(sampling) => {
+                        self.telemetry_sampling = Some(sampling.clone());
+                        Ok(())
+                    }
+                    buck2_data::instant_event::Data::InvocationRecordSink(sink) => {
+                        self.invocation_record_sink = Some(sink.clone());
+                        Ok(())
+                    }
+                    buck2_data::instant_event::Data::PerTargetTelemetry(_) => {
+                        self.target_stats.get_or_insert_with(HashMap::new);
+                        Ok(())
+                    }
+                    buck2_data::instant_event::Data::NetworkTimeline(_) => {
+                        self.network_timeline
+                            .get_or_insert_with(NetworkTimeline::new);
+                        Ok(())
+                    }
+                    buck2_data::instant_event::Data::SoftErrorBudget(budget) => {
+                        self.soft_error_budget = Some(budget.clone());
+                        Ok(())
+                    }
+                    buck2_data::instant_event::Data::LostInputReexecution(reexecution) => {
+                        self.lost_input_reexecutions += 1;
+                        self.lost_input_reexecution_max_depth = max(
+                            self.lost_input_reexecution_max_depth,
+                            reexecution.depth as u64,
+                        );
+                        Ok(())
+                    }
                     _ => Ok(()),
                 }
             }
@@ -1771,6 +2218,24 @@ where
     }
 }
 
+/// Daemon CPU time by subsystem between the first and the last snapshot of the command.
+fn diff_daemon_cpu_attribution(
+    first: Option<&buck2_data::Snapshot>,
+    last: Option<&buck2_data::Snapshot>,
+) -> Option<buck2_data::DaemonCpuAttribution> {
+    let first = first?.daemon_cpu_attribution.as_ref()?;
+    let last = last?.daemon_cpu_attribution.as_ref()?;
+    Some(buck2_data::DaemonCpuAttribution {
+        loading_us: last.loading_us.saturating_sub(first.loading_us),
+        analysis_us: last.analysis_us.saturating_sub(first.analysis_us),
+        action_execution_us: last
+            .action_execution_us
+            .saturating_sub(first.action_execution_us),
+        materializer_us: last.materializer_us.saturating_sub(first.materializer_us),
+        dice_us: last.dice_us.saturating_sub(first.dice_us),
+    })
+}
+
 fn merge_file_watcher_stats(
     a: Option<buck2_data::FileWatcherStats>,
     b: Option<buck2_data::FileWatcherStats>,
@@ -1801,6 +2266,7 @@ pub(crate) fn try_get_invocation_recorder<'a>(
     command_name: &'static str,
     sanitized_argv: Vec<String>,
     log_size_counter_bytes: Option<Arc<AtomicU64>>,
+    console_health: Option<Arc<ConsoleHealth>>,
 ) -> buck2_error::Result<Box<InvocationRecorder<'a>>> {
     let write_to_path = opts
         .unstable_write_invocation_record
@@ -1828,6 +2294,8 @@ pub(crate) fn try_get_invocation_recorder<'a>(
     }
 
     let build_count = paths.map(|p| BuildCountManager::new(p.build_count_dir()));
+    let invocation_records_dir = paths.map(|p| p.invocation_records_dir());
+    let soft_error_history = paths.map(|p| SoftErrorHistoryManager::new(p.soft_error_budget_dir()));
 
     let recorder = InvocationRecorder::new(
         ctx.fbinit(),
@@ -1838,9 +2306,12 @@ pub(crate) fn try_get_invocation_recorder<'a>(
         ctx.trace_id.dupe(),
         ctx.isolation.to_string(),
         build_count,
+        invocation_records_dir,
+        soft_error_history,
         filesystem,
         ctx.restarted_trace_id.dupe(),
         log_size_counter_bytes,
+        console_health,
         ctx.client_metadata
             .iter()
             .map(ClientMetadata::to_proto)
@@ -1849,6 +2320,161 @@ pub(crate) fn try_get_invocation_recorder<'a>(
     Ok(Box::new(recorder))
 }
 
+/// Most targets sent in `InvocationRecord::target_telemetry`.
+const MAX_TELEMETRY_TARGETS: usize = 1000;
+
+/// Actions of a target, for `buck2.per_target_telemetry`.
+#[derive(Default)]
+struct TargetStats {
+    action_count: u64,
+    cache_hits: u64,
+    cache_misses: u64,
+    critical_path_duration: Duration,
+}
+
+/// Configured label of the target owning a critical path entry, if any.
+fn critical_path_entry_target(entry: &buck2_data::CriticalPathEntry2) -> Option<String> {
+    use buck2_data::critical_path_entry2::action_execution;
+    use buck2_data::critical_path_entry2::analysis;
+    use buck2_data::critical_path_entry2::materialization;
+    use buck2_data::critical_path_entry2::Entry;
+
+    let opts = TargetDisplayOptions::for_log();
+    match entry.entry.as_ref()? {
+        Entry::Analysis(analysis) => match analysis.target.as_ref()? {
+            analysis::Target::StandardTarget(label) => {
+                display_configured_target_label(label, opts).ok()
+            }
+        },
+        Entry::ActionExecution(execution) => match execution.owner.as_ref()? {
+            action_execution::Owner::TargetLabel(label) => {
+                display_configured_target_label(label, opts).ok()
+            }
+            action_execution::Owner::BxlKey(key) => display_bxl_key(key).ok(),
+            action_execution::Owner::AnonTarget(target) => display_anon_target(target).ok(),
+        },
+        Entry::Materialization(materialization) => match materialization.owner.as_ref()? {
+            materialization::Owner::TargetLabel(label) => {
+                display_configured_target_label(label, opts).ok()
+            }
+            materialization::Owner::BxlKey(key) => display_bxl_key(key).ok(),
+            materialization::Owner::AnonTarget(target) => display_anon_target(target).ok(),
+        },
+        Entry::ComputeCriticalPath(_) | Entry::Load(_) | Entry::Listing(_) => None,
+    }
+}
+
+/// Wall time during which at least one span of a phase was open. Spans of a phase run
+/// concurrently, so the sum of their durations would be much larger.
+#[derive(Default)]
+struct PhaseWallTime {
+    open: u64,
+    /// When the number of open spans became non-zero.
+    since: Option<SystemTime>,
+    total: Duration,
+}
+
+impl PhaseWallTime {
+    fn start(&mut self, at: SystemTime) {
+        if self.open == 0 {
+            self.since = Some(at);
+        }
+        self.open += 1;
+    }
+
+    fn end(&mut self, at: SystemTime) {
+        self.open = self.open.saturating_sub(1);
+        if self.open == 0 {
+            if let Some(since) = self.since.take() {
+                self.total += at.duration_since(since).unwrap_or_default();
+            }
+        }
+    }
+
+    /// Spans still open, e.g. if the command was interrupted, count until `now`.
+    fn total_ms(&self, now: SystemTime) -> Option<u64> {
+        let open = self.since.map_or(Duration::ZERO, |since| {
+            now.duration_since(since).unwrap_or_default()
+        });
+        u64::try_from((self.total + open).as_millis()).ok()
+    }
+}
+
+/// Phases of a build, in the order they happen for a given target.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Phase {
+    Loading,
+    Analysis,
+    ActionExecution,
+    Materialization,
+}
+
+impl Phase {
+    const ALL: [Phase; 4] = [
+        Phase::Loading,
+        Phase::Analysis,
+        Phase::ActionExecution,
+        Phase::Materialization,
+    ];
+}
+
+/// Wall time of the phases of a command.
+///
+/// Besides the wall time of each phase, time is also attributed to a single phase, so that the
+/// attributed times add up to at most the command duration. When phases overlap, the time is
+/// attributed to the latest phase in [`Phase`] order: the build of a target was already past the
+/// earlier phases. Time during which no phase runs is not attributed.
+#[derive(Default)]
+struct PhaseWallTimes {
+    phases: [PhaseWallTime; 4],
+    exclusive: [Duration; 4],
+    /// Time up to which the time was attributed.
+    last: Option<SystemTime>,
+}
+
+impl PhaseWallTimes {
+    fn start(&mut self, phase: Phase, at: SystemTime) {
+        self.attribute(at);
+        self.phases[phase as usize].start(at);
+    }
+
+    fn end(&mut self, phase: Phase, at: SystemTime) {
+        self.attribute(at);
+        self.phases[phase as usize].end(at);
+    }
+
+    /// The latest phase running.
+    fn current(&self) -> Option<Phase> {
+        Phase::ALL
+            .into_iter()
+            .rev()
+            .find(|phase| self.phases[*phase as usize].open > 0)
+    }
+
+    fn attribute(&mut self, at: SystemTime) {
+        if let (Some(phase), Some(last)) = (self.current(), self.last) {
+            self.exclusive[phase as usize] += at.duration_since(last).unwrap_or_default();
+        }
+        // Events are not strictly ordered by timestamp.
+        self.last = Some(self.last.map_or(at, |last| last.max(at)));
+    }
+
+    fn total_ms(&self, phase: Phase, now: SystemTime) -> Option<u64> {
+        self.phases[phase as usize].total_ms(now)
+    }
+
+    /// Like [`Self::total_ms`], but with overlapping time attributed to a single phase.
+    fn exclusive_ms(&self, phase: Phase, now: SystemTime) -> Option<u64> {
+        let mut exclusive = self.exclusive[phase as usize];
+        if let (Some(current), Some(last)) = (self.current(), self.last) {
+            if current == phase {
+                exclusive += now.duration_since(last).unwrap_or_default();
+            }
+        }
+        u64::try_from(exclusive.as_millis()).ok()
+    }
+}
+
 fn truncate_stderr(stderr: &str) -> &str {
     // If server crashed, it means something is very broken,
     // and we don't really need nicely formatted stderr.
@@ -1861,7 +2487,101 @@ fn truncate_stderr(stderr: &str) -> &str {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    use crate::subscribers::recorder::critical_path_entry_target;
     use crate::subscribers::recorder::truncate_stderr;
+    use crate::subscribers::recorder::Phase;
+    use crate::subscribers::recorder::PhaseWallTime;
+    use crate::subscribers::recorder::PhaseWallTimes;
+
+    #[test]
+    fn test_phase_wall_time() {
+        let t = |s| SystemTime::UNIX_EPOCH + Duration::from_secs(s);
+        let mut phase = PhaseWallTime::default();
+        // Two overlapping spans from 1 to 4, then a gap, then a span from 6 still open at 10.
+        phase.start(t(1));
+        phase.start(t(2));
+        phase.end(t(3));
+        phase.end(t(4));
+        phase.start(t(6));
+        assert_eq!(Some(7_000), phase.total_ms(t(10)));
+        phase.end(t(8));
+        assert_eq!(Some(5_000), phase.total_ms(t(10)));
+    }
+
+    #[test]
+    fn test_phase_wall_times_exclusive() {
+        let t = |s| SystemTime::UNIX_EPOCH + Duration::from_secs(s);
+        let mut phases = PhaseWallTimes::default();
+        // Loading from 0 to 4, analysis from 2 to 6, execution from 5 to 7, and a
+        // materialization from 8 still running at 10.
+        phases.start(Phase::Loading, t(0));
+        phases.start(Phase::Analysis, t(2));
+        phases.end(Phase::Loading, t(4));
+        phases.start(Phase::ActionExecution, t(5));
+        phases.end(Phase::Analysis, t(6));
+        phases.end(Phase::ActionExecution, t(7));
+        phases.start(Phase::Materialization, t(8));
+
+        let now = t(10);
+        assert_eq!(Some(4_000), phases.total_ms(Phase::Loading, now));
+        assert_eq!(Some(4_000), phases.total_ms(Phase::Analysis, now));
+        assert_eq!(Some(2_000), phases.exclusive_ms(Phase::Loading, now));
+        assert_eq!(Some(3_000), phases.exclusive_ms(Phase::Analysis, now));
+        assert_eq!(
+            Some(2_000),
+            phases.exclusive_ms(Phase::ActionExecution, now)
+        );
+        assert_eq!(
+            Some(2_000),
+            phases.exclusive_ms(Phase::Materialization, now)
+        );
+    }
+
+    #[test]
+    fn test_critical_path_entry_target() {
+        let label = buck2_data::ConfiguredTargetLabel {
+            label: Some(buck2_data::TargetLabel {
+                package: "root//foo".to_owned(),
+                name: "bar".to_owned(),
+            }),
+            configuration: Some(buck2_data::Configuration {
+                full_name: "cfg#abc".to_owned(),
+            }),
+            execution_configuration: None,
+        };
+        let entry =
+            |entry: buck2_data::critical_path_entry2::Entry| buck2_data::CriticalPathEntry2 {
+                entry: Some(entry),
+                ..Default::default()
+            };
+
+        let execution = entry(
+            buck2_data::critical_path_entry2::ActionExecution {
+                owner: Some(
+                    buck2_data::critical_path_entry2::action_execution::Owner::TargetLabel(
+                        label.clone(),
+                    ),
+                ),
+                ..Default::default()
+            }
+            .into(),
+        );
+        assert_eq!(
+            Some("root//foo:bar (cfg#abc)".to_owned()),
+            critical_path_entry_target(&execution)
+        );
+
+        let load = entry(
+            buck2_data::critical_path_entry2::Load {
+                package: "root//foo".to_owned(),
+            }
+            .into(),
+        );
+        assert_eq!(None, critical_path_entry_target(&load));
+    }
 
     #[test]
     fn test_truncate_stderr() {
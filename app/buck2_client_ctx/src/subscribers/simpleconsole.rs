@@ -19,6 +19,7 @@ use std::time::SystemTime;
 
 use async_trait::async_trait;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_event_observer::console_theme::ConsoleTheme;
 use buck2_event_observer::display;
 use buck2_event_observer::display::display_file_watcher_end;
 use buck2_event_observer::display::TargetDisplayOptions;
@@ -39,9 +40,13 @@ use buck2_events::BuckEvent;
 use buck2_wrapper_common::invocation_id::TraceId;
 use dupe::Dupe;
 use once_cell::sync::Lazy;
+use superconsole::style::ContentStyle;
 use superconsole::DrawMode;
+use superconsole::Line;
+use superconsole::Span;
 use superconsole::SuperConsole;
 
+use crate::subscribers::failed_action_output::FailedActionOutputFiles;
 use crate::subscribers::subscriber::EventSubscriber;
 use crate::subscribers::subscriber::Tick;
 use crate::subscribers::superconsole::io::io_in_flight_non_zero_counters;
@@ -159,6 +164,8 @@ pub struct SimpleConsole<E> {
     action_errors: Vec<buck2_data::ActionError>,
     last_print_time: Instant,
     last_shown_snapshot_ts: Option<SystemTime>,
+    theme: ConsoleTheme,
+    pub(crate) failed_action_output_files: Option<FailedActionOutputFiles>,
 }
 
 impl<E> SimpleConsole<E>
@@ -180,6 +187,8 @@ where
             action_errors: Vec::new(),
             last_print_time: Instant::now(),
             last_shown_snapshot_ts: None,
+            theme: ConsoleTheme::default(),
+            failed_action_output_files: None,
         }
     }
 
@@ -198,6 +207,8 @@ where
             action_errors: Vec::new(),
             last_print_time: Instant::now(),
             last_shown_snapshot_ts: None,
+            theme: ConsoleTheme::default(),
+            failed_action_output_files: None,
         }
     }
 
@@ -214,10 +225,55 @@ where
         }
     }
 
+    /// Write the output of failed actions to files in `log_dir`, if enabled by the console
+    /// preferences.
+    pub(crate) fn with_failed_action_output_files(
+        mut self,
+        log_dir: Option<AbsNormPathBuf>,
+    ) -> Self {
+        self.failed_action_output_files = log_dir
+            .map(|dir| FailedActionOutputFiles::new(&dir, &self.observer.session_info().trace_id));
+        self
+    }
+
     pub(crate) fn observer(&self) -> &EventObserver<E> {
         &self.observer
     }
 
+    /// Write the output of a failed action to files, if enabled.
+    pub(crate) fn write_failed_action_output(&self, error: &buck2_data::ActionError) {
+        if let Some(files) = &self.failed_action_output_files {
+            if let Err(e) = files.write(error) {
+                tracing::warn!("Error writing output of failed action: {:#}", e);
+            }
+        }
+    }
+
+    /// The lines pointing to the files written by `write_failed_action_output`, with the paths
+    /// linked to the files.
+    pub(crate) fn failed_action_output_lines(
+        &self,
+        error: &buck2_data::ActionError,
+        theme: &ConsoleTheme,
+        style: ContentStyle,
+    ) -> buck2_error::Result<Vec<Line>> {
+        let Some(files) = &self.failed_action_output_files else {
+            return Ok(Vec::new());
+        };
+        Ok(files
+            .paths(error)?
+            .into_iter()
+            .map(|(stream, path)| {
+                let path = path.to_string();
+                let url = theme.file_url(&path);
+                Line::from_iter([
+                    Span::new_styled_lossy(style.apply(format!("Full {} written to: ", stream))),
+                    theme.link_span(Span::new_styled_lossy(style.apply(path)), url),
+                ])
+            })
+            .collect())
+    }
+
     pub(crate) async fn update_event_observer(
         &mut self,
         event: &Arc<BuckEvent>,
@@ -284,7 +340,10 @@ where
     }
 
     fn print_action_error(&mut self, error: &buck2_data::ActionError) -> buck2_error::Result<()> {
-        let display = display::display_action_error(error, TargetDisplayOptions::for_log())?;
+        let mut display = display::display_action_error(error, TargetDisplayOptions::for_log())?;
+        if let Some(target) = &display.target {
+            display.action_id = self.theme.target_link(&display.action_id, target);
+        }
         let message = display.simple_format_with_timestamps(with_timestamps);
         if self.tty_mode == TtyMode::Disabled {
             // patternlint-disable-next-line buck2-cli-simpleconsole-echo
@@ -293,6 +352,9 @@ where
             // patternlint-disable-next-line buck2-cli-simpleconsole-echo
             crate::eprintln!("{}", message)?;
         }
+        for line in self.failed_action_output_lines(error, &self.theme, ContentStyle::default())? {
+            echo!("{}", line.render())?;
+        }
         self.notify_printed();
         Ok(())
     }
@@ -387,6 +449,20 @@ where
                     buck2_data::instant_event::Data::ActionError(error) => {
                         self.handle_action_error(error).await
                     }
+                    buck2_data::instant_event::Data::ActionOutputLines(output) => {
+                        self.handle_action_output_lines(output).await
+                    }
+                    buck2_data::instant_event::Data::ConsolePreferences(prefs) => {
+                        self.theme = ConsoleTheme::from_preferences(prefs);
+                        if let Some(files) = &mut self.failed_action_output_files {
+                            files.set_enabled(prefs.failed_action_output_files);
+                        }
+                        // Escape sequences are only useful when writing to a terminal.
+                        if matches!(self.tty_mode, TtyMode::Disabled) {
+                            self.theme.hyperlinks = false;
+                        }
+                        Ok(())
+                    }
                     _ => Ok(()),
                 }
             }
@@ -510,10 +586,27 @@ where
         Ok(())
     }
 
+    pub(crate) async fn handle_action_output_lines(
+        &mut self,
+        output: &buck2_data::ActionOutputLines,
+    ) -> buck2_error::Result<()> {
+        let lines = display::display_action_output_lines(output, TargetDisplayOptions::for_log())?;
+        for line in lines {
+            match self.tty_mode {
+                // Reset the style so that the output can't mess up the terminal.
+                TtyMode::Enabled => echo!("{}\x1b[0m", line)?,
+                TtyMode::Disabled => echo!("{}", display::sanitize_output_colors(line.as_bytes()))?,
+            }
+        }
+        self.notify_printed();
+        Ok(())
+    }
+
     pub(crate) async fn handle_action_error(
         &mut self,
         error: &buck2_data::ActionError,
     ) -> buck2_error::Result<()> {
+        self.write_failed_action_output(error);
         self.print_action_error(error)?;
         self.action_errors.push(error.clone());
         Ok(())
@@ -542,7 +635,7 @@ where
         result: &buck2_data::TestResult,
         _event: &BuckEvent,
     ) -> buck2_error::Result<()> {
-        if let Some(msg) = display::format_test_result(result)? {
+        if let Some(msg) = display::format_test_result(result, &self.theme)? {
             let mut buffer = String::new();
 
             for line in msg {
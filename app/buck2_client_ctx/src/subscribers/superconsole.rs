@@ -18,6 +18,7 @@ use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_data::CommandExecutionDetails;
 use buck2_error::buck2_error;
 use buck2_error::BuckErrorContext;
+use buck2_event_observer::console_theme::ConsoleTheme;
 use buck2_event_observer::display;
 use buck2_event_observer::display::display_file_watcher_end;
 use buck2_event_observer::display::TargetDisplayOptions;
@@ -52,6 +53,7 @@ use superconsole::Span;
 pub(crate) use superconsole::SuperConsole;
 
 use crate::console_interaction_stream::SuperConsoleToggle;
+use crate::subscribers::console_health::ConsoleHealth;
 use crate::subscribers::simpleconsole::SimpleConsole;
 use crate::subscribers::subscriber::EventSubscriber;
 use crate::subscribers::subscriber::Tick;
@@ -61,6 +63,7 @@ use crate::subscribers::superconsole::debugger::StarlarkDebuggerComponent;
 use crate::subscribers::superconsole::dice::DiceComponent;
 use crate::subscribers::superconsole::header::TasksHeader;
 use crate::subscribers::superconsole::io::IoHeader;
+use crate::subscribers::superconsole::re::DownloadsComponent;
 use crate::subscribers::superconsole::re::ReHeader;
 use crate::subscribers::superconsole::session_info::SessionInfoComponent;
 use crate::subscribers::superconsole::system_warning::SystemWarningComponent;
@@ -102,6 +105,7 @@ pub struct StatefulSuperConsoleImpl {
     state: SuperConsoleState,
     super_console: SuperConsole,
     verbosity: Verbosity,
+    health: Arc<ConsoleHealth>,
 }
 
 #[derive(Copy, Clone, Dupe, Debug)]
@@ -152,6 +156,7 @@ pub struct SuperConsoleConfig {
     /// Two lines for root events with single child event.
     pub two_lines: bool,
     pub max_lines: usize,
+    pub theme: ConsoleTheme,
 }
 
 impl Default for SuperConsoleConfig {
@@ -166,6 +171,7 @@ impl Default for SuperConsoleConfig {
             display_platform: false,
             two_lines: false,
             max_lines: 10,
+            theme: ConsoleTheme::default(),
         }
     }
 }
@@ -234,6 +240,12 @@ impl<'s> Component for BuckRootComponent<'s> {
             },
             mode,
         )?;
+        draw.draw(
+            &DownloadsComponent {
+                download_state: self.state.simple_console.observer.download_state(),
+            },
+            mode,
+        )?;
         draw.draw(
             &IoHeader {
                 super_console_config: &self.state.config,
@@ -245,6 +257,7 @@ impl<'s> Component for BuckRootComponent<'s> {
             &TestHeader {
                 session_info: self.state.session_info(),
                 test_state: self.state.simple_console.observer.test_state(),
+                theme: &self.state.config.theme,
             },
             mode,
         )?;
@@ -318,6 +331,33 @@ impl StatefulSuperConsole {
         )
     }
 
+    /// Report dropped frames, render errors and fallbacks to the simple console to `health`.
+    pub(crate) fn with_console_health(self, health: Arc<ConsoleHealth>) -> Self {
+        match self {
+            Self::Running(mut console) => {
+                console.health = health;
+                Self::Running(console)
+            }
+            Self::Finalized(console) => Self::Finalized(console),
+        }
+    }
+
+    /// Write the output of failed actions to files in `log_dir`, if enabled by the console
+    /// preferences.
+    pub(crate) fn with_failed_action_output_files(self, log_dir: Option<AbsNormPathBuf>) -> Self {
+        match self {
+            Self::Running(mut console) => {
+                take_mut::take(&mut console.state.simple_console, |simple_console| {
+                    simple_console.with_failed_action_output_files(log_dir)
+                });
+                Self::Running(console)
+            }
+            Self::Finalized(console) => {
+                Self::Finalized(console.with_failed_action_output_files(log_dir))
+            }
+        }
+    }
+
     pub(crate) fn new_with_root(
         trace_id: TraceId,
         command_name: &str,
@@ -365,6 +405,7 @@ impl StatefulSuperConsole {
             )?,
             super_console,
             verbosity,
+            health: Arc::default(),
         }))
     }
 
@@ -383,14 +424,17 @@ impl StatefulSuperConsole {
         builder
     }
 
-    pub fn render_result_errors(result: &buck2_cli_proto::CommandResult) -> Lines {
+    pub fn render_result_errors(
+        result: &buck2_cli_proto::CommandResult,
+        theme: &ConsoleTheme,
+    ) -> Lines {
         let mut lines = Lines::new();
         if let buck2_cli_proto::CommandResult {
             result: Some(buck2_cli_proto::command_result::Result::Error(e)),
         } = result
         {
             let style = ContentStyle {
-                foreground_color: Some(Color::DarkRed),
+                foreground_color: Some(theme.color(Color::DarkRed)),
                 ..Default::default()
             };
             for e in &e.errors {
@@ -413,8 +457,10 @@ impl StatefulSuperConsole {
         let mut res = Ok(());
         take_mut::take(self, |this| match this {
             Self::Running(super_console) => {
+                let health = super_console.health.dupe();
                 let (state, err) = super_console.finalize();
                 if let Some(err) = err {
+                    health.render_error();
                     res = Err(err);
                 }
                 Self::Finalized(state.simple_console)
@@ -424,6 +470,23 @@ impl StatefulSuperConsole {
 
         res
     }
+
+    /// Hand the rest of the command over to the simple console after the superconsole failed to
+    /// render, rather than failing the command.
+    fn fall_back_to_simple_console(&mut self, err: buck2_error::Error) {
+        take_mut::take(self, |this| match this {
+            Self::Running(super_console) => {
+                tracing::warn!(
+                    "Superconsole failed to render, falling back to simple console: {:#}",
+                    err
+                );
+                super_console.health.render_error();
+                super_console.health.fall_back_to_simple_console();
+                Self::Finalized(super_console.state.simple_console)
+            }
+            v => v,
+        });
+    }
 }
 
 impl SuperConsoleState {
@@ -534,6 +597,9 @@ impl StatefulSuperConsoleImpl {
                     buck2_data::instant_event::Data::ActionError(error) => {
                         self.handle_action_error(error).await
                     }
+                    buck2_data::instant_event::Data::ActionOutputLines(output) => {
+                        self.handle_action_output_lines(output).await
+                    }
                     _ => Ok(()),
                 }
             }
@@ -607,6 +673,19 @@ impl StatefulSuperConsoleImpl {
         Ok(())
     }
 
+    async fn handle_action_output_lines(
+        &mut self,
+        output: &buck2_data::ActionOutputLines,
+    ) -> buck2_error::Result<()> {
+        let lines = display::display_action_output_lines(
+            output,
+            TargetDisplayOptions::for_console(self.state.config.display_platform),
+        )?;
+        self.super_console
+            .emit(Lines(lines.into_map(|x| Line::sanitized(&x))));
+        Ok(())
+    }
+
     async fn handle_action_execution_end(
         &mut self,
         action: &buck2_data::ActionExecutionEnd,
@@ -647,11 +726,17 @@ impl StatefulSuperConsoleImpl {
         &mut self,
         error: &buck2_data::ActionError,
     ) -> buck2_error::Result<()> {
+        self.state.simple_console.write_failed_action_output(error);
+
         let mut lines = vec![];
         let display_platform = self.state.config.display_platform;
 
+        let theme = &self.state.config.theme;
+        let error_color = theme.color(Color::DarkRed);
+
         let display::ActionErrorDisplay {
             action_id,
+            target,
             reason,
             command,
             ..
@@ -660,26 +745,37 @@ impl StatefulSuperConsoleImpl {
             TargetDisplayOptions::for_console(display_platform),
         )?;
 
-        lines.push(Line::from_iter([Span::new_styled_lossy(
-            StyledContent::new(
-                ContentStyle {
-                    attributes: Attribute::Bold.into(),
-                    ..Default::default()
-                },
-                format!("Action failed: {}", action_id,),
+        let bold = ContentStyle {
+            attributes: Attribute::Bold.into(),
+            ..Default::default()
+        };
+        lines.push(Line::from_iter([
+            Span::new_styled_lossy(bold.apply("Action failed: ".to_owned())),
+            theme.link_span(
+                Span::new_styled_lossy(bold.apply(action_id)),
+                target.and_then(|t| theme.target_url(&t)),
             ),
-        )]));
+        ]));
 
         lines.extend(
-            reason.lines().map(|l| {
-                Line::from_iter([Span::new_styled_lossy(l.to_owned().with(Color::DarkRed))])
-            }),
+            reason
+                .lines()
+                .map(|l| Line::from_iter([Span::new_styled_lossy(l.to_owned().with(error_color))])),
         );
 
         if let Some(command) = command {
-            lines_for_command_details(&command, self.verbosity, &mut lines);
+            lines_for_command_details(&command, self.verbosity, error_color, &mut lines);
         }
 
+        lines.extend(self.state.simple_console.failed_action_output_lines(
+            error,
+            theme,
+            ContentStyle {
+                foreground_color: Some(error_color),
+                ..Default::default()
+            },
+        )?);
+
         self.super_console.emit(Lines(lines));
 
         Ok(())
@@ -689,7 +785,7 @@ impl StatefulSuperConsoleImpl {
         &mut self,
         result: &buck2_data::TestResult,
     ) -> buck2_error::Result<()> {
-        if let Some(msg) = display::format_test_result(result)? {
+        if let Some(msg) = display::format_test_result(result, &self.state.config.theme)? {
             self.super_console.emit(msg);
         }
 
@@ -700,7 +796,13 @@ impl StatefulSuperConsoleImpl {
         &mut self,
         prefs: &buck2_data::ConsolePreferences,
     ) -> buck2_error::Result<()> {
-        self.state.config.max_lines = prefs.max_lines.try_into()?;
+        if let Some(max_lines) = prefs.max_lines {
+            self.state.config.max_lines = max_lines.try_into()?;
+        }
+        self.state.config.theme = ConsoleTheme::from_preferences(prefs);
+        if let Some(files) = &mut self.state.simple_console.failed_action_output_files {
+            files.set_enabled(prefs.failed_action_output_files);
+        }
 
         Ok(())
     }
@@ -780,10 +882,13 @@ impl StatefulSuperConsoleImpl {
 
     async fn tick(&mut self, tick: &Tick) -> buck2_error::Result<()> {
         self.state.current_tick = tick.dupe();
-        self.super_console.render(&BuckRootComponent {
+        let res = self.super_console.render(&BuckRootComponent {
             header: &self.header,
             state: &self.state,
-        })?;
+        });
+        self.health
+            .set_dropped_frames(self.super_console.dropped_frames());
+        res?;
         Ok(())
     }
 
@@ -791,7 +896,7 @@ impl StatefulSuperConsoleImpl {
         &mut self,
         result: &buck2_cli_proto::CommandResult,
     ) -> buck2_error::Result<()> {
-        let lines = StatefulSuperConsole::render_result_errors(result);
+        let lines = StatefulSuperConsole::render_result_errors(result, &self.state.config.theme);
         self.super_console.emit(lines);
         Ok(())
     }
@@ -857,7 +962,9 @@ impl EventSubscriber for StatefulSuperConsole {
 
     async fn tick(&mut self, tick: &Tick) -> buck2_error::Result<()> {
         if let Self::Running(super_console) = self {
-            super_console.tick(tick).await?;
+            if let Err(e) = super_console.tick(tick).await {
+                self.fall_back_to_simple_console(e);
+            }
         }
         Ok(())
     }
@@ -871,6 +978,7 @@ impl EventSubscriber for StatefulSuperConsole {
 fn lines_for_command_details(
     command_failed: &CommandExecutionDetails,
     verbosity: Verbosity,
+    error_color: Color,
     lines: &mut Vec<Line>,
 ) {
     if let Some(command_kind) = command_failed.command_kind.as_ref() {
@@ -893,7 +1001,7 @@ fn lines_for_command_details(
                 };
 
                 lines.push(Line::from_iter([Span::new_styled_lossy(
-                    format!("Reproduce locally: `{}`", command).with(Color::DarkRed),
+                    format!("Reproduce locally: `{}`", command).with(error_color),
                 )]));
             }
             Some(Command::RemoteCommand(remote_command)) => {
@@ -907,7 +1015,7 @@ fn lines_for_command_details(
                 };
 
                 lines.push(Line::from_iter([Span::new_styled_lossy(
-                    help_message.with(Color::DarkRed),
+                    help_message.with(error_color),
                 )]));
             }
             Some(Command::OmittedLocalCommand(..)) | None => {
@@ -929,7 +1037,7 @@ fn lines_for_command_details(
                 };
 
                 lines.push(Line::from_iter([Span::new_styled_lossy(
-                    format!("Reproduce locally: `{}`", command).with(Color::DarkRed),
+                    format!("Reproduce locally: `{}`", command).with(error_color),
                 )]));
             }
             Some(Command::WorkerCommand(worker_command)) => {
@@ -948,7 +1056,7 @@ fn lines_for_command_details(
                 };
 
                 lines.push(Line::from_iter([Span::new_styled_lossy(
-                    format!("Reproduce locally: `{}`", command).with(Color::DarkRed),
+                    format!("Reproduce locally: `{}`", command).with(error_color),
                 )]));
             }
         };
@@ -957,14 +1065,14 @@ fn lines_for_command_details(
     lines.push(Line::from_iter([Span::new_styled_lossy(
         "stdout:"
             .to_owned()
-            .with(Color::DarkRed)
+            .with(error_color)
             .attribute(Attribute::Bold),
     )]));
     lines.extend(Lines::from_colored_multiline_string(&command_failed.stdout));
     lines.push(Line::from_iter([Span::new_styled_lossy(
         "stderr:"
             .to_owned()
-            .with(Color::DarkRed)
+            .with(error_color)
             .attribute(Attribute::Bold),
     )]));
     lines.extend(Lines::from_colored_multiline_string(&command_failed.stderr));
@@ -974,7 +1082,7 @@ fn lines_for_command_details(
             lines.push(Line::from_iter([Span::new_styled_lossy(
                 "info:"
                     .to_owned()
-                    .with(Color::DarkRed)
+                    .with(error_color)
                     .attribute(Attribute::Bold),
             )]));
             lines.extend(Lines::from_colored_multiline_string(additional_message));
@@ -1273,4 +1381,31 @@ mod tests {
         assert_frame_contains(&frame, "some stderr output");
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_console_health() -> buck2_error::Result<()> {
+        let health = Arc::new(ConsoleHealth::default());
+        let mut console = StatefulSuperConsole::new(
+            "build",
+            TraceId::new(),
+            test_console(),
+            Verbosity::default(),
+            true,
+            Default::default(),
+            Default::default(),
+            None,
+        )?
+        .with_console_health(health.dupe());
+
+        console.tick(&Tick::now()).await?;
+        if let StatefulSuperConsole::Running(c) = &mut console {
+            c.super_console.test_output_mut()?.should_render = false;
+        }
+        console.tick(&Tick::now()).await?;
+        console.tick(&Tick::now()).await?;
+
+        assert_eq!(health.dropped_frames(), 2);
+        assert!(!health.is_degraded());
+        Ok(())
+    }
 }
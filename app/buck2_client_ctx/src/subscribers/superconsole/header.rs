@@ -52,6 +52,7 @@ impl<'s> Component for TasksHeader<'s> {
                 progress_stats: self.state.extra().progress_state().progress_stats(),
                 action_stats: self.state.simple_console.observer.action_stats(),
                 time_elapsed: time_elapsed(self.state),
+                time_remaining: time_remaining(self.state),
             }
             .draw(dimensions, mode)
         } else {
@@ -64,6 +65,7 @@ struct HeaderData<'s> {
     header: &'s str,
     action_stats: &'s ActionStats,
     elapsed_str: String,
+    remaining_str: Option<String>,
     finished: u64,
     remaining: u64,
 }
@@ -80,6 +82,7 @@ impl<'s> HeaderData<'s> {
             header,
             action_stats: state.simple_console.observer().action_stats(),
             elapsed_str: time_elapsed(state),
+            remaining_str: time_remaining(state),
             finished,
             remaining,
         }
@@ -88,6 +91,13 @@ impl<'s> HeaderData<'s> {
     fn total(&self) -> u64 {
         self.finished + self.remaining
     }
+
+    fn elapsed_with_remaining(&self) -> String {
+        match &self.remaining_str {
+            Some(remaining) => format!("{} (ETA {})", self.elapsed_str, remaining),
+            None => self.elapsed_str.clone(),
+        }
+    }
 }
 
 struct SimpleHeader<'s> {
@@ -123,6 +133,25 @@ fn time_elapsed(state: &SuperConsoleState) -> String {
     fmt_duration::fmt_duration(state.current_tick.elapsed_time, state.time_speed.speed())
 }
 
+/// Remaining time based on the critical path of previous builds, `None` if this command does not
+/// build targets.
+fn time_remaining(state: &SuperConsoleState) -> Option<String> {
+    let estimate = state
+        .simple_console
+        .observer
+        .build_time_estimator
+        .as_ref()?
+        .estimate()?;
+    let elapsed = state
+        .current_tick
+        .elapsed_time
+        .mul_f64(state.time_speed.speed());
+    Some(match estimate.remaining(elapsed) {
+        Some(remaining) => format!("~{}", fmt_duration::fmt_duration(remaining, 1.0)),
+        None => "unknown".to_owned(),
+    })
+}
+
 /// This component is used to display summary counts about the number of jobs.
 struct CountComponent<'s> {
     data: &'s HeaderData<'s>,
@@ -150,14 +179,15 @@ impl<'s> Component for CountComponent<'s> {
                         )
                         .as_str();
                     }
-                    actions_summary += format!("Time elapsed: {}", self.data.elapsed_str).as_str();
+                    actions_summary +=
+                        format!("Time elapsed: {}", self.data.elapsed_with_remaining()).as_str();
                     actions_summary
                 } else {
                     format!(
                         "Remaining: {}/{}. Time elapsed: {}",
                         self.data.remaining,
                         self.data.total(),
-                        self.data.elapsed_str
+                        self.data.elapsed_with_remaining()
                     )
                 };
                 Ok(Lines(vec![Line::unstyled(&contents)?]))
@@ -182,6 +212,7 @@ pub(crate) struct ProgressHeader<'s> {
     progress_stats: &'s BuildProgressStats,
     action_stats: &'s ActionStats,
     time_elapsed: String,
+    time_remaining: Option<String>,
 }
 
 #[derive(Clone, Copy)]
@@ -420,10 +451,14 @@ impl<'s> Component for ProgressHeader<'s> {
             + 4 * (num_width - 1);
 
         let elapsed = format!("Time elapsed: {}", &self.time_elapsed);
+        let elapsed_with_remaining = match &self.time_remaining {
+            Some(remaining) => format!("Time elapsed: {} (ETA {})", &self.time_elapsed, remaining),
+            None => elapsed.clone(),
+        };
 
         // During normal drawing, the elapsed time is in the last row at the end. In the final rendering it gets its own line and is on the left.
         let inline_elapsed = match mode {
-            DrawMode::Normal => &elapsed,
+            DrawMode::Normal => &elapsed_with_remaining,
             DrawMode::Final => "",
         };
 
@@ -609,6 +644,7 @@ mod tests {
                 progress_stats,
                 action_stats,
                 time_elapsed: "1234s".to_owned(),
+                time_remaining: None,
             };
 
             header.draw(
@@ -644,6 +680,7 @@ mod tests {
                 progress_stats: &progress_stats(),
                 action_stats: &action_stats(),
                 time_elapsed: "1234s".to_owned(),
+                time_remaining: None,
             }
             .draw(
                 Dimensions { width, height: 10 },
@@ -762,6 +799,7 @@ mod tests {
             header: "test",
             action_stats: &action_stats,
             elapsed_str: "123s".to_owned(),
+            remaining_str: None,
             finished: 0,
             remaining: 3,
         })
@@ -792,6 +830,7 @@ mod tests {
             header: "test",
             action_stats: &action_stats,
             elapsed_str: "0.0s".to_owned(),
+            remaining_str: None,
             finished: 0,
             remaining: 2,
         })
@@ -810,6 +849,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_estimated_remaining() -> buck2_error::Result<()> {
+        let action_stats = ActionStats {
+            local_actions: 0,
+            remote_actions: 0,
+            cached_actions: 0,
+            fallback_actions: 0,
+            remote_dep_file_cached_actions: 0,
+        };
+        let output = SimpleHeader::new_for_data(HeaderData {
+            header: "test",
+            action_stats: &action_stats,
+            elapsed_str: "0.0s".to_owned(),
+            remaining_str: Some("~4.0s".to_owned()),
+            finished: 0,
+            remaining: 2,
+        })
+        .draw(
+            Dimensions {
+                width: 60,
+                height: 10,
+            },
+            DrawMode::Normal,
+        )?;
+
+        let expected = "test          Remaining: 2/2. Time elapsed: 0.0s (ETA ~4.0s)\n".to_owned();
+
+        pretty_assertions::assert_eq!(output.fmt_for_test().to_string(), expected);
+
+        Ok(())
+    }
+
     #[test]
     fn test_children() -> buck2_error::Result<()> {
         let action_stats = ActionStats {
@@ -823,6 +894,7 @@ mod tests {
             header: "test",
             action_stats: &action_stats,
             elapsed_str: "0.0s".to_owned(),
+            remaining_str: None,
             finished: 0,
             remaining: 1,
         })
@@ -855,6 +927,7 @@ mod tests {
             header: "test",
             action_stats: &action_stats,
             elapsed_str: "0.0s".to_owned(),
+            remaining_str: None,
             finished: 0,
             remaining: 1,
         })
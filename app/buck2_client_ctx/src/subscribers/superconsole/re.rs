@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use buck2_event_observer::download_state::DownloadState;
 use buck2_event_observer::re_state::ReState;
 use buck2_event_observer::two_snapshots::TwoSnapshots;
 use superconsole::Component;
@@ -33,3 +34,18 @@ impl<'a> Component for ReHeader<'a> {
         )?)
     }
 }
+
+/// Draw the progress of the large downloads in flight.
+pub(crate) struct DownloadsComponent<'a> {
+    pub(crate) download_state: &'a DownloadState,
+}
+
+impl<'a> Component for DownloadsComponent<'a> {
+    fn draw_unchecked(
+        &self,
+        _dimensions: superconsole::Dimensions,
+        mode: superconsole::DrawMode,
+    ) -> anyhow::Result<superconsole::Lines> {
+        Ok(self.download_state.render(mode))
+    }
+}
@@ -8,6 +8,7 @@
  */
 
 use buck2_error::BuckErrorContext;
+use buck2_event_observer::console_theme::ConsoleTheme;
 use buck2_event_observer::test_state::TestState;
 use crossterm::style::Color;
 use crossterm::style::ContentStyle;
@@ -75,11 +76,15 @@ impl TestCounterColumn {
         get_from_test_statues: |_test_statuses| &None,
     };
 
-    fn to_span_from_test_state(&self, test_state: &TestState) -> anyhow::Result<Span> {
+    fn to_span_from_test_state(
+        &self,
+        test_state: &TestState,
+        theme: &ConsoleTheme,
+    ) -> anyhow::Result<Span> {
         StylizedCount {
             label: self.label,
             count: (self.get_from_test_state)(test_state),
-            color: self.color,
+            color: self.color.map(|c| theme.color(c)),
         }
         .to_span()
     }
@@ -104,6 +109,7 @@ impl TestCounterComponent {
     fn draw_unchecked(
         &self,
         test_state: &TestState,
+        theme: &ConsoleTheme,
         _dimensions: Dimensions,
         mode: DrawMode,
     ) -> anyhow::Result<Lines> {
@@ -115,20 +121,20 @@ impl TestCounterComponent {
 
         let mut spans = Vec::new();
         if test_state.listing_failed > 0 {
-            spans.push(TestCounterColumn::LISTING_FAIL.to_span_from_test_state(test_state)?);
+            spans.push(TestCounterColumn::LISTING_FAIL.to_span_from_test_state(test_state, theme)?);
             spans.push(". ".try_into()?);
         }
-        spans.push(TestCounterColumn::DISCOVERED.to_span_from_test_state(test_state)?);
+        spans.push(TestCounterColumn::DISCOVERED.to_span_from_test_state(test_state, theme)?);
         spans.push(". ".try_into()?);
-        spans.push(TestCounterColumn::PASS.to_span_from_test_state(test_state)?);
+        spans.push(TestCounterColumn::PASS.to_span_from_test_state(test_state, theme)?);
         spans.push(". ".try_into()?);
-        spans.push(TestCounterColumn::FAIL.to_span_from_test_state(test_state)?);
+        spans.push(TestCounterColumn::FAIL.to_span_from_test_state(test_state, theme)?);
         spans.push(". ".try_into()?);
-        spans.push(TestCounterColumn::FATAL.to_span_from_test_state(test_state)?);
+        spans.push(TestCounterColumn::FATAL.to_span_from_test_state(test_state, theme)?);
         spans.push(". ".try_into()?);
-        spans.push(TestCounterColumn::SKIP.to_span_from_test_state(test_state)?);
+        spans.push(TestCounterColumn::SKIP.to_span_from_test_state(test_state, theme)?);
         spans.push(". ".try_into()?);
-        spans.push(TestCounterColumn::TIMEOUT.to_span_from_test_state(test_state)?);
+        spans.push(TestCounterColumn::TIMEOUT.to_span_from_test_state(test_state, theme)?);
         Ok(Lines::from_iter([Line::from_iter(spans)]))
     }
 }
@@ -137,6 +143,7 @@ impl TestCounterComponent {
 pub(crate) struct TestHeader<'a> {
     pub(crate) session_info: &'a SessionInfo,
     pub(crate) test_state: &'a TestState,
+    pub(crate) theme: &'a ConsoleTheme,
 }
 
 impl<'a> Component for TestHeader<'a> {
@@ -146,7 +153,7 @@ impl<'a> Component for TestHeader<'a> {
         mode: superconsole::DrawMode,
     ) -> anyhow::Result<superconsole::Lines> {
         if self.session_info.test_session.is_some() {
-            TestCounterComponent.draw_unchecked(self.test_state, dimensions, mode)
+            TestCounterComponent.draw_unchecked(self.test_state, self.theme, dimensions, mode)
         } else {
             Ok(Lines::new())
         }
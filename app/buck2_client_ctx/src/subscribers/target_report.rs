@@ -0,0 +1,129 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::working_dir::AbsWorkingDir;
+use buck2_error::BuckErrorContext;
+use buck2_event_observer::target_status::TargetCompletion;
+use buck2_event_observer::target_status::TargetStatusTracker;
+use buck2_event_observer::unpack_event::unpack_event;
+use buck2_event_observer::unpack_event::UnpackedBuckEvent;
+use buck2_events::BuckEvent;
+use serde::Serialize;
+
+use crate::path_arg::PathArg;
+use crate::subscribers::subscriber::EventSubscriber;
+
+#[derive(Serialize)]
+struct TargetReport {
+    success: bool,
+    targets: Vec<TargetReportEntry>,
+}
+
+#[derive(Serialize)]
+struct TargetReportEntry {
+    target: String,
+    configuration: String,
+    status: &'static str,
+    /// Paths relative to the project root.
+    outputs: Vec<String>,
+}
+
+/// Writes the report requested by `buck2 build --report-file`: the status and outputs of each
+/// requested configured target.
+pub struct TargetReportWriter {
+    path: PathArg,
+    tracker: TargetStatusTracker,
+    completed: Vec<TargetCompletion>,
+    written: bool,
+}
+
+impl TargetReportWriter {
+    pub fn new(path: PathArg) -> Self {
+        Self {
+            path,
+            tracker: TargetStatusTracker::default(),
+            completed: Vec::new(),
+            written: false,
+        }
+    }
+
+    fn handle_event(&mut self, event: &BuckEvent) -> buck2_error::Result<()> {
+        match unpack_event(event)? {
+            UnpackedBuckEvent::SpanEnd(
+                _,
+                _,
+                buck2_data::span_end_event::Data::ActionExecution(action),
+            ) => self.tracker.action_execution_end(action),
+            UnpackedBuckEvent::Instant(
+                _,
+                _,
+                buck2_data::instant_event::Data::TargetBuildEnd(end),
+            ) => {
+                self.completed.push(self.tracker.target_build_end(end)?);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn write(&mut self, success: bool) -> buck2_error::Result<()> {
+        if self.written {
+            return Ok(());
+        }
+        self.written = true;
+
+        let mut targets: Vec<_> = self
+            .completed
+            .iter()
+            .map(|c| TargetReportEntry {
+                target: c.target.clone(),
+                configuration: c.configuration.clone(),
+                status: c.status.as_str(),
+                outputs: c.outputs.clone(),
+            })
+            .collect();
+        targets.sort_by(|a, b| (&a.target, &a.configuration).cmp(&(&b.target, &b.configuration)));
+
+        let path = self.path.resolve(&AbsWorkingDir::current_dir()?);
+        let report = serde_json::to_vec_pretty(&TargetReport { success, targets })?;
+        fs_util::write(&path, report)
+            .with_buck_error_context(|| format!("Writing target report to `{}`", path.display()))
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for TargetReportWriter {
+    async fn handle_events(&mut self, events: &[Arc<BuckEvent>]) -> buck2_error::Result<()> {
+        for event in events {
+            self.handle_event(event)?;
+        }
+        Ok(())
+    }
+
+    async fn handle_command_result(
+        &mut self,
+        result: &buck2_cli_proto::CommandResult,
+    ) -> buck2_error::Result<()> {
+        match &result.result {
+            Some(buck2_cli_proto::command_result::Result::BuildResponse(response)) => {
+                self.write(response.errors.is_empty())
+            }
+            _ => self.write(false),
+        }
+    }
+
+    async fn exit(&mut self) -> buck2_error::Result<()> {
+        // The command did not produce a result, e.g. the daemon went away.
+        self.write(false)
+    }
+}
@@ -12,6 +12,7 @@ mod path_completer;
 mod path_sanitizer;
 mod results;
 mod target;
+mod target_cache;
 
 use std::time::Duration;
 
@@ -23,9 +24,13 @@ use buck2_client_ctx::streaming::BuckSubcommand;
 use buck2_core::buck2_env;
 use buck2_core::fs::fs_util;
 use buck2_core::fs::paths::abs_path::AbsPath;
+use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
 use clap::ArgMatches;
 use package::PackageCompleter;
+use target::CachedTargetResolver;
 use target::CompleteTargetCommand;
+use target::TargetCompleter;
+use target_cache::TargetCache;
 
 #[derive(Debug, clap::Parser)]
 #[clap(name = "complete", hide = true)]
@@ -115,15 +120,36 @@ impl CompleteCommand {
                 let completer = PackageCompleter::new(&ctx.working_dir, roots).await?;
                 print_completions(completer.complete(given_partial_package).await)
             }
-            // Target completion requires a round-trip to the daemon, so we spin up a new command
+            // Target completion requires a round-trip to the daemon, so we spin up a new command,
+            // unless the targets of the package were cached by a recent completion
             [given_package, given_partial_target] => {
-                let completer = CompleteTargetCommand::new(
-                    &ctx.working_dir,
-                    given_package.to_owned(),
-                    given_partial_target.to_owned(),
-                    print_completions,
+                let paths = ctx.paths()?;
+                let cache = TargetCache::new(
+                    paths
+                        .tmp_dir()
+                        .join(ForwardRelativePath::unchecked_new("completion_cache")),
                 );
-                completer.exec_async(matches, ctx).await
+                let mut cached_resolver = CachedTargetResolver { cache: &cache };
+                let cached =
+                    TargetCompleter::new(&ctx.working_dir, &paths.roots, &mut cached_resolver)
+                        .await?
+                        .complete(given_package, given_partial_target)
+                        .await;
+                match cached {
+                    CommandOutcome::Success(completions) => {
+                        print_completions(CommandOutcome::Success(completions))
+                    }
+                    CommandOutcome::Failure(_) => {
+                        let completer = CompleteTargetCommand::new(
+                            &ctx.working_dir,
+                            given_package.to_owned(),
+                            given_partial_target.to_owned(),
+                            cache,
+                            print_completions,
+                        );
+                        completer.exec_async(matches, ctx).await
+                    }
+                }
             }
             _ => ExitResult::bail(
                 "Malformed target string (expected [[cell]//][path/to/package][:target_name])",
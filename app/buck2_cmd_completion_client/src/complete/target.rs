@@ -32,12 +32,15 @@ use futures::future::BoxFuture;
 use futures::FutureExt;
 
 use super::path_sanitizer::PathSanitizer;
+use super::path_sanitizer::SanitizedPath;
 use super::results::CompletionResults;
+use super::target_cache::TargetCache;
 
 type CompleteCallback = fn(CommandOutcome<Vec<String>>) -> ExitResult;
 
 pub(crate) trait TargetResolver: Send {
-    fn resolve(&mut self, partial_target: String) -> BoxFuture<CommandOutcome<Vec<String>>>;
+    /// All targets in the package.
+    fn resolve(&mut self, package: &SanitizedPath) -> BoxFuture<CommandOutcome<Vec<String>>>;
 }
 
 pub(crate) struct CompleteTargetCommand {
@@ -46,6 +49,7 @@ pub(crate) struct CompleteTargetCommand {
     cwd: AbsWorkingDir,
     package: String,
     partial_target: String,
+    cache: TargetCache,
 
     callback: CompleteCallback,
 }
@@ -55,6 +59,7 @@ impl CompleteTargetCommand {
         cwd: &AbsWorkingDir,
         package: String,
         partial_target: String,
+        cache: TargetCache,
         callback: CompleteCallback,
     ) -> Self {
         let target_cfg = TargetCfgOptions::default();
@@ -63,6 +68,7 @@ impl CompleteTargetCommand {
             cwd: cwd.to_owned(),
             package,
             partial_target,
+            cache,
             callback,
         }
     }
@@ -84,6 +90,7 @@ impl StreamingCommand for CompleteTargetCommand {
             buckd_client,
             context,
             target_cfg: self.target_cfg,
+            cache: self.cache,
         };
 
         let completer = TargetCompleter::new(&self.cwd, &ctx.paths()?.roots, &mut target_resolver)
@@ -149,10 +156,7 @@ impl<'a> TargetCompleter<'a> {
     ) -> CommandOutcome<Vec<String>> {
         let sanitizer = PathSanitizer::new(&self.cell_configs, &self.cwd).await?;
         let path = sanitizer.sanitize(given_package)?;
-        let completions = self
-            .target_resolver
-            .resolve(path.given().to_owned() + ":")
-            .await?;
+        let completions = self.target_resolver.resolve(&path).await?;
 
         for label in completions {
             let target = label.split(':').next_back().unwrap();
@@ -169,19 +173,23 @@ struct DaemonTargetResolver<'a, 'b> {
     buckd_client: FlushingBuckdClient<'a, 'b>,
     context: ClientContext,
     target_cfg: TargetCfgOptions,
+    cache: TargetCache,
 }
 
 impl<'a, 'b> TargetResolver for DaemonTargetResolver<'a, 'b> {
-    fn resolve(&mut self, partial_target: String) -> BoxFuture<CommandOutcome<Vec<String>>> {
+    fn resolve(&mut self, package: &SanitizedPath) -> BoxFuture<CommandOutcome<Vec<String>>> {
         let request = NewGenericRequest::Complete(CompleteRequest {
             target_cfg: self.target_cfg.target_cfg(),
-            partial_target,
+            partial_target: package.given().to_owned() + ":",
         });
+        let cache = self.cache.clone();
+        let package_dir = package.abs_path().to_owned();
         self.buckd_client
             .new_generic(self.context.clone(), request, None)
             .then(|res| async move {
                 match res {
                     Ok(CommandOutcome::Success(NewGenericResponse::Complete(res))) => {
+                        cache.put(&package_dir, &res.completions);
                         CommandOutcome::Success(res.completions)
                     }
                     Ok(CommandOutcome::Success(_)) => CommandOutcome::Failure(ExitResult::bail(
@@ -194,3 +202,19 @@ impl<'a, 'b> TargetResolver for DaemonTargetResolver<'a, 'b> {
             .boxed()
     }
 }
+
+/// Resolves targets from the cache only, so that completion does not need the daemon when the
+/// package was completed recently.
+pub(crate) struct CachedTargetResolver<'c> {
+    pub(crate) cache: &'c TargetCache,
+}
+
+impl<'c> TargetResolver for CachedTargetResolver<'c> {
+    fn resolve(&mut self, package: &SanitizedPath) -> BoxFuture<CommandOutcome<Vec<String>>> {
+        let outcome = match self.cache.get(package.abs_path()) {
+            Some(targets) => CommandOutcome::Success(targets),
+            None => CommandOutcome::Failure(ExitResult::bail("Targets are not cached")),
+        };
+        futures::future::ready(outcome).boxed()
+    }
+}
@@ -0,0 +1,120 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::fs;
+use std::hash::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::file_name::FileNameBuf;
+
+/// Targets can also change when `.bzl` files they load change, which the cache does not track.
+/// Bound how long such stale completions can be served.
+const MAX_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// On-disk cache of the targets in a package, so that repeatedly completing targets in the same
+/// package does not need to go through the daemon every time.
+///
+/// Entries are keyed by the package directory and are invalidated when any file directly in
+/// that directory (such as the build file) is modified after the entry was written.
+#[derive(Clone)]
+pub(crate) struct TargetCache {
+    dir: AbsNormPathBuf,
+}
+
+impl TargetCache {
+    pub(crate) fn new(dir: AbsNormPathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn entry_path(&self, package_dir: &AbsNormPath) -> buck2_error::Result<AbsNormPathBuf> {
+        let mut hasher = DefaultHasher::new();
+        package_dir.hash(&mut hasher);
+        let name = FileNameBuf::try_from(format!("{:016x}", hasher.finish()))?;
+        Ok(self.dir.join(name))
+    }
+
+    /// Targets of the package in `package_dir`, if cached and still fresh.
+    pub(crate) fn get(&self, package_dir: &AbsNormPath) -> Option<Vec<String>> {
+        let entry = self.entry_path(package_dir).ok()?;
+        let written = fs::metadata(&entry).ok()?.modified().ok()?;
+        if written.elapsed().map_or(true, |age| age > MAX_AGE)
+            || modified_since(package_dir, written)
+        {
+            return None;
+        }
+        let contents = fs_util::read_to_string_if_exists(&entry).ok()??;
+        let mut lines = contents.lines();
+        // Guard against hash collisions.
+        if lines.next()? != package_dir.to_string() {
+            return None;
+        }
+        Some(lines.map(str::to_owned).collect())
+    }
+
+    /// Best effort: completion should not fail because the cache cannot be written.
+    pub(crate) fn put(&self, package_dir: &AbsNormPath, targets: &[String]) {
+        let Ok(entry) = self.entry_path(package_dir) else {
+            return;
+        };
+        let mut contents = package_dir.to_string();
+        for target in targets {
+            contents.push('\n');
+            contents.push_str(target);
+        }
+        if fs_util::create_dir_all(&self.dir).is_ok() {
+            drop(fs_util::write(entry, contents));
+        }
+    }
+}
+
+/// Whether any file directly in `dir` was modified after `time`. Errs towards `true`.
+fn modified_since(dir: &AbsNormPath, time: SystemTime) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return true;
+    };
+    entries.into_iter().any(|entry| {
+        entry
+            .and_then(|entry| entry.metadata())
+            .and_then(|metadata| metadata.modified())
+            .map_or(true, |modified| modified > time)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::temp_path::TempPath;
+
+    use super::*;
+
+    #[test]
+    fn test_target_cache() {
+        let tempdir = TempPath::new().unwrap();
+        let root = tempdir.path();
+        let package = root.join(FileNameBuf::unchecked_new("pkg"));
+        fs_util::create_dir_all(&package).unwrap();
+        fs_util::write(package.join(FileNameBuf::unchecked_new("BUCK")), "").unwrap();
+
+        let cache = TargetCache::new(root.join(FileNameBuf::unchecked_new("cache")));
+        assert_eq!(cache.get(&package), None);
+
+        let targets = vec!["pkg:a".to_owned(), "pkg:b".to_owned()];
+        cache.put(&package, &targets);
+        assert_eq!(cache.get(&package), Some(targets));
+
+        let other = root.join(FileNameBuf::unchecked_new("other"));
+        fs_util::create_dir_all(&other).unwrap();
+        assert_eq!(cache.get(&other), None);
+    }
+}
@@ -12,12 +12,51 @@ use buck2_client_ctx::path_arg::PathArg;
 use crate::StarlarkClientSubcommand;
 use crate::StarlarkCommandCommonOptions;
 
+/// How to typecheck modules which load each other.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    clap::ValueEnum,
+    serde::Serialize,
+    serde::Deserialize
+)]
+#[clap(rename_all = "kebab-case")]
+pub enum LoadCycles {
+    /// Fail on load cycles.
+    Error,
+    /// Assume the symbols loaded from a module of the cycle have type `Any`.
+    Any,
+    /// Typecheck the modules of the cycle again until their interfaces do not change, starting
+    /// with symbols of type `Any`.
+    FixedPoint,
+}
+
 #[derive(Debug, clap::Parser, serde::Serialize, serde::Deserialize)]
 #[clap(name = "starlark-typecheck", about = "Run the Starlark typechecker.")]
 pub struct StarlarkTypecheckCommand {
     #[clap(flatten)]
     pub common_opts: StarlarkCommandCommonOptions,
 
+    /// How to typecheck modules which load each other, directly or not.
+    #[clap(long, value_enum, default_value = "fixed-point")]
+    pub load_cycles: LoadCycles,
+
+    /// Print the type errors as JSON on stdout, with the number of errors of every file
+    /// typechecked. Files with type errors do not stop the typecheck of other files, but the
+    /// command still fails if any file has type errors. The output also has edits inserting the
+    /// type annotations inferred for functions without them.
+    #[clap(long)]
+    pub json: bool,
+
+    /// Report the number of expressions typed as `Any` in every file typechecked, and in total,
+    /// to measure the progress of adding type annotations. With `--json`, the coverage is in the
+    /// `coverage` field of the output.
+    #[clap(long)]
+    pub coverage: bool,
+
     #[clap(value_name = "PATH", required = true)]
     pub paths: Vec<PathArg>,
 }
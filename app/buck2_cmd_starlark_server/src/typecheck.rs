@@ -8,11 +8,15 @@
  */
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::io::Write;
+use std::num::NonZeroUsize;
+use std::path::Path;
 
 use async_recursion::async_recursion;
 use async_trait::async_trait;
 use buck2_cli_proto::ClientContext;
+use buck2_cmd_starlark_client::typecheck::LoadCycles;
 use buck2_cmd_starlark_client::typecheck::StarlarkTypecheckCommand;
 use buck2_common::dice::cells::HasCellResolver;
 use buck2_common::dice::data::HasIoProvider;
@@ -31,14 +35,43 @@ use buck2_server_ctx::ctx::ServerCommandDiceContext;
 use buck2_server_ctx::partial_result_dispatcher::PartialResultDispatcher;
 use dice::DiceTransaction;
 use dupe::Dupe;
+use once_cell::sync::Lazy;
+use starlark::codemap::FileSpan;
 use starlark::environment::Globals;
+use starlark::errors::EvalMessage;
+use starlark::errors::EvalSeverity;
+use starlark::typing::AnnotationSuggestion;
 use starlark::typing::AstModuleTypecheck;
 use starlark::typing::Interface;
+use starlark::typing::InterfaceCache;
+use starlark::typing::InterfaceKey;
+use starlark::typing::LoadCycle;
+use starlark::typing::LoadStack;
+use starlark::typing::TypeCoverage;
 
 use crate::util::environment::Environment;
 use crate::util::paths::starlark_files;
 use crate::StarlarkServerSubcommand;
 
+/// Interfaces of modules typechecked without errors, kept across commands, so that unchanged
+/// dependencies are not typechecked again on a warm daemon. Bounded so that it does not grow
+/// forever as files are edited.
+static INTERFACE_CACHE: Lazy<InterfaceCache> =
+    Lazy::new(|| InterfaceCache::new(NonZeroUsize::new(INTERFACE_CACHE_CAPACITY).unwrap()));
+
+/// Interfaces kept in `INTERFACE_CACHE`, enough for the `.bzl` files of a large repository.
+const INTERFACE_CACHE_CAPACITY: usize = 20_000;
+
+/// Typechecks of a module starting a load cycle, when computing the fixed point of its interface.
+const MAX_CYCLE_ITERATIONS: usize = 10;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum TypecheckError {
+    #[error("{0}")]
+    LoadCycle(LoadCycle),
+}
+
 struct Cache<'a> {
     // Things we have access to get information
     dice: &'a DiceTransaction,
@@ -50,11 +83,23 @@ struct Cache<'a> {
     // Our accumulated state
     oracle: HashMap<(CellName, StarlarkFileType), Globals>,
     cache: HashMap<OwnedStarlarkModulePath, Interface>,
+    // Modules whose interface is being computed
+    stack: LoadStack,
+    load_cycles: LoadCycles,
+    // Modules whose approximated interface was used, because they start a load cycle
+    cycle_heads: HashSet<String>,
+    // Interfaces used for the modules starting a load cycle, from the previous typecheck
+    provisional: HashMap<String, Interface>,
+    // With `--json`, the type errors and suggested annotations of every file typechecked,
+    // instead of failing on the first
+    json: Option<Vec<(String, Vec<EvalMessage>, Vec<AnnotationSuggestion>)>>,
+    // With `--coverage`, the type coverage of every file typechecked
+    coverage: Option<Vec<(String, TypeCoverage)>>,
 }
 
 impl<'a> Cache<'a> {
     async fn typecheck(&mut self, path: OwnedStarlarkPath) -> buck2_error::Result<()> {
-        self.run(path).await?;
+        self.run(path, None).await?;
         Ok(())
     }
 
@@ -75,19 +120,91 @@ impl<'a> Cache<'a> {
         }
     }
 
-    async fn get(&mut self, path: OwnedStarlarkModulePath) -> buck2_error::Result<Interface> {
+    async fn get(
+        &mut self,
+        path: OwnedStarlarkModulePath,
+        load: FileSpan,
+    ) -> buck2_error::Result<Interface> {
         match self.cache.get(&path) {
             Some(x) => Ok(x.dupe()),
             None => {
-                let res = self.run(path.clone().into_starlark_path()).await?;
+                let res = self
+                    .run(path.clone().into_starlark_path(), Some(load))
+                    .await?;
                 self.cache.insert(path, res.dupe());
                 Ok(res)
             }
         }
     }
 
+    /// Typecheck a file, loaded by the `load()` at `load` if it is not one of the files to
+    /// typecheck.
     #[async_recursion]
-    async fn run(&mut self, path: OwnedStarlarkPath) -> buck2_error::Result<Interface> {
+    async fn run(
+        &mut self,
+        path: OwnedStarlarkPath,
+        load: Option<FileSpan>,
+    ) -> buck2_error::Result<Interface> {
+        let module = path.to_string();
+        if let Err(cycle) = self.stack.push(module.clone(), load) {
+            if self.load_cycles == LoadCycles::Error {
+                return Err(TypecheckError::LoadCycle(cycle).into());
+            }
+            writeln!(
+                self.stderr,
+                "Approximating interface of `{module}`: {cycle}"
+            )?;
+            let interface = match self.provisional.get(&module) {
+                Some(x) => x.dupe(),
+                // All the symbols are `Any`.
+                None => Interface::empty(),
+            };
+            self.cycle_heads.insert(module);
+            return Ok(interface);
+        }
+        let res = self.check_to_fixed_point(path, &module).await;
+        self.stack.pop();
+        res
+    }
+
+    /// Typecheck a file. If its interface was approximated because it starts a load cycle,
+    /// typecheck it again with the interface it had until the interface does not change.
+    async fn check_to_fixed_point(
+        &mut self,
+        path: OwnedStarlarkPath,
+        module: &str,
+    ) -> buck2_error::Result<Interface> {
+        let mut iteration = 0;
+        loop {
+            iteration += 1;
+            let cached: HashSet<OwnedStarlarkModulePath> = self.cache.keys().cloned().collect();
+            let interface = self.check(path.clone()).await?;
+            if !self.cycle_heads.remove(module) {
+                return Ok(interface);
+            }
+            let stable = self
+                .provisional
+                .get(module)
+                .is_some_and(|x| x.fingerprint() == interface.fingerprint());
+            if self.load_cycles == LoadCycles::Any || stable {
+                self.provisional.remove(module);
+                return Ok(interface);
+            }
+            if iteration == MAX_CYCLE_ITERATIONS {
+                writeln!(
+                    self.stderr,
+                    "Interface of `{module}` did not converge after {iteration} typechecks"
+                )?;
+                self.provisional.remove(module);
+                return Ok(interface);
+            }
+            // Interfaces computed since depend on the approximated interface.
+            self.cache.retain(|path, _| cached.contains(path));
+            self.provisional.insert(module.to_owned(), interface);
+        }
+    }
+
+    async fn check(&mut self, path: OwnedStarlarkPath) -> buck2_error::Result<Interface> {
         let path_ref = path.borrow();
         writeln!(self.stderr, "Type checking: {path_ref}")?;
         let proj_path = self
@@ -105,16 +222,24 @@ impl<'a> Cache<'a> {
             .get_interpreter_calculator(path_ref.cell(), path_ref.build_file_cell())
             .await?;
 
-        let ParseData(ast, _) = interp.prepare_eval_with_content(path_ref, src)??;
+        let ParseData(ast, _) = interp.prepare_eval_with_content(path_ref, src.clone())??;
         let mut loads = HashMap::new();
         for x in ast.loads() {
             let y = interp.resolve_load(path_ref, x.module_id).await?;
-            let interface = self.get(y).await?;
+            let interface = self.get(y, x.span.clone()).await?;
             loads.insert(x.module_id.to_owned(), interface);
         }
         let globals = self
             .get_oracle(path_ref.cell(), path_ref.file_type())
             .await?;
+        let key = InterfaceKey::new(&src, &globals, &loads);
+        // Cached modules would have no coverage.
+        if self.coverage.is_none() {
+            if let Some(interface) = INTERFACE_CACHE.get(&key) {
+                writeln!(self.stderr, "Unchanged since last typechecked: {path_ref}")?;
+                return Ok(interface);
+            }
+        }
         let (errors, bindings, interface, approxiomations) = ast.typecheck(&globals, &loads);
 
         if !approxiomations.is_empty() {
@@ -126,8 +251,51 @@ impl<'a> Cache<'a> {
 
         writeln!(self.stderr, "\n\nBINDINGS:\n{bindings}")?;
 
+        let warnings: Vec<EvalMessage> = bindings
+            .deprecated_uses()
+            .iter()
+            .chain(bindings.unused_private())
+            .cloned()
+            .map(EvalMessage::from)
+            .collect();
+        if !warnings.is_empty() && self.json.is_none() {
+            writeln!(self.stdout, "\n\nWARNINGS:")?;
+            for x in &warnings {
+                writeln!(self.stdout, "{x}")?;
+            }
+        }
+
+        if let Some(coverage) = &mut self.coverage {
+            coverage.retain(|(path, _)| path != &path_str);
+            coverage.push((path_str.clone(), bindings.coverage()));
+        }
+
         let errors_count = errors.len();
+        // Cached modules are not typechecked again, so would not report their warnings, or
+        // their suggested annotations with `--json`.
+        let cacheable = warnings.is_empty()
+            && (self.json.is_none() || bindings.annotation_suggestions().is_empty());
+        if let Some(json) = &mut self.json {
+            let messages = errors
+                .iter()
+                .map(|e| EvalMessage::from_error(Path::new(&path_str), e))
+                .chain(warnings)
+                .collect();
+            // Modules starting a load cycle may be typechecked more than once.
+            json.retain(|(path, _, _)| path != &path_str);
+            json.push((
+                path_str,
+                messages,
+                bindings.annotation_suggestions().to_vec(),
+            ));
+        }
         if errors_count == 0 {
+            if cacheable {
+                INTERFACE_CACHE.insert(key, interface.dupe());
+            }
+            Ok(interface)
+        } else if self.json.is_some() {
+            // Dependents are still typechecked.
             Ok(interface)
         } else {
             writeln!(self.stdout, "\n\nERRORS:")?;
@@ -165,14 +333,279 @@ impl StarlarkServerSubcommand for StarlarkTypecheckCommand {
                     stderr: &mut stderr,
                     oracle: HashMap::new(),
                     cache: HashMap::new(),
+                    stack: LoadStack::new(),
+                    load_cycles: self.load_cycles,
+                    cycle_heads: HashSet::new(),
+                    provisional: HashMap::new(),
+                    json: self.json.then(Vec::new),
+                    coverage: self.coverage.then(Vec::new),
                 };
                 for file in files {
                     cache.typecheck(file).await?;
                 }
                 let file_count = cache.cache.len();
+                let coverage = cache.coverage.take();
+                if let Some(json) = cache.json.take() {
+                    let errors_count: usize = json
+                        .iter()
+                        .map(|(_, messages, _)| count_errors(messages))
+                        .sum();
+                    let mut output = diagnostics_json(&json);
+                    if let (Some(output), Some(coverage)) = (output.as_object_mut(), &coverage) {
+                        output.insert("coverage".to_owned(), coverage_json(coverage));
+                    }
+                    writeln!(stdout, "{}", output)?;
+                    if errors_count > 0 {
+                        return Err(buck2_error!([], "Detected {errors_count} errors"));
+                    }
+                } else if let Some(coverage) = &coverage {
+                    write_coverage(&mut stdout, coverage)?;
+                }
                 writeln!(stderr, "Found no type errors in {file_count} files")?;
                 Ok(())
             })
             .await?)
     }
 }
+
+/// The output of `--json`:
+///
+/// ```json
+/// {
+///   "error_count": 1,
+///   "files": [{"path": "foo/defs.bzl", "error_count": 1}],
+///   "diagnostics": [{"path": "foo/defs.bzl", "line": 3, "column": 5, ...}],
+///   "edits": [{"path": "foo/defs.bzl", "line": 7, "column": 12, ..., "replacement": ": int"}]
+/// }
+/// ```
+///
+/// Lines and columns are 1-based. Warnings, like uses of deprecated symbols, are in the
+/// diagnostics but not in the error counts. Edits insert the type annotations inferred for
+/// functions without them, replacing the text between the position and the end position.
+fn diagnostics_json(
+    files: &[(String, Vec<EvalMessage>, Vec<AnnotationSuggestion>)],
+) -> serde_json::Value {
+    let diagnostics = files
+        .iter()
+        .flat_map(|(_, errors, _)| errors)
+        .map(|e| {
+            let span = e.span.map(|span| {
+                serde_json::json!({
+                    "line": span.begin.line + 1,
+                    "column": span.begin.column + 1,
+                    "end_line": span.end.line + 1,
+                    "end_column": span.end.column + 1,
+                })
+            });
+            let mut diagnostic = serde_json::json!({
+                "path": e.path,
+                "severity": e.severity,
+                "name": e.name,
+                "message": e.description,
+            });
+            if let (Some(diagnostic), Some(serde_json::Value::Object(span))) =
+                (diagnostic.as_object_mut(), span)
+            {
+                diagnostic.extend(span);
+            }
+            diagnostic
+        })
+        .collect::<Vec<_>>();
+    let edits = files
+        .iter()
+        .flat_map(|(path, _, edits)| edits.iter().map(move |edit| (path, edit)))
+        .map(|(path, edit)| {
+            let span = edit.span.resolve_span();
+            serde_json::json!({
+                "path": path,
+                "line": span.begin.line + 1,
+                "column": span.begin.column + 1,
+                "end_line": span.end.line + 1,
+                "end_column": span.end.column + 1,
+                "replacement": edit.replacement,
+            })
+        })
+        .collect::<Vec<_>>();
+    let error_count: usize = files
+        .iter()
+        .map(|(_, messages, _)| count_errors(messages))
+        .sum();
+    serde_json::json!({
+        "error_count": error_count,
+        "warning_count": diagnostics.len() - error_count,
+        "files": files
+            .iter()
+            .map(|(path, messages, _)| {
+                let error_count = count_errors(messages);
+                serde_json::json!({
+                    "path": path,
+                    "error_count": error_count,
+                    "warning_count": messages.len() - error_count,
+                })
+            })
+            .collect::<Vec<_>>(),
+        "diagnostics": diagnostics,
+        "edits": edits,
+    })
+}
+
+/// The `coverage` field of the output of `--json --coverage`:
+///
+/// ```json
+/// {
+///   "expressions": 10,
+///   "any": 2,
+///   "any_fraction": 0.2,
+///   "files": [{"path": "foo/defs.bzl", "expressions": 10, "any": 2, "any_fraction": 0.2}]
+/// }
+/// ```
+fn coverage_json(files: &[(String, TypeCoverage)]) -> serde_json::Value {
+    let mut total = TypeCoverage::default();
+    for (_, coverage) in files {
+        total += *coverage;
+    }
+    serde_json::json!({
+        "expressions": total.expressions,
+        "any": total.any,
+        "any_fraction": total.any_fraction(),
+        "files": files
+            .iter()
+            .map(|(path, coverage)| {
+                serde_json::json!({
+                    "path": path,
+                    "expressions": coverage.expressions,
+                    "any": coverage.any,
+                    "any_fraction": coverage.any_fraction(),
+                })
+            })
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn write_coverage(w: &mut impl Write, files: &[(String, TypeCoverage)]) -> buck2_error::Result<()> {
+    let mut total = TypeCoverage::default();
+    writeln!(w, "COVERAGE:")?;
+    for (path, coverage) in files {
+        total += *coverage;
+        writeln!(
+            w,
+            "{path}: {} of {} expressions typed as `Any` ({:.1}%)",
+            coverage.any,
+            coverage.expressions,
+            coverage.any_fraction() * 100.0
+        )?;
+    }
+    writeln!(
+        w,
+        "Total: {} of {} expressions typed as `Any` ({:.1}%)",
+        total.any,
+        total.expressions,
+        total.any_fraction() * 100.0
+    )?;
+    Ok(())
+}
+
+fn count_errors(messages: &[EvalMessage]) -> usize {
+    messages
+        .iter()
+        .filter(|m| matches!(m.severity, EvalSeverity::Error))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use starlark::codemap::CodeMap;
+    use starlark::codemap::Pos;
+    use starlark::codemap::Span;
+    use starlark::errors::EvalMessage;
+    use starlark::errors::EvalSeverity;
+    use starlark::typing::AnnotationSuggestion;
+    use starlark::typing::TypeCoverage;
+
+    use crate::typecheck::coverage_json;
+    use crate::typecheck::diagnostics_json;
+
+    #[test]
+    fn test_diagnostics_json() {
+        let error = EvalMessage::from_any_error(Path::new("foo/a.bzl"), &"Bad type");
+        let mut warning = EvalMessage::from_any_error(Path::new("foo/b.bzl"), &"Deprecated");
+        warning.severity = EvalSeverity::Warning;
+        warning.name = "deprecated".to_owned();
+        let file = CodeMap::new(
+            "foo/b.bzl".to_owned(),
+            "def f(n = 1):\n    pass\n".to_owned(),
+        );
+        let suggestion = AnnotationSuggestion {
+            span: file.file_span(Span::new(Pos::new(7), Pos::new(7))),
+            replacement: ": int".to_owned(),
+        };
+        let json = diagnostics_json(&[
+            ("foo/a.bzl".to_owned(), vec![error], Vec::new()),
+            ("foo/b.bzl".to_owned(), vec![warning], vec![suggestion]),
+        ]);
+        assert_eq!(
+            serde_json::json!({
+                "error_count": 1,
+                "warning_count": 1,
+                "files": [
+                    {"path": "foo/a.bzl", "error_count": 1, "warning_count": 0},
+                    {"path": "foo/b.bzl", "error_count": 0, "warning_count": 1},
+                ],
+                "diagnostics": [{
+                    "path": "foo/a.bzl",
+                    "severity": "error",
+                    "name": "error",
+                    "message": "Bad type",
+                }, {
+                    "path": "foo/b.bzl",
+                    "severity": "warning",
+                    "name": "deprecated",
+                    "message": "Deprecated",
+                }],
+                "edits": [{
+                    "path": "foo/b.bzl",
+                    "line": 1,
+                    "column": 8,
+                    "end_line": 1,
+                    "end_column": 8,
+                    "replacement": ": int",
+                }],
+            }),
+            json
+        );
+    }
+
+    #[test]
+    fn test_coverage_json() {
+        let json = coverage_json(&[
+            (
+                "foo/a.bzl".to_owned(),
+                TypeCoverage {
+                    expressions: 3,
+                    any: 3,
+                },
+            ),
+            (
+                "foo/b.bzl".to_owned(),
+                TypeCoverage {
+                    expressions: 1,
+                    any: 0,
+                },
+            ),
+        ]);
+        assert_eq!(
+            serde_json::json!({
+                "expressions": 4,
+                "any": 3,
+                "any_fraction": 0.75,
+                "files": [
+                    {"path": "foo/a.bzl", "expressions": 3, "any": 3, "any_fraction": 1.0},
+                    {"path": "foo/b.bzl", "expressions": 1, "any": 0, "any_fraction": 0.0},
+                ],
+            }),
+            json
+        );
+    }
+}
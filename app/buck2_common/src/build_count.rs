@@ -16,6 +16,7 @@ use buck2_core::fs::paths::file_name::FileName;
 use buck2_data::ParsedTargetPatterns;
 use buck2_error::BuckErrorContext;
 use fs4::FileExt;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde::Serialize;
 
@@ -25,6 +26,9 @@ use crate::client_utils;
 // Update if changing BuildCount to allow building with deployed and compiled buck on the same rev.
 pub const BUILD_COUNT_VERSION: u64 = 1;
 
+// Version for serialized CriticalPathHistoryMap on disk.
+pub const CRITICAL_PATH_HISTORY_VERSION: u64 = 2;
+
 #[derive(
     Default,
     Clone,
@@ -51,7 +55,7 @@ impl BuildCount {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Default, Serialize, Deserialize)]
 pub struct BuildCountMap(HashMap<String, BuildCount>);
 
 impl BuildCountMap {
@@ -87,6 +91,98 @@ impl BuildCountMap {
     }
 }
 
+/// Time each target contributed to the critical path of a build.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct CriticalPathContributions {
+    /// In the order the targets first appear on the critical path.
+    pub targets: Vec<(String, Duration)>,
+    /// Time spent on critical path entries not owned by a target (e.g. loading packages).
+    pub other: Duration,
+}
+
+impl CriticalPathContributions {
+    pub fn add(&mut self, target: Option<String>, duration: Duration) {
+        match target {
+            Some(target) => match self.targets.iter_mut().find(|(t, _)| *t == target) {
+                Some((_, d)) => *d += duration,
+                None => self.targets.push((target, duration)),
+            },
+            None => self.other += duration,
+        }
+    }
+}
+
+fn duration_to_millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+/// Critical path of the last successful build of a target pattern.
+#[derive(Default, Serialize, Deserialize)]
+struct PatternCriticalPath {
+    /// Targets on the critical path, see `CriticalPathHistoryMap::targets` for their durations.
+    targets: Vec<String>,
+    /// Milliseconds spent on entries not owned by a target.
+    other_ms: u64,
+}
+
+/// Critical path history, keyed by target: the time (in milliseconds) each target contributed to
+/// the critical path of the last successful build it was on. Target patterns only point to the
+/// targets that were on their critical path, so that a later build refreshes the estimate of every
+/// pattern sharing targets with it.
+#[derive(Default, Serialize, Deserialize)]
+pub struct CriticalPathHistoryMap {
+    targets: HashMap<String, u64>,
+    patterns: HashMap<String, PatternCriticalPath>,
+}
+
+impl CriticalPathHistoryMap {
+    pub fn record(
+        &mut self,
+        patterns: &ParsedTargetPatterns,
+        contributions: &CriticalPathContributions,
+    ) {
+        for (target, duration) in &contributions.targets {
+            self.targets
+                .insert(target.clone(), duration_to_millis(*duration));
+        }
+        for pattern in patterns.target_patterns.iter() {
+            self.patterns.insert(
+                pattern.value.clone(),
+                PatternCriticalPath {
+                    targets: contributions
+                        .targets
+                        .iter()
+                        .map(|(target, _)| target.clone())
+                        .collect(),
+                    other_ms: duration_to_millis(contributions.other),
+                },
+            );
+        }
+        // Only keep targets some pattern still points to.
+        let patterns = &self.patterns;
+        self.targets
+            .retain(|target, _| patterns.values().any(|p| p.targets.contains(target)));
+    }
+
+    /// The critical path of a pattern is estimated as the sum of the latest contributions of the
+    /// targets on it. The critical path of a build is at least as long as that of any of its
+    /// patterns built alone, so use the longest one. Returns `None` unless every pattern has
+    /// history.
+    pub fn estimate(&self, patterns: &ParsedTargetPatterns) -> Option<Duration> {
+        let mut estimate = None;
+        for pattern in patterns.target_patterns.iter() {
+            let critical_path = self.patterns.get(&pattern.value)?;
+            let millis = critical_path
+                .targets
+                .iter()
+                .filter_map(|target| self.targets.get(target))
+                .fold(critical_path.other_ms, |acc, ms| acc.saturating_add(*ms));
+            estimate = Some(std::cmp::max(estimate.unwrap_or(0), millis));
+        }
+        estimate.map(Duration::from_millis)
+    }
+}
+
 /// BuildCountManager keeps track of how many times each target has been successfully built since rebase.
 /// This helps understand how much the performance differs between first and incremental builds.
 pub struct BuildCountManager {
@@ -106,20 +202,27 @@ impl BuildCountManager {
     }
 
     async fn read(&self, file_name: &FileName) -> buck2_error::Result<BuildCountMap> {
+        self.read_json(file_name).await
+    }
+
+    async fn read_json<T: DeserializeOwned + Default>(
+        &self,
+        file_name: &FileName,
+    ) -> buck2_error::Result<T> {
         let path = self.base_dir.join(file_name);
         match async_fs_util::read_to_string_if_exists(&path).await? {
             Some(buffer) => Ok(serde_json::from_str(&buffer)
                 .with_buck_error_context(|| format!("Parsing JSON from {}", path.display()))?),
             None => {
                 // it is normal after rebase, clean, etc.
-                Ok(BuildCountMap(HashMap::new()))
+                Ok(T::default())
             }
         }
     }
 
-    async fn write(
+    async fn write<T: Serialize>(
         &self,
-        build_count: &BuildCountMap,
+        build_count: &T,
         file_name: &FileName,
     ) -> buck2_error::Result<()> {
         self.ensure_dir().await?;
@@ -184,6 +287,37 @@ impl BuildCountManager {
         }
         Ok(build_count_map.min_count(target_patterns))
     }
+
+    fn critical_path_file_name() -> String {
+        format!("critical_path-{}", CRITICAL_PATH_HISTORY_VERSION)
+    }
+
+    /// Records the critical path of a successful build of the set of targets.
+    /// Unlike build counts, this history is kept across rebases.
+    pub async fn record_critical_path(
+        &self,
+        target_patterns: &ParsedTargetPatterns,
+        critical_path: &CriticalPathContributions,
+    ) -> buck2_error::Result<()> {
+        let file_name_str = Self::critical_path_file_name();
+        let file_name = FileName::new(&file_name_str)?;
+        let _guard = self.lock_with_timeout(Self::LOCK_TIMEOUT).await?;
+        let mut history: CriticalPathHistoryMap = self.read_json(file_name).await?;
+        history.record(target_patterns, critical_path);
+        self.write(&history, file_name).await
+    }
+
+    /// Returns the expected critical path for the set of targets, if all of them were built before.
+    pub async fn estimated_critical_path(
+        &self,
+        target_patterns: &ParsedTargetPatterns,
+    ) -> buck2_error::Result<Option<Duration>> {
+        let file_name_str = Self::critical_path_file_name();
+        let file_name = FileName::new(&file_name_str)?;
+        let _guard = self.lock_with_timeout(Self::LOCK_TIMEOUT).await?;
+        let history: CriticalPathHistoryMap = self.read_json(file_name).await?;
+        Ok(history.estimate(target_patterns))
+    }
 }
 
 #[must_use]
@@ -210,6 +344,7 @@ mod tests {
             target_patterns: targets.into_map(|v| buck2_data::TargetPattern {
                 value: v.to_owned(),
             }),
+            ..Default::default()
         }
     }
 
@@ -278,6 +413,88 @@ mod tests {
         Ok(())
     }
 
+    fn make_contributions(
+        targets: Vec<(&'static str, u64)>,
+        other_secs: u64,
+    ) -> CriticalPathContributions {
+        let mut contributions = CriticalPathContributions::default();
+        for (target, secs) in targets {
+            contributions.add(Some(target.to_owned()), Duration::from_secs(secs));
+        }
+        contributions.add(None, Duration::from_secs(other_secs));
+        contributions
+    }
+
+    #[test]
+    fn test_critical_path_contributions() {
+        let mut contributions = CriticalPathContributions::default();
+        contributions.add(Some("//some:a".to_owned()), Duration::from_secs(1));
+        contributions.add(None, Duration::from_secs(2));
+        contributions.add(Some("//some:b".to_owned()), Duration::from_secs(3));
+        contributions.add(Some("//some:a".to_owned()), Duration::from_secs(4));
+        assert_eq!(
+            contributions,
+            CriticalPathContributions {
+                targets: vec![
+                    ("//some:a".to_owned(), Duration::from_secs(5)),
+                    ("//some:b".to_owned(), Duration::from_secs(3)),
+                ],
+                other: Duration::from_secs(2),
+            }
+        );
+    }
+
+    #[test]
+    fn test_critical_path_estimate() {
+        let mut history = CriticalPathHistoryMap::default();
+        history.record(
+            &make_patterns(vec!["//some:target1"]),
+            &make_contributions(vec![("//some:lib", 1), ("//some:target1", 1)], 1),
+        );
+        history.record(
+            &make_patterns(vec!["//some:target2"]),
+            &make_contributions(vec![("//some:target2", 5)], 0),
+        );
+        assert_eq!(
+            history.estimate(&make_patterns(vec!["//some:target1"])),
+            Some(Duration::from_secs(3))
+        );
+        assert_eq!(
+            history.estimate(&make_patterns(vec!["//some:target1", "//some:target2"])),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            history.estimate(&make_patterns(vec!["//some:target1", "//some:target3"])),
+            None
+        );
+        assert_eq!(history.estimate(&make_patterns(vec![])), None);
+
+        // A build of another pattern updates the contribution of the targets it shares.
+        history.record(
+            &make_patterns(vec!["//some:target3"]),
+            &make_contributions(vec![("//some:lib", 4)], 0),
+        );
+        assert_eq!(
+            history.estimate(&make_patterns(vec!["//some:target1"])),
+            Some(Duration::from_secs(6))
+        );
+        assert_eq!(
+            history.estimate(&make_patterns(vec!["//some:target3"])),
+            Some(Duration::from_secs(4))
+        );
+
+        // Targets no pattern points to are dropped.
+        history.record(
+            &make_patterns(vec!["//some:target2"]),
+            &make_contributions(vec![("//some:other", 2)], 0),
+        );
+        assert!(!history.targets.contains_key("//some:target2"));
+        assert_eq!(
+            history.estimate(&make_patterns(vec!["//some:target2"])),
+            Some(Duration::from_secs(2))
+        );
+    }
+
     #[tokio::test]
     async fn test_read_no_such_file() -> buck2_error::Result<()> {
         let no_such_dir = if cfg!(windows) {
@@ -171,7 +171,7 @@ pub enum DigestAlgorithm {
 }
 
 impl DigestAlgorithm {
-    fn family(self) -> DigestAlgorithmFamily {
+    pub fn family(self) -> DigestAlgorithmFamily {
         match self {
             Self::Sha1 => DigestAlgorithmFamily::Sha1,
             Self::Sha256 => DigestAlgorithmFamily::Sha256,
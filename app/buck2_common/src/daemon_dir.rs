@@ -37,4 +37,9 @@ impl DaemonDir {
     pub fn buckd_pid(&self) -> AbsNormPathBuf {
         self.path.join(FileName::new("buckd.pid").unwrap())
     }
+
+    /// Path to the socket of the thin client protocol.
+    pub fn thin_client_socket(&self) -> AbsNormPathBuf {
+        self.path.join(FileName::new("thin_client.sock").unwrap())
+    }
 }
@@ -0,0 +1,74 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Hermetic daemon environment. When `buck2_daemon_env.hermetic` is set, the daemon drops the
+//! environment of whatever shell happened to spawn it, so builds don't depend on it.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::sync::OnceLock;
+
+use crate::init::DaemonEnvConfig;
+
+/// Variables the client sets for the daemon, kept regardless of the config.
+const ALWAYS_KEPT: &[&str] = &["RUST_BACKTRACE", "RUST_LIB_BACKTRACE", "FORCE_WANT_RESTART"];
+
+static ORIGINAL_ENV: OnceLock<BTreeMap<OsString, OsString>> = OnceLock::new();
+
+fn is_kept(name: &str, config: &DaemonEnvConfig) -> bool {
+    // `BUCK*` variables configure buck2 itself.
+    name.starts_with("BUCK")
+        || ALWAYS_KEPT.contains(&name)
+        || config.passthrough.iter().any(|p| p == name)
+}
+
+/// Replaces the environment of this process as requested by `config`, recording the original.
+///
+/// Must be called before any threads are spawned, because modifying the environment is not
+/// thread-safe.
+pub fn make_daemon_env_hermetic(config: &DaemonEnvConfig) {
+    if !config.hermetic {
+        return;
+    }
+    let original: BTreeMap<OsString, OsString> = std::env::vars_os().collect();
+    for name in original.keys() {
+        if !name.to_str().map_or(false, |name| is_kept(name, config)) {
+            std::env::remove_var(name);
+        }
+    }
+    if let Some(path) = &config.path {
+        std::env::set_var("PATH", path);
+    }
+    // Only the first call records anything, later ones see the hermetic environment.
+    let _ = ORIGINAL_ENV.set(original);
+}
+
+/// The environment the daemon was started in, if it was replaced by a hermetic one.
+pub fn original_daemon_env() -> Option<&'static BTreeMap<OsString, OsString>> {
+    ORIGINAL_ENV.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_kept() {
+        let config = DaemonEnvConfig {
+            hermetic: true,
+            passthrough: vec!["HOME".to_owned()],
+            path: None,
+        };
+        assert!(is_kept("HOME", &config));
+        assert!(is_kept("BUCK2_RUNTIME_THREADS", &config));
+        assert!(is_kept("RUST_BACKTRACE", &config));
+        assert!(!is_kept("PATH", &config));
+        assert!(!is_kept("SSH_AUTH_SOCK", &config));
+    }
+}
@@ -9,6 +9,7 @@
 
 //! Common dice operations
 
+pub mod build_metadata;
 pub mod cells;
 pub mod cycles;
 pub mod data;
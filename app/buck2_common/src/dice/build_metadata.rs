@@ -0,0 +1,61 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Client metadata passed with `--build-metadata`, which is readable from Starlark.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use derive_more::Display;
+use dice::DiceComputations;
+use dice::DiceTransactionUpdater;
+use dice::InjectedKey;
+use dupe::Dupe;
+
+#[derive(Clone, Dupe, Display, Debug, Eq, Hash, PartialEq, Allocative)]
+#[display("{:?}", self)]
+struct BuildMetadataKey;
+
+impl InjectedKey for BuildMetadataKey {
+    type Value = Arc<BTreeMap<String, String>>;
+
+    fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+        x == y
+    }
+}
+
+#[async_trait]
+pub trait HasBuildMetadata {
+    /// Build-visible metadata of the current command. Computations reading this are invalidated
+    /// when a command passes different metadata.
+    async fn get_build_metadata(&mut self) -> buck2_error::Result<Arc<BTreeMap<String, String>>>;
+}
+
+pub trait SetBuildMetadata {
+    fn set_build_metadata(&mut self, metadata: BTreeMap<String, String>)
+    -> buck2_error::Result<()>;
+}
+
+#[async_trait]
+impl HasBuildMetadata for DiceComputations<'_> {
+    async fn get_build_metadata(&mut self) -> buck2_error::Result<Arc<BTreeMap<String, String>>> {
+        Ok(self.compute(&BuildMetadataKey).await?)
+    }
+}
+
+impl SetBuildMetadata for DiceTransactionUpdater {
+    fn set_build_metadata(
+        &mut self,
+        metadata: BTreeMap<String, String>,
+    ) -> buck2_error::Result<()> {
+        Ok(self.changed_to([(BuildMetadataKey, Arc::new(metadata))])?)
+    }
+}
@@ -313,6 +313,54 @@ impl ResourceControlConfig {
     }
 }
 
+/// The environment the daemon runs with, from the `[buck2_daemon_env]` section.
+#[derive(
+    Allocative,
+    Clone,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq
+)]
+pub struct DaemonEnvConfig {
+    /// Replace the environment the daemon was started in by `passthrough` and `path`.
+    pub hermetic: bool,
+    /// Variables kept from the environment the daemon was started in.
+    pub passthrough: Vec<String>,
+    /// `PATH` of the daemon. If unset, `PATH` is only kept if it is in `passthrough`.
+    pub path: Option<String>,
+}
+
+impl DaemonEnvConfig {
+    pub fn from_config(config: &LegacyBuckConfig) -> buck2_error::Result<Self> {
+        let hermetic = config
+            .parse(BuckconfigKeyRef {
+                section: "buck2_daemon_env",
+                property: "hermetic",
+            })?
+            .unwrap_or(false);
+        let passthrough = config
+            .parse_list(BuckconfigKeyRef {
+                section: "buck2_daemon_env",
+                property: "passthrough",
+            })?
+            .unwrap_or_default();
+        let path = config
+            .get(BuckconfigKeyRef {
+                section: "buck2_daemon_env",
+                property: "path",
+            })
+            .map(ToOwned::to_owned);
+        Ok(Self {
+            hermetic,
+            passthrough,
+            path,
+        })
+    }
+}
+
 /// Configurations that are used at startup by the daemon. Those are actually read by the client,
 /// and passed on to the daemon.
 ///
@@ -332,6 +380,7 @@ pub struct DaemonStartupConfig {
     pub materializations: Option<String>,
     pub http: HttpConfig,
     pub resource_control: ResourceControlConfig,
+    pub daemon_env: DaemonEnvConfig,
 }
 
 impl DaemonStartupConfig {
@@ -365,6 +414,7 @@ impl DaemonStartupConfig {
                 .map(ToOwned::to_owned),
             http: HttpConfig::from_config(config)?,
             resource_control: ResourceControlConfig::from_config(config)?,
+            daemon_env: DaemonEnvConfig::from_config(config)?,
         })
     }
 
@@ -376,6 +426,46 @@ impl DaemonStartupConfig {
         serde_json::from_str::<Self>(s).context("Error deserializing DaemonStartupConfig")
     }
 
+    /// Human-readable list of the settings that differ between `self` and `new`, used to
+    /// explain why the daemon is restarted.
+    pub fn describe_changes(&self, new: &Self) -> Vec<String> {
+        fn diff(
+            path: &str,
+            old: &serde_json::Value,
+            new: &serde_json::Value,
+            out: &mut Vec<String>,
+        ) {
+            match (old, new) {
+                (serde_json::Value::Object(old), serde_json::Value::Object(new)) => {
+                    let keys: std::collections::BTreeSet<_> =
+                        old.keys().chain(new.keys()).collect();
+                    for key in keys {
+                        let path = if path.is_empty() {
+                            key.clone()
+                        } else {
+                            format!("{}.{}", path, key)
+                        };
+                        diff(
+                            &path,
+                            old.get(key).unwrap_or(&serde_json::Value::Null),
+                            new.get(key).unwrap_or(&serde_json::Value::Null),
+                            out,
+                        );
+                    }
+                }
+                (old, new) if old != new => out.push(format!("{}: {} -> {}", path, old, new)),
+                _ => {}
+            }
+        }
+
+        let mut changes = Vec::new();
+        match (serde_json::to_value(self), serde_json::to_value(new)) {
+            (Ok(old), Ok(new)) => diff("", &old, &new, &mut changes),
+            _ => changes.push("unknown".to_owned()),
+        }
+        changes
+    }
+
     pub fn testing_empty() -> Self {
         Self {
             daemon_buster: None,
@@ -385,6 +475,29 @@ impl DaemonStartupConfig {
             materializations: None,
             http: HttpConfig::default(),
             resource_control: ResourceControlConfig::default(),
+            daemon_env: DaemonEnvConfig::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_changes() {
+        let old = DaemonStartupConfig::testing_empty();
+        assert!(old.describe_changes(&old).is_empty());
+
+        let mut new = DaemonStartupConfig::testing_empty();
+        new.materializations = Some("all".to_owned());
+        new.daemon_env.hermetic = true;
+        assert_eq!(
+            old.describe_changes(&new),
+            vec![
+                "daemon_env.hermetic: false -> true".to_owned(),
+                "materializations: null -> \"all\"".to_owned(),
+            ]
+        );
+    }
+}
@@ -101,11 +101,23 @@ impl InvocationPaths {
             .join(ForwardRelativePath::unchecked_new("re_logs"))
     }
 
+    /// Local files of invocation records, see `buck2.invocation_records`.
+    pub fn invocation_records_dir(&self) -> AbsNormPathBuf {
+        self.buck_out_path()
+            .join(ForwardRelativePath::unchecked_new("invocation_records"))
+    }
+
     pub fn build_count_dir(&self) -> AbsNormPathBuf {
         self.buck_out_path()
             .join(ForwardRelativePath::unchecked_new("build_count"))
     }
 
+    /// Soft error categories of recent commands, see `buck2.soft_error_budget`.
+    pub fn soft_error_budget_dir(&self) -> AbsNormPathBuf {
+        self.buck_out_path()
+            .join(ForwardRelativePath::unchecked_new("soft_error_budget"))
+    }
+
     pub fn dice_dump_dir(&self) -> AbsNormPathBuf {
         self.buck_out_path()
             .join(ForwardRelativePath::unchecked_new("dice_dump"))
@@ -104,19 +104,30 @@ fn get_roots(from: &AbsWorkingDir) -> buck2_error::Result<Option<InvocationRoots
 }
 
 pub fn find_invocation_roots(from: &AbsWorkingDir) -> buck2_error::Result<InvocationRoots> {
-    get_roots(from)?.ok_or_else(|| BuckCliError::NoBuckRoot(from.to_owned()).into())
+    get_roots(from)?.ok_or_else(|| no_buck_root_error(from))
 }
 
-pub fn get_invocation_paths_result(
+/// Like [`find_invocation_roots`], but returns `None` outside of a project.
+pub fn find_invocation_roots_opt(
     from: &AbsWorkingDir,
+) -> buck2_error::Result<Option<InvocationRoots>> {
+    get_roots(from)
+}
+
+pub fn no_buck_root_error(from: &AbsWorkingDir) -> buck2_error::Error {
+    BuckCliError::NoBuckRoot(from.to_owned()).into()
+}
+
+/// The invocation paths of roots found with [`find_invocation_roots_opt`].
+pub fn invocation_paths_result(
+    from: &AbsWorkingDir,
+    roots: buck2_error::Result<Option<InvocationRoots>>,
     isolation: FileNameBuf,
 ) -> InvocationPathsResult {
-    match get_roots(from) {
+    match roots {
         Ok(Some(roots)) => InvocationPathsResult::Paths(InvocationPaths { roots, isolation }),
-        Ok(None) => {
-            InvocationPathsResult::OutsideOfRepo(BuckCliError::NoBuckRoot(from.to_owned()).into())
-        }
-        Err(e) => InvocationPathsResult::OtherError(e.into()),
+        Ok(None) => InvocationPathsResult::OutsideOfRepo(no_buck_root_error(from)),
+        Err(e) => InvocationPathsResult::OtherError(e),
     }
 }
 
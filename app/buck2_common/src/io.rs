@@ -40,6 +40,61 @@ impl From<buck2_error::Error> for ReadDirError {
     }
 }
 
+/// A directory whose contents are about to be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefetchDir {
+    pub path: ProjectRelativePathBuf,
+    /// Whether subdirectories will be read too.
+    pub recursive: bool,
+    /// Globs matching the names of the files which will be read, e.g. build file names.
+    /// Directory listings are fetched regardless.
+    pub files: Vec<String>,
+}
+
+impl PrefetchDir {
+    /// Globs relative to the project root matching the files to fetch.
+    pub fn globs(&self) -> impl Iterator<Item = String> + '_ {
+        let prefix = match (self.path.is_empty(), self.recursive) {
+            (true, false) => String::new(),
+            (true, true) => "**/".to_owned(),
+            (false, false) => format!("{}/", self.path),
+            (false, true) => format!("{}/**/", self.path),
+        };
+        self.files.iter().map(move |f| format!("{}{}", prefix, f))
+    }
+
+    /// Whether prefetching `self` also fetches everything `other` would.
+    fn covers(&self, other: &PrefetchDir) -> bool {
+        let covers_path = if self.recursive {
+            other.path.starts_with(&self.path)
+        } else {
+            !other.recursive && other.path == self.path
+        };
+        covers_path && other.files.iter().all(|f| self.files.contains(f))
+    }
+}
+
+/// Directories already prefetched, so that each is only requested once.
+#[derive(Default, Allocative)]
+pub struct PrefetchedDirs {
+    #[allocative(skip)]
+    dirs: Vec<PrefetchDir>,
+}
+
+impl PrefetchedDirs {
+    /// Returns the dirs which weren't prefetched yet, and records them as prefetched.
+    pub fn insert(&mut self, dirs: Vec<PrefetchDir>) -> Vec<PrefetchDir> {
+        let mut new = Vec::new();
+        for dir in dirs {
+            if !self.dirs.iter().any(|d| d.covers(&dir)) {
+                self.dirs.push(dir.clone());
+                new.push(dir);
+            }
+        }
+        new
+    }
+}
+
 #[async_trait]
 pub trait IoProvider: Allocative + Send + Sync {
     async fn read_file_if_exists_impl(
@@ -61,6 +116,11 @@ pub trait IoProvider: Allocative + Send + Sync {
     /// have done until this point.
     async fn settle(&self) -> buck2_error::Result<()>;
 
+    /// Hint that the contents of these directories will be read soon. Providers backed by a
+    /// virtual file system start fetching them in the background and return without waiting
+    /// for the fetch to finish; others do nothing.
+    async fn prefetch(&self, dirs: Vec<PrefetchDir>) -> buck2_error::Result<()>;
+
     fn name(&self) -> &'static str;
 
     /// Returns the Eden version of the underlying system of the IoProvider, if available.
@@ -97,3 +157,49 @@ impl<'a> dyn IoProvider + 'a {
             .tag(ErrorTag::IoSource)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(path: &str, recursive: bool) -> PrefetchDir {
+        PrefetchDir {
+            path: ProjectRelativePathBuf::unchecked_new(path.to_owned()),
+            recursive,
+            files: vec!["BUCK".to_owned()],
+        }
+    }
+
+    #[test]
+    fn test_prefetch_dir_globs() {
+        let globs = |dir: PrefetchDir| dir.globs().collect::<Vec<_>>();
+        assert_eq!(globs(dir("foo/bar", false)), vec!["foo/bar/BUCK"]);
+        assert_eq!(globs(dir("foo/bar", true)), vec!["foo/bar/**/BUCK"]);
+        assert_eq!(globs(dir("", false)), vec!["BUCK"]);
+        assert_eq!(globs(dir("", true)), vec!["**/BUCK"]);
+    }
+
+    #[test]
+    fn test_prefetched_dirs() {
+        let mut prefetched = PrefetchedDirs::default();
+        assert_eq!(
+            prefetched.insert(vec![dir("foo", false), dir("foo", false), dir("bar", true)]),
+            vec![dir("foo", false), dir("bar", true)]
+        );
+        // Already fetched, or below a directory fetched recursively.
+        assert_eq!(
+            prefetched.insert(vec![dir("foo", false), dir("bar/baz", false)]),
+            Vec::new()
+        );
+        assert_eq!(
+            prefetched.insert(vec![dir("foo", true), dir("foo/qux", false)]),
+            vec![dir("foo", true)]
+        );
+        // Other files in the same directory still need fetching.
+        let bzl = PrefetchDir {
+            files: vec!["*.bzl".to_owned()],
+            ..dir("bar", true)
+        };
+        assert_eq!(prefetched.insert(vec![bzl.clone()]), vec![bzl]);
+    }
+}
@@ -38,6 +38,7 @@ use crate::file_ops::RawPathMetadata;
 use crate::file_ops::RawSymlink;
 use crate::file_ops::TrackedFileDigest;
 use crate::io::IoProvider;
+use crate::io::PrefetchDir;
 
 #[derive(Clone, Dupe, Allocative)]
 pub struct FsIoProvider {
@@ -174,6 +175,10 @@ impl IoProvider for FsIoProvider {
         Ok(())
     }
 
+    async fn prefetch(&self, _dirs: Vec<PrefetchDir>) -> buck2_error::Result<()> {
+        Ok(())
+    }
+
     fn name(&self) -> &'static str {
         "fs"
     }
@@ -7,17 +7,22 @@
  * of this source tree.
  */
 
+use std::time::Instant;
+
 use allocative::Allocative;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
 use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
+use buck2_events::dispatch::get_dispatcher_opt;
 use dashmap::DashSet;
 
 use crate::file_ops::RawDirEntry;
 use crate::file_ops::RawPathMetadata;
 use crate::file_ops::RawSymlink;
 use crate::io::IoProvider;
+use crate::io::PrefetchDir;
 
 #[derive(Allocative, Debug, Hash, PartialEq, Eq, Clone)]
 pub struct Symlink {
@@ -103,6 +108,24 @@ impl TracingIoProvider {
     pub fn trace(&self) -> &Trace {
         &self.trace
     }
+
+    /// Send the read to the command performing it, for `buck2 debug io-trace`.
+    fn record_read(
+        &self,
+        path: &ProjectRelativePath,
+        kind: buck2_data::IoTraceReadKind,
+        size: u64,
+        start: Instant,
+    ) {
+        if let Some(dispatcher) = get_dispatcher_opt() {
+            dispatcher.instant_event(buck2_data::IoTraceRead {
+                path: path.to_string(),
+                kind: kind as i32,
+                size,
+                duration_us: start.elapsed().as_micros() as u64,
+            });
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -117,7 +140,14 @@ impl IoProvider for TracingIoProvider {
         &self,
         path: ProjectRelativePathBuf,
     ) -> buck2_error::Result<Option<String>> {
+        let start = Instant::now();
         let res = self.io.read_file_if_exists_impl(path.clone()).await?;
+        self.record_read(
+            &path,
+            buck2_data::IoTraceReadKind::IoTraceReadFile,
+            res.as_ref().map_or(0, |s| s.len() as u64),
+            start,
+        );
         if res.is_some() {
             self.add_project_path(path);
         }
@@ -134,7 +164,14 @@ impl IoProvider for TracingIoProvider {
         &self,
         path: ProjectRelativePathBuf,
     ) -> buck2_error::Result<Vec<RawDirEntry>> {
+        let start = Instant::now();
         let entries = self.io.read_dir_impl(path.clone()).await?;
+        self.record_read(
+            &path,
+            buck2_data::IoTraceReadKind::IoTraceReadDir,
+            entries.len() as u64,
+            start,
+        );
         self.add_project_path(path.clone());
         for entry in entries.iter() {
             self.add_project_path(path.join(ForwardRelativePath::unchecked_new(&entry.file_name)));
@@ -147,10 +184,21 @@ impl IoProvider for TracingIoProvider {
         &self,
         path: ProjectRelativePathBuf,
     ) -> buck2_error::Result<Option<RawPathMetadata<ProjectRelativePathBuf>>> {
+        let start = Instant::now();
         let res = self
             .io
             .read_path_metadata_if_exists_impl(path.clone())
             .await?;
+        let size = match &res {
+            Some(RawPathMetadata::File(meta)) => meta.digest.size(),
+            _ => 0,
+        };
+        self.record_read(
+            &path,
+            buck2_data::IoTraceReadKind::IoTraceReadMetadata,
+            size,
+            start,
+        );
         match &res {
             Some(RawPathMetadata::File(_)) | Some(RawPathMetadata::Directory) => {
                 self.add_project_path(path);
@@ -171,6 +219,10 @@ impl IoProvider for TracingIoProvider {
         self.io.settle().await
     }
 
+    async fn prefetch(&self, dirs: Vec<PrefetchDir>) -> buck2_error::Result<()> {
+        self.io.prefetch(dirs).await
+    }
+
     fn name(&self) -> &'static str {
         self.io.name()
     }
@@ -28,6 +28,7 @@ pub mod chunk_reader;
 pub mod client_utils;
 pub mod convert;
 pub mod daemon_dir;
+pub mod daemon_env;
 pub mod dice;
 pub mod events;
 pub mod external_cells;
@@ -53,9 +54,12 @@ pub mod memory_tracker;
 pub mod package_boundary;
 pub mod package_listing;
 pub mod pattern;
+pub mod prelude_lock;
 pub mod scope;
+pub mod soft_error_budget;
 pub mod sqlite;
 pub mod starlark_profiler;
 pub mod systemd;
 pub mod target_aliases;
 pub mod temp_path;
+pub mod vcs;
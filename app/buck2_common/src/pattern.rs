@@ -9,4 +9,5 @@
 
 pub mod package_roots;
 pub mod parse_from_cli;
+pub mod prefetch;
 pub mod resolve;
@@ -0,0 +1,100 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use buck2_core::cells::name::CellName;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+use buck2_core::pattern::pattern::ParsedPattern;
+use buck2_core::pattern::pattern_type::TargetPatternExtra;
+use dupe::Dupe;
+use gazebo::prelude::*;
+
+use crate::buildfiles::parse_buildfile_name;
+use crate::io::PrefetchDir;
+use crate::legacy_configs::cells::BuckConfigBasedCells;
+use crate::target_aliases::BuckConfigTargetAliasResolver;
+
+/// Directories to prefetch for target patterns passed on the command line, before DICE is
+/// available: the build files of the packages the patterns match, and the `.bzl` files of the
+/// prelude, which every build file loads.
+///
+/// Patterns which don't parse are skipped, the command reports them when it parses them.
+pub async fn prefetch_dirs_for_cli_patterns(
+    cells: &BuckConfigBasedCells,
+    project_fs: &ProjectRoot,
+    cwd: &ProjectRelativePath,
+    patterns: &[String],
+) -> buck2_error::Result<Vec<PrefetchDir>> {
+    let cell_resolver = &cells.cell_resolver;
+    let cwd_cell_path = cell_resolver.get_cell_path(&cwd)?;
+    let cell_alias_resolver = cells
+        .get_cell_alias_resolver_for_cwd_fast(project_fs, cwd)
+        .await?;
+    let target_alias_resolver = BuckConfigTargetAliasResolver::new(cells.root_config.dupe());
+
+    let mut build_files: HashMap<CellName, Vec<String>> = HashMap::new();
+    let mut dirs = Vec::new();
+
+    for pattern in patterns {
+        let Ok(pattern) = ParsedPattern::<TargetPatternExtra>::parse_relaxed(
+            &target_alias_resolver,
+            cwd_cell_path.as_ref(),
+            pattern,
+            cell_resolver,
+            &cell_alias_resolver,
+        ) else {
+            continue;
+        };
+
+        let (path, recursive) = match &pattern {
+            ParsedPattern::Target(package, _, _) | ParsedPattern::Package(package) => {
+                (package.as_cell_path(), false)
+            }
+            ParsedPattern::Recursive(path) => (path.as_ref(), true),
+        };
+
+        // External cells aren't backed by the checkout.
+        if cell_resolver.get(path.cell())?.external().is_some() {
+            continue;
+        }
+
+        let files = match build_files.entry(path.cell()) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let config = cells.parse_single_cell(path.cell(), project_fs).await?;
+                e.insert(parse_buildfile_name(&config)?.into_map(|f| f.as_str().to_owned()))
+            }
+        };
+
+        dirs.push(PrefetchDir {
+            path: cell_resolver.resolve_path(path)?,
+            recursive,
+            files: files.clone(),
+        });
+    }
+
+    let prelude = cell_resolver
+        .root_cell_cell_alias_resolver()
+        .resolve("prelude")
+        .and_then(|cell| cell_resolver.get(cell));
+    if let Ok(prelude) = prelude {
+        if prelude.external().is_none() {
+            dirs.push(PrefetchDir {
+                path: prelude.path().as_project_relative_path().to_buf(),
+                recursive: true,
+                files: vec!["*.bzl".to_owned()],
+            });
+        }
+    }
+
+    Ok(dirs)
+}
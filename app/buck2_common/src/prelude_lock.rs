@@ -0,0 +1,142 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! The prelude lockfile pins the `prelude` external cell to a commit of the prelude git repo.
+//! It is a buckconfig file written by `buck2 prelude update` and included by the root
+//! `.buckconfig`:
+//!
+//! ```ini
+//! [external_cells]
+//!   prelude = git
+//!
+//! <file:prelude.lock>
+//! ```
+
+use std::fmt::Write;
+
+use crate::legacy_configs::configs::LegacyBuckConfig;
+use crate::legacy_configs::key::BuckconfigKeyRef;
+
+/// Name of the lockfile in the project root.
+pub const PRELUDE_LOCKFILE: &str = "prelude.lock";
+
+/// The prelude repo tracked by `buck2 prelude update` unless told otherwise.
+pub const DEFAULT_PRELUDE_GIT_ORIGIN: &str = "https://github.com/facebook/buck2-prelude.git";
+
+const SECTION: &str = "external_cell_prelude";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreludeLock {
+    pub git_origin: String,
+    pub commit_hash: String,
+    /// Revision of the buck2 binary that pinned this commit, if it was a release build.
+    pub buck2_revision: Option<String>,
+}
+
+impl PreludeLock {
+    /// The lock as included in `config`. `None` if the prelude is not pinned by a lockfile.
+    pub fn from_config(config: &LegacyBuckConfig) -> Option<Self> {
+        let get = |property| {
+            config
+                .get(BuckconfigKeyRef {
+                    section: SECTION,
+                    property,
+                })
+                .map(str::to_owned)
+        };
+        Some(Self {
+            git_origin: get("git_origin")?,
+            commit_hash: get("commit_hash")?,
+            buck2_revision: get("buck2_revision"),
+        })
+    }
+
+    /// Parses a lockfile written by `render`.
+    pub fn parse(contents: &str) -> Option<Self> {
+        let mut in_section = false;
+        let mut git_origin = None;
+        let mut commit_hash = None;
+        let mut buck2_revision = None;
+        for line in contents.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_section = line == format!("[{}]", SECTION);
+            } else if let (true, Some((key, value))) = (in_section, line.split_once('=')) {
+                let value = Some(value.trim().to_owned());
+                match key.trim() {
+                    "git_origin" => git_origin = value,
+                    "commit_hash" => commit_hash = value,
+                    "buck2_revision" => buck2_revision = value,
+                    _ => {}
+                }
+            }
+        }
+        Some(Self {
+            git_origin: git_origin?,
+            commit_hash: commit_hash?,
+            buck2_revision,
+        })
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "# Written by `buck2 prelude update`, do not edit.").unwrap();
+        writeln!(out, "[{}]", SECTION).unwrap();
+        writeln!(out, "  git_origin = {}", self.git_origin).unwrap();
+        writeln!(out, "  commit_hash = {}", self.commit_hash).unwrap();
+        if let Some(revision) = &self.buck2_revision {
+            writeln!(out, "  buck2_revision = {}", revision).unwrap();
+        }
+        out
+    }
+
+    /// A warning if the prelude was pinned by a different buck2 than the one at `revision`.
+    /// The prelude and the binary are released together, and mixing versions can fail in
+    /// confusing ways.
+    pub fn check_buck2_revision(&self, revision: Option<&str>) -> Option<String> {
+        let (Some(locked), Some(revision)) = (&self.buck2_revision, revision) else {
+            return None;
+        };
+        if locked == revision {
+            return None;
+        }
+        Some(format!(
+            "The prelude in `{}` was pinned by buck2 `{}`, but this is buck2 `{}`. \
+            Run `buck2 prelude update` to pin a prelude matching this buck2.",
+            PRELUDE_LOCKFILE, locked, revision
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_parse() {
+        let lock = PreludeLock {
+            git_origin: DEFAULT_PRELUDE_GIT_ORIGIN.to_owned(),
+            commit_hash: "0123456789abcdef0123456789abcdef01234567".to_owned(),
+            buck2_revision: Some("abc".to_owned()),
+        };
+        assert_eq!(PreludeLock::parse(&lock.render()), Some(lock.clone()));
+        assert_eq!(PreludeLock::parse("[cells]\n  git_origin = x\n"), None);
+    }
+
+    #[test]
+    fn test_check_buck2_revision() {
+        let lock = PreludeLock {
+            git_origin: DEFAULT_PRELUDE_GIT_ORIGIN.to_owned(),
+            commit_hash: "0123456789abcdef0123456789abcdef01234567".to_owned(),
+            buck2_revision: Some("abc".to_owned()),
+        };
+        assert_eq!(lock.check_buck2_revision(Some("abc")), None);
+        assert_eq!(lock.check_buck2_revision(None), None);
+        assert!(lock.check_buck2_revision(Some("def")).is_some());
+    }
+}
@@ -0,0 +1,254 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Rates of soft errors across commands, for `buck2.soft_error_budget`.
+//!
+//! The client records the categories of soft errors raised by each command in
+//! `buck-out/<isolation>/soft_error_budget`. The daemon reads them back when a command starts, and
+//! escalates the categories raised by more than their budgeted fraction of recent commands to hard
+//! errors if `buck2.soft_error_budget_escalate` is set.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::time::Duration;
+
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::file_name::FileName;
+use buck2_error::BuckErrorContext;
+use fs4::FileExt;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::client_utils;
+
+// Version for serialized SoftErrorHistory on disk.
+pub const SOFT_ERROR_HISTORY_VERSION: u64 = 1;
+
+/// Rates are not computed over fewer commands than this, so that the first commands after a clean
+/// don't exceed every budget.
+const MIN_COMMANDS: usize = 10;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum SoftErrorBudgetError {
+    #[error(
+        "Invalid soft error budget `{0}`, expected comma-separated `<category>=<rate>` with rates in [0, 1]"
+    )]
+    Invalid(String),
+}
+
+/// Parsed from `buck2.soft_error_budget`, e.g. `category_a=0.05, category_b=0.5`: the highest
+/// fraction of recent commands which may raise each category of soft error.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SoftErrorBudget(BTreeMap<String, f64>);
+
+impl FromStr for SoftErrorBudget {
+    type Err = buck2_error::Error;
+
+    fn from_str(val: &str) -> Result<Self, Self::Err> {
+        let mut budget = BTreeMap::new();
+        for item in val.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let parsed = item.split_once('=').and_then(|(category, rate)| {
+                Some((category.trim(), rate.trim().parse::<f64>().ok()?))
+            });
+            match parsed {
+                Some((category, rate)) if !category.is_empty() && (0.0..=1.0).contains(&rate) => {
+                    budget.insert(category.to_owned(), rate);
+                }
+                _ => return Err(SoftErrorBudgetError::Invalid(val.to_owned()).into()),
+            }
+        }
+        Ok(SoftErrorBudget(budget))
+    }
+}
+
+impl SoftErrorBudget {
+    pub fn from_proto(max_rates: &HashMap<String, f64>) -> Self {
+        SoftErrorBudget(
+            max_rates
+                .iter()
+                .map(|(category, rate)| (category.clone(), *rate))
+                .collect(),
+        )
+    }
+
+    pub fn to_proto(&self) -> HashMap<String, f64> {
+        self.0
+            .iter()
+            .map(|(category, rate)| (category.clone(), *rate))
+            .collect()
+    }
+}
+
+/// Categories of soft errors raised by recent commands, oldest first.
+#[derive(Default, Serialize, Deserialize)]
+pub struct SoftErrorHistory {
+    commands: VecDeque<Vec<String>>,
+}
+
+impl SoftErrorHistory {
+    pub fn record(&mut self, categories: impl IntoIterator<Item = String>, window: usize) {
+        let mut categories: Vec<String> = categories.into_iter().collect();
+        categories.sort();
+        categories.dedup();
+        self.commands.push_back(categories);
+        while self.commands.len() > window.max(1) {
+            self.commands.pop_front();
+        }
+    }
+
+    /// The fraction of recent commands which raised this category.
+    pub fn rate(&self, category: &str) -> Option<f64> {
+        if self.commands.len() < MIN_COMMANDS {
+            return None;
+        }
+        let count = self
+            .commands
+            .iter()
+            .filter(|categories| categories.iter().any(|c| c == category))
+            .count();
+        Some(count as f64 / self.commands.len() as f64)
+    }
+
+    /// Categories raised by more commands than their budget, sorted.
+    pub fn over_budget(&self, budget: &SoftErrorBudget) -> Vec<String> {
+        budget
+            .0
+            .iter()
+            .filter(|(category, max_rate)| {
+                self.rate(category).is_some_and(|rate| rate > **max_rate)
+            })
+            .map(|(category, _)| category.clone())
+            .collect()
+    }
+}
+
+/// Reads and updates the soft error history of an isolation dir.
+pub struct SoftErrorHistoryManager {
+    base_dir: AbsNormPathBuf,
+}
+
+impl SoftErrorHistoryManager {
+    const LOCK_FILE_NAME: &'static str = "soft_error_budget.lock";
+    const LOCK_TIMEOUT: Duration = Duration::from_millis(2000);
+
+    pub fn new(base_dir: AbsNormPathBuf) -> Self {
+        Self { base_dir }
+    }
+
+    fn history_path(&self, suffix: &str) -> buck2_error::Result<AbsNormPathBuf> {
+        let file_name = format!("history-{}{}", SOFT_ERROR_HISTORY_VERSION, suffix);
+        Ok(self.base_dir.join(FileName::new(&file_name)?))
+    }
+
+    /// Reads the history without locking it, which is fine because it is replaced atomically.
+    pub fn read(&self) -> buck2_error::Result<SoftErrorHistory> {
+        let path = self.history_path("")?;
+        match fs_util::read_to_string_if_exists(&path)? {
+            Some(buffer) => Ok(serde_json::from_str(&buffer)
+                .with_buck_error_context(|| format!("Parsing JSON from {}", path.display()))?),
+            None => Ok(SoftErrorHistory::default()),
+        }
+    }
+
+    /// Records the soft error categories of a command, keeping the last `window` commands, and
+    /// returns the updated history.
+    pub async fn record(
+        &self,
+        categories: impl IntoIterator<Item = String>,
+        window: usize,
+    ) -> buck2_error::Result<SoftErrorHistory> {
+        fs_util::create_dir_all(&self.base_dir)?;
+        let lock = std::fs::File::create(self.base_dir.join(FileName::new(Self::LOCK_FILE_NAME)?))?;
+        let lock_ref = &lock;
+        client_utils::retrying(
+            Duration::from_millis(5),
+            Duration::from_millis(100),
+            Self::LOCK_TIMEOUT,
+            || async { buck2_error::Ok(lock_ref.try_lock_exclusive()?) },
+        )
+        .await?;
+
+        let res = self.read().and_then(|mut history| {
+            history.record(categories, window);
+            let path = self.history_path("")?;
+            let tmp_path = self.history_path(".tmp")?;
+            fs_util::write(&tmp_path, serde_json::to_vec(&history)?)?;
+            fs_util::rename(&tmp_path, &path)?;
+            Ok(history)
+        });
+        lock.unlock()?;
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn budget(s: &str) -> SoftErrorBudget {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_budget() {
+        assert_eq!(
+            SoftErrorBudget(BTreeMap::from([
+                ("foo".to_owned(), 0.05),
+                ("bar_baz".to_owned(), 1.0)
+            ])),
+            budget("foo=0.05, bar_baz = 1")
+        );
+        assert_eq!(SoftErrorBudget::default(), budget(""));
+        assert!("foo".parse::<SoftErrorBudget>().is_err());
+        assert!("foo=2".parse::<SoftErrorBudget>().is_err());
+        assert!("=0.5".parse::<SoftErrorBudget>().is_err());
+    }
+
+    #[test]
+    fn test_over_budget() {
+        let mut history = SoftErrorHistory::default();
+        let budget = budget("foo=0.25,bar=0.5");
+        for i in 0..MIN_COMMANDS {
+            assert!(history.over_budget(&budget).is_empty());
+            let categories = if i % 3 == 0 {
+                vec!["foo".to_owned(), "foo".to_owned()]
+            } else {
+                vec!["bar".to_owned()]
+            };
+            history.record(categories, 20);
+        }
+        // 4 of 10 commands raised `foo`, 6 of 10 raised `bar`.
+        assert_eq!(Some(0.4), history.rate("foo"));
+        assert_eq!(vec!["bar", "foo"], history.over_budget(&budget));
+
+        // Old commands are forgotten past the window.
+        for _ in 0..20 {
+            history.record(Vec::new(), 20);
+        }
+        assert_eq!(Some(0.0), history.rate("foo"));
+        assert!(history.over_budget(&budget).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_record() -> buck2_error::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let manager = SoftErrorHistoryManager::new(temp_dir.path().to_path_buf().try_into()?);
+        assert_eq!(None, manager.read()?.rate("foo"));
+        for _ in 0..MIN_COMMANDS {
+            manager.record(["foo".to_owned()], 100).await?;
+        }
+        assert_eq!(Some(1.0), manager.read()?.rate("foo"));
+
+        Ok(())
+    }
+}
@@ -73,7 +73,7 @@ impl TargetAliasResolver for BuckConfigTargetAliasResolver {
 }
 
 impl BuckConfigTargetAliasResolver {
-    fn new(config: LegacyBuckConfig) -> Self {
+    pub fn new(config: LegacyBuckConfig) -> Self {
         Self { config }
     }
 
@@ -0,0 +1,567 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Access to the version control checkout a directory is in, for the places that report on it
+//! (revision collection, the file watcher's mergebase details, rage).
+//!
+//! The checkout is found by looking at the filesystem rather than by asking `hg` and `git`, so
+//! the checkout closest to the directory wins. That is what makes git worktrees and submodules
+//! work: their `.git` is a file pointing elsewhere, and commands must run in their root rather
+//! than in the repository that contains them.
+
+use std::collections::HashMap;
+use std::mem;
+use std::process::Output;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_path::AbsPath;
+use buck2_core::fs::paths::abs_path::AbsPathBuf;
+use buck2_error::internal_error;
+use buck2_error::BuckErrorContext;
+use buck2_util::process::async_background_command;
+use tokio::io::AsyncReadExt;
+use tokio::process::Child;
+
+#[derive(Debug, buck2_error::Error)]
+enum VcsError {
+    #[error("`{0}` failed with code {1}, stderr:\n{2}")]
+    Command(String, i32, String),
+    #[error("`{0}` printed invalid UTF-8")]
+    Utf8(String),
+    #[error("`.git` file `{0}` does not contain a `gitdir:` line")]
+    InvalidGitFile(String),
+    #[error("Unexpected output from `{0}`: `{1}`")]
+    UnexpectedOutput(String, String),
+    #[error("Revision `{0}` is not known")]
+    UnknownRevision(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VcsKind {
+    /// Mercurial or Sapling.
+    Hg,
+    Git,
+}
+
+impl VcsKind {
+    fn command(self) -> &'static str {
+        match self {
+            VcsKind::Hg => "hg",
+            VcsKind::Git => "git",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkout {
+    pub kind: VcsKind,
+    /// The root of the working copy. For git worktrees and submodules, that's the root of the
+    /// worktree or submodule rather than of the repository containing it.
+    pub root: AbsPathBuf,
+    /// A git worktree created with `git worktree add`.
+    pub is_worktree: bool,
+    /// A git submodule, or a nested repository without its own `.git` directory.
+    pub is_submodule: bool,
+    /// Only part of the repository is checked out.
+    pub is_sparse: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RevisionDetails {
+    /// Only set in hg repositories that record a global revision number.
+    pub global_rev: Option<u64>,
+    /// Commit time in seconds since the epoch.
+    pub timestamp: u64,
+}
+
+#[async_trait]
+pub trait Vcs: Send + Sync {
+    fn checkout(&self) -> &Checkout;
+
+    /// The full hash of the revision the working copy is based on.
+    async fn current_revision(&self) -> buck2_error::Result<String>;
+
+    /// Whether the working copy has uncommitted changes, including untracked files.
+    async fn has_local_changes(&self) -> buck2_error::Result<bool>;
+
+    async fn revision_details(&self, revision: &str) -> buck2_error::Result<RevisionDetails>;
+
+    /// Human readable state of the checkout for `buck2 rage`.
+    async fn rage_info(&self) -> buck2_error::Result<String>;
+}
+
+/// Finds the checkout containing `dir`, if any.
+pub fn find_checkout(dir: &AbsPath) -> buck2_error::Result<Option<Checkout>> {
+    for root in dir.ancestors() {
+        for hg_dir in [".hg", ".sl"] {
+            let hg_dir = root.join(hg_dir);
+            if fs_util::try_exists(&hg_dir)? {
+                return Ok(Some(Checkout {
+                    kind: VcsKind::Hg,
+                    root: root.to_owned(),
+                    is_worktree: false,
+                    is_submodule: false,
+                    is_sparse: fs_util::try_exists(hg_dir.join("sparse"))?,
+                }));
+            }
+        }
+
+        let dot_git = root.join(".git");
+        if !fs_util::try_exists(&dot_git)? {
+            continue;
+        }
+        let (git_dir, is_worktree, is_submodule) = if fs_util::metadata(&dot_git)?.is_dir() {
+            (dot_git, false, false)
+        } else {
+            let git_dir = read_git_file(&dot_git)?;
+            // Worktrees share the objects and config of the main repository, which their git
+            // directory points to. Submodules have their own.
+            let is_worktree = fs_util::try_exists(git_dir.join("commondir"))?;
+            (git_dir, is_worktree, !is_worktree)
+        };
+        return Ok(Some(Checkout {
+            kind: VcsKind::Git,
+            root: root.to_owned(),
+            is_worktree,
+            is_submodule,
+            is_sparse: is_git_sparse(&git_dir)?,
+        }));
+    }
+    Ok(None)
+}
+
+/// Reads the git directory out of a `.git` file, as used by worktrees and submodules.
+fn read_git_file(dot_git: &AbsPath) -> buck2_error::Result<AbsPathBuf> {
+    let contents = fs_util::read_to_string(dot_git)?;
+    let git_dir = contents
+        .lines()
+        .find_map(|line| line.strip_prefix("gitdir:"))
+        .ok_or_else(|| VcsError::InvalidGitFile(dot_git.as_path().display().to_string()))?
+        .trim();
+    // Relative paths are relative to the directory containing the `.git` file.
+    Ok(dot_git
+        .parent()
+        .internal_error("`.git` must have a parent")?
+        .join(git_dir))
+}
+
+fn is_git_sparse(git_dir: &AbsPath) -> buck2_error::Result<bool> {
+    // Worktrees keep per-worktree settings in their own directory and the rest in the main one.
+    let common_dir = match fs_util::read_to_string_if_exists(git_dir.join("commondir"))? {
+        Some(common_dir) => git_dir.join(common_dir.trim()),
+        None => git_dir.to_owned(),
+    };
+    for config in [git_dir.join("config.worktree"), common_dir.join("config")] {
+        if let Some(config) = fs_util::read_to_string_if_exists(&config)? {
+            if let Some(enabled) = git_config_sparse_checkout(&config) {
+                return Ok(enabled);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// The value of `core.sparseCheckout` in a git config file, if set.
+fn git_config_sparse_checkout(config: &str) -> Option<bool> {
+    let mut in_core = false;
+    let mut value = None;
+    for line in config.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_core = line.eq_ignore_ascii_case("[core]");
+        } else if in_core {
+            if let Some((key, v)) = line.split_once('=') {
+                if key.trim().eq_ignore_ascii_case("sparsecheckout") {
+                    value = Some(v.trim().eq_ignore_ascii_case("true"));
+                }
+            }
+        }
+    }
+    value
+}
+
+/// Finds the checkout containing `dir` and returns a `Vcs` running `hg` or `git` in its root.
+pub fn detect_vcs(dir: &AbsPath) -> buck2_error::Result<Option<Arc<dyn Vcs>>> {
+    Ok(find_checkout(dir)?.map(|checkout| Arc::new(CommandVcs { checkout }) as Arc<dyn Vcs>))
+}
+
+/// `Vcs` implemented by running `hg` or `git`.
+struct CommandVcs {
+    checkout: Checkout,
+}
+
+impl CommandVcs {
+    async fn run(&self, args: &[&str]) -> buck2_error::Result<String> {
+        let command = self.checkout.kind.command();
+        let display = format!("{} {}", command, args.join(" "));
+        let child = async_background_command(command)
+            .args(args)
+            .current_dir(self.checkout.root.as_path())
+            .env("HGPLAIN", "1")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let output = ProperlyReapedChild { child: Some(child) }.output().await?;
+        if !output.status.success() {
+            return Err(VcsError::Command(
+                display,
+                // On Unix, `code()` will return `None` if the process was terminated by a signal.
+                output.status.code().unwrap_or(1),
+                String::from_utf8_lossy(&output.stderr).into_owned(),
+            )
+            .into());
+        }
+        String::from_utf8(output.stdout).buck_error_context(VcsError::Utf8(display))
+    }
+}
+
+#[async_trait]
+impl Vcs for CommandVcs {
+    fn checkout(&self) -> &Checkout {
+        &self.checkout
+    }
+
+    async fn current_revision(&self) -> buck2_error::Result<String> {
+        let args: &[&str] = match self.checkout.kind {
+            VcsKind::Hg => &["whereami"],
+            VcsKind::Git => &["rev-parse", "HEAD"],
+        };
+        let revision = self.run(args).await?.trim().to_owned();
+        if revision.len() != 40 {
+            return Err(VcsError::UnexpectedOutput(
+                format!("{} {}", self.checkout.kind.command(), args.join(" ")),
+                revision,
+            )
+            .into());
+        }
+        Ok(revision)
+    }
+
+    async fn has_local_changes(&self) -> buck2_error::Result<bool> {
+        let args: &[&str] = match self.checkout.kind {
+            VcsKind::Hg => &["status"],
+            VcsKind::Git => &["status", "--porcelain"],
+        };
+        Ok(!self.run(args).await?.trim().is_empty())
+    }
+
+    async fn revision_details(&self, revision: &str) -> buck2_error::Result<RevisionDetails> {
+        match self.checkout.kind {
+            VcsKind::Hg => {
+                let args = [
+                    "log",
+                    "-r",
+                    revision,
+                    "-T",
+                    "{get(extras, \"global_rev\")}\n{date}",
+                ];
+                let output = self.run(&args).await?;
+                let unexpected =
+                    || VcsError::UnexpectedOutput(format!("hg {}", args.join(" ")), output.clone());
+                let (global_rev, timestamp) =
+                    output.trim().split_once('\n').ok_or_else(unexpected)?;
+                Ok(RevisionDetails {
+                    global_rev: global_rev.parse().ok(),
+                    // hg returns the fractional seconds
+                    timestamp: timestamp.parse::<f64>().map_err(|_| unexpected())? as u64,
+                })
+            }
+            VcsKind::Git => {
+                let args = ["show", "-s", "--format=%ct", revision];
+                let output = self.run(&args).await?;
+                Ok(RevisionDetails {
+                    global_rev: None,
+                    timestamp: output.trim().parse().map_err(|_| {
+                        VcsError::UnexpectedOutput(
+                            format!("git {}", args.join(" ")),
+                            output.clone(),
+                        )
+                    })?,
+                })
+            }
+        }
+    }
+
+    async fn rage_info(&self) -> buck2_error::Result<String> {
+        let layout = layout_description(&self.checkout);
+        match self.checkout.kind {
+            VcsKind::Hg => {
+                let snapshot = self.run(&["snapshot", "create"]).await?;
+                let revision = self.run(&["whereami"]).await?;
+                Ok(format!(
+                    "{}hg revision: {}hg snapshot update {}",
+                    layout, revision, snapshot
+                ))
+            }
+            VcsKind::Git => {
+                let (revision, status) = futures::future::try_join(
+                    self.run(&["log", "-1", "--format=%H"]),
+                    self.run(&["status", "-sb"]),
+                )
+                .await?;
+                Ok(format!(
+                    "{}Git base commit hash:\n{}\nGit status:\n{}",
+                    layout, revision, status
+                ))
+            }
+        }
+    }
+}
+
+fn layout_description(checkout: &Checkout) -> String {
+    let mut kinds = Vec::new();
+    if checkout.is_worktree {
+        kinds.push("worktree");
+    }
+    if checkout.is_submodule {
+        kinds.push("submodule");
+    }
+    if checkout.is_sparse {
+        kinds.push("sparse checkout");
+    }
+    let mut description = format!("Checkout root: {}\n", checkout.root.as_path().display());
+    if !kinds.is_empty() {
+        description.push_str(&format!("Checkout layout: {}\n", kinds.join(", ")));
+    }
+    description
+}
+
+/// A wrapper over a child process that will reap the child process on drop.
+/// On Unix platforms, a child process becomes a zombie until it is reaped by its parent.
+///
+/// Callers of `Vcs` may abort their task at any time, e.g. when the command finishes before the
+/// revision was collected.
+struct ProperlyReapedChild {
+    child: Option<Child>,
+}
+
+impl ProperlyReapedChild {
+    async fn output(mut self) -> buck2_error::Result<Output> {
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let mut child = mem::take(&mut self.child).internal_error("child field must be set")?;
+        let mut stdout_pipe = child
+            .stdout
+            .take()
+            .buck_error_context("stdout is not piped")?;
+        let mut stderr_pipe = child
+            .stderr
+            .take()
+            .buck_error_context("stderr is not piped")?;
+        let (stdout_error, stderr_error, status) = tokio::join!(
+            stdout_pipe.read_to_end(&mut stdout),
+            stderr_pipe.read_to_end(&mut stderr),
+            child.wait(),
+        );
+
+        let result = match stdout_error.is_ok() || stderr_error.is_ok() {
+            true => Ok(Output {
+                status: status?,
+                stdout,
+                stderr,
+            }),
+            false => Err(internal_error!("Failed to read stdout and stderr")),
+        };
+        reap_child(child);
+        result
+    }
+}
+
+impl Drop for ProperlyReapedChild {
+    fn drop(&mut self) {
+        if let Some(child) = mem::take(&mut self.child) {
+            reap_child(child);
+        }
+    }
+}
+
+fn reap_child(mut child: Child) {
+    tokio::spawn(async move {
+        if let Some(child_id) = child.id() {
+            // If a child process has already exited, the child.id() is None.
+            tracing::warn!("Killed child process: {:?}", child_id);
+        }
+        drop(child.kill().await);
+    });
+}
+
+/// `Vcs` returning canned answers, for tests.
+pub struct FakeVcs {
+    pub checkout: Checkout,
+    pub revision: String,
+    pub has_local_changes: bool,
+    pub revision_details: HashMap<String, RevisionDetails>,
+    /// How long every call takes, to test timeouts.
+    pub delay: Duration,
+}
+
+impl FakeVcs {
+    pub fn new(checkout: Checkout, revision: &str) -> FakeVcs {
+        FakeVcs {
+            checkout,
+            revision: revision.to_owned(),
+            has_local_changes: false,
+            revision_details: HashMap::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    async fn delay(&self) {
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+    }
+}
+
+#[async_trait]
+impl Vcs for FakeVcs {
+    fn checkout(&self) -> &Checkout {
+        &self.checkout
+    }
+
+    async fn current_revision(&self) -> buck2_error::Result<String> {
+        self.delay().await;
+        Ok(self.revision.clone())
+    }
+
+    async fn has_local_changes(&self) -> buck2_error::Result<bool> {
+        self.delay().await;
+        Ok(self.has_local_changes)
+    }
+
+    async fn revision_details(&self, revision: &str) -> buck2_error::Result<RevisionDetails> {
+        self.delay().await;
+        Ok(*self
+            .revision_details
+            .get(revision)
+            .ok_or_else(|| VcsError::UnknownRevision(revision.to_owned()))?)
+    }
+
+    async fn rage_info(&self) -> buck2_error::Result<String> {
+        self.delay().await;
+        Ok(format!(
+            "{}revision: {}\n",
+            layout_description(&self.checkout),
+            self.revision
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &AbsPath, contents: &str) {
+        fs_util::create_dir_all(path.parent().unwrap()).unwrap();
+        fs_util::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_git_config_sparse_checkout() {
+        assert_eq!(git_config_sparse_checkout("[core]\n\tbare = false\n"), None);
+        assert_eq!(
+            git_config_sparse_checkout("[core]\n\tsparseCheckout = true\n"),
+            Some(true)
+        );
+        assert_eq!(
+            git_config_sparse_checkout("[Core]\n sparsecheckout=false\n"),
+            Some(false)
+        );
+        assert_eq!(
+            git_config_sparse_checkout("[extensions]\n\tsparseCheckout = true\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_checkout() -> buck2_error::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let root = AbsPath::new(tempdir.path())?;
+
+        assert_eq!(find_checkout(root)?, None);
+
+        // A full git checkout with a sparse worktree and a submodule.
+        let repo = root.join("repo");
+        write(&repo.join(".git/config"), "[core]\n\tbare = false\n");
+        write(&repo.join(".git/worktrees/wt/commondir"), "../..\n");
+        write(
+            &repo.join(".git/worktrees/wt/config.worktree"),
+            "[core]\n\tsparseCheckout = true\n",
+        );
+        write(
+            &root.join("wt/.git"),
+            &format!(
+                "gitdir: {}\n",
+                repo.join(".git/worktrees/wt").as_path().display()
+            ),
+        );
+        write(&repo.join(".git/modules/sub/config"), "[core]\n");
+        write(&repo.join("sub/.git"), "gitdir: ../.git/modules/sub\n");
+        fs_util::create_dir_all(repo.join("sub/dir"))?;
+
+        let checkout = find_checkout(&repo)?.unwrap();
+        assert_eq!(checkout.kind, VcsKind::Git);
+        assert_eq!(checkout.root, repo);
+        assert!(!checkout.is_worktree && !checkout.is_submodule && !checkout.is_sparse);
+
+        let checkout = find_checkout(&root.join("wt"))?.unwrap();
+        assert_eq!(checkout.root, root.join("wt"));
+        assert!(checkout.is_worktree && !checkout.is_submodule && checkout.is_sparse);
+
+        let checkout = find_checkout(&repo.join("sub/dir"))?.unwrap();
+        assert_eq!(checkout.root, repo.join("sub"));
+        assert!(!checkout.is_worktree && checkout.is_submodule && !checkout.is_sparse);
+
+        // A sparse hg checkout.
+        let hg = root.join("hg");
+        write(&hg.join(".hg/sparse"), "[include]\nfoo\n");
+        fs_util::create_dir_all(hg.join("foo"))?;
+        let checkout = find_checkout(&hg.join("foo"))?.unwrap();
+        assert_eq!(checkout.kind, VcsKind::Hg);
+        assert_eq!(checkout.root, hg);
+        assert!(checkout.is_sparse);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fake_vcs() -> buck2_error::Result<()> {
+        let tempdir = tempfile::tempdir()?;
+        let mut vcs = FakeVcs::new(
+            Checkout {
+                kind: VcsKind::Git,
+                root: AbsPath::new(tempdir.path())?.to_owned(),
+                is_worktree: true,
+                is_submodule: false,
+                is_sparse: false,
+            },
+            "abc",
+        );
+        let details = RevisionDetails {
+            global_rev: None,
+            timestamp: 1,
+        };
+        vcs.revision_details.insert("abc".to_owned(), details);
+        assert_eq!(vcs.current_revision().await?, "abc");
+        assert_eq!(vcs.revision_details("abc").await?, details);
+        assert!(vcs.revision_details("def").await.is_err());
+        assert!(
+            vcs.rage_info()
+                .await?
+                .contains("Checkout layout: worktree\n")
+        );
+        Ok(())
+    }
+}
@@ -24,6 +24,7 @@ use dupe::Dupe;
 use static_assertions::assert_eq_size;
 
 use crate::execution_types::execution::ExecutionPlatformResolution;
+use crate::fs::output_path_scheme::OutputPathScheme;
 use crate::fs::paths::forward_rel_path::ForwardRelativePath;
 use crate::fs::paths::forward_rel_path::ForwardRelativePathBuf;
 use crate::fs::project_rel_path::ProjectRelativePath;
@@ -117,6 +118,7 @@ impl BaseDeferredKey {
     pub(crate) fn make_hashed_path(
         &self,
         base: &ProjectRelativePath,
+        scheme: &OutputPathScheme,
         prefix: &ForwardRelativePath,
         action_key: Option<&str>,
         path: &ForwardRelativePath,
@@ -126,17 +128,16 @@ impl BaseDeferredKey {
             BaseDeferredKey::TargetLabel(target) => {
                 let cell_relative_path = target.pkg().cell_relative_path().as_str();
                 let escaped_target_name = Self::escape_target_name(target.name().as_str());
+                let cfg = scheme.configuration_component(target.cfg());
+                let exec_cfg = target.exec_cfg().map(|x| scheme.configuration_component(x));
 
                 // It is performance critical that we use slices and allocate via `join` instead of
                 // repeated calls to `join` on the path object because `join` allocates on each call,
                 // which has a significant impact.
                 let path_identifier = [
-                    target.cfg().output_hash().as_str(),
-                    if target.exec_cfg().is_some() { "-" } else { "" },
-                    target
-                        .exec_cfg()
-                        .as_ref()
-                        .map_or("", |x| x.output_hash().as_str()),
+                    cfg.as_ref(),
+                    if exec_cfg.is_some() { "-" } else { "" },
+                    exec_cfg.as_deref().unwrap_or_default(),
                     "/",
                     cell_relative_path,
                     if cell_relative_path.is_empty() {
@@ -36,6 +36,7 @@ pub fn buck2_hard_error_env() -> buck2_error::Result<Option<&'static str>> {
 
 static HARD_ERROR_CONFIG: HardErrorConfigHolder = HardErrorConfigHolder {
     config: ArcSwapOption::const_empty(),
+    escalated: ArcSwapOption::const_empty(),
 };
 
 static ALL_SOFT_ERROR_COUNTERS: Mutex<Vec<&'static AtomicUsize>> = Mutex::new(Vec::new());
@@ -137,6 +138,12 @@ pub fn reload_hard_error_config(var_value: &str) -> buck2_error::Result<()> {
     HARD_ERROR_CONFIG.reload_hard_error_config(var_value)
 }
 
+/// Soft errors of these categories are hard errors, in addition to those of `$BUCK2_HARD_ERROR`.
+/// Set by the daemon for categories over `buck2.soft_error_budget`.
+pub fn set_escalated_soft_error_categories(categories: SmallSet<String>) {
+    HARD_ERROR_CONFIG.set_escalated(categories)
+}
+
 pub struct StructuredErrorOptions {
     /// Log this error (to our event log and possibly to a task), but do not print it to stderr.
     pub quiet: bool,
@@ -202,6 +209,12 @@ pub fn handle_soft_error(
             .into());
     }
 
+    if HARD_ERROR_CONFIG.is_escalated(category) {
+        return Err(err
+            .context("Upgraded warning to failure: category is over `buck2.soft_error_budget`")
+            .into());
+    }
+
     if is_open_source() {
         // We don't log these, and we have no legacy users, and they might not upgrade that often,
         // so lets just break open source things immediately.
@@ -273,6 +286,7 @@ impl FromStr for HardErrorConfig {
 
 struct HardErrorConfigHolder {
     config: ArcSwapOption<HardErrorConfig>,
+    escalated: ArcSwapOption<SmallSet<String>>,
 }
 
 impl HardErrorConfigHolder {
@@ -287,6 +301,22 @@ impl HardErrorConfigHolder {
         self.config.store(Some(Arc::new(config)));
         Ok(())
     }
+
+    fn set_escalated(&self, categories: SmallSet<String>) {
+        let categories = if categories.is_empty() {
+            None
+        } else {
+            Some(Arc::new(categories))
+        };
+        self.escalated.store(categories);
+    }
+
+    fn is_escalated(&self, category: &str) -> bool {
+        self.escalated
+            .load()
+            .as_ref()
+            .is_some_and(|categories| categories.contains(category))
+    }
 }
 
 #[derive(buck2_error::Error, Debug)]
@@ -351,6 +381,7 @@ pub(crate) mod tests {
     fn test_reset_soft_error_handler() {
         let config = HardErrorConfigHolder {
             config: ArcSwapOption::const_empty(),
+            escalated: ArcSwapOption::const_empty(),
         };
 
         assert!(config.config.load().is_none());
@@ -370,6 +401,22 @@ pub(crate) mod tests {
         assert_eq!(**c2, HardErrorConfig::Bool(false));
     }
 
+    #[test]
+    fn test_escalated_categories() {
+        let config = HardErrorConfigHolder {
+            config: ArcSwapOption::const_empty(),
+            escalated: ArcSwapOption::const_empty(),
+        };
+        assert!(!config.is_escalated("foo"));
+
+        config.set_escalated(SmallSet::from_iter(["foo".to_owned()]));
+        assert!(config.is_escalated("foo"));
+        assert!(!config.is_escalated("bar"));
+
+        config.set_escalated(SmallSet::new());
+        assert!(!config.is_escalated("foo"));
+    }
+
     #[test]
     fn test_validate_category() {
         assert_matches!(validate_category("valid"), Ok(_));
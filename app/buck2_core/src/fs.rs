@@ -13,6 +13,7 @@ pub mod buck_out_path;
 pub mod cwd;
 pub mod dynamic_actions_action_key;
 pub mod fs_util;
+pub mod output_path_scheme;
 pub mod paths;
 pub mod project;
 pub mod project_rel_path;
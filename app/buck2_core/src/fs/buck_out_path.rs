@@ -23,6 +23,7 @@ use crate::cells::paths::CellRelativePath;
 use crate::deferred::base_deferred_key::BaseDeferredKey;
 use crate::deferred::key::DeferredHolderKey;
 use crate::fs::dynamic_actions_action_key::DynamicActionsActionKey;
+use crate::fs::output_path_scheme::OutputPathScheme;
 use crate::fs::paths::forward_rel_path::ForwardRelativePath;
 use crate::fs::paths::forward_rel_path::ForwardRelativePathBuf;
 use crate::fs::project_rel_path::ProjectRelativePath;
@@ -174,13 +175,22 @@ impl BuckOutTestPath {
 #[derive(Clone, Allocative)]
 pub struct BuckOutPathResolver {
     buck_out_v2: ProjectRelativePathBuf,
+    scheme: Arc<OutputPathScheme>,
 }
 
 impl BuckOutPathResolver {
     /// creates a 'BuckOutPathResolver' that will resolve outputs to the provided buck-out root.
     /// If not set, buck_out defaults to "buck-out/v2"
     pub fn new(buck_out_v2: ProjectRelativePathBuf) -> Self {
-        BuckOutPathResolver { buck_out_v2 }
+        Self::with_scheme(buck_out_v2, Arc::new(OutputPathScheme::default()))
+    }
+
+    /// Like `new`, but with configurations in paths formatted according to `scheme`.
+    pub fn with_scheme(buck_out_v2: ProjectRelativePathBuf, scheme: Arc<OutputPathScheme>) -> Self {
+        BuckOutPathResolver {
+            buck_out_v2,
+            scheme,
+        }
     }
 
     /// Returns the buck-out root.
@@ -188,6 +198,10 @@ impl BuckOutPathResolver {
         &self.buck_out_v2
     }
 
+    pub fn scheme(&self) -> &OutputPathScheme {
+        &self.scheme
+    }
+
     /// Resolves a 'BuckOutPath' into a 'ProjectRelativePath' based on the base
     /// directory, target and cell.
     pub fn resolve_gen(&self, path: &BuildArtifactPath) -> ProjectRelativePathBuf {
@@ -294,7 +308,14 @@ impl BuckOutPathResolver {
         path: &ForwardRelativePath,
         fully_hash_path: bool,
     ) -> ProjectRelativePathBuf {
-        owner.make_hashed_path(&self.buck_out_v2, prefix, action_key, path, fully_hash_path)
+        owner.make_hashed_path(
+            &self.buck_out_v2,
+            &self.scheme,
+            prefix,
+            action_key,
+            path,
+            fully_hash_path,
+        )
     }
 
     /// This function returns the exact location of the symlink of a given target.
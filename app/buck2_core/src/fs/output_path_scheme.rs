@@ -0,0 +1,187 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use allocative::Allocative;
+use dashmap::DashMap;
+
+use crate::configuration::data::ConfigurationData;
+use crate::configuration::hash::ConfigurationHash;
+use crate::fs::paths::file_name::FileName;
+use crate::soft_error;
+
+/// Length of a full configuration hash.
+const FULL_HASH_LENGTH: usize = 16;
+
+/// Shorter hashes collide too easily.
+const MIN_HASH_LENGTH: usize = 6;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum OutputPathSchemeError {
+    #[error(
+        "Output path hash length must be between {} and {}, got {0}",
+        MIN_HASH_LENGTH,
+        FULL_HASH_LENGTH
+    )]
+    InvalidHashLength(usize),
+    #[error("Output path alias `{1}` for `{0}` is not a valid file name")]
+    InvalidAlias(String, String),
+    #[error(
+        "Configurations `{0}` and `{1}` have the same output path component `{2}`, their outputs \
+        will overwrite each other. Increase `buck2.output_path_hash_length` or change the \
+        aliases in `[buck2_output_path_aliases]`"
+    )]
+    Collision(String, String, String),
+}
+
+/// How configurations appear in `buck-out` paths of target outputs.
+///
+/// By default, paths contain the full configuration hash. Paths can be made shorter by
+/// truncating the hash, and more readable by prefixing it with an alias of the platform.
+/// Since both make it possible for different configurations to map to the same path,
+/// configurations are checked for collisions as paths are resolved.
+#[derive(Debug, Allocative)]
+pub struct OutputPathScheme {
+    hash_length: usize,
+    /// Platform label to alias.
+    aliases: BTreeMap<String, String>,
+    /// Path component of every configuration seen so far, for collision detection.
+    #[allocative(skip)]
+    seen: DashMap<String, ConfigurationHash>,
+}
+
+impl Default for OutputPathScheme {
+    fn default() -> Self {
+        Self {
+            hash_length: FULL_HASH_LENGTH,
+            aliases: BTreeMap::new(),
+            seen: DashMap::new(),
+        }
+    }
+}
+
+impl PartialEq for OutputPathScheme {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash_length == other.hash_length && self.aliases == other.aliases
+    }
+}
+
+impl Eq for OutputPathScheme {}
+
+impl OutputPathScheme {
+    pub fn new(
+        hash_length: Option<usize>,
+        aliases: BTreeMap<String, String>,
+    ) -> buck2_error::Result<Self> {
+        let hash_length = hash_length.unwrap_or(FULL_HASH_LENGTH);
+        if !(MIN_HASH_LENGTH..=FULL_HASH_LENGTH).contains(&hash_length) {
+            return Err(OutputPathSchemeError::InvalidHashLength(hash_length).into());
+        }
+        for (platform, alias) in &aliases {
+            if FileName::new(alias).is_err() {
+                return Err(
+                    OutputPathSchemeError::InvalidAlias(platform.clone(), alias.clone()).into(),
+                );
+            }
+        }
+        Ok(Self {
+            hash_length,
+            aliases,
+            seen: DashMap::new(),
+        })
+    }
+
+    fn is_default(&self) -> bool {
+        self.hash_length == FULL_HASH_LENGTH && self.aliases.is_empty()
+    }
+
+    /// The path component for outputs in `cfg`: `<alias>-<hash>` if the platform has an alias,
+    /// otherwise `<hash>`.
+    pub fn configuration_component<'a>(&self, cfg: &'a ConfigurationData) -> Cow<'a, str> {
+        if self.is_default() {
+            return Cow::Borrowed(cfg.output_hash().as_str());
+        }
+        let hash = &cfg.output_hash().as_str()[..self.hash_length];
+        let component = match cfg.label().ok().and_then(|label| self.aliases.get(label)) {
+            Some(alias) => format!("{}-{}", alias, hash),
+            None => hash.to_owned(),
+        };
+        if let Some(existing) = self.record(&component, cfg.output_hash()) {
+            // Resolving paths cannot fail, and whichever configuration comes first would win.
+            let _ignored = soft_error!(
+                "output_path_collision",
+                OutputPathSchemeError::Collision(
+                    existing.as_str().to_owned(),
+                    cfg.output_hash().as_str().to_owned(),
+                    component.clone(),
+                )
+                .into()
+            );
+        }
+        Cow::Owned(component)
+    }
+
+    /// Records that `component` is used for `hash`. Returns the other configuration hash if the
+    /// component is already used by a different configuration.
+    fn record(&self, component: &str, hash: &ConfigurationHash) -> Option<ConfigurationHash> {
+        if let Some(existing) = self.seen.get(component) {
+            return (*existing != *hash).then(|| existing.clone());
+        }
+        let existing = self
+            .seen
+            .entry(component.to_owned())
+            .or_insert_with(|| hash.clone());
+        (*existing != *hash).then(|| existing.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configuration_component() {
+        let cfg = ConfigurationData::testing_new();
+        let full = cfg.output_hash().as_str();
+
+        let scheme = OutputPathScheme::default();
+        assert_eq!(scheme.configuration_component(&cfg), full);
+
+        let scheme = OutputPathScheme::new(Some(8), BTreeMap::new()).unwrap();
+        assert_eq!(scheme.configuration_component(&cfg), &full[..8]);
+
+        let aliases = BTreeMap::from([("<testing>".to_owned(), "test".to_owned())]);
+        let scheme = OutputPathScheme::new(Some(8), aliases).unwrap();
+        assert_eq!(
+            scheme.configuration_component(&cfg),
+            format!("test-{}", &full[..8])
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(OutputPathScheme::new(Some(4), BTreeMap::new()).is_err());
+        assert!(OutputPathScheme::new(Some(17), BTreeMap::new()).is_err());
+        let aliases = BTreeMap::from([("root//:p".to_owned(), "a/b".to_owned())]);
+        assert!(OutputPathScheme::new(None, aliases).is_err());
+    }
+
+    #[test]
+    fn test_collision() {
+        let scheme = OutputPathScheme::new(Some(8), BTreeMap::new()).unwrap();
+        let a = ConfigurationHash::new(0x1234_5678_0000_0001);
+        let b = ConfigurationHash::new(0x1234_5678_0000_0002);
+        assert_eq!(scheme.record("12345678", &a), None);
+        assert_eq!(scheme.record("12345678", &a), None);
+        assert_eq!(scheme.record("12345678", &b), Some(a));
+    }
+}
@@ -20,6 +20,7 @@ use buck2_client_ctx::daemon_constraints::gen_daemon_constraints;
 use buck2_client_ctx::version::BuckVersion;
 use buck2_common::buckd_connection::ConnectionType;
 use buck2_common::daemon_dir::DaemonDir;
+use buck2_common::daemon_env::make_daemon_env_hermetic;
 use buck2_common::init::DaemonStartupConfig;
 use buck2_common::invocation_paths::InvocationPaths;
 use buck2_common::memory;
@@ -32,6 +33,7 @@ use buck2_server::daemon::daemon_tcp::create_listener;
 use buck2_server::daemon::server::BuckdServer;
 use buck2_server::daemon::server::BuckdServerDelegate;
 use buck2_server::daemon::server::BuckdServerInitPreferences;
+use buck2_server::daemon::server::PrefetchTargetPatterns;
 use buck2_util::threads::thread_spawn;
 use buck2_util::tokio_runtime::new_tokio_runtime;
 use dice::DetectCycles;
@@ -74,8 +76,10 @@ pub struct DaemonCommand {
     skip_macos_qos: bool,
     /// Early configs that the daemon needs at startup. Those are read by the client then passed to
     /// the daemon. The client will restart the daemon if they mismatch.
-    #[clap(value_parser = DaemonStartupConfig::deserialize)]
-    daemon_startup_config: DaemonStartupConfig,
+    ///
+    /// Only optional so that `buck2 daemon` subcommands, which do not take it, can be parsed.
+    #[clap(required = true, value_parser = DaemonStartupConfig::deserialize)]
+    daemon_startup_config: Option<DaemonStartupConfig>,
 
     #[clap(env("ENABLE_TRACE_IO"), long)]
     enable_trace_io: bool,
@@ -85,6 +89,19 @@ pub struct DaemonCommand {
     /// state.
     #[clap(long)]
     reject_materializer_state: Option<String>,
+
+    /// Target patterns of the command the daemon is started for. Their build files are prefetched
+    /// while the daemon starts.
+    #[clap(long = "prefetch-target-pattern", requires = "prefetch_working_dir")]
+    prefetch_target_patterns: Vec<String>,
+    /// Working directory `--prefetch-target-pattern` is relative to.
+    #[clap(long)]
+    prefetch_working_dir: Option<String>,
+
+    /// Position of the file watcher of the daemon this one replaces. The file watcher starts
+    /// from it instead of a fresh instance.
+    #[clap(long)]
+    file_watcher_cursor: Option<String>,
 }
 
 impl DaemonCommand {
@@ -94,9 +111,12 @@ impl DaemonCommand {
             checker_interval_seconds: 60,
             dont_daemonize: true,
             skip_macos_qos: true,
-            daemon_startup_config,
+            daemon_startup_config: Some(daemon_startup_config),
             enable_trace_io: false,
             reject_materializer_state: None,
+            prefetch_target_patterns: Vec::new(),
+            prefetch_working_dir: None,
+            file_watcher_cursor: None,
         }
     }
 }
@@ -179,12 +199,29 @@ impl DaemonCommand {
         // NOTE: Do not create any threads before this point.
         //   Daemonize does not preserve threads.
 
+        let daemon_startup_config = self
+            .daemon_startup_config
+            .internal_error("Daemon startup config is required by clap")?;
+
+        if !in_process {
+            // Also must happen while single-threaded. Not done in-process, where this would
+            // change the environment of the client.
+            make_daemon_env_hermetic(&daemon_startup_config.daemon_env);
+        }
+
         let server_init_ctx = BuckdServerInitPreferences {
             detect_cycles: buck2_env!("DICE_DETECT_CYCLES_UNSTABLE", type=DetectCycles)?,
             which_dice: buck2_env!("WHICH_DICE_UNSTABLE", type=WhichDice)?,
             enable_trace_io: self.enable_trace_io,
             reject_materializer_state: self.reject_materializer_state.map(|s| s.into()),
-            daemon_startup_config: self.daemon_startup_config,
+            prefetch_target_patterns: self.prefetch_working_dir.map(|working_dir| {
+                PrefetchTargetPatterns {
+                    working_dir,
+                    patterns: self.prefetch_target_patterns,
+                }
+            }),
+            file_watcher_cursor: self.file_watcher_cursor,
+            daemon_startup_config,
         };
 
         let span = tracing::info_span!("daemon_listener");
@@ -581,6 +618,8 @@ mod tests {
                 which_dice: None,
                 enable_trace_io: false,
                 reject_materializer_state: None,
+                prefetch_target_patterns: None,
+                file_watcher_cursor: None,
                 daemon_startup_config: DaemonStartupConfig::testing_empty(),
             },
             process_info.clone(),
@@ -92,6 +92,24 @@ impl DirectorySelector {
         }
     }
 
+    /// Whether this selects `path`, or at least some of the entries under it if it is a
+    /// directory.
+    pub fn selects(&self, path: impl IntoFileNameBufIterator) -> bool {
+        let mut selector = self;
+
+        for entry in path.into_iter() {
+            match selector {
+                Self::Traverse(s) => match s.get(&entry) {
+                    Some(s) => selector = s,
+                    None => return false,
+                },
+                Self::Take => return true,
+            }
+        }
+
+        !selector.is_empty()
+    }
+
     /// Filter a DirectoryBuilder by only retaining matching entries.
     pub fn filter<L, H>(&self, dir: &mut DirectoryBuilder<L, H>) -> Result<(), DirectoryFilterError>
     where
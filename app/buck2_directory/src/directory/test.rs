@@ -408,6 +408,23 @@ fn test_filter() -> buck2_error::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_selects() {
+    let mut selector = DirectorySelector::empty();
+    assert!(!selector.selects(path("a")));
+
+    selector.select(path("a"));
+    selector.select(path("b/b"));
+
+    assert!(selector.selects(path("a")));
+    assert!(selector.selects(path("a/aa")));
+    assert!(selector.selects(path("b")));
+    assert!(selector.selects(path("b/b")));
+    assert!(selector.selects(path("b/b/c")));
+    assert!(!selector.selects(path("b/bb")));
+    assert!(!selector.selects(path("c")));
+}
+
 #[test]
 fn test_entry_walk() {
     {
@@ -157,6 +157,23 @@ impl EdenConnectionManager {
             .collect()
     }
 
+    /// Converts globs relative to the project root to globs relative to the mount point.
+    pub fn project_globs_as_eden_globs(
+        &self,
+        globs: impl IntoIterator<Item = String>,
+    ) -> Vec<String> {
+        globs
+            .into_iter()
+            .map(|g| {
+                if self.project_root.is_empty() {
+                    g
+                } else {
+                    format!("{}/{}", self.project_root, g)
+                }
+            })
+            .collect()
+    }
+
     /// Returns a string like "20220102-030405", assuming this is a release version. This is
     /// pattern-matched off of what the Eden CLI does.
     pub async fn get_eden_version(&self) -> buck2_error::Result<Option<String>> {
@@ -441,6 +458,7 @@ impl_has_error_handling_strategy!(ReaddirError);
 impl_has_error_handling_strategy!(GetSHA1Error);
 impl_has_error_handling_strategy!(GetCurrentJournalPositionError);
 impl_has_error_handling_strategy!(ChangesSinceV2Error);
+impl_has_error_handling_strategy!(PrefetchFilesV2Error);
 
 fn eden_error_kind_tag(e: &EdenError) -> Option<ErrorTag> {
     let tag = match e {
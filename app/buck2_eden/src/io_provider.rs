@@ -22,6 +22,8 @@ use buck2_common::file_ops::TrackedFileDigest;
 use buck2_common::io::fs::FsIoProvider;
 use buck2_common::io::fs::ReadUncheckedOptions;
 use buck2_common::io::IoProvider;
+use buck2_common::io::PrefetchDir;
+use buck2_common::io::PrefetchedDirs;
 use buck2_core;
 use buck2_core::buck2_env;
 use buck2_core::fs::project::ProjectRoot;
@@ -33,11 +35,13 @@ use compact_str::CompactString;
 use dupe::Dupe;
 use edenfs::FileAttributes;
 use edenfs::GetAttributesFromFilesParams;
+use edenfs::PrefetchParams;
 use edenfs::ReaddirParams;
 use edenfs::SourceControlType;
 use edenfs::SyncBehavior;
 use edenfs::SynchronizeWorkingCopyParams;
 use fbinit::FacebookInit;
+use parking_lot::Mutex;
 use tokio::sync::Semaphore;
 
 use crate::connection::EdenConnectionManager;
@@ -49,6 +53,9 @@ pub struct EdenIoProvider {
     manager: EdenConnectionManager,
     fs: FsIoProvider,
     digest: Digest,
+    /// Whether to act on prefetch hints (`buck2.eden_prefetch`).
+    prefetch: bool,
+    prefetched: Mutex<PrefetchedDirs>,
 }
 
 #[derive(Allocative, Copy, Clone, Dupe)]
@@ -67,6 +74,7 @@ impl EdenIoProvider {
         fb: FacebookInit,
         fs: &ProjectRoot,
         cas_digest_config: CasDigestConfig,
+        prefetch: bool,
     ) -> buck2_error::Result<Option<Self>> {
         let (digest, min_eden_version) = if cas_digest_config.source_files_config().allows_sha1() {
             (Digest::Sha1, "20220905-214046")
@@ -112,6 +120,8 @@ impl EdenIoProvider {
             manager,
             fs: FsIoProvider::new(fs.dupe(), cas_digest_config),
             digest,
+            prefetch,
+            prefetched: Mutex::new(PrefetchedDirs::default()),
         }))
     }
 
@@ -380,6 +390,38 @@ impl IoProvider for EdenIoProvider {
             .tag(ErrorTag::IoEden)
     }
 
+    async fn prefetch(&self, dirs: Vec<PrefetchDir>) -> buck2_error::Result<()> {
+        if !self.prefetch {
+            return Ok(());
+        }
+
+        let dirs = self.prefetched.lock().insert(dirs);
+        if dirs.is_empty() {
+            return Ok(());
+        }
+
+        let params = PrefetchParams {
+            mountPoint: self.manager.get_mount_point(),
+            globs: self
+                .manager
+                .project_globs_as_eden_globs(dirs.iter().flat_map(|d| d.globs())),
+            // Eden fetches in the background and this returns once the globs are evaluated.
+            background: true,
+            ..Default::default()
+        };
+
+        self.manager
+            .with_eden(|eden| {
+                tracing::debug!("prefetchFilesV2({:?})", params.globs);
+                eden.prefetchFilesV2(&params)
+            })
+            .await
+            .buck_error_context("Error prefetching from Eden")
+            .tag(ErrorTag::IoEden)?;
+
+        Ok(())
+    }
+
     fn name(&self) -> &'static str {
         "eden"
     }
@@ -0,0 +1,92 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::time::Duration;
+
+use buck2_common::build_count::BuildCountManager;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_data::ParsedTargetPatterns;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildTimeEstimate {
+    /// No previous build of these targets was recorded.
+    Unknown,
+    /// Critical path of previous builds of these targets.
+    CriticalPath(Duration),
+}
+
+impl BuildTimeEstimate {
+    /// Time the build is expected to still take. Once a build outlasts its estimate we no longer
+    /// know how much is left.
+    pub fn remaining(self, elapsed: Duration) -> Option<Duration> {
+        match self {
+            BuildTimeEstimate::Unknown => None,
+            BuildTimeEstimate::CriticalPath(d) => d.checked_sub(elapsed).filter(|d| !d.is_zero()),
+        }
+    }
+}
+
+/// Estimates how long a build will take from the critical paths recorded for previous builds.
+/// The state is relevant per command since the estimator is recreated for each command.
+pub struct BuildTimeEstimator {
+    build_count_manager: BuildCountManager,
+    estimate: Option<BuildTimeEstimate>,
+}
+
+impl BuildTimeEstimator {
+    pub fn new(build_count_dir: AbsNormPathBuf) -> Self {
+        Self {
+            build_count_manager: BuildCountManager::new(build_count_dir),
+            estimate: None,
+        }
+    }
+
+    /// `None` until the command has resolved its target patterns.
+    pub fn estimate(&self) -> Option<BuildTimeEstimate> {
+        self.estimate
+    }
+
+    pub async fn update_parsed_target_patterns(
+        &mut self,
+        patterns: &ParsedTargetPatterns,
+    ) -> buck2_error::Result<()> {
+        if self.estimate.is_some() {
+            return Ok(());
+        }
+        let estimate = self
+            .build_count_manager
+            .estimated_critical_path(patterns)
+            .await?;
+        self.estimate = Some(match estimate {
+            Some(d) => BuildTimeEstimate::CriticalPath(d),
+            None => BuildTimeEstimate::Unknown,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remaining() {
+        let estimate = BuildTimeEstimate::CriticalPath(Duration::from_secs(10));
+        assert_eq!(
+            estimate.remaining(Duration::from_secs(4)),
+            Some(Duration::from_secs(6))
+        );
+        assert_eq!(estimate.remaining(Duration::from_secs(10)), None);
+        assert_eq!(estimate.remaining(Duration::from_secs(12)), None);
+        assert_eq!(
+            BuildTimeEstimate::Unknown.remaining(Duration::from_secs(1)),
+            None
+        );
+    }
+}
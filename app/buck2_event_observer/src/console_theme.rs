@@ -0,0 +1,180 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Presentation preferences shared by the simple console and the superconsole, configured in the
+//! `[ui]` section of the buckconfig and received via `ConsolePreferences`.
+
+use superconsole::style::Color;
+use superconsole::Span;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsoleTheme {
+    /// Use a palette that does not rely on telling red and green apart.
+    pub color_blind: bool,
+    /// Use emoji and other symbols in status prefixes.
+    pub emoji: bool,
+    /// Emit OSC 8 hyperlinks.
+    pub hyperlinks: bool,
+    /// URL to link target labels to, where `{target}` is replaced by the (unconfigured) label.
+    pub target_url_template: Option<String>,
+}
+
+impl Default for ConsoleTheme {
+    fn default() -> Self {
+        Self {
+            color_blind: false,
+            emoji: true,
+            hyperlinks: false,
+            target_url_template: None,
+        }
+    }
+}
+
+impl ConsoleTheme {
+    pub fn from_preferences(prefs: &buck2_data::ConsolePreferences) -> Self {
+        Self {
+            color_blind: prefs.color_blind,
+            emoji: !prefs.no_emoji,
+            hyperlinks: prefs.hyperlinks,
+            target_url_template: Some(prefs.target_url_template.clone()).filter(|t| !t.is_empty()),
+        }
+    }
+
+    pub fn success_color(&self) -> Color {
+        self.color(Color::Green)
+    }
+
+    pub fn failure_color(&self) -> Color {
+        self.color(Color::Red)
+    }
+
+    /// The color to use instead of `color` in this palette. Consoles pick colors for what they
+    /// mean (red for errors, green for success), so only those that are hard to tell apart for
+    /// color-blind users are replaced.
+    pub fn color(&self, color: Color) -> Color {
+        if !self.color_blind {
+            return color;
+        }
+        match color {
+            Color::Green | Color::DarkGreen => Color::Blue,
+            // Orange, from the Okabe-Ito palette.
+            Color::Red => Color::Rgb {
+                r: 230,
+                g: 159,
+                b: 0,
+            },
+            // Vermillion, from the Okabe-Ito palette.
+            Color::DarkRed => Color::Rgb {
+                r: 213,
+                g: 94,
+                b: 0,
+            },
+            color => color,
+        }
+    }
+
+    /// `symbol` followed by `label`, or just `label` if emoji are disabled.
+    pub fn status(&self, symbol: &str, label: &str) -> String {
+        if self.emoji {
+            format!("{} {}", symbol, label)
+        } else {
+            label.to_owned()
+        }
+    }
+
+    /// The URL configured for `target`, if hyperlinks are enabled.
+    pub fn target_url(&self, target: &str) -> Option<String> {
+        if !self.hyperlinks {
+            return None;
+        }
+        let template = self.target_url_template.as_ref()?;
+        Some(template.replace("{target}", target))
+    }
+
+    /// A URL opening the file at the absolute `path`, if hyperlinks are enabled.
+    pub fn file_url(&self, path: &str) -> Option<String> {
+        self.hyperlinks.then(|| format!("file://{}", path))
+    }
+
+    /// `text` as an OSC 8 hyperlink to `url`, for text written directly to the terminal. Text
+    /// drawn by the superconsole must use `link_span` instead, which does not count the escape
+    /// sequences towards the width of the line.
+    pub fn hyperlink(&self, text: &str, url: Option<String>) -> String {
+        match url {
+            Some(url) => format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text),
+            None => text.to_owned(),
+        }
+    }
+
+    /// `span` as a hyperlink to `url`, if any.
+    pub fn link_span(&self, span: Span, url: Option<String>) -> Span {
+        match url {
+            Some(url) => span.with_hyperlink(url),
+            None => span,
+        }
+    }
+
+    /// `text` as a hyperlink to the URL configured for `target`, if any.
+    pub fn target_link(&self, text: &str, target: &str) -> String {
+        self.hyperlink(text, self.target_url(target))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use superconsole::style::Color;
+
+    use super::ConsoleTheme;
+
+    #[test]
+    fn test_links() {
+        let theme = ConsoleTheme {
+            hyperlinks: true,
+            target_url_template: Some("https://example.com/t?q={target}".to_owned()),
+            ..ConsoleTheme::default()
+        };
+        assert_eq!(
+            theme.target_link("foo", "cell//pkg:foo"),
+            "\x1b]8;;https://example.com/t?q=cell//pkg:foo\x1b\\foo\x1b]8;;\x1b\\"
+        );
+
+        let disabled = ConsoleTheme {
+            hyperlinks: false,
+            ..theme
+        };
+        assert_eq!(disabled.target_link("foo", "cell//pkg:foo"), "foo");
+        assert_eq!(ConsoleTheme::default().target_link("foo", "x"), "foo");
+    }
+
+    #[test]
+    fn test_color() {
+        let theme = ConsoleTheme::default();
+        assert_eq!(theme.color(Color::DarkRed), Color::DarkRed);
+        assert_eq!(theme.success_color(), Color::Green);
+
+        let theme = ConsoleTheme {
+            color_blind: true,
+            ..theme
+        };
+        assert_eq!(theme.success_color(), Color::Blue);
+        assert_ne!(theme.color(Color::DarkRed), Color::DarkRed);
+        assert_eq!(theme.color(Color::Cyan), Color::Cyan);
+    }
+
+    #[test]
+    fn test_status() {
+        let theme = ConsoleTheme::default();
+        assert_eq!(theme.status("✓", "Pass"), "✓ Pass");
+        let theme = ConsoleTheme {
+            emoji: false,
+            ..theme
+        };
+        assert_eq!(theme.status("✓", "Pass"), "Pass");
+    }
+}
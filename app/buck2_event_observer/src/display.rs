@@ -38,6 +38,7 @@ use superconsole::Span;
 use termwiz::escape::Action;
 use termwiz::escape::ControlCode;
 
+use crate::console_theme::ConsoleTheme;
 use crate::fmt_duration;
 use crate::verbosity::Verbosity;
 use crate::what_ran::command_to_string;
@@ -478,6 +479,7 @@ pub fn display_executor_stage(
 
     let label = match stage {
         Stage::Prepare(..) => "prepare",
+        Stage::BuildInputTree(..) => "build_input_tree",
         Stage::CacheQuery(cache_query) => {
             match buck2_data::CacheType::from_i32(cache_query.cache_type).unwrap() {
                 buck2_data::CacheType::ActionCache => "re_action_cache",
@@ -550,12 +552,14 @@ pub struct InvalidBuckEvent(pub Arc<BuckEvent>);
 
 pub fn format_test_result(
     test_result: &buck2_data::TestResult,
+    theme: &ConsoleTheme,
 ) -> buck2_error::Result<Option<Lines>> {
     let buck2_data::TestResult {
         name,
         status,
         duration,
         details,
+        target_label,
         ..
     } = test_result;
     let status = TestStatus::try_from(*status)?;
@@ -567,19 +571,29 @@ pub fn format_test_result(
         return Ok(None);
     }
 
+    let failure = |symbol, label| theme.status(symbol, label).with(theme.failure_color());
+    let success = |symbol, label| theme.status(symbol, label).with(theme.success_color());
     let prefix = match status {
-        TestStatus::FAIL => Span::new_styled("✗ Fail".to_owned().red()),
-        TestStatus::SKIP => Span::new_styled("↷ Skip".to_owned().cyan()),
-        TestStatus::OMITTED => Span::new_styled("\u{20E0} Omitted".to_owned().cyan()),
-        TestStatus::FATAL => Span::new_styled("⚠ Fatal".to_owned().red()),
-        TestStatus::TIMEOUT => Span::new_styled("✉ Timeout".to_owned().cyan()),
-        TestStatus::PASS => Span::new_styled("✓ Pass".to_owned().green()),
-        TestStatus::LISTING_SUCCESS => Span::new_styled("✓ Listing success".to_owned().green()),
-        TestStatus::UNKNOWN => Span::new_styled("? Unknown".to_owned().cyan()),
-        TestStatus::RERUN => Span::new_styled("↻ Rerun".to_owned().cyan()),
-        TestStatus::LISTING_FAILED => Span::new_styled("⚠ Listing failed".to_owned().red()),
+        TestStatus::FAIL => Span::new_styled(failure("✗", "Fail")),
+        TestStatus::SKIP => Span::new_styled(theme.status("↷", "Skip").cyan()),
+        TestStatus::OMITTED => Span::new_styled(theme.status("\u{20E0}", "Omitted").cyan()),
+        TestStatus::FATAL => Span::new_styled(failure("⚠", "Fatal")),
+        TestStatus::TIMEOUT => Span::new_styled(theme.status("✉", "Timeout").cyan()),
+        TestStatus::PASS => Span::new_styled(success("✓", "Pass")),
+        TestStatus::LISTING_SUCCESS => Span::new_styled(success("✓", "Listing success")),
+        TestStatus::UNKNOWN => Span::new_styled(theme.status("?", "Unknown").cyan()),
+        TestStatus::RERUN => Span::new_styled(theme.status("↻", "Rerun").cyan()),
+        TestStatus::LISTING_FAILED => Span::new_styled(failure("⚠", "Listing failed")),
     }?;
-    let mut base = Line::from_iter([prefix, Span::new_unstyled(format!(": {}", name,))?]);
+    let url = target_label
+        .as_ref()
+        .and_then(|l| l.label.as_ref())
+        .and_then(|label| theme.target_url(&format!("{}:{}", label.package, label.name)));
+    let mut base = Line::from_iter([
+        prefix,
+        Span::new_unstyled(": ")?,
+        theme.link_span(Span::new_unstyled(name)?, url),
+    ]);
     if let Some(duration) = duration {
         if let Ok(duration) = Duration::try_from(duration.clone()) {
             base.push(Span::new_unstyled(format!(
@@ -603,6 +617,8 @@ pub fn format_test_result(
 
 pub struct ActionErrorDisplay<'a> {
     pub action_id: String,
+    /// Unconfigured label of the target owning the action, if it is owned by a target.
+    pub target: Option<String>,
     pub reason: String,
     pub command: Option<&'a buck2_data::CommandExecutionDetails>,
     pub error_diagnostics: Option<&'a buck2_data::ActionErrorDiagnostics>,
@@ -685,6 +701,9 @@ impl<'a> ActionErrorDisplay<'a> {
                             remote_command.action_digest
                         );
                     }
+                    if let Some(action_url) = &remote_command.action_url {
+                        append!("Remote action page: {}", action_url);
+                    }
                 }
                 Some(Command::OmittedLocalCommand(..)) | None => {
                     // Nothing to show in this case.
@@ -776,8 +795,21 @@ pub fn display_action_error<'a>(
 
     let reason = get_action_error_reason(error)?;
 
+    let target = match error.key.as_ref().and_then(|k| k.owner.as_ref()) {
+        Some(
+            action_key::Owner::TargetLabel(label)
+            | action_key::Owner::TestTargetLabel(label)
+            | action_key::Owner::LocalResourceSetup(label),
+        ) => label
+            .label
+            .as_ref()
+            .map(|l| format!("{}:{}", l.package, l.name)),
+        _ => None,
+    };
+
     Ok(ActionErrorDisplay {
         action_id: display_action_identity(error.key.as_ref(), error.name.as_ref(), opts)?,
+        target,
         reason,
         command,
         error_diagnostics: error.error_diagnostics.as_ref(),
@@ -883,6 +915,26 @@ pub fn success_stderr<'a>(
     Ok(Some(stderr))
 }
 
+/// The output lines of an action run with `stream_output`, prefixed by the action.
+pub fn display_action_output_lines(
+    output: &buck2_data::ActionOutputLines,
+    opts: TargetDisplayOptions,
+) -> buck2_error::Result<Vec<String>> {
+    let action = display_action_identity(output.key.as_ref(), output.name.as_ref(), opts)?;
+    let mut lines: Vec<String> = output
+        .lines
+        .iter()
+        .map(|line| format!("[{}] {}", action, line.line))
+        .collect();
+    if output.dropped_lines != 0 {
+        lines.push(format!(
+            "[{}] ... {} lines not shown",
+            action, output.dropped_lines
+        ));
+    }
+    Ok(lines)
+}
+
 pub fn sanitize_output_colors(stderr: &[u8]) -> String {
     let mut sanitized = String::with_capacity(stderr.len());
     let mut parser = termwiz::escape::parser::Parser::new();
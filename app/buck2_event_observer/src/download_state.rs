@@ -0,0 +1,155 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Progress of the large CAS downloads in flight, from `DownloadProgress` events.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use superconsole::DrawMode;
+use superconsole::Line;
+use superconsole::Lines;
+
+use crate::fmt_duration::fmt_duration;
+use crate::humanized::HumanizedBytes;
+use crate::humanized::HumanizedBytesPerSecond;
+
+/// Downloads listed, the others are counted.
+const MAX_DOWNLOADS_SHOWN: usize = 3;
+
+struct Download {
+    total_bytes: u64,
+    /// Time and bytes downloaded of the first and last events.
+    first: (SystemTime, u64),
+    last: (SystemTime, u64),
+}
+
+impl Download {
+    fn bytes_per_second(&self) -> Option<u64> {
+        let elapsed = self.last.0.duration_since(self.first.0).ok()?;
+        if elapsed.is_zero() {
+            return None;
+        }
+        let bytes = self.last.1.saturating_sub(self.first.1);
+        Some((bytes as f64 / elapsed.as_secs_f64()) as u64)
+    }
+
+    fn render(&self, path: &str) -> Line {
+        let mut msg = format!(
+            "Downloading {}: {} / {}",
+            path,
+            HumanizedBytes::new(self.last.1),
+            HumanizedBytes::new(self.total_bytes),
+        );
+        if let Some(bytes_per_second) = self.bytes_per_second().filter(|b| *b > 0) {
+            let eta = Duration::from_secs(
+                self.total_bytes.saturating_sub(self.last.1) / bytes_per_second,
+            );
+            msg.push_str(&format!(
+                " ({}, {} left)",
+                HumanizedBytesPerSecond::new(bytes_per_second),
+                fmt_duration(eta, 1.0),
+            ));
+        }
+        Line::sanitized(&msg)
+    }
+}
+
+#[derive(Default)]
+pub struct DownloadState {
+    /// By path.
+    downloads: BTreeMap<String, Download>,
+}
+
+impl DownloadState {
+    pub fn update(&mut self, timestamp: SystemTime, progress: &buck2_data::DownloadProgress) {
+        let sample = (timestamp, progress.downloaded_bytes);
+        self.downloads
+            .entry(progress.path.clone())
+            .and_modify(|d| d.last = sample)
+            .or_insert(Download {
+                total_bytes: progress.total_bytes,
+                first: sample,
+                last: sample,
+            });
+    }
+
+    /// The materialization of `path` ended.
+    pub fn finish(&mut self, path: &str) {
+        self.downloads.remove(path);
+    }
+
+    pub fn render(&self, draw_mode: DrawMode) -> Lines {
+        if draw_mode == DrawMode::Final {
+            return Lines::new();
+        }
+        let mut lines: Vec<Line> = self
+            .downloads
+            .iter()
+            .take(MAX_DOWNLOADS_SHOWN)
+            .map(|(path, download)| download.render(path))
+            .collect();
+        let more = self.downloads.len().saturating_sub(MAX_DOWNLOADS_SHOWN);
+        if more > 0 {
+            lines.push(Line::sanitized(&format!("...and {} more downloads", more)));
+        }
+        Lines(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    use superconsole::DrawMode;
+
+    use crate::download_state::DownloadState;
+
+    fn progress(path: &str, downloaded_bytes: u64) -> buck2_data::DownloadProgress {
+        buck2_data::DownloadProgress {
+            path: path.to_owned(),
+            downloaded_bytes,
+            total_bytes: 1 << 30,
+        }
+    }
+
+    #[test]
+    fn test_download_state() {
+        let mut state = DownloadState::default();
+        let start = SystemTime::UNIX_EPOCH;
+        state.update(start, &progress("buck-out/v2/gen/big.zip", 0));
+        assert_eq!(
+            "Downloading buck-out/v2/gen/big.zip: 0B / 1.0GiB",
+            state
+                .render(DrawMode::Normal)
+                .fmt_for_test()
+                .to_string()
+                .trim()
+        );
+
+        state.update(
+            start + Duration::from_secs(10),
+            &progress("buck-out/v2/gen/big.zip", 512 << 20),
+        );
+        assert_eq!(
+            "Downloading buck-out/v2/gen/big.zip: 512MiB / 1.0GiB (51MiB/s, 10.0s left)",
+            state
+                .render(DrawMode::Normal)
+                .fmt_for_test()
+                .to_string()
+                .trim()
+        );
+        assert!(state.render(DrawMode::Final).is_empty());
+
+        state.finish("buck-out/v2/gen/big.zip");
+        assert!(state.render(DrawMode::Normal).is_empty());
+    }
+}
@@ -16,9 +16,11 @@ use buck2_events::BuckEvent;
 use buck2_wrapper_common::invocation_id::TraceId;
 
 use crate::action_stats::ActionStats;
+use crate::build_time_estimator::BuildTimeEstimator;
 use crate::cold_build_detector::ColdBuildDetector;
 use crate::debug_events::DebugEventsState;
 use crate::dice_state::DiceState;
+use crate::download_state::DownloadState;
 use crate::progress::BuildProgressStateTracker;
 use crate::re_state::ReState;
 use crate::session_info::SessionInfo;
@@ -31,12 +33,14 @@ pub struct EventObserver<E> {
     pub span_tracker: BuckEventSpanTracker,
     pub action_stats: ActionStats,
     re_state: ReState,
+    download_state: DownloadState,
     two_snapshots: TwoSnapshots, // NOTE: We got many more copies of this than we should.
     system_info: buck2_data::SystemInfo,
     session_info: SessionInfo,
     test_state: TestState,
     starlark_debugger_state: StarlarkDebuggerState,
     pub cold_build_detector: Option<ColdBuildDetector>,
+    pub build_time_estimator: Option<BuildTimeEstimator>,
     dice_state: DiceState,
     /// When running without the Superconsole, we skip some state that we don't need. This might be
     /// premature optimization.
@@ -48,11 +52,13 @@ where
     E: EventObserverExtra,
 {
     pub fn new(trace_id: TraceId, build_count_dir: Option<AbsNormPathBuf>) -> Self {
-        let cold_build_detector = build_count_dir.map(ColdBuildDetector::new);
+        let cold_build_detector = build_count_dir.clone().map(ColdBuildDetector::new);
+        let build_time_estimator = build_count_dir.map(BuildTimeEstimator::new);
         Self {
             span_tracker: BuckEventSpanTracker::new(),
             action_stats: ActionStats::default(),
             re_state: ReState::new(),
+            download_state: DownloadState::default(),
             two_snapshots: TwoSnapshots::default(),
             system_info: buck2_data::SystemInfo::default(),
             session_info: SessionInfo {
@@ -63,6 +69,7 @@ where
             test_state: TestState::default(),
             starlark_debugger_state: StarlarkDebuggerState::new(),
             cold_build_detector,
+            build_time_estimator,
             dice_state: DiceState::new(),
             extra: E::new(),
         }
@@ -90,6 +97,9 @@ where
                         ActionExecution(action_execution_end) => {
                             self.action_stats.update(action_execution_end);
                         }
+                        Materialization(materialization) => {
+                            self.download_state.finish(&materialization.path);
+                        }
                         buck2_data::span_end_event::Data::FileWatcher(file_watcher) => {
                             if let Some(cold_build_detector) = &mut self.cold_build_detector {
                                 cold_build_detector.update_merge_base(file_watcher).await?;
@@ -150,10 +160,18 @@ where
                                     .update_parsed_target_patterns(tag)
                                     .await?;
                             }
+                            if let Some(build_time_estimator) = &mut self.build_time_estimator {
+                                build_time_estimator
+                                    .update_parsed_target_patterns(tag)
+                                    .await?;
+                            }
                         }
                         DiceStateSnapshot(dice) => {
                             self.dice_state.update(dice);
                         }
+                        DownloadProgress(progress) => {
+                            self.download_state.update(event.timestamp(), progress);
+                        }
                         _ => {}
                     }
                 }
@@ -178,6 +196,10 @@ where
         &self.re_state
     }
 
+    pub fn download_state(&self) -> &DownloadState {
+        &self.download_state
+    }
+
     pub fn two_snapshots(&self) -> &TwoSnapshots {
         &self.two_snapshots
     }
@@ -12,11 +12,14 @@
 
 pub mod action_stats;
 pub mod action_util;
+pub mod build_time_estimator;
 pub mod cache_hit_rate;
 pub mod cold_build_detector;
+pub mod console_theme;
 pub mod debug_events;
 pub mod dice_state;
 pub mod display;
+pub mod download_state;
 pub mod event_observer;
 pub mod fmt_duration;
 pub mod humanized;
@@ -27,6 +30,7 @@ pub mod re_state;
 pub mod session_info;
 pub mod span_tracker;
 pub mod starlark_debug;
+pub mod target_status;
 pub mod test_state;
 pub mod two_snapshots;
 pub mod unpack_event;
@@ -115,6 +115,39 @@ impl ReState {
         Some(format!("Network: {}", parts.join("  ")))
     }
 
+    /// The RE bandwidth caps in effect and how many transfers they are currently holding back.
+    fn render_throttle(&self, two_snapshots: &TwoSnapshots) -> Option<String> {
+        fn part(name: &str, cap: Option<u64>, waiting: u32) -> Option<String> {
+            let cap = HumanizedBytesPerSecond::new(cap?);
+            Some(if waiting == 0 {
+                format!("{name} {cap}")
+            } else {
+                format!("{name} {cap} ({waiting} waiting)")
+            })
+        }
+
+        let (_, last) = two_snapshots.last.as_ref()?;
+        let parts: Vec<String> = [
+            part(
+                "Up",
+                last.re_upload_bytes_per_second_cap,
+                last.re_uploads_throttled,
+            ),
+            part(
+                "Down",
+                last.re_download_bytes_per_second_cap,
+                last.re_downloads_throttled,
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if parts.is_empty() {
+            return None;
+        }
+        Some(format!("Bandwidth caps: {}", parts.join("  ")))
+    }
+
     fn render_detailed_item_no_progress_stats(
         &self,
         name: &str,
@@ -243,6 +276,11 @@ impl ReState {
             None => return Ok(Lines::new()),
         };
         let mut lines = vec![Line::unstyled(&header)?];
+        if draw_mode == DrawMode::Normal {
+            if let Some(throttle) = self.render_throttle(two_snapshots) {
+                lines.push(Line::unstyled(&throttle)?);
+            }
+        }
         if detailed {
             lines.extend(self.render_detailed(two_snapshots)?);
         }
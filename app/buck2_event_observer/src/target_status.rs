@@ -0,0 +1,211 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::collections::HashSet;
+
+use buck2_data::ActionExecutionKind;
+use buck2_data::TargetBuildStatus;
+use buck2_error::BuckErrorContext;
+
+use crate::display;
+use crate::display::TargetDisplayOptions;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TargetStatus {
+    Success,
+    Failure,
+    /// Not attempted because a dependency failed.
+    DepFailed,
+    /// Incompatible with the target platform.
+    Skipped,
+    /// Succeeded without running any of the target's own actions.
+    Cached,
+}
+
+impl TargetStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TargetStatus::Success => "success",
+            TargetStatus::Failure => "failure",
+            TargetStatus::DepFailed => "dep_failed",
+            TargetStatus::Skipped => "skipped",
+            TargetStatus::Cached => "cached",
+        }
+    }
+}
+
+/// A requested configured target that finished building.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TargetCompletion {
+    /// The requested label including subtargets, without configuration.
+    pub target: String,
+    pub configuration: String,
+    pub status: TargetStatus,
+    /// Paths relative to the project root.
+    pub outputs: Vec<String>,
+}
+
+/// Turns `TargetBuildEnd` events into per-target statuses. The daemon does not know whether a
+/// target's actions were served from cache, so this also watches action executions to tell
+/// `Cached` apart from `Success`.
+#[derive(Default)]
+pub struct TargetStatusTracker {
+    /// Targets that owned at least one action that actually ran.
+    executed_owners: HashSet<String>,
+}
+
+impl TargetStatusTracker {
+    pub fn action_execution_end(
+        &mut self,
+        action: &buck2_data::ActionExecutionEnd,
+    ) -> buck2_error::Result<()> {
+        let ran = match ActionExecutionKind::from_i32(action.execution_kind) {
+            Some(
+                ActionExecutionKind::ActionCache
+                | ActionExecutionKind::RemoteDepFileCache
+                | ActionExecutionKind::LocalDepFile
+                | ActionExecutionKind::LocalActionCache
+                | ActionExecutionKind::Deferred,
+            ) => action.failed,
+            _ => true,
+        };
+        if ran {
+            if let Some(owner) = action.key.as_ref().and_then(|k| k.owner.as_ref()) {
+                self.executed_owners.insert(display::display_action_owner(
+                    owner,
+                    TargetDisplayOptions::for_log(),
+                )?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Must be called after the target's actions were observed, which is the order the daemon
+    /// sends events in.
+    pub fn target_build_end(
+        &self,
+        end: &buck2_data::TargetBuildEnd,
+    ) -> buck2_error::Result<TargetCompletion> {
+        let target = end
+            .target
+            .as_ref()
+            .buck_error_context("Missing `target` in `TargetBuildEnd`")?;
+        let configuration = target
+            .configuration
+            .as_ref()
+            .map(|c| c.full_name.clone())
+            .unwrap_or_default();
+        let status = match TargetBuildStatus::from_i32(end.status) {
+            Some(TargetBuildStatus::Success) => {
+                let label = display::display_configured_target_label(
+                    target,
+                    TargetDisplayOptions::for_log(),
+                )?;
+                if self.executed_owners.contains(&label) {
+                    TargetStatus::Success
+                } else {
+                    TargetStatus::Cached
+                }
+            }
+            Some(TargetBuildStatus::Skipped) => TargetStatus::Skipped,
+            Some(TargetBuildStatus::DepFailed) => TargetStatus::DepFailed,
+            Some(TargetBuildStatus::Failure | TargetBuildStatus::Unknown) | None => {
+                TargetStatus::Failure
+            }
+        };
+        Ok(TargetCompletion {
+            target: end.providers_label.clone(),
+            configuration,
+            status,
+            outputs: end.outputs.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(name: &str) -> buck2_data::ConfiguredTargetLabel {
+        buck2_data::ConfiguredTargetLabel {
+            label: Some(buck2_data::TargetLabel {
+                package: "root//pkg".to_owned(),
+                name: name.to_owned(),
+            }),
+            configuration: Some(buck2_data::Configuration {
+                full_name: "cfg".to_owned(),
+            }),
+            execution_configuration: None,
+        }
+    }
+
+    fn action(name: &str, kind: ActionExecutionKind) -> buck2_data::ActionExecutionEnd {
+        buck2_data::ActionExecutionEnd {
+            key: Some(buck2_data::ActionKey {
+                owner: Some(buck2_data::action_key::Owner::TargetLabel(label(name))),
+                ..Default::default()
+            }),
+            execution_kind: kind as i32,
+            ..Default::default()
+        }
+    }
+
+    fn end(name: &str, status: TargetBuildStatus) -> buck2_data::TargetBuildEnd {
+        buck2_data::TargetBuildEnd {
+            target: Some(label(name)),
+            status: status as i32,
+            providers_label: format!("root//pkg:{}", name),
+            outputs: vec![format!("buck-out/v2/gen/root/pkg/{}", name)],
+        }
+    }
+
+    #[test]
+    fn test_status() -> buck2_error::Result<()> {
+        let mut tracker = TargetStatusTracker::default();
+        tracker.action_execution_end(&action("ran", ActionExecutionKind::Local))?;
+        tracker.action_execution_end(&action("hit", ActionExecutionKind::ActionCache))?;
+
+        let status = |name, status| -> buck2_error::Result<TargetStatus> {
+            Ok(tracker.target_build_end(&end(name, status))?.status)
+        };
+        assert_eq!(
+            status("ran", TargetBuildStatus::Success)?,
+            TargetStatus::Success
+        );
+        assert_eq!(
+            status("hit", TargetBuildStatus::Success)?,
+            TargetStatus::Cached
+        );
+        assert_eq!(
+            status("other", TargetBuildStatus::Failure)?,
+            TargetStatus::Failure
+        );
+        assert_eq!(
+            status("other", TargetBuildStatus::DepFailed)?,
+            TargetStatus::DepFailed
+        );
+        assert_eq!(
+            status("other", TargetBuildStatus::Skipped)?,
+            TargetStatus::Skipped
+        );
+        assert_eq!(
+            tracker
+                .target_build_end(&end("hit", TargetBuildStatus::Success))?
+                .configuration,
+            "cfg"
+        );
+        assert_eq!(
+            tracker
+                .target_build_end(&end("hit", TargetBuildStatus::Success))?
+                .outputs,
+            vec!["buck-out/v2/gen/root/pkg/hit".to_owned()]
+        );
+        Ok(())
+    }
+}
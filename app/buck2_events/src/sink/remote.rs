@@ -8,11 +8,65 @@
  */
 
 //! A Sink for forwarding events directly to Remote service.
+//!
+//! Internal builds send events to Scribe. Other builds can send them to their own telemetry
+//! backend (Kafka, HTTP, S3, ...) by registering a [`RemoteEventSinkFactory`] on startup with
+//! [`register_remote_event_sink_factory`]. Remote sinks receive the event stream of the daemon
+//! and the `InvocationRecord` of every command.
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::OnceLock;
 use std::time::Duration;
 
 use fbinit::FacebookInit;
+use futures::future::BoxFuture;
+
+use crate::BuckEvent;
+use crate::EventSink;
+use crate::EventSinkWithStats;
+
+/// A sink forwarding events to a remote service. Events sent through [`EventSink::send`] are
+/// placed on an internal message queue, and may be filtered by the implementation.
+pub trait RemoteEventSink: EventSink + EventSinkWithStats {
+    /// Send this event now, bypassing internal message queue.
+    fn send_now(&self, event: BuckEvent) -> BoxFuture<'_, ()> {
+        self.send_messages_now(vec![event])
+    }
+
+    /// Send multiple events now, bypassing internal message queue.
+    fn send_messages_now(&self, events: Vec<BuckEvent>) -> BoxFuture<'_, ()>;
+}
+
+/// Parameters of the internal message queue of a remote sink.
+#[derive(Clone, Debug)]
+pub struct RemoteEventSinkConfig {
+    pub buffer_size: usize,
+    pub retry_backoff: Duration,
+    pub retry_attempts: usize,
+    pub message_batch_size: Option<usize>,
+}
+
+/// Creates the remote sinks. Sinks are created by both the client and the daemon, so the factory
+/// should be registered in the `main` of the binary.
+pub trait RemoteEventSinkFactory: Send + Sync + 'static {
+    /// Returns `None` if events should not be sent anywhere.
+    fn create(
+        &self,
+        fb: FacebookInit,
+        config: &RemoteEventSinkConfig,
+    ) -> buck2_error::Result<Option<Arc<dyn RemoteEventSink>>>;
+}
+
+static REMOTE_EVENT_SINK_FACTORY: OnceLock<&'static dyn RemoteEventSinkFactory> = OnceLock::new();
+
+/// Create remote sinks with `factory` instead of the default Scribe sink. Must be called before
+/// any sink is created, and at most once.
+pub fn register_remote_event_sink_factory(factory: &'static dyn RemoteEventSinkFactory) {
+    if REMOTE_EVENT_SINK_FACTORY.set(factory).is_err() {
+        panic!("remote event sink factory already registered");
+    }
+}
 
 #[cfg(fbcode_build)]
 mod fbcode {
@@ -26,10 +80,12 @@ mod fbcode {
     use buck2_data::StructuredError;
     use buck2_util::truncate::truncate;
     use fbinit::FacebookInit;
+    use futures::future::BoxFuture;
     use prost::Message;
 
     use crate::metadata;
     use crate::schedule_type::ScheduleType;
+    use crate::sink::remote::RemoteEventSink;
     use crate::sink::smart_truncate_event::smart_truncate_event;
     use crate::BuckEvent;
     use crate::Event;
@@ -43,15 +99,15 @@ mod fbcode {
     // 50k characters
     static TRUNCATED_SCRIBE_MESSAGE_SIZE: usize = 50000;
 
-    /// RemoteEventSink is a ScribeSink backed by the Thrift-based client in the `buck2_scribe_client` crate.
-    pub struct RemoteEventSink {
+    /// ScribeEventSink is a RemoteEventSink backed by the Thrift-based client in the `buck2_scribe_client` crate.
+    pub struct ScribeEventSink {
         category: String,
         client: scribe_client::ScribeClient,
         schedule_type: ScheduleType,
     }
 
-    impl RemoteEventSink {
-        /// Creates a new RemoteEventSink that forwards messages onto the Thrift-backed Scribe client.
+    impl ScribeEventSink {
+        /// Creates a new ScribeEventSink that forwards messages onto the Thrift-backed Scribe client.
         pub fn new(
             fb: FacebookInit,
             category: String,
@@ -59,7 +115,7 @@ mod fbcode {
             retry_backoff: Duration,
             retry_attempts: usize,
             message_batch_size: Option<usize>,
-        ) -> buck2_error::Result<RemoteEventSink> {
+        ) -> buck2_error::Result<ScribeEventSink> {
             let client = scribe_client::ScribeClient::new(
                 fb,
                 buffer_size,
@@ -72,34 +128,13 @@ mod fbcode {
             // This would be problematic, because this is run just once on the daemon
             // But in this case we only check for 'diff' type, which shouldn't change
             let schedule_type = ScheduleType::new()?;
-            Ok(RemoteEventSink {
+            Ok(ScribeEventSink {
                 category,
                 client,
                 schedule_type,
             })
         }
 
-        // Send this event now, bypassing internal message queue.
-        pub async fn send_now(&self, event: BuckEvent) {
-            self.send_messages_now(vec![event]).await;
-        }
-
-        // Send multiple events now, bypassing internal message queue.
-        pub async fn send_messages_now(&self, events: Vec<BuckEvent>) {
-            let messages = events
-                .into_iter()
-                .filter_map(|e| {
-                    let message_key = e.trace_id().unwrap().hash();
-                    Self::encode_message(e, false).map(|bytes| scribe_client::Message {
-                        category: self.category.clone(),
-                        message: bytes,
-                        message_key: Some(message_key),
-                    })
-                })
-                .collect();
-            self.client.send_messages_now(messages).await;
-        }
-
         // Send this event by placing it on the internal message queue.
         pub fn offer(&self, event: BuckEvent) {
             let message_key = event.trace_id().unwrap().hash();
@@ -217,7 +252,24 @@ mod fbcode {
         }
     }
 
-    impl EventSink for RemoteEventSink {
+    impl RemoteEventSink for ScribeEventSink {
+        fn send_messages_now(&self, events: Vec<BuckEvent>) -> BoxFuture<'_, ()> {
+            let messages = events
+                .into_iter()
+                .filter_map(|e| {
+                    let message_key = e.trace_id().unwrap().hash();
+                    Self::encode_message(e, false).map(|bytes| scribe_client::Message {
+                        category: self.category.clone(),
+                        message: bytes,
+                        message_key: Some(message_key),
+                    })
+                })
+                .collect();
+            Box::pin(self.client.send_messages_now(messages))
+        }
+    }
+
+    impl EventSink for ScribeEventSink {
         fn send(&self, event: Event) {
             match event {
                 Event::Buck(event) => {
@@ -231,7 +283,7 @@ mod fbcode {
         }
     }
 
-    impl EventSinkWithStats for RemoteEventSink {
+    impl EventSinkWithStats for ScribeEventSink {
         fn to_event_sync(self: Arc<Self>) -> Arc<dyn EventSink> {
             self as _
         }
@@ -305,6 +357,8 @@ mod fbcode {
                     Some(Data::StructuredError(..)) => true,
                     Some(Data::PersistEventLogSubprocess(..)) => true,
                     Some(Data::CleanStaleResult(..)) => true,
+                    Some(Data::DiceInvalidationStorm(..)) => true,
+                    Some(Data::RemoteDepFileDivergence(..)) => true,
                     None => false,
                     _ => false,
                 }
@@ -333,67 +387,27 @@ mod fbcode {
     }
 }
 
-#[cfg(not(fbcode_build))]
-mod fbcode {
-    use std::sync::Arc;
-
-    use crate::BuckEvent;
-    use crate::Event;
-    use crate::EventSink;
-    use crate::EventSinkStats;
-    use crate::EventSinkWithStats;
-
-    pub enum RemoteEventSink {}
-
-    impl RemoteEventSink {
-        pub async fn send_now(&self, _event: BuckEvent) {}
-        pub async fn send_messages_now(&self, _events: Vec<BuckEvent>) {}
-    }
-
-    impl EventSink for RemoteEventSink {
-        fn send(&self, _event: Event) {}
-    }
-
-    impl EventSinkWithStats for RemoteEventSink {
-        fn to_event_sync(self: Arc<Self>) -> Arc<dyn EventSink> {
-            self as _
-        }
-
-        fn stats(&self) -> EventSinkStats {
-            match *self {}
-        }
-    }
-}
-
+#[cfg(fbcode_build)]
 pub use fbcode::*;
 
 fn new_remote_event_sink_if_fbcode(
     fb: FacebookInit,
-    buffer_size: usize,
-    retry_backoff: Duration,
-    retry_attempts: usize,
-    message_batch_size: Option<usize>,
-) -> buck2_error::Result<Option<RemoteEventSink>> {
+    config: &RemoteEventSinkConfig,
+) -> buck2_error::Result<Option<Arc<dyn RemoteEventSink>>> {
     #[cfg(fbcode_build)]
     {
-        Ok(Some(RemoteEventSink::new(
+        Ok(Some(Arc::new(ScribeEventSink::new(
             fb,
             scribe_category()?,
-            buffer_size,
-            retry_backoff,
-            retry_attempts,
-            message_batch_size,
-        )?))
+            config.buffer_size,
+            config.retry_backoff,
+            config.retry_attempts,
+            config.message_batch_size,
+        )?)))
     }
     #[cfg(not(fbcode_build))]
     {
-        let _ = (
-            fb,
-            buffer_size,
-            retry_backoff,
-            retry_attempts,
-            message_batch_size,
-        );
+        let _ = (fb, config);
         Ok(None)
     }
 }
@@ -404,17 +418,19 @@ pub fn new_remote_event_sink_if_enabled(
     retry_backoff: Duration,
     retry_attempts: usize,
     message_batch_size: Option<usize>,
-) -> buck2_error::Result<Option<RemoteEventSink>> {
-    if is_enabled() {
-        new_remote_event_sink_if_fbcode(
-            fb,
-            buffer_size,
-            retry_backoff,
-            retry_attempts,
-            message_batch_size,
-        )
-    } else {
-        Ok(None)
+) -> buck2_error::Result<Option<Arc<dyn RemoteEventSink>>> {
+    if !is_enabled() {
+        return Ok(None);
+    }
+    let config = RemoteEventSinkConfig {
+        buffer_size,
+        retry_backoff,
+        retry_attempts,
+        message_batch_size,
+    };
+    match REMOTE_EVENT_SINK_FACTORY.get() {
+        Some(factory) => factory.create(fb, &config),
+        None => new_remote_event_sink_if_fbcode(fb, &config),
     }
 }
 
@@ -263,10 +263,12 @@ mod tests {
         }];
         record.parsed_target_patterns = Some(buck2_data::ParsedTargetPatterns {
             target_patterns: resolved_target_patterns.clone(),
+            ..Default::default()
         });
         // resolved_target_patterns is expected to be unchanged.
         record_expected.parsed_target_patterns = Some(buck2_data::ParsedTargetPatterns {
             target_patterns: resolved_target_patterns,
+            ..Default::default()
         });
 
         let unresolved_target_patterns = vec![buck2_data::TargetPattern {
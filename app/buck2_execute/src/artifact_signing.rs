@@ -0,0 +1,302 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Signers configured in `[artifact_signing]`, for release pipelines that need signed binaries or
+//! images.
+//!
+//! A signer is a command run as `<command> <artifact> <signature>` from the project root, which
+//! may itself call out to a signing service. Outputs of actions owned by the targets a signer
+//! applies to are signed when the action completes. The signature is written next to the
+//! artifact, along with the digest of the artifact that was signed, so that unchanged artifacts
+//! are not signed again. When the materializer materializes an artifact which already has a
+//! signature for its digest, the signer's `verify_command` (if any) checks that signature.
+
+use buck2_common::legacy_configs::key::BuckconfigKeyRef;
+use buck2_common::legacy_configs::view::LegacyBuckConfigView;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_error::BuckErrorContext;
+use buck2_util::process::async_background_command;
+use tracing::info;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum ArtifactSigningError {
+    #[error("Signer `{0}` listed in `artifact_signing.signers` has no `command` in `[{1}]`")]
+    MissingCommand(String, String),
+    #[error("Signer `{0}` must set `rule_types` or `labels` to select the artifacts it signs")]
+    NoSelector(String),
+    #[error("Signer `{0}` failed to sign `{1}`: {2}")]
+    SignFailed(String, String, String),
+    #[error("Signature `{1}` of signer `{0}` does not verify: {2}")]
+    VerifyFailed(String, String, String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Signer {
+    name: String,
+    command: String,
+    verify_command: Option<String>,
+    rule_types: Vec<String>,
+    labels: Vec<String>,
+}
+
+/// The signers listed in `artifact_signing.signers`.
+#[derive(Debug, Default, PartialEq)]
+pub struct ArtifactSigners {
+    signers: Vec<Signer>,
+}
+
+/// A comma-separated config value, ignoring whitespace around items and empty items.
+fn parse_list(
+    config: &mut impl LegacyBuckConfigView,
+    section: &str,
+    property: &str,
+) -> buck2_error::Result<Vec<String>> {
+    Ok(config
+        .get(BuckconfigKeyRef { section, property })?
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_owned)
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+impl ArtifactSigners {
+    /// Each signer listed in `artifact_signing.signers` is configured in its own
+    /// `[artifact_signer_<name>]` section.
+    pub fn parse(mut config: impl LegacyBuckConfigView) -> buck2_error::Result<Self> {
+        let config = &mut config;
+        let mut signers = Vec::new();
+        for name in parse_list(config, "artifact_signing", "signers")? {
+            let section = format!("artifact_signer_{}", name);
+            let mut get = |property| -> buck2_error::Result<Option<String>> {
+                Ok(config
+                    .get(BuckconfigKeyRef {
+                        section: &section,
+                        property,
+                    })?
+                    .map(|value| value.to_string()))
+            };
+            let command = get("command")?.ok_or_else(|| {
+                ArtifactSigningError::MissingCommand(name.clone(), section.clone())
+            })?;
+            let verify_command = get("verify_command")?;
+            let rule_types = parse_list(config, &section, "rule_types")?;
+            let labels = parse_list(config, &section, "labels")?;
+            if rule_types.is_empty() && labels.is_empty() {
+                return Err(ArtifactSigningError::NoSelector(name).into());
+            }
+            signers.push(Signer {
+                name,
+                command,
+                verify_command,
+                rule_types,
+                labels,
+            });
+        }
+        Ok(Self { signers })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signers.is_empty()
+    }
+
+    /// The signers that apply to a target of type `rule_type` with the given `labels`.
+    pub fn matching<'a>(&'a self, rule_type: &str, labels: &[&str]) -> Vec<&'a Signer> {
+        self.signers
+            .iter()
+            .filter(|s| {
+                s.rule_types.iter().any(|r| r == rule_type)
+                    || s.labels.iter().any(|l| labels.contains(&l.as_str()))
+            })
+            .collect()
+    }
+
+    /// Checks the signatures recorded for `digest` next to `artifact`, which was just
+    /// materialized. Signatures of other digests are stale: they get replaced when the action
+    /// producing the new artifact completes.
+    pub async fn verify(
+        &self,
+        artifact: &AbsNormPath,
+        digest: &str,
+        fs: &ProjectRoot,
+    ) -> buck2_error::Result<()> {
+        for signer in &self.signers {
+            let Some(verify_command) = &signer.verify_command else {
+                continue;
+            };
+            let (signature, digest_path) = signer.signature_paths(artifact)?;
+            let signed_digest = fs_util::read_to_string_if_exists(&digest_path)?;
+            if signed_digest.as_deref() != Some(digest) || !fs_util::try_exists(&signature)? {
+                continue;
+            }
+            if let Some(stderr) = run(verify_command, artifact, &signature, fs).await? {
+                return Err(ArtifactSigningError::VerifyFailed(
+                    signer.name.clone(),
+                    signature.to_string(),
+                    stderr,
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Signer {
+    /// The signature, named `<artifact>.<signer>.sig`, and the file recording the digest it
+    /// signed.
+    fn signature_paths(
+        &self,
+        artifact: &AbsNormPath,
+    ) -> buck2_error::Result<(AbsNormPathBuf, AbsNormPathBuf)> {
+        let signature = format!("{}.{}.sig", artifact, self.name);
+        let digest = format!("{}.digest", signature);
+        Ok((
+            AbsNormPathBuf::new(signature.into())?,
+            AbsNormPathBuf::new(digest.into())?,
+        ))
+    }
+
+    /// Signs a materialized artifact, unless it already has a signature for the same digest.
+    /// Returns whether a signature was created.
+    pub async fn sign(
+        &self,
+        artifact: &AbsNormPath,
+        digest: &str,
+        fs: &ProjectRoot,
+    ) -> buck2_error::Result<bool> {
+        let (signature, digest_path) = self.signature_paths(artifact)?;
+
+        let signed_digest = fs_util::read_to_string_if_exists(&digest_path)?;
+        if signed_digest.as_deref() == Some(digest) && fs_util::try_exists(&signature)? {
+            return Ok(false);
+        }
+
+        // Never leave a digest that does not match the signature on disk.
+        if fs_util::try_exists(&digest_path)? {
+            fs_util::remove_file(&digest_path)?;
+        }
+        info!("Signing `{}` with `{}`", artifact, self.name);
+        if let Some(stderr) = run(&self.command, artifact, &signature, fs).await? {
+            return Err(ArtifactSigningError::SignFailed(
+                self.name.clone(),
+                artifact.to_string(),
+                stderr,
+            )
+            .into());
+        }
+        fs_util::write(&digest_path, digest)?;
+        Ok(true)
+    }
+}
+
+/// Runs `command` with the artifact and signature paths. Returns the error output if it failed.
+async fn run(
+    command: &str,
+    artifact: &AbsNormPath,
+    signature: &AbsNormPath,
+    fs: &ProjectRoot,
+) -> buck2_error::Result<Option<String>> {
+    let output = async_background_command(command)
+        .arg(artifact.as_path())
+        .arg(signature.as_path())
+        .current_dir(fs.root().as_path())
+        .output()
+        .await
+        .with_buck_error_context(|| format!("while running `{}`", command))?;
+    if output.status.success() {
+        Ok(None)
+    } else {
+        Ok(Some(
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::legacy_configs::configs::testing::parse;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_signers() {
+        let config = parse(
+            &[(
+                "config",
+                "[artifact_signing]\n\
+                 signers = apk, firmware\n\
+                 [artifact_signer_apk]\n\
+                 command = tools/sign_apk.sh\n\
+                 rule_types = android_binary\n\
+                 [artifact_signer_firmware]\n\
+                 command = tools/sign_fw.sh\n\
+                 verify_command = tools/verify_fw.sh\n\
+                 labels = release, firmware\n",
+            )],
+            "config",
+        )
+        .unwrap();
+        let signers = ArtifactSigners::parse(&config).unwrap();
+        assert_eq!(signers.signers.len(), 2);
+        assert_eq!(signers.signers[0].command, "tools/sign_apk.sh");
+        assert_eq!(signers.signers[0].verify_command, None);
+        assert_eq!(signers.signers[1].labels, vec!["release", "firmware"]);
+
+        let names = |signers: Vec<&Signer>| {
+            signers
+                .into_iter()
+                .map(|s| s.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(signers.matching("android_binary", &[])), vec!["apk"]);
+        assert_eq!(
+            names(signers.matching("cxx_binary", &["release"])),
+            vec!["firmware"]
+        );
+        assert!(signers.matching("cxx_binary", &["debug"]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_signers_errors() {
+        let config = parse(
+            &[(
+                "config",
+                "[artifact_signing]\n\
+                 signers = apk\n\
+                 [artifact_signer_apk]\n\
+                 rule_types = android_binary\n",
+            )],
+            "config",
+        )
+        .unwrap();
+        assert!(ArtifactSigners::parse(&config).is_err());
+
+        let config = parse(
+            &[(
+                "config",
+                "[artifact_signing]\n\
+                 signers = apk\n\
+                 [artifact_signer_apk]\n\
+                 command = sign.sh\n",
+            )],
+            "config",
+        )
+        .unwrap();
+        assert!(ArtifactSigners::parse(&config).is_err());
+    }
+}
@@ -13,11 +13,13 @@ use allocative::Allocative;
 use buck2_common::cas_digest::CasDigestConfig;
 use buck2_common::cas_digest::CasDigestConfigError;
 use buck2_common::cas_digest::DigestAlgorithm;
+use buck2_common::cas_digest::DigestAlgorithmFamily;
 use buck2_common::file_ops::FileMetadata;
 use derivative::Derivative;
 use dice::DiceData;
 use dice::DiceDataBuilder;
 use dupe::Dupe;
+use itertools::Itertools;
 use once_cell::sync::Lazy;
 use ref_cast::RefCast;
 
@@ -25,6 +27,21 @@ use crate::directory::ActionDirectoryBuilder;
 use crate::directory::ActionSharedDirectory;
 use crate::directory::ReDirectorySerializer;
 use crate::directory::INTERNER;
+use crate::execute::input_tree::InputTreeCache;
+use crate::execute::input_tree::InputTreeHasher;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+#[error(
+    "The RE backend supports digest algorithms [{}], but buck2 hashes {what} with {algorithm} \
+    (see `buck2.digest_algorithms` and `buck2.source_digest_algorithm`)",
+    .supported.iter().join(", ")
+)]
+struct UnsupportedBackendDigestAlgorithm {
+    what: &'static str,
+    algorithm: DigestAlgorithmFamily,
+    supported: Vec<DigestAlgorithmFamily>,
+}
 
 /// This configuration describes how to interpret digests received from a RE backend.
 #[derive(Copy, Clone, Dupe, Debug, Allocative, Hash, Eq, PartialEq)]
@@ -66,9 +83,50 @@ impl DigestConfig {
         ReDirectorySerializer::ref_cast(&self.inner.cas_digest_config)
     }
 
+    /// Digests the directories of action input trees, reusing the digests of the directories
+    /// built for previous actions.
+    pub fn as_input_tree_hasher(&self) -> InputTreeHasher {
+        InputTreeHasher::new(*self)
+    }
+
+    pub(crate) fn input_tree_cache(&self) -> &InputTreeCache {
+        &self.inner.input_tree_cache
+    }
+
     pub fn empty_directory(&self) -> ActionSharedDirectory {
         self.inner.empty_directory.dupe()
     }
+
+    /// Checks that a RE backend which supports the `supported` digest algorithms can store the
+    /// blobs buck2 hashes. An empty list means the backend did not say, so it is trusted.
+    pub fn check_backend_digest_algorithms(
+        &self,
+        supported: &[DigestAlgorithmFamily],
+    ) -> buck2_error::Result<()> {
+        if supported.is_empty() {
+            return Ok(());
+        }
+        let cas = self.cas_digest_config();
+        for (what, algorithm) in [
+            ("artifacts", cas.preferred_algorithm()),
+            (
+                "source files",
+                cas.source_files_config().preferred_algorithm(),
+            ),
+        ] {
+            let algorithm = algorithm.family();
+            // Keyed BLAKE3 is specific to buck2, backends can't advertise it.
+            if algorithm != DigestAlgorithmFamily::Blake3Keyed && !supported.contains(&algorithm) {
+                return Err(UnsupportedBackendDigestAlgorithm {
+                    what,
+                    algorithm,
+                    supported: supported.to_vec(),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for DigestConfig {
@@ -83,6 +141,8 @@ struct DigestConfigInner {
     cas_digest_config: CasDigestConfig,
     #[derivative(PartialEq = "ignore", Hash = "ignore")]
     empty_directory: ActionSharedDirectory,
+    #[derivative(PartialEq = "ignore", Hash = "ignore")]
+    input_tree_cache: InputTreeCache,
 }
 
 impl DigestConfigInner {
@@ -94,6 +154,7 @@ impl DigestConfigInner {
         Self {
             cas_digest_config,
             empty_directory,
+            input_tree_cache: InputTreeCache::default(),
         }
     }
 }
@@ -119,3 +180,27 @@ impl SetDigestConfig for DiceDataBuilder {
         self.set(digest_config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_backend_digest_algorithms() -> buck2_error::Result<()> {
+        let config = DigestConfig::leak_new(
+            vec![DigestAlgorithm::Sha256, DigestAlgorithm::Sha1],
+            Some(DigestAlgorithm::Sha1),
+        )?;
+        config.check_backend_digest_algorithms(&[])?;
+        config.check_backend_digest_algorithms(&[
+            DigestAlgorithmFamily::Sha1,
+            DigestAlgorithmFamily::Sha256,
+        ])?;
+        let err = config
+            .check_backend_digest_algorithms(&[DigestAlgorithmFamily::Sha256])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("hashes source files with SHA1"), "{}", err);
+        Ok(())
+    }
+}
@@ -17,6 +17,7 @@ pub mod clean_output_paths;
 pub mod command_executor;
 pub mod dep_file_digest;
 pub mod environment_inheritance;
+pub mod input_tree;
 pub mod inputs_directory;
 pub mod kind;
 pub mod manager;
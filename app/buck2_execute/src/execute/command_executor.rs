@@ -200,6 +200,7 @@ impl CommandExecutor {
                 digest_config,
                 self.0.options.output_paths_behavior,
                 request.unique_input_inodes(),
+                request.allow_network(),
                 request.remote_execution_dependencies(),
                 request.remote_execution_custom_image(),
             )?;
@@ -209,6 +210,10 @@ impl CommandExecutor {
     }
 }
 
+/// Platform property (and its value) which RE workers use to run an action without network access.
+/// This is the property understood by Bazel-compatible remote execution services.
+const RE_NO_NETWORK_PROPERTY: (&str, &str) = ("dockerNetwork", "off");
+
 fn re_create_action(
     args: Vec<String>,
     outputs: &[(ProjectRelativePathBuf, OutputType)],
@@ -222,9 +227,22 @@ fn re_create_action(
     digest_config: DigestConfig,
     output_paths_behavior: OutputPathsBehavior,
     unique_input_inodes: bool,
+    allow_network: bool,
     remote_execution_dependencies: &Vec<RemoteExecutorDependency>,
     remote_execution_custom_image: &Option<RemoteExecutorCustomImage>,
 ) -> buck2_error::Result<PreparedAction> {
+    let mut platform = platform;
+    if !allow_network {
+        let (name, value) = RE_NO_NETWORK_PROPERTY;
+        platform.properties.retain(|p| p.name != name);
+        platform.properties.push(RE::Property {
+            name: name.to_owned(),
+            value: value.to_owned(),
+        });
+        // Required by the RE spec to be sorted, like the outputs below.
+        platform.properties.sort_by(|p1, p2| p1.name.cmp(&p2.name));
+    }
+
     let mut command = RE::Command {
         arguments: args,
         platform: Some(platform),
@@ -8,6 +8,7 @@
  */
 
 use std::ffi::OsString;
+use std::sync::Arc;
 use std::sync::OnceLock;
 
 use dupe::Dupe;
@@ -57,10 +58,10 @@ const ENV_ALLOW_LIST: &[&str] = &[
     "WINDIR",
 ];
 
-#[derive(Copy, Clone, Dupe, Debug)]
+#[derive(Clone, Dupe, Debug)]
 pub struct EnvironmentInheritance {
     clear: bool,
-    values: &'static [(&'static str, OsString)],
+    values: Arc<[(String, OsString)]>,
     exclusions: &'static [&'static str],
 }
 
@@ -72,23 +73,63 @@ impl EnvironmentInheritance {
 
         // We create this *once* since getenv is actually not cheap (being O(n) of the environment
         // size).
-        static TEST_CELL: OnceLock<Vec<(&'static str, OsString)>> = OnceLock::new();
+        static TEST_CELL: OnceLock<Arc<[(String, OsString)]>> = OnceLock::new();
 
         let values = TEST_CELL.get_or_init(|| {
             let mut ret = Vec::new();
             for list in allowlists.iter() {
                 for key in list.iter() {
                     if let Some(value) = std::env::var_os(key) {
-                        ret.push((*key, value));
+                        ret.push(((*key).to_owned(), value));
                     }
                 }
             }
-            ret
+            ret.into()
         });
 
         Self {
             clear: true,
-            values,
+            values: values.dupe(),
+            exclusions: &[],
+        }
+    }
+
+    /// The test allowlist, extended with `allow` and with `deny` removed. `overrides` are set
+    /// regardless of the daemon environment (and of `deny`), e.g. to point `HOME` or `TMPDIR` to
+    /// a directory private to the test.
+    pub fn test_allowlist_with(
+        allow: &[String],
+        deny: &[String],
+        overrides: Vec<(String, OsString)>,
+    ) -> Self {
+        let base = Self::test_allowlist();
+        if allow.is_empty() && deny.is_empty() && overrides.is_empty() {
+            return base;
+        }
+
+        let is_overridden = |key: &str| overrides.iter().any(|(k, _)| k == key);
+        let mut values: Vec<(String, OsString)> = base
+            .values
+            .iter()
+            .filter(|(k, _)| !deny.contains(k) && !is_overridden(k.as_str()))
+            .cloned()
+            .collect();
+        for key in allow {
+            if deny.contains(key)
+                || is_overridden(key.as_str())
+                || values.iter().any(|(k, _)| k == key)
+            {
+                continue;
+            }
+            if let Some(value) = std::env::var_os(key) {
+                values.push((key.clone(), value));
+            }
+        }
+        values.extend(overrides);
+
+        Self {
+            clear: true,
+            values: values.into(),
             exclusions: &[],
         }
     }
@@ -98,7 +139,7 @@ impl EnvironmentInheritance {
     pub fn local_command_exclusions() -> Self {
         Self {
             clear: false,
-            values: &[],
+            values: Arc::new([]),
             exclusions: &[
                 "PYTHONPATH",
                 "PYTHONHOME",
@@ -111,14 +152,14 @@ impl EnvironmentInheritance {
 
     pub fn empty() -> Self {
         Self {
-            values: &[],
+            values: Arc::new([]),
             exclusions: &[],
             clear: true,
         }
     }
 
-    pub fn values(&self) -> impl Iterator<Item = (&'static str, &'static OsString)> {
-        self.values.iter().map(|(k, v)| (*k, v))
+    pub fn values(&self) -> impl Iterator<Item = (&str, &OsString)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), v))
     }
 
     pub fn exclusions(&self) -> impl Iterator<Item = &'static str> {
@@ -129,3 +170,34 @@ impl EnvironmentInheritance {
         self.clear
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+
+    use super::EnvironmentInheritance;
+
+    #[test]
+    fn test_allowlist_with() {
+        let inheritance = EnvironmentInheritance::test_allowlist_with(
+            &["BUCK2_TEST_ENV_INHERITANCE_UNSET".to_owned()],
+            &["PATH".to_owned()],
+            vec![("HOME".to_owned(), OsString::from("/sandbox/home"))],
+        );
+        assert!(inheritance.clear());
+        let values = inheritance.values().collect::<Vec<_>>();
+        assert!(values.iter().all(|(k, _)| *k != "PATH"));
+        assert!(
+            values
+                .iter()
+                .all(|(k, _)| *k != "BUCK2_TEST_ENV_INHERITANCE_UNSET")
+        );
+        assert_eq!(
+            values
+                .iter()
+                .filter(|(k, _)| *k == "HOME")
+                .collect::<Vec<_>>(),
+            vec![&("HOME", &OsString::from("/sandbox/home"))]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Digests of the directories of action input trees, cached between actions.
+//!
+//! The artifacts an action depends on are already fingerprinted, but the directories containing
+//! them (e.g. `buck-out/v2/gen/<cell>/<cfg>/<package>`) are built again for every action, and
+//! digesting each of them means serializing it as a RE directory and hashing it. Actions of the
+//! same or of neighbouring targets have most of these directories in common, so their digests are
+//! cached, keyed by the names and digests of their entries, which are all the serialized directory
+//! depends on.
+//!
+//! Sharing the `TrackedFileDigest` also shares its expiry, so that directories already known to be
+//! in the CAS are not queried again when uploading the inputs of the next action.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use allocative::Allocative;
+use buck2_common::file_ops::TrackedFileDigest;
+use buck2_core::fs::paths::file_name::FileName;
+use buck2_core::fs::paths::file_name::FileNameBuf;
+use buck2_directory::directory::directory_hasher::DirectoryHasher;
+use buck2_directory::directory::entry::DirectoryEntry;
+use dupe::Dupe;
+
+use crate::digest_config::DigestConfig;
+use crate::directory::ActionDirectoryMember;
+use crate::directory::ActionFingerprintedDirectoryRef;
+use crate::directory::ReDirectorySerializer;
+
+/// Directories cached before the cache is cleared. Directories are small and their digests are
+/// shared with the input trees using them, so this is only a bound on the memory used by the
+/// directories no action uses anymore.
+const MAX_CACHED_DIRECTORIES: usize = 100_000;
+
+/// An entry of a directory, as serialized in a RE directory.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Allocative)]
+enum EntryKey {
+    Dir(TrackedFileDigest),
+    File {
+        digest: TrackedFileDigest,
+        is_executable: bool,
+    },
+    Symlink(String),
+}
+
+#[derive(Debug, Default, Allocative)]
+pub struct InputTreeCache {
+    digests: Mutex<HashMap<Vec<(FileNameBuf, EntryKey)>, TrackedFileDigest>>,
+}
+
+impl InputTreeCache {
+    fn get_or_insert(
+        &self,
+        key: Vec<(FileNameBuf, EntryKey)>,
+        digest: impl FnOnce() -> TrackedFileDigest,
+    ) -> TrackedFileDigest {
+        if let Some(digest) = self.digests.lock().unwrap().get(&key) {
+            return digest.dupe();
+        }
+        // Digest without holding the lock: actions build their input trees concurrently.
+        let digest = digest();
+        let mut digests = self.digests.lock().unwrap();
+        if digests.len() >= MAX_CACHED_DIRECTORIES {
+            digests.clear();
+        }
+        digests.entry(key).or_insert(digest).dupe()
+    }
+
+    pub fn len(&self) -> usize {
+        self.digests.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Digests directories like [`ReDirectorySerializer`], using the [`InputTreeCache`] of the
+/// digest config.
+pub struct InputTreeHasher {
+    digest_config: DigestConfig,
+}
+
+impl InputTreeHasher {
+    pub fn new(digest_config: DigestConfig) -> Self {
+        Self { digest_config }
+    }
+}
+
+impl DirectoryHasher<ActionDirectoryMember, TrackedFileDigest> for InputTreeHasher {
+    fn hash_entries<'a, D, I>(&self, entries: I) -> TrackedFileDigest
+    where
+        I: IntoIterator<Item = (&'a FileName, DirectoryEntry<D, &'a ActionDirectoryMember>)>,
+        D: ActionFingerprintedDirectoryRef<'a>,
+    {
+        let entries: Vec<_> = entries.into_iter().collect();
+        let key = entries
+            .iter()
+            .map(|(name, entry)| {
+                let entry = match entry {
+                    DirectoryEntry::Dir(d) => {
+                        EntryKey::Dir(d.as_fingerprinted_dyn().fingerprint().dupe())
+                    }
+                    DirectoryEntry::Leaf(ActionDirectoryMember::File(f)) => EntryKey::File {
+                        digest: f.digest.dupe(),
+                        is_executable: f.is_executable,
+                    },
+                    DirectoryEntry::Leaf(ActionDirectoryMember::Symlink(s)) => {
+                        EntryKey::Symlink(s.to_string())
+                    }
+                    DirectoryEntry::Leaf(ActionDirectoryMember::ExternalSymlink(s)) => {
+                        EntryKey::Symlink(s.target_str().to_owned())
+                    }
+                };
+                ((*name).to_owned(), entry)
+            })
+            .collect();
+        self.digest_config
+            .input_tree_cache()
+            .get_or_insert(key, || {
+                self.digest_config
+                    .as_directory_serializer()
+                    .hash_entries(entries)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::file_ops::FileMetadata;
+    use buck2_core::fs::project_rel_path::ProjectRelativePath;
+    use buck2_directory::directory::fingerprinted_directory::FingerprintedDirectory;
+
+    use crate::digest_config::DigestConfig;
+    use crate::directory::insert_file;
+    use crate::directory::ActionDirectoryBuilder;
+    use crate::execute::input_tree::InputTreeHasher;
+
+    fn build(files: &[&str]) -> ActionDirectoryBuilder {
+        let digest_config = DigestConfig::testing_default();
+        let mut builder = ActionDirectoryBuilder::empty();
+        for file in files {
+            insert_file(
+                &mut builder,
+                ProjectRelativePath::unchecked_new(file),
+                FileMetadata::empty(digest_config.cas_digest_config()),
+            )
+            .unwrap();
+        }
+        builder
+    }
+
+    #[test]
+    fn test_input_tree_hasher_matches_serializer() {
+        let digest_config = DigestConfig::testing_default();
+        let files = ["gen/foo/a", "gen/foo/b", "gen/bar/c", "src/d"];
+
+        let expected = build(&files).fingerprint(digest_config.as_directory_serializer());
+        let hasher = InputTreeHasher::new(digest_config);
+        let first = build(&files).fingerprint(&hasher);
+        let second = build(&files).fingerprint(&hasher);
+        assert_eq!(expected.fingerprint(), first.fingerprint());
+        assert_eq!(expected.fingerprint(), second.fingerprint());
+        assert!(!digest_config.input_tree_cache().is_empty());
+
+        // A different tree sharing subdirectories gets its own digest.
+        let other = build(&["gen/foo/a", "gen/foo/b", "src/e"]);
+        let other_expected = build(&["gen/foo/a", "gen/foo/b", "src/e"])
+            .fingerprint(digest_config.as_directory_serializer());
+        assert_eq!(
+            other_expected.fingerprint(),
+            other.fingerprint(&hasher).fingerprint()
+        );
+        assert_ne!(expected.fingerprint(), other_expected.fingerprint());
+    }
+}
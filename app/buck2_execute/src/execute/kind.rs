@@ -20,6 +20,7 @@ use sorted_vector_map::SortedVectorMap;
 
 use crate::execute::action_digest::ActionDigest;
 use crate::execute::dep_file_digest::DepFileDigest;
+use crate::re::action_url::ReActionUrlTemplate;
 use crate::re::convert::platform_to_proto;
 
 #[derive(Debug, Display, Clone, Allocative)]
@@ -126,6 +127,7 @@ impl CommandExecutionKind {
                     .as_ref()
                     .map(|paths| paths.clone().map(|p| format!("{}", p)))
                     .unwrap_or_default(),
+                action_url: details.action_url.clone(),
             }),
 
             Self::ActionCache { details } => Command::RemoteCommand(buck2_data::RemoteCommand {
@@ -136,6 +138,7 @@ impl CommandExecutionKind {
                 details: details.to_proto(omit_details),
                 remote_dep_file_key: None,
                 materialized_inputs_for_failed: Vec::new(),
+                action_url: details.action_url.clone(),
             }),
 
             Self::RemoteDepFileCache { details } => {
@@ -150,6 +153,7 @@ impl CommandExecutionKind {
                         .as_ref()
                         .map(|k| k.to_string()),
                     materialized_inputs_for_failed: Vec::new(),
+                    action_url: details.action_url.clone(),
                 })
             }
 
@@ -197,6 +201,8 @@ pub struct RemoteCommandExecutionDetails {
     pub session_id: Option<String>,
     pub use_case: RemoteExecutorUseCase,
     pub platform: RePlatform,
+    /// Link to the page of this action on the RE backend.
+    pub action_url: Option<String>,
 }
 
 impl RemoteCommandExecutionDetails {
@@ -213,9 +219,16 @@ impl RemoteCommandExecutionDetails {
             session_id,
             use_case,
             platform: platform_to_proto(platform),
+            action_url: None,
         }
     }
 
+    pub fn with_action_url(mut self, template: Option<&ReActionUrlTemplate>) -> Self {
+        self.action_url =
+            template.map(|t| t.render(&self.action_digest, self.session_id.as_deref()));
+        self
+    }
+
     fn to_proto(&self, omit_details: bool) -> Option<buck2_data::RemoteCommandDetails> {
         if omit_details {
             return None;
@@ -30,6 +30,7 @@ use buck2_error::buck2_error;
 use derive_more::Display;
 use dupe::Dupe;
 use gazebo::variants::UnpackVariants;
+use host_sharing::host_sharing::HostResourceRequirements;
 use host_sharing::host_sharing::HostSharingRequirements;
 use indexmap::IndexSet;
 use itertools::Itertools;
@@ -45,6 +46,7 @@ use crate::directory::insert_entry;
 use crate::directory::ActionDirectoryMember;
 use crate::directory::ActionImmutableDirectory;
 use crate::execute::environment_inheritance::EnvironmentInheritance;
+use crate::execute::executor_stage;
 use crate::execute::inputs_directory::inputs_directory;
 use crate::execute::paths_with_digest::PathsWithDigestBlobData;
 
@@ -240,7 +242,9 @@ impl CommandExecutionPaths {
             ))),
         )?;
 
-        let input_directory = builder.fingerprint(digest_config.as_directory_serializer());
+        let input_directory = executor_stage(buck2_data::BuildInputTree {}, || {
+            builder.fingerprint(&digest_config.as_input_tree_hasher())
+        });
 
         let mut input_files_bytes = 0;
 
@@ -296,6 +300,8 @@ pub struct CommandExecutionRequest {
     timeout: Option<Duration>,
     pub executor_preference: ExecutorPreference,
     host_sharing_requirements: Arc<HostSharingRequirements>,
+    /// Memory and devices the command reserves when it runs locally.
+    host_resource_requirements: Arc<HostResourceRequirements>,
     // Used to disable the low pass filter for concurrent local actions. Enabled by default
     low_pass_filter: bool,
     /// Working directory, relative to the project root.
@@ -319,6 +325,15 @@ pub struct CommandExecutionRequest {
     /// Whether the executor should guarantee that the inodes for all inputs are unique (i.e. avoid
     /// hardlinking identical input files, for example)
     unique_input_inodes: bool,
+    /// Whether the command may access the network. When false, local execution runs the command
+    /// network-isolated and remote execution disables networking via a platform property.
+    allow_network: bool,
+    /// Whether the output of the command is sent to the console while it runs, when it runs
+    /// locally.
+    stream_output: bool,
+    /// Whether the command is predicted to be on the critical path, and should run before the
+    /// commands which are not.
+    prioritized: bool,
     /// Remote dep file key, if the action has a dep file.
     /// If this key is set and remote dep file caching is enabled, it will be used to query the cache.
     pub remote_dep_file_key: Option<DepFileDigest>,
@@ -343,6 +358,7 @@ impl CommandExecutionRequest {
             timeout: None,
             executor_preference: ExecutorPreference::Default,
             host_sharing_requirements: Arc::new(HostSharingRequirements::default()),
+            host_resource_requirements: Arc::new(HostResourceRequirements::default()),
             low_pass_filter: true,
             working_directory: ProjectRelativePathBuf::default(),
             prefetch_lossy_stderr: false,
@@ -353,6 +369,9 @@ impl CommandExecutionRequest {
             required_local_resources: SortedSet::new(),
             worker: None,
             unique_input_inodes: false,
+            allow_network: true,
+            stream_output: false,
+            prioritized: false,
             remote_dep_file_key: None,
             remote_execution_dependencies: Vec::new(),
             remote_execution_custom_image: None,
@@ -381,6 +400,14 @@ impl CommandExecutionRequest {
         self
     }
 
+    pub fn with_host_resource_requirements(
+        mut self,
+        host_resource_requirements: Arc<HostResourceRequirements>,
+    ) -> Self {
+        self.host_resource_requirements = host_resource_requirements;
+        self
+    }
+
     pub fn with_low_pass_filter(mut self, low_pass_filter: bool) -> Self {
         self.low_pass_filter = low_pass_filter;
         self
@@ -471,6 +498,10 @@ impl CommandExecutionRequest {
         &self.host_sharing_requirements
     }
 
+    pub fn host_resource_requirements(&self) -> &HostResourceRequirements {
+        &self.host_resource_requirements
+    }
+
     pub fn low_pass_filter(&self) -> bool {
         self.low_pass_filter
     }
@@ -537,6 +568,33 @@ impl CommandExecutionRequest {
         self.unique_input_inodes
     }
 
+    pub fn with_allow_network(mut self, allow_network: bool) -> Self {
+        self.allow_network = allow_network;
+        self
+    }
+
+    pub fn allow_network(&self) -> bool {
+        self.allow_network
+    }
+
+    pub fn with_stream_output(mut self, stream_output: bool) -> Self {
+        self.stream_output = stream_output;
+        self
+    }
+
+    pub fn stream_output(&self) -> bool {
+        self.stream_output
+    }
+
+    pub fn with_prioritized(mut self, prioritized: bool) -> Self {
+        self.prioritized = prioritized;
+        self
+    }
+
+    pub fn prioritized(&self) -> bool {
+        self.prioritized
+    }
+
     pub fn with_remote_execution_dependencies(
         mut self,
         remote_execution_dependencies: Vec<RemoteExecutorDependency>,
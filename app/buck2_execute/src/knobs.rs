@@ -7,6 +7,8 @@
  * of this source tree.
  */
 
+use std::sync::Arc;
+
 use dupe::Dupe;
 
 /// Command-level config that can tweak how the executors work.
@@ -17,4 +19,21 @@ pub struct ExecutorGlobalKnobs {
     /// Whether to emit action keys to execution logs (thos are pretty verbose and omitted by
     /// default).
     pub log_action_keys: bool,
+
+    /// Trace id of the system which invoked buck2 (from `BUCK2_PARENT_TRACE_ID`), forwarded to
+    /// locally executed actions.
+    pub parent_trace_id: Option<Arc<str>>,
+
+    /// Command prefix used to run local actions without network access (from
+    /// `buck2.network_isolation_command`). Actions which may not access the network fail to run
+    /// locally if this is unset.
+    pub network_isolation_command: Option<Arc<[String]>>,
+
+    /// Maximum number of output lines per second sent to the console by an action run with
+    /// `stream_output` (from `buck2.stream_output_max_lines_per_second`). `None` means no limit.
+    pub stream_output_max_lines_per_second: Option<u32>,
+
+    /// How long the output lines of an action run with `stream_output` are batched before being
+    /// sent to the console (from `buck2.stream_output_flush_interval_ms`).
+    pub stream_output_flush_interval_ms: u64,
 }
@@ -16,6 +16,7 @@
 #![feature(trait_upcasting)]
 
 pub mod artifact;
+pub mod artifact_signing;
 pub mod artifact_utils;
 pub mod artifact_value;
 pub mod bxl;
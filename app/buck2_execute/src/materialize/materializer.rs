@@ -101,6 +101,32 @@ pub struct CasNotFoundError {
     pub error: Arc<buck2_error::Error>,
 }
 
+/// Typed context of the errors of commands whose inputs could not be materialized because they
+/// were missing from the CAS, to find the action producing the missing input.
+#[derive(Allocative, Debug, PartialEq, Eq)]
+pub struct CasArtifactMissing {
+    pub path: ProjectRelativePathBuf,
+}
+
+impl fmt::Display for CasArtifactMissing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Artifact `{}` is missing from the CAS", self.path)
+    }
+}
+
+impl buck2_error::TypedContext for CasArtifactMissing {
+    fn eq(&self, other: &dyn buck2_error::TypedContext) -> bool {
+        match (other as &dyn std::any::Any).downcast_ref::<Self>() {
+            Some(right) => self == right,
+            None => false,
+        }
+    }
+
+    fn should_display(&self) -> bool {
+        false
+    }
+}
+
 #[derive(buck2_error::Error, Debug)]
 #[buck2(tag = MaterializationError)]
 pub enum MaterializationError {
@@ -8,7 +8,10 @@
  */
 
 pub mod action_identity;
+pub mod action_url;
+mod bandwidth;
 pub mod client;
+pub mod conformance;
 pub mod convert;
 pub mod error;
 pub mod manager;
@@ -0,0 +1,101 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use std::str::FromStr;
+
+use allocative::Allocative;
+use dupe::Dupe;
+
+use crate::execute::action_digest::ActionDigest;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum ReActionUrlTemplateError {
+    #[error("Unknown placeholder `{{{0}}}` in RE action URL template `{1}`")]
+    UnknownPlaceholder(String, String),
+    #[error("Unterminated placeholder in RE action URL template `{0}`")]
+    Unterminated(String),
+}
+
+/// Template for a link to the page of an action on the RE backend, configured via
+/// `[buck2_re_client] action_url_template`.
+///
+/// Supported placeholders:
+/// * `{digest}`: the action digest, as `hash:size`.
+/// * `{hash}`: the hash of the action digest.
+/// * `{size}`: the size of the action digest.
+/// * `{session_id}`: the RE session id, or empty if unknown.
+#[derive(Debug, Clone, Dupe, Allocative, PartialEq, Eq)]
+pub struct ReActionUrlTemplate(std::sync::Arc<str>);
+
+const PLACEHOLDERS: &[&str] = &["digest", "hash", "size", "session_id"];
+
+impl FromStr for ReActionUrlTemplate {
+    type Err = buck2_error::Error;
+
+    fn from_str(template: &str) -> buck2_error::Result<Self> {
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let after = &rest[start + 1..];
+            let end = after
+                .find('}')
+                .ok_or_else(|| ReActionUrlTemplateError::Unterminated(template.to_owned()))?;
+            let name = &after[..end];
+            if !PLACEHOLDERS.contains(&name) {
+                return Err(ReActionUrlTemplateError::UnknownPlaceholder(
+                    name.to_owned(),
+                    template.to_owned(),
+                )
+                .into());
+            }
+            rest = &after[end + 1..];
+        }
+        Ok(ReActionUrlTemplate(template.into()))
+    }
+}
+
+impl ReActionUrlTemplate {
+    pub fn render(&self, digest: &ActionDigest, session_id: Option<&str>) -> String {
+        self.0
+            .replace("{digest}", &digest.to_string())
+            .replace("{hash}", &digest.raw_digest().to_string())
+            .replace("{size}", &digest.size().to_string())
+            .replace("{session_id}", session_id.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let template: ReActionUrlTemplate =
+            "https://re.example.com/action/{hash}/{size}?s={session_id}"
+                .parse()
+                .unwrap();
+        let digest = ActionDigest::new_sha1([1; 20], 3);
+        let hash = "01".repeat(20);
+        assert_eq!(
+            format!("https://re.example.com/action/{}/3?s=abc", hash),
+            template.render(&digest, Some("abc"))
+        );
+        assert_eq!(
+            format!("https://re.example.com/action/{}/3?s=", hash),
+            template.render(&digest, None)
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!("https://x/{digets}".parse::<ReActionUrlTemplate>().is_err());
+        assert!("https://x/{digest".parse::<ReActionUrlTemplate>().is_err());
+        assert!("https://x/{digest}".parse::<ReActionUrlTemplate>().is_ok());
+    }
+}
@@ -0,0 +1,137 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Token buckets enforcing `buck2_re_client.max_download_bytes_per_second` and
+//! `buck2_re_client.max_upload_bytes_per_second`.
+
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+
+struct TokenBucket {
+    bytes_per_second: u64,
+    /// Bytes that may be transferred right away. Negative when transfers were admitted on credit
+    /// and later ones have to wait for the bucket to refill.
+    available: i128,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    /// Takes `bytes` out of the bucket and returns how long the caller must wait before
+    /// transferring them. The bucket holds at most one second worth of traffic, and transfers
+    /// larger than that are admitted on credit rather than never.
+    fn reserve(&mut self, bytes: u64, now: Instant) -> Duration {
+        let rate = self.bytes_per_second as i128;
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_nanos() as i128;
+        self.available = (self.available + elapsed * rate / NANOS_PER_SECOND).min(rate);
+        self.refilled_at = now;
+        self.available -= bytes as i128;
+        if self.available >= 0 {
+            Duration::ZERO
+        } else {
+            let nanos = -self.available * NANOS_PER_SECOND / rate;
+            Duration::from_nanos(nanos.try_into().unwrap_or(u64::MAX))
+        }
+    }
+}
+
+/// Limits the rate of transfers in one direction. Transfers are accounted for by the size of
+/// the blobs they request, before they start, since that's all the RE client API exposes.
+pub struct BandwidthLimiter {
+    /// `None` when there is no cap.
+    bucket: Option<Mutex<TokenBucket>>,
+    waiting: AtomicU32,
+}
+
+impl BandwidthLimiter {
+    /// A cap of zero is treated like no cap at all.
+    pub fn new(bytes_per_second: Option<u64>) -> Self {
+        Self {
+            bucket: bytes_per_second.filter(|b| *b > 0).map(|bytes_per_second| {
+                Mutex::new(TokenBucket {
+                    bytes_per_second,
+                    available: bytes_per_second as i128,
+                    refilled_at: Instant::now(),
+                })
+            }),
+            waiting: AtomicU32::new(0),
+        }
+    }
+
+    pub fn bytes_per_second(&self) -> Option<u64> {
+        self.bucket
+            .as_ref()
+            .map(|b| b.lock().unwrap().bytes_per_second)
+    }
+
+    /// Number of transfers currently held back by the cap.
+    pub fn waiting(&self) -> u32 {
+        self.waiting.load(Ordering::Relaxed)
+    }
+
+    /// Waits until `bytes` may be transferred.
+    pub async fn acquire(&self, bytes: u64) {
+        let Some(bucket) = &self.bucket else {
+            return;
+        };
+        let wait = bucket.lock().unwrap().reserve(bytes, Instant::now());
+        if wait.is_zero() {
+            return;
+        }
+
+        struct WaitingGuard<'a>(&'a AtomicU32);
+
+        impl Drop for WaitingGuard<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+
+        self.waiting.fetch_add(1, Ordering::Relaxed);
+        let _guard = WaitingGuard(&self.waiting);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserve() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket {
+            bytes_per_second: 1000,
+            available: 1000,
+            refilled_at: start,
+        };
+        // The initial burst is free.
+        assert_eq!(bucket.reserve(600, start), Duration::ZERO);
+        // Then transfers wait for the missing tokens.
+        assert_eq!(bucket.reserve(600, start), Duration::from_millis(200));
+        // Later transfers queue up behind the ones admitted on credit.
+        assert_eq!(bucket.reserve(100, start), Duration::from_millis(300));
+        // The bucket refills at the configured rate, up to one second worth of traffic.
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(1000, later), Duration::ZERO);
+        assert_eq!(bucket.reserve(1, later), Duration::from_millis(1));
+    }
+
+    #[tokio::test]
+    async fn test_no_cap() {
+        let limiter = BandwidthLimiter::new(Some(0));
+        assert_eq!(limiter.bytes_per_second(), None);
+        limiter.acquire(u64::MAX).await;
+        assert_eq!(limiter.waiting(), 0);
+    }
+}
@@ -7,11 +7,14 @@
  * of this source tree.
  */
 
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
 
 use allocative::Allocative;
 use anyhow::Context;
+use buck2_common::cas_digest::DigestAlgorithmFamily;
 use buck2_core::buck2_env;
 use buck2_core::execution_types::executor_config::RemoteExecutorDependency;
 use buck2_core::execution_types::executor_config::RemoteExecutorUseCase;
@@ -70,12 +73,14 @@ use crate::execute::manager::CommandExecutionManager;
 use crate::knobs::ExecutorGlobalKnobs;
 use crate::materialize::materializer::Materializer;
 use crate::re::action_identity::ReActionIdentity;
+use crate::re::bandwidth::BandwidthLimiter;
 use crate::re::convert::platform_to_proto;
 use crate::re::error::test_re_error;
 use crate::re::error::with_error_handler;
 use crate::re::error::RemoteExecutionError;
 use crate::re::manager::RemoteExecutionConfig;
 use crate::re::metadata::RemoteExecutionMetadataExt;
+use crate::re::stats::BandwidthThrottleStats;
 use crate::re::stats::LocalCacheRemoteExecutionClientStats;
 use crate::re::stats::LocalCacheStats;
 use crate::re::stats::OpStats;
@@ -250,11 +255,15 @@ impl RemoteExecutionClient {
         &self,
         files: Vec<NamedDigestWithPermissions>,
         use_case: RemoteExecutorUseCase,
+        downloaded: Option<&AtomicU64>,
     ) -> buck2_error::Result<()> {
         let stat = self
             .data
             .materializes
-            .op(self.data.client.materialize_files(files, use_case))
+            .op(self
+                .data
+                .client
+                .materialize_files(files, use_case, downloaded))
             .await?;
         self.data.local_cache.update(&stat);
         Ok(())
@@ -363,6 +372,18 @@ impl RemoteExecutionClient {
         stats.get_digest_expirations =
             RemoteExecutionClientOpStats::from(&self.data.get_digest_expirations);
         stats.local_cache = LocalCacheRemoteExecutionClientStats::from(&self.data.local_cache);
+        stats.download_throttle =
+            BandwidthThrottleStats::from(&self.data.client.download_bandwidth);
+        stats.upload_throttle = BandwidthThrottleStats::from(&self.data.client.upload_bandwidth);
+        #[cfg(not(fbcode_build))]
+        {
+            let rpcs = self.data.client.client().get_cas_rpc_stats();
+            stats.cas_rpcs = crate::re::stats::CasRpcStats {
+                find_missing_blobs: rpcs.find_missing_blobs,
+                batch_update_blobs: rpcs.batch_update_blobs,
+                bytestream_writes: rpcs.bytestream_writes,
+            };
+        }
     }
 }
 
@@ -382,6 +403,10 @@ struct RemoteExecutionClientImpl {
     download_chunk_size: usize,
     /// Preserve file symlinks as symlinks when uploading action result.
     respect_file_symlinks: bool,
+    #[allocative(skip)]
+    download_bandwidth: BandwidthLimiter,
+    #[allocative(skip)]
+    upload_bandwidth: BandwidthLimiter,
 }
 
 fn re_platform(x: &RE::Platform) -> remote_execution::TPlatform {
@@ -726,6 +751,14 @@ impl RemoteExecutionClientImpl {
                 .await?
             };
 
+            let backend_digest_algorithms = match &re_config.backend_digest_algorithms {
+                Some(algorithms) => algorithms.clone(),
+                None => reported_digest_algorithms(&client),
+            };
+            re_config
+                .digest_config
+                .check_backend_digest_algorithms(&backend_digest_algorithms)?;
+
             let respect_file_symlinks = {
                 #[cfg(fbcode_build)]
                 {
@@ -744,6 +777,12 @@ impl RemoteExecutionClientImpl {
                 download_files_semapore: Arc::new(Semaphore::new(download_concurrency)),
                 download_chunk_size,
                 respect_file_symlinks,
+                download_bandwidth: BandwidthLimiter::new(
+                    re_config.bandwidth_caps.max_download_bytes_per_second,
+                ),
+                upload_bandwidth: BandwidthLimiter::new(
+                    re_config.bandwidth_caps.max_upload_bytes_per_second,
+                ),
             }
         };
 
@@ -820,6 +859,7 @@ impl RemoteExecutionClientImpl {
                 use_case,
                 identity,
                 digest_config,
+                &self.upload_bandwidth,
             )
             .await,
         )
@@ -833,6 +873,18 @@ impl RemoteExecutionClientImpl {
         inlined_blobs_with_digest: Vec<InlinedBlobWithDigest>,
         use_case: RemoteExecutorUseCase,
     ) -> buck2_error::Result<()> {
+        // This counts blobs the CAS already has too, since it only skips them during the upload.
+        let bytes = files_with_digest
+            .iter()
+            .map(|f| digest_size(&f.digest))
+            .chain(
+                inlined_blobs_with_digest
+                    .iter()
+                    .map(|b| b.blob.len() as u64),
+            )
+            .sum();
+        self.upload_bandwidth.acquire(bytes).await;
+
         with_error_handler(
             "upload_files_and_directories",
             self.get_session_id(),
@@ -1134,6 +1186,9 @@ impl RemoteExecutionClientImpl {
             return Ok((Vec::new(), TLocalCacheStats::default()));
         }
         let expected_blobs = digests.len();
+        self.download_bandwidth
+            .acquire(digests.iter().map(digest_size).sum())
+            .await;
         let response = with_error_handler(
             "download_typed_blobs",
             self.get_session_id(),
@@ -1178,6 +1233,7 @@ impl RemoteExecutionClientImpl {
         use_case: RemoteExecutorUseCase,
     ) -> buck2_error::Result<(Vec<u8>, TLocalCacheStats)> {
         let re_action = format!("download_blob for digest {}", digest);
+        self.download_bandwidth.acquire(digest_size(digest)).await;
         let response = with_error_handler(
             re_action.as_str(),
             self.get_session_id(),
@@ -1210,6 +1266,7 @@ impl RemoteExecutionClientImpl {
         blob: Vec<u8>,
         use_case: RemoteExecutorUseCase,
     ) -> buck2_error::Result<TDigest> {
+        self.upload_bandwidth.acquire(blob.len() as u64).await;
         with_error_handler(
             "upload_blob",
             self.get_session_id(),
@@ -1224,6 +1281,7 @@ impl RemoteExecutionClientImpl {
         &self,
         files: Vec<NamedDigestWithPermissions>,
         use_case: RemoteExecutorUseCase,
+        downloaded: Option<&AtomicU64>,
     ) -> buck2_error::Result<TLocalCacheStats> {
         if buck2_env!(
             "BUCK2_TEST_FAIL_RE_DOWNLOADS",
@@ -1247,6 +1305,14 @@ impl RemoteExecutionClientImpl {
                 .await
                 .buck_error_context("Failed to acquire download_files_semapore")?;
 
+            let chunk_bytes: u64 = chunk
+                .iter()
+                .map(|f| digest_size(&f.named_digest.digest))
+                .sum();
+            // Files found in the local cache are counted too, we only learn about them from the
+            // response.
+            self.download_bandwidth.acquire(chunk_bytes).await;
+
             let response = with_error_handler(
                 "materialize_files",
                 self.get_session_id(),
@@ -1263,6 +1329,9 @@ impl RemoteExecutionClientImpl {
             )
             .await?;
 
+            if let Some(downloaded) = downloaded {
+                downloaded.fetch_add(chunk_bytes, Ordering::Relaxed);
+            }
             buck2_error::Ok(response.local_cache_stats)
         });
 
@@ -1383,6 +1452,27 @@ impl Drop for RemoteExecutionClientImpl {
     }
 }
 
+/// The digest algorithms the backend reported in its capabilities, if it did.
+fn reported_digest_algorithms(client: &REClient) -> Vec<DigestAlgorithmFamily> {
+    #[cfg(fbcode_build)]
+    {
+        let _unused = client;
+        Vec::new()
+    }
+    #[cfg(not(fbcode_build))]
+    {
+        client
+            .get_digest_functions()
+            .iter()
+            .filter_map(|f| f.parse().ok())
+            .collect()
+    }
+}
+
+fn digest_size(digest: &TDigest) -> u64 {
+    digest.size_in_bytes.try_into().unwrap_or_default()
+}
+
 fn chunks<T>(v: Vec<T>, chunk_size: usize) -> impl Iterator<Item = Vec<T>> {
     if !v.is_empty() && v.len() <= chunk_size {
         return Either::Left(std::iter::once(v));
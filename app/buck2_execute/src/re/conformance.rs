@@ -0,0 +1,478 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Checks that the RE backend the daemon is configured with behaves the way buck2 relies on,
+//! for `buck2 audit re-conformance`. Every check writes blobs or action results that no build
+//! will ever look up, so running them against a production backend is harmless.
+
+use buck2_common::file_ops::FileDigest;
+use buck2_core::execution_types::executor_config::RemoteExecutorUseCase;
+use buck2_error::BuckErrorContext;
+use chrono::Utc;
+use prost::Message;
+use remote_execution as RE;
+use remote_execution::DigestWithStatus;
+use remote_execution::TActionResult2;
+use remote_execution::TCode;
+use remote_execution::TDigest;
+use remote_execution::TDirectory2;
+use remote_execution::TFile;
+use remote_execution::TSymlink;
+
+use crate::digest::CasDigestToReExt;
+use crate::digest_config::DigestConfig;
+use crate::execute::action_digest_and_blobs::ActionDigestAndBlobsBuilder;
+use crate::re::error::RemoteExecutionError;
+use crate::re::manager::ManagedRemoteExecutionClient;
+
+/// Larger than the 4 MB default batch size limit, so the blob goes through the ByteStream API
+/// rather than batch requests.
+const LARGE_BLOB_SIZE: usize = 5 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConformanceOutcome {
+    Passed,
+    Failed(String),
+    /// The backend doesn't allow the check to run, e.g. it rejects action cache writes.
+    Skipped(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ConformanceCheck {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub outcome: ConformanceOutcome,
+}
+
+struct Checker<'a> {
+    client: &'a ManagedRemoteExecutionClient,
+    digest_config: DigestConfig,
+    use_case: RemoteExecutorUseCase,
+    /// Makes the blobs and actions of this run unique, so they can't already be on the backend.
+    nonce: String,
+}
+
+impl Checker<'_> {
+    fn digest(&self, content: &[u8]) -> FileDigest {
+        FileDigest::from_content(content, self.digest_config.cas_digest_config())
+    }
+
+    async fn upload(&self, content: Vec<u8>) -> buck2_error::Result<TDigest> {
+        let expected = self.digest(&content).to_re();
+        let digest = self.client.upload_blob(content, self.use_case).await?;
+        if digest != expected {
+            return Err(buck2_error::buck2_error!(
+                [],
+                "Uploaded blob has digest `{}`, expected `{}`",
+                digest,
+                expected
+            ));
+        }
+        Ok(digest)
+    }
+
+    async fn round_trip(&self, content: Vec<u8>) -> buck2_error::Result<ConformanceOutcome> {
+        let digest = self.upload(content.clone()).await?;
+        let downloaded = self.client.download_blob(&digest, self.use_case).await?;
+        if downloaded != content {
+            return Ok(ConformanceOutcome::Failed(format!(
+                "Downloaded {} bytes for `{}`, which differ from the {} bytes uploaded",
+                downloaded.len(),
+                digest,
+                content.len()
+            )));
+        }
+        Ok(ConformanceOutcome::Passed)
+    }
+
+    async fn cas_batch(&self) -> buck2_error::Result<ConformanceOutcome> {
+        self.round_trip(format!("buck2 re conformance {}", self.nonce).into_bytes())
+            .await
+    }
+
+    async fn cas_bytestream(&self) -> buck2_error::Result<ConformanceOutcome> {
+        let mut content = vec![0; LARGE_BLOB_SIZE];
+        content[..self.nonce.len()].copy_from_slice(self.nonce.as_bytes());
+        self.round_trip(content).await
+    }
+
+    async fn cas_empty_blob(&self) -> buck2_error::Result<ConformanceOutcome> {
+        let digest = self.digest(&[]).to_re();
+        let downloaded = self.client.download_blob(&digest, self.use_case).await?;
+        if !downloaded.is_empty() {
+            return Ok(ConformanceOutcome::Failed(format!(
+                "Downloaded {} bytes for the empty blob",
+                downloaded.len()
+            )));
+        }
+        Ok(ConformanceOutcome::Passed)
+    }
+
+    async fn cas_find_missing(&self) -> buck2_error::Result<ConformanceOutcome> {
+        let present = self
+            .upload(format!("buck2 re conformance present {}", self.nonce).into_bytes())
+            .await?;
+        let missing = self
+            .digest(format!("buck2 re conformance missing {}", self.nonce).as_bytes())
+            .to_re();
+        let expirations = self
+            .client
+            .get_digest_expirations(vec![present.clone(), missing.clone()], self.use_case)
+            .await?;
+        let now = Utc::now();
+        let is_present = |digest: &TDigest| {
+            expirations
+                .iter()
+                .any(|(d, expires)| d == digest && *expires > now)
+        };
+        if !is_present(&present) {
+            return Ok(ConformanceOutcome::Failed(format!(
+                "Blob `{}` was reported missing right after it was uploaded",
+                present
+            )));
+        }
+        if is_present(&missing) {
+            return Ok(ConformanceOutcome::Failed(format!(
+                "Blob `{}` was reported present but was never uploaded",
+                missing
+            )));
+        }
+        Ok(ConformanceOutcome::Passed)
+    }
+
+    /// Writes an action result with an output directory and symlinks to the action cache and
+    /// reads it back. Remote actions produce such results, so this checks the backend stores them
+    /// the way buck2 expects without depending on an execution platform.
+    async fn action_cache_outputs(&self) -> buck2_error::Result<ConformanceOutcome> {
+        let file_content = format!("buck2 re conformance output {}", self.nonce).into_bytes();
+        let file_digest = self.digest(&file_content);
+        self.upload(file_content).await?;
+
+        let root = RE::Directory {
+            files: vec![RE::FileNode {
+                name: "file".to_owned(),
+                digest: Some(file_digest.to_grpc()),
+                is_executable: true,
+                ..Default::default()
+            }],
+            symlinks: vec![RE::SymlinkNode {
+                name: "link".to_owned(),
+                target: "file".to_owned(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let root_digest = self.upload(root.encode_to_vec()).await?;
+        let tree = RE::Tree {
+            root: Some(root),
+            children: Vec::new(),
+        };
+        let tree_digest = self.upload(tree.encode_to_vec()).await?;
+
+        let mut blobs = ActionDigestAndBlobsBuilder::new(self.digest_config);
+        let command = blobs.add_command(&RE::Command {
+            arguments: vec![
+                "buck2-re-conformance".to_owned(),
+                "action-cache-outputs".to_owned(),
+                self.nonce.clone(),
+            ],
+            ..Default::default()
+        });
+        let action = blobs.build(&RE::Action {
+            command_digest: Some(command.to_grpc()),
+            ..Default::default()
+        });
+        self.client
+            .upload_files_and_directories(
+                Vec::new(),
+                Vec::new(),
+                action.blobs.to_inlined_blobs(),
+                self.use_case,
+            )
+            .await?;
+
+        let expected = expected_action_result(file_digest.to_re(), tree_digest, root_digest);
+        let written = self
+            .client
+            .write_action_result(
+                action.action,
+                expected.clone(),
+                self.use_case,
+                &RE::Platform::default(),
+            )
+            .await;
+        if let Err(e) = written {
+            return match e.find_typed_context::<RemoteExecutionError>() {
+                Some(re)
+                    if re.code == TCode::PERMISSION_DENIED || re.code == TCode::UNIMPLEMENTED =>
+                {
+                    Ok(ConformanceOutcome::Skipped(format!(
+                        "The backend does not accept action cache writes: {}",
+                        re.message
+                    )))
+                }
+                _ => Err(e),
+            };
+        }
+
+        let Some(response) = self
+            .client
+            .action_cache(action.action, self.use_case)
+            .await?
+        else {
+            return Ok(ConformanceOutcome::Failed(
+                "The action result written to the action cache could not be read back".to_owned(),
+            ));
+        };
+        Ok(match compare_outputs(&expected, &response.action_result) {
+            Some(difference) => ConformanceOutcome::Failed(difference),
+            None => ConformanceOutcome::Passed,
+        })
+    }
+}
+
+fn expected_action_result(
+    file_digest: TDigest,
+    tree_digest: TDigest,
+    root_digest: TDigest,
+) -> TActionResult2 {
+    TActionResult2 {
+        output_files: vec![TFile {
+            digest: DigestWithStatus {
+                digest: file_digest,
+                ..Default::default()
+            },
+            name: "out/file".to_owned(),
+            executable: true,
+            ..Default::default()
+        }],
+        output_directories: vec![TDirectory2 {
+            path: "out".to_owned(),
+            tree_digest,
+            root_directory_digest: root_digest,
+            ..Default::default()
+        }],
+        output_symlinks: vec![
+            TSymlink {
+                name: "relative_link".to_owned(),
+                target: "out/file".to_owned(),
+                ..Default::default()
+            },
+            TSymlink {
+                name: "dangling_link".to_owned(),
+                target: "../does/not/exist".to_owned(),
+                ..Default::default()
+            },
+        ],
+        exit_code: 0,
+        stdout_raw: Some(Vec::new()),
+        stderr_raw: Some(Vec::new()),
+        ..Default::default()
+    }
+}
+
+/// Describes the first output of `expected` that `actual` lost or changed, if any.
+fn compare_outputs(expected: &TActionResult2, actual: &TActionResult2) -> Option<String> {
+    for file in &expected.output_files {
+        match actual.output_files.iter().find(|f| f.name == file.name) {
+            None => return Some(format!("Output file `{}` was dropped", file.name)),
+            Some(f) if f.digest.digest != file.digest.digest => {
+                return Some(format!(
+                    "Output file `{}` has digest `{}`, expected `{}`",
+                    file.name, f.digest.digest, file.digest.digest
+                ));
+            }
+            Some(f) if f.executable != file.executable => {
+                return Some(format!(
+                    "Output file `{}` lost its executable bit",
+                    file.name
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    for dir in &expected.output_directories {
+        match actual
+            .output_directories
+            .iter()
+            .find(|d| d.path == dir.path)
+        {
+            None => return Some(format!("Output directory `{}` was dropped", dir.path)),
+            Some(d) if d.tree_digest != dir.tree_digest => {
+                return Some(format!(
+                    "Output directory `{}` has tree digest `{}`, expected `{}`",
+                    dir.path, d.tree_digest, dir.tree_digest
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    for symlink in &expected.output_symlinks {
+        match actual
+            .output_symlinks
+            .iter()
+            .find(|s| s.name == symlink.name)
+        {
+            None => return Some(format!("Output symlink `{}` was dropped", symlink.name)),
+            Some(s) if s.target != symlink.target => {
+                return Some(format!(
+                    "Output symlink `{}` points to `{}`, expected `{}`",
+                    symlink.name, s.target, symlink.target
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    None
+}
+
+/// Runs all the checks in order. When the connection fails, the other checks are skipped since
+/// they would all fail the same way.
+pub async fn run_conformance_checks(
+    client: &ManagedRemoteExecutionClient,
+    digest_config: DigestConfig,
+) -> Vec<ConformanceCheck> {
+    let checker = Checker {
+        client,
+        digest_config,
+        use_case: RemoteExecutorUseCase::buck2_default(),
+        nonce: Utc::now()
+            .timestamp_nanos_opt()
+            .unwrap_or_default()
+            .to_string(),
+    };
+
+    let mut checks = Vec::new();
+    let mut add = |name, description, outcome: buck2_error::Result<ConformanceOutcome>| {
+        let outcome = outcome.unwrap_or_else(|e| ConformanceOutcome::Failed(format!("{:#}", e)));
+        let passed = outcome == ConformanceOutcome::Passed;
+        checks.push(ConformanceCheck {
+            name,
+            description,
+            outcome,
+        });
+        passed
+    };
+
+    let connected = add(
+        "capabilities",
+        "Connect, fetch capabilities and check the backend supports buck2's digest algorithms",
+        client
+            .get_session_id()
+            .await
+            .map(|_| ConformanceOutcome::Passed)
+            .buck_error_context("Error connecting to the RE backend"),
+    );
+    let not_connected = || -> buck2_error::Result<_> {
+        Ok(ConformanceOutcome::Skipped(
+            "Could not connect to the backend".to_owned(),
+        ))
+    };
+    add(
+        "cas_batch",
+        "Upload and download a small blob",
+        if connected {
+            checker.cas_batch().await
+        } else {
+            not_connected()
+        },
+    );
+    add(
+        "cas_bytestream",
+        "Upload and download a blob over the batch size limit",
+        if connected {
+            checker.cas_bytestream().await
+        } else {
+            not_connected()
+        },
+    );
+    add(
+        "cas_empty_blob",
+        "Download the empty blob without uploading it",
+        if connected {
+            checker.cas_empty_blob().await
+        } else {
+            not_connected()
+        },
+    );
+    add(
+        "cas_find_missing",
+        "Tell present blobs from missing ones",
+        if connected {
+            checker.cas_find_missing().await
+        } else {
+            not_connected()
+        },
+    );
+    add(
+        "action_cache_outputs",
+        "Preserve output directories and symlinks in action results",
+        if connected {
+            checker.action_cache_outputs().await
+        } else {
+            not_connected()
+        },
+    );
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(hash: &str) -> TDigest {
+        TDigest {
+            hash: hash.to_owned(),
+            size_in_bytes: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_compare_outputs() {
+        let expected = expected_action_result(digest("f"), digest("t"), digest("r"));
+        assert_eq!(compare_outputs(&expected, &expected), None);
+
+        // Backends may not echo the root directory digest, which buck2 doesn't need.
+        let mut actual = expected.clone();
+        actual.output_directories[0].root_directory_digest = digest("t");
+        assert_eq!(compare_outputs(&expected, &actual), None);
+
+        let mut actual = expected.clone();
+        actual.output_symlinks.pop();
+        assert_eq!(
+            compare_outputs(&expected, &actual),
+            Some("Output symlink `dangling_link` was dropped".to_owned())
+        );
+
+        let mut actual = expected.clone();
+        actual.output_symlinks[0].target = "/abs/out/file".to_owned();
+        assert_eq!(
+            compare_outputs(&expected, &actual),
+            Some(
+                "Output symlink `relative_link` points to `/abs/out/file`, expected `out/file`"
+                    .to_owned()
+            )
+        );
+
+        let mut actual = expected.clone();
+        actual.output_directories.clear();
+        assert_eq!(
+            compare_outputs(&expected, &actual),
+            Some("Output directory `out` was dropped".to_owned())
+        );
+
+        let mut actual = expected.clone();
+        actual.output_files[0].executable = false;
+        assert_eq!(
+            compare_outputs(&expected, &actual),
+            Some("Output file `out/file` lost its executable bit".to_owned())
+        );
+    }
+}
@@ -10,6 +10,7 @@
 // This triggers on Arc<Arc<...>>, but we do that here for lifetime/ownership reasons
 #![allow(clippy::redundant_allocation)]
 
+use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::RwLock;
@@ -18,6 +19,7 @@ use std::time::Duration;
 
 use allocative::Allocative;
 use async_trait::async_trait;
+use buck2_common::cas_digest::DigestAlgorithmFamily;
 use buck2_core::async_once_cell::AsyncOnceCell;
 use buck2_core::buck2_env;
 use buck2_core::execution_types::executor_config::RemoteExecutorDependency;
@@ -26,6 +28,7 @@ use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::project::ProjectRoot;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_error::BuckErrorContext;
+use buck2_re_configuration::ReBandwidthCaps;
 use buck2_re_configuration::RemoteExecutionStaticMetadata;
 use chrono::DateTime;
 use chrono::Utc;
@@ -89,6 +92,11 @@ pub struct RemoteExecutionConfig {
     /// number of retries when attempting the initial RE connection
     pub connection_retries: usize,
     pub static_metadata: Arc<RemoteExecutionStaticMetadata>,
+    pub bandwidth_caps: ReBandwidthCaps,
+    /// From `buck2_re_client.digest_algorithms`. When not set, the backend's capabilities are
+    /// used if it reports them.
+    pub backend_digest_algorithms: Option<Vec<DigestAlgorithmFamily>>,
+    pub digest_config: DigestConfig,
     pub logs_dir_path: Option<AbsNormPathBuf>,
     pub buck_out_path: AbsNormPathBuf,
     /// Whether Buck is running in paranoid mode.
@@ -195,6 +203,9 @@ impl ReConnectionManager {
         skip_remote_cache: bool,
         connection_retries: usize,
         static_metadata: Arc<RemoteExecutionStaticMetadata>,
+        bandwidth_caps: ReBandwidthCaps,
+        backend_digest_algorithms: Option<Vec<DigestAlgorithmFamily>>,
+        digest_config: DigestConfig,
         logs_dir_path: Option<AbsNormPathBuf>,
         buck_out_path: AbsNormPathBuf,
         is_paranoid_mode: bool,
@@ -206,6 +217,9 @@ impl ReConnectionManager {
                 skip_remote_cache,
                 connection_retries,
                 static_metadata,
+                bandwidth_caps,
+                backend_digest_algorithms,
+                digest_config,
                 logs_dir_path,
                 buck_out_path,
                 is_paranoid_mode,
@@ -259,6 +273,42 @@ impl ReConnectionManager {
 
         Ok(res)
     }
+
+    /// Report the state of the RE connection without establishing one.
+    pub fn connection_status(&self) -> ReConnectionStatus {
+        let conn = match self.data.read().unwrap().upgrade() {
+            Some(conn) => conn,
+            None => return ReConnectionStatus::NotConnected,
+        };
+        match conn.client.get() {
+            None => ReConnectionStatus::Connecting,
+            Some(Ok(_)) => ReConnectionStatus::Connected,
+            Some(Err(e)) => ReConnectionStatus::Failed(format!("{:#}", e)),
+        }
+    }
+}
+
+/// State of the RE connection, as reported by [ReConnectionManager::connection_status].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReConnectionStatus {
+    /// No command currently holds an RE connection.
+    NotConnected,
+    /// A connection was requested but is not established yet.
+    Connecting,
+    Connected,
+    /// The last connection attempt failed.
+    Failed(String),
+}
+
+impl ReConnectionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReConnectionStatus::NotConnected => "not_connected",
+            ReConnectionStatus::Connecting => "connecting",
+            ReConnectionStatus::Connected => "connected",
+            ReConnectionStatus::Failed(_) => "failed",
+        }
+    }
 }
 
 #[async_trait]
@@ -424,16 +474,19 @@ impl ManagedRemoteExecutionClient {
             .await
     }
 
+    /// Download `files`. If `downloaded` is set, it is incremented by the bytes downloaded as
+    /// chunks of files complete.
     pub async fn materialize_files(
         &self,
         files: Vec<NamedDigestWithPermissions>,
         use_case: RemoteExecutorUseCase,
+        downloaded: Option<&AtomicU64>,
     ) -> buck2_error::Result<()> {
         let use_case = self.re_use_case_override.unwrap_or(use_case);
         self.lock()?
             .get()
             .await?
-            .materialize_files(files, use_case)
+            .materialize_files(files, use_case, downloaded)
             .await
     }
 
@@ -15,6 +15,8 @@ use std::sync::atomic::Ordering;
 use allocative::Allocative;
 use futures::FutureExt;
 
+use crate::re::bandwidth::BandwidthLimiter;
+
 #[derive(Default)]
 pub struct RemoteExecutionClientOpStats {
     pub started: u32,
@@ -53,6 +55,19 @@ pub struct RemoteExecutionClientStats {
 
     // Local cache hits and misses stats
     pub local_cache: LocalCacheRemoteExecutionClientStats,
+
+    pub download_throttle: BandwidthThrottleStats,
+    pub upload_throttle: BandwidthThrottleStats,
+
+    pub cas_rpcs: CasRpcStats,
+}
+
+/// Number of CAS RPCs sent. Only known for the OSS client.
+#[derive(Default)]
+pub struct CasRpcStats {
+    pub find_missing_blobs: u64,
+    pub batch_update_blobs: u64,
+    pub bytestream_writes: u64,
 }
 
 #[derive(Default, Allocative)]
@@ -82,6 +97,24 @@ impl OpStats {
     }
 }
 
+/// State of a bandwidth cap from `buck2_re_client`.
+#[derive(Default)]
+pub struct BandwidthThrottleStats {
+    /// `None` if there is no cap.
+    pub bytes_per_second: Option<u64>,
+    /// Transfers currently waiting for bandwidth.
+    pub waiting: u32,
+}
+
+impl From<&'_ BandwidthLimiter> for BandwidthThrottleStats {
+    fn from(limiter: &BandwidthLimiter) -> BandwidthThrottleStats {
+        BandwidthThrottleStats {
+            bytes_per_second: limiter.bytes_per_second(),
+            waiting: limiter.waiting(),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct PerBackendRemoteExecutionClientStats {
     pub zdb: BackendStats,
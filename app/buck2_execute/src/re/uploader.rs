@@ -53,9 +53,11 @@ use crate::directory::ActionImmutableDirectory;
 use crate::directory::ReDirectorySerializer;
 use crate::execute::blobs::ActionBlobs;
 use crate::materialize::materializer::ArtifactNotMaterializedReason;
+use crate::materialize::materializer::CasArtifactMissing;
 use crate::materialize::materializer::CasDownloadInfo;
 use crate::materialize::materializer::Materializer;
 use crate::re::action_identity::ReActionIdentity;
+use crate::re::bandwidth::BandwidthLimiter;
 use crate::re::metadata::RemoteExecutionMetadataExt;
 
 #[derive(Clone, Debug, Default)]
@@ -182,6 +184,7 @@ impl Uploader {
         use_case: RemoteExecutorUseCase,
         identity: Option<&ReActionIdentity<'_>>,
         digest_config: DigestConfig,
+        bandwidth: &BandwidthLimiter,
     ) -> anyhow::Result<UploadStats> {
         let (mut upload_blobs, mut missing_digests) =
             Self::find_missing(client, input_dir, blobs, &use_case, identity, digest_config)
@@ -257,9 +260,9 @@ impl Uploader {
                     }
                     Err(
                         ref err @ ArtifactNotMaterializedReason::RequiresCasDownload {
+                            ref path,
                             ref entry,
                             ref info,
-                            ..
                         },
                     ) => {
                         if let DirectoryEntry::Leaf(ActionDirectoryMember::File(ref file)) =
@@ -282,7 +285,7 @@ impl Uploader {
                                         action_cache_is_corrupted: info.origin.guaranteed_by_action_cache()
                                     )?;
 
-                                    return Err(anyhow::anyhow!(
+                                    let error = buck2_error::Error::from(anyhow::anyhow!(
                                         "Your build requires an artifact that has expired in the RE CAS \
                                         and Buck does not have it. This likely happened because your Buck daemon \
                                         has been online for a long time. This error is currently unrecoverable. \
@@ -290,6 +293,10 @@ impl Uploader {
                                         Debug information: {:#}",
                                         err
                                     ));
+                                    // Lets the action producing the input re-execute it.
+                                    return Err(error
+                                        .context(CasArtifactMissing { path: path.clone() })
+                                        .into());
                                 }
 
                                 soft_error!(
@@ -369,6 +376,7 @@ impl Uploader {
 
         // Upload
         if !upload_files.is_empty() || !upload_blobs.is_empty() {
+            bandwidth.acquire(stats.total.bytes_uploaded).await;
             client
                 .upload(
                     use_case.metadata(identity),
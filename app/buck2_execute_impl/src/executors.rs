@@ -16,5 +16,6 @@ pub mod local;
 pub mod local_actions_throttle;
 pub mod re;
 pub mod stacked;
+pub(crate) mod streamed_output;
 pub mod to_re_platform;
 pub mod worker;
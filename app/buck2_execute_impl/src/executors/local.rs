@@ -31,6 +31,7 @@ use buck2_core::tag_error;
 use buck2_core::tag_result;
 use buck2_error::buck2_error;
 use buck2_error::BuckErrorContext;
+use buck2_events::dispatch::current_span;
 use buck2_events::dispatch::get_dispatcher_opt;
 use buck2_events::dispatch::EventDispatcher;
 use buck2_execute::artifact_value::ArtifactValue;
@@ -59,13 +60,16 @@ use buck2_execute::execute::request::CommandExecutionRequest;
 use buck2_execute::execute::request::ExecutorPreference;
 use buck2_execute::execute::result::CommandExecutionMetadata;
 use buck2_execute::execute::result::CommandExecutionResult;
+use buck2_execute::execute::target::CommandExecutionTarget;
 use buck2_execute::knobs::ExecutorGlobalKnobs;
+use buck2_execute::materialize::materializer::CasArtifactMissing;
 use buck2_execute::materialize::materializer::MaterializationError;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_forkserver::client::ForkserverClient;
 use buck2_forkserver::run::gather_output;
 use buck2_forkserver::run::maybe_absolutize_exe;
 use buck2_forkserver::run::timeout_into_cancellation;
+use buck2_forkserver::run::CommandOutputObserver;
 use buck2_forkserver::run::GatherOutputStatus;
 use buck2_futures::cancellable_future::CancellationObserver;
 use buck2_futures::cancellation::CancellationContext;
@@ -83,6 +87,7 @@ use host_sharing::HostSharingRequirements;
 use indexmap::IndexMap;
 use tracing::info;
 
+use crate::executors::streamed_output::streamed_output;
 use crate::executors::worker::WorkerHandle;
 use crate::executors::worker::WorkerPool;
 
@@ -94,8 +99,26 @@ enum LocalExecutionError {
 
     #[error("Trying to execute a remote-only action on a local executor")]
     RemoteOnlyAction,
+
+    #[error(
+        "Action may not access the network, but network isolation is unavailable for local \
+        execution (set `buck2.network_isolation_command`, or run the action remotely)"
+    )]
+    NetworkIsolationUnavailable,
+
+    #[error("Action may not access the network, which is unsupported for persistent workers")]
+    NetworkIsolationWorker,
 }
 
+/// Trace id of the buck2 invocation running the action. Exported to all locally executed actions
+/// (including tests).
+pub const BUCK_BUILD_ID_ENV_VAR: &str = "BUCK_BUILD_ID";
+/// Id of the event span of the action being executed, within the `BUCK_BUILD_ID` trace.
+pub const BUCK2_ACTION_SPAN_ID_ENV_VAR: &str = "BUCK2_ACTION_SPAN_ID";
+/// Trace id of the system which invoked buck2, if it was passed to the client via the environment
+/// variable of the same name.
+pub const BUCK2_PARENT_TRACE_ID_ENV_VAR: &str = "BUCK2_PARENT_TRACE_ID";
+
 #[derive(Clone)]
 pub struct LocalExecutor {
     artifact_fs: ArtifactFs,
@@ -105,7 +128,6 @@ pub struct LocalExecutor {
     root: AbsNormPathBuf,
     #[cfg_attr(not(unix), allow(unused))]
     forkserver: Option<ForkserverClient>,
-    #[allow(unused)]
     knobs: ExecutorGlobalKnobs,
     #[allow(unused)]
     worker_pool: Option<Arc<WorkerPool>>,
@@ -147,6 +169,7 @@ impl LocalExecutor {
         liveliness_observer: impl LivelinessObserver + 'static,
         disable_miniperf: bool,
         action_digest: &'a str,
+        output_observer: Option<&'a mut dyn CommandOutputObserver>,
     ) -> impl futures::future::Future<
         Output = buck2_error::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>,
     > + Send
@@ -169,13 +192,15 @@ impl LocalExecutor {
                             liveliness_observer,
                             self.knobs.enable_miniperf && !disable_miniperf,
                             action_digest,
+                            output_observer,
                         )
                         .await
                     }
 
                     #[cfg(not(unix))]
                     {
-                        let _unused = (forkserver, disable_miniperf, action_digest);
+                        let _unused =
+                            (forkserver, disable_miniperf, action_digest, output_observer);
                         Err(buck2_error!([], "Forkserver is not supported off-UNIX"))
                     }
                 }
@@ -200,7 +225,7 @@ impl LocalExecutor {
                     let cancellation =
                         select(timeout.boxed(), alive.boxed()).map(|r| r.factor_first().0);
 
-                    gather_output(cmd, cancellation).await
+                    gather_output(cmd, cancellation, output_observer).await
                 }
                 .with_buck_error_context(|| {
                     format!("Failed to gather output from command: {}", exe)
@@ -213,16 +238,37 @@ impl LocalExecutor {
         &self,
         action_digest: &ActionDigest,
         request: &CommandExecutionRequest,
+        target: &dyn CommandExecutionTarget,
         manager: CommandExecutionManager,
         cancellation: CancellationObserver,
         cancellations: &CancellationContext<'_>,
         digest_config: DigestConfig,
         local_resource_holders: &[LocalResourceHolder],
     ) -> CommandExecutionResult {
-        let args = &request.all_args_vec();
+        let mut args = request.all_args_vec();
         if args.is_empty() {
             return manager.error("no_args", LocalExecutionError::NoArgs);
         }
+        if !request.allow_network() {
+            if request.worker().is_some() {
+                return manager.error(
+                    "network_isolation_worker",
+                    LocalExecutionError::NetworkIsolationWorker,
+                );
+            }
+            match &self.knobs.network_isolation_command {
+                Some(isolation_command) => {
+                    args.splice(0..0, isolation_command.iter().cloned());
+                }
+                None => {
+                    return manager.error(
+                        "network_isolation_unavailable",
+                        LocalExecutionError::NetworkIsolationUnavailable,
+                    );
+                }
+            }
+        }
+        let args = &args;
 
         let executor_stage_result = executor_stage_async(
             buck2_data::LocalStage {
@@ -353,6 +399,8 @@ impl LocalExecutor {
             }
         };
         let build_id: &str = &dispatcher.trace_id().to_string();
+        let action_span_id = current_span().map(|span| span.to_string());
+        let parent_trace_id = self.knobs.parent_trace_id.as_deref();
 
         let iter_env = || {
             tmpdirs
@@ -370,12 +418,27 @@ impl LocalExecutor {
                     StrOrOsStr::from(daemon_uuid),
                 )))
                 .chain(std::iter::once((
-                    "BUCK_BUILD_ID",
+                    BUCK_BUILD_ID_ENV_VAR,
                     StrOrOsStr::from(build_id),
                 )))
+                .chain(
+                    action_span_id
+                        .as_deref()
+                        .map(|v| (BUCK2_ACTION_SPAN_ID_ENV_VAR, StrOrOsStr::from(v))),
+                )
+                .chain(
+                    parent_trace_id.map(|v| (BUCK2_PARENT_TRACE_ID_ENV_VAR, StrOrOsStr::from(v))),
+                )
         };
         let liveliness_observer = manager.inner.liveliness_observer.dupe().and(cancellation);
 
+        let (output_observer, output_events) = if request.stream_output() {
+            let (observer, events) = streamed_output(target, &self.knobs, dispatcher.dupe());
+            (Some(observer), Some(events))
+        } else {
+            (None, None)
+        };
+
         let (worker, manager) = self
             .initialize_worker(request, manager, dispatcher)
             .boxed()
@@ -410,6 +473,7 @@ impl LocalExecutor {
                             argv: args.to_vec(),
                             env,
                         }),
+                        weight: Some(self.requested_permits(request) as u64),
                     }
                     .into(),
                     Some(_) => buck2_data::WorkerExecute {
@@ -438,18 +502,29 @@ impl LocalExecutor {
                         .exec_cmd(request.args(), env, request.timeout())
                         .await)
                 } else {
-                    self.exec(
-                        &args[0],
-                        &args[1..],
-                        env,
-                        request.working_directory(),
-                        request.timeout(),
-                        request.local_environment_inheritance(),
-                        liveliness_observer,
-                        request.disable_miniperf(),
-                        &action_digest.to_string(),
-                    )
-                    .await
+                    let exec = async {
+                        // Dropped once the command exits, which ends `output_events`.
+                        let mut output_observer = output_observer;
+                        self.exec(
+                            &args[0],
+                            &args[1..],
+                            env,
+                            request.working_directory(),
+                            request.timeout(),
+                            request.local_environment_inheritance(),
+                            liveliness_observer,
+                            request.disable_miniperf(),
+                            &action_digest.to_string(),
+                            output_observer
+                                .as_mut()
+                                .map(|o| o as &mut dyn CommandOutputObserver),
+                        )
+                        .await
+                    };
+                    match output_events {
+                        Some(output_events) => future::join(exec, output_events).await.0,
+                        None => exec.await,
+                    }
                 };
 
                 let execution_time = execution_start.elapsed();
@@ -618,6 +693,20 @@ impl LocalExecutor {
         ))
     }
 
+    /// Permits of the host sharing broker a command holds while it runs.
+    fn requested_permits(&self, request: &CommandExecutionRequest) -> usize {
+        match request.host_sharing_requirements() {
+            HostSharingRequirements::ExclusiveAccess => {
+                self.host_sharing_broker.num_machine_permits()
+            }
+            HostSharingRequirements::OnePerToken(.., class)
+            | HostSharingRequirements::Shared(class) => self
+                .host_sharing_broker
+                .requested_permits(class)
+                .into_count(),
+        }
+    }
+
     async fn acquire_worker_permit(
         &self,
         request: &CommandExecutionRequest,
@@ -742,7 +831,7 @@ impl PreparedCommandExecutor for LocalExecutor {
 
         let PreparedCommand {
             request,
-            target: _,
+            target,
             prepared_action,
             digest_config,
         } = command;
@@ -772,8 +861,11 @@ impl PreparedCommandExecutor for LocalExecutor {
             buck2_data::LocalStage {
                 stage: Some(buck2_data::LocalQueued {}.into()),
             },
-            self.host_sharing_broker
-                .acquire(request.host_sharing_requirements()),
+            self.host_sharing_broker.acquire_with_priority(
+                request.host_sharing_requirements(),
+                request.host_resource_requirements(),
+                request.prioritized(),
+            ),
         )
         .await;
 
@@ -785,6 +877,7 @@ impl PreparedCommandExecutor for LocalExecutor {
                     self,
                     &prepared_action.action_and_blobs.action,
                     request,
+                    *target,
                     manager,
                     cancellation,
                     cancellations,
@@ -877,8 +970,11 @@ pub async fn materialize_inputs(
             Ok(()) => {}
             Err(MaterializationError::NotFound { source }) => {
                 let corrupted = source.info.origin.guaranteed_by_action_cache();
+                let missing = CasArtifactMissing {
+                    path: (*source.path).clone(),
+                };
 
-                return Err(tag_error!(
+                let error: buck2_error::Error = tag_error!(
                     "cas_missing_fatal",
                     MaterializationError::NotFound { source }.into(),
                     quiet: true,
@@ -886,7 +982,9 @@ pub async fn materialize_inputs(
                     daemon_in_memory_state_is_corrupted: true,
                     action_cache_is_corrupted: corrupted
                 )
-                .into());
+                .into();
+                // Lets the action producing the input re-execute it.
+                return Err(error.context(missing));
             }
             Err(e) => {
                 return Err(e.into());
@@ -1096,6 +1194,7 @@ mod unix {
         liveliness_observer: impl LivelinessObserver + 'static,
         enable_miniperf: bool,
         action_digest: &str,
+        output_observer: Option<&mut dyn CommandOutputObserver>,
     ) -> buck2_error::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)> {
         let exe = exe.as_ref();
 
@@ -1117,7 +1216,11 @@ mod unix {
         };
         apply_local_execution_environment(&mut req, working_directory, env, env_inheritance);
         forkserver
-            .execute(req, async move { liveliness_observer.while_alive().await })
+            .execute(
+                req,
+                async move { liveliness_observer.while_alive().await },
+                output_observer,
+            )
             .await
     }
 
@@ -1234,6 +1337,7 @@ mod tests {
                 NoopLivelinessObserver::create(),
                 false,
                 "",
+                None,
             )
             .await?;
         assert!(matches!(status, GatherOutputStatus::Finished { exit_code, .. } if exit_code == 0));
@@ -1271,6 +1375,7 @@ mod tests {
                 NoopLivelinessObserver::create(),
                 false,
                 "",
+                None,
             )
             .await?;
         assert!(matches!(status, GatherOutputStatus::Finished { exit_code, .. } if exit_code == 0));
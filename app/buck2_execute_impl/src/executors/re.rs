@@ -38,6 +38,7 @@ use buck2_execute::execute::result::CommandExecutionResult;
 use buck2_execute::knobs::ExecutorGlobalKnobs;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute::re::action_identity::ReActionIdentity;
+use buck2_execute::re::action_url::ReActionUrlTemplate;
 use buck2_execute::re::client::ExecuteResponseOrCancelled;
 use buck2_execute::re::error::get_re_error_tag;
 use buck2_execute::re::error::RemoteExecutionError;
@@ -79,6 +80,7 @@ pub struct ReExecutor {
     pub paranoid: Option<ParanoidDownloader>,
     pub materialize_failed_inputs: bool,
     pub dependencies: Vec<RemoteExecutorDependency>,
+    pub action_url_template: Option<ReActionUrlTemplate>,
 }
 
 impl ReExecutor {
@@ -191,7 +193,8 @@ impl ReExecutor {
             self.re_client.get_session_id().await.ok(),
             self.re_use_case,
             &platform,
-        );
+        )
+        .with_action_url(self.action_url_template.as_ref());
 
         let execution_kind = response.execution_kind(remote_details);
         let manager = manager.with_execution_kind(execution_kind.clone());
@@ -303,7 +306,8 @@ impl PreparedCommandExecutor for ReExecutor {
             self.re_client.get_session_id().await.ok(),
             self.re_use_case,
             &platform,
-        );
+        )
+        .with_action_url(self.action_url_template.as_ref());
         let manager = manager.with_execution_kind(CommandExecutionKind::Remote {
             details: details.clone(),
             queue_time: Duration::ZERO,
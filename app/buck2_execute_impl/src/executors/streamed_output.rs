@@ -0,0 +1,250 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Output of local commands sent to the console while they run.
+//!
+//! Actions run with `stream_output` send their output lines as `ActionOutputLines` events.
+//! Lines are batched over the configured flush interval, so that the lines of concurrent actions
+//! are printed in blocks rather than interleaved one by one. Each action may only send a limited
+//! number of lines per second: the other lines are dropped from the console, and only counted.
+//! The full output is still available once the action finishes, like for any other action.
+
+use std::future::Future;
+use std::mem;
+use std::time::Duration;
+use std::time::Instant;
+
+use buck2_events::dispatch::EventDispatcher;
+use buck2_execute::execute::target::CommandExecutionTarget;
+use buck2_execute::knobs::ExecutorGlobalKnobs;
+use buck2_forkserver::run::CommandOutputObserver;
+use buck2_forkserver::run::CommandOutputStream;
+use bytes::Bytes;
+use tokio::sync::mpsc;
+
+/// Splits the output of a command into lines, and batches them.
+struct LineBatcher {
+    max_lines_per_second: Option<u32>,
+    /// Incomplete last line of stdout and stderr.
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    lines: Vec<buck2_data::ActionOutputLine>,
+    dropped_lines: u64,
+    /// Start of the current one second window, and the number of lines accepted in it.
+    window_start: Instant,
+    window_lines: u32,
+}
+
+impl LineBatcher {
+    fn new(max_lines_per_second: Option<u32>, now: Instant) -> Self {
+        Self {
+            max_lines_per_second,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+            lines: Vec::new(),
+            dropped_lines: 0,
+            window_start: now,
+            window_lines: 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.dropped_lines == 0
+    }
+
+    fn push(&mut self, stream: CommandOutputStream, bytes: &[u8], now: Instant) {
+        let partial = match stream {
+            CommandOutputStream::Stdout => &mut self.stdout,
+            CommandOutputStream::Stderr => &mut self.stderr,
+        };
+        partial.extend_from_slice(bytes);
+        let complete = match partial.iter().rposition(|b| *b == b'\n') {
+            Some(end) => partial.drain(..=end).collect::<Vec<u8>>(),
+            None => return,
+        };
+        for line in complete[..complete.len() - 1].split(|b| *b == b'\n') {
+            self.push_line(stream, line, now);
+        }
+    }
+
+    fn push_line(&mut self, stream: CommandOutputStream, line: &[u8], now: Instant) {
+        if now.duration_since(self.window_start) >= Duration::from_secs(1) {
+            self.window_start = now;
+            self.window_lines = 0;
+        }
+        if self
+            .max_lines_per_second
+            .is_some_and(|max| self.window_lines >= max)
+        {
+            self.dropped_lines += 1;
+            return;
+        }
+        self.window_lines += 1;
+
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        self.lines.push(buck2_data::ActionOutputLine {
+            stderr: stream == CommandOutputStream::Stderr,
+            line: String::from_utf8_lossy(line).into_owned(),
+        });
+    }
+
+    /// Take the incomplete last lines once the command exited.
+    fn finish(&mut self, now: Instant) {
+        for stream in [CommandOutputStream::Stdout, CommandOutputStream::Stderr] {
+            let partial = match stream {
+                CommandOutputStream::Stdout => mem::take(&mut self.stdout),
+                CommandOutputStream::Stderr => mem::take(&mut self.stderr),
+            };
+            if !partial.is_empty() {
+                self.push_line(stream, &partial, now);
+            }
+        }
+    }
+
+    /// The lines batched and the number of lines dropped since the last call.
+    fn take(&mut self) -> (Vec<buck2_data::ActionOutputLine>, u64) {
+        (
+            mem::take(&mut self.lines),
+            mem::take(&mut self.dropped_lines),
+        )
+    }
+}
+
+/// Observer of a command forwarding its output to the future returned by [`streamed_output`].
+pub(crate) struct StreamedOutput {
+    sender: mpsc::UnboundedSender<(CommandOutputStream, Bytes)>,
+}
+
+impl CommandOutputObserver for StreamedOutput {
+    fn output(&mut self, stream: CommandOutputStream, bytes: &Bytes) {
+        // The receiver only stops once this is dropped.
+        let _ignored = self.sender.send((stream, bytes.clone()));
+    }
+}
+
+/// Create an observer for the command of `target`, and the future sending its output as events.
+/// The future completes once the observer is dropped. It should be polled in the span of the
+/// action, so that the events are attached to it.
+pub(crate) fn streamed_output(
+    target: &dyn CommandExecutionTarget,
+    knobs: &ExecutorGlobalKnobs,
+    dispatcher: EventDispatcher,
+) -> (StreamedOutput, impl Future<Output = ()> + Send + 'static) {
+    let key = target.as_proto_action_key();
+    let name = target.as_proto_action_name();
+    let flush_interval = Duration::from_millis(knobs.stream_output_flush_interval_ms);
+    let mut batcher = LineBatcher::new(knobs.stream_output_max_lines_per_second, Instant::now());
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+
+    let send = move |batcher: &mut LineBatcher| {
+        if batcher.is_empty() {
+            return;
+        }
+        let (lines, dropped_lines) = batcher.take();
+        dispatcher.instant_event(buck2_data::ActionOutputLines {
+            key: Some(key.clone()),
+            name: Some(name.clone()),
+            lines,
+            dropped_lines,
+        });
+    };
+
+    let events = async move {
+        let mut last_flush = Instant::now();
+        loop {
+            // Nothing to wait for until some output arrives.
+            let output = if batcher.is_empty() {
+                receiver.recv().await
+            } else {
+                let timeout = flush_interval.saturating_sub(last_flush.elapsed());
+                match tokio::time::timeout(timeout, receiver.recv()).await {
+                    Ok(output) => output,
+                    Err(_) => {
+                        send(&mut batcher);
+                        last_flush = Instant::now();
+                        continue;
+                    }
+                }
+            };
+            match output {
+                Some((stream, bytes)) => batcher.push(stream, &bytes, Instant::now()),
+                None => break,
+            }
+            if last_flush.elapsed() >= flush_interval {
+                send(&mut batcher);
+                last_flush = Instant::now();
+            }
+        }
+        batcher.finish(Instant::now());
+        send(&mut batcher);
+    };
+
+    (StreamedOutput { sender }, events)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use buck2_forkserver::run::CommandOutputStream;
+
+    use crate::executors::streamed_output::LineBatcher;
+
+    fn lines(batcher: &mut LineBatcher) -> (Vec<(bool, String)>, u64) {
+        let (lines, dropped) = batcher.take();
+        (
+            lines.into_iter().map(|l| (l.stderr, l.line)).collect(),
+            dropped,
+        )
+    }
+
+    #[test]
+    fn test_lines() {
+        let now = Instant::now();
+        let mut batcher = LineBatcher::new(None, now);
+        batcher.push(CommandOutputStream::Stdout, b"a\r\nb", now);
+        batcher.push(CommandOutputStream::Stderr, b"c\n", now);
+        batcher.push(CommandOutputStream::Stdout, b"c\n\nd", now);
+        assert_eq!(
+            (
+                vec![
+                    (false, "a".to_owned()),
+                    (true, "c".to_owned()),
+                    (false, "bc".to_owned()),
+                    (false, "".to_owned()),
+                ],
+                0
+            ),
+            lines(&mut batcher)
+        );
+        assert!(batcher.is_empty());
+
+        batcher.finish(now);
+        assert_eq!((vec![(false, "d".to_owned())], 0), lines(&mut batcher));
+    }
+
+    #[test]
+    fn test_max_lines_per_second() {
+        let now = Instant::now();
+        let mut batcher = LineBatcher::new(Some(2), now);
+        batcher.push(CommandOutputStream::Stdout, b"a\nb\nc\nd\n", now);
+        assert_eq!(
+            (vec![(false, "a".to_owned()), (false, "b".to_owned())], 2),
+            lines(&mut batcher)
+        );
+
+        batcher.push(
+            CommandOutputStream::Stdout,
+            b"e\n",
+            now + Duration::from_secs(1),
+        );
+        assert_eq!((vec![(false, "e".to_owned())], 0), lines(&mut batcher));
+    }
+}
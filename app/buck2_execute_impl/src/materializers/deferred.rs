@@ -52,6 +52,7 @@ use buck2_events::dispatch::get_dispatcher_opt;
 use buck2_events::dispatch::with_dispatcher_async;
 use buck2_events::dispatch::EventDispatcher;
 use buck2_events::span::SpanId;
+use buck2_execute::artifact_signing::ArtifactSigners;
 use buck2_execute::artifact_value::ArtifactValue;
 use buck2_execute::digest_config::DigestConfig;
 use buck2_execute::directory::ActionDirectory;
@@ -73,6 +74,7 @@ use buck2_execute::materialize::materializer::WriteRequest;
 use buck2_execute::output_size::OutputSize;
 use buck2_execute::re::manager::ReConnectionManager;
 use buck2_futures::cancellation::CancellationContext;
+use buck2_futures::cpu_attribution::CpuSubsystem;
 use buck2_http::HttpClient;
 use buck2_util::threads::check_stack_overflow;
 use buck2_util::threads::thread_spawn;
@@ -115,6 +117,7 @@ use crate::materializers::deferred::extension::ExtensionCommand;
 use crate::materializers::deferred::file_tree::FileTree;
 use crate::materializers::deferred::io_handler::DefaultIoHandler;
 use crate::materializers::deferred::io_handler::IoHandler;
+use crate::materializers::deferred::io_handler::TtlRefreshStats;
 use crate::materializers::deferred::subscriptions::MaterializerSubscriptionOperation;
 use crate::materializers::deferred::subscriptions::MaterializerSubscriptions;
 use crate::materializers::sqlite::MaterializerState;
@@ -195,12 +198,17 @@ pub struct DeferredMaterializerConfigs {
     pub verbose_materializer_log: bool,
     pub clean_stale_config: Option<CleanStaleConfig>,
     pub disable_eager_write_dispatch: bool,
+    /// Signers whose `verify_command` checks the signatures of materialized artifacts.
+    pub artifact_signers: Arc<ArtifactSigners>,
 }
 
 pub struct TtlRefreshConfiguration {
     pub frequency: std::time::Duration,
     pub min_ttl: Duration,
     pub enabled: bool,
+    /// How much to extend the TTL of digests that would still expire within `min_ttl` after a
+    /// refresh. When unset, the TTL refresh only fetches the current expiration of digests.
+    pub extension: Option<Duration>,
 }
 
 #[derive(Clone, Copy, Debug, Dupe, PartialEq)]
@@ -317,7 +325,8 @@ pub(crate) struct DeferredMaterializerCommandProcessor<T: 'static> {
     /// small and we create it infrequently, that's fine.
     ttl_refresh_history: Vec<TtlRefreshHistoryEntry>,
     /// The current ttl_refresh instance, if any exists.
-    ttl_refresh_instance: Option<oneshot::Receiver<(DateTime<Utc>, buck2_error::Result<()>)>>,
+    ttl_refresh_instance:
+        Option<oneshot::Receiver<(DateTime<Utc>, buck2_error::Result<TtlRefreshStats>)>>,
     cancellations: &'static CancellationContext<'static>,
     stats: Arc<DeferredMaterializerStats>,
     access_times_buffer: Option<HashSet<ProjectRelativePathBuf>>,
@@ -328,7 +337,7 @@ pub(crate) struct DeferredMaterializerCommandProcessor<T: 'static> {
 
 struct TtlRefreshHistoryEntry {
     at: DateTime<Utc>,
-    outcome: Option<buck2_error::Result<()>>,
+    outcome: Option<buck2_error::Result<TtlRefreshStats>>,
 }
 
 // NOTE: This doesn't derive `Error` and that's on purpose.  We don't want to make it easy (or
@@ -1033,6 +1042,7 @@ impl DeferredMaterializerAccessor<DefaultIoHandler> {
             re_client_manager,
             io_executor,
             http_client,
+            configs.artifact_signers.dupe(),
         ));
 
         let command_processor = {
@@ -1072,12 +1082,14 @@ impl DeferredMaterializerAccessor<DefaultIoHandler> {
 
                 let cancellations = CancellationContext::never_cancelled();
 
-                rt.block_on(command_processor(cancellations).run(
-                    command_receiver,
-                    configs.ttl_refresh,
-                    access_time_update_max_buffer_size,
-                    configs.update_access_times,
-                    configs.clean_stale_config,
+                rt.block_on(CpuSubsystem::Materializer.attribute(
+                    command_processor(cancellations).run(
+                        command_receiver,
+                        configs.ttl_refresh,
+                        access_time_update_max_buffer_size,
+                        configs.update_access_times,
+                        configs.clean_stale_config,
+                    ),
                 ));
             }
         })
@@ -1218,6 +1230,7 @@ impl<T: IoHandler> DeferredMaterializerCommandProcessor<T> {
     {
         // FIXME(JakobDegen): Ideally there wouldn't be a `None` case, but I don't know this code
         // well enough to be confident in removing it
+        let f = CpuSubsystem::Materializer.attribute(f);
         match get_dispatcher_opt() {
             Some(dispatcher) => rt.spawn(with_dispatcher_async(dispatcher, f)),
             None => rt.spawn(f),
@@ -1301,7 +1314,11 @@ impl<T: IoHandler> DeferredMaterializerCommandProcessor<T> {
                     if self.ttl_refresh_instance.is_none() {
                         let ttl_refresh = self
                             .io
-                            .create_ttl_refresh(&self.tree, ttl_refresh.min_ttl)
+                            .create_ttl_refresh(
+                                &self.tree,
+                                ttl_refresh.min_ttl,
+                                ttl_refresh.extension,
+                            )
                             .map(|fut| {
                                 // We sue a channel here and not JoinHandle so we get blocking
                                 // `try_recv`.
@@ -43,6 +43,7 @@ use crate::materializers::deferred::clean_stale::CleanStaleArtifactsCommand;
 use crate::materializers::deferred::clean_stale::CleanStaleArtifactsExtensionCommand;
 use crate::materializers::deferred::io_handler::create_ttl_refresh;
 use crate::materializers::deferred::io_handler::IoHandler;
+use crate::materializers::deferred::io_handler::TtlRefreshStats;
 use crate::materializers::deferred::subscriptions::MaterializerSubscriptionOperation;
 use crate::materializers::deferred::ArtifactMaterializationMethod;
 use crate::materializers::deferred::ArtifactMaterializationStage;
@@ -240,7 +241,7 @@ impl<T: IoHandler> ExtensionCommand<T> for Fsck {
 #[derive(Derivative)]
 #[derivative(Debug)]
 struct RefreshTtls {
-    sender: Sender<Option<JoinHandle<buck2_error::Result<()>>>>,
+    sender: Sender<Option<JoinHandle<buck2_error::Result<TtlRefreshStats>>>>,
     min_ttl: i64,
 }
 
@@ -250,6 +251,7 @@ impl<T: IoHandler> ExtensionCommand<T> for RefreshTtls {
             &processor.tree,
             processor.io.re_client_manager(),
             Duration::seconds(self.min_ttl),
+            None,
             processor.io.digest_config(),
         )
         .map(|f| processor.spawn(f));
@@ -276,8 +278,13 @@ impl<T: IoHandler> ExtensionCommand<T> for GetTtlRefreshLog {
                 None => {
                     writeln!(&mut out, "SKIP").unwrap();
                 }
-                Some(Ok(())) => {
-                    writeln!(&mut out, "OK").unwrap();
+                Some(Ok(stats)) => {
+                    writeln!(
+                        &mut out,
+                        "OK\tchecked={}\textended={}\texpired={}",
+                        stats.checked, stats.extended, stats.expired
+                    )
+                    .unwrap();
                 }
                 Some(Err(e)) => {
                     writeln!(&mut out, "ERR\t{:#}", e).unwrap();
@@ -9,7 +9,10 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::pin::pin;
 use std::str::FromStr;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use allocative::Allocative;
@@ -21,6 +24,7 @@ use buck2_core::fs::fs_util::IoError;
 use buck2_core::fs::fs_util::ReadDir;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_core::fs::project_rel_path::ProjectRelativePathBuf;
 use buck2_directory::directory::directory::Directory;
 use buck2_directory::directory::directory_iterator::DirectoryIterator;
@@ -28,7 +32,9 @@ use buck2_directory::directory::directory_iterator::DirectoryIteratorPathStack;
 use buck2_directory::directory::entry::DirectoryEntry;
 use buck2_directory::directory::walk::unordered_entry_walk;
 use buck2_error::BuckErrorContext;
+use buck2_error::ErrorTag;
 use buck2_events::dispatch::EventDispatcher;
+use buck2_execute::artifact_signing::ArtifactSigners;
 use buck2_execute::artifact_value::ArtifactValue;
 use buck2_execute::digest::CasDigestFromReExt;
 use buck2_execute::digest::CasDigestToReExt;
@@ -47,6 +53,7 @@ use buck2_execute::re::error::RemoteExecutionError;
 use buck2_execute::re::manager::ReConnectionManager;
 use buck2_futures::cancellation::CancellationContext;
 use buck2_http::HttpClient;
+use chrono::DateTime;
 use chrono::Duration;
 use chrono::Utc;
 use dupe::Dupe;
@@ -86,8 +93,14 @@ pub struct DefaultIoHandler {
     /// Executor for blocking IO operations
     io_executor: Arc<dyn BlockingExecutor>,
     http_client: HttpClient,
+    artifact_signers: Arc<ArtifactSigners>,
 }
 
+/// CAS downloads of at least this many bytes report their progress to the console.
+const DOWNLOAD_PROGRESS_MIN_BYTES: u64 = 64 << 20;
+
+const DOWNLOAD_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
 struct MaterializationStat {
     file_count: u64,
     total_bytes: u64,
@@ -136,7 +149,8 @@ pub trait IoHandler: Sized + Sync + Send + 'static {
         self: &Arc<Self>,
         tree: &ArtifactTree,
         min_ttl: Duration,
-    ) -> Option<BoxFuture<'static, buck2_error::Result<()>>>;
+        extension: Option<Duration>,
+    ) -> Option<BoxFuture<'static, buck2_error::Result<TtlRefreshStats>>>;
 
     fn read_dir(&self, path: &AbsNormPathBuf) -> Result<ReadDir, IoError>;
     fn buck_out_path(&self) -> &ProjectRelativePathBuf;
@@ -153,6 +167,7 @@ impl DefaultIoHandler {
         re_client_manager: Arc<ReConnectionManager>,
         io_executor: Arc<dyn BlockingExecutor>,
         http_client: HttpClient,
+        artifact_signers: Arc<ArtifactSigners>,
     ) -> Self {
         Self {
             fs,
@@ -161,16 +176,18 @@ impl DefaultIoHandler {
             re_client_manager,
             io_executor,
             http_client,
+            artifact_signers,
         }
     }
     /// Materializes an `entry` at `path`, using the materialization `method`
-    #[instrument(level = "debug", skip(self, stat, cancellations), fields(path = %path, method = %method, entry = %entry))]
+    #[instrument(level = "debug", skip(self, stat, event_dispatcher, cancellations), fields(path = %path, method = %method, entry = %entry))]
     async fn materialize_entry_span(
         &self,
         path: ProjectRelativePathBuf,
         method: Arc<ArtifactMaterializationMethod>,
         entry: ActionDirectoryEntry<ActionSharedDirectory>,
         stat: &mut MaterializationStat,
+        event_dispatcher: &EventDispatcher,
         cancellations: &CancellationContext<'_>,
     ) -> Result<(), MaterializeEntryError> {
         // Materialize the dir structure, and symlinks
@@ -225,32 +242,49 @@ impl DefaultIoHandler {
                 let connection = self.re_client_manager.get_re_connection();
                 let re_client = connection.get_client();
 
-                re_client
-                    .materialize_files(files, info.re_use_case)
+                let downloaded = AtomicU64::new(0);
+                let download =
+                    re_client.materialize_files(files, info.re_use_case, Some(&downloaded));
+                let res = if stat.total_bytes >= DOWNLOAD_PROGRESS_MIN_BYTES {
+                    report_download_progress(
+                        event_dispatcher,
+                        &path,
+                        stat.total_bytes,
+                        &downloaded,
+                        download,
+                    )
                     .await
-                    .map_err(|e| {
-                        let e: buck2_error::Error = e.into();
-                        match e.find_typed_context::<RemoteExecutionError>() {
-                            Some(re_error) if re_error.code == TCode::NOT_FOUND => {
-                                let e: buck2_error::Error = e.into();
-                                MaterializeEntryError::NotFound(CasNotFoundError {
-                                    path: Arc::from(path),
-                                    info: info.dupe(),
-                                    directory: entry,
-                                    error: Arc::from(e),
-                                })
-                            }
-                            _ => MaterializeEntryError::Error(
-                                e.context({
-                                    format!(
-                                        "Error materializing files declared by action: {}",
-                                        info.origin
-                                    )
-                                })
-                                .into(),
-                            ),
+                } else {
+                    download.await
+                };
+                res.map_err(|e| {
+                    let e: buck2_error::Error = e.into();
+                    match e.find_typed_context::<RemoteExecutionError>() {
+                        Some(re_error) if re_error.code == TCode::NOT_FOUND => {
+                            let e = match known_expiration(&entry) {
+                                Some(expires) => e
+                                    .context(format!("Artifact expired in the CAS at {}", expires))
+                                    .tag([ErrorTag::MaterializationCasExpired]),
+                                None => e,
+                            };
+                            MaterializeEntryError::NotFound(CasNotFoundError {
+                                path: Arc::from(path),
+                                info: info.dupe(),
+                                directory: entry,
+                                error: Arc::from(e),
+                            })
                         }
-                    })?;
+                        _ => MaterializeEntryError::Error(
+                            e.context({
+                                format!(
+                                    "Error materializing files declared by action: {}",
+                                    info.origin
+                                )
+                            })
+                            .into(),
+                        ),
+                    }
+                })?;
             }
             ArtifactMaterializationMethod::HttpDownload { info } => {
                 async {
@@ -414,6 +448,7 @@ impl IoHandler for DefaultIoHandler {
         let materialization_start = buck2_data::MaterializationStart {
             action_digest: action_digest.clone(),
         };
+        let dispatcher = event_dispatcher.dupe();
         event_dispatcher
             .span_async(materialization_start, async move {
                 let path_string = path.as_str().to_owned();
@@ -421,9 +456,32 @@ impl IoHandler for DefaultIoHandler {
                     file_count: 0,
                     total_bytes: 0,
                 };
-                let res = self
-                    .materialize_entry_span(path, method.dupe(), entry, &mut stat, cancellations)
+                // Signatures are checked once the artifact is on disk.
+                let signed = match ArtifactValue::new(entry.dupe(), None).digest() {
+                    Some(digest) if !self.artifact_signers.is_empty() => {
+                        Some((self.fs.resolve(&path), digest.to_string()))
+                    }
+                    _ => None,
+                };
+                let mut res = self
+                    .materialize_entry_span(
+                        path,
+                        method.dupe(),
+                        entry,
+                        &mut stat,
+                        &dispatcher,
+                        cancellations,
+                    )
                     .await;
+                if let Some((artifact, digest)) = signed {
+                    if res.is_ok() {
+                        res = self
+                            .artifact_signers
+                            .verify(&artifact, &digest, &self.fs)
+                            .await
+                            .map_err(MaterializeEntryError::from);
+                    }
+                }
                 let error = res.as_ref().err().map(|e| format!("{:#}", e));
 
                 (
@@ -447,9 +505,16 @@ impl IoHandler for DefaultIoHandler {
         self: &Arc<Self>,
         tree: &ArtifactTree,
         min_ttl: Duration,
-    ) -> Option<BoxFuture<'static, buck2_error::Result<()>>> {
-        create_ttl_refresh(tree, &self.re_client_manager, min_ttl, self.digest_config)
-            .map(|f| f.boxed())
+        extension: Option<Duration>,
+    ) -> Option<BoxFuture<'static, buck2_error::Result<TtlRefreshStats>>> {
+        create_ttl_refresh(
+            tree,
+            &self.re_client_manager,
+            min_ttl,
+            extension,
+            self.digest_config,
+        )
+        .map(|f| f.boxed())
     }
 
     fn read_dir(&self, path: &AbsNormPathBuf) -> Result<ReadDir, IoError> {
@@ -473,7 +538,52 @@ impl IoHandler for DefaultIoHandler {
     }
 }
 
+/// The earliest expiration of the files of `entry` that is known to have passed, if any. This
+/// tells apart artifacts that expired in the CAS from ones that went missing before their TTL.
+pub(super) fn known_expiration(
+    entry: &ActionDirectoryEntry<ActionSharedDirectory>,
+) -> Option<DateTime<Utc>> {
+    let now = Utc::now();
+    let mut expired: Option<DateTime<Utc>> = None;
+    let mut walk = unordered_entry_walk(entry.as_ref().map_dir(Directory::as_ref));
+    while let Some((_entry_path, entry)) = walk.next() {
+        if let DirectoryEntry::Leaf(ActionDirectoryMember::File(f)) = entry {
+            let expires = f.digest.expires();
+            // An expiration at the epoch means it's unknown.
+            if expires.timestamp() > 0 && expires <= now {
+                expired = Some(expired.map_or(expires, |e| e.min(expires)));
+            }
+        }
+    }
+    expired
+}
+
 /// This is used for testing to ingest digests (via BUCK2_TEST_TOMBSTONED_DIGESTS).
+/// Run `download`, reporting the bytes `downloaded` so far periodically.
+async fn report_download_progress<F: Future>(
+    event_dispatcher: &EventDispatcher,
+    path: &ProjectRelativePath,
+    total_bytes: u64,
+    downloaded: &AtomicU64,
+    download: F,
+) -> F::Output {
+    let mut download = pin!(download);
+    let mut interval = tokio::time::interval(DOWNLOAD_PROGRESS_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        tokio::select! {
+            res = &mut download => return res,
+            _ = interval.tick() => {
+                event_dispatcher.instant_event(buck2_data::DownloadProgress {
+                    path: path.to_string(),
+                    downloaded_bytes: downloaded.load(Ordering::Relaxed),
+                    total_bytes,
+                });
+            }
+        }
+    }
+}
+
 fn maybe_tombstone_digest(digest: &FileDigest) -> buck2_error::Result<&FileDigest> {
     // This has to be of size 1 since size 0 will result in the RE client just producing an empty
     // instead of a not-found error.
@@ -507,13 +617,30 @@ fn maybe_tombstone_digest(digest: &FileDigest) -> buck2_error::Result<&FileDiges
     Ok(digest)
 }
 
+/// Outcome of a TTL refresh.
+#[derive(Debug, Default, Clone, Copy, Dupe)]
+pub struct TtlRefreshStats {
+    /// Digests whose expiration was queried from the CAS.
+    pub checked: u64,
+    /// Digests whose TTL was extended because they were still about to expire.
+    pub extended: u64,
+    /// Digests that had already expired, so the artifacts referencing them can no longer be
+    /// materialized.
+    pub expired: u64,
+}
+
 /// Spawn a task to refresh TTLs.
+///
+/// The expiration of digests of declared artifacts expiring within `min_ttl` is fetched from the
+/// CAS, as other clients may have extended it. If `extension` is set, the TTL of the digests that
+/// would still expire within `min_ttl` is then extended by `extension`.
 pub(super) fn create_ttl_refresh(
     tree: &ArtifactTree,
     re_manager: &Arc<ReConnectionManager>,
     min_ttl: Duration,
+    extension: Option<Duration>,
     digest_config: DigestConfig,
-) -> Option<impl Future<Output = buck2_error::Result<()>>> {
+) -> Option<impl Future<Output = buck2_error::Result<TtlRefreshStats>>> {
     let mut digests_to_refresh = HashMap::<_, HashSet<_>>::new();
 
     let ttl_deadline = Utc::now() + min_ttl;
@@ -555,6 +682,7 @@ pub(super) fn create_ttl_refresh(
 
         let re_connection = re_manager.get_re_connection();
         let re_client = re_connection.get_client();
+        let mut stats = TtlRefreshStats::default();
 
         for (use_case, digests_to_refresh) in digests_to_refresh {
             let mut digests_to_refresh = digests_to_refresh.into_iter().collect::<Vec<_>>();
@@ -591,10 +719,50 @@ pub(super) fn create_ttl_refresh(
 
                     digest.update_expires(*expires);
                 }
+                stats.checked += chunk.len() as u64;
+
+                let now = Utc::now();
+                let mut to_extend = Vec::new();
+                for digest in chunk {
+                    let expires = digest.expires();
+                    if expires <= now {
+                        stats.expired += 1;
+                    } else if expires < ttl_deadline {
+                        to_extend.push(digest);
+                    }
+                }
+
+                let Some(extension) = extension else {
+                    continue;
+                };
+                if to_extend.is_empty() {
+                    continue;
+                }
+
+                tracing::debug!("Extend {} TTLs", to_extend.len());
+                re_client
+                    .extend_digest_ttl(
+                        to_extend.iter().map(|d| d.to_re()).collect(),
+                        extension.to_std()?,
+                        use_case,
+                    )
+                    .await?;
+                let expires = Utc::now() + extension;
+                for digest in &to_extend {
+                    digest.update_expires(expires);
+                }
+                stats.extended += to_extend.len() as u64;
             }
         }
 
-        buck2_error::Ok(())
+        if stats.expired > 0 {
+            tracing::warn!(
+                "{} digests referenced by declared artifacts have expired in the CAS",
+                stats.expired
+            );
+        }
+
+        buck2_error::Ok(stats)
     }
     .map(|res| {
         if let Err(e) = &res {
@@ -10,6 +10,7 @@
 use std::collections::HashMap;
 
 use buck2_common::file_ops::FileMetadata;
+use buck2_common::file_ops::TrackedFileDigest;
 use buck2_core::fs::fs_util::IoError;
 use buck2_core::fs::paths::forward_rel_path::ForwardRelativePath;
 use buck2_execute::digest_config::DigestConfig;
@@ -62,6 +63,55 @@ fn test_find_artifacts() -> buck2_error::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_known_expiration() -> buck2_error::Result<()> {
+    let digest_config = DigestConfig::testing_default();
+    let file = |content: &str, expires: DateTime<Utc>| {
+        let digest =
+            TrackedFileDigest::from_content(content.as_bytes(), digest_config.cas_digest_config());
+        digest.update_expires(expires);
+        FileMetadata {
+            digest,
+            is_executable: false,
+        }
+    };
+    let entry = |files: Vec<(&str, FileMetadata)>| -> buck2_error::Result<_> {
+        let mut builder = ActionDirectoryBuilder::empty();
+        for (path, file) in files {
+            insert_file(&mut builder, ProjectRelativePath::unchecked_new(path), file)?;
+        }
+        Ok(ActionDirectoryEntry::Dir(
+            builder
+                .fingerprint(digest_config.as_directory_serializer())
+                .shared(&*buck2_execute::directory::INTERNER),
+        ))
+    };
+
+    let now = Utc::now();
+    let past = now - Duration::hours(2);
+    let earlier = now - Duration::hours(3);
+    let future = now + Duration::hours(1);
+    let unknown = DateTime::<Utc>::UNIX_EPOCH;
+
+    assert_eq!(
+        super::io_handler::known_expiration(&entry(vec![
+            ("a", file("a", future)),
+            ("b", file("b", unknown)),
+        ])?),
+        None
+    );
+    assert_eq!(
+        super::io_handler::known_expiration(&entry(vec![
+            ("a", file("a", past)),
+            ("b", file("b", earlier)),
+            ("c", file("c", future)),
+        ])?)
+        .map(|e| e.timestamp()),
+        Some(earlier.timestamp())
+    );
+    Ok(())
+}
+
 #[test]
 fn test_remove_path() {
     fn insert(tree: &mut FileTree<String>, path: &str) {
@@ -311,7 +361,8 @@ mod state_machine {
             self: &Arc<Self>,
             _tree: &ArtifactTree,
             _min_ttl: Duration,
-        ) -> Option<BoxFuture<'static, buck2_error::Result<()>>> {
+            _extension: Option<Duration>,
+        ) -> Option<BoxFuture<'static, buck2_error::Result<TtlRefreshStats>>> {
             unimplemented!()
         }
 
@@ -502,6 +553,7 @@ mod state_machine {
                         frequency: std::time::Duration::default(),
                         min_ttl: chrono::Duration::zero(),
                         enabled: false,
+                        extension: None,
                     },
                     0,
                     AccessTimesUpdates::Disabled,
@@ -332,7 +332,7 @@ pub async fn cas_download<'a, 'b>(
     let re_conn = re.get_re_connection();
     let re_client = re_conn.get_client();
     cancellations
-        .critical_section(|| re_client.materialize_files(files, info.re_use_case))
+        .critical_section(|| re_client.materialize_files(files, info.re_use_case, None))
         .await?;
     Ok(())
 }
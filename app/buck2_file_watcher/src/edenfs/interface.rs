@@ -18,6 +18,7 @@ use buck2_core::cells::name::CellName;
 use buck2_core::cells::CellResolver;
 use buck2_core::fs::project::ProjectRoot;
 use buck2_eden::connection::EdenConnectionManager;
+use buck2_error::buck2_error;
 use buck2_events::dispatch::span_async;
 use dice::DiceTransactionUpdater;
 use edenfs::ChangesSinceV2Params;
@@ -46,6 +47,7 @@ impl EdenFsFileWatcher {
         root: &ProjectRoot,
         cells: CellResolver,
         ignore_specs: HashMap<CellName, IgnoreSet>,
+        cursor: Option<&str>,
     ) -> buck2_error::Result<Self> {
         let eden_semaphore =
             buck2_env!("BUCK2_EDEN_SEMAPHORE", type=usize, default=2048, applicability=internal)?;
@@ -54,10 +56,18 @@ impl EdenFsFileWatcher {
             .expect("Failed to connect to EdenFS");
         let mount_point = manager.get_mount_point();
 
+        let position = match cursor.map(parse_position).transpose() {
+            Ok(position) => position.unwrap_or_default(),
+            Err(e) => {
+                tracing::warn!("Ignoring the cursor of the previous daemon: {:#}", e);
+                JournalPosition::default()
+            }
+        };
+
         Ok(Self {
             manager,
             mount_point,
-            position: RwLock::new(JournalPosition::default()),
+            position: RwLock::new(position),
             cells,
             ignore_specs,
         })
@@ -87,6 +97,37 @@ impl EdenFsFileWatcher {
     }
 }
 
+/// Formats a journal position as `<mount generation>:<sequence number>:<hex snapshot hash>`.
+fn format_position(position: &JournalPosition) -> String {
+    let mut out = format!("{}:{}:", position.mountGeneration, position.sequenceNumber);
+    for b in &position.snapshotHash {
+        out.push_str(&format!("{:02x}", b));
+    }
+    out
+}
+
+fn parse_position(cursor: &str) -> buck2_error::Result<JournalPosition> {
+    let invalid = || buck2_error!([], "Invalid EdenFS cursor: `{}`", cursor);
+    let mut parts = cursor.splitn(3, ':');
+    let mut next = || parts.next().ok_or_else(invalid);
+    let mount_generation = next()?.parse().map_err(|_| invalid())?;
+    let sequence_number = next()?.parse().map_err(|_| invalid())?;
+    let hash = next()?;
+    if !hash.is_ascii() || hash.len() % 2 != 0 {
+        return Err(invalid());
+    }
+    let snapshot_hash = (0..hash.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hash[i..i + 2], 16).map_err(|_| invalid()))
+        .collect::<buck2_error::Result<Vec<u8>>>()?;
+    Ok(JournalPosition {
+        mountGeneration: mount_generation,
+        sequenceNumber: sequence_number,
+        snapshotHash: snapshot_hash,
+        ..Default::default()
+    })
+}
+
 #[async_trait]
 impl FileWatcher for EdenFsFileWatcher {
     async fn sync(
@@ -110,4 +151,8 @@ impl FileWatcher for EdenFsFileWatcher {
         )
         .await
     }
+
+    async fn cursor(&self) -> Option<String> {
+        Some(format_position(&*self.position.read().await))
+    }
 }
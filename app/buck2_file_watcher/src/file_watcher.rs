@@ -36,17 +36,27 @@ pub trait FileWatcher: Allocative + Send + Sync + 'static {
         &self,
         dice: DiceTransactionUpdater,
     ) -> buck2_error::Result<(DiceTransactionUpdater, Mergebase)>;
+
+    /// Position of the watcher in the stream of file changes, which a watcher of the daemon
+    /// replacing this one can start from. `None` for watchers without such a position.
+    async fn cursor(&self) -> Option<String> {
+        None
+    }
 }
 
 impl dyn FileWatcher {
     /// Create a new FileWatcher. Note that this is not async, since it's called during daemon
     /// startup and shouldn't be doing any work that could warrant suspending.
+    ///
+    /// `cursor` is the position of the watcher of the previous daemon. Watchers which understand
+    /// it report the changes since then, instead of starting with a fresh instance.
     pub fn new(
         fb: fbinit::FacebookInit,
         project_root: &ProjectRoot,
         root_config: &LegacyBuckConfig,
         cells: CellResolver,
         ignore_specs: HashMap<CellName, IgnoreSet>,
+        cursor: Option<&str>,
     ) -> buck2_error::Result<Arc<dyn FileWatcher>> {
         let default = if is_open_source() {
             "notify"
@@ -64,8 +74,14 @@ impl dyn FileWatcher {
             .unwrap_or(default)
         {
             "watchman" => Ok(Arc::new(
-                WatchmanFileWatcher::new(project_root.root(), root_config, cells, ignore_specs)
-                    .buck_error_context("Creating watchman file watcher")?,
+                WatchmanFileWatcher::new(
+                    project_root.root(),
+                    root_config,
+                    cells,
+                    ignore_specs,
+                    cursor,
+                )
+                .buck_error_context("Creating watchman file watcher")?,
             )),
             "notify" => Ok(Arc::new(
                 NotifyFileWatcher::new(project_root, cells, ignore_specs)
@@ -77,7 +93,7 @@ impl dyn FileWatcher {
             )),
             #[cfg(fbcode_build)]
             "edenfs" => Ok(Arc::new(
-                EdenFsFileWatcher::new(fb, project_root, cells, ignore_specs)
+                EdenFsFileWatcher::new(fb, project_root, cells, ignore_specs, cursor)
                     .buck_error_context("Creating edenfs file watcher")?,
             )),
             other => Err(buck2_error!([], "Invalid buck2.file_watcher: {}", other)),
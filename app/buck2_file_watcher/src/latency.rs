@@ -0,0 +1,120 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Latency between the modification of a file and the daemon processing the watcher event for it,
+//! to judge "buck didn't see my change" reports against actual watcher lag.
+//!
+//! The notify watcher processes events as they arrive, so its latency is that of the notifications
+//! of the OS. Watchman events are only processed when a command syncs the watcher, so their latency
+//! also includes the time until that command started.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+
+/// Latencies are kept for this many of the most recent events.
+const MAX_SAMPLES: usize = 1000;
+
+/// Latencies of the most recent events processed by the daemon, for snapshots.
+static DAEMON_LATENCIES: Mutex<LatencySamples> = Mutex::new(LatencySamples::new());
+
+/// Percentiles of the latency of the most recent events processed by the daemon, if any.
+pub fn file_watcher_latency() -> Option<buck2_data::FileWatcherLatency> {
+    DAEMON_LATENCIES.lock().unwrap().percentiles()
+}
+
+/// How long ago the file at `path` was modified, or `None` if it no longer exists or was modified
+/// in the future, e.g. because of clock skew on a network file system.
+pub(crate) fn event_latency(path: &AbsNormPath, now: SystemTime) -> Option<Duration> {
+    let mtime = fs_util::symlink_metadata_if_exists(path)
+        .ok()??
+        .modified()
+        .ok()?;
+    now.duration_since(mtime).ok()
+}
+
+/// A bounded window of the most recent latencies, in milliseconds.
+#[derive(Default)]
+pub(crate) struct LatencySamples {
+    samples: VecDeque<u64>,
+}
+
+impl LatencySamples {
+    pub(crate) const fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub(crate) fn add(&mut self, latency: Duration) {
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples
+            .push_back(latency.as_millis().try_into().unwrap_or(u64::MAX));
+    }
+
+    pub(crate) fn percentiles(&self) -> Option<buck2_data::FileWatcherLatency> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut samples: Vec<u64> = self.samples.iter().copied().collect();
+        samples.sort_unstable();
+        // Nearest-rank percentile.
+        let percentile = |p: usize| samples[(samples.len() * p).div_ceil(100) - 1];
+        Some(buck2_data::FileWatcherLatency {
+            count: samples.len() as u64,
+            p50_ms: percentile(50),
+            p90_ms: percentile(90),
+            p99_ms: percentile(99),
+            max_ms: percentile(100),
+        })
+    }
+}
+
+/// Add the latency of an event processed by the daemon to the window reported in snapshots.
+pub(crate) fn record_daemon_latency(latency: Duration) {
+    DAEMON_LATENCIES.lock().unwrap().add(latency);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::latency::LatencySamples;
+
+    #[test]
+    fn test_percentiles() {
+        let mut samples = LatencySamples::new();
+        assert_eq!(None, samples.percentiles());
+
+        for ms in (1..=100).rev() {
+            samples.add(Duration::from_millis(ms));
+        }
+        let latency = samples.percentiles().unwrap();
+        assert_eq!(100, latency.count);
+        assert_eq!(50, latency.p50_ms);
+        assert_eq!(90, latency.p90_ms);
+        assert_eq!(99, latency.p99_ms);
+        assert_eq!(100, latency.max_ms);
+
+        let mut samples = LatencySamples::new();
+        samples.add(Duration::from_millis(7));
+        let latency = samples.percentiles().unwrap();
+        assert_eq!((7, 7, 7), (latency.p50_ms, latency.p99_ms, latency.max_ms));
+    }
+}
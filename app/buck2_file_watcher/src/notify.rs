@@ -11,6 +11,8 @@ use std::collections::HashMap;
 use std::mem;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use allocative::Allocative;
 use async_trait::async_trait;
@@ -36,8 +38,10 @@ use starlark_map::ordered_set::OrderedSet;
 use tracing::info;
 
 use crate::file_watcher::FileWatcher;
+use crate::latency::event_latency;
 use crate::mergebase::Mergebase;
 use crate::stats::FileWatcherStats;
+use crate::stats::MAX_LATENCY_RECORDS;
 
 #[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq, Hash, Allocative)]
 enum ChangeType {
@@ -83,6 +87,8 @@ impl ChangeType {
 struct NotifyFileData {
     ignored: u64,
     events: OrderedSet<(CellPath, ChangeType)>,
+    // Bounded by MAX_LATENCY_RECORDS
+    latencies: Vec<Duration>,
 }
 
 impl NotifyFileData {
@@ -90,6 +96,7 @@ impl NotifyFileData {
         Self {
             ignored: 0,
             events: OrderedSet::new(),
+            latencies: Vec::new(),
         }
     }
 
@@ -105,7 +112,8 @@ impl NotifyFileData {
         for path in event.paths {
             // Testing shows that we get absolute paths back from the `notify` library.
             // It's not documented though.
-            let path = root.relativize(AbsNormPath::new(&path)?)?;
+            let abs_path = AbsNormPath::new(&path)?;
+            let path = root.relativize(abs_path)?;
 
             // We ignore the buck-out prefix, as those are uninteresting events caused by us.
             // We also ignore other buck-out directories, as if you have two isolation dirs running at once, they are not interesting.
@@ -132,6 +140,15 @@ impl NotifyFileData {
                 self.ignored += 1;
             } else {
                 self.events.insert((cell_path, change_type));
+                // Directory events aren't caused by writing a file, and removed files have no
+                // modification time, so neither gives a latency.
+                if change_type != ChangeType::DirExistence
+                    && self.latencies.len() < MAX_LATENCY_RECORDS
+                {
+                    if let Some(latency) = event_latency(abs_path, SystemTime::now()) {
+                        self.latencies.push(latency);
+                    }
+                }
             }
         }
         Ok(())
@@ -161,6 +178,9 @@ impl NotifyFileData {
 
         let mut stats = FileWatcherStats::new(Default::default(), changed_paths.len());
         stats.add_ignored(self.ignored);
+        for latency in self.latencies {
+            stats.add_latency(latency);
+        }
         for path in changed_paths {
             // The event type and watcher kind are just made up, but that's not a big deal
             // since we only use this path open source, where we don't log the information to Scuba anyway.
@@ -7,8 +7,13 @@
  * of this source tree.
  */
 
+use std::time::Duration;
+
 use allocative::Allocative;
 
+use crate::latency::record_daemon_latency;
+use crate::latency::LatencySamples;
+
 /// We limit the number of file change records so we don't use too much memory
 /// or too much space in scribe.
 ///
@@ -16,6 +21,10 @@ use allocative::Allocative;
 /// Number needs to be < 850 or it is often bigger than a scribe message.
 const MAX_FILE_CHANGE_RECORDS: usize = 100;
 
+/// Measuring the latency of an event needs a stat of its file, so it is only measured for this
+/// many events of each sync.
+pub(crate) const MAX_LATENCY_RECORDS: usize = 100;
+
 #[derive(Allocative)]
 pub(crate) struct FileWatcherStats {
     stats: buck2_data::FileWatcherStats,
@@ -23,6 +32,9 @@ pub(crate) struct FileWatcherStats {
     changes: Vec<buck2_data::FileWatcherEvent>,
     // Did we not insert things into changes
     changes_missed: bool,
+    // Bounded by MAX_LATENCY_RECORDS
+    #[allocative(skip)]
+    latencies: LatencySamples,
 }
 
 impl FileWatcherStats {
@@ -33,9 +45,21 @@ impl FileWatcherStats {
             stats,
             changes,
             changes_missed: false,
+            latencies: LatencySamples::new(),
         }
     }
 
+    /// Whether the latency of the next event should be measured.
+    pub(crate) fn wants_latency(&self) -> bool {
+        self.latencies.len() < MAX_LATENCY_RECORDS
+    }
+
+    /// The daemon processed an event for a file which was modified `latency` ago.
+    pub(crate) fn add_latency(&mut self, latency: Duration) {
+        record_daemon_latency(latency);
+        self.latencies.add(latency);
+    }
+
     /// I have seen an event that I am ignoring
     pub(crate) fn add_ignored(&mut self, count: u64) {
         self.stats.events_total += count;
@@ -67,9 +91,11 @@ impl FileWatcherStats {
             mut stats,
             changes,
             changes_missed,
+            latencies,
         } = self;
 
         stats.events = changes;
+        stats.latency = latencies.percentiles();
         if changes_missed {
             let reason = format!(
                 "Too many files changed ({}, max {})",
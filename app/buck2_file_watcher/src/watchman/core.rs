@@ -12,6 +12,7 @@ use std::fmt::Display;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::Mutex;
 use std::time::Duration;
 
 use async_trait::async_trait;
@@ -23,6 +24,7 @@ use buck2_error::ErrorTag;
 use dupe::Dupe;
 use futures::future::Future;
 use serde::Deserialize;
+use serde::Serialize;
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::oneshot;
@@ -266,6 +268,30 @@ enum SyncableQueryCommand<T, P> {
 /// only an optimization and users should use `sync()` when they want events to have been processed.
 pub struct SyncableQuery<T, P> {
     control_tx: UnboundedSender<SyncableQueryCommand<T, P>>,
+    cursor: Arc<Mutex<Option<WatchmanCursor>>>,
+}
+
+/// The clock and mergebase a SyncableQuery has processed changes up to. A daemon replacing this
+/// one starts its query from here, so it only sees the changes made since.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct WatchmanCursor {
+    clock: String,
+    mergebase: Option<String>,
+}
+
+impl WatchmanCursor {
+    pub(crate) fn parse(cursor: &str) -> buck2_error::Result<Self> {
+        serde_json::from_str(cursor)
+            .with_buck_error_context(|| format!("Invalid Watchman cursor: `{}`", cursor))
+    }
+
+    pub(crate) fn mergebase(&self) -> Option<&String> {
+        self.mergebase.as_ref()
+    }
+
+    pub(crate) fn serialize(&self) -> buck2_error::Result<String> {
+        serde_json::to_string(self).buck_error_context("Error serializing Watchman cursor")
+    }
 }
 
 enum WatchmanSyncResult {
@@ -296,6 +322,11 @@ struct SyncableQueryHandler<T, P> {
     last_clock: ClockSpec,
     last_mergebase: Option<String>,
     mergebase_with: Option<String>,
+    /// Cursor handed over by the previous daemon, used in place of a null clock on the first
+    /// connection.
+    initial_cursor: Option<WatchmanCursor>,
+    /// Cursor of the changes processed so far, shared with the SyncableQuery.
+    cursor: Arc<Mutex<Option<WatchmanCursor>>>,
     control_rx: UnboundedReceiver<SyncableQueryCommand<T, P>>,
 }
 
@@ -386,6 +417,13 @@ where
             ),
         };
 
+        *self.cursor.lock().unwrap() = match &clock {
+            ClockSpec::StringClock(clock) => Some(WatchmanCursor {
+                clock: clock.clone(),
+                mergebase: new_mergebase.clone(),
+            }),
+            ClockSpec::UnixTimestamp(..) => None,
+        };
         self.last_mergebase = new_mergebase;
         self.last_clock = clock;
 
@@ -400,6 +438,14 @@ where
                 .await
                 .buck_error_context("Error reconnecting to Watchman")?,
         );
+        // Only the first connection can pick up where the previous daemon left off: after that,
+        // our own clock is the one that matters.
+        if let Some(cursor) = self.initial_cursor.take() {
+            self.last_clock = ClockSpec::StringClock(cursor.clock);
+            if self.mergebase_with.is_some() {
+                self.last_mergebase = cursor.mergebase;
+            }
+        }
         Ok(())
     }
 
@@ -556,6 +602,21 @@ where
         }
     }
 
+    /// The cursor of the changes synced so far, serialized for the daemon replacing this one.
+    /// Before the first sync, this is the cursor this query was created with.
+    pub(crate) fn cursor(&self) -> Option<String> {
+        let cursor = self.cursor.lock().unwrap().clone()?;
+        match cursor.serialize() {
+            Ok(cursor) => Some(cursor),
+            Err(e) => {
+                tracing::warn!("Failed to serialize Watchman cursor: {:#}", e);
+                None
+            }
+        }
+    }
+
+    /// `cursor` is the position of the query of a previous daemon, if any. When given, the first
+    /// sync reports the changes since then rather than a fresh instance.
     pub(crate) fn new(
         connector: Connector,
         path: impl AsRef<Path>,
@@ -563,6 +624,7 @@ where
         processor: Box<dyn SyncableQueryProcessor<Output = T, Payload = P>>,
         mergebase_with: Option<String>,
         empty_on_fresh_instance: bool,
+        cursor: Option<WatchmanCursor>,
     ) -> buck2_error::Result<SyncableQuery<T, P>> {
         let path = path.as_ref();
         let path = CanonicalPath::canonicalize(path)
@@ -586,20 +648,30 @@ where
         let (control_tx, control_rx) =
             tokio::sync::mpsc::unbounded_channel::<SyncableQueryCommand<T, P>>();
 
-        tokio::spawn(async move {
-            let handler = SyncableQueryHandler {
-                connector,
-                path,
-                query,
-                last_clock: ClockSpec::default(),
-                last_mergebase: None,
-                mergebase_with,
-                processor,
-                control_rx,
-            };
-            handler.run_loop().await
+        let shared_cursor = Arc::new(Mutex::new(cursor.clone()));
+
+        tokio::spawn({
+            let shared_cursor = shared_cursor.dupe();
+            async move {
+                let handler = SyncableQueryHandler {
+                    connector,
+                    path,
+                    query,
+                    last_clock: ClockSpec::default(),
+                    last_mergebase: None,
+                    mergebase_with,
+                    initial_cursor: cursor,
+                    cursor: shared_cursor,
+                    processor,
+                    control_rx,
+                };
+                handler.run_loop().await
+            }
         });
 
-        Ok(Self { control_tx })
+        Ok(Self {
+            control_tx,
+            cursor: shared_cursor,
+        })
     }
 }
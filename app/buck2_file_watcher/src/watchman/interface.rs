@@ -10,6 +10,7 @@
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use allocative::Allocative;
 use async_trait::async_trait;
@@ -17,14 +18,17 @@ use buck2_common::dice::file_ops::FileChangeTracker;
 use buck2_common::ignores::ignore_set::IgnoreSet;
 use buck2_common::legacy_configs::configs::LegacyBuckConfig;
 use buck2_common::legacy_configs::key::BuckconfigKeyRef;
+use buck2_common::vcs::detect_vcs;
+use buck2_common::vcs::RevisionDetails;
+use buck2_common::vcs::Vcs;
 use buck2_core::cells::name::CellName;
 use buck2_core::cells::CellResolver;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::project_rel_path::ProjectRelativePath;
 use buck2_core::rollout_percentage::RolloutPercentage;
 use buck2_error::BuckErrorContext;
 use buck2_events::dispatch::span_async;
-use buck2_util::process::async_background_command;
 use dice::DiceTransactionUpdater;
 use tracing::info;
 use tracing::warn;
@@ -33,10 +37,12 @@ use watchman_client::prelude::Connector;
 use watchman_client::prelude::FileType;
 
 use crate::file_watcher::FileWatcher;
+use crate::latency::event_latency;
 use crate::mergebase::Mergebase;
 use crate::stats::FileWatcherStats;
 use crate::watchman::core::SyncableQuery;
 use crate::watchman::core::SyncableQueryProcessor;
+use crate::watchman::core::WatchmanCursor;
 use crate::watchman::core::WatchmanEvent;
 use crate::watchman::core::WatchmanEventType;
 use crate::watchman::core::WatchmanKind;
@@ -47,8 +53,11 @@ struct WatchmanQueryProcessor {
     // a bug.
     cells: CellResolver,
     ignore_specs: HashMap<CellName, IgnoreSet>,
+    /// To find the modification time of changed files for their latency.
+    project_root: AbsNormPathBuf,
     empty_on_fresh_instance: bool,
-    report_global_rev: bool,
+    /// Set when `buck2.watchman_report_global_rev` is, to look up details of the mergebase.
+    vcs: Option<Arc<dyn Vcs>>,
     last_mergebase: Option<String>,
     last_mergebase_global_rev: Option<u64>,
     last_mergebase_timestamp: Option<u64>,
@@ -70,6 +79,11 @@ impl WatchmanQueryProcessor {
     ) -> buck2_error::Result<(buck2_data::FileWatcherStats, DiceTransactionUpdater)> {
         let mut handler = FileChangeTracker::new();
 
+        // On a fresh instance the events are everything changed since the mergebase, so how long
+        // ago they were modified says nothing about the watcher.
+        let measure_latency = !base_stats.fresh_instance;
+        let now = SystemTime::now();
+
         let mut stats = FileWatcherStats::new(base_stats, events.len());
 
         for ev in events {
@@ -92,6 +106,18 @@ impl WatchmanQueryProcessor {
                 }
             };
 
+            // Like the notify watcher, only take the latency of events for files that still exist.
+            if measure_latency
+                && stats.wants_latency()
+                && !matches!(ev.kind, WatchmanKind::Directory)
+                && !matches!(ev.event, WatchmanEventType::Delete)
+                && matches!(event, ChangeEvent::Watchman(_))
+            {
+                if let Some(latency) = event_latency(&self.project_root.join(path), now) {
+                    stats.add_latency(latency);
+                }
+            }
+
             self.process_one_change(path, event, &mut handler, &mut stats)?;
         }
 
@@ -210,38 +236,16 @@ fn find_first_valid_parent(mut path: &Path) -> Option<&ProjectRelativePath> {
     }
 }
 
-struct RevisionDetails {
-    global_rev: u64,
-    timestamp: u64,
-}
-
-async fn try_fetch_revision_details(hash: &str) -> Option<RevisionDetails> {
-    // There's a variety of ways in which this might go wrong: `PATH` is messed up, this somehow got
-    // turned on in a non-`hg` repo, etc. To make sure we don't fail any builds from this, ignore
-    // all errors.
-    let command = async_background_command("hg")
-        .args([
-            "log",
-            "-r",
-            hash,
-            "-T",
-            "{get(extras, \"global_rev\")}\n{date}",
-        ])
-        .env("HPGPLAIN", "1")
-        .output();
-    let output = tokio::time::timeout(std::time::Duration::from_millis(500), command)
-        .await
-        .ok()?
-        .ok()?;
-    if !output.status.success() {
-        return None;
-    }
-    let stdout = String::from_utf8(output.stdout).ok()?;
-    let (global_rev_string, time_string) = stdout.trim().split_once('\n')?;
-    Some(RevisionDetails {
-        global_rev: global_rev_string.parse::<u64>().ok()?,
-        timestamp: time_string.parse::<f64>().ok()? as u64, // hg returns the fractional seconds
-    })
+async fn try_fetch_revision_details(vcs: &dyn Vcs, hash: &str) -> Option<RevisionDetails> {
+    // There's a variety of ways in which this might go wrong: `PATH` is messed up, the hash is
+    // not known locally, etc. To make sure we don't fail any builds from this, ignore all errors.
+    tokio::time::timeout(
+        std::time::Duration::from_millis(500),
+        vcs.revision_details(hash),
+    )
+    .await
+    .ok()?
+    .ok()
 }
 
 #[async_trait]
@@ -301,9 +305,9 @@ impl SyncableQueryProcessor for WatchmanQueryProcessor {
         self.last_mergebase = mergebase.clone();
 
         if let Some(hash) = self.last_mergebase.as_ref() {
-            if self.report_global_rev {
-                if let Some(revision_details) = try_fetch_revision_details(hash).await {
-                    self.last_mergebase_global_rev = Some(revision_details.global_rev);
+            if let Some(vcs) = &self.vcs {
+                if let Some(revision_details) = try_fetch_revision_details(&**vcs, hash).await {
+                    self.last_mergebase_global_rev = revision_details.global_rev;
                     self.last_mergebase_timestamp = Some(revision_details.timestamp);
                 } else {
                     self.last_mergebase_global_rev = None;
@@ -357,7 +361,17 @@ impl WatchmanFileWatcher {
         root_config: &LegacyBuckConfig,
         cells: CellResolver,
         ignore_specs: HashMap<CellName, IgnoreSet>,
+        cursor: Option<&str>,
     ) -> buck2_error::Result<Self> {
+        // A bad cursor only costs us a fresh instance, so don't fail the daemon startup over it.
+        let cursor = cursor.and_then(|cursor| match WatchmanCursor::parse(cursor) {
+            Ok(cursor) => Some(cursor),
+            Err(e) => {
+                warn!("Ignoring the cursor of the previous daemon: {:#}", e);
+                None
+            }
+        });
+
         let watchman_merge_base = root_config
             .get(BuckconfigKeyRef {
                 section: "project",
@@ -389,6 +403,11 @@ impl WatchmanFileWatcher {
                 property: "watchman_report_global_rev",
             })?
             .unwrap_or(false);
+        let vcs = if report_global_rev {
+            detect_vcs(project_root.as_abs_path())?
+        } else {
+            None
+        };
 
         let query = SyncableQuery::new(
             Connector::new(),
@@ -401,14 +420,16 @@ impl WatchmanFileWatcher {
             Box::new(WatchmanQueryProcessor {
                 cells,
                 ignore_specs,
+                project_root: project_root.to_buf(),
                 empty_on_fresh_instance,
-                report_global_rev,
-                last_mergebase: None,
+                vcs,
+                last_mergebase: cursor.as_ref().and_then(|c| c.mergebase().cloned()),
                 last_mergebase_global_rev: None,
                 last_mergebase_timestamp: None,
             }),
             watchman_merge_base,
             empty_on_fresh_instance,
+            cursor,
         )?;
 
         Ok(Self { query })
@@ -438,4 +459,8 @@ impl FileWatcher for WatchmanFileWatcher {
         )
         .await
     }
+
+    async fn cursor(&self) -> Option<String> {
+        self.query.cursor()
+    }
 }
@@ -27,6 +27,7 @@ use watchman_client::prelude::FileType;
 
 use crate::watchman::core::SyncableQuery;
 use crate::watchman::core::SyncableQueryProcessor;
+use crate::watchman::core::WatchmanCursor;
 use crate::watchman::core::WatchmanEvent;
 
 struct TestQueryProcessor;
@@ -205,6 +206,7 @@ async fn test_syncable_query() -> buck2_error::Result<()> {
         Box::new(TestQueryProcessor),
         None,
         true,
+        None,
     )?;
 
     // Startup
@@ -233,3 +235,63 @@ async fn test_syncable_query() -> buck2_error::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_cursor_roundtrip() -> buck2_error::Result<()> {
+    let cursor = r#"{"clock":"c:123:45","mergebase":"abcdef"}"#;
+    let parsed = WatchmanCursor::parse(cursor)?;
+    assert_eq!(WatchmanCursor::parse(&parsed.serialize()?)?, parsed);
+    assert_matches!(WatchmanCursor::parse("c:123:45"), Err(..));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_syncable_query_cursor() -> buck2_error::Result<()> {
+    // See `test_syncable_query`.
+    if !cfg!(fbcode_build) {
+        return Ok(());
+    }
+
+    let tempdir = tempfile::tempdir()?;
+
+    let root = tempdir.path().join("root");
+    let watchman_dir = tempdir.path().join("watchman");
+    fs::create_dir(&watchman_dir)?;
+    fs::create_dir(&root)?;
+
+    let mut watchman_instance = spawn_watchman(&watchman_dir).await?;
+
+    let new_query = |cursor: Option<String>| -> buck2_error::Result<_> {
+        SyncableQuery::new(
+            Connector::default().unix_domain_socket(&watchman_instance.sock),
+            &root,
+            Expr::Any(vec![Expr::FileType(FileType::Regular)]),
+            Box::new(TestQueryProcessor),
+            None,
+            true,
+            cursor.map(|c| WatchmanCursor::parse(&c)).transpose()?,
+        )
+    };
+
+    let watchman_query = new_query(None)?;
+    assert_eq!(watchman_query.cursor(), None);
+    assert_eq!(watchman_query.sync(()).await?.0, Out::FreshInstance(vec![]));
+    let cursor = watchman_query.cursor();
+    assert!(cursor.is_some());
+    drop(watchman_query);
+
+    // A file created while no query is running is reported to the query taking over the cursor,
+    // and not as a fresh instance.
+    File::create(root.join("test"))?;
+    let watchman_query = new_query(cursor.clone())?;
+    assert_eq!(watchman_query.cursor(), cursor);
+    assert_eq!(
+        watchman_query.sync(()).await?.0,
+        Out::Files(vec!["test".into()])
+    );
+    assert_ne!(watchman_query.cursor(), cursor);
+
+    watchman_instance.shutdown().await?;
+
+    Ok(())
+}
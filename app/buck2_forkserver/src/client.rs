@@ -27,6 +27,7 @@ use tonic::Request;
 
 use crate::convert::decode_event_stream;
 use crate::run::decode_command_event_stream;
+use crate::run::CommandOutputObserver;
 use crate::run::GatherOutputStatus;
 
 #[derive(Clone, Dupe, Allocative)]
@@ -90,6 +91,7 @@ impl ForkserverClient {
         &self,
         req: buck2_forkserver_proto::CommandRequest,
         cancel: C,
+        observer: Option<&mut dyn CommandOutputObserver>,
     ) -> buck2_error::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
     where
         C: Future<Output = ()> + Send + 'static,
@@ -123,7 +125,7 @@ impl ForkserverClient {
             .buck_error_context("Error dispatching command to Forkserver")?
             .into_inner();
         let stream = decode_event_stream(stream);
-        decode_command_event_stream(stream).await
+        decode_command_event_stream(stream, observer).await
     }
 
     pub async fn set_log_filter(&self, log_filter: String) -> buck2_error::Result<()> {
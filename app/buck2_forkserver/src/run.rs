@@ -70,6 +70,18 @@ impl From<DecodedStatus> for GatherOutputStatus {
     }
 }
 
+/// Which output of a command some bytes were written to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandOutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Receives the output of a command as it is produced, in addition to it being gathered.
+pub trait CommandOutputObserver: Send {
+    fn output(&mut self, stream: CommandOutputStream, bytes: &Bytes);
+}
+
 #[derive(Debug)]
 pub(crate) enum CommandEvent {
     Stdout(Bytes),
@@ -269,6 +281,7 @@ where
 
 pub(crate) async fn decode_command_event_stream<S>(
     stream: S,
+    mut observer: Option<&mut dyn CommandOutputObserver>,
 ) -> buck2_error::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
 where
     S: Stream<Item = buck2_error::Result<CommandEvent>>,
@@ -280,8 +293,18 @@ where
 
     while let Some(event) = stream.try_next().await? {
         match event {
-            CommandEvent::Stdout(bytes) => stdout.extend(&bytes),
-            CommandEvent::Stderr(bytes) => stderr.extend(&bytes),
+            CommandEvent::Stdout(bytes) => {
+                if let Some(observer) = &mut observer {
+                    observer.output(CommandOutputStream::Stdout, &bytes);
+                }
+                stdout.extend(&bytes)
+            }
+            CommandEvent::Stderr(bytes) => {
+                if let Some(observer) = &mut observer {
+                    observer.output(CommandOutputStream::Stderr, &bytes);
+                }
+                stderr.extend(&bytes)
+            }
             CommandEvent::Exit(exit) => return Ok((exit, stdout, stderr)),
         }
     }
@@ -292,9 +315,12 @@ where
     ))
 }
 
+/// Run a command and gather its output. If an `observer` is passed, it is also given the output
+/// as it is produced.
 pub async fn gather_output<T>(
     cmd: Command,
     cancellation: T,
+    observer: Option<&mut dyn CommandOutputObserver>,
 ) -> buck2_error::Result<(GatherOutputStatus, Vec<u8>, Vec<u8>)>
 where
     T: Future<Output = buck2_error::Result<GatherOutputStatus>> + Send,
@@ -311,7 +337,7 @@ where
         DefaultKillProcess::default(),
         true,
     )?;
-    decode_command_event_stream(stream).await
+    decode_command_event_stream(stream, observer).await
 }
 
 /// Dependency injection for kill. We use this in testing.
@@ -431,7 +457,7 @@ mod tests {
         };
         cmd.args(["-c", "echo hello"]);
 
-        let (status, stdout, stderr) = gather_output(cmd, futures::future::pending()).await?;
+        let (status, stdout, stderr) = gather_output(cmd, futures::future::pending(), None).await?;
         assert!(matches!(status, GatherOutputStatus::Finished { exit_code, .. } if exit_code == 0));
         assert_eq!(str::from_utf8(&stdout)?.trim(), "hello");
         assert_eq!(stderr, b"");
@@ -439,6 +465,39 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_gather_output_observer() -> buck2_error::Result<()> {
+        #[derive(Default)]
+        struct Observer {
+            stdout: Vec<u8>,
+            stderr: Vec<u8>,
+        }
+
+        impl CommandOutputObserver for Observer {
+            fn output(&mut self, stream: CommandOutputStream, bytes: &Bytes) {
+                match stream {
+                    CommandOutputStream::Stdout => self.stdout.extend(bytes),
+                    CommandOutputStream::Stderr => self.stderr.extend(bytes),
+                }
+            }
+        }
+
+        let mut cmd = background_command("sh");
+        cmd.args(["-c", "echo hello && echo world >&2"]);
+
+        let mut observer = Observer::default();
+        let (status, stdout, stderr) =
+            gather_output(cmd, futures::future::pending(), Some(&mut observer)).await?;
+        assert!(matches!(status, GatherOutputStatus::Finished { exit_code, .. } if exit_code == 0));
+        assert_eq!(stdout, b"hello\n");
+        assert_eq!(stderr, b"world\n");
+        assert_eq!(observer.stdout, stdout);
+        assert_eq!(observer.stderr, stderr);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_gather_does_not_wait_for_children() -> buck2_error::Result<()> {
         // If we wait for sleep, this will time out.
@@ -460,6 +519,7 @@ mod tests {
         let (status, stdout, stderr) = gather_output(
             cmd,
             timeout_into_cancellation(Some(Duration::from_secs(timeout))),
+            None,
         )
         .await?;
         assert!(
@@ -491,6 +551,7 @@ mod tests {
         let (status, stdout, _stderr) = gather_output(
             cmd,
             timeout_into_cancellation(Some(Duration::from_secs(timeout))),
+            None,
         )
         .await?;
         assert!(
@@ -585,6 +646,7 @@ mod tests {
         let (_status, stdout, _stderr) = gather_output(
             cmd,
             timeout_into_cancellation(Some(Duration::from_secs(timeout))),
+            None,
         )
         .await?;
         let out = str::from_utf8(&stdout)?;
@@ -636,7 +698,8 @@ mod tests {
 
         let mut cmd = background_command("sh");
         cmd.arg("-c").arg("kill -KILL \"$$\"");
-        let (status, _stdout, _stderr) = gather_output(cmd, futures::future::pending()).await?;
+        let (status, _stdout, _stderr) =
+            gather_output(cmd, futures::future::pending(), None).await?;
 
         assert_matches!(
             status,
@@ -710,7 +773,7 @@ mod tests {
             true,
         )?;
 
-        let (status, _stdout, _stderr) = decode_command_event_stream(stream).await?;
+        let (status, _stdout, _stderr) = decode_command_event_stream(stream, None).await?;
         assert!(matches!(status, GatherOutputStatus::TimedOut(..)));
 
         assert!(*killed.lock().unwrap());
@@ -0,0 +1,179 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Always-on attribution of daemon CPU time to subsystems.
+//!
+//! Futures of a subsystem are wrapped with [`CpuSubsystem::attribute`], and the CPU time of the
+//! thread polling them is charged to the subsystem. Attribution is exclusive: when a future of a
+//! subsystem is polled within the poll of another one (e.g. a DICE task running analysis),
+//! only the innermost subsystem is charged for that time.
+
+use std::cell::Cell;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use dupe::Dupe;
+use pin_project::pin_project;
+
+#[derive(Copy, Clone, Dupe, Debug, Eq, PartialEq)]
+pub enum CpuSubsystem {
+    /// Evaluation of build files.
+    Loading,
+    /// Evaluation of rule implementations.
+    Analysis,
+    /// Action execution, excluding the work done by the processes it spawns.
+    ActionExecution,
+    /// The materializer.
+    Materializer,
+    /// DICE bookkeeping, i.e. time in DICE tasks not charged to another subsystem.
+    Dice,
+}
+
+static CPU_TIME_NS: [AtomicU64; CpuSubsystem::ALL.len()] =
+    [const { AtomicU64::new(0) }; CpuSubsystem::ALL.len()];
+
+thread_local! {
+    /// The subsystem charged for the CPU time of this thread, and the thread CPU time it is
+    /// charged since.
+    static CURRENT: Cell<Option<(CpuSubsystem, u64)>> = const { Cell::new(None) };
+}
+
+impl CpuSubsystem {
+    pub const ALL: [CpuSubsystem; 5] = [
+        CpuSubsystem::Loading,
+        CpuSubsystem::Analysis,
+        CpuSubsystem::ActionExecution,
+        CpuSubsystem::Materializer,
+        CpuSubsystem::Dice,
+    ];
+
+    /// CPU time charged to this subsystem since the daemon started.
+    pub fn cpu_time(self) -> Duration {
+        Duration::from_nanos(CPU_TIME_NS[self as usize].load(Ordering::Relaxed))
+    }
+
+    /// Charge the CPU time of the thread to this subsystem until the guard is dropped.
+    pub fn enter(self) -> CpuAttributionGuard {
+        let now = thread_cpu_time_ns();
+        let previous = CURRENT.with(|current| current.replace(Some((self, now))));
+        if let Some((subsystem, since)) = previous {
+            subsystem.charge(now.saturating_sub(since));
+        }
+        CpuAttributionGuard {
+            previous: previous.map(|(subsystem, _)| subsystem),
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Charge the CPU time spent polling the future to this subsystem.
+    pub fn attribute<F: Future>(self, future: F) -> CpuAttributed<F> {
+        CpuAttributed {
+            subsystem: self,
+            future,
+        }
+    }
+
+    fn charge(self, ns: u64) {
+        CPU_TIME_NS[self as usize].fetch_add(ns, Ordering::Relaxed);
+    }
+}
+
+/// Returned by [`CpuSubsystem::enter`]. Charges the time since to the subsystem when dropped.
+pub struct CpuAttributionGuard {
+    previous: Option<CpuSubsystem>,
+    /// Attribution uses thread CPU time.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for CpuAttributionGuard {
+    fn drop(&mut self) {
+        let now = thread_cpu_time_ns();
+        let current = CURRENT
+            .with(|current| current.replace(self.previous.map(|subsystem| (subsystem, now))));
+        if let Some((subsystem, since)) = current {
+            subsystem.charge(now.saturating_sub(since));
+        }
+    }
+}
+
+/// Returned by [`CpuSubsystem::attribute`].
+#[pin_project]
+pub struct CpuAttributed<F> {
+    subsystem: CpuSubsystem,
+    #[pin]
+    future: F,
+}
+
+impl<F: Future> Future for CpuAttributed<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _guard = this.subsystem.enter();
+        this.future.poll(cx)
+    }
+}
+
+#[cfg(unix)]
+fn thread_cpu_time_ns() -> u64 {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `ts` is a valid `timespec`.
+    if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) } != 0 {
+        return 0;
+    }
+    (ts.tv_sec as u64) * 1_000_000_000 + (ts.tv_nsec as u64)
+}
+
+#[cfg(not(unix))]
+fn thread_cpu_time_ns() -> u64 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hint::black_box;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use crate::cpu_attribution::CpuSubsystem;
+
+    fn spin(duration: Duration) {
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            black_box(());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_nested_attribution_is_exclusive() {
+        // Tests run in parallel in the same process, so only check the time increased.
+        let loading = CpuSubsystem::Loading.cpu_time();
+        let analysis = CpuSubsystem::Analysis.cpu_time();
+
+        futures::executor::block_on(CpuSubsystem::Analysis.attribute(async {
+            spin(Duration::from_millis(20));
+            CpuSubsystem::Loading
+                .attribute(async { spin(Duration::from_millis(20)) })
+                .await;
+        }));
+
+        assert!(CpuSubsystem::Loading.cpu_time() - loading >= Duration::from_millis(10));
+        assert!(CpuSubsystem::Analysis.cpu_time() - analysis >= Duration::from_millis(10));
+    }
+}
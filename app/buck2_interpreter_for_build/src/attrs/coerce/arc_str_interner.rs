@@ -8,21 +8,86 @@
  */
 
 use std::cell::RefCell;
+use std::fmt;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::Mutex;
 
+use allocative::Allocative;
 use buck2_util::arc_str::ArcStr;
 use dupe::Dupe;
+use hashbrown::hash_table;
 use hashbrown::raw::RawTable;
+use hashbrown::HashTable;
 
 use crate::attrs::coerce::str_hash::str_hash;
 
+const SHARDS: usize = 64;
+
+/// String interner shared by all the packages evaluated in a transaction, so that strings
+/// repeated across packages (compiler flags, labels in `deps`, etc.) are stored once.
+#[derive(Allocative)]
+pub struct ConcurrentArcStrInterner {
+    shards: Box<[Mutex<HashTable<(u64, ArcStr)>>]>,
+}
+
+impl Default for ConcurrentArcStrInterner {
+    fn default() -> Self {
+        ConcurrentArcStrInterner {
+            shards: (0..SHARDS).map(|_| Mutex::new(HashTable::new())).collect(),
+        }
+    }
+}
+
+impl Debug for ConcurrentArcStrInterner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConcurrentArcStrInterner")
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ConcurrentArcStrInterner {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl ConcurrentArcStrInterner {
+    pub fn intern(&self, s: &str) -> ArcStr {
+        self.intern_hashed(str_hash(s), s)
+    }
+
+    fn intern_hashed(&self, hash: u64, s: &str) -> ArcStr {
+        // Low bits select the bucket and high bits are the control bytes of the shard table,
+        // so pick the shard with the middle bits.
+        let shard = &self.shards[(hash >> 32) as usize % SHARDS];
+        match shard.lock().unwrap().entry(
+            hash,
+            |(h, v)| *h == hash && s == v.as_str(),
+            |(h, _v)| *h,
+        ) {
+            hash_table::Entry::Occupied(e) => e.get().1.dupe(),
+            hash_table::Entry::Vacant(e) => {
+                let value = ArcStr::from(s);
+                e.insert((hash, value.dupe()));
+                value
+            }
+        }
+    }
+}
+
+/// Per-package cache in front of the [`ConcurrentArcStrInterner`], which avoids locking for
+/// strings repeated within the package.
 pub(crate) struct ArcStrInterner {
     cache: RefCell<RawTable<(u64, ArcStr)>>,
+    global: Arc<ConcurrentArcStrInterner>,
 }
 
 impl ArcStrInterner {
-    pub(crate) fn new() -> ArcStrInterner {
+    pub(crate) fn new(global: Arc<ConcurrentArcStrInterner>) -> ArcStrInterner {
         ArcStrInterner {
             cache: RefCell::new(RawTable::new()),
+            global,
         }
     }
 
@@ -38,7 +103,7 @@ impl ArcStrInterner {
             return v.dupe();
         }
 
-        let value = ArcStr::from(s);
+        let value = self.global.intern_hashed(hash, s);
         cache.insert(hash, (hash, value.dupe()), |(h, _v)| *h);
         value
     }
@@ -47,12 +112,16 @@ impl ArcStrInterner {
 #[cfg(test)]
 mod tests {
     use std::ptr;
+    use std::sync::Arc;
+
+    use dupe::Dupe;
 
     use crate::attrs::coerce::arc_str_interner::ArcStrInterner;
+    use crate::attrs::coerce::arc_str_interner::ConcurrentArcStrInterner;
 
     #[test]
     fn test_arc_str_interner() {
-        let interner = ArcStrInterner::new();
+        let interner = ArcStrInterner::new(Arc::new(ConcurrentArcStrInterner::default()));
         let foo0 = interner.intern("foo");
         let foo1 = interner.intern("foo");
 
@@ -63,4 +132,14 @@ mod tests {
 
         assert!(ptr::eq(bar0.as_ptr(), bar1.as_ptr()));
     }
+
+    #[test]
+    fn test_arc_str_interner_shared_between_packages() {
+        let global = Arc::new(ConcurrentArcStrInterner::default());
+        let foo0 = ArcStrInterner::new(global.dupe()).intern("foo");
+        let foo1 = ArcStrInterner::new(global.dupe()).intern("foo");
+
+        assert!(ptr::eq(foo0.as_ptr(), foo1.as_ptr()));
+        assert!(ptr::eq(foo0.as_ptr(), global.intern("foo").as_ptr()));
+    }
 }
@@ -49,6 +49,6 @@ impl AttrTypeCoerce for EnumAttrType {
     }
 
     fn starlark_type(&self) -> TyMaybeSelect {
-        TyMaybeSelect::Basic(Ty::string())
+        TyMaybeSelect::Basic(Ty::enum_of(self.variants.iter().map(|v| v.as_str())))
     }
 }
@@ -9,6 +9,7 @@
 
 use buck2_node::attrs::attr_type::list::ListAttrType;
 use buck2_node::attrs::attr_type::list::ListLiteral;
+use buck2_node::attrs::attr_type::AttrTypeInner;
 use buck2_node::attrs::coerced_attr::CoercedAttr;
 use buck2_node::attrs::coercion_context::AttrCoercionContext;
 use buck2_node::attrs::configurable::AttrIsConfigurable;
@@ -17,6 +18,7 @@ use starlark::values::list::ListRef;
 use starlark::values::tuple::TupleRef;
 use starlark::values::Value;
 
+use crate::attrs::coerce::attr_type::source::coerce_source_paths;
 use crate::attrs::coerce::attr_type::ty_maybe_select::TyMaybeSelect;
 use crate::attrs::coerce::attr_type::AttrTypeExt;
 use crate::attrs::coerce::error::CoercionError;
@@ -30,6 +32,11 @@ impl AttrTypeCoerce for ListAttrType {
         value: Value,
     ) -> buck2_error::Result<CoercedAttr> {
         let list = coerce_list(value)?;
+        if let AttrTypeInner::Source(source) = &self.inner.0.inner {
+            if let Some(list) = coerce_source_paths(source, ctx, list) {
+                return Ok(CoercedAttr::List(ListLiteral(ctx.intern_list(list))));
+            }
+        }
         Ok(CoercedAttr::List(ListLiteral(ctx.intern_list(
             list.try_map(|v| (self.inner).coerce(configurable, ctx, *v))?,
         ))))
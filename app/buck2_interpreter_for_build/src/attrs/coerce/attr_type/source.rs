@@ -35,6 +35,25 @@ fn cleanup_path(value: &str) -> &str {
     if value == "." { "" } else { value }
 }
 
+/// Coerce a list of sources with a single call to `coerce_paths`, which may coerce them in
+/// parallel. Returns `None` if any item is not a plain path, or fails to coerce, so that the items
+/// are coerced one by one, with the proper error.
+pub(crate) fn coerce_source_paths(
+    attr: &SourceAttrType,
+    ctx: &dyn AttrCoercionContext,
+    values: &[Value],
+) -> Option<Vec<CoercedAttr>> {
+    let paths = values
+        .iter()
+        .map(|v| match v.unpack_str() {
+            Some(s) if !s.contains(':') => Some(cleanup_path(s)),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let paths = ctx.coerce_paths(&paths, attr.allow_directory).ok()?;
+    Some(paths.into_iter().map(CoercedAttr::SourceFile).collect())
+}
+
 impl AttrTypeCoerce for SourceAttrType {
     fn coerce_item(
         &self,
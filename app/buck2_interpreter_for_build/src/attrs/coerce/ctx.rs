@@ -11,6 +11,7 @@ use std::cell::RefCell;
 use std::fmt;
 use std::fmt::Debug;
 use std::sync::Arc;
+use std::thread;
 
 use buck2_common::package_listing::listing::PackageListing;
 use buck2_core::cells::name::CellName;
@@ -47,6 +48,7 @@ use tracing::info;
 
 use super::interner::AttrCoercionInterner;
 use crate::attrs::coerce::arc_str_interner::ArcStrInterner;
+use crate::attrs::coerce::arc_str_interner::ConcurrentArcStrInterner;
 use crate::attrs::coerce::str_hash::str_hash;
 
 #[derive(Debug, buck2_error::Error)]
@@ -66,6 +68,13 @@ enum BuildAttrCoercionContextError {
     SourceDirectoryIncludesSubPackage(PackageLabel, String, PackageRelativePathBuf),
 }
 
+/// Lists of paths shorter than this are coerced on the evaluating thread,
+/// spawning threads would cost more than it saves.
+const PARALLEL_COERCION_MIN_PATHS: usize = 1024;
+
+/// Max number of threads coercing the paths of a single attribute.
+const MAX_COERCION_THREADS: usize = 8;
+
 /// An incomplete attr coercion context. Will be replaced with a real one later.
 pub struct BuildAttrCoercionContext {
     /// Used to coerce targets
@@ -113,6 +122,7 @@ impl BuildAttrCoercionContext {
         enclosing_package: Option<(PackageLabel, PackageListing)>,
         package_boundary_exception: bool,
         global_label_interner: Arc<ConcurrentTargetLabelInterner>,
+        global_str_interner: Arc<ConcurrentArcStrInterner>,
     ) -> Self {
         Self {
             cell_resolver,
@@ -123,7 +133,7 @@ impl BuildAttrCoercionContext {
             alloc: Bump::new(),
             global_label_interner,
             label_cache: RefCell::new(HashTable::new()),
-            str_interner: ArcStrInterner::new(),
+            str_interner: ArcStrInterner::new(global_str_interner),
             list_interner: AttrCoercionInterner::new(),
             dict_interner: AttrCoercionInterner::new(),
             select_interner: AttrCoercionInterner::new(),
@@ -143,6 +153,8 @@ impl BuildAttrCoercionContext {
             None,
             false,
             global_label_interner,
+            // Only default values of attributes are coerced without a package.
+            Arc::new(ConcurrentArcStrInterner::default()),
         )
     }
 
@@ -152,6 +164,7 @@ impl BuildAttrCoercionContext {
         enclosing_package: (PackageLabel, PackageListing),
         package_boundary_exception: bool,
         global_label_interner: Arc<ConcurrentTargetLabelInterner>,
+        global_str_interner: Arc<ConcurrentArcStrInterner>,
     ) -> Self {
         Self::new(
             cell_resolver,
@@ -160,6 +173,7 @@ impl BuildAttrCoercionContext {
             Some(enclosing_package),
             package_boundary_exception,
             global_label_interner,
+            global_str_interner,
         )
     }
 
@@ -196,6 +210,56 @@ impl BuildAttrCoercionContext {
     }
 }
 
+fn coerce_path_in_package(
+    package: &PackageLabel,
+    listing: &PackageListing,
+    package_boundary_exception: bool,
+    value: &str,
+    allow_directory: bool,
+) -> buck2_error::Result<CoercedPath> {
+    let path = <&PackageRelativePath>::try_from(value)?;
+
+    if let Some(path) = listing.get_file(path) {
+        return Ok(CoercedPath::File(path));
+    }
+
+    // TODO: Make the warnings below into errors
+    if let Some(path) = listing.get_dir(path) {
+        if !allow_directory {
+            return Err(BuildAttrCoercionContextError::SourceFileIsDirectory(
+                package.dupe(),
+                value.to_owned(),
+            )
+            .into());
+        } else if let Some(subpackage) = listing.subpackages_within(&path).next() {
+            let e = BuildAttrCoercionContextError::SourceDirectoryIncludesSubPackage(
+                package.dupe(),
+                value.to_owned(),
+                subpackage.to_owned(),
+            );
+            if package_boundary_exception {
+                info!("{} (could be due to a package boundary violation)", e);
+            } else {
+                soft_error!("source_directory_includes_subpackage", e.into())?;
+            }
+        }
+        let files = listing.files_within(&path).duped().collect();
+        Ok(CoercedPath::Directory(Box::new(CoercedDirectory {
+            dir: path,
+            files,
+        })))
+    } else {
+        let e = BuildAttrCoercionContextError::SourceFileMissing(package.dupe(), value.to_owned());
+        if package_boundary_exception {
+            info!("{} (could be due to a package boundary violation)", e);
+        } else {
+            soft_error!("source_file_missing", e.into(), quiet: true)?;
+        }
+
+        Ok(CoercedPath::File(path.to_arc()))
+    }
+}
+
 impl AttrCoercionContext for BuildAttrCoercionContext {
     fn coerce_providers_label(&self, value: &str) -> buck2_error::Result<ProvidersLabel> {
         let hash = str_hash(value);
@@ -243,49 +307,63 @@ impl AttrCoercionContext for BuildAttrCoercionContext {
     }
 
     fn coerce_path(&self, value: &str, allow_directory: bool) -> buck2_error::Result<CoercedPath> {
-        let path = <&PackageRelativePath>::try_from(value)?;
         let (package, listing) = self.require_enclosing_package(value)?;
+        coerce_path_in_package(
+            package,
+            listing,
+            self.package_boundary_exception,
+            value,
+            allow_directory,
+        )
+    }
 
-        if let Some(path) = listing.get_file(path) {
-            return Ok(CoercedPath::File(path));
+    fn coerce_paths(
+        &self,
+        values: &[&str],
+        allow_directory: bool,
+    ) -> buck2_error::Result<Vec<CoercedPath>> {
+        let threads = thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(MAX_COERCION_THREADS);
+        if values.len() < PARALLEL_COERCION_MIN_PATHS || threads <= 1 {
+            return values
+                .iter()
+                .map(|value| self.coerce_path(value, allow_directory))
+                .collect();
         }
 
-        // TODO: Make the warnings below into errors
-        if let Some(path) = listing.get_dir(path) {
-            if !allow_directory {
-                return Err(BuildAttrCoercionContextError::SourceFileIsDirectory(
-                    package.dupe(),
-                    value.to_owned(),
-                )
-                .into());
-            } else if let Some(subpackage) = listing.subpackages_within(&path).next() {
-                let e = BuildAttrCoercionContextError::SourceDirectoryIncludesSubPackage(
-                    package.dupe(),
-                    value.to_owned(),
-                    subpackage.to_owned(),
+        let (package, listing) = self.require_enclosing_package(values[0])?;
+        let package_boundary_exception = self.package_boundary_exception;
+        thread::scope(|s| {
+            let chunks: Vec<_> = values
+                .chunks(values.len().div_ceil(threads))
+                .map(|chunk| {
+                    s.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|value| {
+                                coerce_path_in_package(
+                                    package,
+                                    listing,
+                                    package_boundary_exception,
+                                    value,
+                                    allow_directory,
+                                )
+                            })
+                            .collect::<buck2_error::Result<Vec<_>>>()
+                    })
+                })
+                .collect();
+            let mut paths = Vec::with_capacity(values.len());
+            for chunk in chunks {
+                paths.extend(
+                    chunk
+                        .join()
+                        .unwrap_or_else(|e| std::panic::resume_unwind(e))?,
                 );
-                if self.package_boundary_exception {
-                    info!("{} (could be due to a package boundary violation)", e);
-                } else {
-                    soft_error!("source_directory_includes_subpackage", e.into())?;
-                }
             }
-            let files = listing.files_within(&path).duped().collect();
-            Ok(CoercedPath::Directory(Box::new(CoercedDirectory {
-                dir: path,
-                files,
-            })))
-        } else {
-            let e =
-                BuildAttrCoercionContextError::SourceFileMissing(package.dupe(), value.to_owned());
-            if self.package_boundary_exception {
-                info!("{} (could be due to a package boundary violation)", e);
-            } else {
-                soft_error!("source_file_missing", e.into(), quiet: true)?;
-            }
-
-            Ok(CoercedPath::File(path.to_arc()))
-        }
+            Ok(paths)
+        })
     }
 
     fn coerce_target_pattern(
@@ -32,6 +32,7 @@ use starlark::eval::Evaluator;
 use starlark::syntax::AstModule;
 use starlark::values::Value;
 
+use crate::attrs::coerce::arc_str_interner::ConcurrentArcStrInterner;
 use crate::attrs::coerce::ctx::BuildAttrCoercionContext;
 use crate::interpreter::buckconfig::LegacyConfigsViewForStarlark;
 use crate::interpreter::build_context::BuildContext;
@@ -68,6 +69,7 @@ pub fn coercion_ctx_listing(package_listing: PackageListing) -> impl AttrCoercio
         (package, package_listing),
         false,
         Arc::new(ConcurrentTargetLabelInterner::default()),
+        Arc::new(ConcurrentArcStrInterner::default()),
     )
 }
 
@@ -26,6 +26,7 @@ use dupe::Dupe;
 use starlark::environment::Globals;
 use starlark::environment::GlobalsBuilder;
 
+use crate::attrs::coerce::arc_str_interner::ConcurrentArcStrInterner;
 use crate::attrs::coerce::ctx::BuildAttrCoercionContext;
 use crate::interpreter::cell_info::InterpreterCellInfo;
 use crate::interpreter::functions::host_info::HostInfo;
@@ -70,6 +71,8 @@ pub struct BuildInterpreterConfiguror {
     record_target_call_stack: bool,
     skip_targets_with_duplicate_names: bool,
     global_target_interner: Arc<ConcurrentTargetLabelInterner>,
+    /// Strings of coerced attributes, shared by the packages evaluated with this configuror.
+    global_str_interner: Arc<ConcurrentArcStrInterner>,
     /// For test.
     additional_globals: Option<AdditionalGlobalsFn>,
 }
@@ -92,6 +95,7 @@ impl BuildInterpreterConfiguror {
             skip_targets_with_duplicate_names,
             additional_globals,
             global_target_interner,
+            global_str_interner: Arc::new(ConcurrentArcStrInterner::default()),
         }))
     }
 
@@ -143,6 +147,7 @@ impl BuildInterpreterConfiguror {
             (buildfile_path.package().dupe(), package_listing.dupe()),
             package_boundary_exception,
             self.global_target_interner.dupe(),
+            self.global_str_interner.dupe(),
         );
 
         let imports = loaded_modules.imports().cloned().collect();
@@ -56,6 +56,7 @@ use starlark::codemap::FileSpan;
 use starlark::environment::FrozenModule;
 use starlark::environment::Module;
 use starlark::syntax::AstModule;
+use starlark::typing::TypingProfile;
 use starlark::values::any_complex::StarlarkAnyComplex;
 use starlark::values::OwnedFrozenRef;
 
@@ -93,6 +94,29 @@ enum StarlarkPeakMemorySoftError {
     CloseToThreshold(BuildFilePath, HumanizedBytes, HumanizedBytes, String),
 }
 
+#[derive(Debug, buck2_error::Error)]
+#[error(
+    "Invalid value for buckconfig `[buck2] typing_profile`: `{0}`. Expected one of `lenient`, `strict` or `pedantic`"
+)]
+#[buck2(input)]
+struct TypingProfileConfigError(String);
+
+/// How strictly to typecheck files of the current cell, from `buck2.typing_profile`.
+fn typing_profile(
+    buckconfigs: &mut dyn BuckConfigsViewForStarlark,
+) -> buck2_error::Result<TypingProfile> {
+    let value = buckconfigs.read_current_cell_config(BuckconfigKeyRef {
+        section: "buck2",
+        property: "typing_profile",
+    })?;
+    match value.as_deref() {
+        None | Some("") | Some("lenient") => Ok(TypingProfile::Lenient),
+        Some("strict") => Ok(TypingProfile::Strict),
+        Some("pedantic") => Ok(TypingProfile::Pedantic),
+        Some(v) => Err(TypingProfileConfigError(v.to_owned()).into()),
+    }
+}
+
 /// A ParseData includes the parsed AST and a list of the imported files.
 ///
 /// The imports are under a separate Arc so that that can be shared with
@@ -490,6 +514,11 @@ impl InterpreterForCell {
     ) -> buck2_error::Result<EvalResult> {
         let import = extra_context.starlark_path();
         let globals = self.global_state.globals();
+        let typing_profile = if unstable_typecheck {
+            typing_profile(buckconfigs)?
+        } else {
+            TypingProfile::Lenient
+        };
         let file_loader =
             InterpreterFileLoader::new(loaded_modules, Arc::new(self.load_resolver(import)));
         let host_info = self.global_state.configuror.host_info();
@@ -507,6 +536,7 @@ impl InterpreterForCell {
             let (mut eval, is_profiling_enabled_by_provider) = eval_provider.make(env)?;
             is_profiling_enabled = is_profiling_enabled_by_provider;
             eval.enable_static_typechecking(unstable_typecheck);
+            eval.set_typing_profile(typing_profile);
             eval.set_print_handler(&print);
             eval.set_soft_error_handler(&Buck2StarlarkSoftErrorHandler);
             eval.set_loader(&file_loader);
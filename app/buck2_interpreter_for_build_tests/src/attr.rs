@@ -17,11 +17,13 @@ use buck2_core::package::package_relative_path::PackageRelativePathBuf;
 use buck2_core::package::PackageLabel;
 use buck2_core::plugins::PluginKindSet;
 use buck2_core::target::label::interner::ConcurrentTargetLabelInterner;
+use buck2_interpreter_for_build::attrs::coerce::arc_str_interner::ConcurrentArcStrInterner;
 use buck2_interpreter_for_build::attrs::coerce::attr_type::AttrTypeExt;
 use buck2_interpreter_for_build::attrs::coerce::ctx::BuildAttrCoercionContext;
 use buck2_interpreter_for_build::interpreter::testing::cells;
 use buck2_interpreter_for_build::interpreter::testing::Tester;
 use buck2_node::attrs::attr_type::AttrType;
+use buck2_node::attrs::coerced_attr::CoercedAttr;
 use buck2_node::attrs::coercion_context::AttrCoercionContext;
 use buck2_node::attrs::configurable::AttrIsConfigurable;
 use buck2_node::attrs::hacks::value_to_string;
@@ -121,6 +123,7 @@ fn attr_coercer_coerces() -> buck2_error::Result<()> {
         enclosing_package,
         false,
         Arc::new(ConcurrentTargetLabelInterner::default()),
+        Arc::new(ConcurrentArcStrInterner::default()),
     );
     let label_coercer = AttrType::dep(ProviderIdSet::EMPTY, PluginKindSet::EMPTY);
     let string_coercer = AttrType::string();
@@ -296,6 +299,7 @@ fn coercing_src_to_path_works() -> buck2_error::Result<()> {
         ),
         false,
         Arc::new(ConcurrentTargetLabelInterner::default()),
+        Arc::new(ConcurrentArcStrInterner::default()),
     );
     let no_package_ctx = BuildAttrCoercionContext::new_no_package(
         cell_resolver,
@@ -329,3 +333,48 @@ fn coercing_src_to_path_works() -> buck2_error::Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn coercing_many_srcs_works() -> buck2_error::Result<()> {
+    let cell_resolver = cells(None).unwrap().1;
+    let cell_alias_resolver = cells(None).unwrap().0;
+    let package = PackageLabel::new(
+        CellName::testing_new("root"),
+        CellRelativePath::unchecked_new("foo/bar"),
+    );
+    let files: Vec<String> = (0..5000).map(|i| format!("src/{i}.cpp")).collect();
+    let files: Vec<&str> = files.iter().map(|f| f.as_str()).collect();
+    let package_ctx = BuildAttrCoercionContext::new_with_package(
+        cell_resolver,
+        cell_alias_resolver,
+        (package, PackageListing::testing_files(&files)),
+        false,
+        Arc::new(ConcurrentTargetLabelInterner::default()),
+        Arc::new(ConcurrentArcStrInterner::default()),
+    );
+
+    let paths = package_ctx.coerce_paths(&files, false)?;
+    assert_eq!(files.len(), paths.len());
+    for (file, path) in files.iter().zip(&paths) {
+        assert_eq!(*file, path.path().as_str());
+    }
+
+    let heap = Heap::new();
+    let srcs = heap.alloc(files.clone());
+    let coerced = AttrType::list(AttrType::source(false)).coerce(
+        AttrIsConfigurable::Yes,
+        &package_ctx,
+        srcs,
+    )?;
+    let CoercedAttr::List(coerced) = coerced else {
+        panic!("expected a list, got {coerced:?}");
+    };
+    assert_eq!(files.len(), coerced.len());
+    for (file, attr) in files.iter().zip(coerced.iter()) {
+        let CoercedAttr::SourceFile(path) = attr else {
+            panic!("expected a source file, got {attr:?}");
+        };
+        assert_eq!(*file, path.path().as_str());
+    }
+    Ok(())
+}
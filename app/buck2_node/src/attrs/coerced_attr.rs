@@ -59,6 +59,7 @@ use crate::attrs::display::AttrDisplayWithContext;
 use crate::attrs::display::AttrDisplayWithContextExt;
 use crate::attrs::fmt_context::AttrFmtContext;
 use crate::attrs::json::ToJsonWithContext;
+use crate::attrs::select_cache::SELECT_CACHE;
 use crate::attrs::serialize::AttrSerializeWithContext;
 use crate::attrs::traversal::CoercedAttrTraversal;
 use crate::configuration::resolved::ConfigurationSettingKey;
@@ -555,13 +556,24 @@ impl CoercedAttr {
     ) -> buck2_error::Result<&'a CoercedAttr> {
         let CoercedSelector { entries, default } = select;
         let resolved_cfg_settings = ctx.resolved_cfg_settings();
-        let resolved_entries = entries.iter().filter_map(|(k, v)| {
-            resolved_cfg_settings
-                .setting_matches(k)
-                .map(|conf| (k, conf, v))
-        });
-        if let Some(v) = Self::select_the_most_specific(resolved_entries)? {
-            Ok(v)
+        let selected = SELECT_CACHE.resolve(&ctx.cfg(), resolved_cfg_settings, entries, || {
+            let resolved_entries = entries.iter().filter_map(|(k, v)| {
+                resolved_cfg_settings
+                    .setting_matches(k)
+                    .map(|conf| (k, conf, v))
+            });
+            match Self::select_the_most_specific(resolved_entries)? {
+                Some(v) => Ok(Some(
+                    entries
+                        .iter()
+                        .position(|(_, e)| std::ptr::eq(e, v))
+                        .internal_error("selected value is not an entry of the select")?,
+                )),
+                None => Ok(None),
+            }
+        })?;
+        if let Some(i) = selected {
+            Ok(&entries[i].1)
         } else {
             default.as_ref().ok_or_else(|| {
                 buck2_error!(
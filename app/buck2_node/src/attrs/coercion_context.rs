@@ -74,6 +74,19 @@ pub trait AttrCoercionContext {
     /// Attempt to convert a string into a BuckPath
     fn coerce_path(&self, value: &str, allow_directory: bool) -> buck2_error::Result<CoercedPath>;
 
+    /// Convert many strings into BuckPaths, e.g. the `srcs` of a huge target.
+    /// Implementations may coerce them in parallel.
+    fn coerce_paths(
+        &self,
+        values: &[&str],
+        allow_directory: bool,
+    ) -> buck2_error::Result<Vec<CoercedPath>> {
+        values
+            .iter()
+            .map(|value| self.coerce_path(value, allow_directory))
+            .collect()
+    }
+
     fn coerce_target_pattern(
         &self,
         pattern: &str,
@@ -0,0 +1,193 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Daemon-wide memoization of `select()` resolution.
+//!
+//! Generated `BUCK` files often repeat the same `select()` in many targets, which are configured
+//! in a handful of configurations. Resolving a `select()` compares the config settings of all
+//! matching keys to find the most specific one, so the result, the index of the selected entry,
+//! is memoized by the keys of the `select()` and the configuration.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+use buck2_core::configuration::pair::ConfigurationNoExec;
+use dupe::Dupe;
+use once_cell::sync::Lazy;
+use smallvec::SmallVec;
+
+use crate::attrs::coerced_attr::CoercedAttr;
+use crate::configuration::resolved::ConfigurationNode;
+use crate::configuration::resolved::ConfigurationSettingKey;
+use crate::configuration::resolved::ResolvedConfigurationSettings;
+
+/// The cache is sharded to limit contention, configuration being highly parallel.
+const SHARDS: usize = 64;
+
+/// A shard is cleared when it reaches this many entries, to bound memory.
+const MAX_SHARD_ENTRIES: usize = 4096;
+
+pub(crate) static SELECT_CACHE: Lazy<SelectCache> = Lazy::new(SelectCache::new);
+
+/// Statistics of the `select()` resolution cache since the daemon started.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SelectCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+pub fn select_cache_stats() -> SelectCacheStats {
+    SELECT_CACHE.stats()
+}
+
+struct CachedSelect {
+    keys: Box<[ConfigurationSettingKey]>,
+    /// The resolved config settings the result was computed with. These are compared by pointer,
+    /// so a config setting that was recomputed, e.g. after a change to `.buckconfig`, is a miss.
+    nodes: Box<[ConfigurationNode]>,
+    /// Index of the selected entry, `None` if no entry matched.
+    selected: Option<usize>,
+}
+
+pub(crate) struct SelectCache {
+    shards: Box<[Mutex<HashMap<u64, SmallVec<[CachedSelect; 1]>>>]>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl SelectCache {
+    fn new() -> Self {
+        Self {
+            shards: (0..SHARDS).map(|_| Mutex::new(HashMap::new())).collect(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn stats(&self) -> SelectCacheStats {
+        SelectCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Index of the entry `compute` selects, computing it only if it is not cached for these
+    /// entries in this configuration.
+    pub(crate) fn resolve(
+        &self,
+        cfg: &ConfigurationNoExec,
+        settings: &ResolvedConfigurationSettings,
+        entries: &[(ConfigurationSettingKey, CoercedAttr)],
+        compute: impl FnOnce() -> buck2_error::Result<Option<usize>>,
+    ) -> buck2_error::Result<Option<usize>> {
+        let mut hasher = DefaultHasher::new();
+        cfg.hash(&mut hasher);
+        for (key, _) in entries {
+            key.hash(&mut hasher);
+        }
+        let digest = hasher.finish();
+        let shard = &self.shards[(digest % SHARDS as u64) as usize];
+
+        let is_match = |cached: &CachedSelect| {
+            cached.keys.len() == entries.len()
+                && cached
+                    .keys
+                    .iter()
+                    .zip(entries)
+                    .all(|(k, (key, _))| k == key)
+                && cached
+                    .nodes
+                    .iter()
+                    .zip(entries)
+                    .all(|(node, (key, _))| node.ptr_eq(settings.setting_node(key)))
+        };
+        if let Some(bucket) = shard.lock().unwrap().get(&digest) {
+            if let Some(cached) = bucket.iter().find(|cached| is_match(cached)) {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(cached.selected);
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let selected = compute()?;
+        let cached = CachedSelect {
+            keys: entries.iter().map(|(key, _)| key.dupe()).collect(),
+            nodes: entries
+                .iter()
+                .map(|(key, _)| settings.setting_node(key).dupe())
+                .collect(),
+            selected,
+        };
+
+        let mut shard = shard.lock().unwrap();
+        if shard.len() >= MAX_SHARD_ENTRIES {
+            shard.clear();
+        }
+        let bucket = shard.entry(digest).or_default();
+        // Replace the entry computed with outdated config settings, if any.
+        bucket.retain(|c| c.keys != cached.keys);
+        bucket.push(cached);
+        Ok(selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use starlark_map::unordered_map::UnorderedMap;
+
+    use super::*;
+    use crate::attrs::attr_type::bool::BoolLiteral;
+
+    #[test]
+    fn test_resolve() {
+        let cache = SelectCache::new();
+        let cfg = ConfigurationNoExec::testing_new();
+        let key = ConfigurationSettingKey::testing_parse("config//:linux");
+        let entries = [(key.dupe(), CoercedAttr::Bool(BoolLiteral(true)))];
+        let settings = |node: &ConfigurationNode| {
+            ResolvedConfigurationSettings::new(UnorderedMap::from_iter([(key.dupe(), node.dupe())]))
+        };
+        let node = ConfigurationNode::new(None);
+
+        let computed = Cell::new(0);
+        let compute = || {
+            computed.set(computed.get() + 1);
+            Ok(Some(0))
+        };
+
+        assert_eq!(
+            cache
+                .resolve(&cfg, &settings(&node), &entries, compute)
+                .unwrap(),
+            Some(0)
+        );
+        assert_eq!(
+            cache
+                .resolve(&cfg, &settings(&node), &entries, compute)
+                .unwrap(),
+            Some(0)
+        );
+        assert_eq!(computed.get(), 1);
+        assert_eq!(cache.stats(), SelectCacheStats { hits: 1, misses: 1 });
+
+        // Recomputed config settings are a miss.
+        let node = ConfigurationNode::new(None);
+        cache
+            .resolve(&cfg, &settings(&node), &entries, compute)
+            .unwrap();
+        assert_eq!(computed.get(), 2);
+    }
+}
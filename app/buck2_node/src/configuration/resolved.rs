@@ -85,10 +85,14 @@ impl ResolvedConfigurationSettings {
     }
 
     pub fn setting_matches(&self, key: &ConfigurationSettingKey) -> Option<&ConfigSettingData> {
+        self.setting_node(key).configuration_data()
+    }
+
+    pub(crate) fn setting_node(&self, key: &ConfigurationSettingKey) -> &ConfigurationNode {
         let Some(configuration_node) = self.settings.get(key) else {
             panic!("unresolved configuration setting: `{key}`");
         };
-        configuration_node.configuration_data()
+        configuration_node
     }
 }
 
@@ -110,4 +114,8 @@ impl ConfigurationNode {
     pub fn configuration_data(&self) -> Option<&ConfigSettingData> {
         self.0.config_setting.as_ref()
     }
+
+    pub(crate) fn ptr_eq(&self, other: &ConfigurationNode) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
 }
@@ -30,4 +30,5 @@ pub mod rule;
 pub mod rule_type;
 pub mod super_package;
 pub mod target_calculation;
+pub mod target_label_filter;
 pub mod visibility;
@@ -244,6 +244,41 @@ impl ConfiguredTargetNode {
         )
     }
 
+    /// Creates a minimal ConfiguredTargetNode with the given deps and no attributes.
+    pub fn testing_with_deps(
+        name: ConfiguredTargetLabel,
+        rule_type: &str,
+        deps: Vec<ConfiguredTargetNode>,
+    ) -> Self {
+        use crate::nodes::unconfigured::testing::TargetNodeExt;
+
+        let rule_type = RuleType::Starlark(Arc::new(StarlarkRuleType {
+            path: BzlOrBxlPath::Bzl(ImportPath::testing_new("cell//pkg:rules.bzl")),
+            name: rule_type.to_owned(),
+        }));
+
+        Self::new(
+            name.dupe(),
+            TargetNode::testing_new(
+                name.unconfigured().dupe(),
+                rule_type,
+                Vec::new(),
+                Vec::new(),
+                None,
+            ),
+            ResolvedConfiguration::new(
+                ConfigurationNoExec::new(name.cfg().dupe()),
+                ResolvedConfigurationSettings::empty(),
+            ),
+            OrderedMap::new(),
+            ExecutionPlatformResolution::new(None, Vec::new()),
+            deps,
+            Vec::new(),
+            OrderedMap::new(),
+            PluginLists::new(),
+        )
+    }
+
     pub fn new(
         name: ConfiguredTargetLabel,
         target_node: TargetNode,
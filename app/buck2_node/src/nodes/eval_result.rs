@@ -110,6 +110,11 @@ impl MissingTargets {
         message
     }
 
+    /// The missing targets, for callers that report them in their own way.
+    pub fn into_missing_targets(self) -> Vec<TargetLabel> {
+        self.missing_targets
+    }
+
     /// Warning message emitted when missing targets are skipped.
     pub fn missing_targets_warning(self) -> String {
         Self::gen_missing_target_warning(self.missing_targets)
@@ -0,0 +1,135 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+use crate::attrs::coerced_attr::CoercedAttr;
+use crate::attrs::inspect_options::AttrInspectOptions;
+use crate::nodes::unconfigured::TargetNode;
+
+/// Filters the targets expanded from command line patterns by the values of their `labels`
+/// attribute (`--include-labels` and `--exclude-labels`).
+///
+/// Targets are filtered before configuration, so labels added by a `select()` count as
+/// labels of the target whatever the configuration: a target with
+/// `labels = select({"//:linux": ["flaky"], "DEFAULT": []})` is excluded by
+/// `--exclude-labels=flaky` and included by `--include-labels=flaky` on all platforms.
+#[derive(Debug, Clone, Default)]
+pub struct TargetLabelFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl TargetLabelFilter {
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> Self {
+        Self { include, exclude }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether a target should be kept.
+    pub fn matches(&self, node: &TargetNode) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+
+        let mut labels = Vec::new();
+        if let Some(attr) = node.attr_or_none("labels", AttrInspectOptions::All) {
+            collect_labels(attr.value, &mut labels);
+        }
+        self.matches_labels(&labels)
+    }
+
+    fn matches_labels(&self, labels: &[&str]) -> bool {
+        if self.exclude.iter().any(|l| labels.contains(&l.as_str())) {
+            return false;
+        }
+        self.include.is_empty() || self.include.iter().any(|l| labels.contains(&l.as_str()))
+    }
+}
+
+/// Labels in the value of a `labels` attribute, in all the branches of its selects.
+fn collect_labels<'a>(attr: &'a CoercedAttr, labels: &mut Vec<&'a str>) {
+    match attr {
+        CoercedAttr::String(label) => labels.push(&***label),
+        CoercedAttr::List(list) => {
+            for item in list.iter() {
+                collect_labels(item, labels);
+            }
+        }
+        CoercedAttr::Selector(selector) => {
+            for (_, value) in selector.all_entries() {
+                collect_labels(value, labels);
+            }
+        }
+        CoercedAttr::Concat(items) => {
+            for item in items.iter() {
+                collect_labels(item, labels);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_util::arc_str::ArcSlice;
+    use buck2_util::arc_str::ArcStr;
+
+    use crate::attrs::attr_type::list::ListLiteral;
+    use crate::attrs::attr_type::string::StringLiteral;
+    use crate::attrs::coerced_attr::CoercedAttr;
+    use crate::attrs::coerced_attr::CoercedSelector;
+    use crate::configuration::resolved::ConfigurationSettingKey;
+    use crate::target_label_filter::collect_labels;
+    use crate::target_label_filter::TargetLabelFilter;
+
+    fn labels(labels: &[&str]) -> CoercedAttr {
+        CoercedAttr::List(ListLiteral(
+            labels
+                .iter()
+                .map(|l| CoercedAttr::String(StringLiteral(ArcStr::from(*l))))
+                .collect(),
+        ))
+    }
+
+    #[test]
+    fn test_matches_labels() {
+        let filter = TargetLabelFilter::new(vec![], vec!["flaky".to_owned()]);
+        assert!(filter.matches_labels(&[]));
+        assert!(filter.matches_labels(&["team:foo"]));
+        assert!(!filter.matches_labels(&["team:foo", "flaky"]));
+
+        let filter = TargetLabelFilter::new(vec!["team:foo".to_owned()], vec!["flaky".to_owned()]);
+        assert!(!filter.matches_labels(&[]));
+        assert!(filter.matches_labels(&["team:foo"]));
+        assert!(!filter.matches_labels(&["team:bar"]));
+        assert!(!filter.matches_labels(&["flaky", "team:foo"]));
+    }
+
+    #[test]
+    fn test_collect_labels_in_selects() {
+        let attr = CoercedAttr::Concat(Box::new([
+            labels(&["team:foo"]),
+            CoercedAttr::Selector(Box::new(
+                CoercedSelector::new(
+                    ArcSlice::new([(
+                        ConfigurationSettingKey::testing_parse("root//:linux"),
+                        labels(&["flaky"]),
+                    )]),
+                    Some(labels(&[])),
+                )
+                .unwrap(),
+            )),
+        ]));
+        let mut collected = Vec::new();
+        collect_labels(&attr, &mut collected);
+        assert_eq!(vec!["team:foo", "flaky"], collected);
+    }
+}
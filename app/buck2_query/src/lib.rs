@@ -26,6 +26,7 @@ extern crate self as buck2_query;
 pub mod __derive_refs {
     pub use async_trait;
     pub use buck2_query_parser;
+    pub use futures;
     pub use indexmap;
     pub use ref_cast;
 }
@@ -138,6 +138,11 @@ pub trait TraversalFilter<T: QueryTarget>: Send + Sync {
     async fn get_children(&self, target: &T) -> buck2_error::Result<TargetSet<T>>;
 }
 
+/// Predicate on the nodes reached by `deps()`. Unlike a [`TraversalFilter`], it does not prune the
+/// traversal: non-matching nodes are still traversed, only left out of the result. Used to push
+/// `kind()` and `filter()` down into `deps()` so that the transitive closure is never collected.
+pub type DepsPredicate<T> = dyn Fn(&T) -> buck2_error::Result<bool> + Send + Sync;
+
 /// The environment of a Buck query that can evaluate queries to produce a
 /// result.
 #[async_trait]
@@ -361,8 +366,9 @@ pub trait QueryEnvironment: Send + Sync {
         targets: &TargetSet<Self::Target>,
         depth: Option<i32>,
         filter: Option<&dyn TraversalFilter<Self::Target>>,
+        predicate: Option<&DepsPredicate<Self::Target>>,
     ) -> buck2_error::Result<TargetSet<Self::Target>> {
-        deps(self, targets, depth, filter).await
+        deps(self, targets, depth, filter, predicate).await
     }
 
     async fn owner(&self, _paths: &FileSet) -> buck2_error::Result<TargetSet<Self::Target>>;
@@ -378,11 +384,17 @@ pub async fn deps<Env: QueryEnvironment + ?Sized>(
     targets: &TargetSet<Env::Target>,
     depth: Option<i32>,
     filter: Option<&dyn TraversalFilter<Env::Target>>,
+    predicate: Option<&DepsPredicate<Env::Target>>,
 ) -> buck2_error::Result<TargetSet<Env::Target>> {
     let mut deps = TargetSet::new();
 
     let visitor = QueryTargetFilteredDepsSuccesors { filter };
     let visit = |target| {
+        if let Some(predicate) = predicate {
+            if !predicate(&target)? {
+                return Ok(());
+            }
+        }
         deps.insert_unique_unchecked(target);
         Ok(())
     };
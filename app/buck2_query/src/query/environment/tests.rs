@@ -9,13 +9,19 @@
 
 #![cfg(test)]
 
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::fmt;
+use std::future::Future;
 use std::sync::Arc;
 
+use buck2_query::query::syntax::simple::eval::evaluator::QueryEvaluator;
+use buck2_query::query::syntax::simple::eval::values::QueryValue;
+use buck2_query::query::syntax::simple::functions::DefaultQueryFunctionsModule;
 use buck2_query::query::traversal::async_depth_first_postorder_traversal;
 use buck2_query::query::traversal::async_depth_limited_traversal;
 use buck2_query::query::traversal::NodeLookup;
+use buck2_query_parser::parse_expr;
 use derive_more::Display;
 use derive_more::From;
 use indexmap::IndexSet;
@@ -34,6 +40,49 @@ struct TestTargetAttr;
 struct TestTarget {
     id: TestTargetId,
     deps: Arc<IndexSet<TestTargetId>>,
+    live: LiveTarget,
+}
+
+thread_local! {
+    static LIVE_TARGETS: Cell<usize> = const { Cell::new(0) };
+    static PEAK_LIVE_TARGETS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts the `TestTarget`s alive on this thread, to measure how many targets a query holds on
+/// to at once.
+#[derive(Eq, PartialEq)]
+struct LiveTarget;
+
+impl LiveTarget {
+    fn new() -> Self {
+        let live = LIVE_TARGETS.get() + 1;
+        LIVE_TARGETS.set(live);
+        PEAK_LIVE_TARGETS.set(PEAK_LIVE_TARGETS.get().max(live));
+        LiveTarget
+    }
+}
+
+impl Clone for LiveTarget {
+    fn clone(&self) -> Self {
+        LiveTarget::new()
+    }
+}
+
+impl Dupe for LiveTarget {}
+
+impl Drop for LiveTarget {
+    fn drop(&mut self) {
+        LIVE_TARGETS.set(LIVE_TARGETS.get() - 1);
+    }
+}
+
+/// Runs `fut` and returns its output along with the most `TestTarget`s it held at once, on top of
+/// the ones alive before. The test runtime is single-threaded, so everything `fut` does is counted.
+async fn peak_live_targets<R>(fut: impl Future<Output = R>) -> (R, usize) {
+    let before = LIVE_TARGETS.get();
+    PEAK_LIVE_TARGETS.set(before);
+    let res = fut.await;
+    (res, PEAK_LIVE_TARGETS.get() - before)
 }
 
 /// Custom debug to make the test output more readable
@@ -59,7 +108,7 @@ impl QueryTarget for TestTarget {
     }
 
     fn rule_type(&self) -> Cow<str> {
-        unimplemented!()
+        Cow::Borrowed(if self.id.0 % 2 == 0 { "even" } else { "odd" })
     }
 
     fn name(&self) -> Cow<str> {
@@ -169,9 +218,13 @@ impl QueryEnvironment for TestEnv {
 
     async fn eval_literals(
         &self,
-        _literal: &[&str],
+        literals: &[&str],
     ) -> buck2_error::Result<TargetSet<Self::Target>> {
-        unimplemented!()
+        let mut set = TargetSet::new();
+        for literal in literals {
+            set.extend(self.set(literal.trim_start_matches("//:"))?.iter());
+        }
+        Ok(set)
     }
 
     async fn eval_file_literal(&self, _literal: &str) -> buck2_error::Result<FileSet> {
@@ -222,6 +275,19 @@ impl TestEnv {
     }
 }
 
+/// Evaluates `query` to a target set.
+async fn eval_query(env: &TestEnv, query: &str) -> buck2_error::Result<TargetSet<TestTarget>> {
+    let parsed = parse_expr(query)?;
+    let functions = DefaultQueryFunctionsModule::new();
+    match QueryEvaluator::new(env, &functions).eval(&parsed).await {
+        Ok(v) => match v.value {
+            QueryValue::TargetSet(targets) => Ok(targets),
+            _ => panic!("`{}` did not evaluate to a target set", query),
+        },
+        Err(e) => Err(QueryError::convert_error(e, query)),
+    }
+}
+
 #[derive(Default)]
 pub struct TestEnvBuilder {
     graph: HashMap<u64, IndexSet<u64>>,
@@ -241,7 +307,14 @@ impl TestEnvBuilder {
                 .map(|(id, vs)| {
                     let id = TestTargetId(*id);
                     let deps = Arc::new(vs.iter().map(|v| TestTargetId(*v)).collect());
-                    (id, TestTarget { id, deps })
+                    (
+                        id,
+                        TestTarget {
+                            id,
+                            deps,
+                            live: LiveTarget::new(),
+                        },
+                    )
                 })
                 .collect(),
         }
@@ -427,3 +500,100 @@ async fn test_rdeps() -> buck2_error::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_filtered_deps() -> buck2_error::Result<()> {
+    let mut env = TestEnvBuilder::default();
+    env.edge(1, 2);
+    env.edge(2, 3);
+    env.edge(3, 4);
+    env.edge(1, 12);
+    env.edge(12, 13);
+    env.edge(13, 4);
+    env.edge(4, 14);
+    // Introduce a cycle.
+    env.edge(14, 2);
+    let env = env.build();
+
+    // `kind()` and `filter()` of `deps()` are evaluated during the traversal, so compare them with
+    // filtering the result of the same `deps()` expression.
+    for deps in [
+        "deps(//:1)",
+        "deps(//:1, 2)",
+        "deps(//:1, 100)",
+        "deps(//:12)",
+        "deps(//:1, 1, first_order_deps())",
+        "deps(//:1, 3, first_order_deps())",
+        "deps(set(//:3 //:13))",
+    ] {
+        let unfiltered = eval_query(&env, deps).await?;
+
+        for regex in ["odd", "even", "^o", "nothing"] {
+            assert_eq!(
+                eval_query(&env, &format!("kind({}, {})", regex, deps)).await?,
+                unfiltered.kind(regex)?,
+                "kind({}, {})",
+                regex,
+                deps,
+            );
+        }
+
+        for regex in ["1", "^1", "4$", "nothing"] {
+            assert_eq!(
+                eval_query(&env, &format!("filter('{}', {})", regex, deps)).await?,
+                unfiltered.filter_name(regex)?,
+                "filter({}, {})",
+                regex,
+                deps,
+            );
+        }
+    }
+
+    assert_eq!(
+        eval_query(&env, "kind(even, deps(//:1))").await?,
+        env.set("2,4,14,12")?
+    );
+    assert_eq!(
+        eval_query(&env, "filter('^1', deps(//:1, 1))").await?,
+        env.set("12,1")?
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_filtered_deps_memory() -> buck2_error::Result<()> {
+    // A wide graph: 0 depends on 1..=WIDTH, each of which depends on WIDTH leaves of its own.
+    const WIDTH: u64 = 300;
+    let mut env = TestEnvBuilder::default();
+    for i in 1..=WIDTH {
+        env.edge(0, i);
+        for j in 0..WIDTH {
+            env.edge(i, WIDTH + 1 + (i - 1) * WIDTH + j);
+        }
+    }
+    let env = env.build();
+    let closure = env.graph.len();
+
+    let (unfiltered, unfiltered_peak) = peak_live_targets(eval_query(&env, "deps(//:0)")).await;
+    assert_eq!(unfiltered?.len(), closure);
+    drop(unfiltered);
+
+    let (filtered, filtered_peak) =
+        peak_live_targets(eval_query(&env, "filter('^12$', deps(//:0))")).await;
+    assert_eq!(filtered?, env.set("12")?);
+
+    // Filtering during the traversal only removes the result set: whatever the traversal itself
+    // holds is the same for both queries. So the pushed-down query holds (almost) the whole closure
+    // fewer targets at its peak than `deps()` does.
+    assert!(unfiltered_peak >= closure);
+    assert!(
+        filtered_peak + closure <= unfiltered_peak + 10,
+        "filtered peak: {}, unfiltered peak: {}, closure: {}",
+        filtered_peak,
+        unfiltered_peak,
+        closure,
+    );
+
+    Ok(())
+}
@@ -9,6 +9,8 @@
 
 //! Implementation of the cli and query_* attr query language.
 
+use std::time::Instant;
+
 use buck2_query_parser::parse_expr;
 use buck2_query_parser::spanned::Spanned;
 use buck2_query_parser::Expr;
@@ -17,23 +19,41 @@ use gazebo::prelude::*;
 use gazebo::variants::VariantName;
 
 use crate::__derive_refs::indexmap::IndexSet;
+use crate::query::environment::DepsPredicate;
 use crate::query::environment::QueryEnvironment;
 use crate::query::syntax::simple::eval::error::QueryError;
 use crate::query::syntax::simple::eval::file_set::FileNode;
 use crate::query::syntax::simple::eval::file_set::FileSet;
+use crate::query::syntax::simple::eval::profile::QueryProfile;
+use crate::query::syntax::simple::eval::set::kind_matcher;
+use crate::query::syntax::simple::eval::set::name_matcher;
 use crate::query::syntax::simple::eval::set::TargetSet;
 use crate::query::syntax::simple::eval::values::QueryEvaluationValue;
 use crate::query::syntax::simple::eval::values::QueryResult;
 use crate::query::syntax::simple::eval::values::QueryValue;
+use crate::query::syntax::simple::functions::helpers::eval_arg;
+use crate::query::syntax::simple::functions::helpers::CapturedExpr;
+use crate::query::syntax::simple::functions::DefaultQueryFunctions;
 use crate::query::syntax::simple::functions::QueryFunctions;
 pub struct QueryEvaluator<'e, Env: QueryEnvironment> {
     env: &'e Env,
     functions: &'e dyn QueryFunctions<Env = Env>,
+    profile: Option<&'e QueryProfile>,
 }
 
 impl<'e, Env: QueryEnvironment> QueryEvaluator<'e, Env> {
     pub fn new(env: &'e Env, functions: &'e dyn QueryFunctions<Env = Env>) -> Self {
-        Self { env, functions }
+        Self {
+            env,
+            functions,
+            profile: None,
+        }
+    }
+
+    /// Record the evaluation time of every subexpression into `profile`.
+    pub fn with_profile(mut self, profile: &'e QueryProfile) -> Self {
+        self.profile = Some(profile);
+        self
     }
 
     pub fn env(&self) -> &Env {
@@ -59,12 +79,17 @@ impl<'e, Env: QueryEnvironment> QueryEvaluator<'e, Env> {
             Expr::Function {
                 function_name,
                 args,
-            } => match self.functions.get(function_name) {
-                Some(func) => func.invoke(self, args).await,
-                None => Err(QueryError::UnknownFunction(
-                    (*function_name.fragment()).to_owned(),
-                )),
-            },
+            } => {
+                if let Some(value) = self.eval_filtered_deps(function_name, args).await? {
+                    return Ok(value);
+                }
+                match self.functions.get(function_name) {
+                    Some(func) => func.invoke(self, args).await,
+                    None => Err(QueryError::UnknownFunction(
+                        (*function_name.fragment()).to_owned(),
+                    )),
+                }
+            }
             Expr::BinaryOpSequence(left, exprs) => {
                 let (left, rights) = futures::future::try_join(
                     self.eval(left),
@@ -108,13 +133,87 @@ impl<'e, Env: QueryEnvironment> QueryEvaluator<'e, Env> {
         }
     }
 
+    /// Evaluates `kind(regex, deps(...))` and `filter(regex, deps(...))` by applying the filter
+    /// during the traversal, so that the transitive closure (which can be most of the graph for
+    /// universes like `//...`) is not collected into a result set. The argument of `deps()` is
+    /// still evaluated as usual. Returns `None` for any other expression.
+    async fn eval_filtered_deps(
+        &self,
+        function_name: &str,
+        args: &[Spanned<Expr<'_>>],
+    ) -> Result<Option<QueryValue<Env::Target>>, QueryError> {
+        let [regex, inner] = args else {
+            return Ok(None);
+        };
+        let (
+            Expr::String(regex),
+            Expr::Function {
+                function_name: inner_function_name,
+                args: inner_args,
+            },
+        ) = (&regex.value, &inner.value)
+        else {
+            return Ok(None);
+        };
+        if *inner_function_name.fragment() != "deps"
+            || inner_args.is_empty()
+            || inner_args.len() > 3
+            || self.functions.get(inner_function_name).is_none()
+            || self.functions.get(function_name).is_none()
+        {
+            return Ok(None);
+        }
+
+        let predicate: Box<DepsPredicate<Env::Target>> = match function_name {
+            "kind" => Box::new(kind_matcher::<Env::Target>(regex)?),
+            "filter" => Box::new(name_matcher::<Env::Target>(regex)?),
+            _ => return Ok(None),
+        };
+
+        // Same arguments as `deps()` itself takes.
+        let (targets, depth, captured_expr): (
+            TargetSet<Env::Target>,
+            Option<u64>,
+            Option<CapturedExpr>,
+        ) = futures::try_join!(
+            eval_arg("deps", self, inner_args, 0),
+            eval_arg("deps", self, inner_args, 1),
+            eval_arg("deps", self, inner_args, 2),
+        )?;
+
+        Ok(Some(
+            DefaultQueryFunctions::<Env>::new()
+                .deps_matching(
+                    self.env,
+                    self.functions,
+                    &targets,
+                    depth.map(|v| v as i32),
+                    captured_expr.as_ref(),
+                    &*predicate,
+                )
+                .await?
+                .into(),
+        ))
+    }
+
     pub fn eval<'a>(
         &'a self,
         expr: &'a Spanned<Expr<'a>>,
     ) -> std::pin::Pin<
         Box<dyn std::future::Future<Output = QueryResult<QueryValue<Env::Target>>> + Send + 'a>,
     > {
-        async move { expr.span(self.eval_internal(&expr.value).await) }.boxed()
+        async move {
+            match self.profile {
+                Some(profile) if !matches!(expr.value, Expr::String(_) | Expr::Integer(_)) => {
+                    let start = Instant::now();
+                    let result = self.eval_internal(&expr.value).await;
+                    profile.record(expr.position.clone(), start.elapsed());
+                    expr.span(result)
+                }
+                _ => expr.span(self.eval_internal(&expr.value).await),
+            }
+        }
+        .boxed()
     }
 
     pub async fn eval_query<'a>(
@@ -0,0 +1,84 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Per-subexpression timings of a query evaluation (`--profile-query`).
+
+use std::fmt::Write;
+use std::ops::Range;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Default, Debug)]
+pub struct QueryProfile {
+    timings: Mutex<Vec<(Range<usize>, Duration)>>,
+}
+
+impl QueryProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn record(&self, position: Range<usize>, elapsed: Duration) {
+        self.timings.lock().unwrap().push((position, elapsed));
+    }
+
+    /// Renders the timings as a tree following the structure of `query`. Subexpressions
+    /// evaluated concurrently (e.g. operands of `+`) overlap in time, so children can add up to
+    /// more than their parent.
+    pub fn format(&self, query: &str) -> String {
+        let mut timings = self.timings.lock().unwrap().clone();
+        timings.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start).then(b.end.cmp(&a.end)));
+
+        let mut out = String::new();
+        let mut parents: Vec<Range<usize>> = Vec::new();
+        for (position, elapsed) in timings {
+            while parents
+                .last()
+                .is_some_and(|parent| parent.end < position.end)
+            {
+                parents.pop();
+            }
+            let text = query.get(position.clone()).unwrap_or("<unknown>");
+            writeln!(
+                out,
+                "{:>10}  {}{}",
+                format!("{:.1?}", elapsed),
+                "  ".repeat(parents.len()),
+                text
+            )
+            .unwrap();
+            parents.push(position);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::query::syntax::simple::eval::profile::QueryProfile;
+
+    #[test]
+    fn test_format() {
+        let query = "deps(a) + deps(b)";
+        let profile = QueryProfile::new();
+        profile.record(10..17, Duration::from_millis(2));
+        profile.record(0..17, Duration::from_millis(3));
+        profile.record(0..7, Duration::from_millis(1));
+        assert_eq!(
+            concat!(
+                "     3.0ms  deps(a) + deps(b)\n",
+                "     1.0ms    deps(a)\n",
+                "     2.0ms    deps(b)\n",
+            ),
+            profile.format(query)
+        );
+    }
+}
@@ -207,15 +207,11 @@ impl<T: QueryTarget> TargetSet<T> {
 
     /// Filter targets by fully qualified name using regex partial match.
     pub fn filter_name(&self, regex: &str) -> buck2_error::Result<TargetSet<T>> {
-        let mut re = RegexBuilder::new(regex);
-        re.delegate_dfa_size_limit(100 << 20);
-        let re = re.build()?;
-        self.filter(|node| Ok(re.is_match(&node.label_for_filter())?))
+        self.filter(name_matcher(regex)?)
     }
 
     pub fn kind(&self, regex: &str) -> buck2_error::Result<TargetSet<T>> {
-        let re = Regex::new(regex)?;
-        self.filter(|node| Ok(re.is_match(&node.rule_type())?))
+        self.filter(kind_matcher(regex)?)
     }
 
     pub fn intersect(&self, right: &TargetSet<T>) -> buck2_error::Result<TargetSet<T>> {
@@ -227,6 +223,24 @@ impl<T: QueryTarget> TargetSet<T> {
     }
 }
 
+/// Predicate used by `filter()`: regex partial match on the target name.
+pub(crate) fn name_matcher<T: QueryTarget>(
+    regex: &str,
+) -> buck2_error::Result<impl Fn(&T) -> buck2_error::Result<bool> + Send + Sync> {
+    let mut re = RegexBuilder::new(regex);
+    re.delegate_dfa_size_limit(100 << 20);
+    let re = re.build()?;
+    Ok(move |node: &T| Ok(re.is_match(&node.label_for_filter())?))
+}
+
+/// Predicate used by `kind()`: regex partial match on the rule type.
+pub(crate) fn kind_matcher<T: QueryTarget>(
+    regex: &str,
+) -> buck2_error::Result<impl Fn(&T) -> buck2_error::Result<bool> + Send + Sync> {
+    let re = Regex::new(regex)?;
+    Ok(move |node: &T| Ok(re.is_match(&node.rule_type())?))
+}
+
 impl<T: QueryTarget> Display for TargetSet<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt_container(f, "[", "]", self.targets.iter().map(|t| t.node_key()))
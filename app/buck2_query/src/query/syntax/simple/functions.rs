@@ -20,6 +20,7 @@ use buck2_query_parser::BinaryOp;
 use buck2_query_parser::Expr;
 use gazebo::variants::VariantName;
 
+use crate::query::environment::DepsPredicate;
 use crate::query::environment::QueryEnvironment;
 use crate::query::syntax::simple::eval::error::QueryError;
 use crate::query::syntax::simple::eval::evaluator::QueryEvaluator;
@@ -728,7 +729,32 @@ impl<Env: QueryEnvironment> DefaultQueryFunctions<Env> {
         DepsFunction::<Env> {
             _marker: PhantomData,
         }
-        .invoke_deps(env, functions, targets, depth, captured_expr)
+        .invoke_deps(env, functions, targets, depth, captured_expr, None)
+        .await
+    }
+
+    /// Same as `deps`, but only returns the targets matching `predicate`. The traversal is not
+    /// pruned, this is equivalent to filtering the result of `deps`.
+    pub async fn deps_matching(
+        &self,
+        env: &Env,
+        functions: &dyn QueryFunctions<Env = Env>,
+        targets: &TargetSet<Env::Target>,
+        depth: Option<i32>,
+        captured_expr: Option<&CapturedExpr<'_>>,
+        predicate: &DepsPredicate<Env::Target>,
+    ) -> buck2_error::Result<TargetSet<Env::Target>> {
+        DepsFunction::<Env> {
+            _marker: PhantomData,
+        }
+        .invoke_deps(
+            env,
+            functions,
+            targets,
+            depth,
+            captured_expr,
+            Some(predicate),
+        )
         .await
     }
 
@@ -15,6 +15,7 @@ use async_trait::async_trait;
 use buck2_query_derive::query_module;
 use gazebo::variants::VariantName;
 
+use crate::query::environment::DepsPredicate;
 use crate::query::environment::QueryEnvironment;
 use crate::query::environment::QueryTarget;
 use crate::query::environment::TraversalFilter;
@@ -139,13 +140,14 @@ impl<'a, Env: QueryEnvironment> DepsFunction<Env> {
         targets: &TargetSet<Env::Target>,
         depth: Option<i32>,
         captured_expr: Option<&CapturedExpr<'_>>,
+        predicate: Option<&DepsPredicate<Env::Target>>,
     ) -> buck2_error::Result<TargetSet<Env::Target>> {
         let filter = self.make_filter(&env, functions, captured_expr);
         let filter_ref = filter
             .as_ref()
             .map(|v| v as &dyn TraversalFilter<Env::Target>);
 
-        env.deps(targets, depth, filter_ref).await
+        env.deps(targets, depth, filter_ref, predicate).await
     }
 
     pub(crate) async fn invoke_rdeps(
@@ -167,6 +167,7 @@ fn gen_for_method(parsed: &Parsed, method: &Method) -> syn::Result<MethodCodegen
     };
 
     let mut describe_args = Vec::new();
+    let mut eval_args = Vec::new();
     let mut pass_args = Vec::new();
     let mut arg_type_match = Vec::new();
     for (i, arg) in value_args.iter().enumerate() {
@@ -174,8 +175,8 @@ fn gen_for_method(parsed: &Parsed, method: &Method) -> syn::Result<MethodCodegen
         let as_arg_type = quote! {<#arg_type as QueryFunctionArg<'_, #env_ident>>};
         let arg_name = arg.name.to_string();
         arg_type_match.push(quote_spanned!(arg.span => #i => Ok(#as_arg_type::ARG_TYPE)));
-        pass_args
-            .push(quote_spanned!(arg.span => eval_arg(self.name(), evaluator, args, #i).await?));
+        eval_args.push(quote_spanned!(arg.span => eval_arg(self.name(), evaluator, args, #i)));
+        pass_args.push(Ident::new(&format!("__arg{}", i), arg.span));
         describe_args.push(quote_spanned!(arg.span => ArgDescription {
             name: #arg_name.to_owned(),
             repr_format: #as_arg_type::describe_format(),
@@ -247,6 +248,21 @@ fn gen_for_method(parsed: &Parsed, method: &Method) -> syn::Result<MethodCodegen
         None => {
             let max_args = value_args.len();
 
+            // Arguments don't depend on each other, so evaluate them concurrently (e.g. the two
+            // sets in `allpaths(deps(a), rdeps(b, c))`). Captured expressions are not evaluated
+            // here, so they are ready immediately.
+            let eval_args = match &eval_args[..] {
+                [] => quote! {},
+                [eval_arg] => {
+                    let pass_arg = &pass_args[0];
+                    quote! { let #pass_arg = #eval_arg.await?; }
+                }
+                _ => quote! {
+                    let (#(#pass_args,)*) =
+                        ::buck2_query::__derive_refs::futures::try_join!(#(#eval_args),*)?;
+                },
+            };
+
             let method_dispatch = Some(quote_spanned!(method.name.span() =>
                 stringify!(#func_ident) => Some(#func_ty::ref_cast(self) as &dyn QueryFunction<#env_ident>)
             ));
@@ -267,6 +283,7 @@ fn gen_for_method(parsed: &Parsed, method: &Method) -> syn::Result<MethodCodegen
                         evaluator: &QueryEvaluator<#env_ident>,
                         args: &[SpannedExpr<'_>],
                     ) -> Result<QueryValue<#env_target>, QueryError> {
+                        #eval_args
                         self.0.#func_ident(
                             #pass_ctx
                             #(#pass_args,)*
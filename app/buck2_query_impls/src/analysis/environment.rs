@@ -39,6 +39,7 @@ use buck2_node::nodes::configured_node_ref::ConfiguredTargetNodeRefNodeDeps;
 use buck2_node::nodes::configured_ref::ConfiguredGraphNodeRef;
 use buck2_node::query::query_functions::CONFIGURED_GRAPH_QUERY_FUNCTIONS;
 use buck2_query::query::environment::deps;
+use buck2_query::query::environment::DepsPredicate;
 use buck2_query::query::environment::QueryEnvironment;
 use buck2_query::query::environment::TraversalFilter;
 use buck2_query::query::graph::dfs::dfs_postorder;
@@ -262,6 +263,7 @@ impl<'a> QueryEnvironment for ConfiguredGraphQueryEnvironment<'a> {
         targets: &TargetSet<Self::Target>,
         depth: Option<i32>,
         filter: Option<&dyn TraversalFilter<Self::Target>>,
+        predicate: Option<&DepsPredicate<Self::Target>>,
     ) -> buck2_error::Result<TargetSet<Self::Target>> {
         if depth.is_none() && filter.is_none() {
             // TODO(nga): fast lookup with depth too.
@@ -270,13 +272,19 @@ impl<'a> QueryEnvironment for ConfiguredGraphQueryEnvironment<'a> {
                 targets.iter().map(|n| ConfiguredTargetNodeRefNode::new(n)),
                 ConfiguredTargetNodeRefNodeDeps,
                 |target| {
-                    deps.insert(ConfiguredGraphNodeRef::new(target.to_node()));
+                    let target = ConfiguredGraphNodeRef::new(target.to_node());
+                    if let Some(predicate) = predicate {
+                        if !predicate(&target)? {
+                            return Ok(());
+                        }
+                    }
+                    deps.insert(target);
                     Ok(())
                 },
             )?;
             Ok(deps)
         } else {
-            deps(self, targets, depth, filter).await
+            deps(self, targets, depth, filter, predicate).await
         }
     }
 }
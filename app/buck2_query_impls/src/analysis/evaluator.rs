@@ -9,13 +9,17 @@
 
 //! Implementation of common cquery/uquery pieces.
 
+use std::time::Instant;
+
 use buck2_common::scope::scope_and_collect_with_dispatcher;
 use buck2_error::BuckErrorContext;
+use buck2_events::dispatch::console_message;
 use buck2_events::dispatch::EventDispatcher;
 use buck2_query::query::environment::QueryEnvironment;
 use buck2_query::query::syntax::simple::eval::evaluator::QueryEvaluator;
 use buck2_query::query::syntax::simple::eval::literals::extract_target_literals;
 use buck2_query::query::syntax::simple::eval::multi_query::MultiQueryResult;
+use buck2_query::query::syntax::simple::eval::profile::QueryProfile;
 use buck2_query::query::syntax::simple::eval::values::QueryEvaluationResult;
 use buck2_query::query::syntax::simple::eval::values::QueryEvaluationValue;
 use buck2_query::query::syntax::simple::functions::QueryFunctions;
@@ -32,16 +36,18 @@ pub(crate) async fn eval_query<
     functions: &F,
     query: &str,
     query_args: &[String],
+    profile: bool,
     environment: impl Fn(Vec<String>) -> Fut + Send + Sync,
 ) -> buck2_error::Result<QueryEvaluationResult<Env::Target>> {
     let query = MaybeMultiQuery::parse(query, query_args)?;
     match query {
         MaybeMultiQuery::MultiQuery(queries) => {
-            let results = process_multi_query(dispatcher, functions, environment, &queries).await?;
+            let results =
+                process_multi_query(dispatcher, functions, environment, &queries, profile).await?;
             Ok(QueryEvaluationResult::Multiple(results))
         }
         MaybeMultiQuery::SingleQuery(query) => {
-            let result = eval_single_query(functions, &query, environment).await?;
+            let result = eval_single_query(functions, &query, profile, environment).await?;
             Ok(QueryEvaluationResult::Single(result))
         }
     }
@@ -54,6 +60,7 @@ async fn eval_single_query<
 >(
     functions: &F,
     query: &str,
+    profile: bool,
     environment: impl Fn(Vec<String>) -> Fut,
 ) -> buck2_error::Result<QueryEvaluationValue<<Env as QueryEnvironment>::Target>>
 where
@@ -62,8 +69,26 @@ where
     Fut: Future<Output = buck2_error::Result<Env>>,
 {
     let literals = extract_target_literals(functions, query)?;
+    if !profile {
+        let env = environment(literals).await?;
+        return QueryEvaluator::new(&env, functions).eval_query(query).await;
+    }
+
+    let start = Instant::now();
     let env = environment(literals).await?;
-    QueryEvaluator::new(&env, functions).eval_query(query).await
+    let environment_elapsed = start.elapsed();
+    let query_profile = QueryProfile::new();
+    let result = QueryEvaluator::new(&env, functions)
+        .with_profile(&query_profile)
+        .eval_query(query)
+        .await;
+    console_message(format!(
+        "Query profile for `{}`:\n{:>10}  (literal resolution)\n{}",
+        query,
+        format!("{:.1?}", environment_elapsed),
+        query_profile.format(query),
+    ));
+    result
 }
 
 async fn process_multi_query<Env, EnvFut, Qf>(
@@ -71,6 +96,7 @@ async fn process_multi_query<Env, EnvFut, Qf>(
     functions: &Qf,
     env: impl Fn(Vec<String>) -> EnvFut + Send + Sync,
     queries: &[MultiQueryItem],
+    profile: bool,
 ) -> buck2_error::Result<MultiQueryResult<Env::Target>>
 where
     Qf: QueryFunctions<Env = Env>,
@@ -86,7 +112,7 @@ where
                 let env = &env;
                 scope.spawn_cancellable(
                     async move {
-                        let result = eval_single_query(functions, &query.query, env);
+                        let result = eval_single_query(functions, &query.query, profile, env);
                         let result: buck2_error::Result<_> = result.await.map_err(|e| e.into());
                         (i, arg, result)
                     },
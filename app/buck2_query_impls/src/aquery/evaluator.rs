@@ -35,6 +35,7 @@ impl AqueryEvaluator<'_, '_> {
         &self,
         query: &str,
         query_args: &[String],
+        profile: bool,
     ) -> buck2_error::Result<QueryEvaluationResult<ActionQueryNode>> {
         let functions = aquery_functions();
 
@@ -47,6 +48,7 @@ impl AqueryEvaluator<'_, '_> {
             &functions,
             query,
             query_args,
+            profile,
             |literals| async move {
                 let resolved_literals = PreresolvedQueryLiterals::pre_resolve(
                     &**self.dice_query_delegate.query_data(),
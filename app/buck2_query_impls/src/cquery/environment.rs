@@ -20,6 +20,7 @@ use buck2_node::nodes::configured::ConfiguredTargetNode;
 use buck2_node::nodes::configured_node_ref::ConfiguredTargetNodeRefNode;
 use buck2_node::nodes::configured_node_ref::ConfiguredTargetNodeRefNodeDeps;
 use buck2_query::query::environment::deps;
+use buck2_query::query::environment::DepsPredicate;
 use buck2_query::query::environment::QueryEnvironment;
 use buck2_query::query::environment::QueryEnvironmentAsNodeLookup;
 use buck2_query::query::environment::TraversalFilter;
@@ -222,6 +223,7 @@ impl<'c> QueryEnvironment for CqueryEnvironment<'c> {
         targets: &TargetSet<Self::Target>,
         depth: Option<i32>,
         filter: Option<&dyn TraversalFilter<Self::Target>>,
+        predicate: Option<&DepsPredicate<Self::Target>>,
     ) -> buck2_error::Result<TargetSet<Self::Target>> {
         if depth.is_none() && filter.is_none() {
             // TODO(nga): fast lookup with depth too.
@@ -231,13 +233,19 @@ impl<'c> QueryEnvironment for CqueryEnvironment<'c> {
                 targets.iter().map(ConfiguredTargetNodeRefNode::new),
                 ConfiguredTargetNodeRefNodeDeps,
                 |target| {
-                    deps.insert_unique_unchecked(target.to_node());
+                    let target = target.to_node();
+                    if let Some(predicate) = predicate {
+                        if !predicate(&target)? {
+                            return Ok(());
+                        }
+                    }
+                    deps.insert_unique_unchecked(target);
                     Ok(())
                 },
             )?;
             Ok(deps)
         } else {
-            deps(self, targets, depth, filter).await
+            deps(self, targets, depth, filter, predicate).await
         }
     }
 }
@@ -39,6 +39,7 @@ pub(crate) async fn eval_cquery(
     query_args: &[String],
     target_universe: Option<&[String]>,
     collect_universes: bool,
+    profile: bool,
 ) -> buck2_error::Result<(
     QueryEvaluationResult<ConfiguredTargetNode>,
     Option<Vec<Arc<CqueryUniverse>>>,
@@ -91,6 +92,7 @@ pub(crate) async fn eval_cquery(
         &functions,
         query,
         query_args,
+        profile,
         |literals| async move {
             let (resolved_literals, universe) = match target_universe {
                 None => {
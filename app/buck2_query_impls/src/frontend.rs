@@ -42,11 +42,12 @@ impl QueryFrontend for QueryFrontendImpl {
         working_dir: &ProjectRelativePath,
         query: &str,
         query_args: &[String],
+        profile: bool,
     ) -> buck2_error::Result<QueryEvaluationResult<TargetNode>> {
         Ok(ctx
             .with_linear_recompute(|ctx| async move {
                 let evaluator = get_uquery_evaluator(&ctx, working_dir).await?;
-                evaluator.eval_query(query, query_args).await
+                evaluator.eval_query(query, query_args, profile).await
             })
             .await?)
     }
@@ -65,6 +66,7 @@ impl QueryFrontend for QueryFrontendImpl {
         global_cfg_options: GlobalCfgOptions,
         target_universe: Option<&[String]>,
         collect_universes: bool,
+        profile: bool,
     ) -> buck2_error::Result<(
         QueryEvaluationResult<ConfiguredTargetNode>,
         Option<Vec<Arc<CqueryUniverse>>>,
@@ -86,6 +88,7 @@ impl QueryFrontend for QueryFrontendImpl {
                     query_args,
                     target_universe.as_ref().map(|v| &v[..]),
                     collect_universes,
+                    profile,
                 )
                 .await
             })
@@ -99,11 +102,12 @@ impl QueryFrontend for QueryFrontendImpl {
         query: &str,
         query_args: &[String],
         global_cfg_options: GlobalCfgOptions,
+        profile: bool,
     ) -> buck2_error::Result<QueryEvaluationResult<ActionQueryNode>> {
         Ok(ctx
             .with_linear_recompute(|ctx| async move {
                 let evaluator = get_aquery_evaluator(&ctx, working_dir, global_cfg_options).await?;
-                evaluator.eval_query(query, query_args).await
+                evaluator.eval_query(query, query_args, profile).await
             })
             .await?)
     }
@@ -35,6 +35,7 @@ impl UqueryEvaluator<'_, '_> {
         &self,
         query: &str,
         query_args: &[String],
+        profile: bool,
     ) -> buck2_error::Result<QueryEvaluationResult<TargetNode>> {
         eval_query(
             self.dice_query_delegate
@@ -45,6 +46,7 @@ impl UqueryEvaluator<'_, '_> {
             &self.functions,
             query,
             query_args,
+            profile,
             |literals| async move {
                 let resolved_literals = PreresolvedQueryLiterals::pre_resolve(
                     &**self.dice_query_delegate.query_data(),
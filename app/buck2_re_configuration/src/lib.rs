@@ -330,6 +330,15 @@ pub struct Buck2OssReConfiguration {
     pub max_total_batch_size: Option<usize>,
     /// Maximum number of concurrent upload requests for each action.
     pub max_concurrent_uploads_per_action: Option<usize>,
+    /// Maximum size of a `BatchUpdateBlobs` request. Smaller batches of small blobs are uploaded
+    /// concurrently, so they complete earlier, but need more requests.
+    pub max_batch_upload_size: Option<usize>,
+    /// Maximum number of digests checked by one `FindMissingBlobs` request.
+    pub find_missing_blobs_batch_size: Option<usize>,
+    /// How long to wait for the checks of other actions to send them in the same
+    /// `FindMissingBlobs` request. Higher values reduce the number of requests on high latency
+    /// links, at the cost of delaying uploads.
+    pub find_missing_blobs_batch_latency_ms: Option<u64>,
 }
 
 #[derive(Clone, Debug, Default, Allocative)]
@@ -431,6 +440,41 @@ impl Buck2OssReConfiguration {
                 section: BUCK2_RE_CLIENT_CFG_SECTION,
                 property: "max_concurrent_uploads_per_action",
             })?,
+            max_batch_upload_size: legacy_config.parse(BuckconfigKeyRef {
+                section: BUCK2_RE_CLIENT_CFG_SECTION,
+                property: "max_batch_upload_size",
+            })?,
+            find_missing_blobs_batch_size: legacy_config.parse(BuckconfigKeyRef {
+                section: BUCK2_RE_CLIENT_CFG_SECTION,
+                property: "find_missing_blobs_batch_size",
+            })?,
+            find_missing_blobs_batch_latency_ms: legacy_config.parse(BuckconfigKeyRef {
+                section: BUCK2_RE_CLIENT_CFG_SECTION,
+                property: "find_missing_blobs_batch_latency_ms",
+            })?,
+        })
+    }
+}
+
+/// Caps on the bandwidth RE transfers may use, so builds don't saturate slow or metered links.
+/// Both the executor's uploads and the materializer's downloads respect them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Allocative)]
+pub struct ReBandwidthCaps {
+    pub max_download_bytes_per_second: Option<u64>,
+    pub max_upload_bytes_per_second: Option<u64>,
+}
+
+impl ReBandwidthCaps {
+    pub fn from_legacy_config(legacy_config: &LegacyBuckConfig) -> buck2_error::Result<Self> {
+        Ok(Self {
+            max_download_bytes_per_second: legacy_config.parse(BuckconfigKeyRef {
+                section: BUCK2_RE_CLIENT_CFG_SECTION,
+                property: "max_download_bytes_per_second",
+            })?,
+            max_upload_bytes_per_second: legacy_config.parse(BuckconfigKeyRef {
+                section: BUCK2_RE_CLIENT_CFG_SECTION,
+                property: "max_upload_bytes_per_second",
+            })?,
         })
     }
 }
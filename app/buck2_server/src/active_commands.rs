@@ -9,7 +9,11 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
 use buck2_event_observer::dice_state::DiceState;
 use buck2_event_observer::pending_estimate::pending_estimate;
@@ -29,6 +33,13 @@ use tokio::sync::oneshot;
 static ACTIVE_COMMANDS: Lazy<Mutex<HashMap<TraceId, ActiveCommandHandle>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// When the last command finished running on this daemon.
+static LAST_COMMAND_FINISHED: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+
+/// Set under the `ACTIVE_COMMANDS` lock once the daemon is shutting down because it was idle, so
+/// that no command can start after the check.
+static IDLE_SHUTDOWN: AtomicBool = AtomicBool::new(false);
+
 /// Return the active commands, if you can access them.
 pub fn try_active_commands() -> Option<HashMap<TraceId, ActiveCommandHandle>> {
     // Note that this function is accessed during panic, so have to be super careful
@@ -39,6 +50,32 @@ pub fn active_commands() -> MutexGuard<'static, HashMap<TraceId, ActiveCommandHa
     ACTIVE_COMMANDS.lock()
 }
 
+/// Return when the daemon became idle: `None` if a command is currently running, or `start` if
+/// no command has finished yet.
+pub fn idle_since(start: Instant) -> Option<Instant> {
+    if !ACTIVE_COMMANDS.lock().is_empty() {
+        return None;
+    }
+    Some(LAST_COMMAND_FINISHED.lock().unwrap_or(start))
+}
+
+/// Check that no command has run for at least `idle_for` and, if so, prevent any command from
+/// starting from now on. Both happen under the active commands lock, so a command starting
+/// concurrently either makes the daemon not idle or fails to start. Returns whether the daemon
+/// was idle, in which case the caller must shut it down.
+pub fn stop_if_idle(start: Instant, idle_for: Duration) -> bool {
+    let active_commands = ACTIVE_COMMANDS.lock();
+    if !active_commands.is_empty() {
+        return false;
+    }
+    let idle_since = LAST_COMMAND_FINISHED.lock().unwrap_or(start);
+    if idle_since.elapsed() < idle_for {
+        return false;
+    }
+    IDLE_SHUTDOWN.store(true, Ordering::Relaxed);
+    true
+}
+
 /// Broadcasts an instant event, returns whether any subscribers were connected.
 pub fn broadcast_instant_event<E: Into<buck2_data::instant_event::Data> + Clone>(
     event: &E,
@@ -93,7 +130,9 @@ pub struct ActiveCommandDropGuard {
 
 impl Drop for ActiveCommandDropGuard {
     fn drop(&mut self) {
-        ACTIVE_COMMANDS.lock().remove(&self.trace_id);
+        let mut active_commands = ACTIVE_COMMANDS.lock();
+        active_commands.remove(&self.trace_id);
+        *LAST_COMMAND_FINISHED.lock() = Some(Instant::now());
     }
 }
 
@@ -221,7 +260,10 @@ pub struct ActiveCommand {
 }
 
 impl ActiveCommand {
-    pub fn new(event_dispatcher: &EventDispatcher, sanitized_argv: Vec<String>) -> Self {
+    pub fn new(
+        event_dispatcher: &EventDispatcher,
+        sanitized_argv: Vec<String>,
+    ) -> buck2_error::Result<Self> {
         let (sender, receiver) = oneshot::channel();
 
         let state = Arc::new(ActiveCommandState::new(sanitized_argv));
@@ -231,6 +273,13 @@ impl ActiveCommand {
             // Scope the guard so it's locked as little as possible
             let mut active_commands = ACTIVE_COMMANDS.lock();
 
+            if IDLE_SHUTDOWN.load(Ordering::Relaxed) {
+                return Err(buck2_error::buck2_error!(
+                    [],
+                    "Failed to run command, `buckd` is shutting down because it was idle"
+                ));
+            }
+
             let existing_active_commands = if active_commands.len() > 0 {
                 Some(active_commands.clone())
             } else {
@@ -264,11 +313,11 @@ impl ActiveCommand {
             }
         }
 
-        Self {
+        Ok(Self {
             guard: ActiveCommandDropGuard { trace_id },
             daemon_shutdown_channel: receiver,
             state: ActiveCommandStateWriter::new(state),
-        }
+        })
     }
 }
 
@@ -445,16 +494,16 @@ mod tests {
     #[test]
     fn test_multiple_active_commands() {
         let (dispatcher1, mut source1, id1) = create_dispatcher();
-        let _active1 = ActiveCommand::new(&dispatcher1, Vec::new());
+        let _active1 = ActiveCommand::new(&dispatcher1, Vec::new()).unwrap();
 
         let (dispatcher2, mut source2, id2) = create_dispatcher();
-        let _active2 = ActiveCommand::new(&dispatcher2, Vec::new());
+        let _active2 = ActiveCommand::new(&dispatcher2, Vec::new()).unwrap();
 
         check_concurrent_command_trace_ids_eq(source1.try_receive(), &[id2.to_string()]);
         check_concurrent_command_trace_ids_eq(source2.try_receive(), &[id1.to_string()]);
 
         let (dispatcher3, mut source3, id3) = create_dispatcher();
-        let _active3 = ActiveCommand::new(&dispatcher3, Vec::new());
+        let _active3 = ActiveCommand::new(&dispatcher3, Vec::new()).unwrap();
 
         check_concurrent_command_trace_ids_eq(source1.try_receive(), &[id3.to_string()]);
         check_concurrent_command_trace_ids_eq(source2.try_receive(), &[id3.to_string()]);
@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::io::BufWriter;
@@ -19,6 +20,7 @@ use buck2_build_api::actions::execute::dice_data::set_fallback_executor_config;
 use buck2_build_api::actions::execute::dice_data::SetCommandExecutor;
 use buck2_build_api::actions::execute::dice_data::SetReClient;
 use buck2_build_api::actions::impls::run_action_knobs::HasRunActionKnobs;
+use buck2_build_api::actions::impls::run_action_knobs::NetworkPolicy;
 use buck2_build_api::actions::impls::run_action_knobs::RunActionKnobs;
 use buck2_build_api::build::HasCreateUnhashedSymlinkLock;
 use buck2_build_api::build_signals::create_build_signals;
@@ -28,6 +30,8 @@ use buck2_build_api::context::SetBuildContextData;
 use buck2_build_api::keep_going::HasKeepGoing;
 use buck2_build_api::materialize::HasMaterializationQueueTracker;
 use buck2_build_api::spawner::BuckSpawner;
+use buck2_build_signals::critical_path_history::CriticalPathScheduling;
+use buck2_build_signals::critical_path_history::HasCriticalPathScheduling;
 use buck2_build_signals::env::CriticalPathBackendName;
 use buck2_build_signals::env::HasCriticalPathBackend;
 use buck2_certs::validate::CertState;
@@ -39,6 +43,7 @@ use buck2_cli_proto::config_override::ConfigType;
 use buck2_cli_proto::ClientContext;
 use buck2_cli_proto::CommonBuildOptions;
 use buck2_cli_proto::ConfigOverride;
+use buck2_common::dice::build_metadata::SetBuildMetadata;
 use buck2_common::dice::cells::HasCellResolver;
 use buck2_common::dice::cycles::CycleDetectorAdapter;
 use buck2_common::dice::cycles::PairDiceCycleDetector;
@@ -53,11 +58,16 @@ use buck2_common::legacy_configs::dice::HasLegacyConfigs;
 use buck2_common::legacy_configs::diffs::ConfigDiffTracker;
 use buck2_common::legacy_configs::file_ops::ConfigPath;
 use buck2_common::legacy_configs::key::BuckconfigKeyRef;
+use buck2_common::prelude_lock::PreludeLock;
+use buck2_common::soft_error_budget::SoftErrorBudget;
+use buck2_common::soft_error_budget::SoftErrorHistoryManager;
 use buck2_configured::calculation::ConfiguredGraphCycleDescriptor;
+use buck2_core::error::set_escalated_soft_error_categories;
 use buck2_core::execution_types::executor_config::CommandExecutorConfig;
 use buck2_core::execution_types::executor_config::RemoteExecutorUseCase;
 use buck2_core::facebook_only;
 use buck2_core::fs::fs_util;
+use buck2_core::fs::output_path_scheme::OutputPathScheme;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::fs::paths::file_name::FileName;
@@ -70,6 +80,7 @@ use buck2_core::pattern::pattern::ParsedPattern;
 use buck2_core::pattern::pattern_type::ConfiguredProvidersPatternExtra;
 use buck2_core::rollout_percentage::RolloutPercentage;
 use buck2_core::target::label::interner::ConcurrentTargetLabelInterner;
+use buck2_error::BuckErrorContext;
 use buck2_events::daemon_id;
 use buck2_events::dispatch::EventDispatcher;
 use buck2_events::metadata;
@@ -77,6 +88,7 @@ use buck2_execute::execute::blocking::SetBlockingExecutor;
 use buck2_execute::knobs::ExecutorGlobalKnobs;
 use buck2_execute::materialize::materializer::Materializer;
 use buck2_execute::materialize::materializer::SetMaterializer;
+use buck2_execute::re::action_url::ReActionUrlTemplate;
 use buck2_execute::re::client::RemoteExecutionClient;
 use buck2_execute::re::manager::ReConnectionHandle;
 use buck2_execute::re::manager::ReConnectionObserver;
@@ -103,6 +115,7 @@ use buck2_server_starlark_debug::create_debugger_handle;
 use buck2_server_starlark_debug::BuckStarlarkDebuggerHandle;
 use buck2_test::local_resource_registry::InitLocalResourceRegistry;
 use buck2_util::arc_str::ArcS;
+use buck2_util::system_stats::system_memory_stats;
 use buck2_util::truncate::truncate_container;
 use buck2_validation::enabled_optional_validations_key::SetEnabledOptionalValidations;
 use buck2_wrapper_common::DOT_BUCKCONFIG_D;
@@ -128,6 +141,7 @@ use crate::dice_tracker::BuckDiceTracker;
 use crate::heartbeat_guard::HeartbeatGuard;
 use crate::host_info;
 use crate::snapshot::SnapshotCollector;
+use crate::telemetry_sampling::TelemetrySampleRate;
 
 #[derive(Debug, buck2_error::Error)]
 enum DaemonCommunicationError {
@@ -173,6 +187,10 @@ pub struct ServerCommandContext<'a> {
     pub oncall: Option<String>,
     /// The client ID, if one was provided via --client-metadata.
     pub client_id_from_client_metadata: Option<String>,
+    /// Client metadata passed with --build-metadata, which is readable from Starlark.
+    build_metadata: BTreeMap<String, String>,
+    /// Trace id of the caller which invoked buck2, if any.
+    pub parent_trace_id: Option<Arc<str>>,
 
     host_platform_override: HostPlatformOverride,
     host_arch_override: HostArchOverride,
@@ -199,6 +217,7 @@ pub struct ServerCommandContext<'a> {
 
     pub buck_out_dir: ProjectRelativePathBuf,
     isolation_prefix: FileNameBuf,
+    soft_error_history: SoftErrorHistoryManager,
 
     /// Common build options associated with this command.
     build_options: Option<CommonBuildOptions>,
@@ -303,6 +322,13 @@ impl<'a> ServerCommandContext<'a> {
             .find(|m| m.key == "id")
             .map(|m| m.value.clone());
 
+        let build_metadata = client_context
+            .client_metadata
+            .iter()
+            .filter(|m| m.build_visible)
+            .map(|m| (m.key.clone(), m.value.clone()))
+            .collect();
+
         let heartbeat_guard_handle =
             HeartbeatGuard::new(base_context.events.dupe(), snapshot_collector);
 
@@ -319,11 +345,14 @@ impl<'a> ServerCommandContext<'a> {
             config_overrides: client_context.config_overrides.clone(),
             oncall,
             client_id_from_client_metadata,
+            build_metadata,
+            parent_trace_id: client_context.parent_trace_id.as_deref().map(Arc::from),
             _re_connection_handle: re_connection_handle,
             cert_state,
             starlark_profiler_instrumentation_override,
             buck_out_dir: paths.buck_out_dir(),
             isolation_prefix: paths.isolation.clone(),
+            soft_error_history: SoftErrorHistoryManager::new(paths.soft_error_budget_dir()),
             build_options: build_options.cloned(),
             record_target_call_stacks: client_context.target_call_stacks,
             skip_targets_with_duplicate_names: client_context.skip_targets_with_duplicate_names,
@@ -377,6 +406,10 @@ impl<'a> ServerCommandContext<'a> {
                 .daemon
                 .use_network_action_output_cache,
             eager_dep_files,
+            // Set from the buckconfig when the DICE transaction is created.
+            network_policy: NetworkPolicy::default(),
+            remote_dep_file_divergence_sampling: 0,
+            lost_inputs_max_reexecution_depth: 0,
         };
 
         let concurrency = self
@@ -470,6 +503,11 @@ impl ServerCommandContext<'_> {
         .await?;
 
         self.report_traced_config_paths(&new_configs.config_paths)?;
+        if let Some(lock) = PreludeLock::from_config(&new_configs.root_config) {
+            if let Some(warning) = lock.check_buck2_revision(buck2_build_info::revision()) {
+                warn!("{}", warning);
+            }
+        }
         // Normally, this code should execute only once (hence we should fire only one BuckconfigInputValues event) but there might be an additional call once concurrent command is detected.
         // Even if there is no concurrent command, we sometimes end up having two events due to a bug where concurrency manager treats many more commands as being concurrent than it's supposed to.
         let components = new_configs.external_data.get_buckconfig_components();
@@ -556,6 +594,55 @@ struct DiceCommandUpdater<'s, 'a: 's> {
     interpreter_xcode_version: Option<XcodeVersionInfo>,
 }
 
+fn output_path_scheme(root_config: &LegacyBuckConfig) -> buck2_error::Result<OutputPathScheme> {
+    let hash_length = root_config.parse(BuckconfigKeyRef {
+        section: "buck2",
+        property: "output_path_hash_length",
+    })?;
+    let aliases = root_config
+        .get_section("buck2_output_path_aliases")
+        .map(|section| {
+            section
+                .iter()
+                .map(|(platform, alias)| (platform.to_owned(), alias.as_str().to_owned()))
+                .collect()
+        })
+        .unwrap_or_default();
+    OutputPathScheme::new(hash_length, aliases)
+}
+
+/// Number of devices of each class the host has, from the `test_local_devices` section (e.g.
+/// `gpu = 2`). Classes which are not listed have one device.
+fn local_devices(root_config: &LegacyBuckConfig) -> buck2_error::Result<HashMap<String, usize>> {
+    let Some(section) = root_config.get_section("test_local_devices") else {
+        return Ok(HashMap::new());
+    };
+    section
+        .iter()
+        .map(|(class, count)| {
+            let count = count.as_str().parse::<usize>().with_buck_error_context(|| {
+                format!("Invalid number of devices for `test_local_devices.{}`", class)
+            })?;
+            Ok((class.to_owned(), count))
+        })
+        .collect()
+}
+
+/// Command to run local actions which may not access the network under, when
+/// `buck2.network_isolation_command` is not set. This puts the action in a new network namespace,
+/// which only has a loopback interface.
+fn default_network_isolation_command() -> Option<Vec<String>> {
+    if cfg!(target_os = "linux") {
+        Some(
+            ["unshare", "--user", "--map-current-user", "--net", "--"]
+                .map(str::to_owned)
+                .to_vec(),
+        )
+    } else {
+        None
+    }
+}
+
 fn create_cycle_detector() -> Arc<dyn UserCycleDetector> {
     Arc::new(PairDiceCycleDetector(
         CycleDetectorAdapter::<LoadCycleDescriptor>::new(),
@@ -585,7 +672,10 @@ impl<'s, 'a> DiceUpdater for DiceCommandUpdater<'s, 'a> {
             Arc::new(ConcurrentTargetLabelInterner::default()),
         )?;
 
-        ctx.set_buck_out_path(Some(self.cmd_ctx.buck_out_dir.clone()))?;
+        ctx.set_build_context_data(
+            Some(self.cmd_ctx.buck_out_dir.clone()),
+            output_path_scheme(&cells_and_configs.root_config)?,
+        )?;
 
         let optional_validations = self
             .cmd_ctx
@@ -595,6 +685,8 @@ impl<'s, 'a> DiceUpdater for DiceCommandUpdater<'s, 'a> {
 
         ctx.set_enabled_optional_validations(optional_validations)?;
 
+        ctx.set_build_metadata(self.cmd_ctx.build_metadata.clone())?;
+
         setup_interpreter(
             &mut ctx,
             cell_resolver,
@@ -644,13 +736,147 @@ impl<'a, 's> DiceCommandUpdater<'a, 's> {
             None => parse_concurrency(config_threads)?,
         };
 
-        if let Some(max_lines) = root_config.parse(BuckconfigKeyRef {
-            section: "ui",
-            property: "thread_line_limit",
-        })? {
+        let console_preferences = buck2_data::ConsolePreferences {
+            max_lines: root_config.parse(BuckconfigKeyRef {
+                section: "ui",
+                property: "thread_line_limit",
+            })?,
+            hyperlinks: root_config
+                .parse(BuckconfigKeyRef {
+                    section: "ui",
+                    property: "hyperlinks",
+                })?
+                .unwrap_or(false),
+            target_url_template: root_config
+                .get(BuckconfigKeyRef {
+                    section: "ui",
+                    property: "target_url",
+                })
+                .map(|s| s.to_owned())
+                .unwrap_or_default(),
+            color_blind: root_config
+                .parse(BuckconfigKeyRef {
+                    section: "ui",
+                    property: "color_blind",
+                })?
+                .unwrap_or(false),
+            no_emoji: !root_config
+                .parse(BuckconfigKeyRef {
+                    section: "ui",
+                    property: "emoji",
+                })?
+                .unwrap_or(true),
+            failed_action_output_files: root_config
+                .parse(BuckconfigKeyRef {
+                    section: "ui",
+                    property: "failed_action_output_files",
+                })?
+                .unwrap_or(false),
+        };
+        if console_preferences != buck2_data::ConsolePreferences::default() {
+            self.cmd_ctx.events().instant_event(console_preferences);
+        }
+
+        if let Some(telemetry_sample_rate) =
+            root_config.parse::<TelemetrySampleRate>(BuckconfigKeyRef {
+                section: "buck2",
+                property: "telemetry_sample_rate",
+            })?
+        {
+            let events = self.cmd_ctx.events();
+            events.instant_event(telemetry_sample_rate.to_proto(events.trace_id()));
+        }
+
+        if root_config
+            .parse::<bool>(BuckconfigKeyRef {
+                section: "buck2",
+                property: "per_target_telemetry",
+            })?
+            .unwrap_or(false)
+        {
+            self.cmd_ctx
+                .events()
+                .instant_event(buck2_data::PerTargetTelemetry {});
+        }
+
+        if root_config
+            .parse::<bool>(BuckconfigKeyRef {
+                section: "buck2",
+                property: "network_timeline",
+            })?
+            .unwrap_or(false)
+        {
+            self.cmd_ctx
+                .events()
+                .instant_event(buck2_data::NetworkTimeline {});
+        }
+
+        if root_config
+            .parse::<bool>(BuckconfigKeyRef {
+                section: "buck2",
+                property: "invocation_records",
+            })?
+            .unwrap_or(false)
+        {
+            let max_file_bytes = root_config
+                .parse::<u64>(BuckconfigKeyRef {
+                    section: "buck2",
+                    property: "invocation_records_max_file_bytes",
+                })?
+                .unwrap_or(64 << 20);
+            let max_files = root_config
+                .parse::<u64>(BuckconfigKeyRef {
+                    section: "buck2",
+                    property: "invocation_records_max_files",
+                })?
+                .unwrap_or(10);
+            self.cmd_ctx
+                .events()
+                .instant_event(buck2_data::InvocationRecordSink {
+                    max_file_bytes,
+                    max_files,
+                });
+        }
+
+        let soft_error_budget = root_config.parse::<SoftErrorBudget>(BuckconfigKeyRef {
+            section: "buck2",
+            property: "soft_error_budget",
+        })?;
+        let escalated = match &soft_error_budget {
+            Some(budget)
+                if root_config
+                    .parse::<bool>(BuckconfigKeyRef {
+                        section: "buck2",
+                        property: "soft_error_budget_escalate",
+                    })?
+                    .unwrap_or(false) =>
+            {
+                match self.cmd_ctx.soft_error_history.read() {
+                    Ok(history) => history.over_budget(budget),
+                    Err(e) => {
+                        tracing::warn!("Failed to read soft error history: {:#}", e);
+                        Vec::new()
+                    }
+                }
+            }
+            _ => Vec::new(),
+        };
+        // This is daemon-wide, but so is the history, so concurrent commands agree on it.
+        set_escalated_soft_error_categories(escalated.iter().cloned().collect());
+        if let Some(budget) = soft_error_budget {
+            let window = root_config
+                .parse::<u64>(BuckconfigKeyRef {
+                    section: "buck2",
+                    property: "soft_error_budget_window",
+                })?
+                .unwrap_or(100);
             self.cmd_ctx
                 .events()
-                .instant_event(buck2_data::ConsolePreferences { max_lines });
+                .instant_event(buck2_data::SoftErrorBudget {
+                    max_rates: budget.to_proto(),
+                    window,
+                    escalated,
+                });
         }
 
         let enable_miniperf = root_config
@@ -683,13 +909,45 @@ impl<'a, 's> DiceCommandUpdater<'a, 's> {
             })?
             .or(Some(10));
 
+        let network_isolation_command = match root_config.get(BuckconfigKeyRef {
+            section: "buck2",
+            property: "network_isolation_command",
+        }) {
+            Some(command) => Some(command.split_whitespace().map(str::to_owned).collect()),
+            None => default_network_isolation_command(),
+        }
+        .filter(|command: &Vec<String>| !command.is_empty())
+        .map(Arc::from);
+
+        let stream_output_max_lines_per_second = root_config
+            .parse::<u32>(BuckconfigKeyRef {
+                section: "buck2",
+                property: "stream_output_max_lines_per_second",
+            })?
+            .unwrap_or(100);
+
+        let stream_output_flush_interval_ms = root_config
+            .parse::<u64>(BuckconfigKeyRef {
+                section: "buck2",
+                property: "stream_output_flush_interval_ms",
+            })?
+            .unwrap_or(200);
+
         let executor_global_knobs = ExecutorGlobalKnobs {
             enable_miniperf,
             log_action_keys,
+            parent_trace_id: self.cmd_ctx.parent_trace_id.dupe(),
+            network_isolation_command,
+            // Zero disables the limit.
+            stream_output_max_lines_per_second: Some(stream_output_max_lines_per_second)
+                .filter(|max| *max != 0),
+            stream_output_flush_interval_ms,
         };
 
         let host_sharing_broker =
-            HostSharingBroker::new(HostSharingStrategy::SmallerTasksFirst, concurrency);
+            HostSharingBroker::new(HostSharingStrategy::SmallerTasksFirst, concurrency)
+                .with_memory_mb((system_memory_stats() / (1024 * 1024)) as usize)
+                .with_devices(local_devices(root_config)?);
 
         // We use the job count for the low pass filter too. The low pass filter prevents sending
         // RE-eligile tasks to local if their concurrency is higher than our threshold. While it
@@ -721,6 +979,24 @@ impl<'a, 's> DiceCommandUpdater<'a, 's> {
                 property: "use_network_action_output_cache",
             })?
             .unwrap_or(false);
+        if let Some(network_policy) = root_config.parse(BuckconfigKeyRef {
+            section: "buck2",
+            property: "network_policy",
+        })? {
+            run_action_knobs.network_policy = network_policy;
+        }
+        run_action_knobs.remote_dep_file_divergence_sampling = root_config
+            .parse::<u32>(BuckconfigKeyRef {
+                section: "buck2",
+                property: "remote_dep_file_divergence_sampling",
+            })?
+            .unwrap_or(0);
+        run_action_knobs.lost_inputs_max_reexecution_depth = root_config
+            .parse::<u32>(BuckconfigKeyRef {
+                section: "buck2",
+                property: "lost_inputs_max_reexecution_depth",
+            })?
+            .unwrap_or(3);
 
         let mut data = UserComputationData {
             data,
@@ -744,6 +1020,12 @@ impl<'a, 's> DiceCommandUpdater<'a, 's> {
             property: "override_use_case",
         })?;
 
+        let re_action_url_template =
+            root_config.parse::<ReActionUrlTemplate>(BuckconfigKeyRef {
+                section: "buck2_re_client",
+                property: "action_url_template",
+            })?;
+
         set_fallback_executor_config(&mut data.data, self.executor_config.dupe());
         data.set_re_client(
             self.re_connection
@@ -770,6 +1052,7 @@ impl<'a, 's> DiceCommandUpdater<'a, 's> {
             override_use_case,
             self.cmd_ctx.base_context.daemon.memory_tracker.dupe(),
             resource_control_config.hybrid_execution_memory_limit_gibibytes,
+            re_action_url_template,
         )));
         data.set_blocking_executor(self.cmd_ctx.base_context.daemon.blocking_executor.dupe());
         data.set_http_client(self.cmd_ctx.base_context.daemon.http_client.dupe());
@@ -792,6 +1075,20 @@ impl<'a, 's> DiceCommandUpdater<'a, 's> {
         );
         data.set_keep_going(self.keep_going);
         data.set_critical_path_backend(critical_path_backend);
+        data.set_critical_path_scheduling(CriticalPathScheduling {
+            history: self
+                .cmd_ctx
+                .base_context
+                .daemon
+                .critical_path_history
+                .dupe(),
+            enabled: root_config
+                .parse::<bool>(BuckconfigKeyRef {
+                    section: "buck2",
+                    property: "critical_path_scheduling",
+                })?
+                .unwrap_or(false),
+        });
         data.init_local_resource_registry();
         data.spawner = self.cmd_ctx.base_context.daemon.spawner.dupe();
 
@@ -915,6 +1212,10 @@ impl<'a> ServerCommandContextTrait for ServerCommandContext<'a> {
             metadata.insert("client".to_owned(), client_id_from_client_metadata.clone());
         }
 
+        if let Some(parent_trace_id) = &self.parent_trace_id {
+            metadata.insert("parent_trace_id".to_owned(), parent_trace_id.to_string());
+        }
+
         metadata.insert(
             "vpnless".to_owned(),
             self.base_context
@@ -1039,14 +1340,18 @@ impl<'a> ServerCommandContextTrait for ServerCommandContext<'a> {
     fn log_target_pattern(
         &self,
         providers_patterns: &[ParsedPattern<ConfiguredProvidersPatternExtra>],
+        label_filter: Option<&buck2_cli_proto::TargetLabelFilter>,
     ) {
         let patterns = providers_patterns.map(|pat| buck2_data::TargetPattern {
             value: format!("{}", pat),
         });
+        let label_filter = label_filter.cloned().unwrap_or_default();
 
         self.events()
             .instant_event(buck2_data::ParsedTargetPatterns {
                 target_patterns: patterns,
+                include_labels: label_filter.include_labels,
+                exclude_labels: label_filter.exclude_labels,
             })
     }
 
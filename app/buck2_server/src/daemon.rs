@@ -20,3 +20,4 @@ pub mod panic;
 pub mod server;
 pub(crate) mod server_allocative;
 pub mod state;
+pub(crate) mod thin_client;
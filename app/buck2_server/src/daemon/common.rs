@@ -41,6 +41,7 @@ use buck2_execute::execute::prepared::PreparedCommandOptionalExecutor;
 use buck2_execute::execute::request::ExecutorPreference;
 use buck2_execute::knobs::ExecutorGlobalKnobs;
 use buck2_execute::materialize::materializer::Materializer;
+use buck2_execute::re::action_url::ReActionUrlTemplate;
 use buck2_execute::re::manager::ManagedRemoteExecutionClient;
 use buck2_execute::re::manager::ReConnectionHandle;
 use buck2_execute_impl::executors::action_cache::ActionCacheChecker;
@@ -101,6 +102,7 @@ pub struct CommandExecutorFactory {
     re_use_case_override: Option<RemoteExecutorUseCase>,
     memory_tracker: Option<Arc<MemoryTracker>>,
     hybrid_execution_memory_limit_gibibytes: Option<u64>,
+    re_action_url_template: Option<ReActionUrlTemplate>,
 }
 
 impl CommandExecutorFactory {
@@ -123,6 +125,7 @@ impl CommandExecutorFactory {
         re_use_case_override: Option<RemoteExecutorUseCase>,
         memory_tracker: Option<Arc<MemoryTracker>>,
         hybrid_execution_memory_limit_gibibytes: Option<u64>,
+        re_action_url_template: Option<ReActionUrlTemplate>,
     ) -> Self {
         let cache_upload_permission_checker = Arc::new(ActionCacheUploadPermissionChecker::new(
             re_connection
@@ -150,6 +153,7 @@ impl CommandExecutorFactory {
             re_use_case_override,
             memory_tracker,
             hybrid_execution_memory_limit_gibibytes,
+            re_action_url_template,
         }
     }
 
@@ -237,6 +241,7 @@ impl HasCommandExecutor for CommandExecutorFactory {
                     paranoid: self.paranoid.dupe(),
                     materialize_failed_inputs: self.materialize_failed_inputs,
                     dependencies: dependencies.to_vec(),
+                    action_url_template: self.re_action_url_template.dupe(),
                 }
             };
 
@@ -13,8 +13,14 @@ use buck2_common::cas_digest::CasDigestConfig;
 use buck2_common::io::fs::FsIoProvider;
 use buck2_common::io::trace::TracingIoProvider;
 use buck2_common::io::IoProvider;
+use buck2_common::legacy_configs::cells::BuckConfigBasedCells;
 use buck2_common::legacy_configs::configs::LegacyBuckConfig;
+use buck2_common::pattern::prefetch::prefetch_dirs_for_cli_patterns;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
 use buck2_core::fs::project::ProjectRoot;
+use buck2_core::fs::project_rel_path::ProjectRelativePath;
+
+use crate::daemon::server::PrefetchTargetPatterns;
 
 pub async fn create_io_provider(
     fb: fbinit::FacebookInit,
@@ -22,6 +28,7 @@ pub async fn create_io_provider(
     root_config: &LegacyBuckConfig,
     cas_digest_config: CasDigestConfig,
     trace_io: bool,
+    eden_prefetch: bool,
 ) -> buck2_error::Result<Arc<dyn IoProvider>> {
     #[cfg(fbcode_build)]
     {
@@ -40,9 +47,13 @@ pub async fn create_io_provider(
             .roll();
 
         if allow_eden_io {
-            if let Some(eden) =
-                buck2_eden::io_provider::EdenIoProvider::new(fb, &project_fs, cas_digest_config)
-                    .await?
+            if let Some(eden) = buck2_eden::io_provider::EdenIoProvider::new(
+                fb,
+                &project_fs,
+                cas_digest_config,
+                eden_prefetch,
+            )
+            .await?
             {
                 return if trace_io {
                     Ok(Arc::new(TracingIoProvider::new(Box::new(eden))))
@@ -53,7 +64,7 @@ pub async fn create_io_provider(
         }
     }
 
-    let _allow_unused = (fb, root_config);
+    let _allow_unused = (fb, root_config, eden_prefetch);
 
     if trace_io {
         Ok(Arc::new(TracingIoProvider::new(Box::new(
@@ -63,3 +74,24 @@ pub async fn create_io_provider(
         Ok(Arc::new(FsIoProvider::new(project_fs, cas_digest_config)))
     }
 }
+
+/// Asks the file system to prefetch the build files of the target patterns of the command the
+/// daemon is started for, and the prelude, so that fetching them overlaps with daemon startup.
+pub(crate) async fn prefetch_on_startup(
+    io: &dyn IoProvider,
+    cells: &BuckConfigBasedCells,
+    project_fs: &ProjectRoot,
+    patterns: Option<&PrefetchTargetPatterns>,
+) -> buck2_error::Result<()> {
+    let dirs = match patterns {
+        Some(patterns) => {
+            let cwd = project_fs.relativize(AbsNormPath::new(&patterns.working_dir)?)?;
+            prefetch_dirs_for_cli_patterns(cells, project_fs, &cwd, &patterns.patterns).await?
+        }
+        None => {
+            prefetch_dirs_for_cli_patterns(cells, project_fs, ProjectRelativePath::empty(), &[])
+                .await?
+        }
+    };
+    io.prefetch(dirs).await
+}
@@ -55,6 +55,7 @@ use buck2_events::source::ChannelEventSource;
 use buck2_events::Event;
 use buck2_execute::digest_config::DigestConfig;
 use buck2_execute::materialize::materializer::MaterializationMethod;
+use buck2_execute::re::manager::ReConnectionStatus;
 use buck2_execute_impl::materializers::sqlite::MaterializerStateIdentity;
 use buck2_futures::cancellation::ExplicitCancellationContext;
 use buck2_futures::drop::DropTogether;
@@ -101,6 +102,9 @@ use tonic::Request;
 use tonic::Response;
 use tonic::Status;
 
+use crate::active_commands::active_commands;
+use crate::active_commands::idle_since;
+use crate::active_commands::stop_if_idle;
 use crate::active_commands::ActiveCommand;
 use crate::active_commands::ActiveCommandStateWriter;
 use crate::clean_stale::clean_stale_command;
@@ -109,6 +113,7 @@ use crate::daemon::crash::crash;
 use crate::daemon::multi_event_stream::MultiEventStream;
 use crate::daemon::server_allocative::spawn_allocative;
 use crate::daemon::state::DaemonState;
+use crate::daemon::thin_client::ThinClientListener;
 use crate::file_status::file_status_command;
 use crate::lsp::run_lsp_server_command;
 use crate::new_generic::new_generic_command;
@@ -163,9 +168,21 @@ pub struct BuckdServerInitPreferences {
     pub which_dice: Option<WhichDice>,
     pub enable_trace_io: bool,
     pub reject_materializer_state: Option<MaterializerStateIdentity>,
+    pub prefetch_target_patterns: Option<PrefetchTargetPatterns>,
+    /// Position of the file watcher of the daemon this one replaces, to start from.
+    pub file_watcher_cursor: Option<String>,
     pub daemon_startup_config: DaemonStartupConfig,
 }
 
+/// Target patterns of the command the daemon is started for, whose build files are prefetched
+/// while the daemon starts.
+#[derive(Allocative, Clone)]
+pub struct PrefetchTargetPatterns {
+    /// Absolute working directory the patterns are relative to.
+    pub working_dir: String,
+    pub patterns: Vec<String>,
+}
+
 impl BuckdServerInitPreferences {
     pub async fn construct_dice(
         &self,
@@ -226,13 +243,15 @@ pub(crate) struct BuckdServerData {
     log_reload_handle: Arc<dyn LogConfigurationReloadHandle>,
     #[allocative(skip)]
     rt: Handle,
+    /// The socket the thin client protocol is served on, if any.
+    thin_client_socket: Option<String>,
 }
 
 /// The BuckdServer implements the DaemonApi.
 ///
 /// Simple endpoints are implemented here and complex things will be implemented in a sibling
 /// module taking just a ServerCommandContext.
-#[derive(Allocative)]
+#[derive(Allocative, Clone, Dupe)]
 pub struct BuckdServer(Arc<BuckdServerData>);
 
 impl BuckdServer {
@@ -271,6 +290,10 @@ impl BuckdServer {
         let cert_state = CertState::new().await;
         certs_validation_background_job(cert_state.dupe()).await;
 
+        let thin_client_listener =
+            ThinClientListener::bind(paths.daemon_dir()?.thin_client_socket());
+        let project_root = paths.project_root().to_string();
+
         let daemon_state = Arc::new(
             DaemonState::new(fb, paths, init_ctx, rt.clone(), materializations, cwd).await,
         );
@@ -294,8 +317,15 @@ impl BuckdServer {
             command_channel,
             log_reload_handle,
             rt,
+            thin_client_socket: thin_client_listener
+                .as_ref()
+                .map(|listener| listener.socket().to_string()),
         }));
 
+        if let Some(thin_client_listener) = thin_client_listener {
+            thin_client_listener.serve(api_server.dupe(), project_root);
+        }
+
         let shutdown = server_shutdown_signal(command_receiver, shutdown_receiver)?;
         let server = Server::builder()
             .layer(interceptor(BuckCheckAuthTokenInterceptor { auth_token }))
@@ -401,7 +431,7 @@ impl BuckdServer {
             guard,
             daemon_shutdown_channel,
             state,
-        } = ActiveCommand::new(&dispatch, client_ctx.sanitized_argv.clone());
+        } = ActiveCommand::new(&dispatch, client_ctx.sanitized_argv.clone())?;
         let data = daemon_state.data()?;
 
         // Fire off a system-wide event to record the memory usage of this process.
@@ -435,7 +465,10 @@ impl BuckdServer {
         // Spawn an async task to collect expensive info
         // We start collecting inmediately, and emit the event as soon as it is ready
         let version_control_revision_collector =
-            version_control_revision::spawn_version_control_collector(dispatch.dupe());
+            version_control_revision::spawn_version_control_collector(
+                dispatch.dupe(),
+                daemon_state.paths.project_root().root().to_buf(),
+            );
 
         let resp = streaming(
             req,
@@ -544,6 +577,18 @@ impl BuckdServer {
             Ok(())
         }
     }
+
+    /// Saves what the daemon replacing this one can reuse: the pending materializer state is
+    /// written to its DB, and the position of the file watcher is returned.
+    async fn handoff(&self) -> Option<String> {
+        let data = self.0.daemon_state.data().ok()?;
+        if let Some(deferred) = data.materializer.as_deferred_materializer_extension() {
+            if let Err(e) = deferred.flush_all_access_times().await {
+                tracing::warn!("Failed to flush materializer state before handoff: {:#}", e);
+            }
+        }
+        data.file_watcher.cursor().await
+    }
 }
 
 fn convert_positive_duration(proto_duration: &prost_types::Duration) -> Result<Duration, Status> {
@@ -801,10 +846,26 @@ impl DaemonApi for BuckdServer {
         }
 
         self.oneshot(req, KillRunCommandOptions, move |req| async move {
+            if let Some(idle_for) = &req.only_if_idle_for {
+                let idle_for = convert_positive_duration(idle_for)?;
+                if !stop_if_idle(self.0.start_instant, idle_for) {
+                    return Ok(KillResponse {
+                        killed: false,
+                        file_watcher_cursor: None,
+                    });
+                }
+            }
+
             self.0
                 .stop_accepting_requests
                 .store(true, Ordering::Relaxed);
 
+            let file_watcher_cursor = if req.handoff {
+                self.handoff().await
+            } else {
+                None
+            };
+
             let timeout = req
                 .timeout
                 .as_ref()
@@ -817,7 +878,10 @@ impl DaemonApi for BuckdServer {
             };
 
             self.0.daemon_shutdown.start_shutdown(reason, timeout);
-            Ok(KillResponse {})
+            Ok(KillResponse {
+                killed: true,
+                file_watcher_cursor,
+            })
         })
         .await
     }
@@ -884,7 +948,17 @@ impl DaemonApi for BuckdServer {
                 .ok()
                 .map(|state| state.io.name().to_owned());
 
+            let re_connection_status = daemon_state
+                .data()
+                .as_ref()
+                .ok()
+                .map(|state| state.re_client_manager.connection_status());
+
             let uptime = self.0.start_instant.elapsed();
+            let idle_duration = match idle_since(self.0.start_instant) {
+                Some(idle_since) => idle_since.elapsed(),
+                None => Duration::ZERO,
+            };
             let base = StatusResponse {
                 process_info: Some(self.0.process_info.clone()),
                 start_time: Some(self.0.start_time.clone()),
@@ -911,6 +985,14 @@ impl DaemonApi for BuckdServer {
                 valid_working_directory: Some(valid_working_directory),
                 valid_buck_out_mount: Some(valid_buck_out_mount),
                 io_provider,
+                active_commands: Some(active_commands().len() as u64),
+                re_connection_status: re_connection_status.as_ref().map(|s| s.as_str().to_owned()),
+                re_connection_error: match re_connection_status {
+                    Some(ReConnectionStatus::Failed(e)) => Some(e),
+                    _ => None,
+                },
+                idle_duration: Some(idle_duration.try_into()?),
+                thin_client_socket: self.0.thin_client_socket.clone(),
                 ..Default::default()
             };
             Ok(base)
@@ -1275,7 +1357,8 @@ impl DaemonApi for BuckdServer {
             let client_ctx = req.get_ref().client_context()?;
             let trace_id = client_ctx.trace_id.parse()?;
             let (event_source, dispatcher) = self.0.daemon_state.prepare_events(trace_id).await?;
-            let active_command = ActiveCommand::new(&dispatcher, client_ctx.sanitized_argv.clone());
+            let active_command =
+                ActiveCommand::new(&dispatcher, client_ctx.sanitized_argv.clone())?;
             (event_source, dispatcher, active_command)
         };
 
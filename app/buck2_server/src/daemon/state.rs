@@ -16,6 +16,7 @@ use std::time::Instant;
 
 use allocative::Allocative;
 use buck2_build_api::spawner::BuckSpawner;
+use buck2_build_signals::critical_path_history::CriticalPathHistory;
 use buck2_cli_proto::unstable_dice_dump_request::DiceDumpFormat;
 use buck2_common::cas_digest::DigestAlgorithm;
 use buck2_common::cas_digest::DigestAlgorithmFamily;
@@ -44,9 +45,11 @@ use buck2_error::buck2_error;
 use buck2_error::BuckErrorContext;
 use buck2_events::dispatch::EventDispatcher;
 use buck2_events::sink::remote;
+use buck2_events::sink::remote::RemoteEventSink;
 use buck2_events::sink::tee::TeeSink;
 use buck2_events::source::ChannelEventSource;
 use buck2_events::EventSinkWithStats;
+use buck2_execute::artifact_signing::ArtifactSigners;
 use buck2_execute::digest_config::DigestConfig;
 use buck2_execute::execute::blocking::BlockingExecutor;
 use buck2_execute::execute::blocking::BuckBlockingExecutor;
@@ -67,6 +70,7 @@ use buck2_file_watcher::file_watcher::FileWatcher;
 use buck2_forkserver::client::ForkserverClient;
 use buck2_http::HttpClient;
 use buck2_http::HttpClientBuilder;
+use buck2_re_configuration::ReBandwidthCaps;
 use buck2_re_configuration::RemoteExecutionStaticMetadata;
 use buck2_re_configuration::RemoteExecutionStaticMetadataImpl;
 use buck2_server_ctx::concurrency::ConcurrencyHandler;
@@ -86,6 +90,7 @@ use crate::daemon::disk_state::maybe_initialize_materializer_sqlite_db;
 use crate::daemon::disk_state::DiskStateOptions;
 use crate::daemon::forkserver::maybe_launch_forkserver;
 use crate::daemon::io_provider::create_io_provider;
+use crate::daemon::io_provider::prefetch_on_startup;
 use crate::daemon::panic::DaemonStatePanicDiceDump;
 use crate::daemon::server::BuckdServerInitPreferences;
 
@@ -141,7 +146,7 @@ pub struct DaemonStateData {
     pub(crate) forkserver: Option<ForkserverClient>,
 
     #[allocative(skip)]
-    pub scribe_sink: Option<Arc<dyn EventSinkWithStats>>,
+    pub scribe_sink: Option<Arc<dyn RemoteEventSink>>,
 
     /// Whether or not to hash all commands
     pub hash_all_commands: bool,
@@ -188,6 +193,9 @@ pub struct DaemonStateData {
 
     /// Tracks memory usage. Used to make scheduling decisions.
     pub memory_tracker: Option<Arc<MemoryTracker>>,
+
+    /// Actions on the critical path of the previous builds, which are scheduled first.
+    pub critical_path_history: CriticalPathHistory,
 }
 
 impl DaemonStateData {
@@ -349,6 +357,11 @@ impl DaemonState {
             let static_metadata = Arc::new(RemoteExecutionStaticMetadata::from_legacy_config(
                 root_config,
             )?);
+            let re_bandwidth_caps = ReBandwidthCaps::from_legacy_config(root_config)?;
+            let re_digest_algorithms = root_config.parse_list(BuckconfigKeyRef {
+                section: "buck2_re_client",
+                property: "digest_algorithms",
+            })?;
 
             let mut ignore_specs: HashMap<CellName, IgnoreSet> = HashMap::new();
             for (cell, _) in cells.cells() {
@@ -397,6 +410,13 @@ impl DaemonState {
                     })?
                     .unwrap_or(3600);
 
+                let ttl_refresh_extension = root_config
+                    .parse::<i64>(BuckconfigKeyRef {
+                        section: "buck2",
+                        property: "ttl_refresh_extension_seconds",
+                    })?
+                    .filter(|s| *s > 0);
+
                 let ttl_refresh_enabled = root_config
                     .parse::<RolloutPercentage>(BuckconfigKeyRef {
                         section: "buck2",
@@ -429,6 +449,8 @@ impl DaemonState {
                     .unwrap_or_else(RolloutPercentage::never)
                     .roll();
 
+                let artifact_signers = Arc::new(ArtifactSigners::parse(root_config)?);
+
                 DeferredMaterializerConfigs {
                     materialize_final_artifacts: matches!(
                         materializations,
@@ -439,16 +461,25 @@ impl DaemonState {
                         frequency: std::time::Duration::from_secs(ttl_refresh_frequency),
                         min_ttl: chrono::Duration::seconds(ttl_refresh_min_ttl),
                         enabled: ttl_refresh_enabled,
+                        extension: ttl_refresh_extension.map(chrono::Duration::seconds),
                     },
                     update_access_times,
                     verbose_materializer_log,
                     clean_stale_config,
                     disable_eager_write_dispatch,
+                    artifact_signers,
                 }
             };
             let disable_eager_write_dispatch =
                 deferred_materializer_configs.disable_eager_write_dispatch;
 
+            let eden_prefetch = root_config
+                .parse(BuckconfigKeyRef {
+                    section: "buck2",
+                    property: "eden_prefetch",
+                })?
+                .unwrap_or(false);
+
             let (io, _, (materializer_db, materializer_state)) = futures::future::try_join3(
                 create_io_provider(
                     fb,
@@ -456,6 +487,7 @@ impl DaemonState {
                     root_config,
                     digest_config.cas_digest_config(),
                     init_ctx.enable_trace_io,
+                    eden_prefetch,
                 ),
                 (blocking_executor.dupe() as Arc<dyn BlockingExecutor>).execute_io_inline(|| {
                     // Using `execute_io_inline` is just out of convenience.
@@ -475,6 +507,22 @@ impl DaemonState {
             )
             .await?;
 
+            if eden_prefetch {
+                // Loading on a cold EdenFS checkout otherwise waits on fetching every build file
+                // when it is first read.
+                let io = io.dupe();
+                let legacy_cells = legacy_cells.clone();
+                let fs = fs.dupe();
+                let patterns = init_ctx.prefetch_target_patterns.clone();
+                tokio::spawn(async move {
+                    if let Err(e) =
+                        prefetch_on_startup(&*io, &legacy_cells, &fs, patterns.as_ref()).await
+                    {
+                        tracing::warn!("Failed to prefetch on daemon startup: {:#}", e);
+                    }
+                });
+            }
+
             let http_client = http_client_from_startup_config(&init_ctx.daemon_startup_config)
                 .await
                 .buck_error_context("Error creating HTTP client")?
@@ -488,6 +536,9 @@ impl DaemonState {
                 false,
                 10,
                 static_metadata.dupe(),
+                re_bandwidth_caps,
+                re_digest_algorithms,
+                digest_config,
                 Some(paths.re_logs_dir()),
                 paths.buck_out_path(),
                 init_ctx.daemon_startup_config.paranoid,
@@ -537,6 +588,7 @@ impl DaemonState {
                 root_config,
                 cells.dupe(),
                 ignore_specs,
+                init_ctx.file_watcher_cursor.as_deref(),
             )
             .with_buck_error_context(|| {
                 format!(
@@ -609,6 +661,15 @@ impl DaemonState {
                 ),
             ];
             let system_warning_config = SystemWarningConfig::from_config(root_config)?;
+
+            let critical_path_history =
+                match CriticalPathHistory::load(&paths.build_count_dir()).await {
+                    Ok(history) => history,
+                    Err(e) => {
+                        tracing::warn!("Failed to load critical path history: {:#}", e);
+                        CriticalPathHistory::new(&paths.build_count_dir())
+                    }
+                };
             // Kick off an initial sync eagerly. This gets Watchamn to start watching the path we care
             // about (potentially kicking off an initial crawl).
 
@@ -640,6 +701,7 @@ impl DaemonState {
                 tags,
                 system_warning_config,
                 memory_tracker,
+                critical_path_history,
             }))
         })
         .await?
@@ -721,7 +783,7 @@ impl DaemonState {
         retry_backoff: Duration,
         retry_attempts: usize,
         message_batch_size: Option<usize>,
-    ) -> buck2_error::Result<Option<Arc<dyn EventSinkWithStats>>> {
+    ) -> buck2_error::Result<Option<Arc<dyn RemoteEventSink>>> {
         facebook_only();
         remote::new_remote_event_sink_if_enabled(
             fb,
@@ -730,7 +792,6 @@ impl DaemonState {
             retry_attempts,
             message_batch_size,
         )
-        .map(|maybe_scribe| maybe_scribe.map(|scribe| Arc::new(scribe) as _))
     }
 
     /// Prepares an event stream for a request by bootstrapping an event source and EventDispatcher pair. The given
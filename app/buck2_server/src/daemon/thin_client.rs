@@ -0,0 +1,364 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Thin client protocol: a minimal way for scripts (shell prompts, editor plugins) to run
+//! queries on the daemon without the gRPC client.
+//!
+//! The daemon listens on a UNIX socket in the daemon dir. Every line written to the socket is a
+//! JSON-RPC 2.0 request, and is answered with one line containing the JSON-RPC response. The
+//! requests are run like the corresponding gRPC requests, but events are discarded and only the
+//! output of the command is returned. Access is controlled by the permissions of the socket.
+//!
+//! The protocol is documented in `docs/users/advanced/thin_client.md`.
+
+use buck2_cli_proto::ClientContext;
+use buck2_cli_proto::QueryOutputFormat;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+
+use crate::daemon::server::BuckdServer;
+
+/// Standard JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The command ran and failed.
+const COMMAND_FAILED: i64 = -32000;
+
+#[derive(serde::Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    #[serde(default)]
+    id: serde_json::Value,
+    method: String,
+    #[serde(default)]
+    params: serde_json::Value,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct QueryParams {
+    query: String,
+    /// Literals for a query containing `%s`.
+    #[serde(default)]
+    query_args: Vec<String>,
+    /// Regexes of attributes to output.
+    #[serde(default)]
+    output_attributes: Vec<String>,
+    /// Output in JSON format.
+    #[serde(default)]
+    json: bool,
+    /// Absolute path to resolve relative patterns against. Defaults to the project root.
+    #[serde(default)]
+    working_dir: Option<String>,
+    /// `cquery` only.
+    #[serde(default)]
+    target_universe: Vec<String>,
+    /// `cquery` only.
+    #[serde(default)]
+    target_platforms: Option<String>,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> RpcError {
+        RpcError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+fn response(id: serde_json::Value, result: Result<serde_json::Value, RpcError>) -> String {
+    let response = match result {
+        Ok(result) => serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": result,
+        }),
+        Err(RpcError { code, message }) => serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": code,
+                "message": message,
+            },
+        }),
+    };
+    response.to_string()
+}
+
+/// Listener for thin client connections.
+pub(crate) struct ThinClientListener {
+    socket: AbsNormPathBuf,
+    #[cfg(unix)]
+    listener: tokio::net::UnixListener,
+}
+
+impl ThinClientListener {
+    /// Listen on the thin client socket in the daemon dir. Failures are logged: the daemon works
+    /// without the socket.
+    pub(crate) fn bind(socket: AbsNormPathBuf) -> Option<ThinClientListener> {
+        #[cfg(unix)]
+        {
+            match Self::bind_unix(&socket) {
+                Ok(listener) => Some(ThinClientListener { socket, listener }),
+                Err(e) => {
+                    tracing::warn!("Not listening on thin client socket `{}`: {:#}", socket, e);
+                    None
+                }
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _unused = socket;
+            None
+        }
+    }
+
+    #[cfg(unix)]
+    fn bind_unix(socket: &AbsNormPath) -> buck2_error::Result<tokio::net::UnixListener> {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Left behind by a previous daemon.
+        buck2_core::fs::fs_util::remove_all(socket)?;
+        let listener = tokio::net::UnixListener::bind(socket)?;
+        std::fs::set_permissions(socket, std::fs::Permissions::from_mode(0o600))?;
+        Ok(listener)
+    }
+
+    pub(crate) fn socket(&self) -> &AbsNormPath {
+        &self.socket
+    }
+
+    /// Serve connections until the daemon exits.
+    pub(crate) fn serve(self, server: BuckdServer, project_root: String) {
+        #[cfg(unix)]
+        tokio::spawn(async move {
+            use dupe::Dupe;
+
+            loop {
+                match self.listener.accept().await {
+                    Ok((stream, _)) => {
+                        let server = server.dupe();
+                        let project_root = project_root.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = handle_connection(&server, &project_root, stream).await
+                            {
+                                tracing::debug!("Thin client connection failed: {:#}", e);
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        tracing::warn!("Error accepting thin client connection: {:#}", e);
+                        return;
+                    }
+                }
+            }
+        });
+        #[cfg(not(unix))]
+        {
+            let _unused = (server, project_root);
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn handle_connection(
+    server: &BuckdServer,
+    project_root: &str,
+    stream: tokio::net::UnixStream,
+) -> buck2_error::Result<()> {
+    use tokio::io::AsyncBufReadExt;
+    use tokio::io::AsyncWriteExt;
+
+    let (read, mut write) = stream.into_split();
+    let mut lines = tokio::io::BufReader::new(read).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut response = handle_request(server, project_root, &line).await;
+        response.push('\n');
+        write.write_all(response.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+async fn handle_request(server: &BuckdServer, project_root: &str, line: &str) -> String {
+    let request: RpcRequest = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return response(
+                serde_json::Value::Null,
+                Err(RpcError::new(PARSE_ERROR, e.to_string())),
+            );
+        }
+    };
+    if request.jsonrpc != "2.0" {
+        return response(
+            request.id,
+            Err(RpcError::new(INVALID_REQUEST, "`jsonrpc` must be `2.0`")),
+        );
+    }
+    let result = match request.method.as_str() {
+        "ping" => Ok(serde_json::json!({})),
+        "uquery" | "cquery" => match serde_json::from_value::<QueryParams>(request.params) {
+            Ok(params) => run_query(server, project_root, &request.method, params).await,
+            Err(e) => Err(RpcError::new(INVALID_PARAMS, e.to_string())),
+        },
+        method => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method `{}`", method),
+        )),
+    };
+    response(request.id, result)
+}
+
+async fn run_query(
+    server: &BuckdServer,
+    project_root: &str,
+    method: &str,
+    params: QueryParams,
+) -> Result<serde_json::Value, RpcError> {
+    use buck2_cli_proto::daemon_api_server::DaemonApi;
+
+    let context = ClientContext {
+        working_dir: params
+            .working_dir
+            .unwrap_or_else(|| project_root.to_owned()),
+        trace_id: buck2_wrapper_common::invocation_id::TraceId::new().to_string(),
+        command_name: method.to_owned(),
+        sanitized_argv: vec![
+            "buck2".to_owned(),
+            "thin-client".to_owned(),
+            method.to_owned(),
+        ],
+        ..Default::default()
+    };
+    let unstable_output_format = if params.json {
+        QueryOutputFormat::Json
+    } else {
+        QueryOutputFormat::Default
+    } as i32;
+
+    let response = if method == "uquery" {
+        server
+            .uquery(tonic::Request::new(buck2_cli_proto::UqueryRequest {
+                context: Some(context),
+                query: params.query,
+                query_args: params.query_args,
+                output_attributes: params.output_attributes,
+                unstable_output_format,
+                ..Default::default()
+            }))
+            .await
+    } else {
+        server
+            .cquery(tonic::Request::new(buck2_cli_proto::CqueryRequest {
+                context: Some(context),
+                query: params.query,
+                query_args: params.query_args,
+                output_attributes: params.output_attributes,
+                target_universe: params.target_universe,
+                target_cfg: Some(buck2_cli_proto::TargetCfg {
+                    target_platform: params.target_platforms.unwrap_or_default(),
+                    cli_modifiers: Vec::new(),
+                }),
+                unstable_output_format,
+                ..Default::default()
+            }))
+            .await
+    };
+    let mut stream = response
+        .map_err(|e| RpcError::new(COMMAND_FAILED, e.message()))?
+        .into_inner();
+
+    let mut stdout = Vec::new();
+    while let Some(progress) = futures::StreamExt::next(&mut stream).await {
+        let progress = progress.map_err(|e| RpcError::new(COMMAND_FAILED, e.message()))?;
+        for message in progress.messages {
+            match message.progress {
+                Some(buck2_cli_proto::command_progress::Progress::PartialResult(partial)) => {
+                    if let Some(buck2_cli_proto::partial_result::PartialResult::StdoutBytes(
+                        bytes,
+                    )) = partial.partial_result
+                    {
+                        stdout.extend(bytes.data);
+                    }
+                }
+                Some(buck2_cli_proto::command_progress::Progress::Result(result)) => {
+                    if let Some(buck2_cli_proto::command_result::Result::Error(error)) =
+                        result.result
+                    {
+                        let message = error
+                            .errors
+                            .iter()
+                            .map(|e| e.message.as_str())
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        return Err(RpcError::new(COMMAND_FAILED, message));
+                    }
+                    return Ok(serde_json::json!({
+                        "stdout": String::from_utf8_lossy(&stdout),
+                    }));
+                }
+                Some(buck2_cli_proto::command_progress::Progress::Event(_)) | None => {}
+            }
+        }
+    }
+    Err(RpcError::new(
+        COMMAND_FAILED,
+        "Command finished without a result",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::daemon::thin_client::response;
+    use crate::daemon::thin_client::QueryParams;
+    use crate::daemon::thin_client::RpcError;
+    use crate::daemon::thin_client::METHOD_NOT_FOUND;
+
+    #[test]
+    fn test_response() {
+        assert_eq!(
+            r#"{"id":1,"jsonrpc":"2.0","result":{"stdout":"//:a\n"}}"#,
+            response(
+                serde_json::json!(1),
+                Ok(serde_json::json!({"stdout": "//:a\n"}))
+            )
+        );
+        assert_eq!(
+            r#"{"error":{"code":-32601,"message":"Unknown method `build`"},"id":"x","jsonrpc":"2.0"}"#,
+            response(
+                serde_json::json!("x"),
+                Err(RpcError::new(METHOD_NOT_FOUND, "Unknown method `build`"))
+            )
+        );
+    }
+
+    #[test]
+    fn test_query_params() {
+        let params: QueryParams =
+            serde_json::from_str(r#"{"query": "deps(%s)", "query_args": ["//:a"], "json": true}"#)
+                .unwrap();
+        assert_eq!("deps(%s)", params.query);
+        assert_eq!(vec!["//:a".to_owned()], params.query_args);
+        assert!(params.json);
+        assert!(params.working_dir.is_none());
+
+        assert!(serde_json::from_str::<QueryParams>(r#"{"query": "x", "foo": 1}"#).is_err());
+    }
+}
@@ -29,5 +29,6 @@ pub(crate) mod new_generic;
 pub mod profile;
 mod snapshot;
 mod subscription;
+mod telemetry_sampling;
 mod trace_io;
 mod version_control_revision;
@@ -16,7 +16,9 @@ use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_core::io_counters::IoCounterKey;
 use buck2_error::BuckErrorContext;
 use buck2_events::EventSinkStats;
+use buck2_events::EventSinkWithStats;
 use buck2_execute::re::manager::ReConnectionManager;
+use buck2_futures::cpu_attribution::CpuSubsystem;
 use buck2_util::process_stats::process_stats;
 use buck2_util::system_stats::UnixSystemStats;
 use dupe::Dupe;
@@ -65,6 +67,11 @@ impl SnapshotCollector {
     fn add_daemon_metrics(&self, snapshot: &mut buck2_data::Snapshot) {
         snapshot.blocking_executor_io_queue_size =
             self.daemon.blocking_executor.queue_size() as u64;
+        let select_cache = buck2_node::attrs::select_cache::select_cache_stats();
+        snapshot.select_cache_hits = select_cache.hits;
+        snapshot.select_cache_misses = select_cache.misses;
+        snapshot.daemon_cpu_attribution = Some(daemon_cpu_attribution());
+        snapshot.file_watcher_latency = buck2_file_watcher::latency::file_watcher_latency();
     }
 
     fn add_io_metrics(&self, snapshot: &mut buck2_data::Snapshot) {
@@ -132,6 +139,13 @@ impl SnapshotCollector {
                 stats.get_digest_expirations.finished_successfully;
             snapshot.re_get_digest_expirations_finished_with_error =
                 stats.get_digest_expirations.finished_with_error;
+            snapshot.re_download_bytes_per_second_cap = stats.download_throttle.bytes_per_second;
+            snapshot.re_upload_bytes_per_second_cap = stats.upload_throttle.bytes_per_second;
+            snapshot.re_downloads_throttled = stats.download_throttle.waiting;
+            snapshot.re_uploads_throttled = stats.upload_throttle.waiting;
+            snapshot.re_find_missing_blobs_rpcs = stats.cas_rpcs.find_missing_blobs;
+            snapshot.re_batch_update_blobs_rpcs = stats.cas_rpcs.batch_update_blobs;
+            snapshot.re_bytestream_write_rpcs = stats.cas_rpcs.bytestream_writes;
 
             snapshot.zdb_download_queries = stats.download_stats.zdb.queries;
             snapshot.zdb_download_bytes = stats.download_stats.zdb.bytes;
@@ -290,3 +304,19 @@ impl SnapshotCollector {
         }
     }
 }
+
+fn daemon_cpu_attribution() -> buck2_data::DaemonCpuAttribution {
+    let mut attribution = buck2_data::DaemonCpuAttribution::default();
+    // Using loop here to make sure no subsystem is forgotten.
+    for subsystem in CpuSubsystem::ALL {
+        let pointer = match subsystem {
+            CpuSubsystem::Loading => &mut attribution.loading_us,
+            CpuSubsystem::Analysis => &mut attribution.analysis_us,
+            CpuSubsystem::ActionExecution => &mut attribution.action_execution_us,
+            CpuSubsystem::Materializer => &mut attribution.materializer_us,
+            CpuSubsystem::Dice => &mut attribution.dice_us,
+        };
+        *pointer = subsystem.cpu_time().as_micros() as u64;
+    }
+    attribution
+}
@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Sampling of the high volume telemetry sent by the client.
+//!
+//! On large fleets, the snapshots and per-target stats sent by every command make up most of the
+//! telemetry volume. With `buck2.telemetry_sample_rate`, only a fraction of commands send them.
+//! The decision is made once per command here, and sent to the client as a `TelemetrySampling`
+//! event. Aggregate counters are still sent by every command.
+
+use std::str::FromStr;
+
+use buck2_wrapper_common::invocation_id::TraceId;
+use rand::Rng;
+
+#[derive(Debug, buck2_error::Error)]
+#[buck2(input)]
+enum TelemetrySampleRateError {
+    #[error(
+        "Invalid telemetry sample rate `{0}`, expected a float in [0, 1], optionally prefixed with `trace_id:` or `random:`"
+    )]
+    Invalid(String),
+}
+
+/// Parsed from `<rate>`, `trace_id:<rate>` or `random:<rate>`. By default, commands are sampled by
+/// hash of their trace ID, so that all the telemetry of a trace is consistently sampled, including
+/// by other tools using the same trace ID.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct TelemetrySampleRate {
+    rate: f64,
+    random: bool,
+}
+
+impl FromStr for TelemetrySampleRate {
+    type Err = buck2_error::Error;
+
+    fn from_str(val: &str) -> Result<Self, Self::Err> {
+        let (rate, random) = if let Some(rate) = val.strip_prefix("random:") {
+            (rate, true)
+        } else {
+            (val.strip_prefix("trace_id:").unwrap_or(val), false)
+        };
+        match rate.parse::<f64>() {
+            Ok(rate) if (0.0..=1.0).contains(&rate) => Ok(TelemetrySampleRate { rate, random }),
+            _ => Err(TelemetrySampleRateError::Invalid(val.to_owned()).into()),
+        }
+    }
+}
+
+impl TelemetrySampleRate {
+    fn is_sampled(&self, trace_id: &TraceId) -> bool {
+        let roll = if self.random {
+            rand::thread_rng().gen::<f64>()
+        } else {
+            (trace_id.hash() as u64 % 1_000_000) as f64 / 1_000_000.0
+        };
+        roll < self.rate
+    }
+
+    pub(crate) fn to_proto(&self, trace_id: &TraceId) -> buck2_data::TelemetrySampling {
+        buck2_data::TelemetrySampling {
+            sample_rate: self.rate,
+            sampled: self.is_sampled(trace_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_wrapper_common::invocation_id::TraceId;
+
+    use crate::telemetry_sampling::TelemetrySampleRate;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            TelemetrySampleRate {
+                rate: 0.5,
+                random: false
+            },
+            "0.5".parse().unwrap()
+        );
+        assert_eq!(
+            TelemetrySampleRate {
+                rate: 0.25,
+                random: false
+            },
+            "trace_id:0.25".parse().unwrap()
+        );
+        assert_eq!(
+            TelemetrySampleRate {
+                rate: 1.0,
+                random: true
+            },
+            "random:1".parse().unwrap()
+        );
+        assert!("1.5".parse::<TelemetrySampleRate>().is_err());
+        assert!("hostname:0.5".parse::<TelemetrySampleRate>().is_err());
+    }
+
+    #[test]
+    fn test_is_sampled() {
+        let trace_id = TraceId::new();
+        for random in [false, true] {
+            let always = TelemetrySampleRate { rate: 1.0, random };
+            let never = TelemetrySampleRate { rate: 0.0, random };
+            assert!(always.is_sampled(&trace_id));
+            assert!(!never.is_sampled(&trace_id));
+        }
+
+        let half = TelemetrySampleRate {
+            rate: 0.5,
+            random: false,
+        };
+        assert_eq!(half.is_sampled(&trace_id), half.is_sampled(&trace_id));
+    }
+}
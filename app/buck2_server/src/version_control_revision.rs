@@ -7,24 +7,31 @@
  * of this source tree.
  */
 
-use std::mem;
-use std::process::Output;
-use std::process::Stdio;
-
-use buck2_error::internal_error;
-use buck2_error::BuckErrorContext;
+use buck2_common::vcs::detect_vcs;
+use buck2_common::vcs::Vcs;
+use buck2_common::vcs::VcsKind;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
 use buck2_events::dispatch::EventDispatcher;
-use buck2_util::process::async_background_command;
-use tokio::io::AsyncReadExt;
-use tokio::process::Child;
-use tokio::sync::OnceCell;
 
 /// Spawn tasks to collect version control information
 /// and return a droppable handle that will cancel them on drop.
-pub(crate) fn spawn_version_control_collector(dispatch: EventDispatcher) -> AbortOnDropHandle {
+pub(crate) fn spawn_version_control_collector(
+    dispatch: EventDispatcher,
+    project_root: AbsNormPathBuf,
+) -> AbortOnDropHandle {
     AbortOnDropHandle {
         handle: tokio::spawn(async move {
-            let event = create_revision_data().await;
+            let event = match detect_vcs(project_root.as_abs_path()) {
+                Ok(Some(vcs)) => create_revision_data(&*vcs).await,
+                Ok(None) => buck2_data::VersionControlRevision {
+                    command_error: Some("Unknown repository type".to_owned()),
+                    ..Default::default()
+                },
+                Err(e) => buck2_data::VersionControlRevision {
+                    command_error: Some(e.to_string()),
+                    ..Default::default()
+                },
+            };
             dispatch.instant_event(event);
         }),
     }
@@ -41,189 +48,71 @@ impl Drop for AbortOnDropHandle {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-enum RepoVcs {
-    Hg,
-    Git,
-    Unknown,
-}
-
-/// A wrapper over a child process that will reap the child process on drop.
-/// On Unix platforms, a child process becomes a zombie until it is reaped by its parent.
-struct ProperlyReapedChild {
-    child: Option<Child>,
-}
-
-impl ProperlyReapedChild {
-    async fn output(mut self) -> buck2_error::Result<Output> {
-        let mut stdout = Vec::new();
-        let mut stderr = Vec::new();
-        let mut child = mem::take(&mut self.child).internal_error("child field must be set")?;
-        let mut stdout_pipe = child
-            .stdout
-            .take()
-            .buck_error_context("stdout is not piped")?;
-        let mut stderr_pipe = child
-            .stderr
-            .take()
-            .buck_error_context("stderr is not piped")?;
-        let (stdout_error, stderr_error, status) = tokio::join!(
-            stdout_pipe.read_to_end(&mut stdout),
-            stderr_pipe.read_to_end(&mut stderr),
-            child.wait(),
-        );
-
-        let result = match stdout_error.is_ok() || stderr_error.is_ok() {
-            true => Ok(Output {
-                status: status?,
-                stdout,
-                stderr,
-            }),
-            false => Err(internal_error!("Failed to read stdout and stderr")),
-        };
-        reap_child(child);
-        result
-    }
-}
-
-impl Drop for ProperlyReapedChild {
-    fn drop(&mut self) {
-        if let Some(child) = mem::take(&mut self.child) {
-            reap_child(child);
-        }
-    }
-}
-
-fn reap_on_drop_command(command: &str, args: &[&str]) -> buck2_error::Result<ProperlyReapedChild> {
-    async_background_command(command)
-        .args(args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .kill_on_drop(true)
-        .spawn()
-        .map(|child| ProperlyReapedChild { child: Some(child) })
-        .map_err(|e| e.into())
-}
+async fn create_revision_data(vcs: &dyn Vcs) -> buck2_data::VersionControlRevision {
+    let checkout = vcs.checkout();
+    let mut revision = buck2_data::VersionControlRevision {
+        sparse_checkout: Some(checkout.is_sparse),
+        git_worktree: Some(checkout.is_worktree),
+        git_submodule: Some(checkout.is_submodule),
+        ..Default::default()
+    };
 
-fn reap_child(mut child: Child) {
-    tokio::spawn(async move {
-        if let Some(child_id) = child.id() {
-            // If a child process has already exited, the child.id() is None.
-            tracing::warn!("Killed child process: {:?}", child_id);
-        }
-        drop(child.kill().await);
-    });
-}
+    // Both commands run in parallel: the revision hash, and whether there are local changes.
+    let (current_revision, has_local_changes) =
+        tokio::join!(vcs.current_revision(), vcs.has_local_changes());
 
-async fn create_revision_data() -> buck2_data::VersionControlRevision {
-    let mut revision = buck2_data::VersionControlRevision::default();
-    match repo_type().await {
-        Ok(repo_vcs) => {
-            match repo_vcs {
-                RepoVcs::Hg => {
-                    match add_hg_data(&mut revision).await {
-                        Err(e) => {
-                            revision.command_error = Some(e.to_string());
-                        }
-                        Ok(_) => {}
-                    };
-                }
-                RepoVcs::Git => {
-                    // TODO(rajneeshl): Implement the git data
-                }
-                RepoVcs::Unknown => {
-                    revision.command_error = Some("Unknown repository type".to_owned());
-                }
-            }
-        }
+    match current_revision {
+        Ok(current_revision) => match checkout.kind {
+            VcsKind::Hg => revision.hg_revision = Some(current_revision),
+            VcsKind::Git => revision.git_revision = Some(current_revision),
+        },
         Err(e) => {
             revision.command_error = Some(e.to_string());
+            return revision;
         }
     }
+    match has_local_changes {
+        Ok(has_local_changes) => revision.has_local_changes = Some(has_local_changes),
+        Err(e) => revision.command_error = Some(e.to_string()),
+    }
     revision
 }
 
-async fn add_hg_data(revision: &mut buck2_data::VersionControlRevision) -> buck2_error::Result<()> {
-    // We fire 2 hg command in parallel:
-    //  The `hg whereami` returns the full hash of the revision
-    //  The `hg status` returns if there are any local changes
-    let whereami_command = reap_on_drop_command("hg", &["whereami"])?;
-    let status_command = reap_on_drop_command("hg", &["status"])?;
-
-    let (whereami_output, status_output) =
-        tokio::join!(whereami_command.output(), status_command.output());
-
-    match whereami_output {
-        Ok(result) => {
-            if !result.status.success() {
-                revision.command_error = Some(format!(
-                    "Command `hg whereami` failed with error code {}; stderr:\n{}",
-                    result.status,
-                    std::str::from_utf8(&result.stderr)?
-                ));
-                return Ok(());
-            }
-            let stdout = std::str::from_utf8(&result.stdout)?.trim();
-            if stdout.len() == 40 {
-                revision.hg_revision = Some(stdout.to_owned());
-            } else {
-                revision.command_error = Some(format!("Unexpected revision : {}", stdout));
-            }
-        }
-        Err(e) => {
-            revision.command_error =
-                Some(format!("Command `hg whereami` failed with error: {:?}", e));
-        }
-    }
-
-    match status_output {
-        Ok(result) => {
-            if !result.status.success() {
-                revision.command_error = Some(format!(
-                    "Command `hg status` failed with error code {}; stderr:\n{}",
-                    result.status,
-                    std::str::from_utf8(&result.stderr)?
-                ));
-                return Ok(());
+#[cfg(test)]
+mod tests {
+    use buck2_common::vcs::Checkout;
+    use buck2_common::vcs::FakeVcs;
+    use buck2_core::fs::paths::abs_path::AbsPathBuf;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_revision_data() -> buck2_error::Result<()> {
+        let mut vcs = FakeVcs::new(
+            Checkout {
+                kind: VcsKind::Git,
+                root: AbsPathBuf::new(std::env::current_dir()?)?,
+                is_worktree: true,
+                is_submodule: false,
+                is_sparse: true,
+            },
+            "0123456789012345678901234567890123456789",
+        );
+        vcs.has_local_changes = true;
+
+        let revision = create_revision_data(&vcs).await;
+        assert_eq!(
+            revision,
+            buck2_data::VersionControlRevision {
+                hg_revision: None,
+                git_revision: Some("0123456789012345678901234567890123456789".to_owned()),
+                has_local_changes: Some(true),
+                command_error: None,
+                sparse_checkout: Some(true),
+                git_worktree: Some(true),
+                git_submodule: Some(false),
             }
-            revision.has_local_changes =
-                Some(!std::str::from_utf8(&result.stdout)?.trim().is_empty());
-            return Ok(());
-        }
-        Err(e) => {
-            revision.command_error =
-                Some(format!("Command `hg status` failed with error: {:?}", e));
-        }
-    };
-    Ok(())
-}
-
-async fn repo_type() -> buck2_error::Result<&'static RepoVcs> {
-    static REPO_TYPE: OnceCell<buck2_error::Result<RepoVcs>> = OnceCell::const_new();
-    async fn repo_type_impl() -> buck2_error::Result<RepoVcs> {
-        let (hg_output, git_output) = tokio::join!(
-            reap_on_drop_command("hg", &["root"])?.output(),
-            reap_on_drop_command("git", &["rev-parse", "--is-inside-work-tree"])?.output()
         );
-
-        let is_hg = hg_output.map_or(false, |output| {
-            std::str::from_utf8(&output.stdout).map_or(false, |s| !s.trim().is_empty())
-        });
-        let is_git = git_output.map_or(false, |output| {
-            std::str::from_utf8(&output.stdout).map_or(false, |s| s.trim() == "true")
-        });
-
-        if is_hg {
-            Ok(RepoVcs::Hg)
-        } else if is_git {
-            Ok(RepoVcs::Git)
-        } else {
-            Ok(RepoVcs::Unknown)
-        }
+        Ok(())
     }
-    REPO_TYPE
-        .get_or_init(repo_type_impl)
-        .await
-        .as_ref()
-        .map_err(|e| e.clone())
 }
@@ -28,6 +28,7 @@ use buck2_build_api::build::ConfiguredBuildEvent;
 use buck2_build_api::build::HasCreateUnhashedSymlinkLock;
 use buck2_build_api::build::ProviderArtifacts;
 use buck2_build_api::build::ProvidersToBuild;
+use buck2_build_api::build::SkippedTarget;
 use buck2_build_api::materialize::MaterializationContext;
 use buck2_cli_proto::build_request::build_providers::Action as BuildProviderAction;
 use buck2_cli_proto::build_request::BuildProviders;
@@ -64,6 +65,7 @@ use buck2_node::load_patterns::MissingTargetBehavior;
 use buck2_node::nodes::frontend::TargetGraphCalculation;
 use buck2_node::nodes::unconfigured::TargetNode;
 use buck2_node::target_calculation::ConfiguredTargetCalculation;
+use buck2_node::target_label_filter::TargetLabelFilter;
 use buck2_server_ctx::commands::send_target_cfg_event;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::partial_result_dispatcher::NoPartialResult;
@@ -83,10 +85,14 @@ use itertools::Itertools;
 use serde::ser::SerializeSeq;
 use serde::ser::Serializer;
 
+use crate::commands::build::latest_symlinks::update_latest_symlinks;
+use crate::commands::build::provenance::write_provenance;
 use crate::commands::build::result_report::ResultReporter;
 use crate::commands::build::result_report::ResultReporterOptions;
 use crate::commands::build::unhashed_outputs::create_unhashed_outputs;
 
+mod latest_symlinks;
+mod provenance;
 #[allow(unused)]
 mod result_report;
 mod unhashed_outputs;
@@ -220,7 +226,7 @@ async fn build(
 
     let parsed_patterns: Vec<ParsedPattern<ConfiguredProvidersPatternExtra>> =
         parse_patterns_from_cli_args(&mut ctx, &request.target_patterns, cwd).await?;
-    server_ctx.log_target_pattern(&parsed_patterns);
+    server_ctx.log_target_pattern(&parsed_patterns, request.target_label_filter.as_ref());
 
     let resolved_pattern: ResolvedPattern<ConfiguredProvidersPatternExtra> =
         ResolveTargetPatterns::resolve(&mut ctx, &parsed_patterns).await?;
@@ -238,6 +244,13 @@ async fn build(
 
     let build_providers = Arc::new(request.build_providers.clone().unwrap());
 
+    let target_label_filter = request
+        .target_label_filter
+        .clone()
+        .map(|f| TargetLabelFilter::new(f.include_labels, f.exclude_labels))
+        .unwrap_or_default();
+    let target_label_filter = &target_label_filter;
+
     let final_artifact_materializations =
         Materializations::from_i32(request.final_artifact_materializations)
             .with_buck_error_context(|| "Invalid final_artifact_materializations")
@@ -260,6 +273,7 @@ async fn build(
                 &ctx,
                 resolved_pattern,
                 target_resolution_config,
+                target_label_filter,
                 build_providers,
                 &final_artifact_materializations.into(),
                 build_opts.fail_fast,
@@ -277,6 +291,13 @@ async fn build(
         &request.target_cfg,
     );
 
+    if let Some(message) = SkippedTarget::summary_message(&build_result.skipped_targets()) {
+        console_message(message);
+    }
+    if let Some(message) = build_result.annotations_message() {
+        console_message(message);
+    }
+
     process_build_result(server_ctx, ctx, request, build_result).await
 }
 
@@ -318,20 +339,25 @@ async fn process_build_result(
             server_ctx.events().trace_id(),
             &build_result.configured,
             &build_result.other_errors,
+            build_result.skipped_targets(),
         )?
     } else {
         None
     };
 
     let mut provider_artifacts = Vec::new();
-    for v in build_result.configured.into_values() {
+    // Range of `provider_artifacts` for each requested target.
+    let mut requested_outputs = Vec::new();
+    for (label, v) in build_result.configured {
         // We omit skipped targets here.
         let Some(v) = v else { continue };
         let mut outputs = v.outputs.into_iter().filter_map(|output| match output {
             Ok(output) => Some(output),
             _ => None,
         });
+        let start = provider_artifacts.len();
         provider_artifacts.extend(&mut outputs);
+        requested_outputs.push((label, start..provider_artifacts.len()));
     }
 
     if let Some(output_hashes_file) = &request.output_hashes_file {
@@ -349,6 +375,45 @@ async fn process_build_result(
         .await?;
     }
 
+    if let Some(provenance_file) = &request.provenance_file {
+        write_provenance(
+            &mut ctx,
+            provenance_file,
+            requested_outputs
+                .iter()
+                .map(|(label, range)| (label, &provider_artifacts[range.clone()])),
+            &artifact_fs,
+        )
+        .await
+        .with_buck_error_context(|| {
+            format!("Failed to write provenance file to {provenance_file}")
+        })?;
+    }
+
+    let should_update_latest_links = ctx
+        .parse_legacy_config_property(
+            cell_resolver.root_cell(),
+            BuckconfigKeyRef {
+                section: "buck2",
+                property: "latest_output_links",
+            },
+        )
+        .await?;
+
+    if should_update_latest_links.unwrap_or(false) {
+        let lock = ctx
+            .per_transaction_data()
+            .get_create_unhashed_symlink_lock();
+        let _guard = lock.lock().await;
+        update_latest_symlinks(
+            requested_outputs
+                .iter()
+                .map(|(label, range)| (label, &provider_artifacts[range.clone()])),
+            &artifact_fs,
+            fs,
+        )?;
+    }
+
     let should_create_unhashed_links = ctx
         .parse_legacy_config_property(
             cell_resolver.root_cell(),
@@ -399,6 +464,7 @@ async fn build_targets(
     ctx: &LinearRecomputeDiceComputations<'_>,
     spec: ResolvedPattern<ConfiguredProvidersPatternExtra>,
     target_resolution_config: TargetResolutionConfig,
+    target_label_filter: &TargetLabelFilter,
     build_providers: Arc<BuildProviders>,
     materialization: &MaterializationContext,
     fail_fast: bool,
@@ -415,6 +481,7 @@ async fn build_targets(
                 ctx,
                 spec,
                 global_cfg_options,
+                target_label_filter,
                 build_providers,
                 materialization,
                 missing_target_behavior,
@@ -427,6 +494,7 @@ async fn build_targets(
             ctx,
             spec,
             universe,
+            target_label_filter,
             build_providers,
             materialization,
             want_configured_graph_size,
@@ -442,12 +510,23 @@ fn build_targets_in_universe<'a>(
     ctx: &'a LinearRecomputeDiceComputations,
     spec: ResolvedPattern<ConfiguredProvidersPatternExtra>,
     universe: CqueryUniverse,
+    target_label_filter: &TargetLabelFilter,
     build_providers: Arc<BuildProviders>,
     materialization: &'a MaterializationContext,
     want_configured_graph_size: bool,
 ) -> impl Stream<Item = ConfiguredBuildEvent> + Unpin + 'a {
     let providers_to_build = build_providers_to_providers_to_build(&build_providers);
-    let provider_labels = universe.get_provider_labels(&spec);
+    let mut provider_labels = universe.get_provider_labels(&spec);
+    if !target_label_filter.is_empty() {
+        provider_labels.retain(|p| {
+            universe
+                .get_from_targets([p.target().unconfigured().dupe()])
+                .iter()
+                .any(|node| {
+                    node.label() == p.target() && target_label_filter.matches(node.target_node())
+                })
+        });
+    }
     provider_labels
         .into_iter()
         .map(|p| {
@@ -474,6 +553,7 @@ fn build_targets_with_global_target_platform<'a>(
     ctx: &'a LinearRecomputeDiceComputations<'_>,
     spec: ResolvedPattern<ProvidersPatternExtra>,
     global_cfg_options: GlobalCfgOptions,
+    target_label_filter: &'a TargetLabelFilter,
     build_providers: Arc<BuildProviders>,
     materialization: &'a MaterializationContext,
     missing_target_behavior: MissingTargetBehavior,
@@ -486,6 +566,7 @@ fn build_targets_with_global_target_platform<'a>(
             spec,
             package,
             global_cfg_options.dupe(),
+            target_label_filter,
             build_providers.dupe(),
             materialization,
             missing_target_behavior,
@@ -533,6 +614,7 @@ async fn build_targets_for_spec<'a>(
     spec: PackageSpec<ProvidersPatternExtra>,
     package: PackageLabel,
     global_cfg_options: GlobalCfgOptions,
+    target_label_filter: &TargetLabelFilter,
     build_providers: Arc<BuildProviders>,
     materialization: &'a MaterializationContext,
     missing_target_behavior: MissingTargetBehavior,
@@ -587,16 +669,20 @@ async fn build_targets_for_spec<'a>(
             }))
             .left_stream()
         }
-        (Some(missing), MissingTargetBehavior::Warn) => {
-            // TODO: This should be reported in the build report eventually.
-            console_message(missing.missing_targets_warning());
-            futures::stream::empty().right_stream()
-        }
-        (None, _) => futures::stream::empty().right_stream(),
+        (Some(missing), MissingTargetBehavior::Warn) => futures::stream::iter(
+            missing
+                .into_missing_targets()
+                .into_iter()
+                .map(|label| BuildEvent::SkippedMissing { label }),
+        )
+        .left_stream()
+        .right_stream(),
+        (None, _) => futures::stream::empty().right_stream().right_stream(),
     };
 
     let todo_targets: Vec<TargetBuildSpec> = targets
         .into_iter()
+        .filter(|(_, target)| target_label_filter.matches(target))
         .map(|((_target_name, extra), target)| TargetBuildSpec {
             target,
             providers: extra.providers,
@@ -0,0 +1,144 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Maintains `buck-out/latest/<target name>`, a symlink to the default output of each target
+//! requested by the last build that built it, so that scripts can use a stable path.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+
+use buck2_build_api::build::BuildProviderType;
+use buck2_build_api::build::ProviderArtifacts;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::fs::fs_util;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPath;
+use buck2_core::fs::paths::abs_norm_path::AbsNormPathBuf;
+use buck2_core::fs::paths::file_name::FileNameBuf;
+use buck2_core::fs::project::ProjectRoot;
+use buck2_core::provider::label::ConfiguredProvidersLabel;
+use buck2_error::BuckErrorContext;
+use itertools::Itertools;
+use tracing::info;
+
+/// The link name for a target. Target names may contain `/`, which would nest the link.
+fn link_name(label: &ConfiguredProvidersLabel) -> FileNameBuf {
+    FileNameBuf::unchecked_new(label.target().name().as_str().replace('/', "+"))
+}
+
+/// The single default output of a target, if it has exactly one.
+fn default_output(
+    outputs: &[ProviderArtifacts],
+    artifact_fs: &ArtifactFs,
+    fs: &ProjectRoot,
+) -> buck2_error::Result<Option<AbsNormPathBuf>> {
+    let default_outputs = outputs
+        .iter()
+        .filter(|o| matches!(o.provider_type, BuildProviderType::Default))
+        .flat_map(|o| o.values.iter());
+    match default_outputs.exactly_one() {
+        Ok((artifact, _)) => Ok(Some(fs.resolve(&artifact.get_path().resolve(artifact_fs)?))),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Points the links of the requested targets at their default output. Targets without exactly
+/// one default output, or whose name is shared with another requested target, are skipped.
+pub(crate) fn update_latest_symlinks<'a>(
+    requested: impl IntoIterator<Item = (&'a ConfiguredProvidersLabel, &'a [ProviderArtifacts])>,
+    artifact_fs: &ArtifactFs,
+    fs: &ProjectRoot,
+) -> buck2_error::Result<u64> {
+    let buck_out_root = fs.resolve(artifact_fs.buck_out_path_resolver().root());
+    let Some(latest_dir) = buck_out_root
+        .parent()
+        .map(|p| p.join(FileNameBuf::unchecked_new("latest")))
+    else {
+        return Ok(0);
+    };
+
+    let mut links: BTreeMap<FileNameBuf, AbsNormPathBuf> = BTreeMap::new();
+    let mut conflicts = HashSet::new();
+    for (label, outputs) in requested {
+        let Some(output) = default_output(outputs, artifact_fs, fs)? else {
+            continue;
+        };
+        let name = link_name(label);
+        if links.insert(name.clone(), output).is_some() {
+            conflicts.insert(name);
+        }
+    }
+    for name in &conflicts {
+        info!(
+            "Not updating `{}`, several requested targets are named `{}`",
+            latest_dir.join(name),
+            name
+        );
+        links.remove(name);
+    }
+
+    if links.is_empty() {
+        return Ok(0);
+    }
+    fs_util::create_dir_all(&latest_dir)
+        .with_buck_error_context(|| "while creating the latest outputs directory")?;
+    let mut created = 0;
+    for (name, output) in links {
+        replace_symlink(&latest_dir, &name, &output)?;
+        created += 1;
+    }
+    Ok(created)
+}
+
+/// Readers of the link either see the old or the new target: the new link is created next to
+/// it and renamed over it.
+fn replace_symlink(
+    dir: &AbsNormPath,
+    name: &FileNameBuf,
+    original: &AbsNormPath,
+) -> buck2_error::Result<()> {
+    let link = dir.join(name);
+    let tmp = dir.join(FileNameBuf::unchecked_new(format!(".{}.tmp", name)));
+    if fs_util::symlink_metadata_if_exists(&tmp)?.is_some() {
+        fs_util::remove_all(&tmp)?;
+    }
+    fs_util::symlink(original, &tmp)?;
+    if let Some(meta) = fs_util::symlink_metadata_if_exists(&link)? {
+        // A rename cannot replace a real directory, which can only be there if put by hand.
+        if meta.is_dir() {
+            fs_util::remove_all(&link)?;
+        }
+    }
+    fs_util::rename(&tmp, &link)
+        .with_buck_error_context(|| format!("while updating latest output link `{}`", link))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use buck2_common::temp_path::TempPath;
+
+    use super::*;
+
+    #[test]
+    fn test_replace_symlink() {
+        let tempdir = TempPath::new().unwrap();
+        let dir = tempdir.path();
+        fs_util::create_dir_all(dir).unwrap();
+        let a = dir.join(FileNameBuf::unchecked_new("a"));
+        let b = dir.join(FileNameBuf::unchecked_new("b"));
+        fs_util::write(&a, "a").unwrap();
+        fs_util::write(&b, "b").unwrap();
+
+        let name = FileNameBuf::unchecked_new("out");
+        replace_symlink(dir, &name, &a).unwrap();
+        assert_eq!(fs_util::read_to_string(dir.join(&name)).unwrap(), "a");
+        replace_symlink(dir, &name, &b).unwrap();
+        assert_eq!(fs_util::read_to_string(dir.join(&name)).unwrap(), "b");
+    }
+}
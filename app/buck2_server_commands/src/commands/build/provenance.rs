@@ -0,0 +1,259 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Provenance of the outputs of `buck2 build --provenance`: what each requested output was built
+//! from and how, assembled from the action graph of the build.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::BufWriter;
+use std::io::Write;
+use std::sync::Arc;
+
+use buck2_artifact::actions::key::ActionKey;
+use buck2_build_api::actions::calculation::ActionCalculation;
+use buck2_build_api::artifact_groups::calculation::ArtifactGroupCalculation;
+use buck2_build_api::build::ProviderArtifacts;
+use buck2_core::execution_types::executor_config::Executor;
+use buck2_core::fs::artifact_path_resolver::ArtifactFs;
+use buck2_core::provider::label::ConfiguredProvidersLabel;
+use buck2_core::target::configured_target_label::ConfiguredTargetLabel;
+use buck2_error::BuckErrorContext;
+use buck2_execute::artifact::fs::ExecutorFs;
+use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
+use buck2_node::nodes::unconfigured::RuleKind;
+use dice::DiceComputations;
+use dupe::Dupe;
+use indexmap::IndexMap;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Provenance {
+    outputs: Vec<OutputProvenance>,
+}
+
+#[derive(Serialize)]
+struct OutputProvenance {
+    target: String,
+    path: String,
+    /// Absent for symlinks.
+    digest: Option<String>,
+    /// Source files and directories the output was built from, transitively.
+    sources: Vec<SourceProvenance>,
+    /// Toolchain targets that defined any of the actions the output was built with.
+    toolchains: BTreeSet<String>,
+    /// Files downloaded by `download_file` actions, with their URL and checksums.
+    downloads: Vec<IndexMap<String, String>>,
+    actions: Vec<ActionProvenance>,
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct SourceProvenance {
+    path: String,
+    digest: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+struct ActionProvenance {
+    owner: String,
+    name: String,
+    kind: &'static str,
+    executor: String,
+    /// Remote execution metadata, for actions with a remote-enabled executor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    re_use_case: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    re_properties: Option<BTreeMap<String, String>>,
+}
+
+/// What a single action contributes to provenance, without its dependencies.
+struct ActionNode {
+    action: ActionProvenance,
+    toolchain: Option<String>,
+    download: Option<IndexMap<String, String>>,
+    sources: Vec<SourceProvenance>,
+    deps: Vec<ActionKey>,
+}
+
+struct ProvenanceCollector<'c, 'd> {
+    ctx: &'c mut DiceComputations<'d>,
+    artifact_fs: &'c ArtifactFs,
+    /// Shared across outputs, whose action graphs usually overlap.
+    nodes: HashMap<ActionKey, Arc<ActionNode>>,
+    toolchains: HashMap<ConfiguredTargetLabel, bool>,
+}
+
+impl ProvenanceCollector<'_, '_> {
+    async fn is_toolchain(&mut self, label: &ConfiguredTargetLabel) -> buck2_error::Result<bool> {
+        if let Some(is_toolchain) = self.toolchains.get(label) {
+            return Ok(*is_toolchain);
+        }
+        let is_toolchain = self
+            .ctx
+            .get_configured_target_node(label)
+            .await?
+            .require_compatible()?
+            .rule_kind()
+            == RuleKind::Toolchain;
+        self.toolchains.insert(label.dupe(), is_toolchain);
+        Ok(is_toolchain)
+    }
+
+    async fn node(&mut self, key: &ActionKey) -> buck2_error::Result<Arc<ActionNode>> {
+        if let Some(node) = self.nodes.get(key) {
+            return Ok(node.dupe());
+        }
+        let action = ActionCalculation::get_action(self.ctx, key).await?;
+
+        let mut sources = Vec::new();
+        let mut deps = Vec::new();
+        for input in action.inputs()?.iter() {
+            let values = self.ctx.ensure_artifact_group(input).await?;
+            for (artifact, value) in values.iter() {
+                if let Some(key) = artifact.action_key() {
+                    deps.push(key.dupe());
+                } else if artifact.is_source() {
+                    sources.push(SourceProvenance {
+                        path: artifact.get_path().resolve(self.artifact_fs)?.to_string(),
+                        digest: value.digest().map(|d| d.to_string()),
+                    });
+                }
+            }
+        }
+
+        let config = action.execution_config();
+        let (re_use_case, re_properties) = match &config.executor {
+            Executor::Local(_) => (None, None),
+            Executor::RemoteEnabled(options) => (
+                Some(options.re_use_case.to_string()),
+                Some(
+                    options
+                        .re_properties
+                        .properties
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect(),
+                ),
+            ),
+        };
+        let toolchain = match action.owner().unpack_target_label() {
+            Some(label) if self.is_toolchain(label).await? => {
+                Some(label.unconfigured().to_string())
+            }
+            _ => None,
+        };
+        let download = match action.kind() {
+            buck2_data::ActionKind::DownloadFile => Some(action.action().aquery_attributes(
+                &ExecutorFs::new(self.artifact_fs, config.options.path_separator),
+            )),
+            _ => None,
+        };
+
+        let node = Arc::new(ActionNode {
+            action: ActionProvenance {
+                owner: action.owner().to_string(),
+                name: action.name(),
+                kind: action.kind().as_str_name(),
+                executor: config.executor.to_string(),
+                re_use_case,
+                re_properties,
+            },
+            toolchain,
+            download,
+            sources,
+            deps,
+        });
+        self.nodes.insert(key.dupe(), node.dupe());
+        Ok(node)
+    }
+
+    /// Provenance of an output built by the action `key`.
+    async fn output(
+        &mut self,
+        target: String,
+        path: String,
+        digest: Option<String>,
+        key: &ActionKey,
+    ) -> buck2_error::Result<OutputProvenance> {
+        let mut sources = BTreeSet::new();
+        let mut toolchains = BTreeSet::new();
+        let mut downloads = Vec::new();
+        let mut actions = Vec::new();
+
+        let mut visited = HashSet::new();
+        let mut queue = vec![key.dupe()];
+        while let Some(key) = queue.pop() {
+            if !visited.insert(key.dupe()) {
+                continue;
+            }
+            let node = self.node(&key).await?;
+            sources.extend(node.sources.iter().cloned());
+            toolchains.extend(node.toolchain.clone());
+            downloads.extend(node.download.clone());
+            actions.push(node.action.clone());
+            queue.extend(node.deps.iter().map(|k| k.dupe()));
+        }
+
+        Ok(OutputProvenance {
+            target,
+            path,
+            digest,
+            sources: sources.into_iter().collect(),
+            toolchains,
+            downloads,
+            actions,
+        })
+    }
+}
+
+/// Writes the provenance of the outputs of the requested targets to `path`, as JSON.
+pub(crate) async fn write_provenance<'a>(
+    ctx: &mut DiceComputations<'_>,
+    path: &str,
+    requested: impl IntoIterator<Item = (&'a ConfiguredProvidersLabel, &'a [ProviderArtifacts])>,
+    artifact_fs: &ArtifactFs,
+) -> buck2_error::Result<()> {
+    let mut collector = ProvenanceCollector {
+        ctx,
+        artifact_fs,
+        nodes: HashMap::new(),
+        toolchains: HashMap::new(),
+    };
+    let mut outputs = Vec::new();
+    for (label, provider_artifacts) in requested {
+        for (artifact, value) in provider_artifacts.iter().flat_map(|o| o.values.iter()) {
+            // Requested outputs can be source files, which have nothing to trace.
+            let Some(key) = artifact.action_key() else {
+                continue;
+            };
+            outputs.push(
+                collector
+                    .output(
+                        label.to_string(),
+                        artifact.get_path().resolve(artifact_fs)?.to_string(),
+                        value.digest().map(|d| d.to_string()),
+                        key,
+                    )
+                    .await?,
+            );
+        }
+    }
+
+    let file =
+        std::fs::File::create(path).buck_error_context("Failed to create provenance file")?;
+    let mut writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut writer, &Provenance { outputs })
+        .buck_error_context("Failed to write provenance file")?;
+    writer
+        .flush()
+        .buck_error_context("Failed to flush provenance file")?;
+    Ok(())
+}
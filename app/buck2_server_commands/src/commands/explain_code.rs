@@ -223,6 +223,7 @@ pub(crate) async fn explain(
                 global_cfg_options.dupe(),
                 target_universe,
                 false, // collect universes
+                false, // profile
             )
             .await?;
 
@@ -246,6 +247,7 @@ pub(crate) async fn explain(
                     global_cfg_options.dupe(),
                     Some(&[req.target.clone()]), // target universe
                     false,
+                    false,
                 )
                 .await?;
 
@@ -259,7 +259,7 @@ async fn collect_install_request_data<'a>(
         cwd,
     )
     .await?;
-    server_ctx.log_target_pattern(&parsed_patterns);
+    server_ctx.log_target_pattern(&parsed_patterns, None);
     let resolved_pattern = ResolveTargetPatterns::resolve(ctx, &parsed_patterns).await?;
 
     let resolved_pattern = resolved_pattern
@@ -129,6 +129,7 @@ async fn aquery(
             query,
             query_args,
             global_cfg_options,
+            request.profile_query,
         )
         .await?;
 
@@ -193,6 +193,7 @@ async fn cquery(
             global_cfg_options,
             target_universe,
             profile_mode.is_some(),
+            request.profile_query,
         )
         .await?;
 
@@ -145,7 +145,13 @@ async fn uquery(
 
     let query_result = QUERY_FRONTEND
         .get()?
-        .eval_uquery(&mut ctx, server_ctx.working_dir(), query, query_args)
+        .eval_uquery(
+            &mut ctx,
+            server_ctx.working_dir(),
+            query,
+            query_args,
+            request.profile_query,
+        )
         .await?;
 
     match query_result {
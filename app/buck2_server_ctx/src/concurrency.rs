@@ -58,6 +58,8 @@ use tokio::sync::oneshot::error::RecvError;
 use tokio::sync::Mutex;
 use tokio::sync::MutexGuard;
 
+use crate::invalidation_storm::invalidation_storm;
+
 #[derive(buck2_error::Error, Debug)]
 enum ConcurrencyHandlerError {
     #[error(
@@ -487,6 +489,10 @@ impl ConcurrencyHandler {
                     }
                     .await?;
 
+                    if let Some(storm) = invalidation_storm(&self.dice.last_invalidation().await) {
+                        event_dispatcher.instant_event(storm);
+                    }
+
                     if let Some(active) = active {
                         let is_same_state = transaction.equivalent(&active.version);
 
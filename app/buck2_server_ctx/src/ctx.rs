@@ -12,6 +12,7 @@ use std::future::Future;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use buck2_build_signals::critical_path_history::HasCriticalPathScheduling;
 use buck2_build_signals::env::BuildSignalsContext;
 use buck2_build_signals::env::DeferredBuildSignals;
 use buck2_build_signals::env::HasCriticalPathBackend;
@@ -74,6 +75,7 @@ pub trait ServerCommandContextTrait: Send + Sync {
     fn log_target_pattern(
         &self,
         providers_patterns: &[ParsedPattern<ConfiguredProvidersPatternExtra>],
+        label_filter: Option<&buck2_cli_proto::TargetLabelFilter>,
     );
 
     fn cancellation_context(&self) -> &ExplicitCancellationContext;
@@ -181,6 +183,9 @@ impl ServerCommandDiceContext for dyn ServerCommandContextTrait + '_ {
                                                     isolation_prefix: self
                                                         .isolation_prefix()
                                                         .to_owned(),
+                                                    critical_path_scheduling: dice
+                                                        .per_transaction_data()
+                                                        .get_critical_path_scheduling(),
                                                 },
                                                 || exec(self, dice),
                                             )
@@ -0,0 +1,81 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Detection of small changes, e.g. to a single `.bzl` file loaded by everything, that
+//! invalidate a large part of the DICE graph.
+
+use dice::InvalidationMetrics;
+
+/// Changes of more keys than this are expected to invalidate a lot, e.g. a rebase.
+const MAX_CHANGED_KEYS: usize = 32;
+
+/// Invalidating fewer keys than this is cheap, whatever the fraction of the graph.
+const MIN_INVALIDATED_KEYS: usize = 10_000;
+
+/// Fraction of the graph a small change has to invalidate to be reported.
+const MIN_INVALIDATED_FRACTION: f64 = 0.2;
+
+/// Returns the event to report if the latest change to the DICE state was a small change that
+/// invalidated an anomalously large fraction of the graph.
+pub fn invalidation_storm(
+    invalidation: &InvalidationMetrics,
+) -> Option<buck2_data::DiceInvalidationStorm> {
+    if invalidation.changed_key_count == 0
+        || invalidation.changed_key_count > MAX_CHANGED_KEYS
+        || invalidation.invalidated_key_count < MIN_INVALIDATED_KEYS
+        || (invalidation.invalidated_key_count as f64)
+            < (invalidation.key_count as f64) * MIN_INVALIDATED_FRACTION
+    {
+        return None;
+    }
+
+    Some(buck2_data::DiceInvalidationStorm {
+        changed_keys: invalidation.changed_key_count as u64,
+        invalidated_keys: invalidation.invalidated_key_count as u64,
+        graph_keys: invalidation.key_count as u64,
+        hotspots: invalidation
+            .hotspots
+            .iter()
+            .map(|(key, invalidated)| buck2_data::DiceInvalidationHotspot {
+                key: key.clone(),
+                invalidated_keys: *invalidated as u64,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalidation(changed: usize, invalidated: usize, total: usize) -> InvalidationMetrics {
+        InvalidationMetrics {
+            changed_key_count: changed,
+            invalidated_key_count: invalidated,
+            key_count: total,
+            hotspots: vec![("root//defs.bzl".to_owned(), invalidated)],
+        }
+    }
+
+    #[test]
+    fn test_invalidation_storm() {
+        let storm = invalidation_storm(&invalidation(1, 50_000, 100_000)).unwrap();
+        assert_eq!(storm.invalidated_keys, 50_000);
+        assert_eq!(storm.hotspots[0].key, "root//defs.bzl");
+
+        // Nothing changed.
+        assert!(invalidation_storm(&invalidation(0, 0, 100_000)).is_none());
+        // Large change.
+        assert!(invalidation_storm(&invalidation(1000, 50_000, 100_000)).is_none());
+        // Small graph.
+        assert!(invalidation_storm(&invalidation(1, 500, 1000)).is_none());
+        // Small fraction of the graph.
+        assert!(invalidation_storm(&invalidation(1, 50_000, 1_000_000)).is_none());
+    }
+}
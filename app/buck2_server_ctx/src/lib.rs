@@ -17,6 +17,7 @@ pub mod commands;
 pub mod concurrency;
 pub mod ctx;
 pub mod global_cfg_options;
+pub mod invalidation_storm;
 pub mod late_bindings;
 pub mod partial_result_dispatcher;
 pub mod pattern_parse_and_resolve;
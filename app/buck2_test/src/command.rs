@@ -65,6 +65,7 @@ use buck2_node::load_patterns::MissingTargetBehavior;
 use buck2_node::nodes::configured_frontend::ConfiguredTargetNodeCalculation;
 use buck2_node::nodes::frontend::TargetGraphCalculation;
 use buck2_node::target_calculation::ConfiguredTargetCalculation;
+use buck2_node::target_label_filter::TargetLabelFilter;
 use buck2_server_ctx::commands::send_target_cfg_event;
 use buck2_server_ctx::ctx::ServerCommandContextTrait;
 use buck2_server_ctx::global_cfg_options::global_cfg_options_from_client_context;
@@ -316,7 +317,7 @@ async fn test(
 
     let parsed_patterns =
         parse_patterns_from_cli_args(&mut ctx, &request.target_patterns, cwd).await?;
-    server_ctx.log_target_pattern(&parsed_patterns);
+    server_ctx.log_target_pattern(&parsed_patterns, request.target_label_filter.as_ref());
 
     let resolved_pattern = ResolveTargetPatterns::resolve(&mut ctx, &parsed_patterns).await?;
 
@@ -331,11 +332,12 @@ async fn test(
         .as_ref()
         .context("Missing `options`")?;
 
-    let session = TestSession::new(TestSessionOptions {
+    let session = Arc::new(TestSession::new(TestSessionOptions {
         allow_re: options.allow_re,
         force_use_project_relative_paths: options.force_use_project_relative_paths,
         force_run_from_project_root: options.force_run_from_project_root,
-    });
+        list_only: options.list_only,
+    }));
 
     let build_opts = request
         .build_opts
@@ -361,13 +363,18 @@ async fn test(
             request.build_filtered_targets,
         )),
         &*launcher,
-        session,
+        session.dupe(),
         cell_resolver.dupe(),
         working_dir_cell,
         build_opts.skip_incompatible_targets,
         MissingTargetBehavior::from_skip(build_opts.skip_missing_targets),
         timeout,
         request.ignore_tests_attribute,
+        request
+            .target_label_filter
+            .clone()
+            .map(|f| TargetLabelFilter::new(f.include_labels, f.exclude_labels))
+            .unwrap_or_default(),
     )
     .await?;
 
@@ -438,11 +445,23 @@ async fn test(
             server_ctx.events().trace_id(),
             &test_outcome.build_target_result.configured,
             &test_outcome.build_target_result.other_errors,
+            test_outcome.build_target_result.skipped_targets(),
         )?
     } else {
         None
     };
 
+    let listed_tests = session
+        .take_discovered()
+        .into_iter()
+        .map(|tests| buck2_cli_proto::test_response::ListedTests {
+            target: tests.target.to_string(),
+            suite: tests.suite,
+            test_names: tests.names,
+            labels: tests.labels,
+        })
+        .collect();
+
     Ok(TestResponse {
         exit_code,
         errors: test_outcome.errors,
@@ -451,6 +470,7 @@ async fn test(
         executor_stderr: test_outcome.executor_stderr,
         executor_info_messages: test_outcome.executor_report.info_messages,
         serialized_build_report,
+        listed_tests,
     })
 }
 
@@ -461,15 +481,15 @@ async fn test_targets(
     external_runner_args: Vec<String>,
     label_filtering: Arc<TestLabelFiltering>,
     launcher: &dyn ExecutorLauncher,
-    session: TestSession,
+    session: Arc<TestSession>,
     cell_resolver: CellResolver,
     working_dir_cell: CellName,
     skip_incompatible_targets: bool,
     missing_target_behavior: MissingTargetBehavior,
     timeout: Option<Duration>,
     ignore_tests_attribute: bool,
+    target_label_filter: TargetLabelFilter,
 ) -> anyhow::Result<TestOutcome> {
-    let session = Arc::new(session);
 
     let (mut liveliness_observer, _guard) = LivelinessGuard::create();
     let timeout_observer = timeout.map(|timeout| {
@@ -548,6 +568,7 @@ async fn test_targets(
                     working_dir_cell,
                     missing_target_behavior,
                     ignore_tests_attribute,
+                    target_label_filter: &target_label_filter,
                 });
 
                 driver.push_pattern(
@@ -687,6 +708,7 @@ struct TestDriverState<'a, 'e> {
     working_dir_cell: CellName,
     missing_target_behavior: MissingTargetBehavior,
     ignore_tests_attribute: bool,
+    target_label_filter: &'a TargetLabelFilter,
 }
 
 /// Maintains the state of an ongoing test execution.
@@ -839,9 +861,12 @@ impl<'a, 'e> TestDriver<'a, 'e> {
                     }
                 }
 
-                let labels = targets.into_keys().map(|(target_name, providers_pattern)| {
-                    providers_pattern.into_providers_label(package.dupe(), target_name.as_ref())
-                });
+                let labels = targets
+                    .into_iter()
+                    .filter(|(_, target)| state.target_label_filter.matches(target))
+                    .map(|((target_name, providers_pattern), _)| {
+                        providers_pattern.into_providers_label(package.dupe(), target_name.as_ref())
+                    });
 
                 let work = labels
                     .into_iter()
@@ -20,8 +20,10 @@ pub mod local_resource_registry;
 pub(crate) mod local_resource_setup;
 pub mod orchestrator;
 pub(crate) mod remote_storage;
+pub(crate) mod resource_requirements;
 pub mod session;
 pub(crate) mod tcp;
+pub(crate) mod test_env;
 pub mod translations;
 #[cfg(unix)]
 pub(crate) mod unix;
@@ -15,6 +15,7 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::ffi::OsStr;
+use std::ffi::OsString;
 use std::fmt::Display;
 use std::ops::ControlFlow;
 use std::ops::DerefMut;
@@ -157,8 +158,13 @@ use crate::local_resource_setup::required_local_resources_setup_contexts;
 use crate::local_resource_setup::LocalResourceSetupContext;
 use crate::local_resource_setup::TestStageSimple;
 use crate::remote_storage;
+use crate::resource_requirements::TestResourceRequirements;
+use crate::session::DiscoveredTests;
 use crate::session::TestSession;
 use crate::session::TestSessionOptions;
+use crate::test_env::resolve_sandbox;
+use crate::test_env::SandboxedVar;
+use crate::test_env::TestEnvConfig;
 use crate::translations;
 
 const MAX_SUFFIX_LEN: usize = 1024;
@@ -240,6 +246,12 @@ impl<'a> BuckTestOrchestrator<'a> {
     ) -> Result<ExecutionResult2, ExecuteError> {
         Self::require_alive(self.liveliness_observer.dupe()).await?;
 
+        // When only listing tests, the test runner still runs the listings to discover the test
+        // cases, but nothing is tested.
+        if self.session.options().list_only && matches!(stage, TestStage::Testing { .. }) {
+            return Err(ExecuteError::Cancelled(Cancelled));
+        }
+
         let test_target = self.session.get(test_target)?;
 
         let fs = self
@@ -371,6 +383,10 @@ impl<'a> BuckTestOrchestrator<'a> {
         } = key;
         let fs = dice.get_artifact_fs().await.map_err(anyhow::Error::from)?;
         let test_info = Self::get_test_info(dice, &test_target).await?;
+        let test_env_config = TestEnvConfig::get(dice).await?.for_test(&test_info)?;
+        let resource_requirements = TestResourceRequirements::from_test_info(&test_info);
+        let host_sharing_requirements =
+            resource_requirements.host_sharing_requirements(host_sharing_requirements);
         let test_executor = Self::get_test_executor(
             dice,
             &test_target,
@@ -390,6 +406,7 @@ impl<'a> BuckTestOrchestrator<'a> {
             &test_executor.executor().executor_fs(),
             prefix,
             options,
+            test_env_config.sandbox(),
         )
         .boxed()
         .await?;
@@ -401,6 +418,7 @@ impl<'a> BuckTestOrchestrator<'a> {
             supports_re,
             declared_outputs,
             worker,
+            sandboxed_env,
         } = test_executable_expanded;
         let executor_preference = Self::executor_preference(options, supports_re)?;
         let required_resources = if test_executor
@@ -448,9 +466,13 @@ impl<'a> BuckTestOrchestrator<'a> {
             required_resources,
             worker,
             test_executor.re_dynamic_image(),
+            test_env_config.env_inheritance(sandboxed_env),
         )
         .boxed()
-        .await?;
+        .await?
+        .with_host_resource_requirements(Arc::new(
+            resource_requirements.host_resource_requirements(),
+        ));
         let result = Self::execute_request(
             dice,
             cancellation,
@@ -698,6 +720,16 @@ impl<'a> TestOrchestrator for BuckTestOrchestrator<'a> {
     ) -> anyhow::Result<()> {
         let test_target = self.session.get(test_target)?;
 
+        if self.session.options().list_only {
+            let test_info = Self::get_test_info(self.dice.dupe().deref_mut(), &test_target).await?;
+            self.session.record_discovered(DiscoveredTests {
+                target: test_target.dupe(),
+                suite: suite.clone(),
+                names: names.clone(),
+                labels: test_info.labels().map(str::to_owned).collect(),
+            });
+        }
+
         self.events.instant_event(TestDiscovery {
             data: Some(buck2_data::test_discovery::Data::Tests(TestSuite {
                 suite_name: suite,
@@ -742,6 +774,9 @@ impl<'a> TestOrchestrator for BuckTestOrchestrator<'a> {
         let fs = self.dice.clone().get_artifact_fs().await?;
 
         let test_info = Self::get_test_info(self.dice.dupe().deref_mut(), &test_target).await?;
+        let test_env_config = TestEnvConfig::get(self.dice.dupe().deref_mut())
+            .await?
+            .for_test(&test_info)?;
 
         // In contrast from actual test execution we do not check if local execution is possible.
         // We leave that decision to actual local execution runner that requests local execution preparation.
@@ -791,6 +826,7 @@ impl<'a> TestOrchestrator for BuckTestOrchestrator<'a> {
             &test_executor.executor().executor_fs(),
             TestExecutionPrefix::new(&stage, &self.session),
             self.session.options(),
+            test_env_config.sandbox(),
         )
         .await?;
 
@@ -802,6 +838,7 @@ impl<'a> TestOrchestrator for BuckTestOrchestrator<'a> {
             supports_re: _,
             declared_outputs,
             worker,
+            sandboxed_env,
         } = test_executable_expanded;
 
         let execution_request = Self::create_command_execution_request(
@@ -818,6 +855,7 @@ impl<'a> TestOrchestrator for BuckTestOrchestrator<'a> {
             vec![],
             worker,
             test_executor.re_dynamic_image(),
+            test_env_config.env_inheritance(sandboxed_env),
         )
         .await?;
 
@@ -1138,6 +1176,27 @@ impl<'b> BuckTestOrchestrator<'b> {
         }
     }
 
+    fn executor_config_with_resource_requirements<'a>(
+        executor_config: Cow<'a, CommandExecutorConfig>,
+        requirements: &TestResourceRequirements,
+    ) -> Cow<'a, CommandExecutorConfig> {
+        if requirements.is_empty() {
+            return executor_config;
+        }
+
+        if let Executor::RemoteEnabled(options) = &executor_config.executor {
+            let mut exec_options = options.clone();
+            exec_options.re_properties =
+                requirements.apply_to_re_properties(&options.re_properties);
+            return Cow::Owned(CommandExecutorConfig {
+                executor: Executor::RemoteEnabled(exec_options),
+                options: executor_config.options.dupe(),
+            });
+        }
+
+        executor_config
+    }
+
     async fn get_command_executor(
         dice: &mut DiceComputations<'_>,
         fs: &ArtifactFs,
@@ -1252,6 +1311,10 @@ impl<'b> BuckTestOrchestrator<'b> {
             resolved_executor_override.as_ref().map(|a| &***a),
             &stage,
         )?;
+        let executor_config = Self::executor_config_with_resource_requirements(
+            executor_config,
+            &TestResourceRequirements::from_test_info(test_info),
+        );
 
         let executor = Self::get_command_executor(dice, fs, &executor_config, stage)
             .await
@@ -1273,6 +1336,7 @@ impl<'b> BuckTestOrchestrator<'b> {
         executor_fs: &ExecutorFs<'_>,
         prefix: TestExecutionPrefix,
         opts: TestSessionOptions,
+        env_sandbox: &[SandboxedVar],
     ) -> anyhow::Result<ExpandedTestExecutable> {
         let output_root = resolve_output_root(dice, test_target, prefix).await?;
 
@@ -1320,6 +1384,25 @@ impl<'b> BuckTestOrchestrator<'b> {
             declared_outputs.insert(test_path, OutputCreationBehavior::Create);
         }
 
+        let sandboxed_env = resolve_sandbox(env_sandbox, |var| {
+            let name = ForwardRelativePathBuf::new(format!("env_sandbox/{}", var))
+                .with_buck_error_context_anyhow(|| {
+                    format!("Invalid variable in `env_sandbox`: `{}`", var)
+                })?;
+            let test_path = BuckOutTestPath::new(output_root.clone(), name);
+            let path = executor_fs.fs().fs().resolve(
+                executor_fs
+                    .fs()
+                    .buck_out_path_resolver()
+                    .resolve_test(&test_path),
+            );
+            declared_outputs.insert(test_path, OutputCreationBehavior::Create);
+            Ok(path.to_string())
+        })?
+        .into_iter()
+        .map(|(var, value)| (var, OsString::from(value)))
+        .collect();
+
         Ok(ExpandedTestExecutable {
             cwd: cwd.as_project_relative_path().to_buf(),
             cmd: expanded_cmd,
@@ -1328,6 +1411,7 @@ impl<'b> BuckTestOrchestrator<'b> {
             declared_outputs,
             supports_re,
             worker: expanded_worker,
+            sandboxed_env,
         })
     }
 
@@ -1345,6 +1429,7 @@ impl<'b> BuckTestOrchestrator<'b> {
         required_local_resources: Vec<LocalResourceState>,
         worker: Option<WorkerSpec>,
         re_dynamic_image: Option<RemoteExecutorCustomImage>,
+        env_inheritance: EnvironmentInheritance,
     ) -> anyhow::Result<CommandExecutionRequest> {
         let mut inputs = Vec::with_capacity(cmd_inputs.len());
         for input in &cmd_inputs {
@@ -1371,7 +1456,7 @@ impl<'b> BuckTestOrchestrator<'b> {
         );
         request = request
             .with_working_directory(cwd)
-            .with_local_environment_inheritance(EnvironmentInheritance::test_allowlist())
+            .with_local_environment_inheritance(env_inheritance)
             .with_disable_miniperf(true)
             .with_worker(worker)
             .with_remote_execution_custom_image(re_dynamic_image)
@@ -1785,6 +1870,8 @@ struct ExpandedTestExecutable {
     supports_re: bool,
     declared_outputs: IndexMap<BuckOutTestPath, OutputCreationBehavior>,
     worker: Option<WorkerSpec>,
+    /// Values for the variables listed in `env_sandbox`, only applied locally.
+    sandboxed_env: Vec<(String, OsString)>,
 }
 
 fn create_prepare_for_local_execution_result(
@@ -2046,13 +2133,13 @@ mod tests {
     use buck2_common::dice::cells::SetCellResolver;
     use buck2_common::dice::data::testing::SetTestingIoProvider;
     use buck2_common::liveliness_observer::NoopLivelinessObserver;
-    use buck2_core::cells::name::CellName;
     use buck2_core::cells::CellResolver;
+    use buck2_core::cells::name::CellName;
     use buck2_core::configuration::data::ConfigurationData;
     use buck2_core::fs::project::ProjectRootTemp;
     use buck2_test_api::data::TestStatus;
-    use dice::testing::DiceBuilder;
     use dice::UserComputationData;
+    use dice::testing::DiceBuilder;
     use futures::channel::mpsc;
     use futures::channel::mpsc::UnboundedReceiver;
     use futures::future;
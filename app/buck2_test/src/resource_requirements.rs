@@ -0,0 +1,206 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Resources declared by a test via `ExternalRunnerTestInfo(resource_requirements = ...)`.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use buck2_build_api::interpreter::rule_defs::provider::builtin::external_runner_test_info::FrozenExternalRunnerTestInfo;
+use buck2_core::execution_types::executor_config::RePlatformFields;
+use host_sharing::HostResourceRequirements;
+use host_sharing::HostSharingRequirements;
+use host_sharing::WeightClass;
+
+/// Prefix of the RE platform properties the requirements are mapped to.
+const RE_PROPERTY_PREFIX: &str = "resource.";
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct TestResourceRequirements {
+    cpu: Option<u32>,
+    memory_mb: Option<u32>,
+    /// Device class to the number of devices needed.
+    devices: BTreeMap<String, u32>,
+}
+
+impl TestResourceRequirements {
+    pub(crate) fn from_test_info(test_info: &FrozenExternalRunnerTestInfo) -> Self {
+        Self::from_entries(test_info.resource_requirements())
+    }
+
+    fn from_entries<'a>(entries: impl IntoIterator<Item = (&'a str, u32)>) -> Self {
+        let mut requirements = Self::default();
+        for (name, amount) in entries {
+            match name {
+                "cpu" => requirements.cpu = Some(amount),
+                "memory_mb" => requirements.memory_mb = Some(amount),
+                device => {
+                    if amount > 0 {
+                        requirements.devices.insert(device.to_owned(), amount);
+                    }
+                }
+            }
+        }
+        requirements
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Adjusts what the test runner asked for so that local scheduling accounts for the declared
+    /// cpus: the test takes (at least) one permit per cpu.
+    pub(crate) fn host_sharing_requirements(
+        &self,
+        requested: Arc<HostSharingRequirements>,
+    ) -> Arc<HostSharingRequirements> {
+        let Some(cpu) = self.cpu else {
+            return requested;
+        };
+
+        let weight = |weight: &WeightClass| match weight {
+            WeightClass::Permits(permits) => WeightClass::Permits((*permits).max(cpu as usize)),
+            weight => *weight,
+        };
+
+        let adjusted = match &*requested {
+            HostSharingRequirements::ExclusiveAccess => return requested,
+            HostSharingRequirements::OnePerToken(token, w) => {
+                HostSharingRequirements::OnePerToken(token.clone(), weight(w))
+            }
+            HostSharingRequirements::Shared(w) => HostSharingRequirements::Shared(weight(w)),
+        };
+        Arc::new(adjusted)
+    }
+
+    /// Memory and devices the test reserves when it runs locally, so that tests never use more
+    /// memory than the host has, nor more devices of a class than the host has.
+    pub(crate) fn host_resource_requirements(&self) -> HostResourceRequirements {
+        HostResourceRequirements {
+            memory_mb: self.memory_mb.map(|memory_mb| memory_mb as usize),
+            devices: self
+                .devices
+                .iter()
+                .map(|(class, count)| (class.clone(), *count as usize))
+                .collect(),
+        }
+    }
+
+    /// Platform properties to add when the test runs on RE. Properties already set by the
+    /// executor config take precedence.
+    pub(crate) fn apply_to_re_properties(
+        &self,
+        re_properties: &RePlatformFields,
+    ) -> RePlatformFields {
+        let mut properties: BTreeMap<String, String> = re_properties
+            .properties
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        let entries = self
+            .cpu
+            .map(|cpu| ("cpu".to_owned(), cpu))
+            .into_iter()
+            .chain(self.memory_mb.map(|mem| ("memory_mb".to_owned(), mem)))
+            .chain(self.devices.iter().map(|(d, n)| (d.clone(), *n)));
+        for (name, amount) in entries {
+            properties
+                .entry(format!("{}{}", RE_PROPERTY_PREFIX, name))
+                .or_insert_with(|| amount.to_string());
+        }
+        RePlatformFields {
+            properties: Arc::new(properties.into_iter().collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use buck2_core::execution_types::executor_config::RePlatformFields;
+    use host_sharing::HostResourceRequirements;
+    use host_sharing::HostSharingRequirements;
+    use host_sharing::WeightClass;
+
+    use super::TestResourceRequirements;
+
+    #[test]
+    fn test_host_sharing_requirements() {
+        let shared = Arc::new(HostSharingRequirements::Shared(WeightClass::Permits(1)));
+
+        let none = TestResourceRequirements::from_entries([]);
+        assert!(none.is_empty());
+        assert_eq!(none.host_sharing_requirements(shared.clone()), shared);
+
+        let cpu = TestResourceRequirements::from_entries([("cpu", 4), ("memory_mb", 1024)]);
+        assert_eq!(
+            *cpu.host_sharing_requirements(shared.clone()),
+            HostSharingRequirements::Shared(WeightClass::Permits(4))
+        );
+
+        let token = Arc::new(HostSharingRequirements::OnePerToken(
+            "token".to_owned(),
+            WeightClass::Permits(1),
+        ));
+        let gpu = TestResourceRequirements::from_entries([("gpu", 1), ("cpu", 2)]);
+        assert_eq!(
+            *gpu.host_sharing_requirements(shared),
+            HostSharingRequirements::Shared(WeightClass::Permits(2))
+        );
+        assert_eq!(
+            *gpu.host_sharing_requirements(token),
+            HostSharingRequirements::OnePerToken("token".to_owned(), WeightClass::Permits(2))
+        );
+
+        let exclusive = Arc::new(HostSharingRequirements::ExclusiveAccess);
+        assert_eq!(gpu.host_sharing_requirements(exclusive.clone()), exclusive);
+    }
+
+    #[test]
+    fn test_host_resource_requirements() {
+        assert!(
+            TestResourceRequirements::from_entries([("cpu", 4)])
+                .host_resource_requirements()
+                .is_empty()
+        );
+
+        let requirements =
+            TestResourceRequirements::from_entries([("memory_mb", 1024), ("gpu", 2), ("tpu", 0)]);
+        assert_eq!(
+            requirements.host_resource_requirements(),
+            HostResourceRequirements {
+                memory_mb: Some(1024),
+                devices: [("gpu".to_owned(), 2)].into_iter().collect(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_re_properties() {
+        let requirements =
+            TestResourceRequirements::from_entries([("cpu", 8), ("memory_mb", 2048), ("gpu", 2)]);
+        let existing = RePlatformFields {
+            properties: Arc::new(
+                [("resource.cpu".to_owned(), "16".to_owned())]
+                    .into_iter()
+                    .collect(),
+            ),
+        };
+        let properties = requirements.apply_to_re_properties(&existing).properties;
+        assert_eq!(
+            properties.iter().collect::<Vec<_>>(),
+            vec![
+                (&"resource.cpu".to_owned(), &"16".to_owned()),
+                (&"resource.gpu".to_owned(), &"2".to_owned()),
+                (&"resource.memory_mb".to_owned(), &"2048".to_owned()),
+            ]
+        );
+    }
+}
@@ -11,6 +11,7 @@ use core::fmt;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
+use std::sync::Mutex;
 
 use allocative::Allocative;
 use anyhow::Context as _;
@@ -27,18 +28,33 @@ pub struct TestSessionOptions {
     pub allow_re: bool,
     pub force_use_project_relative_paths: bool,
     pub force_run_from_project_root: bool,
+    /// Whether tests are only listed: the test runner discovers the test cases, but they are not
+    /// executed.
+    pub list_only: bool,
 }
 
 impl fmt::Display for TestSessionOptions {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "allow_re = {}, force_use_project_relative_paths = {}, force_run_from_project_root = {}",
-            self.allow_re, self.force_use_project_relative_paths, self.force_run_from_project_root
+            "allow_re = {}, force_use_project_relative_paths = {}, force_run_from_project_root = {}, list_only = {}",
+            self.allow_re,
+            self.force_use_project_relative_paths,
+            self.force_run_from_project_root,
+            self.list_only
         )
     }
 }
 
+/// Test cases the test runner discovered in a suite of a target.
+pub struct DiscoveredTests {
+    pub target: ConfiguredProvidersLabel,
+    pub suite: String,
+    pub names: Vec<String>,
+    /// Labels of the target.
+    pub labels: Vec<String>,
+}
+
 /// The state of a buck2 test command.
 pub struct TestSession {
     /// The next ConfiguredTargetHandle that will be assigned.
@@ -53,6 +69,8 @@ pub struct TestSession {
     /// Options overriding the behavior of tests executed in this session. This is primarily
     /// intended for unstable or debugging features.
     options: TestSessionOptions,
+    /// Test cases discovered so far, only recorded when listing tests.
+    discovered: Mutex<Vec<DiscoveredTests>>,
 }
 
 impl TestSession {
@@ -69,6 +87,7 @@ impl TestSession {
             labels: DashMap::new(),
             prefix: Arc::new(prefix),
             options,
+            discovered: Mutex::new(Vec::new()),
         }
     }
 
@@ -97,4 +116,15 @@ impl TestSession {
 
         Ok(res.clone())
     }
+
+    pub fn record_discovered(&self, tests: DiscoveredTests) {
+        self.discovered.lock().unwrap().push(tests);
+    }
+
+    /// The test cases discovered in this session, ordered by target and suite.
+    pub fn take_discovered(&self) -> Vec<DiscoveredTests> {
+        let mut discovered = std::mem::take(&mut *self.discovered.lock().unwrap());
+        discovered.sort_by(|a, b| (&a.target, &a.suite).cmp(&(&b.target, &b.suite)));
+        discovered
+    }
 }
@@ -0,0 +1,329 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Controls over which parts of the daemon environment leak into locally executed tests.
+//!
+//! Configured in the root cell, and extended by each test through the `env_allowlist`,
+//! `env_denylist` and `env_sandbox` attributes of `ExternalRunnerTestInfo`:
+//!
+//! ```ini
+//! [test]
+//! # Additional variables to inherit on top of the built-in allowlist.
+//! env_allowlist = JAVA_HOME, LANG
+//! # Variables never inherited, even if on the built-in allowlist.
+//! env_denylist = USER
+//! # Variables pointed to an empty directory private to each test run, or set from a template
+//! # referring to other sandboxed variables.
+//! env_sandbox = HOME, TMPDIR, XDG_CACHE_HOME=$HOME/.cache
+//! ```
+
+use std::ffi::OsString;
+use std::sync::Arc;
+
+use allocative::Allocative;
+use async_trait::async_trait;
+use buck2_build_api::interpreter::rule_defs::provider::builtin::external_runner_test_info::FrozenExternalRunnerTestInfo;
+use buck2_common::legacy_configs::dice::HasLegacyConfigs;
+use buck2_common::legacy_configs::key::BuckconfigKeyRef;
+use buck2_common::legacy_configs::view::LegacyBuckConfigView;
+use buck2_execute::execute::environment_inheritance::EnvironmentInheritance;
+use buck2_futures::cancellation::CancellationContext;
+use dice::DiceComputations;
+use dice::Key;
+use dupe::Dupe;
+
+#[derive(Debug, buck2_error::Error)]
+enum TestEnvError {
+    #[error("Invalid variable name in `env_sandbox`: `{0}`")]
+    InvalidName(String),
+    #[error("`env_sandbox` template for `{0}` refers to `${1}`, which is not sandboxed before it")]
+    UnknownReference(String, String),
+}
+
+/// A variable listed in `env_sandbox`.
+#[derive(Debug, Clone, PartialEq, Eq, Allocative)]
+pub(crate) struct SandboxedVar {
+    pub(crate) name: String,
+    /// `None` to point the variable to a fresh directory, otherwise a value where `$VAR` (or
+    /// `${VAR}`) is replaced with the value of another sandboxed variable.
+    pub(crate) template: Option<String>,
+}
+
+impl SandboxedVar {
+    fn parse(entry: &str) -> anyhow::Result<Self> {
+        let (name, template) = match entry.split_once('=') {
+            Some((name, template)) => (name.trim(), Some(template.trim().to_owned())),
+            None => (entry, None),
+        };
+        if !is_var_name(name) {
+            return Err(TestEnvError::InvalidName(entry.to_owned()).into());
+        }
+        Ok(Self {
+            name: name.to_owned(),
+            template,
+        })
+    }
+}
+
+fn is_var_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Allocative)]
+pub(crate) struct TestEnvConfig {
+    allowlist: Vec<String>,
+    denylist: Vec<String>,
+    sandbox: Vec<SandboxedVar>,
+}
+
+impl TestEnvConfig {
+    pub(crate) async fn get(dice: &mut DiceComputations<'_>) -> anyhow::Result<Arc<Self>> {
+        #[derive(
+            Clone,
+            Dupe,
+            derive_more::Display,
+            Debug,
+            Eq,
+            Hash,
+            PartialEq,
+            Allocative
+        )]
+        struct TestEnvConfigKey;
+
+        #[async_trait]
+        impl Key for TestEnvConfigKey {
+            type Value = buck2_error::Result<Arc<TestEnvConfig>>;
+
+            async fn compute(
+                &self,
+                mut dice: &mut DiceComputations,
+                _cancellation: &CancellationContext,
+            ) -> Self::Value {
+                let root_conf = dice.get_legacy_root_config_on_dice().await?;
+                let mut view = root_conf.view(&mut dice);
+                let mut list = |property| -> buck2_error::Result<Vec<String>> {
+                    Ok(view
+                        .parse_list::<String>(BuckconfigKeyRef {
+                            section: "test",
+                            property,
+                        })?
+                        .unwrap_or_default())
+                };
+                let allowlist = list("env_allowlist")?;
+                let denylist = list("env_denylist")?;
+                let sandbox = list("env_sandbox")?;
+                Ok(Arc::new(TestEnvConfig::default().extended(
+                    allowlist.iter().map(|s| s.as_str()),
+                    denylist.iter().map(|s| s.as_str()),
+                    sandbox.iter().map(|s| s.as_str()),
+                )?))
+            }
+
+            fn equality(x: &Self::Value, y: &Self::Value) -> bool {
+                match (x, y) {
+                    (Ok(x), Ok(y)) => x == y,
+                    _ => false,
+                }
+            }
+        }
+
+        Ok(dice.compute(&TestEnvConfigKey).await??)
+    }
+
+    /// The configuration for a given test: the buckconfig lists, extended with those declared by
+    /// the test rule.
+    pub(crate) fn for_test(
+        &self,
+        test_info: &FrozenExternalRunnerTestInfo,
+    ) -> anyhow::Result<Self> {
+        self.extended(
+            test_info.env_allowlist(),
+            test_info.env_denylist(),
+            test_info.env_sandbox(),
+        )
+    }
+
+    fn extended<'a>(
+        &self,
+        allowlist: impl IntoIterator<Item = &'a str>,
+        denylist: impl IntoIterator<Item = &'a str>,
+        sandbox: impl IntoIterator<Item = &'a str>,
+    ) -> anyhow::Result<Self> {
+        fn extend<'a>(list: &mut Vec<String>, new: impl IntoIterator<Item = &'a str>) {
+            for s in new {
+                let s = s.trim();
+                if !s.is_empty() && !list.iter().any(|v| v == s) {
+                    list.push(s.to_owned());
+                }
+            }
+        }
+
+        let mut res = Self {
+            allowlist: self.allowlist.clone(),
+            denylist: self.denylist.clone(),
+            sandbox: self.sandbox.clone(),
+        };
+        extend(&mut res.allowlist, allowlist);
+        extend(&mut res.denylist, denylist);
+        for entry in sandbox {
+            let entry = entry.trim();
+            if entry.is_empty() {
+                continue;
+            }
+            let var = SandboxedVar::parse(entry)?;
+            // A later declaration (i.e. the rule's) replaces an earlier one.
+            res.sandbox.retain(|v| v.name != var.name);
+            res.sandbox.push(var);
+        }
+        Ok(res)
+    }
+
+    /// Variables to point at a per-test directory or derive from one.
+    pub(crate) fn sandbox(&self) -> &[SandboxedVar] {
+        &self.sandbox
+    }
+
+    /// `sandboxed` are the values chosen for the variables in `sandbox()`.
+    pub(crate) fn env_inheritance(
+        &self,
+        sandboxed: Vec<(String, OsString)>,
+    ) -> EnvironmentInheritance {
+        EnvironmentInheritance::test_allowlist_with(&self.allowlist, &self.denylist, sandboxed)
+    }
+}
+
+/// Computes the values of the sandboxed variables. `dir` is called for each variable without a
+/// template and returns the directory to use for it. Templates are expanded after that, in order,
+/// so they can refer to any directory variable and to templated variables declared before them.
+pub(crate) fn resolve_sandbox(
+    sandbox: &[SandboxedVar],
+    mut dir: impl FnMut(&str) -> anyhow::Result<String>,
+) -> anyhow::Result<Vec<(String, String)>> {
+    let mut values = Vec::with_capacity(sandbox.len());
+    for var in sandbox {
+        if var.template.is_none() {
+            values.push((var.name.clone(), dir(&var.name)?));
+        }
+    }
+    for var in sandbox {
+        if let Some(template) = &var.template {
+            let value = substitute(&var.name, template, &values)?;
+            values.push((var.name.clone(), value));
+        }
+    }
+    Ok(values)
+}
+
+fn substitute(name: &str, template: &str, values: &[(String, String)]) -> anyhow::Result<String> {
+    let mut res = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(i) = rest.find('$') {
+        res.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        let (reference, after) = match rest.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => (braced, ""),
+            },
+            None => {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            }
+        };
+        match values.iter().find(|(k, _)| k == reference) {
+            Some((_, value)) => res.push_str(value),
+            None => {
+                return Err(
+                    TestEnvError::UnknownReference(name.to_owned(), reference.to_owned()).into(),
+                );
+            }
+        }
+        rest = after;
+    }
+    res.push_str(rest);
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve_sandbox;
+    use super::SandboxedVar;
+    use super::TestEnvConfig;
+
+    fn config(allow: &[&str], deny: &[&str], sandbox: &[&str]) -> TestEnvConfig {
+        TestEnvConfig::default()
+            .extended(
+                allow.iter().copied(),
+                deny.iter().copied(),
+                sandbox.iter().copied(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_extended() {
+        let buckconfig = config(&["LANG", " "], &["USER"], &["HOME", "TMPDIR"]);
+        let rule = buckconfig
+            .extended(["JAVA_HOME", "LANG"], ["LC_ALL"], ["TMPDIR=$HOME/tmp"])
+            .unwrap();
+        assert_eq!(rule.allowlist, vec!["LANG", "JAVA_HOME"]);
+        assert_eq!(rule.denylist, vec!["USER", "LC_ALL"]);
+        assert_eq!(
+            rule.sandbox(),
+            &[
+                SandboxedVar {
+                    name: "HOME".to_owned(),
+                    template: None,
+                },
+                SandboxedVar {
+                    name: "TMPDIR".to_owned(),
+                    template: Some("$HOME/tmp".to_owned()),
+                },
+            ]
+        );
+
+        assert!(TestEnvConfig::default().extended([], [], ["A/B"]).is_err());
+        assert!(TestEnvConfig::default().extended([], [], ["=x"]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_sandbox() {
+        let sandbox = config(
+            &[],
+            &[],
+            &[
+                "CACHE=${HOME}/.cache",
+                "HOME",
+                "XDG=$CACHE:$TMPDIR!",
+                "TMPDIR",
+            ],
+        );
+        let values =
+            resolve_sandbox(sandbox.sandbox(), |name| Ok(format!("/sandbox/{}", name))).unwrap();
+        assert_eq!(
+            values,
+            vec![
+                ("HOME".to_owned(), "/sandbox/HOME".to_owned()),
+                ("TMPDIR".to_owned(), "/sandbox/TMPDIR".to_owned()),
+                ("CACHE".to_owned(), "/sandbox/HOME/.cache".to_owned()),
+                (
+                    "XDG".to_owned(),
+                    "/sandbox/HOME/.cache:/sandbox/TMPDIR!".to_owned()
+                ),
+            ]
+        );
+
+        let unknown = config(&[], &[], &["HOME=$USER/home"]);
+        assert!(resolve_sandbox(unknown.sandbox(), |_| unreachable!()).is_err());
+    }
+}
@@ -76,6 +76,16 @@ impl Buck2TestRunner {
                 );
                 let target_handle = spec.target.handle.to_owned();
 
+                // Each target is a single test, named after the target.
+                self.orchestrator_client
+                    .report_tests_discovered(
+                        target_handle,
+                        spec.target.target.clone(),
+                        vec![name.clone()],
+                    )
+                    .await
+                    .expect("Test discovery reporting failed");
+
                 let execution_response = self
                     .execute_test_from_spec(spec)
                     .await
@@ -173,6 +173,7 @@ use serde::Serializer;
 use crate::api::cycles::DetectCycles;
 use crate::api::transaction::DiceTransactionUpdater;
 use crate::api::user_data::UserComputationData;
+use crate::metrics::InvalidationMetrics;
 use crate::metrics::Metrics;
 use crate::DiceDataBuilderImpl;
 use crate::DiceImplementation;
@@ -240,6 +241,11 @@ impl Dice {
         self.implementation.metrics()
     }
 
+    /// Keys invalidated by the changes of the most recently committed transaction.
+    pub async fn last_invalidation(&self) -> InvalidationMetrics {
+        self.implementation.last_invalidation().await
+    }
+
     /// Wait until all active versions have exited.
     pub fn wait_for_idle(&self) -> impl Future<Output = ()> + 'static {
         self.implementation.wait_for_idle()
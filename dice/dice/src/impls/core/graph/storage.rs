@@ -274,14 +274,14 @@ impl VersionedGraph {
         }
     }
 
-    /// Invalidates an entry and its transitive rdeps. Returning true if this caused any type of
-    /// change
+    /// Invalidates an entry and its transitive rdeps. Returning the number of transitive rdeps
+    /// that were invalidated if this caused any type of change, or `None` otherwise.
     pub(crate) fn invalidate(
         &mut self,
         key: VersionedGraphKey,
         invalidate: InvalidateKind,
         invalidation_priority: InvalidationSourcePriority,
-    ) -> bool {
+    ) -> Option<usize> {
         let entry = match self.nodes.get_mut(&key.k) {
             Some(entry) => entry,
             _ => {
@@ -313,7 +313,7 @@ impl VersionedGraph {
                 };
 
                 self.nodes.insert(key.k, new_entry);
-                return true;
+                return Some(0);
             }
         };
 
@@ -331,12 +331,11 @@ impl VersionedGraph {
             if let InvalidateResult::Changed(rdeps) = res {
                 rdeps.into_iter().flatten().collect()
             } else {
-                return false;
+                return None;
             }
         };
 
-        self.invalidate_rdeps(key.v, queue);
-        true
+        Some(self.invalidate_rdeps(key.v, queue))
     }
 
     // -----------------------------------------------------------------------------
@@ -369,13 +368,16 @@ impl VersionedGraph {
         res
     }
 
-    fn invalidate_rdeps(&mut self, version: VersionNumber, mut queued: HashSet<DiceKey>) {
+    /// Returns the number of rdeps that were invalidated.
+    fn invalidate_rdeps(&mut self, version: VersionNumber, mut queued: HashSet<DiceKey>) -> usize {
         let mut queue: Vec<_> = queued.iter().copied().collect();
+        let mut invalidated = 0;
 
         while let Some(rdep) = queue.pop() {
             if let Some(node) = self.nodes.get_mut(&rdep) {
                 if let InvalidateResult::Changed(Some(rdeps)) = node.mark_invalidated(version, None)
                 {
+                    invalidated += 1;
                     for dep in rdeps.into_iter() {
                         if queued.insert(dep) {
                             queue.push(dep);
@@ -384,6 +386,8 @@ impl VersionedGraph {
                 }
             }
         }
+
+        invalidated
     }
 }
 
@@ -709,22 +713,30 @@ mod tests {
         let res = DiceValidValue::testing_new(DiceKeyValue::<K>::new(100));
         let key = VersionedGraphKey::new(VersionNumber::new(0), DiceKey { index: 0 });
 
-        assert!(cache.invalidate(
-            key,
-            InvalidateKind::Update(res.dupe(), StorageType::Injected),
-            InvalidationSourcePriority::Normal,
-        ));
+        assert!(
+            cache
+                .invalidate(
+                    key,
+                    InvalidateKind::Update(res.dupe(), StorageType::Injected),
+                    InvalidationSourcePriority::Normal,
+                )
+                .is_some()
+        );
 
         assert!(cache.get(key.dupe()).assert_match().value().equality(&res));
 
         let res2 = DiceValidValue::testing_new(DiceKeyValue::<K>::new(200));
         let key2 = VersionedGraphKey::new(VersionNumber::new(1), DiceKey { index: 0 });
 
-        assert!(cache.invalidate(
-            key2,
-            InvalidateKind::Update(res2.dupe(), StorageType::Injected),
-            InvalidationSourcePriority::Normal,
-        ));
+        assert!(
+            cache
+                .invalidate(
+                    key2,
+                    InvalidateKind::Update(res2.dupe(), StorageType::Injected),
+                    InvalidationSourcePriority::Normal,
+                )
+                .is_some()
+        );
 
         assert!(
             cache
@@ -739,11 +751,15 @@ mod tests {
         let res3 = DiceValidValue::testing_new(DiceKeyValue::<K>::new(300));
         let key3 = VersionedGraphKey::new(VersionNumber::new(5), DiceKey { index: 0 });
         let key2 = VersionedGraphKey::new(VersionNumber::new(1), DiceKey { index: 0 });
-        assert!(cache.invalidate(
-            key3,
-            InvalidateKind::Update(res3.dupe(), StorageType::Injected),
-            InvalidationSourcePriority::Normal,
-        ));
+        assert!(
+            cache
+                .invalidate(
+                    key3,
+                    InvalidateKind::Update(res3.dupe(), StorageType::Injected),
+                    InvalidationSourcePriority::Normal,
+                )
+                .is_some()
+        );
 
         assert!(
             cache
@@ -799,7 +815,7 @@ mod tests {
             InvalidateKind::ForceDirty,
             InvalidationSourcePriority::Normal,
         );
-        assert!(existing);
+        assert!(existing.is_some());
 
         cache.get(key_a(0).dupe()).assert_compute();
         cache.get(key_a(1).dupe()).assert_compute();
@@ -809,7 +825,7 @@ mod tests {
             InvalidateKind::ForceDirty,
             InvalidationSourcePriority::Normal,
         );
-        assert!(existing);
+        assert!(existing.is_some());
 
         cache.get(key_a(0).dupe()).assert_compute();
         cache.get(key_a(1).dupe()).assert_compute();
@@ -1216,11 +1232,15 @@ mod tests {
             TrackedInvalidationPaths::clean(),
         );
 
-        assert!(cache.invalidate(
-            VersionedGraphKey::new(VersionNumber::new(1), DiceKey { index: 0 }),
-            InvalidateKind::ForceDirty,
-            InvalidationSourcePriority::Normal,
-        ));
+        // Both rdeps are invalidated.
+        assert_eq!(
+            cache.invalidate(
+                VersionedGraphKey::new(VersionNumber::new(1), DiceKey { index: 0 }),
+                InvalidateKind::ForceDirty,
+                InvalidationSourcePriority::Normal,
+            ),
+            Some(2)
+        );
 
         assert_eq!(
             cache
@@ -1281,14 +1301,18 @@ mod tests {
             TrackedInvalidationPaths::clean(),
         );
 
-        assert!(cache.invalidate(
-            VersionedGraphKey::new(VersionNumber::new(1), DiceKey { index: 0 }),
-            InvalidateKind::Update(
-                DiceValidValue::testing_new(DiceKeyValue::<K>::new(30)),
-                StorageType::Normal
-            ),
-            InvalidationSourcePriority::Normal,
-        ));
+        assert!(
+            cache
+                .invalidate(
+                    VersionedGraphKey::new(VersionNumber::new(1), DiceKey { index: 0 }),
+                    InvalidateKind::Update(
+                        DiceValidValue::testing_new(DiceKeyValue::<K>::new(30)),
+                        StorageType::Normal
+                    ),
+                    InvalidationSourcePriority::Normal,
+                )
+                .is_some()
+        );
 
         Ok(())
     }
@@ -38,12 +38,30 @@ use crate::result::CancellableResult;
 use crate::result::CancellationReason;
 use crate::versions::VersionNumber;
 
+/// Number of changed keys that invalidated the most rdeps to keep in `InvalidationStats`.
+const MAX_INVALIDATION_HOTSPOTS: usize = 10;
+
+/// How many keys were invalidated by the latest update of the state.
+#[derive(allocative::Allocative, Clone, Debug, Default)]
+pub(crate) struct InvalidationStats {
+    /// Number of keys whose change was recorded.
+    pub(crate) changed_key_count: usize,
+    /// Number of keys invalidated as transitive rdeps of the changed keys.
+    pub(crate) invalidated_key_count: usize,
+    /// Number of keys in the graph after the update.
+    pub(crate) key_count: usize,
+    /// The changed keys that invalidated the most rdeps, most first. An rdep reachable from
+    /// several changed keys is only counted for the first one that invalidated it.
+    pub(crate) hotspots: Vec<(DiceKey, usize)>,
+}
+
 /// Core state of DICE, holding the actual graph and version information
 #[derive(allocative::Allocative)]
 pub(super) struct CoreState {
     version_tracker: VersionTracker,
     graph: VersionedGraph,
     pending_termination_tasks: Vec<DiceTask>,
+    last_invalidation: InvalidationStats,
 }
 
 impl CoreState {
@@ -52,6 +70,7 @@ impl CoreState {
             version_tracker: VersionTracker::new(),
             graph: VersionedGraph::new(),
             pending_termination_tasks: Vec::new(),
+            last_invalidation: InvalidationStats::default(),
         }
     }
 
@@ -62,9 +81,9 @@ impl CoreState {
         let version_update = self.version_tracker.write();
         let v = version_update.version();
 
-        let mut changes_recorded = false;
+        let mut stats = InvalidationStats::default();
         for (key, change, invalidation_priority) in updates {
-            changes_recorded |= self.graph.invalidate(
+            if let Some(invalidated) = self.graph.invalidate(
                 VersionedGraphKey::new(v, key),
                 match change {
                     ChangeType::Invalidate => InvalidateKind::ForceDirty,
@@ -73,8 +92,20 @@ impl CoreState {
                     ChangeType::TestingSoftDirty => InvalidateKind::Invalidate,
                 },
                 invalidation_priority,
-            );
+            ) {
+                stats.changed_key_count += 1;
+                stats.invalidated_key_count += invalidated;
+                if invalidated > 0 {
+                    stats.hotspots.push((key, invalidated));
+                }
+            }
         }
+        stats.hotspots.sort_by(|a, b| b.1.cmp(&a.1));
+        stats.hotspots.truncate(MAX_INVALIDATION_HOTSPOTS);
+        stats.key_count = self.graph.nodes.len();
+
+        let changes_recorded = stats.changed_key_count > 0;
+        self.last_invalidation = stats;
         if changes_recorded {
             version_update.commit()
         } else {
@@ -166,6 +197,10 @@ impl CoreState {
         }
     }
 
+    pub(super) fn last_invalidation(&self) -> InvalidationStats {
+        self.last_invalidation.clone()
+    }
+
     pub(super) fn introspection(&self) -> (VersionedGraphIntrospectable, VersionIntrospectable) {
         let graph = self.graph.introspect();
         let version_data = self.version_tracker.introspect();
@@ -219,6 +254,7 @@ mod tests {
             )]),
             VersionNumber::new(1)
         );
+        assert_eq!(core.last_invalidation().changed_key_count, 1);
 
         assert_eq!(
             core.update_state([(
@@ -7,6 +7,7 @@
  * of this source tree.
  */
 
+use buck2_futures::cpu_attribution::CpuSubsystem;
 use gazebo::variants::VariantName;
 
 use crate::impls::core::graph::storage::ValueReusable;
@@ -50,6 +51,7 @@ impl StateProcessor {
 
     #[cfg_attr(debug_assertions, instrument(skip_all, fields(kind = %message.variant_name())))]
     fn iteration(&mut self, message: StateRequest) {
+        let _cpu = CpuSubsystem::Dice.enter();
         match message {
             StateRequest::UpdateState { changes, resp } => {
                 // ignore error if the requester dropped it.
@@ -119,6 +121,9 @@ impl StateProcessor {
             StateRequest::Metrics { resp } => {
                 let _ignored = resp.send(self.state.metrics());
             }
+            StateRequest::LastInvalidation { resp } => {
+                let _ignored = resp.send(self.state.last_invalidation());
+            }
             StateRequest::Introspection { resp } => {
                 let _ignored = resp.send(self.state.introspection());
             }
@@ -25,6 +25,7 @@ use crate::impls::core::graph::types::VersionedGraphKey;
 use crate::impls::core::graph::types::VersionedGraphResult;
 use crate::impls::core::graph::types::VersionedGraphResultMismatch;
 use crate::impls::core::internals::CoreState;
+use crate::impls::core::internals::InvalidationStats;
 use crate::impls::core::processor::StateProcessor;
 use crate::impls::core::versions::introspection::VersionIntrospectable;
 use crate::impls::core::versions::VersionEpoch;
@@ -179,6 +180,12 @@ impl CoreStateHandle {
         tokio::task::block_in_place(|| recv.blocking_recv().unwrap())
     }
 
+    /// Collect how many keys were invalidated by the latest update of the state
+    pub(crate) fn last_invalidation(&self) -> impl Future<Output = InvalidationStats> {
+        let (resp, recv) = oneshot::channel();
+        self.call(StateRequest::LastInvalidation { resp }, recv)
+    }
+
     /// Collects the introspectable dice state
     pub(crate) fn introspection(&self) -> (VersionedGraphIntrospectable, VersionIntrospectable) {
         let (resp, recv) = oneshot::channel();
@@ -278,6 +285,8 @@ pub(super) enum StateRequest {
     UnstableDropEverything,
     /// Collect metrics
     Metrics { resp: Sender<Metrics> },
+    /// Collect how many keys were invalidated by the latest update of the state
+    LastInvalidation { resp: Sender<InvalidationStats> },
     /// Collects the introspectable dice state
     Introspection {
         #[derivative(Debug = "ignore")]
@@ -23,6 +23,7 @@ use crate::impls::key_index::DiceKeyIndex;
 use crate::impls::transaction::TransactionUpdater;
 use crate::introspection::graph::GraphIntrospectable;
 use crate::introspection::graph::ModernIntrospectable;
+use crate::metrics::InvalidationMetrics;
 use crate::metrics::Metrics;
 
 #[derive(Allocative)]
@@ -82,6 +83,20 @@ impl DiceModern {
         self.state_handle.metrics()
     }
 
+    pub async fn last_invalidation(&self) -> InvalidationMetrics {
+        let stats = self.state_handle.last_invalidation().await;
+        InvalidationMetrics {
+            changed_key_count: stats.changed_key_count,
+            invalidated_key_count: stats.invalidated_key_count,
+            key_count: stats.key_count,
+            hotspots: stats
+                .hotspots
+                .into_iter()
+                .map(|(key, invalidated)| (self.key_index.get(key).to_string(), invalidated))
+                .collect(),
+        }
+    }
+
     pub fn to_introspectable(&self) -> GraphIntrospectable {
         let (graph_introspectable, version_introspectable) = self.state_handle.introspection();
         // a bit subtle, but make sure we introspect the key_index after we get the graphs as
@@ -9,6 +9,7 @@
 
 use std::any::Any;
 
+use buck2_futures::cpu_attribution::CpuSubsystem;
 use buck2_futures::owning_future::OwningFuture;
 use buck2_futures::spawn::spawn_cancellable;
 use buck2_futures::spawn::FutureAndCancellationHandle;
@@ -49,7 +50,9 @@ pub(crate) fn spawn_dice_task<S>(
             |cancellations| {
                 let handle = DiceTaskHandle::new(internal, cancellations);
 
-                OwningFuture::new(handle, f).boxed()
+                CpuSubsystem::Dice
+                    .attribute(OwningFuture::new(handle, f))
+                    .boxed()
             }
         },
         spawner,
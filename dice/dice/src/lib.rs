@@ -245,6 +245,7 @@ use crate::impls::dice::DiceModernDataBuilder;
 use crate::introspection::graph::GraphIntrospectable;
 use crate::introspection::serialize_dense_graph;
 use crate::introspection::serialize_graph;
+pub use crate::metrics::InvalidationMetrics;
 pub use crate::stats::GlobalStats;
 use crate::transaction_update::DiceTransactionUpdaterImpl;
 
@@ -310,6 +311,12 @@ impl DiceImplementation {
         }
     }
 
+    pub async fn last_invalidation(&self) -> InvalidationMetrics {
+        match self {
+            DiceImplementation::Modern(dice) => dice.last_invalidation().await,
+        }
+    }
+
     /// Wait until all active versions have exited.
     pub fn wait_for_idle(&self) -> impl Future<Output = ()> + 'static {
         match self {
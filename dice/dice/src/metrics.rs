@@ -15,3 +15,17 @@ pub struct Metrics {
     pub currently_active_key_count: usize,
     pub active_transaction_count: u32,
 }
+
+/// Keys invalidated by the latest change to the Dice state.
+#[derive(Debug, Default)]
+pub struct InvalidationMetrics {
+    /// Number of keys that changed.
+    pub changed_key_count: usize,
+    /// Number of keys invalidated as transitive rdeps of the changed keys.
+    pub invalidated_key_count: usize,
+    /// Number of keys in the graph after the change.
+    pub key_count: usize,
+    /// The changed keys that invalidated the most rdeps, with how many they invalidated, most
+    /// first.
+    pub hotspots: Vec<(String, usize)>,
+}
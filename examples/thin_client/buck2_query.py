@@ -0,0 +1,69 @@
+#!/usr/bin/env python3
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+#
+# This source code is licensed under both the MIT license found in the
+# LICENSE-MIT file in the root directory of this source tree and the Apache
+# License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+# of this source tree.
+
+"""
+Example thin client: run a query on a running buck2 daemon through its thin
+client socket, without the buck2 client.
+
+    buck2_query.py --daemon-socket SOCKET 'deps(//foo:bar)'
+
+Without `--daemon-socket`, the socket is found with `buck2 status`, which is
+slow; scripts running many queries should look it up once and cache it.
+"""
+
+import argparse
+import json
+import os
+import socket
+import subprocess
+import sys
+
+
+def find_socket() -> str:
+    status = json.loads(subprocess.check_output(["buck2", "status"]))
+    path = status.get("thin_client_socket")
+    if not path:
+        sys.exit("buck2 daemon does not serve the thin client protocol")
+    return path
+
+
+def call(sock_path: str, method: str, params: dict) -> dict:
+    with socket.socket(socket.AF_UNIX, socket.SOCK_STREAM) as sock:
+        sock.connect(sock_path)
+        request = {"jsonrpc": "2.0", "id": 1, "method": method, "params": params}
+        sock.sendall(json.dumps(request).encode() + b"\n")
+        with sock.makefile("rb") as f:
+            return json.loads(f.readline())
+
+
+def main() -> None:
+    parser = argparse.ArgumentParser(description=__doc__)
+    parser.add_argument("--daemon-socket", help="path to the thin client socket")
+    parser.add_argument("--cquery", action="store_true", help="run a cquery")
+    parser.add_argument("--json", action="store_true", help="output in JSON format")
+    parser.add_argument("query")
+    parser.add_argument("query_args", nargs="*")
+    args = parser.parse_args()
+
+    response = call(
+        args.daemon_socket or find_socket(),
+        "cquery" if args.cquery else "uquery",
+        {
+            "query": args.query,
+            "query_args": args.query_args,
+            "json": args.json,
+            "working_dir": os.getcwd(),
+        },
+    )
+    if "error" in response:
+        sys.exit(response["error"]["message"])
+    sys.stdout.write(response["result"]["stdout"])
+
+
+if __name__ == "__main__":
+    main()
@@ -7,10 +7,14 @@
  * of this source tree.
  */
 
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::Mutex;
 
 use allocative::Allocative;
 use anyhow::Context;
+use futures_intrusive::sync::ManualResetEvent;
 use futures_intrusive::sync::SharedSemaphore;
 use futures_intrusive::sync::SharedSemaphoreReleaser;
 
@@ -107,12 +111,28 @@ impl Default for HostSharingRequirements {
     }
 }
 
+/// Host resources a command uses while it runs, on top of its `HostSharingRequirements`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Allocative, Hash)]
+pub struct HostResourceRequirements {
+    /// Memory, in megabytes.
+    pub memory_mb: Option<usize>,
+    /// Number of devices needed, by device class (e.g. `gpu`).
+    pub devices: BTreeMap<String, usize>,
+}
+
+impl HostResourceRequirements {
+    pub fn is_empty(&self) -> bool {
+        self.memory_mb.is_none() && self.devices.is_empty()
+    }
+}
+
 /// A guard for all permits and resources acquired for a HostSharingBroker.acquire request.
 /// Keeps the data structures received from semaphores after acquiring.
 /// Semaphores are held until this struct is dropped.
 pub struct HostSharingGuard {
     _run_guard: SharedSemaphoreReleaser,
     _name_guard: Option<SharedSemaphoreReleaser>,
+    _resource_guards: Vec<SharedSemaphoreReleaser>,
 }
 
 /// Used to ensure that host resources are properly reserved before executing a command spec.
@@ -120,6 +140,63 @@ pub struct HostSharingBroker {
     permits: SharedSemaphore,
     num_machine_permits: usize,
     named_semaphores: NamedSemaphores,
+    prioritized_waiters: PrioritizedWaiters,
+    /// Megabytes of memory of the host, if known, and the semaphore commands reserve them from.
+    memory: Option<(SharedSemaphore, usize)>,
+    /// Number of devices of the host by device class. Classes which are not listed have one
+    /// device.
+    num_devices: HashMap<String, usize>,
+    device_semaphores: NamedSemaphores,
+}
+
+/// Tracks the prioritized requests waiting for permits, which other requests let go first.
+struct PrioritizedWaiters {
+    /// Number of prioritized requests waiting, and the machine permits they need together.
+    waiting: Mutex<(usize, usize)>,
+    /// Set when no prioritized request is waiting.
+    none_waiting: ManualResetEvent,
+}
+
+impl PrioritizedWaiters {
+    fn new() -> Self {
+        Self {
+            waiting: Mutex::new((0, 0)),
+            none_waiting: ManualResetEvent::new(true),
+        }
+    }
+
+    fn enter(&self, permits: usize) -> PrioritizedWaiter<'_> {
+        let mut waiting = self.waiting.lock().unwrap();
+        waiting.0 += 1;
+        waiting.1 += permits;
+        self.none_waiting.reset();
+        PrioritizedWaiter {
+            waiters: self,
+            permits,
+        }
+    }
+
+    /// Machine permits needed by the prioritized requests waiting.
+    fn permits(&self) -> usize {
+        self.waiting.lock().unwrap().1
+    }
+}
+
+/// A prioritized request waiting for permits, until dropped.
+struct PrioritizedWaiter<'a> {
+    waiters: &'a PrioritizedWaiters,
+    permits: usize,
+}
+
+impl Drop for PrioritizedWaiter<'_> {
+    fn drop(&mut self) {
+        let mut waiting = self.waiters.waiting.lock().unwrap();
+        waiting.0 -= 1;
+        waiting.1 -= self.permits;
+        if waiting.0 == 0 {
+            self.waiters.none_waiting.set();
+        }
+    }
 }
 
 pub struct RequestedPermits {
@@ -167,9 +244,26 @@ impl HostSharingBroker {
             permits,
             num_machine_permits,
             named_semaphores: NamedSemaphores::new(),
+            prioritized_waiters: PrioritizedWaiters::new(),
+            memory: None,
+            num_devices: HashMap::new(),
+            device_semaphores: NamedSemaphores::new(),
         }
     }
 
+    /// Reserve the memory of `HostResourceRequirements` out of `memory_mb` megabytes. Memory is
+    /// not reserved if this is not called.
+    pub fn with_memory_mb(mut self, memory_mb: usize) -> Self {
+        self.memory = Some((SharedSemaphore::new(true, memory_mb), memory_mb));
+        self
+    }
+
+    /// Set the number of devices of each class the host has.
+    pub fn with_devices(mut self, num_devices: HashMap<String, usize>) -> Self {
+        self.num_devices = num_devices;
+        self
+    }
+
     pub fn num_machine_permits(&self) -> usize {
         self.num_machine_permits
     }
@@ -177,21 +271,97 @@ impl HostSharingBroker {
     pub async fn acquire(
         &self,
         host_sharing_requirements: &HostSharingRequirements,
+    ) -> HostSharingGuard {
+        self.acquire_with_priority(
+            host_sharing_requirements,
+            &HostResourceRequirements::default(),
+            false,
+        )
+        .await
+    }
+
+    /// Like `acquire`, but also reserves `resources`, and a prioritized request goes before the
+    /// requests that are not and have not started waiting for permits yet. This is used to run
+    /// actions predicted to be on the critical path first.
+    ///
+    /// A request that is not prioritized only waits for the prioritized ones when the free machine
+    /// permits can't cover both, so that it doesn't sit idle next to permits nobody competes for.
+    pub async fn acquire_with_priority(
+        &self,
+        host_sharing_requirements: &HostSharingRequirements,
+        resources: &HostResourceRequirements,
+        prioritized: bool,
+    ) -> HostSharingGuard {
+        let permits = self.machine_permits(host_sharing_requirements);
+        if prioritized {
+            let _waiter = self.prioritized_waiters.enter(permits);
+            self.acquire_permits(host_sharing_requirements, resources)
+                .await
+        } else {
+            if self.permits.permits() < self.prioritized_waiters.permits() + permits {
+                self.prioritized_waiters.none_waiting.wait().await;
+            }
+            self.acquire_permits(host_sharing_requirements, resources)
+                .await
+        }
+    }
+
+    fn machine_permits(&self, host_sharing_requirements: &HostSharingRequirements) -> usize {
+        match host_sharing_requirements {
+            HostSharingRequirements::ExclusiveAccess => self.num_machine_permits,
+            HostSharingRequirements::Shared(weight_class)
+            | HostSharingRequirements::OnePerToken(_, weight_class) => {
+                self.requested_permits(weight_class).into_count()
+            }
+        }
+    }
+
+    /// Devices and memory are reserved before the permits, so that no permits are held while
+    /// waiting for them. Devices are reserved in the order of their classes so that two commands
+    /// needing the same classes can't each hold the devices the other one waits for.
+    async fn acquire_resources(
+        &self,
+        resources: &HostResourceRequirements,
+    ) -> Vec<SharedSemaphoreReleaser> {
+        let mut guards = Vec::new();
+        for (class, count) in &resources.devices {
+            let num_devices = self.num_devices.get(class).copied().unwrap_or(1);
+            let semaphore = self.device_semaphores.get_with_permits(class, num_devices);
+            // Like permits, a command asking for more devices than the host has is capped so that
+            // it can run at all.
+            guards.push(semaphore.acquire((*count).min(num_devices)).await);
+        }
+        if let (Some(memory_mb), Some((semaphore, host_memory_mb))) =
+            (resources.memory_mb, &self.memory)
+        {
+            guards.push(semaphore.acquire(memory_mb.min(*host_memory_mb)).await);
+        }
+        guards
+    }
+
+    async fn acquire_permits(
+        &self,
+        host_sharing_requirements: &HostSharingRequirements,
+        resources: &HostResourceRequirements,
     ) -> HostSharingGuard {
         match host_sharing_requirements {
             HostSharingRequirements::Shared(weight_class) => {
+                let _resource_guards = self.acquire_resources(resources).await;
                 let permits = self.requested_permits(weight_class).into_count();
                 let _run_guard = self.permits.acquire(permits).await;
                 HostSharingGuard {
                     _run_guard,
                     _name_guard: None,
+                    _resource_guards,
                 }
             }
             HostSharingRequirements::ExclusiveAccess => {
+                let _resource_guards = self.acquire_resources(resources).await;
                 let _run_guard = self.permits.acquire(self.num_machine_permits).await;
                 HostSharingGuard {
                     _run_guard,
                     _name_guard: None,
+                    _resource_guards,
                 }
             }
             HostSharingRequirements::OnePerToken(identifier, weight_class) => {
@@ -201,11 +371,13 @@ impl HostSharingBroker {
                 // for the previous run on this identifier to finish.
                 let run_semaphore = self.named_semaphores.get(identifier);
                 let _name_guard = Some(run_semaphore.acquire(SINGLE_RUN).await);
+                let _resource_guards = self.acquire_resources(resources).await;
                 let permits = self.requested_permits(weight_class).into_count();
                 let _run_guard = self.permits.acquire(permits).await;
                 HostSharingGuard {
                     _run_guard,
                     _name_guard,
+                    _resource_guards,
                 }
             }
         }
@@ -220,6 +392,8 @@ pub enum HostSharingStrategy {
 
 #[cfg(test)]
 mod tests {
+    use futures::FutureExt;
+
     use super::*;
 
     #[test]
@@ -279,4 +453,108 @@ mod tests {
             10,
         );
     }
+
+    fn gpus(count: usize) -> HostResourceRequirements {
+        HostResourceRequirements {
+            memory_mb: None,
+            devices: [("gpu".to_owned(), count)].into_iter().collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_devices() {
+        let broker = HostSharingBroker::new(HostSharingStrategy::SmallerTasksFirst, 10)
+            .with_devices([("gpu".to_owned(), 2)].into_iter().collect());
+        let shared = HostSharingRequirements::default();
+
+        let first = broker.acquire_with_priority(&shared, &gpus(1), false).await;
+        let second = broker.acquire_with_priority(&shared, &gpus(1), false).await;
+        // Both devices are in use.
+        assert!(
+            broker
+                .acquire_with_priority(&shared, &gpus(1), false)
+                .now_or_never()
+                .is_none()
+        );
+        drop(first);
+        // Asking for more devices than the host has waits for all of them.
+        assert!(
+            broker
+                .acquire_with_priority(&shared, &gpus(3), false)
+                .now_or_never()
+                .is_none()
+        );
+        drop(second);
+        assert!(
+            broker
+                .acquire_with_priority(&shared, &gpus(3), false)
+                .now_or_never()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_memory() {
+        let broker =
+            HostSharingBroker::new(HostSharingStrategy::SmallerTasksFirst, 10).with_memory_mb(1024);
+        let shared = HostSharingRequirements::default();
+        let memory = |memory_mb| HostResourceRequirements {
+            memory_mb: Some(memory_mb),
+            devices: BTreeMap::new(),
+        };
+
+        let first = broker
+            .acquire_with_priority(&shared, &memory(768), false)
+            .await;
+        assert!(
+            broker
+                .acquire_with_priority(&shared, &memory(512), false)
+                .now_or_never()
+                .is_none()
+        );
+        assert!(
+            broker
+                .acquire_with_priority(&shared, &memory(256), false)
+                .now_or_never()
+                .is_some()
+        );
+        drop(first);
+    }
+
+    #[tokio::test]
+    async fn test_prioritized() {
+        let broker = HostSharingBroker::new(HostSharingStrategy::SmallerTasksFirst, 4);
+        let shared = |count| HostSharingRequirements::Shared(WeightClass::Permits(count));
+        let token =
+            || HostSharingRequirements::OnePerToken("token".to_owned(), WeightClass::Permits(1));
+        let none = HostResourceRequirements::default();
+
+        let running = broker.acquire(&shared(3)).await;
+        // Waits for 2 permits while only 1 is free.
+        let mut prioritized = Box::pin(broker.acquire_with_priority(&shared(2), &none, true));
+        assert!((&mut prioritized).now_or_never().is_none());
+        // The free permit would be taken from the prioritized request.
+        assert!(
+            broker
+                .acquire_with_priority(&shared(1), &none, false)
+                .now_or_never()
+                .is_none()
+        );
+        drop(running);
+        drop(prioritized.await);
+
+        let running = broker.acquire(&token()).await;
+        // Waits for the token, with 3 permits free.
+        let mut prioritized = Box::pin(broker.acquire_with_priority(&token(), &none, true));
+        assert!((&mut prioritized).now_or_never().is_none());
+        // There are enough permits for both.
+        assert!(
+            broker
+                .acquire_with_priority(&shared(1), &none, false)
+                .now_or_never()
+                .is_some()
+        );
+        drop(running);
+        drop(prioritized.await);
+    }
 }
@@ -23,21 +23,27 @@ impl NamedSemaphores {
     }
 
     pub fn get(&self, name: &str) -> SharedSemaphore {
+        self.get_with_permits(name, SINGLE_WORKER)
+    }
+
+    /// Like `get`, but the semaphore has `permits` permits when it is created. Later calls for the
+    /// same name return the existing semaphore regardless of `permits`.
+    pub fn get_with_permits(&self, name: &str, permits: usize) -> SharedSemaphore {
         if let Some(bucket_semaphore) = self.buckets.get(name) {
             bucket_semaphore.clone()
         } else {
             // Fairness on this semaphore doesn't control the order in which waiters are woken up, but
-            // simply whether we delay wakeups to wait for waiters that want > 1 permit. Since we only
-            // ever request a single permit here, the fairness doesn't matter.
+            // simply whether we delay wakeups to wait for waiters that want > 1 permit. When only a
+            // single permit is ever requested, the fairness doesn't matter, and when more are, it
+            // keeps waiters which want many permits from being starved by those which want few.
             //
             // Since a fair semaphore will only ever wake a single waiter, that code path is a bit
-            // faster and doesn't require any additional looping. Therefore despite the fact that a
-            // fair and unfair semaphore would have the same end result, we use a fair semaphore
-            // here as a small performance optimization.
-            let bucket_semaphore = SharedSemaphore::new(true, SINGLE_WORKER);
+            // faster and doesn't require any additional looping. Therefore we always use a fair
+            // semaphore here.
             self.buckets
-                .insert(name.to_owned(), bucket_semaphore.clone());
-            bucket_semaphore
+                .entry(name.to_owned())
+                .or_insert_with(|| SharedSemaphore::new(true, permits))
+                .clone()
         }
     }
 }
@@ -70,4 +76,15 @@ mod tests {
         let second_permit = named_semaphore.get(&identifier);
         assert!(second_permit.try_acquire(1).is_none());
     }
+
+    #[test]
+    fn test_get_with_permits() {
+        let named_semaphore = NamedSemaphores::new();
+        let first = named_semaphore.get_with_permits("gpu", 2);
+        let first_permit = first.try_acquire(1);
+        assert!(first_permit.is_some());
+        let second = named_semaphore.get_with_permits("gpu", 2);
+        assert!(second.try_acquire(2).is_none());
+        assert!(second.try_acquire(1).is_some());
+    }
 }
@@ -11,6 +11,8 @@ use std::collections::HashMap;
 use std::env::VarError;
 use std::num::NonZeroUsize;
 use std::pin::Pin;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
@@ -35,6 +37,7 @@ use re_grpc_proto::build::bazel::remote::execution::v2::batch_update_blobs_reque
 use re_grpc_proto::build::bazel::remote::execution::v2::capabilities_client::CapabilitiesClient;
 use re_grpc_proto::build::bazel::remote::execution::v2::compressor;
 use re_grpc_proto::build::bazel::remote::execution::v2::content_addressable_storage_client::ContentAddressableStorageClient;
+use re_grpc_proto::build::bazel::remote::execution::v2::digest_function;
 use re_grpc_proto::build::bazel::remote::execution::v2::execution_client::ExecutionClient;
 use re_grpc_proto::build::bazel::remote::execution::v2::execution_stage;
 use re_grpc_proto::build::bazel::remote::execution::v2::ActionResult;
@@ -46,13 +49,17 @@ use re_grpc_proto::build::bazel::remote::execution::v2::Digest;
 use re_grpc_proto::build::bazel::remote::execution::v2::ExecuteOperationMetadata;
 use re_grpc_proto::build::bazel::remote::execution::v2::ExecuteRequest as GExecuteRequest;
 use re_grpc_proto::build::bazel::remote::execution::v2::ExecuteResponse as GExecuteResponse;
+use re_grpc_proto::build::bazel::remote::execution::v2::ExecutedActionMetadata;
 use re_grpc_proto::build::bazel::remote::execution::v2::FindMissingBlobsRequest;
-use re_grpc_proto::build::bazel::remote::execution::v2::FindMissingBlobsResponse;
 use re_grpc_proto::build::bazel::remote::execution::v2::GetActionResultRequest;
 use re_grpc_proto::build::bazel::remote::execution::v2::GetCapabilitiesRequest;
+use re_grpc_proto::build::bazel::remote::execution::v2::OutputDirectory;
+use re_grpc_proto::build::bazel::remote::execution::v2::OutputFile;
+use re_grpc_proto::build::bazel::remote::execution::v2::OutputSymlink;
 use re_grpc_proto::build::bazel::remote::execution::v2::RequestMetadata;
 use re_grpc_proto::build::bazel::remote::execution::v2::ResultsCachePolicy;
 use re_grpc_proto::build::bazel::remote::execution::v2::ToolDetails;
+use re_grpc_proto::build::bazel::remote::execution::v2::UpdateActionResultRequest;
 use re_grpc_proto::google::bytestream::byte_stream_client::ByteStreamClient;
 use re_grpc_proto::google::bytestream::ReadRequest;
 use re_grpc_proto::google::bytestream::ReadResponse;
@@ -77,12 +84,16 @@ use tonic::transport::Identity;
 use tonic::transport::Uri;
 
 use crate::error::*;
+use crate::find_missing::FindMissingBatchConfig;
+use crate::find_missing::FindMissingBatcher;
 use crate::metadata::*;
 use crate::request::*;
 use crate::response::*;
 
 const DEFAULT_MAX_TOTAL_BATCH_SIZE: usize = 4 * 1000 * 1000;
 
+const DEFAULT_FIND_MISSING_BLOBS_BATCH_SIZE: usize = 1000;
+
 fn tdigest_to(tdigest: TDigest) -> Digest {
     Digest {
         hash: tdigest.hash,
@@ -208,6 +219,8 @@ pub struct RECapabilities {
     max_total_batch_size: usize,
     /// Does the remote server support execution.
     exec_enabled: bool,
+    /// Names of the digest functions the server supports, e.g. `SHA256`. Empty if unknown.
+    digest_functions: Vec<String>,
 }
 
 /// Contains runtime options for the remote execution client as set under `buck2_re_client`
@@ -216,6 +229,33 @@ pub struct RERuntimeOpts {
     use_fbcode_metadata: bool,
     /// Maximum number of concurrent upload requests.
     max_concurrent_uploads_per_action: Option<usize>,
+    /// Maximum size of a `BatchUpdateBlobs` request, if smaller than the one allowed by the server.
+    max_batch_upload_size: Option<usize>,
+}
+
+/// Number of CAS RPCs sent by a client.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CasRpcStats {
+    pub find_missing_blobs: u64,
+    pub batch_update_blobs: u64,
+    pub bytestream_writes: u64,
+}
+
+#[derive(Default)]
+struct CasRpcCounters {
+    find_missing_blobs: AtomicU64,
+    batch_update_blobs: AtomicU64,
+    bytestream_writes: AtomicU64,
+}
+
+impl CasRpcCounters {
+    fn stats(&self) -> CasRpcStats {
+        CasRpcStats {
+            find_missing_blobs: self.find_missing_blobs.load(Ordering::Relaxed),
+            batch_update_blobs: self.batch_update_blobs.load(Ordering::Relaxed),
+            bytestream_writes: self.bytestream_writes.load(Ordering::Relaxed),
+        }
+    }
 }
 
 struct InstanceName(Option<String>);
@@ -300,6 +340,7 @@ impl REClientBuilder {
             RECapabilities {
                 exec_enabled: true,
                 max_total_batch_size: DEFAULT_MAX_TOTAL_BATCH_SIZE,
+                digest_functions: Vec::new(),
             }
         };
 
@@ -342,6 +383,15 @@ impl REClientBuilder {
             RERuntimeOpts {
                 use_fbcode_metadata: opts.use_fbcode_metadata,
                 max_concurrent_uploads_per_action: opts.max_concurrent_uploads_per_action,
+                max_batch_upload_size: opts.max_batch_upload_size,
+            },
+            FindMissingBatchConfig {
+                max_digests: opts
+                    .find_missing_blobs_batch_size
+                    .unwrap_or(DEFAULT_FIND_MISSING_BLOBS_BATCH_SIZE),
+                latency: Duration::from_millis(
+                    opts.find_missing_blobs_batch_latency_ms.unwrap_or(0),
+                ),
             },
             grpc_clients,
             capabilities,
@@ -365,9 +415,26 @@ impl REClientBuilder {
             .into_inner();
 
         let mut exec_enabled = true;
+        let mut digest_functions: Vec<String> = Vec::new();
+        let mut add_digest_function = |f: i32| {
+            let name = match digest_function::Value::from_i32(f) {
+                Some(digest_function::Value::Unknown) => return,
+                Some(f) => f.as_str_name(),
+                // Our copy of the protos predates BLAKE3.
+                None if f == 9 => "BLAKE3",
+                None => return,
+            };
+            if !digest_functions.iter().any(|d| d == name) {
+                digest_functions.push(name.to_owned());
+            }
+        };
 
         let max_total_batch_size_from_capabilities: Option<usize> =
             if let Some(cache_cap) = resp.cache_capabilities {
+                cache_cap
+                    .digest_functions
+                    .iter()
+                    .for_each(|f| add_digest_function(*f));
                 let size = cache_cap.max_batch_total_size_bytes as usize;
                 // A value of 0 means no limit is set
                 if size != 0 { Some(size) } else { None }
@@ -385,11 +452,13 @@ impl REClientBuilder {
 
         if let Some(exec_cap) = resp.execution_capabilities {
             exec_enabled = exec_cap.exec_enabled;
+            add_digest_function(exec_cap.digest_function);
         }
 
         Ok(RECapabilities {
             max_total_batch_size,
             exec_enabled,
+            digest_functions,
         })
     }
 }
@@ -486,6 +555,8 @@ pub struct REClient {
     instance_name: InstanceName,
     // buck2 calls find_missing for same blobs
     find_missing_cache: Mutex<FindMissingCache>,
+    find_missing_batcher: FindMissingBatcher,
+    rpc_counters: Arc<CasRpcCounters>,
 }
 
 impl Drop for REClient {
@@ -528,7 +599,7 @@ impl BatchUploadReqAggregator {
         };
         self.curr_request_size += size_in_bytes;
 
-        if self.curr_request_size >= self.max_msg_size {
+        if self.curr_request_size >= self.max_msg_size && !self.curr_req.is_empty() {
             self.requests.push(std::mem::take(&mut self.curr_req));
             self.curr_request_size = size_in_bytes;
         }
@@ -546,10 +617,43 @@ impl BatchUploadReqAggregator {
 impl REClient {
     fn new(
         runtime_opts: RERuntimeOpts,
+        find_missing_batch_config: FindMissingBatchConfig,
         grpc_clients: GRPCClients,
         capabilities: RECapabilities,
         instance_name: InstanceName,
     ) -> Self {
+        let rpc_counters = Arc::new(CasRpcCounters::default());
+        let find_missing_batcher = {
+            let cas_client = grpc_clients.cas_client.clone();
+            let instance_name = instance_name.as_str().to_owned();
+            let use_fbcode_metadata = runtime_opts.use_fbcode_metadata;
+            let rpc_counters = rpc_counters.dupe();
+            FindMissingBatcher::new(find_missing_batch_config, move |digests, metadata| {
+                let mut cas_client = cas_client.clone();
+                let instance_name = instance_name.clone();
+                let rpc_counters = rpc_counters.dupe();
+                async move {
+                    rpc_counters
+                        .find_missing_blobs
+                        .fetch_add(1, Ordering::Relaxed);
+                    let resp = cas_client
+                        .find_missing_blobs(with_re_metadata(
+                            FindMissingBlobsRequest {
+                                instance_name,
+                                blob_digests: digests.map(|b| tdigest_to(b.clone())),
+                            },
+                            metadata,
+                            use_fbcode_metadata,
+                        ))
+                        .await
+                        .context("Failed to request what blobs are not present on remote")?;
+                    Ok(resp
+                        .into_inner()
+                        .missing_blob_digests
+                        .map(|d| tdigest_from(d.clone())))
+                }
+            })
+        };
         REClient {
             runtime_opts,
             grpc_clients,
@@ -560,9 +664,16 @@ impl REClient {
                 ttl: Duration::from_secs(12 * 60 * 60), // 12 hours TODO: Tune this parameter
                 last_check: Instant::now(),
             }),
+            find_missing_batcher,
+            rpc_counters,
         }
     }
 
+    /// Number of CAS RPCs sent by this client.
+    pub fn get_cas_rpc_stats(&self) -> CasRpcStats {
+        self.rpc_counters.stats()
+    }
+
     pub async fn get_action_result(
         &self,
         metadata: RemoteExecutionMetadata,
@@ -590,10 +701,33 @@ impl REClient {
 
     pub async fn write_action_result(
         &self,
-        _metadata: RemoteExecutionMetadata,
-        _request: WriteActionResultRequest,
+        metadata: RemoteExecutionMetadata,
+        request: WriteActionResultRequest,
     ) -> anyhow::Result<WriteActionResultResponse> {
-        Err(anyhow::anyhow!("Not supported"))
+        let mut client = self.grpc_clients.action_cache_client.clone();
+
+        let res = client
+            .update_action_result(with_re_metadata(
+                UpdateActionResultRequest {
+                    instance_name: self.instance_name.as_str().to_owned(),
+                    action_digest: Some(tdigest_to(request.action_digest)),
+                    action_result: Some(convert_t_action_result2(request.action_result)),
+                    ..Default::default()
+                },
+                metadata,
+                self.runtime_opts.use_fbcode_metadata,
+            ))
+            .await
+            .map_err(|status| REClientError {
+                code: TCode(status.code() as i32),
+                message: status.message().to_owned(),
+                group: TCodeReasonGroup::UNKNOWN,
+            })?;
+
+        Ok(WriteActionResultResponse {
+            actual_action_result: convert_action_result(res.into_inner())?,
+            ttl_seconds: 0,
+        })
     }
 
     pub async fn execute_with_progress(
@@ -729,9 +863,13 @@ impl REClient {
             &self.instance_name,
             request,
             self.capabilities.max_total_batch_size,
+            self.runtime_opts.max_batch_upload_size,
             self.runtime_opts.max_concurrent_uploads_per_action,
             |re_request| async {
                 let metadata = metadata.clone();
+                self.rpc_counters
+                    .batch_update_blobs
+                    .fetch_add(1, Ordering::Relaxed);
                 let mut cas_client = self.grpc_clients.cas_client.clone();
                 let resp = cas_client
                     .batch_update_blobs(with_re_metadata(
@@ -744,6 +882,9 @@ impl REClient {
             },
             |segments| async {
                 let metadata = metadata.clone();
+                self.rpc_counters
+                    .bytestream_writes
+                    .fetch_add(1, Ordering::Relaxed);
                 let mut bytestream_client = self.grpc_clients.bytestream_client.clone();
                 let requests = futures::stream::iter(segments);
                 let resp = bytestream_client
@@ -814,55 +955,43 @@ impl REClient {
         metadata: RemoteExecutionMetadata,
         request: GetDigestsTtlRequest,
     ) -> anyhow::Result<GetDigestsTtlResponse> {
-        let mut cas_client = self.grpc_clients.cas_client.clone();
         let mut remote_ttl: HashMap<TDigest, DigestWithTtl> = HashMap::new();
 
-        for digest_chunk in request.digests.chunks(100) {
-            let mut digest_to_check: Vec<TDigest> = Vec::new();
-            {
-                let mut find_missing_cache = self.find_missing_cache.lock().unwrap();
-                for digest in digest_chunk {
-                    // Assume that all digests are present on the remote because the API
-                    // returns what is *not* present.
-                    remote_ttl.insert(
-                        digest.clone(),
-                        DigestWithTtl {
-                            digest: digest.clone(),
-                            // NOTE: This is an arbitrary number because RBE does not return information
-                            // on the TTL of the remote blob.
-                            ttl: 60,
-                        },
-                    );
-                    match find_missing_cache.get(digest) {
-                        Some(DigestRemoteState::Missing) | None => {
-                            digest_to_check.push(digest.clone());
-                        }
-                        _ => {}
+        let mut digest_to_check: Vec<TDigest> = Vec::new();
+        {
+            let mut find_missing_cache = self.find_missing_cache.lock().unwrap();
+            for digest in &request.digests {
+                // Assume that all digests are present on the remote because the API
+                // returns what is *not* present.
+                remote_ttl.insert(
+                    digest.clone(),
+                    DigestWithTtl {
+                        digest: digest.clone(),
+                        // NOTE: This is an arbitrary number because RBE does not return information
+                        // on the TTL of the remote blob.
+                        ttl: 60,
+                    },
+                );
+                match find_missing_cache.get(digest) {
+                    Some(DigestRemoteState::Missing) | None => {
+                        digest_to_check.push(digest.clone());
                     }
+                    _ => {}
                 }
             }
+        }
 
-            if digest_to_check.is_empty() {
-                continue;
-            }
-
-            let missing_blobs = cas_client
-                .find_missing_blobs(with_re_metadata(
-                    FindMissingBlobsRequest {
-                        instance_name: self.instance_name.as_str().to_owned(),
-                        blob_digests: digest_to_check.map(|b| tdigest_to(b.clone())),
-                    },
-                    metadata.clone(),
-                    self.runtime_opts.use_fbcode_metadata,
-                ))
-                .await
-                .context("Failed to request what blobs are not present on remote")?;
-            let resp: FindMissingBlobsResponse = missing_blobs.into_inner();
+        if !digest_to_check.is_empty() {
+            // Batched with the requests of other actions.
+            let missing = self
+                .find_missing_batcher
+                .find_missing(digest_to_check.clone(), metadata)
+                .await?;
             let mut find_missing_cache = self.find_missing_cache.lock().unwrap();
             for digest in &digest_to_check {
                 find_missing_cache.put(digest.clone(), DigestRemoteState::ExistsOnRemote);
             }
-            for digest in &resp.missing_blob_digests.map(|d| tdigest_from(d.clone())) {
+            for digest in &missing {
                 // If it's present in the MissingBlobsResponse, it's expired on the remote and
                 // needs to be refetched.
                 remote_ttl.insert(
@@ -914,6 +1043,12 @@ impl REClient {
     pub fn get_experiment_name(&self) -> anyhow::Result<Option<String>> {
         Ok(None)
     }
+
+    /// Names of the digest functions the server reported in its capabilities, e.g. `SHA256`.
+    /// Empty if capabilities were not queried.
+    pub fn get_digest_functions(&self) -> &[String] {
+        &self.capabilities.digest_functions
+    }
 }
 
 fn convert_action_result(action_result: ActionResult) -> anyhow::Result<TActionResult2> {
@@ -1012,6 +1147,80 @@ fn convert_action_result(action_result: ActionResult) -> anyhow::Result<TActionR
     Ok(action_result)
 }
 
+fn ttimestamp_to(ts: TTimestamp) -> Option<::prost_types::Timestamp> {
+    Some(::prost_types::Timestamp {
+        seconds: ts.seconds,
+        nanos: ts.nanos,
+    })
+}
+
+/// The inverse of `convert_action_result`, for writes to the action cache.
+fn convert_t_action_result2(t_action_result: TActionResult2) -> ActionResult {
+    let t_execution_metadata = t_action_result.execution_metadata;
+    let execution_metadata = Some(ExecutedActionMetadata {
+        worker: t_execution_metadata.worker,
+        queued_timestamp: ttimestamp_to(t_execution_metadata.queued_timestamp),
+        worker_start_timestamp: ttimestamp_to(t_execution_metadata.worker_start_timestamp),
+        worker_completed_timestamp: ttimestamp_to(t_execution_metadata.worker_completed_timestamp),
+        input_fetch_start_timestamp: ttimestamp_to(
+            t_execution_metadata.input_fetch_start_timestamp,
+        ),
+        input_fetch_completed_timestamp: ttimestamp_to(
+            t_execution_metadata.input_fetch_completed_timestamp,
+        ),
+        execution_start_timestamp: ttimestamp_to(t_execution_metadata.execution_start_timestamp),
+        execution_completed_timestamp: ttimestamp_to(
+            t_execution_metadata.execution_completed_timestamp,
+        ),
+        output_upload_start_timestamp: ttimestamp_to(
+            t_execution_metadata.output_upload_start_timestamp,
+        ),
+        output_upload_completed_timestamp: ttimestamp_to(
+            t_execution_metadata.output_upload_completed_timestamp,
+        ),
+        ..Default::default()
+    });
+
+    let output_files = t_action_result
+        .output_files
+        .into_map(|output_file| OutputFile {
+            digest: Some(tdigest_to(output_file.digest.digest)),
+            path: output_file.name,
+            is_executable: output_file.executable,
+            ..Default::default()
+        });
+
+    let output_symlinks =
+        t_action_result
+            .output_symlinks
+            .into_map(|output_symlink| OutputSymlink {
+                path: output_symlink.name,
+                target: output_symlink.target,
+                ..Default::default()
+            });
+
+    let output_directories = t_action_result
+        .output_directories
+        .into_map(|output_directory| OutputDirectory {
+            path: output_directory.path,
+            tree_digest: Some(tdigest_to(output_directory.tree_digest)),
+            ..Default::default()
+        });
+
+    ActionResult {
+        output_files,
+        output_symlinks,
+        output_directories,
+        exit_code: t_action_result.exit_code,
+        stdout_raw: t_action_result.stdout_raw.unwrap_or_default(),
+        stdout_digest: t_action_result.stdout_digest.map(tdigest_to),
+        stderr_raw: t_action_result.stderr_raw.unwrap_or_default(),
+        stderr_digest: t_action_result.stderr_digest.map(tdigest_to),
+        execution_metadata,
+        ..Default::default()
+    }
+}
+
 async fn download_impl<Byt, BytRet, Cas>(
     instance_name: &InstanceName,
     request: DownloadRequest,
@@ -1190,6 +1399,7 @@ async fn upload_impl<Byt, Cas>(
     instance_name: &InstanceName,
     request: UploadRequest,
     max_total_batch_size: usize,
+    max_batch_upload_size: Option<usize>,
     max_concurrent_uploads: Option<usize>,
     cas_f: impl Fn(BatchUpdateBlobsRequest) -> Cas + Sync + Send + Copy,
     bystream_fut: impl Fn(Vec<WriteRequest>) -> Byt + Sync + Send + Copy,
@@ -1203,7 +1413,10 @@ where
 
     // For small file uploads the client should group them together and call `BatchUpdateBlobs`
     // https://github.com/bazelbuild/remote-apis/blob/main/build/bazel/remote/execution/v2/remote_execution.proto#L205
-    let mut batched_blob_updates = BatchUploadReqAggregator::new(max_total_batch_size);
+    // Smaller batches are sent earlier and concurrently, at the cost of more requests.
+    let mut batched_blob_updates = BatchUploadReqAggregator::new(
+        max_batch_upload_size.map_or(max_total_batch_size, |s| s.min(max_total_batch_size)),
+    );
 
     // Create futures for any blobs that need uploading.
     for blob in request.inlined_blobs_with_digest.unwrap_or_default() {
@@ -2076,6 +2289,7 @@ mod tests {
             req,
             10000,
             None,
+            None,
             |req| {
                 let res = res.clone();
                 let digest1 = digest1.clone();
@@ -2159,6 +2373,7 @@ mod tests {
             req,
             10, // kept small to simulate a large file upload
             None,
+            None,
             |req| {
                 let res = res.clone();
                 let digest1 = digest1.clone();
@@ -2233,6 +2448,7 @@ mod tests {
             req,
             10, // kept small to simulate a large inlined upload
             None,
+            None,
             |req| {
                 let res = res.clone();
                 let digest1 = digest1.clone();
@@ -2294,6 +2510,7 @@ mod tests {
             req,
             10,
             None,
+            None,
             |_req| async move {
                 panic!("This should not be called as there are no blobs to upload in batch");
             },
@@ -2355,6 +2572,7 @@ mod tests {
             req,
             3,
             None,
+            None,
             |_req| async move {
                 panic!("Not called");
             },
@@ -2396,6 +2614,7 @@ mod tests {
             req,
             0,
             None,
+            None,
             |_req| async move {
                 panic!("Not called");
             },
@@ -2442,6 +2661,7 @@ mod tests {
             req,
             1,
             None,
+            None,
             |_req| async move {
                 panic!("Not called");
             },
@@ -0,0 +1,227 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under both the MIT license found in the
+ * LICENSE-MIT file in the root directory of this source tree and the Apache
+ * License, Version 2.0 found in the LICENSE-APACHE file in the root directory
+ * of this source tree.
+ */
+
+//! Batching of `FindMissingBlobs` requests.
+//!
+//! Every action checks which of its inputs are missing before uploading them. On high latency
+//! links, one RPC per action is expensive, so the checks of concurrent actions are sent together:
+//! a request waits up to the configured latency for other requests, and batches are sent as soon
+//! as they have enough digests, without waiting for the previous batches to complete.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+
+use crate::digest::TDigest;
+use crate::metadata::RemoteExecutionMetadata;
+
+type FindMissingResult = Result<Arc<HashSet<TDigest>>, Arc<anyhow::Error>>;
+
+struct PendingRequest {
+    digests: Vec<TDigest>,
+    metadata: RemoteExecutionMetadata,
+    response: oneshot::Sender<FindMissingResult>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FindMissingBatchConfig {
+    /// Maximum number of digests in one `FindMissingBlobs` request.
+    pub(crate) max_digests: usize,
+    /// How long a request waits for other requests to be batched with.
+    pub(crate) latency: Duration,
+}
+
+pub(crate) struct FindMissingBatcher {
+    requests: mpsc::UnboundedSender<PendingRequest>,
+}
+
+impl FindMissingBatcher {
+    /// Start batching requests. `find_missing` sends one `FindMissingBlobs` request and returns
+    /// the missing digests. Batches are sent with the metadata of their first request.
+    pub(crate) fn new<F, Fut>(config: FindMissingBatchConfig, find_missing: F) -> Self
+    where
+        F: Fn(Vec<TDigest>, RemoteExecutionMetadata) -> Fut + Clone + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<Vec<TDigest>>> + Send + 'static,
+    {
+        let (requests, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(batch_requests(receiver, config, find_missing));
+        FindMissingBatcher { requests }
+    }
+
+    /// The digests missing on the remote, among the given ones.
+    pub(crate) async fn find_missing(
+        &self,
+        digests: Vec<TDigest>,
+        metadata: RemoteExecutionMetadata,
+    ) -> anyhow::Result<Vec<TDigest>> {
+        let (response, receiver) = oneshot::channel();
+        self.requests
+            .send(PendingRequest {
+                digests: digests.clone(),
+                metadata,
+                response,
+            })
+            .map_err(|_| anyhow::anyhow!("FindMissingBlobs batcher stopped"))?;
+        let missing = receiver
+            .await
+            .map_err(|_| anyhow::anyhow!("FindMissingBlobs batcher stopped"))?
+            .map_err(|e| anyhow::anyhow!("{:#}", e))?;
+        Ok(digests
+            .into_iter()
+            .filter(|d| missing.contains(d))
+            .collect())
+    }
+}
+
+/// Runs until the batcher is dropped.
+async fn batch_requests<F, Fut>(
+    mut receiver: mpsc::UnboundedReceiver<PendingRequest>,
+    config: FindMissingBatchConfig,
+    find_missing: F,
+) where
+    F: Fn(Vec<TDigest>, RemoteExecutionMetadata) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = anyhow::Result<Vec<TDigest>>> + Send + 'static,
+{
+    while let Some(first) = receiver.recv().await {
+        let deadline = Instant::now() + config.latency;
+        let mut digests = first.digests.len();
+        let mut batch = vec![first];
+        // Requests already queued are taken even if the latency is zero.
+        while digests < config.max_digests {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(request)) => {
+                    digests += request.digests.len();
+                    batch.push(request);
+                }
+                Ok(None) | Err(_) => break,
+            }
+        }
+        tokio::spawn(send_batch(batch, config.max_digests, find_missing.clone()));
+    }
+}
+
+async fn send_batch<F, Fut>(batch: Vec<PendingRequest>, max_digests: usize, find_missing: F)
+where
+    F: Fn(Vec<TDigest>, RemoteExecutionMetadata) -> Fut,
+    Fut: Future<Output = anyhow::Result<Vec<TDigest>>>,
+{
+    let mut digests: Vec<TDigest> = batch.iter().flat_map(|r| r.digests.clone()).collect();
+    digests.sort_by(|a, b| a.hash.cmp(&b.hash));
+    digests.dedup();
+
+    let metadata = batch[0].metadata.clone();
+    let result = future::try_join_all(
+        digests
+            .chunks(max_digests.max(1))
+            .map(|chunk| find_missing(chunk.to_vec(), metadata.clone())),
+    )
+    .await;
+    let result: FindMissingResult = match result {
+        Ok(missing) => Ok(Arc::new(missing.into_iter().flatten().collect())),
+        Err(e) => Err(Arc::new(e)),
+    };
+
+    for request in batch {
+        // The requester may have been cancelled.
+        let _ignored = request.response.send(result.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::digest::TDigest;
+    use crate::find_missing::FindMissingBatchConfig;
+    use crate::find_missing::FindMissingBatcher;
+    use crate::metadata::RemoteExecutionMetadata;
+
+    fn digest(hash: &str) -> TDigest {
+        TDigest {
+            hash: hash.to_owned(),
+            size_in_bytes: 1,
+            ..Default::default()
+        }
+    }
+
+    fn batcher(max_digests: usize, rpcs: Arc<AtomicUsize>) -> FindMissingBatcher {
+        FindMissingBatcher::new(
+            FindMissingBatchConfig {
+                max_digests,
+                latency: Duration::from_millis(100),
+            },
+            move |digests: Vec<TDigest>, _metadata| {
+                let rpcs = rpcs.clone();
+                async move {
+                    assert!(digests.len() <= max_digests);
+                    rpcs.fetch_add(1, Ordering::SeqCst);
+                    Ok(digests
+                        .into_iter()
+                        .filter(|d| d.hash.starts_with("missing"))
+                        .collect())
+                }
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_are_batched() -> anyhow::Result<()> {
+        let rpcs = Arc::new(AtomicUsize::new(0));
+        let batcher = batcher(100, rpcs.clone());
+
+        let (a, b) = futures::future::join(
+            batcher.find_missing(
+                vec![digest("missing_a"), digest("present")],
+                RemoteExecutionMetadata::default(),
+            ),
+            batcher.find_missing(
+                vec![digest("present"), digest("missing_b")],
+                RemoteExecutionMetadata::default(),
+            ),
+        )
+        .await;
+
+        assert_eq!(vec![digest("missing_a")], a?);
+        assert_eq!(vec![digest("missing_b")], b?);
+        assert_eq!(1, rpcs.load(Ordering::SeqCst));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_large_requests_are_split() -> anyhow::Result<()> {
+        let rpcs = Arc::new(AtomicUsize::new(0));
+        let batcher = batcher(2, rpcs.clone());
+
+        let missing = batcher
+            .find_missing(
+                vec![
+                    digest("missing_a"),
+                    digest("b"),
+                    digest("missing_c"),
+                    digest("d"),
+                    digest("e"),
+                ],
+                RemoteExecutionMetadata::default(),
+            )
+            .await?;
+
+        assert_eq!(vec![digest("missing_a"), digest("missing_c")], missing);
+        assert_eq!(3, rpcs.load(Ordering::SeqCst));
+        Ok(())
+    }
+}
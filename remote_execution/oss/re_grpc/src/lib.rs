@@ -10,6 +10,7 @@
 mod client;
 mod digest;
 mod error;
+mod find_missing;
 mod grpc;
 mod metadata;
 mod request;
@@ -35,7 +35,7 @@ mod incompatible;
 mod lint_message;
 mod names;
 mod performance;
-mod types;
+pub(crate) mod types;
 mod underscore;
 mod unused_loads;
 
@@ -42,7 +42,7 @@ pub(crate) struct LintT<T> {
 }
 
 /// A lint produced by `AstModule::lint`.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Lint {
     /// Which code location does this lint refer to.
     pub location: FileSpan,
@@ -50,12 +50,20 @@ pub(crate) struct Constants {
     pub(crate) fn_set: BuiltinFn,
     // Technically, this is not a function.
     pub(crate) typing_callable: BuiltinFn,
+    pub(crate) typing_literal: BuiltinFn,
+    pub(crate) typing_type_var: BuiltinFn,
+    pub(crate) typing_struct: BuiltinFn,
 }
 
 impl Constants {
     pub fn get() -> &'static Constants {
         static RES: Lazy<Constants> = Lazy::new(|| {
             let g = Globals::extended_internal();
+            let typing = g
+                .get_frozen("typing")
+                .unwrap()
+                .downcast_frozen_ref::<FrozenNamespace>()
+                .unwrap();
             Constants {
                 fn_len: BuiltinFn(g.get_frozen("len").unwrap()),
                 fn_type: BuiltinFn(g.get_frozen("type").unwrap()),
@@ -64,14 +72,10 @@ impl Constants {
                 fn_tuple: BuiltinFn(g.get_frozen("tuple").unwrap()),
                 fn_isinstance: BuiltinFn(g.get_frozen("isinstance").unwrap()),
                 fn_set: BuiltinFn(g.get_frozen("set").unwrap()),
-                typing_callable: {
-                    let typing = g
-                        .get_frozen("typing")
-                        .unwrap()
-                        .downcast_frozen_ref::<FrozenNamespace>()
-                        .unwrap();
-                    BuiltinFn(typing.as_ref().get("Callable").unwrap())
-                },
+                typing_callable: BuiltinFn(typing.as_ref().get("Callable").unwrap()),
+                typing_literal: BuiltinFn(typing.as_ref().get("Literal").unwrap()),
+                typing_type_var: BuiltinFn(typing.as_ref().get("TypeVar").unwrap()),
+                typing_struct: BuiltinFn(typing.as_ref().get("Struct").unwrap()),
             }
         });
         Lazy::force(&RES)
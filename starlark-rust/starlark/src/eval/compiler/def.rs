@@ -199,13 +199,6 @@ impl<T> ParameterCompiled<T> {
         }
     }
 
-    pub(crate) fn ty(&self) -> Ty {
-        match self.name_ty() {
-            (_, Some(t)) => t.as_ty().clone(),
-            (_, None) => Ty::any(),
-        }
-    }
-
     pub(crate) fn required(&self) -> ParamIsRequired {
         match self {
             ParameterCompiled::Normal(_, _, None) => ParamIsRequired::Yes,
@@ -273,36 +266,31 @@ impl<T> ParametersCompiled<T> {
             .collect()
     }
 
-    pub(crate) fn to_ty_params(&self) -> ParamSpec {
+    /// Signature for the typechecker, given the types of the parameters, by index.
+    pub(crate) fn to_ty_params(&self, tys: &[Ty]) -> ParamSpec {
         ParamSpec::new_parts(
             self.indices.pos_only().map(|i| {
                 let p = &self.params[i].node;
-                (p.required(), p.ty())
+                (p.required(), tys[i].clone())
             }),
             self.indices.pos_or_named().map(|i| {
                 let p = &self.params[i].node;
                 (
                     ArcStr::from(p.name_ty().0.name.as_str()),
                     p.required(),
-                    p.ty(),
+                    tys[i].clone(),
                 )
             }),
-            self.indices.args.map(|i| {
-                let p = &self.params[i as usize].node;
-                p.ty()
-            }),
+            self.indices.args.map(|i| tys[i as usize].clone()),
             self.indices.named_only(self.params.len()).map(|i| {
                 let p = &self.params[i].node;
                 (
                     ArcStr::from(p.name_ty().0.name.as_str()),
                     p.required(),
-                    p.ty(),
+                    tys[i].clone(),
                 )
             }),
-            self.indices.kwargs.map(|i| {
-                let p = &self.params[i as usize].node;
-                p.ty()
-            }),
+            self.indices.kwargs.map(|i| tys[i as usize].clone()),
         )
         // TODO(nga): do not unwrap.
         .unwrap()
@@ -444,6 +432,17 @@ impl Compiler<'_, '_, '_, '_> {
         })
     }
 
+    /// Type of an annotation for the typechecker. It is kept even when the annotation is not
+    /// checked at runtime because it matches any value, like a type variable.
+    fn annotation_ty(&self, expr: Option<&CstTypeExpr>) -> Ty {
+        match expr {
+            Some(expr) if self.check_types => {
+                expr.payload.compiler_ty.clone().unwrap_or_else(Ty::any)
+            }
+            _ => Ty::any(),
+        }
+    }
+
     pub fn function(
         &mut self,
         name: &str,
@@ -462,6 +461,9 @@ impl Compiler<'_, '_, '_, '_> {
             Err(e) => return Err(CompilerInternalError::from_eval_exception(e)),
         };
 
+        let param_tys: Vec<Ty> = params.iter().map(|x| self.annotation_ty(x.ty)).collect();
+        let return_ty = self.annotation_ty(return_type);
+
         // The parameters run in the scope of the parent, so compile them with the outer
         // scope
         let params: Vec<_> = params
@@ -471,10 +473,7 @@ impl Compiler<'_, '_, '_, '_> {
         let params = ParametersCompiled { params, indices };
         let return_type = self.expr_for_type(return_type).map(|t| t.node);
 
-        let ty = Ty::function(
-            params.to_ty_params(),
-            return_type.map_or(Ty::any(), |t| t.as_ty().clone()),
-        );
+        let ty = Ty::function(params.to_ty_params(&param_tys), return_ty);
 
         self.enter_scope(scope_id);
 
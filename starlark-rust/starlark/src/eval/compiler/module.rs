@@ -18,7 +18,9 @@
 //! Compile and evaluate module top-level statements.
 
 use starlark_syntax::eval_exception::EvalException;
+use starlark_syntax::syntax::ast::DefP;
 use starlark_syntax::syntax::ast::LoadP;
+use starlark_syntax::syntax::ast::ParameterP;
 use starlark_syntax::syntax::ast::StmtP;
 use starlark_syntax::syntax::top_level_stmts::top_level_stmts_mut;
 
@@ -39,8 +41,10 @@ use crate::typing::error::InternalError;
 use crate::typing::fill_types_for_lint::ModuleVarTypes;
 use crate::typing::mode::TypecheckMode;
 use crate::typing::typecheck::solve_bindings;
+use crate::typing::typecheck::TypecheckOptions;
 use crate::typing::Ty;
 use crate::typing::TypingOracleCtx;
+use crate::typing::TypingProfile;
 use crate::values::FrozenRef;
 use crate::values::FrozenStringValue;
 use crate::values::Value;
@@ -55,6 +59,14 @@ enum ModuleError {
     TopLevelStmtCountMismatch,
 }
 
+#[derive(Debug, thiserror::Error)]
+enum TypingProfileError {
+    #[error("Parameter `{0}` of function `{1}` has no type annotation (typing profile is `{2:?}`)")]
+    UnannotatedParameter(String, String, TypingProfile),
+    #[error("Public function `{0}` has no return type annotation")]
+    UnannotatedReturnType(String),
+}
+
 impl<'v> Compiler<'v, '_, '_, '_> {
     fn eval_load(&mut self, load: Spanned<&LoadP<CstPayload>>) -> Result<(), EvalException> {
         let name = &load.node.module.node;
@@ -178,6 +190,9 @@ impl<'v> Compiler<'v, '_, '_, '_> {
         };
         let module_var_types = self.mk_module_var_types();
         for top in stmts.iter_mut() {
+            if let StmtP::Def(def) = &top.node {
+                self.check_typing_profile(def)?;
+            }
             if let StmtP::Def(_) = &mut top.node {
                 let BindingsCollect { bindings, .. } = BindingsCollect::collect_one(
                     top,
@@ -186,7 +201,12 @@ impl<'v> Compiler<'v, '_, '_, '_> {
                     &mut Vec::new(),
                 )
                 .map_err(InternalError::into_eval_exception)?;
-                let (errors, ..) = match solve_bindings(bindings, oracle, &module_var_types) {
+                let (errors, ..) = match solve_bindings(
+                    bindings,
+                    oracle,
+                    &module_var_types,
+                    &TypecheckOptions::default(),
+                ) {
                     Ok(x) => x,
                     Err(e) => return Err(e.into_eval_exception()),
                 };
@@ -200,6 +220,48 @@ impl<'v> Compiler<'v, '_, '_, '_> {
         Ok(())
     }
 
+    /// Checks of the typing profile beyond type compatibility.
+    fn check_typing_profile(&self, def: &DefP<CstPayload>) -> Result<(), EvalException> {
+        let profile = self.eval.typing_profile;
+        if profile >= TypingProfile::Strict {
+            for param in &def.params {
+                let (name, ty) = match &param.node {
+                    ParameterP::Normal(name, ty, _)
+                    | ParameterP::Args(name, ty)
+                    | ParameterP::KwArgs(name, ty) => (name, ty),
+                    ParameterP::NoArgs | ParameterP::Slash => continue,
+                };
+                if ty.is_none() {
+                    return Err(EvalException::new_anyhow(
+                        TypingProfileError::UnannotatedParameter(
+                            name.ident.clone(),
+                            def.name.ident.clone(),
+                            profile,
+                        )
+                        .into(),
+                        param.span,
+                        &self.codemap,
+                    ));
+                }
+            }
+        }
+        if profile >= TypingProfile::Pedantic
+            && def.return_type.is_none()
+            && !def.name.ident.starts_with('_')
+        {
+            let error = EvalException::new_anyhow(
+                TypingProfileError::UnannotatedReturnType(def.name.ident.clone()).into(),
+                def.signature_span(),
+                &self.codemap,
+            );
+            self.eval
+                .soft_error_handler
+                .soft_error("typing_pedantic", error.into_error())
+                .map_err(EvalException::new_unknown_span)?;
+        }
+        Ok(())
+    }
+
     fn mk_module_var_types(&self) -> ModuleVarTypes {
         let types = self
             .eval
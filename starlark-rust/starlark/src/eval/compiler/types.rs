@@ -54,6 +54,8 @@ enum TypesError {
     TypeIndexOnNonList,
     #[error("[,] can only be applied to dict or tuple functions in type expression")]
     TypeIndexOnNonDictOrTuple,
+    #[error("Only `typing.Literal` can be indexed with strings in type expression")]
+    TypeIndexWithStringOnNonLiteral,
 }
 
 impl<'v> Compiler<'v, '_, '_, '_> {
@@ -201,6 +203,18 @@ impl<'v> Compiler<'v, '_, '_, '_> {
                     ));
                 }
             }
+            TypeExprUnpackP::Literal(a, values) => {
+                let a = self.eval_path(a.node)?;
+                if !a.ptr_eq(Constants::get().typing_literal.0.to_value()) {
+                    return Err(EvalException::new_anyhow(
+                        TypesError::TypeIndexWithStringOnNonLiteral.into(),
+                        expr.span,
+                        &self.codemap,
+                    ));
+                }
+                let ty = Ty::enum_of(values.into_iter().map(|v| v.node));
+                Ok(TypeCompiled::from_ty(&ty, self.eval.heap()).to_inner())
+            }
             TypeExprUnpackP::Union(xs) => {
                 let xs = xs.into_try_map(|x| self.eval_expr_as_type(x))?;
                 Ok(TypeCompiled::type_any_of(xs, self.eval.heap()).to_inner())
@@ -73,6 +73,7 @@ use crate::stdlib::breakpoint::BreakpointConsole;
 use crate::stdlib::breakpoint::RealBreakpointConsole;
 use crate::stdlib::extra::PrintHandler;
 use crate::stdlib::extra::StderrPrintHandler;
+use crate::typing::TypingProfile;
 use crate::values::function::NativeFunction;
 use crate::values::layout::value_captured::value_captured_get;
 use crate::values::layout::value_captured::FrozenValueCaptured;
@@ -137,6 +138,8 @@ pub struct Evaluator<'v, 'a, 'e> {
     pub(crate) next_gc_level: usize,
     /// Run static typechecking of the module being evaluated.
     pub(crate) static_typechecking: bool,
+    /// How strict static typechecking is.
+    pub(crate) typing_profile: TypingProfile,
     // Profiling or instrumentation enabled.
     pub(crate) profile_or_instrumentation_mode: ProfileOrInstrumentationMode,
     // Used for line profiling
@@ -234,6 +237,7 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
             soft_error_handler: &HardErrorSoftErrorHandler,
             verbose_gc: false,
             static_typechecking: false,
+            typing_profile: TypingProfile::default(),
             max_callstack_size: None,
         }
     }
@@ -261,6 +265,11 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
         self.static_typechecking = enable;
     }
 
+    /// Set how strict static typechecking is. Default is [`TypingProfile::Lenient`].
+    pub fn set_typing_profile(&mut self, profile: TypingProfile) {
+        self.typing_profile = profile;
+    }
+
     /// Set the [`FileLoader`] used to resolve `load()` statements.
     /// A list of all load statements can be obtained through
     /// [`AstModule::loads`](crate::syntax::AstModule::loads).
@@ -23,26 +23,32 @@
 
 //! Types required to support the [`typecheck`](crate::syntax::AstModule::typecheck) function.
 
+pub(crate) mod annotate;
 pub(crate) mod arc_ty;
 pub(crate) mod basic;
+pub(crate) mod batch;
 pub(crate) mod bindings;
 pub(crate) mod call_args;
 pub(crate) mod callable;
 pub(crate) mod callable_param;
 pub(crate) mod ctx;
 pub(crate) mod custom;
+pub(crate) mod deprecated;
 pub(crate) mod error;
 pub(crate) mod fill_types_for_lint;
 pub(crate) mod function;
 pub(crate) mod interface;
 pub(crate) mod mode;
+pub(crate) mod narrow;
 pub(crate) mod oracle;
 pub(crate) mod small_arc_vec;
 pub(crate) mod small_arc_vec_or_static;
 pub(crate) mod starlark_value;
+pub(crate) mod str_enum;
 pub(crate) mod structs;
 pub(crate) mod tuple;
 pub(crate) mod ty;
+pub(crate) mod type_var;
 pub(crate) mod typecheck;
 pub(crate) mod user;
 
@@ -51,23 +57,37 @@ pub mod macro_support;
 #[cfg(test)]
 mod tests;
 
+pub use annotate::AnnotationSuggestion;
 pub use basic::TyBasic;
+pub use batch::typecheck_batch;
+pub use batch::TypecheckResult;
 pub use callable::TyCallable;
 pub use callable_param::ParamIsRequired;
 pub use callable_param::ParamSpec;
 pub use function::TyFunction;
 pub use interface::Interface;
+pub use interface::InterfaceCache;
+pub use interface::InterfaceKey;
+pub use interface::LoadCycle;
+pub use interface::LoadStack;
+pub use mode::TypingProfile;
+pub use oracle::buck::OracleBuck;
 pub use oracle::ctx::TypingOracleCtx;
 pub use oracle::traits::TypingBinOp;
 pub use oracle::traits::TypingUnOp;
 pub use starlark_value::TyStarlarkValue;
+pub use str_enum::TyStrEnum;
 pub use structs::TyStruct;
 pub use ty::Approximation;
 pub use ty::Ty;
 pub use ty::TypeRenderConfig;
 pub use typecheck::AstModuleTypecheck;
+pub use typecheck::AttributeCompletion;
+pub use typecheck::TypeCoverage;
 pub use typecheck::TypeMap;
+pub use typecheck::TypecheckOptions;
 pub use user::TyUser;
+pub use user::TyUserBinOp;
 pub use user::TyUserFields;
 pub use user::TyUserIndex;
 pub use user::TyUserParams;
@@ -0,0 +1,244 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Type annotations suggested from the inferred types.
+//!
+//! For functions without annotations, the typechecker suggests:
+//!
+//! * The type of parameters with a literal default value: `def f(n = 1)` gets `n: int`.
+//! * The return type, from the types of the returned expressions, if they are all known.
+//!
+//! Suggestions are edits which insert the annotation, for tools to apply.
+
+use std::collections::HashMap;
+
+use starlark_syntax::syntax::ast::AstLiteral;
+use starlark_syntax::syntax::ast::DefP;
+use starlark_syntax::syntax::ast::ExprP;
+use starlark_syntax::syntax::ast::ParameterP;
+use starlark_syntax::syntax::ast::StmtP;
+
+use crate::codemap::CodeMap;
+use crate::codemap::FileSpan;
+use crate::codemap::Pos;
+use crate::codemap::Span;
+use crate::eval::compiler::scope::payload::CstExpr;
+use crate::eval::compiler::scope::payload::CstPayload;
+use crate::eval::compiler::scope::payload::CstStmt;
+use crate::eval::compiler::scope::ResolvedIdent;
+use crate::typing::narrow::always_exits;
+use crate::typing::tuple::TyTuple;
+use crate::typing::Ty;
+use crate::typing::TyBasic;
+
+/// An edit adding a type annotation to a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotationSuggestion {
+    /// The span to replace, which is empty: the annotation is inserted at its position.
+    pub span: FileSpan,
+    /// The text to insert, like `: int` or ` -> str`.
+    pub replacement: String,
+}
+
+/// Suggest annotations for the functions of the module, given the types of the expressions.
+pub(crate) fn suggest_annotations(
+    stmts: &[&mut CstStmt],
+    codemap: &CodeMap,
+    types: &HashMap<Span, Ty>,
+) -> Vec<AnnotationSuggestion> {
+    let mut res = Vec::new();
+    for stmt in stmts {
+        visit_defs(stmt, &mut |def| {
+            for param in &def.params {
+                if let ParameterP::Normal(name, None, Some(default)) = &param.node {
+                    if let Some(ty) = literal_ty(default) {
+                        res.push(AnnotationSuggestion {
+                            span: codemap.file_span(Span::new(name.span.end(), name.span.end())),
+                            replacement: format!(": {}", ty),
+                        });
+                    }
+                }
+            }
+            if def.return_type.is_none() {
+                if let (Some(ty), Some(pos)) = (return_ty(def, types), params_end(def, codemap)) {
+                    res.push(AnnotationSuggestion {
+                        span: codemap.file_span(Span::new(pos, pos)),
+                        replacement: format!(" -> {}", ty),
+                    });
+                }
+            }
+        });
+    }
+    res.sort_by_key(|s| s.span.span.begin());
+    res
+}
+
+fn visit_defs<'a>(stmt: &'a CstStmt, f: &mut impl FnMut(&'a DefP<CstPayload>)) {
+    if let StmtP::Def(def) = &stmt.node {
+        f(def);
+    }
+    stmt.visit_stmt(|x| visit_defs(x, f));
+}
+
+/// The type of a literal default value.
+fn literal_ty(x: &CstExpr) -> Option<Ty> {
+    match &x.node {
+        ExprP::Literal(AstLiteral::Int(_)) => Some(Ty::int()),
+        ExprP::Literal(AstLiteral::Float(_)) => Some(Ty::float()),
+        ExprP::Literal(AstLiteral::String(_)) => Some(Ty::string()),
+        ExprP::Identifier(x)
+            if matches!(x.node.payload, Some(ResolvedIdent::Global(_)))
+                && (x.node.ident == "True" || x.node.ident == "False") =>
+        {
+            Some(Ty::bool())
+        }
+        _ => None,
+    }
+}
+
+/// The union of the types of the returned values, if they are all known and can be written as
+/// an annotation.
+fn return_ty(def: &DefP<CstPayload>, types: &HashMap<Span, Ty>) -> Option<Ty> {
+    let mut returns = Vec::new();
+    collect_returns(&def.body, &mut returns);
+    let mut tys = Vec::new();
+    for ret in returns {
+        match ret {
+            Some(x) => tys.push(types.get(&x.span)?.clone()),
+            None => tys.push(Ty::none()),
+        }
+    }
+    if !always_exits(&def.body) {
+        tys.push(Ty::none());
+    }
+    let ty = Ty::unions(tys);
+    is_expressible(&ty).then_some(ty)
+}
+
+fn collect_returns<'a>(stmt: &'a CstStmt, out: &mut Vec<Option<&'a CstExpr>>) {
+    match &stmt.node {
+        StmtP::Return(x) => out.push(x.as_ref()),
+        // Returns of nested functions.
+        StmtP::Def(_) => {}
+        _ => stmt.visit_stmt(|x| collect_returns(x, out)),
+    }
+}
+
+/// The position after the closing parenthesis of the parameters.
+fn params_end(def: &DefP<CstPayload>, codemap: &CodeMap) -> Option<Pos> {
+    let begin = def
+        .params
+        .last()
+        .map_or(def.name.span, |p| p.span)
+        .end()
+        .get() as usize;
+    let source = codemap.source();
+    let mut chars = source.get(begin..)?.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            ')' => return Some(Pos::new((begin + i + 1) as u32)),
+            '#' => {
+                // Skip the comment.
+                chars.by_ref().find(|(_, c)| *c == '\n');
+            }
+            '(' if def.params.is_empty() => {}
+            c if c.is_whitespace() || c == ',' => {}
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// The annotation can be written with the display of the type.
+fn is_expressible(ty: &Ty) -> bool {
+    let simple = [Ty::int(), Ty::float(), Ty::string(), Ty::bool(), Ty::none()];
+    !ty.is_any()
+        && !ty.is_never()
+        && ty.iter_union().iter().all(|basic| match basic {
+            TyBasic::StarlarkValue(_) => simple.contains(&Ty::basic(basic.clone())),
+            TyBasic::List(x) | TyBasic::Set(x) => is_expressible(x),
+            TyBasic::Dict(k, v) => is_expressible(k) && is_expressible(v),
+            TyBasic::Tuple(TyTuple::Elems(xs)) => xs.iter().all(is_expressible),
+            TyBasic::Tuple(TyTuple::Of(x)) => is_expressible(x),
+            _ => false,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::environment::Globals;
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
+    use crate::typing::AstModuleTypecheck;
+
+    #[test]
+    fn test_suggest_annotations() {
+        let code = r#"
+def count(xs, start = 0):
+    return len(xs)
+
+def name(prefix = "lib"):
+    return "lib_{}".format(prefix)
+
+def nothing(flag = True):
+    if flag:
+        return
+    str(flag)
+
+def untyped(x):
+    return x
+
+def annotated(n: int = 1) -> int:
+    return n
+"#;
+        let (errors, typemap, _, _) =
+            AstModule::parse("defs.bzl", code.to_owned(), &Dialect::AllOptionsInternal)
+                .unwrap()
+                .typecheck(&Globals::standard(), &HashMap::new());
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let mut fixed = code.to_owned();
+        for s in typemap.annotation_suggestions().iter().rev() {
+            assert_eq!(s.span.span.begin(), s.span.span.end());
+            let pos = s.span.span.begin().get() as usize;
+            fixed.insert_str(pos, &s.replacement);
+        }
+        assert_eq!(
+            r#"
+def count(xs, start: int = 0) -> int:
+    return len(xs)
+
+def name(prefix: str = "lib") -> str:
+    return "lib_{}".format(prefix)
+
+def nothing(flag: bool = True) -> None:
+    if flag:
+        return
+    str(flag)
+
+def untyped(x):
+    return x
+
+def annotated(n: int = 1) -> int:
+    return n
+"#,
+            fixed
+        );
+    }
+}
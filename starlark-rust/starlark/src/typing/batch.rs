@@ -0,0 +1,285 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Typechecking of many modules concurrently.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::sync::Condvar;
+use std::sync::Mutex;
+
+use dupe::Dupe;
+
+use crate::environment::Globals;
+use crate::syntax::AstModule;
+use crate::typing::ty::Approximation;
+use crate::typing::AstModuleTypecheck;
+use crate::typing::Interface;
+use crate::typing::TypeMap;
+use crate::typing::TypecheckOptions;
+
+/// The result of typechecking a module, as returned by [`AstModuleTypecheck::typecheck`].
+pub type TypecheckResult = (Vec<crate::Error>, TypeMap, Interface, Vec<Approximation>);
+
+struct State {
+    /// Modules ready to be typechecked: the modules of the batch they load are typechecked.
+    ready: VecDeque<usize>,
+    /// Modules queued or typechecked.
+    started: Vec<bool>,
+    /// Number of the modules of the batch loaded by a module which are not typechecked.
+    pending: Vec<usize>,
+    /// Interfaces of the modules of the batch typechecked, by name.
+    interfaces: HashMap<String, Interface>,
+    /// Modules being typechecked.
+    running: usize,
+    /// Modules not typechecked yet.
+    remaining: usize,
+}
+
+/// Typecheck a batch of modules concurrently, each after the modules of the batch it loads.
+///
+/// Modules are named by the string other modules load them with. The interfaces of the
+/// modules loaded from outside the batch are in `loads`. Up to `threads` workers take the
+/// modules whose loads are typechecked from a shared queue, so modules are typechecked as soon
+/// as they can be, whichever worker is free. The interfaces of typechecked modules are shared
+/// by the modules loading them, without copying their types.
+///
+/// Modules of the batch which load each other in a cycle are typechecked without the
+/// interfaces of the modules of the cycle not typechecked yet, like modules missing from
+/// `loads`.
+///
+/// Results are in the order of `modules`.
+pub fn typecheck_batch(
+    modules: Vec<(String, AstModule)>,
+    globals: &Globals,
+    loads: &HashMap<String, Interface>,
+    options: &TypecheckOptions,
+    threads: usize,
+) -> Vec<(String, TypecheckResult)> {
+    let indices: HashMap<&str, usize> = modules
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| (name.as_str(), i))
+        .collect();
+    // The names of the modules each module loads, and the modules of the batch loading them.
+    let mut module_loads: Vec<Vec<String>> = Vec::with_capacity(modules.len());
+    let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); modules.len()];
+    let mut pending = vec![0; modules.len()];
+    for (i, (_, ast)) in modules.iter().enumerate() {
+        let mut names = Vec::new();
+        let mut loaded = HashSet::new();
+        for load in ast.loads() {
+            if let Some(&dep) = indices.get(load.module_id) {
+                if dep != i && loaded.insert(dep) {
+                    dependents[dep].push(i);
+                    pending[i] += 1;
+                }
+            }
+            names.push(load.module_id.to_owned());
+        }
+        module_loads.push(names);
+    }
+    drop(indices);
+
+    let names: Vec<String> = modules.iter().map(|(name, _)| name.clone()).collect();
+    let asts: Vec<Mutex<Option<AstModule>>> = modules
+        .into_iter()
+        .map(|(_, ast)| Mutex::new(Some(ast)))
+        .collect();
+    let results: Vec<Mutex<Option<TypecheckResult>>> =
+        asts.iter().map(|_| Mutex::new(None)).collect();
+
+    let ready: VecDeque<usize> = (0..asts.len()).filter(|i| pending[*i] == 0).collect();
+    let mut started = vec![false; asts.len()];
+    for i in &ready {
+        started[*i] = true;
+    }
+    let state = Mutex::new(State {
+        ready,
+        started,
+        pending,
+        interfaces: HashMap::new(),
+        running: 0,
+        remaining: asts.len(),
+    });
+    let changed = Condvar::new();
+
+    let worker = || {
+        loop {
+            let (i, loaded) = {
+                let mut state = state.lock().unwrap();
+                loop {
+                    if let Some(i) = state.ready.pop_front() {
+                        state.running += 1;
+                        let loaded: HashMap<String, Interface> = module_loads[i]
+                            .iter()
+                            .filter_map(|name| {
+                                let interface =
+                                    state.interfaces.get(name).or_else(|| loads.get(name))?;
+                                Some((name.clone(), interface.dupe()))
+                            })
+                            .collect();
+                        break (i, loaded);
+                    }
+                    if state.remaining == 0 {
+                        return;
+                    }
+                    if state.running == 0 {
+                        // The remaining modules load each other in cycles: start one of them.
+                        if let Some(i) = state.started.iter().position(|s| !s) {
+                            state.started[i] = true;
+                            state.ready.push_back(i);
+                            continue;
+                        }
+                    }
+                    state = changed.wait(state).unwrap();
+                }
+            };
+
+            let ast = asts[i].lock().unwrap().take().unwrap();
+            let result = ast.typecheck_with_options(globals, &loaded, options);
+            let interface = result.2.dupe();
+            *results[i].lock().unwrap() = Some(result);
+
+            let mut state = state.lock().unwrap();
+            state.interfaces.insert(names[i].clone(), interface);
+            state.running -= 1;
+            state.remaining -= 1;
+            for &dependent in &dependents[i] {
+                state.pending[dependent] -= 1;
+                if state.pending[dependent] == 0 && !state.started[dependent] {
+                    state.started[dependent] = true;
+                    state.ready.push_back(dependent);
+                }
+            }
+            changed.notify_all();
+        }
+    };
+
+    let threads = threads.clamp(1, asts.len().max(1));
+    std::thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(&worker);
+        }
+    });
+
+    names
+        .into_iter()
+        .zip(results)
+        .map(|(name, result)| (name, result.into_inner().unwrap().unwrap()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::environment::Globals;
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
+    use crate::typing::batch::typecheck_batch;
+    use crate::typing::TypecheckOptions;
+
+    fn module(name: &str, code: &str) -> (String, AstModule) {
+        (
+            name.to_owned(),
+            AstModule::parse(name, code.to_owned(), &Dialect::AllOptionsInternal).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_typecheck_batch() {
+        // Dependents first, so that they wait for the modules they load.
+        let modules = vec![
+            module(
+                "c.bzl",
+                r#"
+load("b.bzl", "g")
+def h():
+    g().upper()
+"#,
+            ),
+            module(
+                "b.bzl",
+                r#"
+load("a.bzl", "f")
+def g() -> int:
+    return f("x")
+"#,
+            ),
+            module(
+                "a.bzl",
+                r#"
+def f(x: int) -> int:
+    return x
+"#,
+            ),
+            module("d.bzl", "D = 1"),
+        ];
+        let results = typecheck_batch(
+            modules,
+            &Globals::standard(),
+            &HashMap::new(),
+            &TypecheckOptions::default(),
+            4,
+        );
+        let errors: Vec<(String, String)> = results
+            .iter()
+            .flat_map(|(name, (errors, _, _, _))| {
+                errors.iter().map(|e| (name.clone(), format!("{:#}", e)))
+            })
+            .collect();
+        assert_eq!(
+            vec!["c.bzl", "b.bzl", "a.bzl", "d.bzl"],
+            results.iter().map(|(name, _)| name).collect::<Vec<_>>()
+        );
+        assert_eq!(2, errors.len(), "{:?}", errors);
+        assert_eq!("c.bzl", errors[0].0);
+        assert!(
+            errors[0]
+                .1
+                .contains("The attribute `upper` is not available on the type `int`"),
+            "{:?}",
+            errors
+        );
+        assert_eq!("b.bzl", errors[1].0);
+        assert!(
+            errors[1].1.contains("Expected type `int` but got `str`"),
+            "{:?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn test_typecheck_batch_cycle() {
+        let modules = vec![
+            module("x.bzl", "load(\"y.bzl\", \"Y\")\nX = 1"),
+            module("y.bzl", "load(\"x.bzl\", \"X\")\nY = 1"),
+            module("z.bzl", "load(\"x.bzl\", \"X\")\nZ = X"),
+        ];
+        let results = typecheck_batch(
+            modules,
+            &Globals::standard(),
+            &HashMap::new(),
+            &TypecheckOptions::default(),
+            2,
+        );
+        assert_eq!(3, results.len());
+        assert!(results[2].1.2.get("Z").is_some());
+    }
+}
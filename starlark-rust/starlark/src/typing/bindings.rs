@@ -16,12 +16,16 @@
  */
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 use dupe::Dupe;
 use starlark_map::small_map::SmallMap;
 use starlark_syntax::syntax::ast::AssignOp;
 use starlark_syntax::syntax::ast::AssignP;
 use starlark_syntax::syntax::ast::AssignTargetP;
+use starlark_syntax::syntax::ast::AstLiteral;
+use starlark_syntax::syntax::ast::AstParameterP;
+use starlark_syntax::syntax::ast::BinOp;
 use starlark_syntax::syntax::ast::ClauseP;
 use starlark_syntax::syntax::ast::DefP;
 use starlark_syntax::syntax::ast::ExprP;
@@ -33,7 +37,12 @@ use starlark_syntax::syntax::def::DefParamKind;
 use starlark_syntax::syntax::def::DefParams;
 use starlark_syntax::syntax::def::DefRegularParamMode;
 use starlark_syntax::syntax::uniplate::Visit;
+use thiserror::Error;
 
+use crate::analysis::types::LintT;
+use crate::analysis::types::LintWarning;
+use crate::analysis::EvalSeverity;
+use crate::analysis::Lint;
 use crate::codemap::CodeMap;
 use crate::codemap::Span;
 use crate::codemap::Spanned;
@@ -47,8 +56,10 @@ use crate::eval::compiler::scope::BindingId;
 use crate::eval::compiler::scope::ResolvedIdent;
 use crate::typing::arc_ty::ArcTy;
 use crate::typing::callable_param::ParamIsRequired;
+use crate::typing::deprecated::visit_idents;
 use crate::typing::error::InternalError;
 use crate::typing::mode::TypecheckMode;
+use crate::typing::narrow::Narrowing;
 use crate::typing::tuple::TyTuple;
 use crate::typing::ty::Approximation;
 use crate::typing::ty::Ty;
@@ -83,6 +94,79 @@ impl<'a> BindExpr<'a> {
     }
 }
 
+/// Chain of `if`/`elif` without `else` comparing the same variable to string literals:
+///
+/// ```python
+/// if mode == "debug": ...
+/// elif mode in ("release", "opt"): ...
+/// ```
+pub(crate) struct MatchChain<'a> {
+    pub(crate) span: Span,
+    /// The variable in the first condition.
+    pub(crate) scrutinee: &'a CstExpr,
+    /// Values handled by the branches.
+    pub(crate) values: Vec<&'a str>,
+}
+
+impl<'a> MatchChain<'a> {
+    /// Record the values the variable is compared to in the condition, which is one of
+    /// `x == "a"`, `"a" == x`, `x in ["a", "b"]`, or an `or` of those.
+    fn arm(&mut self, cond: &'a CstExpr) -> bool {
+        match &cond.node {
+            ExprP::Op(lhs, BinOp::Or, rhs) => self.arm(lhs) && self.arm(rhs),
+            ExprP::Op(lhs, BinOp::Equal, rhs) => match (string_literal(lhs), string_literal(rhs)) {
+                (None, Some(v)) if self.is_scrutinee(lhs) => {
+                    self.values.push(v);
+                    true
+                }
+                (Some(v), None) if self.is_scrutinee(rhs) => {
+                    self.values.push(v);
+                    true
+                }
+                _ => false,
+            },
+            ExprP::Op(lhs, BinOp::In, rhs) if self.is_scrutinee(lhs) => match &rhs.node {
+                ExprP::List(xs) | ExprP::Tuple(xs) => xs.iter().all(|x| match string_literal(x) {
+                    Some(v) => {
+                        self.values.push(v);
+                        true
+                    }
+                    None => false,
+                }),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn is_scrutinee(&self, x: &CstExpr) -> bool {
+        match (&self.scrutinee.node, &x.node) {
+            (ExprP::Identifier(a), ExprP::Identifier(b)) => a.ident == b.ident,
+            _ => false,
+        }
+    }
+
+    /// The first variable compared to a string literal in the condition.
+    fn first_scrutinee(cond: &'a CstExpr) -> Option<&'a CstExpr> {
+        match &cond.node {
+            ExprP::Op(lhs, BinOp::Or, _) => Self::first_scrutinee(lhs),
+            ExprP::Op(lhs, BinOp::Equal | BinOp::In, rhs) => match (&lhs.node, &rhs.node) {
+                (ExprP::Identifier(_), _) => Some(&**lhs),
+                (_, ExprP::Identifier(_)) => Some(&**rhs),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+fn string_literal(x: &CstExpr) -> Option<&str> {
+    match &x.node {
+        ExprP::Literal(AstLiteral::String(s)) => Some(&s.node),
+        _ => None,
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct Bindings<'a> {
     pub(crate) expressions: SmallMap<BindingId, Vec<BindExpr<'a>>>,
@@ -98,11 +182,21 @@ pub(crate) struct Bindings<'a> {
     /// ```
     pub(crate) check: Vec<&'a CstExpr>,
     pub(crate) check_type: Vec<(Span, Option<&'a CstExpr>, Ty)>,
+    /// Checked for exhaustiveness if the variable is a string enum.
+    pub(crate) match_chains: Vec<MatchChain<'a>>,
+    /// Types of variables narrowed by conditions.
+    pub(crate) narrowings: Vec<Narrowing>,
+    /// The `ctx` parameter of a documented rule implementation, and the type of its `attrs`.
+    pub(crate) rule_ctx: Option<(BindingId, Ty)>,
+    /// Signatures of lambdas, by span of the lambda.
+    pub(crate) lambdas: HashMap<Span, ParamSpec>,
 }
 
 pub(crate) struct BindingsCollect<'a, 'b> {
     pub(crate) bindings: Bindings<'a>,
     pub(crate) approximations: &'b mut Vec<Approximation>,
+    /// Spans of `elif` statements of already visited `if` chains.
+    elifs: HashSet<Span>,
 }
 
 impl<'a, 'b> BindingsCollect<'a, 'b> {
@@ -118,9 +212,16 @@ impl<'a, 'b> BindingsCollect<'a, 'b> {
         let mut res = BindingsCollect {
             bindings: Bindings::default(),
             approximations,
+            elifs: HashSet::new(),
         };
 
         res.visit(Visit::Stmt(x), &Ty::any(), typecheck_mode, codemap)?;
+        let expressions = &res.bindings.expressions;
+        res.bindings.narrowings.retain(|n| {
+            expressions
+                .get(&n.binding())
+                .map_or(true, |exprs| !exprs.iter().any(|e| n.assigned_at(e.span())))
+        });
         Ok(res)
     }
 
@@ -218,6 +319,24 @@ impl<'a, 'b> BindingsCollect<'a, 'b> {
             return_type,
             ..
         } = def;
+        let params2 = self.visit_params(params, def.signature_span(), typecheck_mode, codemap)?;
+        let ret_ty = Self::resolve_ty_opt(return_type.as_deref(), typecheck_mode, codemap)?;
+        self.bindings.types.insert(
+            name.resolved_binding_id(codemap)?,
+            Ty::function(params2, ret_ty.clone()),
+        );
+        def.visit_children_err(|x| self.visit(x, &ret_ty, typecheck_mode, codemap))?;
+        Ok(())
+    }
+
+    /// Record the types of the parameters of a `def` or a `lambda`, and return its signature.
+    fn visit_params(
+        &mut self,
+        params: &'a [AstParameterP<CstPayload>],
+        signature_span: Span,
+        typecheck_mode: TypecheckMode,
+        codemap: &CodeMap,
+    ) -> Result<ParamSpec, InternalError> {
         let DefParams { params, indices: _ } =
             DefParams::unpack(params, codemap).map_err(InternalError::from_eval_exception)?;
 
@@ -276,15 +395,51 @@ impl<'a, 'b> BindingsCollect<'a, 'b> {
                     .insert(name.resolved_binding_id(codemap)?, ty);
             }
         }
-        let params2 = ParamSpec::new_parts(pos_only, pos_or_named, args, named_only, kwargs)
-            .map_err(|e| InternalError::from_error(e, def.signature_span(), codemap))?;
-        let ret_ty = Self::resolve_ty_opt(return_type.as_deref(), typecheck_mode, codemap)?;
-        self.bindings.types.insert(
-            name.resolved_binding_id(codemap)?,
-            Ty::function(params2, ret_ty.clone()),
-        );
-        def.visit_children_err(|x| self.visit(x, &ret_ty, typecheck_mode, codemap))?;
-        Ok(())
+        ParamSpec::new_parts(pos_only, pos_or_named, args, named_only, kwargs)
+            .map_err(|e| InternalError::from_error(e, signature_span, codemap))
+    }
+
+    fn visit_if_chain(&mut self, stmt: &'a CstStmt) {
+        if self.elifs.contains(&stmt.span) {
+            return;
+        }
+        let span = stmt.span;
+        let mut conds = Vec::new();
+        let mut stmt = stmt;
+        let has_else = loop {
+            match &stmt.node {
+                StmtP::If(c, _) => {
+                    conds.push(c);
+                    break false;
+                }
+                StmtP::IfElse(c, branches) => {
+                    conds.push(c);
+                    match &branches.1.node {
+                        StmtP::If(..) | StmtP::IfElse(..) => {
+                            stmt = &branches.1;
+                            self.elifs.insert(stmt.span);
+                        }
+                        _ => break true,
+                    }
+                }
+                _ => break true,
+            }
+        };
+        // A single `if` is not a chain.
+        if has_else || conds.len() < 2 {
+            return;
+        }
+        let Some(scrutinee) = MatchChain::first_scrutinee(conds[0]) else {
+            return;
+        };
+        let mut chain = MatchChain {
+            span,
+            scrutinee,
+            values: Vec::new(),
+        };
+        if conds.into_iter().all(|c| chain.arm(c)) {
+            self.bindings.match_chains.push(chain);
+        }
     }
 
     fn visit(
@@ -360,8 +515,21 @@ impl<'a, 'b> BindingsCollect<'a, 'b> {
 
                     self.bindings.check.push(x)
                 }
-                StmtP::If(x, _) => self.bindings.check.push(x),
-                StmtP::IfElse(x, _) => self.bindings.check.push(x),
+                StmtP::If(c, body) => {
+                    self.bindings.check.push(c);
+                    self.visit_if_chain(x);
+                    Narrowing::collect(c, true, body.span, &mut self.bindings.narrowings);
+                }
+                StmtP::IfElse(c, branches) => {
+                    self.bindings.check.push(c);
+                    self.visit_if_chain(x);
+                    let (then_block, else_block) = &**branches;
+                    Narrowing::collect(c, true, then_block.span, &mut self.bindings.narrowings);
+                    Narrowing::collect(c, false, else_block.span, &mut self.bindings.narrowings);
+                }
+                StmtP::Statements(xs) => {
+                    Narrowing::collect_early_exits(xs, &mut self.bindings.narrowings);
+                }
                 _ => {}
             },
             Visit::Expr(x) => match &**x {
@@ -383,6 +551,30 @@ impl<'a, 'b> BindingsCollect<'a, 'b> {
                         )?
                     }
                 }
+                ExprP::Op(lhs, op @ (BinOp::And | BinOp::Or), rhs) => {
+                    // The right operand is only evaluated if the left one is true for `and`,
+                    // and false for `or`.
+                    Narrowing::collect(
+                        lhs,
+                        *op == BinOp::And,
+                        rhs.span,
+                        &mut self.bindings.narrowings,
+                    );
+                }
+                ExprP::If(c_t_f) => {
+                    let (c, t, f) = &**c_t_f;
+                    Narrowing::collect(c, true, t.span, &mut self.bindings.narrowings);
+                    Narrowing::collect(c, false, f.span, &mut self.bindings.narrowings);
+                }
+                ExprP::Lambda(lambda) => {
+                    let params = self.visit_params(
+                        &lambda.params,
+                        lambda.signature_span(),
+                        typecheck_mode,
+                        codemap,
+                    )?;
+                    self.bindings.lambdas.insert(x.span, params);
+                }
                 _ => {}
             },
         }
@@ -390,3 +582,154 @@ impl<'a, 'b> BindingsCollect<'a, 'b> {
         Ok(())
     }
 }
+
+#[derive(Error, Debug)]
+#[error("Private {kind} `{name}` is never used")]
+pub(crate) struct UnusedPrivate {
+    kind: &'static str,
+    name: String,
+}
+
+impl LintWarning for UnusedPrivate {
+    fn severity(&self) -> EvalSeverity {
+        EvalSeverity::Warning
+    }
+
+    fn short_name(&self) -> &'static str {
+        "unused-private"
+    }
+}
+
+/// A `# @allow-unused` comment.
+fn allows_unused(line: &str) -> bool {
+    line.trim()
+        .strip_prefix('#')
+        .is_some_and(|x| x.trim() == "@allow-unused")
+}
+
+/// Warnings for the top-level private functions and variables (those named `_foo`) which are
+/// never referenced in the module.
+///
+/// Private symbols cannot be loaded by other modules, so those are dead code. References in the
+/// body of a function to itself do not count as uses. The warning is suppressed by an
+/// `# @allow-unused` comment on the line before the definition.
+pub(crate) fn unused_private(stmts: &[&mut CstStmt], codemap: &CodeMap) -> Vec<Lint> {
+    // The private bindings, with the span of their name, and of the body of functions.
+    let mut private: SmallMap<BindingId, (&str, &'static str, Span, Option<Span>)> =
+        SmallMap::new();
+    for stmt in stmts {
+        let (name, kind, body) = match &stmt.node {
+            StmtP::Def(def) => (&def.name, "function", Some(stmt.span)),
+            StmtP::Assign(AssignP { lhs, .. }) => match &lhs.node {
+                AssignTargetP::Identifier(x) => (x, "variable", None),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let (Some(binding), true) = (name.payload, is_private(&name.ident)) else {
+            continue;
+        };
+        let line = codemap.find_line(stmt.span.begin());
+        if line
+            .checked_sub(1)
+            .is_some_and(|line| allows_unused(codemap.source_line(line)))
+        {
+            continue;
+        }
+        // Reassigned variables are reported at their first assignment.
+        if !private.contains_key(&binding) {
+            private.insert(binding, (&name.ident, kind, name.span, body));
+        }
+    }
+    if private.is_empty() {
+        return Vec::new();
+    }
+
+    let mut used = HashSet::new();
+    for stmt in stmts {
+        stmt.visit_expr(|x| {
+            visit_idents(x, &mut |ident| {
+                if let Some(ResolvedIdent::Slot(_, binding)) = &ident.node.payload {
+                    if let Some((_, _, _, body)) = private.get(binding) {
+                        if !body.is_some_and(|body| body.contains(ident.span.begin())) {
+                            used.insert(*binding);
+                        }
+                    }
+                }
+            })
+        });
+    }
+    private
+        .into_iter()
+        .filter(|(binding, _)| !used.contains(binding))
+        .map(|(_, (name, kind, span, _))| {
+            LintT::new(
+                codemap,
+                span,
+                UnusedPrivate {
+                    kind,
+                    name: name.to_owned(),
+                },
+            )
+            .erase()
+        })
+        .collect()
+}
+
+/// Named `_foo`, but not the `_` placeholder.
+fn is_private(name: &str) -> bool {
+    name.starts_with('_') && name != "_"
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::environment::Globals;
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
+    use crate::typing::AstModuleTypecheck;
+
+    #[test]
+    fn test_unused_private() {
+        let code = r#"
+def _used():
+    pass
+
+def _recursive(x):
+    return _recursive(x - 1) if x else 0
+
+def _unused():
+    _used()
+
+# @allow-unused
+def _kept():
+    pass
+
+_CONSTANT = 1
+_UNUSED_CONSTANT = 2
+_ = _CONSTANT
+
+def public():
+    return _CONSTANT
+"#;
+        let (errors, typemap, _, _) =
+            AstModule::parse("rules.bzl", code.to_owned(), &Dialect::AllOptionsInternal)
+                .unwrap()
+                .typecheck(&Globals::standard(), &HashMap::new());
+        assert!(errors.is_empty());
+        let warnings = typemap
+            .unused_private()
+            .iter()
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "rules.bzl:5:5-15: Private function `_recursive` is never used",
+                "rules.bzl:8:5-12: Private function `_unused` is never used",
+                "rules.bzl:16:1-17: Private variable `_UNUSED_CONSTANT` is never used",
+            ],
+            warnings
+        );
+    }
+}
@@ -16,8 +16,12 @@
  */
 
 use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::fmt::Debug;
 
+use dupe::Dupe;
+use either::Either;
 use starlark_map::unordered_map::UnorderedMap;
 use starlark_syntax::slice_vec_ext::SliceExt;
 use starlark_syntax::syntax::ast::AssignOp;
@@ -42,15 +46,23 @@ use crate::eval::compiler::scope::Slot;
 use crate::typing::basic::TyBasic;
 use crate::typing::bindings::BindExpr;
 use crate::typing::call_args::TyCallArgs;
+use crate::typing::callable::TyCallable;
+use crate::typing::callable_param::Param;
+use crate::typing::callable_param::ParamMode;
 use crate::typing::error::InternalError;
 use crate::typing::error::TypingError;
 use crate::typing::error::TypingOrInternalError;
 use crate::typing::fill_types_for_lint::ModuleVarTypes;
+use crate::typing::narrow::Narrowing;
+use crate::typing::oracle::buck::OracleBuck;
 use crate::typing::oracle::ctx::TypingOracleCtx;
+use crate::typing::oracle::dependency::TyDependency;
 use crate::typing::oracle::traits::TypingBinOp;
 use crate::typing::oracle::traits::TypingUnOp;
+use crate::typing::str_enum::TyStrEnum;
 use crate::typing::ty::Approximation;
 use crate::typing::ty::Ty;
+use crate::typing::ParamSpec;
 
 pub(crate) struct TypingContext<'a> {
     pub(crate) oracle: TypingOracleCtx<'a>,
@@ -60,6 +72,20 @@ pub(crate) struct TypingContext<'a> {
     pub(crate) approximoations: RefCell<Vec<Approximation>>,
     pub(crate) types: UnorderedMap<BindingId, Ty>,
     pub(crate) module_var_types: &'a ModuleVarTypes,
+    /// Types of the objects of attribute accesses, keyed by the span of the attribute name.
+    pub(crate) attr_objects: RefCell<HashMap<Span, Ty>>,
+    /// Types of the expressions, keyed by their span.
+    pub(crate) expr_types: RefCell<HashMap<Span, Ty>>,
+    /// Arguments of type `Any` passed to annotated parameters, if they are reported.
+    pub(crate) any_args: Option<RefCell<BTreeMap<Span, String>>>,
+    /// Types of variables narrowed by conditions.
+    pub(crate) narrowings: &'a [Narrowing],
+    /// Types of documented buck2 globals, members and rule attributes.
+    pub(crate) buck: Option<&'a OracleBuck>,
+    /// The `ctx` parameter of a documented rule implementation, and the type of its `attrs`.
+    pub(crate) rule_ctx: Option<(BindingId, Ty)>,
+    /// Signatures of lambdas, by span of the lambda.
+    pub(crate) lambdas: &'a HashMap<Span, ParamSpec>,
 }
 
 impl TypingContext<'_> {
@@ -121,9 +147,24 @@ impl TypingContext<'_> {
     }
 
     fn expr_dot(&self, ty: &Ty, attr: &str, span: Span) -> Ty {
+        if let Some(ty) = self.buck.and_then(|buck| buck.attribute(ty, attr)) {
+            return ty;
+        }
         self.result_to_ty(self.oracle.expr_dot(span, ty, attr))
     }
 
+    /// The type of `ctx.attrs` in a documented rule implementation.
+    fn rule_attrs(&self, object: &CstExpr, attr: &str) -> Option<Ty> {
+        let (ctx, attrs) = self.rule_ctx.as_ref()?;
+        match &object.node {
+            ExprP::Identifier(x) if attr == "attrs" => match &x.node.payload {
+                Some(ResolvedIdent::Slot(_, binding)) if binding == ctx => Some(attrs.clone()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     fn expr_index(
         &self,
         span: Span,
@@ -145,10 +186,45 @@ impl TypingContext<'_> {
             }
         }
 
+        self.check_dependency_provider(span, &array_ty, index);
         let index = self.expression_type_spanned(index)?;
+        self.check_str_enum_dict_keys(span, array, &index.node);
         self.result_to_ty_with_internal_error(self.oracle.expr_index(span, array_ty, index))
     }
 
+    /// `dep[SomeInfo]` on the value of a dependency attribute must use a provider the attribute
+    /// requires, like `attrs.dep(providers = [SomeInfo])`.
+    fn check_dependency_provider(&self, span: Span, dep: &Ty, index: &CstExpr) {
+        let (Some(dep), ExprP::Identifier(provider)) = (TyDependency::from_ty(dep), &index.node)
+        else {
+            return;
+        };
+        if let Err(e) = dep.check_provider(&provider.node.ident) {
+            self.errors.borrow_mut().push(self.oracle.mk_error(span, e));
+        }
+    }
+
+    /// A dict literal with string keys indexed with a string enum, like
+    /// `{"debug": "-O0", "release": "-O2"}[mode]`, must have a key for every value.
+    fn check_str_enum_dict_keys(&self, span: Span, array: &CstExpr, index: &Ty) {
+        let (ExprP::Dict(entries), Some(str_enum)) = (&array.node, TyStrEnum::from_ty(index))
+        else {
+            return;
+        };
+        let keys: Option<Vec<&str>> = entries
+            .iter()
+            .map(|(k, _)| match &k.node {
+                ExprP::Literal(AstLiteral::String(s)) => Some(s.node.as_str()),
+                _ => None,
+            })
+            .collect();
+        if let Some(keys) = keys {
+            if let Err(e) = str_enum.check_dict_keys(keys) {
+                self.errors.borrow_mut().push(self.oracle.mk_error(span, e));
+            }
+        }
+    }
+
     fn expression_un_op(
         &self,
         span: Span,
@@ -293,9 +369,24 @@ impl TypingContext<'_> {
         op: BinOp,
         rhs: &CstExpr,
     ) -> Result<Ty, InternalError> {
-        let lhs = self.expression_type_spanned(lhs)?;
-        let rhs = self.expression_type_spanned(rhs)?;
-        self.result_to_ty_with_internal_error(self.oracle.expr_bin_op(span, lhs, op, rhs))
+        let lhs_ty = self.expression_type_spanned(lhs)?;
+        let rhs_ty = self.expression_type_spanned(rhs)?;
+        if let BinOp::Equal | BinOp::NotEqual = op {
+            self.check_str_enum_comparison(span, &lhs_ty.node, rhs, op == BinOp::Equal);
+            self.check_str_enum_comparison(span, &rhs_ty.node, lhs, op == BinOp::Equal);
+        }
+        self.result_to_ty_with_internal_error(self.oracle.expr_bin_op(span, lhs_ty, op, rhs_ty))
+    }
+
+    /// Comparison of a string enum with a string literal not in the enum.
+    fn check_str_enum_comparison(&self, span: Span, ty: &Ty, other: &CstExpr, eq: bool) {
+        if let (Some(str_enum), ExprP::Literal(AstLiteral::String(value))) =
+            (TyStrEnum::from_ty(ty), &other.node)
+        {
+            if let Err(e) = str_enum.check_comparison(&value.node, eq) {
+                self.errors.borrow_mut().push(self.oracle.mk_error(span, e));
+            }
+        }
     }
 
     fn expr_call(
@@ -314,8 +405,14 @@ impl TypingContext<'_> {
             star_star,
         } = args;
 
+        // String literal arguments, checked against string enum parameters.
+        let mut str_args: Vec<(Span, Either<usize, &str>, &str)> = Vec::new();
+
         let mut pos_ty: Vec<Spanned<Ty>> = Vec::new();
-        for pos in pos {
+        for (i, pos) in pos.into_iter().enumerate() {
+            if let ExprP::Literal(AstLiteral::String(s)) = &pos.node.expr().node {
+                str_args.push((pos.span, Either::Left(i), &s.node));
+            }
             pos_ty.push(Spanned {
                 span: pos.span,
                 node: self.expression_type(&pos.node.expr())?,
@@ -331,6 +428,9 @@ impl TypingContext<'_> {
                     self.oracle.codemap,
                 ));
             };
+            if let ExprP::Literal(AstLiteral::String(s)) = &named.node.expr().node {
+                str_args.push((named.span, Either::Right(name), &s.node));
+            }
             named_ty.push(Spanned {
                 span: named.span,
                 node: (name, self.expression_type(&named.node.expr())?),
@@ -361,11 +461,87 @@ impl TypingContext<'_> {
         };
 
         let f_ty = self.expression_type(f)?;
+        self.check_str_enum_args(&f_ty, &str_args);
+        self.record_any_args(&f_ty, &args_ty);
         // If we can't resolve the types of the arguments, we can't validate the call,
         // but we still know the type of the result since the args don't impact that
         self.validate_call(&f_ty, &args_ty, span)
     }
 
+    /// The callable, if the function type is a single callable.
+    fn single_callable(f: &Ty) -> Option<TyCallable> {
+        match f.iter_union() {
+            [TyBasic::Callable(c)] => Some(c.dupe()),
+            [TyBasic::Custom(c)] => c.0.as_callable_dyn(),
+            _ => None,
+        }
+    }
+
+    /// The parameter an argument, given by position or by name, is bound to.
+    /// Arguments bound to `*args` or `**kwargs` are not considered.
+    fn param_for_arg<'p>(params: &'p [Param], arg: Either<usize, &str>) -> Option<&'p Param> {
+        match arg {
+            Either::Left(i) => params
+                .get(i)
+                .filter(|p| matches!(p.mode, ParamMode::PosOnly(_) | ParamMode::PosOrName(..))),
+            Either::Right(name) => params.iter().find(|p| p.name() == Some(name)),
+        }
+    }
+
+    /// Record the arguments of type `Any` passed to annotated parameters, if requested.
+    fn record_any_args(&self, f: &Ty, args: &TyCallArgs) {
+        let Some(any_args) = &self.any_args else {
+            return;
+        };
+        let Some(callable) = Self::single_callable(f) else {
+            return;
+        };
+        let params = callable.params().params();
+        let pos = args
+            .pos
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| (Either::Left(i), arg.span, &arg.node));
+        let named = args
+            .named
+            .iter()
+            .map(|arg| (Either::Right(arg.node.0), arg.span, &arg.node.1));
+        for (arg, span, ty) in pos.chain(named) {
+            if !ty.is_any() {
+                continue;
+            }
+            if let Some(param) = Self::param_for_arg(params, arg).filter(|p| !p.ty.is_any()) {
+                any_args.borrow_mut().insert(
+                    span,
+                    format!(
+                        "`{}: {}` at {}",
+                        param.name_display(),
+                        param.ty,
+                        self.oracle.codemap.file_span(span)
+                    ),
+                );
+            }
+        }
+    }
+
+    /// String literals passed to string enum parameters must be values of the enum.
+    fn check_str_enum_args(&self, f: &Ty, str_args: &[(Span, Either<usize, &str>, &str)]) {
+        let Some(callable) = Self::single_callable(f) else {
+            return;
+        };
+        let params = callable.params().params();
+        for (span, arg, value) in str_args {
+            let param = Self::param_for_arg(params, *arg);
+            if let Some(str_enum) = param.and_then(|p| TyStrEnum::from_ty(&p.ty)) {
+                if let Err(e) = str_enum.check_value(value) {
+                    self.errors
+                        .borrow_mut()
+                        .push(self.oracle.mk_error(*span, e));
+                }
+            }
+        }
+    }
+
     fn expr_slice(
         &self,
         span: Span,
@@ -380,8 +556,8 @@ impl TypingContext<'_> {
         Ok(self.result_to_ty(self.oracle.expr_slice(span, self.expression_type(x)?)))
     }
 
-    fn expr_ident(&self, x: &CstIdent) -> Ty {
-        match &x.node.payload {
+    fn expr_ident(&self, x: &CstIdent) -> Result<Ty, InternalError> {
+        let ty = match &x.node.payload {
             Some(ResolvedIdent::Slot(Slot::Module(module_slot_id), _)) => self
                 .module_var_types
                 .types
@@ -402,16 +578,45 @@ impl TypingContext<'_> {
                 // All identifiers must be resolved at this point,
                 // but we don't stop after scope resolution error,
                 // so this code is reachable.
-                Ty::any()
+                self.buck
+                    .and_then(|buck| buck.global(&x.node.ident))
+                    .unwrap_or_else(Ty::any)
             }
+        };
+        match &x.node.payload {
+            Some(ResolvedIdent::Slot(_, binding)) => self.narrow(x.span, *binding, ty),
+            _ => Ok(ty),
         }
     }
 
+    /// The type of a variable, narrowed by the conditions guarding its use.
+    fn narrow(&self, span: Span, binding: BindingId, mut ty: Ty) -> Result<Ty, InternalError> {
+        for narrowing in self.narrowings {
+            if narrowing.applies(binding, span) {
+                ty = narrowing.apply(&ty, &self.oracle)?;
+            }
+        }
+        Ok(ty)
+    }
+
     pub(crate) fn expression_type(&self, x: &CstExpr) -> Result<Ty, InternalError> {
+        let ty = self.expression_type_impl(x)?;
+        self.expr_types.borrow_mut().insert(x.span, ty.dupe());
+        Ok(ty)
+    }
+
+    fn expression_type_impl(&self, x: &CstExpr) -> Result<Ty, InternalError> {
         let span = x.span;
         match &**x {
             ExprP::Tuple(xs) => Ok(Ty::tuple(xs.try_map(|x| self.expression_type(x))?)),
-            ExprP::Dot(a, b) => Ok(self.expr_dot(&self.expression_type(a)?, b, b.span)),
+            ExprP::Dot(a, b) => {
+                let ty = self.expression_type(a)?;
+                self.attr_objects.borrow_mut().insert(b.span, ty.dupe());
+                if let Some(attrs) = self.rule_attrs(a, b) {
+                    return Ok(attrs);
+                }
+                Ok(self.expr_dot(&ty, b, b.span))
+            }
             ExprP::Call(f, args) => self.expr_call(span, f, args),
             ExprP::Index(a_b) => self.expr_index(span, &a_b.0, &a_b.1),
             ExprP::Index2(a_i0_i1) => {
@@ -428,10 +633,13 @@ impl TypingContext<'_> {
                 stop.as_deref(),
                 stride.as_deref(),
             ),
-            ExprP::Identifier(x) => Ok(self.expr_ident(x)),
-            ExprP::Lambda(_) => {
-                self.approximation("We don't type check lambdas", ());
-                Ok(Ty::any_callable())
+            ExprP::Identifier(x) => self.expr_ident(x),
+            ExprP::Lambda(lambda) => {
+                let ret = self.expression_type(&lambda.body)?;
+                match self.lambdas.get(&span) {
+                    Some(params) => Ok(Ty::function(params.clone(), ret)),
+                    None => Ok(self.approximation("Lambda without signature", ())),
+                }
             }
             ExprP::Literal(x) => match x {
                 AstLiteral::Int(_) => Ok(Ty::int()),
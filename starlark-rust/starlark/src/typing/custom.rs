@@ -89,6 +89,10 @@ pub trait TyCustomImpl: Debug + Display + Hash + Ord + Allocative + Send + Sync
         Err(TypingNoContextOrInternalError::Typing)
     }
     fn attribute(&self, attr: &str) -> Result<Ty, TypingNoContextError>;
+    /// Names of the attributes known to be available, for completion.
+    fn attribute_names(&self) -> Vec<String> {
+        Vec::new()
+    }
     fn union2(x: Arc<Self>, other: Arc<Self>) -> Result<Arc<Self>, (Arc<Self>, Arc<Self>)> {
         if x == other { Ok(x) } else { Err((x, other)) }
     }
@@ -129,6 +133,7 @@ pub(crate) trait TyCustomDyn: Debug + Display + Allocative + Send + Sync + 'stat
         ctx: &TypingOracleCtx,
     ) -> Result<Ty, TypingNoContextOrInternalError>;
     fn attribute_dyn(&self, attr: &str) -> Result<Ty, TypingNoContextError>;
+    fn attribute_names_dyn(&self) -> Vec<String>;
     fn bin_op_dyn(
         &self,
         bin_op: TypingBinOp,
@@ -201,6 +206,10 @@ impl<T: TyCustomImpl> TyCustomDyn for T {
         self.attribute(attr)
     }
 
+    fn attribute_names_dyn(&self) -> Vec<String> {
+        self.attribute_names()
+    }
+
     fn iter_item_dyn(&self) -> Result<Ty, TypingNoContextError> {
         self.iter_item()
     }
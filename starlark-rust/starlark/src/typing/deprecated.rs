@@ -0,0 +1,245 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Deprecated functions and variables.
+//!
+//! A top-level `def` or assignment is deprecated by a comment on the line before it:
+//!
+//! ```python
+//! # @deprecated("use new_macro instead")
+//! def old_macro():
+//!     pass
+//! ```
+//!
+//! Deprecations are recorded in the [`Interface`] of the module, and the typechecker warns
+//! where the modules loading them use the deprecated symbols.
+
+use std::collections::HashMap;
+
+use starlark_syntax::syntax::ast::AssignTargetP;
+use starlark_syntax::syntax::ast::ExprP;
+use starlark_syntax::syntax::ast::StmtP;
+use thiserror::Error;
+
+use crate::analysis::types::LintT;
+use crate::analysis::types::LintWarning;
+use crate::analysis::EvalSeverity;
+use crate::analysis::Lint;
+use crate::codemap::CodeMap;
+use crate::codemap::Span;
+use crate::eval::compiler::scope::payload::CstExpr;
+use crate::eval::compiler::scope::payload::CstIdent;
+use crate::eval::compiler::scope::payload::CstStmt;
+use crate::eval::compiler::scope::BindingId;
+use crate::eval::compiler::scope::ResolvedIdent;
+
+#[derive(Error, Debug)]
+#[error("`{name}` is deprecated: {message}")]
+pub(crate) struct Deprecated {
+    name: String,
+    message: String,
+}
+
+impl LintWarning for Deprecated {
+    fn severity(&self) -> EvalSeverity {
+        EvalSeverity::Warning
+    }
+
+    fn short_name(&self) -> &'static str {
+        "deprecated"
+    }
+}
+
+/// The message of a `# @deprecated("message")` comment.
+fn parse_comment(line: &str) -> Option<String> {
+    let args = line
+        .trim()
+        .strip_prefix('#')?
+        .trim()
+        .strip_prefix("@deprecated(")?
+        .strip_suffix(')')?
+        .trim();
+    let message = args
+        .strip_prefix('"')
+        .and_then(|x| x.strip_suffix('"'))
+        .or_else(|| args.strip_prefix('\'').and_then(|x| x.strip_suffix('\'')))?;
+    Some(message.to_owned())
+}
+
+/// The deprecation messages of the top-level statements of a module, by the name they bind.
+pub(crate) fn collect_deprecated(
+    stmts: &[&mut CstStmt],
+    codemap: &CodeMap,
+) -> HashMap<String, String> {
+    let mut deprecated = HashMap::new();
+    for stmt in stmts {
+        let line = codemap.find_line(stmt.span.begin());
+        let Some(message) = line
+            .checked_sub(1)
+            .and_then(|line| parse_comment(codemap.source_line(line)))
+        else {
+            continue;
+        };
+        match &stmt.node {
+            StmtP::Def(def) => {
+                deprecated.insert(def.name.ident.clone(), message);
+            }
+            StmtP::Assign(assign) => {
+                if let AssignTargetP::Identifier(x) = &assign.lhs.node {
+                    deprecated.insert(x.ident.clone(), message);
+                }
+            }
+            _ => {}
+        }
+    }
+    deprecated
+}
+
+/// Warnings for the uses of the deprecated symbols loaded by a module.
+pub(crate) fn deprecated_uses(stmts: &[&mut CstStmt], codemap: &CodeMap) -> Vec<Lint> {
+    let mut loaded: HashMap<BindingId, (&str, &str)> = HashMap::new();
+    for stmt in stmts {
+        if let StmtP::Load(load) = &stmt.node {
+            for arg in &load.args {
+                if let (Some(binding), Some(message)) =
+                    (arg.local.payload, load.payload.deprecated(&arg.their.node))
+                {
+                    loaded.insert(binding, (&arg.their.node, message));
+                }
+            }
+        }
+    }
+    if loaded.is_empty() {
+        return Vec::new();
+    }
+
+    let mut uses: Vec<(Span, BindingId)> = Vec::new();
+    for stmt in stmts {
+        stmt.visit_expr(|x| {
+            visit_idents(x, &mut |ident| {
+                if let Some(ResolvedIdent::Slot(_, binding)) = &ident.node.payload {
+                    if loaded.contains_key(binding) {
+                        uses.push((ident.span, *binding));
+                    }
+                }
+            })
+        });
+    }
+    uses.sort_by_key(|(span, _)| span.begin());
+    uses.into_iter()
+        .map(|(span, binding)| {
+            let (name, message) = loaded[&binding];
+            LintT::new(
+                codemap,
+                span,
+                Deprecated {
+                    name: name.to_owned(),
+                    message: message.to_owned(),
+                },
+            )
+            .erase()
+        })
+        .collect()
+}
+
+pub(crate) fn visit_idents<'a>(x: &'a CstExpr, f: &mut impl FnMut(&'a CstIdent)) {
+    if let ExprP::Identifier(ident) = &x.node {
+        f(ident);
+    }
+    x.node.visit_expr(|x| visit_idents(x, f));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::environment::Globals;
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
+    use crate::typing::deprecated::parse_comment;
+    use crate::typing::AstModuleTypecheck;
+    use crate::typing::Interface;
+
+    #[test]
+    fn test_parse_comment() {
+        assert_eq!(
+            Some("use y instead".to_owned()),
+            parse_comment("# @deprecated(\"use y instead\")")
+        );
+        assert_eq!(Some("".to_owned()), parse_comment("  #@deprecated('')"));
+        assert_eq!(None, parse_comment("# deprecated"));
+        assert_eq!(None, parse_comment("x = 1"));
+    }
+
+    #[test]
+    fn test_deprecated_uses() {
+        let typecheck = |name: &str, code: &str, loads: &HashMap<String, Interface>| {
+            AstModule::parse(name, code.to_owned(), &Dialect::AllOptionsInternal)
+                .unwrap()
+                .typecheck(&Globals::standard(), loads)
+        };
+        let (errors, _, interface, _) = typecheck(
+            "dep.bzl",
+            r#"
+# @deprecated("use new_macro instead")
+def old_macro():
+    pass
+
+def new_macro():
+    pass
+
+# @deprecated("use new_macro instead")
+OLD_CONSTANT = 1
+"#,
+            &HashMap::new(),
+        );
+        assert!(errors.is_empty());
+        assert_eq!(
+            Some("use new_macro instead"),
+            interface.deprecated("old_macro")
+        );
+        assert_eq!(None, interface.deprecated("new_macro"));
+        assert!(interface.deprecated("OLD_CONSTANT").is_some());
+
+        let loads = HashMap::from([("dep.bzl".to_owned(), interface)]);
+        let (errors, typemap, _, _) = typecheck(
+            "user.bzl",
+            r#"
+load("dep.bzl", "new_macro", "old_macro", x = "OLD_CONSTANT")
+
+def f():
+    old_macro()
+    new_macro()
+    return x
+"#,
+            &loads,
+        );
+        assert!(errors.is_empty());
+        let warnings = typemap
+            .deprecated_uses()
+            .iter()
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "user.bzl:5:5-14: `old_macro` is deprecated: use new_macro instead",
+                "user.bzl:7:12-13: `OLD_CONSTANT` is deprecated: use new_macro instead",
+            ],
+            warnings
+        );
+    }
+}
@@ -21,6 +21,7 @@ use std::iter;
 use starlark_map::unordered_map;
 use starlark_map::unordered_map::UnorderedMap;
 use starlark_syntax::slice_vec_ext::SliceExt;
+use starlark_syntax::syntax::ast::ArgumentP;
 use starlark_syntax::syntax::ast::AssignP;
 use starlark_syntax::syntax::ast::AssignTargetP;
 use starlark_syntax::syntax::ast::AstLiteral;
@@ -63,7 +64,9 @@ use crate::typing::TypingOracleCtx;
 use crate::util::arc_str::ArcStr;
 use crate::values::tuple::AllocTuple;
 use crate::values::types::ellipsis::Ellipsis;
+use crate::values::typing::structs::typing_struct_ty;
 use crate::values::typing::type_compiled::compiled::TypeCompiled;
+use crate::values::typing::type_var::TypingTypeVar;
 use crate::values::Heap;
 use crate::values::Value;
 
@@ -136,13 +139,66 @@ impl<'a, 'v> GlobalTypesBuilder<'a, 'v> {
 
     fn call(
         &mut self,
-        _f: &CstExpr,
-        _args: &CallArgsP<CstPayload>,
+        f: &CstExpr,
+        args: &CallArgsP<CstPayload>,
     ) -> Result<GlobalValue<'v>, InternalError> {
+        let f = self.expr(f)?;
+        // `typing.TypeVar("T")` is evaluated because type variables are used in types.
+        if let (Some(f), [arg]) = (
+            f.value.and_then(|f| f.unpack_frozen()),
+            args.args.as_slice(),
+        ) {
+            if f == Constants::get().typing_type_var {
+                if let ArgumentP::Positional(name) = &arg.node {
+                    if let Some(name) = self.expr(name)?.value.and_then(|n| n.unpack_str()) {
+                        return Ok(GlobalValue::value(
+                            self.heap.alloc(TypingTypeVar::new(name)),
+                        ));
+                    }
+                }
+            }
+        }
+        // `typing.Struct(...)` is evaluated because struct types are used in types.
+        if f.value
+            .and_then(|f| f.unpack_frozen())
+            .is_some_and(|f| f == Constants::get().typing_struct)
+        {
+            if let Some(ty) = self.typing_struct(args)? {
+                return Ok(GlobalValue::value(
+                    TypeCompiled::from_ty(&ty, self.heap).to_inner(),
+                ));
+            }
+        }
         // TODO(nga): could be a call like `record(...)`, and we need to evaluate it.
         Ok(GlobalValue::any())
     }
 
+    /// The type of `typing.Struct(...)`, if all its arguments are known.
+    fn typing_struct(&mut self, args: &CallArgsP<CstPayload>) -> Result<Option<Ty>, InternalError> {
+        let mut strict = false;
+        let mut fields = Vec::new();
+        for arg in &args.args {
+            let ArgumentP::Named(name, value) = &arg.node else {
+                return Ok(None);
+            };
+            let Some(value) = self.expr(value)?.value else {
+                return Ok(None);
+            };
+            if name.node == "strict" {
+                match value.unpack_bool() {
+                    Some(value) => strict = value,
+                    None => return Ok(None),
+                }
+            } else {
+                fields.push((name.node.as_str(), value));
+            }
+        }
+        match typing_struct_ty(fields, strict, self.heap) {
+            Ok(ty) => Ok(Some(ty)),
+            Err(e) => Ok(Some(self.err(args.args[0].span, e.into()).ty)),
+        }
+    }
+
     fn expr_ident(&self, ident: &CstIdent) -> Result<GlobalValue<'v>, InternalError> {
         let Some(resolved_ident) = &ident.payload else {
             return Err(self.internal_error(ident.span, "unresolved ident"));
@@ -164,8 +220,10 @@ impl<'a, 'v> GlobalTypesBuilder<'a, 'v> {
     fn expr_literal(&mut self, literal: &AstLiteral) -> Result<GlobalValue<'v>, InternalError> {
         match literal {
             AstLiteral::String(s) => Ok(GlobalValue::value(self.heap.alloc(s.node.as_str()))),
-            // Not used in type expressions.
-            _ => Ok(GlobalValue::any()),
+            // Not used in type expressions, but the types are exported in the module interface.
+            AstLiteral::Int(_) => Ok(GlobalValue::ty(Ty::int())),
+            AstLiteral::Float(_) => Ok(GlobalValue::ty(Ty::float())),
+            AstLiteral::Ellipsis => Ok(GlobalValue::any()),
         }
     }
 
@@ -590,6 +648,16 @@ impl<'a, 'v> GlobalTypesBuilder<'a, 'v> {
                 Ok(Ty::unions(xs.try_map(|x| self.from_type_expr_impl(x))?))
             }
             TypeExprUnpackP::Path(path) => self.path_ty(path),
+            TypeExprUnpackP::Literal(a, values) => match self.eval_path(&a.node)? {
+                Some(a) if a.ptr_eq(Constants::get().typing_literal.0.to_value()) => {
+                    Ok(Ty::enum_of(values.iter().map(|v| v.node)))
+                }
+                _ => {
+                    self.approximations
+                        .push(Approximation::new("Not typing.Literal", x));
+                    Ok(Ty::any())
+                }
+            },
             TypeExprUnpackP::Index(a, i) => {
                 if let Some(a) = self.expr_ident(a)?.value {
                     if !a.ptr_eq(Constants::get().fn_list.0.to_value()) {
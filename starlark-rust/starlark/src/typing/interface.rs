@@ -15,16 +15,31 @@
  * limitations under the License.
  */
 
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
+use std::sync::Mutex;
 
 use dupe::Dupe;
+use lru::LruCache;
 
+use crate::codemap::FileSpan;
+use crate::environment::Globals;
 use crate::typing::Ty;
 
 /// Interface representing the types of all bindings in a module.
 #[derive(Default, Dupe, Clone, Debug)]
-pub struct Interface(Arc<HashMap<String, Ty>>);
+pub struct Interface {
+    bindings: Arc<HashMap<String, Ty>>,
+    /// Messages of the bindings marked deprecated.
+    deprecated: Arc<HashMap<String, String>>,
+}
 
 impl Interface {
     /// Create an empty interface, with no bindings.
@@ -34,11 +49,348 @@ impl Interface {
 
     /// Create a new interface with the given bindings.
     pub fn new(bindings: HashMap<String, Ty>) -> Self {
-        Self(Arc::new(bindings))
+        Self {
+            bindings: Arc::new(bindings),
+            deprecated: Arc::default(),
+        }
+    }
+
+    /// Mark bindings deprecated, with the message to show where they are used.
+    pub fn with_deprecated(self, deprecated: HashMap<String, String>) -> Self {
+        Self {
+            bindings: self.bindings,
+            deprecated: Arc::new(deprecated),
+        }
     }
 
     /// Get the type for a given binding.
     pub fn get(&self, name: &str) -> Option<&Ty> {
-        self.0.get(name)
+        self.bindings.get(name)
+    }
+
+    /// The deprecation message of a binding, if it is deprecated.
+    pub fn deprecated(&self, name: &str) -> Option<&str> {
+        self.deprecated.get(name).map(|x| x.as_str())
+    }
+
+    /// Hash of the bindings, which is equal for interfaces with equal bindings.
+    pub fn fingerprint(&self) -> u64 {
+        let mut bindings: Vec<(&String, &Ty)> = self.bindings.iter().collect();
+        bindings.sort_by_key(|(name, _)| *name);
+        let mut deprecated: Vec<(&String, &String)> = self.deprecated.iter().collect();
+        deprecated.sort();
+        let mut hasher = DefaultHasher::new();
+        bindings.hash(&mut hasher);
+        deprecated.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Key of the interface of a typechecked module in an [`InterfaceCache`].
+///
+/// The interface of a module only depends on its source, the types of the globals and the
+/// interfaces of the modules it loads, so the key holds those, or fingerprints of those.
+/// Keys are hashed for lookup, but compared in full, so a hash collision is never a hit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceKey {
+    hash: u64,
+    source: Arc<str>,
+    globals: u64,
+    loads: Vec<(String, u64)>,
+}
+
+impl Hash for InterfaceKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+    }
+}
+
+impl InterfaceKey {
+    /// Compute the key of a module from the arguments it is typechecked with.
+    pub fn new(source: &str, globals: &Globals, loads: &HashMap<String, Interface>) -> Self {
+        let mut global_types: Vec<(&str, Ty)> = globals
+            .iter()
+            .map(|(name, value)| (name, Ty::of_value(value.to_value())))
+            .collect();
+        global_types.sort_by_key(|(name, _)| *name);
+        let mut hasher = DefaultHasher::new();
+        global_types.hash(&mut hasher);
+        let globals = hasher.finish();
+
+        let mut loads: Vec<(String, u64)> = loads
+            .iter()
+            .map(|(module, interface)| (module.clone(), interface.fingerprint()))
+            .collect();
+        loads.sort();
+
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        globals.hash(&mut hasher);
+        loads.hash(&mut hasher);
+        InterfaceKey {
+            hash: hasher.finish(),
+            source: source.into(),
+            globals,
+            loads,
+        }
+    }
+}
+
+/// Interfaces of typechecked modules, so that a module which did not change, and whose
+/// loads did not change either, does not need to be typechecked again to get its interface.
+///
+/// The cache keeps at most a fixed number of interfaces, evicting the least recently used.
+///
+/// Interfaces are not serialized: they hold arbitrary types, including types of native values
+/// and of values defined in other modules, which only exist in the process which created them.
+/// The cache lives in memory instead, and is meant to be shared across typechecks of a
+/// long-running process.
+#[derive(Debug)]
+pub struct InterfaceCache(Mutex<LruCache<InterfaceKey, Interface>>);
+
+impl InterfaceCache {
+    /// Create an empty cache, keeping at most `capacity` interfaces.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self(Mutex::new(LruCache::new(capacity)))
+    }
+
+    /// Get the interface of a module typechecked before with the same key.
+    pub fn get(&self, key: &InterfaceKey) -> Option<Interface> {
+        self.0.lock().unwrap().get(key).map(|x| x.dupe())
+    }
+
+    /// Record the interface of a typechecked module.
+    pub fn insert(&self, key: InterfaceKey, interface: Interface) {
+        self.0.lock().unwrap().put(key, interface);
+    }
+
+    /// Number of interfaces in the cache.
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all interfaces from the cache.
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+}
+
+/// Cycle of `load()` statements, found while building the interfaces of modules.
+#[derive(Debug, Clone)]
+pub struct LoadCycle {
+    /// The module the cycle starts and ends with.
+    module: String,
+    /// The modules loaded along the cycle, with the `load()` statements loading them.
+    loads: Vec<(String, FileSpan)>,
+}
+
+impl LoadCycle {
+    /// The modules in the cycle, starting and ending with the same module.
+    pub fn modules(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.module.as_str()).chain(self.loads.iter().map(|(m, _)| m.as_str()))
+    }
+
+    /// The `load()` statements forming the cycle.
+    pub fn spans(&self) -> impl Iterator<Item = &FileSpan> {
+        self.loads.iter().map(|(_, span)| span)
+    }
+}
+
+impl Display for LoadCycle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Load cycle: `{}`", self.module)?;
+        for (module, span) in &self.loads {
+            write!(f, "\n  loads `{}` at {}", module, span)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LoadCycle {}
+
+/// Modules whose interfaces are being built, to detect `load()` cycles when building
+/// the interfaces of the loaded modules first, recursively.
+#[derive(Default, Debug)]
+pub struct LoadStack(Vec<(String, Option<FileSpan>)>);
+
+impl LoadStack {
+    /// Create an empty stack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start building the interface of a module, loaded by the `load()` statement at `span`,
+    /// or `None` for the modules to typecheck. Fails if the module is already being built.
+    pub fn push(&mut self, module: String, span: Option<FileSpan>) -> Result<(), LoadCycle> {
+        if let Some(i) = self.0.iter().position(|(m, _)| *m == module) {
+            let loads = self.0[i + 1..]
+                .iter()
+                .cloned()
+                .chain([(module.clone(), span)])
+                // Only the first module of the stack is not loaded.
+                .filter_map(|(m, span)| Some((m, span?)))
+                .collect();
+            return Err(LoadCycle { module, loads });
+        }
+        self.0.push((module, span));
+        Ok(())
+    }
+
+    /// Finish building the interface of the last pushed module.
+    pub fn pop(&mut self) {
+        self.0.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::num::NonZeroUsize;
+
+    use crate::codemap::CodeMap;
+    use crate::codemap::Pos;
+    use crate::codemap::Span;
+    use crate::environment::Globals;
+    use crate::environment::GlobalsBuilder;
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
+    use crate::typing::AstModuleTypecheck;
+    use crate::typing::Interface;
+    use crate::typing::InterfaceCache;
+    use crate::typing::InterfaceKey;
+    use crate::typing::LoadStack;
+
+    fn typecheck(source: &str, loads: &HashMap<String, Interface>) -> Interface {
+        let ast =
+            AstModule::parse("test.star", source.to_owned(), &Dialect::AllOptionsInternal).unwrap();
+        let (errors, _, interface, _) = ast.typecheck(&Globals::standard(), loads);
+        assert!(errors.is_empty());
+        interface
+    }
+
+    #[test]
+    fn test_interface_key() {
+        let globals = Globals::standard();
+        let dep = typecheck("x = 1", &HashMap::new());
+        let dep_changed = typecheck("x = 'x'", &HashMap::new());
+        assert_eq!(
+            dep.fingerprint(),
+            typecheck("x = 2", &HashMap::new()).fingerprint()
+        );
+        assert_ne!(dep.fingerprint(), dep_changed.fingerprint());
+
+        let source = "load('dep.star', 'x')\ny = x";
+        let loads = HashMap::from([("dep.star".to_owned(), dep)]);
+        let loads_changed = HashMap::from([("dep.star".to_owned(), dep_changed)]);
+        let key = InterfaceKey::new(source, &globals, &loads);
+        assert_eq!(key, InterfaceKey::new(source, &globals, &loads));
+        assert_ne!(key, InterfaceKey::new("y = 1", &globals, &loads));
+        assert_ne!(key, InterfaceKey::new(source, &globals, &loads_changed));
+        // Same global names, different types.
+        let str_globals = |value: &str| {
+            let mut builder = GlobalsBuilder::new();
+            builder.set("g", 1);
+            builder.set("h", value.to_owned());
+            builder.build()
+        };
+        let int_globals = |value: i32| {
+            let mut builder = GlobalsBuilder::new();
+            builder.set("g", 1);
+            builder.set("h", value);
+            builder.build()
+        };
+        assert_ne!(
+            InterfaceKey::new(source, &str_globals("x"), &loads),
+            InterfaceKey::new(source, &int_globals(1), &loads)
+        );
+        assert_eq!(
+            InterfaceKey::new(source, &str_globals("x"), &loads),
+            InterfaceKey::new(source, &str_globals("y"), &loads)
+        );
+
+        let cache = InterfaceCache::new(NonZeroUsize::new(10).unwrap());
+        assert!(cache.get(&key).is_none());
+        cache.insert(key.clone(), typecheck(source, &loads));
+        assert_eq!(
+            Some("int".to_owned()),
+            cache.get(&key).unwrap().get("y").map(|ty| ty.to_string())
+        );
+        assert!(
+            cache
+                .get(&InterfaceKey::new(source, &globals, &loads_changed))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_interface_key_collision() {
+        let globals = Globals::standard();
+        let key = InterfaceKey::new("x = 1", &globals, &HashMap::new());
+        // Same hash, different source.
+        let colliding = InterfaceKey {
+            source: "x = 'x'".into(),
+            ..key.clone()
+        };
+
+        let cache = InterfaceCache::new(NonZeroUsize::new(10).unwrap());
+        cache.insert(key.clone(), typecheck("x = 1", &HashMap::new()));
+        assert!(cache.get(&key).is_some());
+        assert!(cache.get(&colliding).is_none());
+    }
+
+    #[test]
+    fn test_interface_cache_evicts_least_recently_used() {
+        let globals = Globals::standard();
+        let key = |source: &str| InterfaceKey::new(source, &globals, &HashMap::new());
+        let cache = InterfaceCache::new(NonZeroUsize::new(2).unwrap());
+        cache.insert(key("x = 1"), Interface::empty());
+        cache.insert(key("x = 2"), Interface::empty());
+        assert!(cache.get(&key("x = 1")).is_some());
+        cache.insert(key("x = 3"), Interface::empty());
+        assert_eq!(2, cache.len());
+        assert!(cache.get(&key("x = 1")).is_some());
+        assert!(cache.get(&key("x = 2")).is_none());
+        assert!(cache.get(&key("x = 3")).is_some());
+    }
+
+    #[test]
+    fn test_load_stack_cycle() {
+        let load = |file: &str, module: &str| {
+            let source = format!("load('{}', 'x')", module);
+            let codemap = CodeMap::new(file.to_owned(), source);
+            codemap.file_span(Span::new(Pos::new(5), Pos::new(7 + module.len() as u32)))
+        };
+
+        let mut stack = LoadStack::new();
+        stack.push("a.bzl".to_owned(), None).unwrap();
+        stack
+            .push("b.bzl".to_owned(), Some(load("a.bzl", "b.bzl")))
+            .unwrap();
+        stack
+            .push("c.bzl".to_owned(), Some(load("b.bzl", "c.bzl")))
+            .unwrap();
+        let cycle = stack
+            .push("b.bzl".to_owned(), Some(load("c.bzl", "b.bzl")))
+            .unwrap_err();
+        assert_eq!(
+            vec!["b.bzl", "c.bzl", "b.bzl"],
+            cycle.modules().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            "Load cycle: `b.bzl`\n  loads `c.bzl` at b.bzl:1:6-13\n  loads `b.bzl` at c.bzl:1:6-13",
+            cycle.to_string()
+        );
+
+        // Loading the same module from different modules is not a cycle.
+        stack.pop();
+        stack.pop();
+        stack
+            .push("c.bzl".to_owned(), Some(load("a.bzl", "c.bzl")))
+            .unwrap();
     }
 }
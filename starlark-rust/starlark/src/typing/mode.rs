@@ -24,3 +24,17 @@ pub(crate) enum TypecheckMode {
     /// Invoked from the compiler.
     Compiler,
 }
+
+/// How strict static typechecking is, so that code can be annotated gradually.
+#[derive(Copy, Clone, Dupe, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
+pub enum TypingProfile {
+    /// Parameters without type annotation are implicitly `Any`.
+    #[default]
+    Lenient,
+    /// Function parameters must be annotated, so values of unknown type do not implicitly
+    /// flow into function bodies.
+    Strict,
+    /// Like `Strict`, and also warn about public functions without a return type annotation,
+    /// through the [`SoftErrorHandler`](crate::eval::SoftErrorHandler).
+    Pedantic,
+}
@@ -0,0 +1,318 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Narrowing of union types by conditions.
+//!
+//! In code guarded by `x == None`, `isinstance(x, T)` or `type(x) == "name"` (or their
+//! negations), the type of `x` only keeps the alternatives allowed by the condition:
+//!
+//! ```python
+//! def f(x: str | None):
+//!     if x != None:
+//!         x.upper() # `x` is `str` here
+//! ```
+//!
+//! Conditions narrow the branches of `if` statements and expressions, the right operand of
+//! `and` and `or`, and the statements following an `if` whose body always exits.
+//! A variable is not narrowed in code which assigns it.
+
+use starlark_syntax::syntax::ast::ArgumentP;
+use starlark_syntax::syntax::ast::AstLiteral;
+use starlark_syntax::syntax::ast::BinOp;
+use starlark_syntax::syntax::ast::ExprP;
+use starlark_syntax::syntax::ast::StmtP;
+
+use crate::codemap::Span;
+use crate::eval::compiler::scope::payload::CstExpr;
+use crate::eval::compiler::scope::payload::CstStmt;
+use crate::eval::compiler::scope::BindingId;
+use crate::eval::compiler::scope::ResolvedIdent;
+use crate::typing::basic::TyBasic;
+use crate::typing::error::InternalError;
+use crate::typing::oracle::ctx::TypingOracleCtx;
+use crate::typing::ty::Ty;
+
+#[derive(Debug, Clone)]
+enum NarrowTest {
+    /// `isinstance(x, T)`, or `x == None`.
+    Type(Ty),
+    /// `type(x) == "name"`.
+    TypeName(String),
+}
+
+/// The condition `test` is known to be `positive` in `span`.
+#[derive(Debug, Clone)]
+pub(crate) struct Narrowing {
+    binding: BindingId,
+    span: Span,
+    test: NarrowTest,
+    positive: bool,
+}
+
+impl Narrowing {
+    /// Collect the narrowings by `cond` being `positive` in `span`.
+    pub(crate) fn collect(cond: &CstExpr, positive: bool, span: Span, out: &mut Vec<Narrowing>) {
+        match &cond.node {
+            ExprP::Not(x) => Self::collect(x, !positive, span, out),
+            ExprP::Op(lhs, BinOp::And, rhs) if positive => {
+                Self::collect(lhs, positive, span, out);
+                Self::collect(rhs, positive, span, out);
+            }
+            ExprP::Op(lhs, BinOp::Or, rhs) if !positive => {
+                Self::collect(lhs, positive, span, out);
+                Self::collect(rhs, positive, span, out);
+            }
+            ExprP::Op(lhs, op @ (BinOp::Equal | BinOp::NotEqual), rhs) => {
+                if let Some((binding, test)) =
+                    Self::equality(lhs, rhs).or_else(|| Self::equality(rhs, lhs))
+                {
+                    out.push(Narrowing {
+                        binding,
+                        span,
+                        test,
+                        positive: positive == (*op == BinOp::Equal),
+                    });
+                }
+            }
+            ExprP::Call(f, args) if is_global(f, "isinstance") => {
+                if let [x, ty] = args.args.as_slice() {
+                    if let (ArgumentP::Positional(x), ArgumentP::Positional(ty)) =
+                        (&x.node, &ty.node)
+                    {
+                        if let (Some(binding), Some(ty)) = (variable(x), eval_type(ty)) {
+                            out.push(Narrowing {
+                                binding,
+                                span,
+                                test: NarrowTest::Type(ty),
+                                positive,
+                            });
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Collect the narrowings of the statements following an `if` whose body always exits.
+    pub(crate) fn collect_early_exits(stmts: &[CstStmt], out: &mut Vec<Narrowing>) {
+        for (i, stmt) in stmts.iter().enumerate() {
+            let (StmtP::If(cond, body), Some(next), Some(last)) =
+                (&stmt.node, stmts.get(i + 1), stmts.last())
+            else {
+                continue;
+            };
+            if always_exits(body) {
+                Self::collect(cond, false, next.span.merge(last.span), out);
+            }
+        }
+    }
+
+    /// `x == None` or `type(x) == "name"`.
+    fn equality(lhs: &CstExpr, rhs: &CstExpr) -> Option<(BindingId, NarrowTest)> {
+        if is_global(rhs, "None") {
+            return Some((variable(lhs)?, NarrowTest::Type(Ty::none())));
+        }
+        let (ExprP::Call(f, args), ExprP::Literal(AstLiteral::String(name))) =
+            (&lhs.node, &rhs.node)
+        else {
+            return None;
+        };
+        match args.args.as_slice() {
+            [x] if is_global(f, "type") => match &x.node {
+                ArgumentP::Positional(x) => {
+                    Some((variable(x)?, NarrowTest::TypeName(name.node.clone())))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The narrowing applies to the use of the variable `binding` at `span`.
+    pub(crate) fn applies(&self, binding: BindingId, span: Span) -> bool {
+        self.binding == binding && self.span.contains(span.begin())
+    }
+
+    /// The narrowing does not apply in code assigning the variable.
+    pub(crate) fn assigned_at(&self, span: Span) -> bool {
+        self.span.contains(span.begin())
+    }
+
+    pub(crate) fn binding(&self) -> BindingId {
+        self.binding
+    }
+
+    /// Keep the alternatives of `ty` allowed by the condition.
+    pub(crate) fn apply(&self, ty: &Ty, oracle: &TypingOracleCtx) -> Result<Ty, InternalError> {
+        let mut alternatives = Vec::new();
+        for basic in ty.iter_union() {
+            match (&self.test, self.positive) {
+                (NarrowTest::Type(test), true) => {
+                    if basic == &TyBasic::Any {
+                        alternatives.push(test.clone());
+                    } else if oracle.intersects(&Ty::basic(basic.clone()), test)? {
+                        alternatives.push(Ty::basic(basic.clone()));
+                    }
+                }
+                (NarrowTest::Type(test), false) => {
+                    if !test.iter_union().iter().any(|t| covers(t, basic)) {
+                        alternatives.push(Ty::basic(basic.clone()));
+                    }
+                }
+                (NarrowTest::TypeName(name), positive) => {
+                    let matches = builtin_name(basic).map(|n| n == name.as_str());
+                    // Alternatives without a builtin name are not narrowed.
+                    if matches.unwrap_or(positive) == positive {
+                        alternatives.push(Ty::basic(basic.clone()));
+                    }
+                }
+            }
+        }
+        Ok(Ty::unions(alternatives))
+    }
+}
+
+/// All the values of type `basic` are values of type `test`.
+fn covers(test: &TyBasic, basic: &TyBasic) -> bool {
+    if basic == &TyBasic::Any {
+        return false;
+    }
+    // `isinstance(x, list)` is true for lists of any type.
+    let unparameterized = [
+        Ty::any_list(),
+        Ty::any_dict(),
+        Ty::any_set(),
+        Ty::any_tuple(),
+    ];
+    test == basic
+        || (test.as_name().is_some()
+            && test.as_name() == basic.as_name()
+            && unparameterized
+                .iter()
+                .any(|u| u.iter_union() == std::slice::from_ref(test)))
+}
+
+/// The name `type()` returns for values of `basic`, for builtin types.
+fn builtin_name(basic: &TyBasic) -> Option<&str> {
+    match basic {
+        TyBasic::StarlarkValue(_)
+        | TyBasic::List(_)
+        | TyBasic::Tuple(_)
+        | TyBasic::Dict(..)
+        | TyBasic::Set(_) => basic.as_name(),
+        _ => None,
+    }
+}
+
+/// Exits the enclosing block: `return`, `break`, `continue` or `fail()`.
+pub(crate) fn always_exits(stmt: &CstStmt) -> bool {
+    match &stmt.node {
+        StmtP::Return(_) | StmtP::Break | StmtP::Continue => true,
+        StmtP::Statements(xs) => xs.last().is_some_and(always_exits),
+        StmtP::IfElse(_, branches) => always_exits(&branches.0) && always_exits(&branches.1),
+        StmtP::Expression(x) => matches!(&x.node, ExprP::Call(f, _) if is_global(f, "fail")),
+        _ => false,
+    }
+}
+
+fn is_global(x: &CstExpr, name: &str) -> bool {
+    match &x.node {
+        ExprP::Identifier(x) => {
+            x.node.ident == name && matches!(x.node.payload, Some(ResolvedIdent::Global(_)))
+        }
+        _ => false,
+    }
+}
+
+fn variable(x: &CstExpr) -> Option<BindingId> {
+    match &x.node {
+        ExprP::Identifier(x) => match &x.node.payload {
+            Some(ResolvedIdent::Slot(_, binding)) => Some(*binding),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The type of a global like `int` or `list` used as a type.
+fn eval_type(x: &CstExpr) -> Option<Ty> {
+    match &x.node {
+        ExprP::Identifier(x) => match &x.node.payload {
+            Some(ResolvedIdent::Global(g)) => g.to_value().get_ref().eval_type(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert::Assert;
+
+    #[test]
+    fn test_narrow_none() {
+        let a = Assert::new();
+        a.fail(
+            r#"
+def f(x: str | None) -> str:
+    if x == None:
+        return x.upper()
+    return x
+"#,
+            "The attribute `upper` is not available on the type `None`",
+        );
+    }
+
+    #[test]
+    fn test_narrow_early_exit() {
+        let a = Assert::new();
+        a.fail(
+            r#"
+def f(x: int | list[int]) -> int:
+    if isinstance(x, list):
+        return len(x)
+    return x.upper()
+"#,
+            "The attribute `upper` is not available on the type `int`",
+        );
+        a.pass(
+            r#"
+def f(x: str | None) -> str:
+    if x == None:
+        fail("x is required")
+    return x.upper()
+
+f("a")
+"#,
+        );
+    }
+
+    #[test]
+    fn test_narrow_not_applied_after_assignment() {
+        Assert::new().pass(
+            r#"
+def f(x: str | None):
+    if x == None:
+        x = "a"
+        x.upper()
+
+f(None)
+"#,
+        );
+    }
+}
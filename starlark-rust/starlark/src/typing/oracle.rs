@@ -15,5 +15,7 @@
  * limitations under the License.
  */
 
+pub(crate) mod buck;
 pub(crate) mod ctx;
+pub(crate) mod dependency;
 pub(crate) mod traits;
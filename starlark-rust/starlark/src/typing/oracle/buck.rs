@@ -0,0 +1,572 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+
+use dupe::Dupe;
+use starlark_map::sorted_map::SortedMap;
+use starlark_syntax::syntax::ast::ArgumentP;
+use starlark_syntax::syntax::ast::AstLiteral;
+use starlark_syntax::syntax::ast::ExprP;
+
+use crate::docs::DocFunction;
+use crate::docs::DocItem;
+use crate::docs::DocMember;
+use crate::docs::DocModule;
+use crate::docs::DocParam;
+use crate::eval::compiler::scope::payload::CstArgument;
+use crate::eval::compiler::scope::payload::CstExpr;
+use crate::typing::callable_param::ParamIsRequired;
+use crate::typing::oracle::dependency::TyDependency;
+use crate::typing::ParamSpec;
+use crate::typing::Ty;
+use crate::typing::TyStruct;
+use crate::util::arc_str::ArcStr;
+
+/// Types of buck2 globals, provider fields and rule attributes, from their generated
+/// documentation (the same data `buck2 docs` emits).
+///
+/// With this oracle, the typechecker knows the types of documented globals which are not in the
+/// globals the module is checked with, of the members of documented types like providers, and
+/// of `ctx.attrs` in the implementation of a documented rule:
+///
+/// ```python
+/// def _impl(ctx):
+///     ctx.attrs.srcs # Typed by the attributes of `my_rule`.
+///
+/// my_rule = rule(impl = _impl, attrs = {...})
+/// ```
+///
+/// Rules defined in the module being checked have their `ctx.attrs` typed from their `attrs`
+/// declarations instead, by modeling the `attrs.*` constructors: `attrs.list(attrs.source())`
+/// is a `list[artifact]`.
+#[derive(Debug, Default, Clone)]
+pub struct OracleBuck {
+    /// Types of documented globals.
+    globals: HashMap<String, Ty>,
+    /// Types of the members of documented types, by type name.
+    members: HashMap<String, HashMap<String, Ty>>,
+    /// Types of `ctx.attrs` in the implementation of rules, by rule name.
+    rule_attrs: HashMap<String, Ty>,
+    /// Documented types, by name.
+    types: HashMap<String, Ty>,
+}
+
+impl OracleBuck {
+    /// Create an oracle from the documentation of the globals.
+    pub fn new(globals: &DocModule) -> OracleBuck {
+        let mut oracle = OracleBuck::default();
+        for (name, item) in &globals.members {
+            let ty = oracle.item_ty(name, item);
+            oracle.globals.insert(name.clone(), ty);
+        }
+        oracle
+    }
+
+    /// Add rules, documented as functions taking their attributes as parameters.
+    pub fn add_rules(&mut self, rules: &DocModule) {
+        for (name, item) in &rules.members {
+            let DocItem::Member(DocMember::Function(rule)) = item else {
+                continue;
+            };
+            let attrs = rule
+                .params
+                .regular_params()
+                .map(|p| (ArcStr::from(p.name.as_str()), p.typ.dupe()));
+            self.rule_attrs.insert(
+                name.clone(),
+                Ty::custom(TyStruct {
+                    fields: SortedMap::from_iter(attrs),
+                    extra: false,
+                }),
+            );
+            self.globals.insert(name.clone(), function_ty(rule));
+        }
+    }
+
+    fn item_ty(&mut self, name: &str, item: &DocItem) -> Ty {
+        match item {
+            DocItem::Module(module) => {
+                let fields = module
+                    .members
+                    .iter()
+                    .map(|(k, v)| (ArcStr::from(k.as_str()), self.item_ty(k, v)))
+                    .collect::<Vec<_>>();
+                Ty::custom(TyStruct {
+                    fields: SortedMap::from_iter(fields),
+                    extra: false,
+                })
+            }
+            DocItem::Type(ty) => {
+                let members = ty
+                    .members
+                    .iter()
+                    .map(|(k, v)| (k.clone(), member_ty(v)))
+                    .collect();
+                let type_name = ty.ty.as_name().unwrap_or(name).to_owned();
+                self.members.insert(type_name.clone(), members);
+                self.types.insert(type_name, ty.ty.dupe());
+                match &ty.constructor {
+                    Some(constructor) => function_ty(constructor),
+                    None => Ty::function(ParamSpec::any(), ty.ty.dupe()),
+                }
+            }
+            DocItem::Member(member) => member_ty(member),
+        }
+    }
+
+    /// The type of an identifier not resolved to a global or a variable.
+    pub(crate) fn global(&self, name: &str) -> Option<Ty> {
+        self.globals.get(name).cloned()
+    }
+
+    /// The type of the attribute of a value of a documented type.
+    pub(crate) fn attribute(&self, ty: &Ty, attr: &str) -> Option<Ty> {
+        self.members.get(ty.as_name()?)?.get(attr).cloned()
+    }
+
+    /// The type of `ctx.attrs` in the implementation of the rule.
+    pub(crate) fn rule_attrs(&self, rule: &str) -> Option<Ty> {
+        self.rule_attrs.get(rule).cloned()
+    }
+
+    /// The type of `ctx.attrs` for the attributes declared by the `attrs` argument of `rule()`,
+    /// if it is a dictionary literal with string keys.
+    pub(crate) fn declared_attrs(&self, attrs: &CstExpr) -> Option<Ty> {
+        let ExprP::Dict(attrs) = &attrs.node else {
+            return None;
+        };
+        let mut fields = INTERNAL_ATTRS
+            .iter()
+            .map(|(name, ty)| (ArcStr::from(*name), ty()))
+            .collect::<Vec<_>>();
+        for (name, attr) in attrs {
+            let ExprP::Literal(AstLiteral::String(name)) = &name.node else {
+                return None;
+            };
+            fields.push((ArcStr::from(name.node.as_str()), self.attr_ty(attr)));
+        }
+        Some(Ty::custom(TyStruct {
+            fields: SortedMap::from_iter(fields),
+            extra: false,
+        }))
+    }
+
+    /// The type of the value of an attribute in `ctx.attrs`, from its `attrs.*` constructor.
+    fn attr_ty(&self, attr: &CstExpr) -> Ty {
+        let ExprP::Call(f, args) = &attr.node else {
+            return Ty::any();
+        };
+        let ExprP::Dot(module, constructor) = &f.node else {
+            return Ty::any();
+        };
+        if !matches!(&module.node, ExprP::Identifier(x) if x.node.ident == "attrs") {
+            return Ty::any();
+        }
+        let positional = || {
+            args.args.iter().filter_map(|arg| match &arg.node {
+                ArgumentP::Positional(x) => Some(x),
+                _ => None,
+            })
+        };
+        // The attribute type argument: positional, or named like `inner`.
+        let arg = |i: usize, name: &str| {
+            positional()
+                .nth(i)
+                .or_else(|| {
+                    args.args.iter().find_map(|arg| match &arg.node {
+                        ArgumentP::Named(n, x) if n.node == name => Some(x),
+                        _ => None,
+                    })
+                })
+                .map_or_else(Ty::any, |x| self.attr_ty(x))
+        };
+        match constructor.node.as_str() {
+            "string" | "enum" | "regex" => Ty::string(),
+            "bool" => Ty::bool(),
+            "int" => Ty::int(),
+            "list" => Ty::list(arg(0, "inner")),
+            "dict" => Ty::dict(arg(0, "key"), arg(1, "value")),
+            "tuple" => Ty::tuple(positional().map(|x| self.attr_ty(x)).collect()),
+            "one_of" => Ty::unions(positional().map(|x| self.attr_ty(x)).collect()),
+            "option" => Ty::union2(arg(0, "inner"), Ty::none()),
+            "default_only" => arg(0, "inner"),
+            "source" => self.named_type("artifact"),
+            "dep" | "exec_dep" | "toolchain_dep" | "transition_dep" | "configured_dep" => {
+                dependency(args.args.iter())
+            }
+            "split_transition_dep" => Ty::dict(Ty::string(), dependency(args.args.iter())),
+            "label" => self.named_type("label"),
+            "arg" => self.named_type("resolved_macro"),
+            _ => Ty::any(),
+        }
+    }
+
+    /// A documented type, or `Any` if it is not documented.
+    fn named_type(&self, name: &str) -> Ty {
+        self.types.get(name).cloned().unwrap_or_else(Ty::any)
+    }
+}
+
+/// The value of a dependency attribute, with the providers of its `providers` argument if they
+/// are a list of provider names.
+fn dependency<'a>(mut args: impl Iterator<Item = &'a CstArgument>) -> Ty {
+    let providers = args.find_map(|arg| match &arg.node {
+        ArgumentP::Named(name, providers) if name.node == "providers" => Some(providers),
+        _ => None,
+    });
+    let providers = match providers.map(|x| &x.node) {
+        Some(ExprP::List(providers)) => providers
+            .iter()
+            .map(|p| match &p.node {
+                ExprP::Identifier(p) => Some(ArcStr::from(p.node.ident.as_str())),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    Ty::custom(TyDependency::new(providers))
+}
+
+/// Attributes buck2 adds to every rule.
+const INTERNAL_ATTRS: &[(&str, fn() -> Ty)] = &[
+    ("name", Ty::string),
+    ("default_target_platform", Ty::any),
+    ("target_compatible_with", Ty::any),
+    ("compatible_with", Ty::any),
+    ("exec_compatible_with", Ty::any),
+    ("visibility", || Ty::list(Ty::string())),
+    ("within_view", || Ty::list(Ty::string())),
+    ("metadata", Ty::any),
+    ("tests", Ty::any),
+    ("modifiers", Ty::any),
+];
+
+fn member_ty(member: &DocMember) -> Ty {
+    match member {
+        DocMember::Property(p) => p.typ.dupe(),
+        DocMember::Function(f) => function_ty(f),
+    }
+}
+
+fn function_ty(f: &DocFunction) -> Ty {
+    fn required(p: &DocParam) -> ParamIsRequired {
+        match p.default_value {
+            Some(_) => ParamIsRequired::No,
+            None => ParamIsRequired::Yes,
+        }
+    }
+    let named = |p: &DocParam| (ArcStr::from(p.name.as_str()), required(p), p.typ.dupe());
+    let params = ParamSpec::new_parts(
+        f.params
+            .pos_only
+            .iter()
+            .map(|p| (required(p), p.typ.dupe())),
+        f.params.pos_or_named.iter().map(named),
+        f.params.args.as_ref().map(|p| p.typ.dupe()),
+        f.params.named_only.iter().map(named),
+        f.params.kwargs.as_ref().map(|p| p.typ.dupe()),
+    );
+    match params {
+        Ok(params) => Ty::function(params, f.ret.typ.dupe()),
+        // Documentation with duplicate parameters.
+        Err(_) => Ty::function(ParamSpec::any(), f.ret.typ.dupe()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use starlark_derive::starlark_module;
+    use starlark_map::small_map::SmallMap;
+
+    use crate as starlark;
+    use crate::docs::DocFunction;
+    use crate::docs::DocItem;
+    use crate::docs::DocMember;
+    use crate::docs::DocModule;
+    use crate::docs::DocParam;
+    use crate::docs::DocParams;
+    use crate::docs::DocProperty;
+    use crate::docs::DocReturn;
+    use crate::docs::DocType;
+    use crate::environment::GlobalsBuilder;
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
+    use crate::typing::oracle::buck::OracleBuck;
+    use crate::typing::AstModuleTypecheck;
+    use crate::typing::Ty;
+    use crate::typing::TypecheckOptions;
+    use crate::values::none::NoneType;
+    use crate::values::Value;
+
+    fn param(name: &str, typ: Ty) -> DocParam {
+        DocParam {
+            name: name.to_owned(),
+            docs: None,
+            typ,
+            default_value: None,
+        }
+    }
+
+    fn oracle() -> OracleBuck {
+        let mut oracle = OracleBuck::new(&DocModule {
+            docs: None,
+            members: SmallMap::from_iter([(
+                "get_label".to_owned(),
+                DocItem::Member(DocMember::Function(DocFunction {
+                    params: DocParams {
+                        pos_only: vec![param("x", Ty::int())],
+                        ..DocParams::default()
+                    },
+                    ret: DocReturn {
+                        docs: None,
+                        typ: Ty::string(),
+                    },
+                    docs: None,
+                })),
+            )]),
+        });
+        oracle.add_rules(&DocModule {
+            docs: None,
+            members: SmallMap::from_iter([(
+                "my_rule".to_owned(),
+                DocItem::Member(DocMember::Function(DocFunction {
+                    params: DocParams {
+                        named_only: vec![
+                            param("name", Ty::string()),
+                            param("srcs", Ty::list(Ty::string())),
+                        ],
+                        ..DocParams::default()
+                    },
+                    ..DocFunction::default()
+                })),
+            )]),
+        });
+        oracle
+    }
+
+    /// Untyped stand-ins for the buck2 globals the test modules use, so the identifiers resolve.
+    #[starlark_module]
+    fn register_rule_globals(globals: &mut GlobalsBuilder) {
+        fn rule<'v>(
+            #[starlark(kwargs)] kwargs: SmallMap<String, Value<'v>>,
+        ) -> anyhow::Result<NoneType> {
+            let _ignore = kwargs;
+            Ok(NoneType)
+        }
+    }
+
+    #[starlark_module]
+    fn register_attrs(globals: &mut GlobalsBuilder) {
+        fn list<'v>(
+            #[starlark(require = pos)] item: Value<'v>,
+            #[starlark(kwargs)] kwargs: SmallMap<String, Value<'v>>,
+        ) -> anyhow::Result<NoneType> {
+            let _ignore = (item, kwargs);
+            Ok(NoneType)
+        }
+
+        fn string<'v>(
+            #[starlark(kwargs)] kwargs: SmallMap<String, Value<'v>>,
+        ) -> anyhow::Result<NoneType> {
+            let _ignore = kwargs;
+            Ok(NoneType)
+        }
+
+        fn int<'v>(
+            #[starlark(kwargs)] kwargs: SmallMap<String, Value<'v>>,
+        ) -> anyhow::Result<NoneType> {
+            let _ignore = kwargs;
+            Ok(NoneType)
+        }
+
+        fn dep<'v>(
+            #[starlark(kwargs)] kwargs: SmallMap<String, Value<'v>>,
+        ) -> anyhow::Result<NoneType> {
+            let _ignore = kwargs;
+            Ok(NoneType)
+        }
+
+        fn toolchain_dep<'v>(
+            #[starlark(kwargs)] kwargs: SmallMap<String, Value<'v>>,
+        ) -> anyhow::Result<NoneType> {
+            let _ignore = kwargs;
+            Ok(NoneType)
+        }
+    }
+
+    #[starlark_module]
+    fn register_providers(globals: &mut GlobalsBuilder) {
+        const DefaultInfo: NoneType = NoneType;
+        const RunInfo: NoneType = NoneType;
+        const ToolchainInfo: NoneType = NoneType;
+    }
+
+    fn typecheck(code: &str) -> Vec<String> {
+        let globals = GlobalsBuilder::standard()
+            .with(register_rule_globals)
+            .with_namespace("attrs", register_attrs)
+            .with(register_providers)
+            .build();
+        let ast =
+            AstModule::parse("rules.bzl", code.to_owned(), &Dialect::AllOptionsInternal).unwrap();
+        let (errors, _, _, _) = ast.typecheck_with_options(
+            &globals,
+            &HashMap::new(),
+            &TypecheckOptions {
+                oracle: Some(Arc::new(oracle())),
+                ..TypecheckOptions::default()
+            },
+        );
+        errors.into_iter().map(|e| format!("{:#}", e)).collect()
+    }
+
+    #[test]
+    fn test_rule_attrs() {
+        let errors = typecheck(
+            r#"
+def _impl(ctx):
+    ctx.attrs.srcs.upper()
+    ctx.attrs.name.upper()
+    ctx.attrs.deps
+
+my_rule = rule(impl = _impl, attrs = {})
+"#,
+        );
+        let errors = errors.join("\n");
+        assert!(
+            errors.contains("The attribute `upper` is not available on the type `list[str]`"),
+            "{}",
+            errors
+        );
+        assert!(
+            errors.contains("The attribute `deps` is not available"),
+            "{}",
+            errors
+        );
+        assert!(!errors.contains("type `str`"), "{}", errors);
+    }
+
+    #[test]
+    fn test_declared_attrs() {
+        let errors = typecheck(
+            r#"
+def _impl(ctx):
+    ctx.attrs.srcs.upper()
+    ctx.attrs.name.upper()
+    ctx.attrs.out.upper()
+    ctx.attrs.count.upper()
+    ctx.attrs.deps
+
+other_rule = rule(impl = _impl, attrs = {
+    "srcs": attrs.list(attrs.string(), default = []),
+    "out": attrs.string(),
+    "count": attrs.int(),
+})
+"#,
+        );
+        let errors = errors.join("\n");
+        assert!(
+            errors.contains("The attribute `upper` is not available on the type `list[str]`"),
+            "{}",
+            errors
+        );
+        assert!(
+            errors.contains("The attribute `upper` is not available on the type `int`"),
+            "{}",
+            errors
+        );
+        assert!(
+            errors.contains("The attribute `deps` is not available"),
+            "{}",
+            errors
+        );
+        assert!(!errors.contains("type `str`"), "{}", errors);
+    }
+
+    #[test]
+    fn test_dependency_providers() {
+        let errors = typecheck(
+            r#"
+def _impl(ctx):
+    ctx.attrs.toolchain[ToolchainInfo]
+    ctx.attrs.toolchain[DefaultInfo]
+    ctx.attrs.toolchain[RunInfo]
+    ctx.attrs.any_dep[RunInfo]
+
+other_rule = rule(impl = _impl, attrs = {
+    "toolchain": attrs.toolchain_dep(providers = [ToolchainInfo]),
+    "any_dep": attrs.dep(),
+})
+"#,
+        )
+        .join("\n");
+        assert!(
+            errors.contains(
+                "Provider `RunInfo` is not required by the attribute, which only requires `ToolchainInfo`"
+            ),
+            "{}",
+            errors
+        );
+        assert_eq!(1, errors.matches("is not required").count(), "{}", errors);
+    }
+
+    #[test]
+    fn test_documented_global() {
+        let errors = typecheck(
+            r#"
+def f():
+    get_label("x")
+"#,
+        )
+        .join("\n");
+        assert!(
+            errors.contains("Expected type `int` but got `str`"),
+            "{}",
+            errors
+        );
+    }
+
+    #[test]
+    fn test_documented_type() {
+        let oracle = OracleBuck::new(&DocModule {
+            docs: None,
+            members: SmallMap::from_iter([(
+                "MyInfo".to_owned(),
+                DocItem::Type(DocType {
+                    docs: None,
+                    members: SmallMap::from_iter([(
+                        "x".to_owned(),
+                        DocMember::Property(DocProperty {
+                            docs: None,
+                            typ: Ty::int(),
+                        }),
+                    )]),
+                    ty: Ty::any_struct(),
+                    constructor: None,
+                }),
+            )]),
+        });
+        assert_eq!(Some(Ty::int()), oracle.attribute(&Ty::any_struct(), "x"));
+        assert_eq!(None, oracle.attribute(&Ty::any(), "x"));
+        assert!(oracle.global("MyInfo").is_some());
+    }
+}
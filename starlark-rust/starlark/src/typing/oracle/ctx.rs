@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::iter;
 
@@ -25,6 +26,7 @@ use starlark_syntax::syntax::ast::BinOp;
 use crate::codemap::CodeMap;
 use crate::codemap::Span;
 use crate::codemap::Spanned;
+use crate::docs::DocMember;
 use crate::typing::basic::TyBasic;
 use crate::typing::call_args::TyCallArgs;
 use crate::typing::callable::TyCallable;
@@ -37,6 +39,9 @@ use crate::typing::error::TypingNoContextOrInternalError;
 use crate::typing::error::TypingOrInternalError;
 use crate::typing::starlark_value::TyStarlarkValue;
 use crate::typing::tuple::TyTuple;
+use crate::typing::type_var::bind_type_vars;
+use crate::typing::type_var::contains_type_vars;
+use crate::typing::type_var::substitute_type_vars;
 use crate::typing::ParamSpec;
 use crate::typing::Ty;
 use crate::typing::TypingBinOp;
@@ -58,6 +63,20 @@ enum TypingOracleCtxError {
     UnexpectedNamedArgument { name: String },
     #[error("Too many positional arguments")]
     TooManyPositionalArguments,
+    #[error("Multiple values for parameter `{name}`")]
+    MultipleValuesForParameter { name: String },
+    #[error("Expected type `{require}` for the elements of `{param}` but got `{got}`")]
+    IncompatibleArgsType {
+        param: String,
+        got: String,
+        require: String,
+    },
+    #[error("Expected type `{require}` for the values of `{param}` but got `{got}`")]
+    IncompatibleKwargsType {
+        param: String,
+        got: String,
+        require: String,
+    },
     #[error("Call arguments incompatible, fn type is `{fun}`")]
     CallArgumentsIncompatible { fun: Ty },
     #[error("Type `{ty}` does not have [] operator or [] cannot accept `{index}`")]
@@ -142,18 +161,18 @@ impl<'a> TypingOracleCtx<'a> {
         }
     }
 
+    /// Validate the arguments of a call, and return the arguments bound to each parameter.
     #[allow(clippy::redundant_pattern_matching)]
-    fn validate_args(
+    fn validate_args<'t>(
         &self,
         params: &ParamSpec,
-        args: &TyCallArgs,
+        args: &'t TyCallArgs,
         span: Span,
-    ) -> Result<(), TypingOrInternalError> {
+    ) -> Result<Vec<Vec<Spanned<&'t Ty>>>, TypingOrInternalError> {
         // Want to figure out which arguments go in which positions
         let mut param_args: Vec<Vec<Spanned<&Ty>>> = vec![vec![]; params.params().len()];
         // The next index a positional parameter might fill
         let mut param_pos = 0;
-        let mut seen_vargs = false;
 
         let TyCallArgs {
             pos: args_pos,
@@ -205,20 +224,32 @@ impl<'a> TypingOracleCtx<'a> {
                 ));
             }
         }
-        if let Some(_) = args_args {
-            seen_vargs = true;
-        }
-        if let Some(_) = args_kwargs {
-            seen_vargs = true;
-        }
+        // `*args` can only fill positional parameters, and `**kwargs` named parameters.
+        // Call sites report the errors of `*args` which are not iterable.
+        let args_item = args_args.as_ref().map(|a| Spanned {
+            span: a.span,
+            node: self.iter_item(a.as_ref()).unwrap_or_else(|_| Ty::any()),
+        });
+        let kwargs_value = args_kwargs.as_ref().map(|k| Spanned {
+            span: k.span,
+            node: match k.node.iter_union() {
+                [TyBasic::Dict(_, v)] => (**v).dupe(),
+                _ => Ty::any(),
+            },
+        });
 
-        for (param, args) in iter::zip(params.params(), param_args) {
+        for (param, args) in iter::zip(params.params(), &param_args) {
             match param.mode {
                 ParamMode::PosOnly(req)
                 | ParamMode::PosOrName(_, req)
                 | ParamMode::NameOnly(_, req) => match args.as_slice() {
                     [] => {
-                        if req == ParamIsRequired::Yes && !seen_vargs {
+                        let filled = match param.mode {
+                            ParamMode::PosOnly(_) => args_item.is_some(),
+                            ParamMode::NameOnly(..) => kwargs_value.is_some(),
+                            _ => args_item.is_some() || kwargs_value.is_some(),
+                        };
+                        if req == ParamIsRequired::Yes && !filled {
                             return Err(self.mk_error_as_maybe_internal(
                                 span,
                                 TypingOracleCtxError::MissingRequiredParameter {
@@ -228,29 +259,55 @@ impl<'a> TypingOracleCtx<'a> {
                         }
                     }
                     [arg] => self.validate_type(*arg, &param.ty)?,
-                    [_, _, ..] => {
-                        return Err(TypingOrInternalError::Internal(InternalError::msg(
-                            "Multiple arguments bound to parameter",
-                            span,
-                            self.codemap,
-                        )));
+                    [_, arg, ..] => {
+                        // Passed by position and by name.
+                        return Err(self.mk_error_as_maybe_internal(
+                            arg.span,
+                            TypingOracleCtxError::MultipleValuesForParameter {
+                                name: param.name_display().to_owned(),
+                            },
+                        ));
                     }
                 },
                 ParamMode::Args => {
                     for ty in args {
                         // For an arg, we require the type annotation to be inner value,
                         // rather than the outer (which is always a tuple)
-                        self.validate_type(ty, &param.ty)?;
+                        self.validate_type(*ty, &param.ty)?;
+                    }
+                    if let Some(item) = &args_item {
+                        if !self.intersects(&item.node, &param.ty)? {
+                            return Err(self.mk_error_as_maybe_internal(
+                                item.span,
+                                TypingOracleCtxError::IncompatibleArgsType {
+                                    param: param.name_display().to_owned(),
+                                    got: item.node.to_string(),
+                                    require: param.ty.to_string(),
+                                },
+                            ));
+                        }
                     }
                 }
                 ParamMode::Kwargs => {
                     for ty in args {
-                        self.validate_type(ty, &param.ty)?;
+                        self.validate_type(*ty, &param.ty)?;
+                    }
+                    if let Some(value) = &kwargs_value {
+                        if !self.intersects(&value.node, &param.ty)? {
+                            return Err(self.mk_error_as_maybe_internal(
+                                value.span,
+                                TypingOracleCtxError::IncompatibleKwargsType {
+                                    param: param.name_display().to_owned(),
+                                    got: value.node.to_string(),
+                                    require: param.ty.to_string(),
+                                },
+                            ));
+                        }
                     }
                 }
             }
         }
-        Ok(())
+        Ok(param_args)
     }
 
     pub(crate) fn validate_fn_call(
@@ -259,8 +316,17 @@ impl<'a> TypingOracleCtx<'a> {
         fun: &TyCallable,
         args: &TyCallArgs,
     ) -> Result<Ty, TypingOrInternalError> {
-        self.validate_args(fun.params(), args, span)?;
-        Ok(fun.result().dupe())
+        let param_args = self.validate_args(fun.params(), args, span)?;
+        if !contains_type_vars(fun.result()) {
+            return Ok(fun.result().dupe());
+        }
+        let mut bindings = SmallMap::new();
+        for (param, args) in iter::zip(fun.params().params(), &param_args) {
+            for arg in args {
+                bind_type_vars(&param.ty, arg.node, &mut bindings);
+            }
+        }
+        Ok(substitute_type_vars(fun.result(), &bindings))
     }
 
     #[allow(clippy::collapsible_else_if)]
@@ -514,6 +580,49 @@ impl<'a> TypingOracleCtx<'a> {
         }
     }
 
+    fn attrs_basic(&self, ty: &TyBasic) -> Vec<(String, Option<DocMember>)> {
+        match ty {
+            TyBasic::Any
+            | TyBasic::Callable(_)
+            | TyBasic::Iter(_)
+            | TyBasic::Type
+            | TyBasic::Tuple(_) => Vec::new(),
+            TyBasic::StarlarkValue(s) => s.attrs_with_docs(),
+            TyBasic::List(_) => TyStarlarkValue::new::<List>().attrs_with_docs(),
+            TyBasic::Dict(..) => TyStarlarkValue::new::<MutableDict>().attrs_with_docs(),
+            TyBasic::Set(_) => TyStarlarkValue::new::<MutableSet>().attrs_with_docs(),
+            TyBasic::Custom(custom) => custom
+                .0
+                .attribute_names_dyn()
+                .into_iter()
+                .map(|name| (name, None))
+                .collect(),
+        }
+    }
+
+    /// Attributes available on a value of a type, with their types and documentation,
+    /// sorted by name. For unions, attributes available on any of the alternatives.
+    pub(crate) fn attributes(&self, ty: &Ty) -> Vec<(String, Ty, Option<DocMember>)> {
+        let mut attrs: BTreeMap<String, Option<DocMember>> = BTreeMap::new();
+        for basic in ty.iter_union() {
+            for (name, doc) in self.attrs_basic(basic) {
+                let entry = attrs.entry(name).or_default();
+                if entry.is_none() {
+                    *entry = doc;
+                }
+            }
+        }
+        attrs
+            .into_iter()
+            .filter_map(|(name, doc)| {
+                let attr_ty = ty
+                    .typecheck_union_simple(|basic| self.expr_dot_basic(basic, &name))
+                    .ok()?;
+                Some((name, attr_ty, doc))
+            })
+            .collect()
+    }
+
     pub(crate) fn expr_dot(&self, span: Span, array: &Ty, attr: &str) -> Result<Ty, TypingError> {
         match array.typecheck_union_simple(|basic| self.expr_dot_basic(basic, attr)) {
             Ok(x) => Ok(x),
@@ -918,7 +1027,7 @@ impl<'a> TypingOracleCtx<'a> {
             },
             Span::default(),
         ) {
-            Ok(()) => Ok(true),
+            Ok(_) => Ok(true),
             Err(TypingOrInternalError::Internal(e)) => Err(e),
             Err(TypingOrInternalError::Typing(_)) => Ok(false),
         }
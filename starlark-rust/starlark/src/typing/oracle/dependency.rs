@@ -0,0 +1,133 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use allocative::Allocative;
+use thiserror::Error;
+
+use crate::typing::custom::TyCustomImpl;
+use crate::typing::error::TypingNoContextError;
+use crate::typing::error::TypingNoContextOrInternalError;
+use crate::typing::Ty;
+use crate::typing::TyBasic;
+use crate::typing::TypingOracleCtx;
+use crate::util::arc_str::ArcStr;
+use crate::values::typing::type_compiled::alloc::TypeMatcherAlloc;
+use crate::values::typing::type_compiled::matcher::TypeMatcher;
+use crate::values::Value;
+
+/// Name of the buck2 dependency type.
+const DEPENDENCY: &str = "dependency";
+
+/// Provider every dependency has.
+const DEFAULT_INFO: &str = "DefaultInfo";
+
+#[derive(Debug, Error)]
+pub(crate) enum TyDependencyError {
+    #[error(
+        "Provider `{provider}` is not required by the attribute, which only requires {required}"
+    )]
+    ProviderNotRequired { provider: String, required: String },
+}
+
+/// Value of a dependency attribute, like `attrs.dep(providers = [SomeInfo])`, with the names
+/// of the providers the attribute requires its targets to provide.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Allocative)]
+pub(crate) struct TyDependency {
+    /// Sorted. Empty if the attribute does not require providers, or if they are not known.
+    providers: Vec<ArcStr>,
+}
+
+impl TyDependency {
+    pub(crate) fn new(providers: impl IntoIterator<Item = ArcStr>) -> TyDependency {
+        let mut providers: Vec<ArcStr> = providers.into_iter().collect();
+        providers.sort();
+        providers.dedup();
+        TyDependency { providers }
+    }
+
+    pub(crate) fn from_ty(ty: &Ty) -> Option<&TyDependency> {
+        match ty.iter_union() {
+            [TyBasic::Custom(custom)] => custom.0.as_any().downcast_ref::<TyDependency>(),
+            _ => None,
+        }
+    }
+
+    /// Error if `dep[provider]` may fail because the attribute does not require the provider.
+    pub(crate) fn check_provider(&self, provider: &str) -> Result<(), TyDependencyError> {
+        if self.providers.is_empty()
+            || provider == DEFAULT_INFO
+            || self.providers.iter().any(|p| p.as_str() == provider)
+        {
+            Ok(())
+        } else {
+            Err(TyDependencyError::ProviderNotRequired {
+                provider: provider.to_owned(),
+                required: self
+                    .providers
+                    .iter()
+                    .map(|p| format!("`{}`", p))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            })
+        }
+    }
+}
+
+impl TyCustomImpl for TyDependency {
+    fn as_name(&self) -> Option<&str> {
+        Some(DEPENDENCY)
+    }
+
+    fn index(
+        &self,
+        _item: &TyBasic,
+        _ctx: &TypingOracleCtx,
+    ) -> Result<Ty, TypingNoContextOrInternalError> {
+        Ok(Ty::any())
+    }
+
+    fn attribute(&self, _attr: &str) -> Result<Ty, TypingNoContextError> {
+        Ok(Ty::any())
+    }
+
+    fn intersects_with(&self, other: &TyBasic) -> bool {
+        other.as_name() == Some(DEPENDENCY)
+    }
+
+    fn matcher<T: TypeMatcherAlloc>(&self, factory: T) -> T::Result {
+        #[derive(Allocative, Debug, Clone)]
+        struct DependencyMatcher;
+
+        impl TypeMatcher for DependencyMatcher {
+            fn matches(&self, value: Value) -> bool {
+                value.get_type() == DEPENDENCY
+            }
+        }
+
+        factory.alloc(DependencyMatcher)
+    }
+}
+
+impl Display for TyDependency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", DEPENDENCY)
+    }
+}
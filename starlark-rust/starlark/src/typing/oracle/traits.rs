@@ -18,6 +18,7 @@
 // This makes for a better API.
 #![allow(clippy::result_unit_err)]
 
+use allocative::Allocative;
 use dupe::Dupe;
 
 /// Unary operator for typechecker.
@@ -35,7 +36,7 @@ pub enum TypingUnOp {
 }
 
 /// Binary operator for typechecker.
-#[derive(Copy, Clone, Dupe, Eq, PartialEq, derive_more::Display, Debug)]
+#[derive(Copy, Clone, Dupe, Eq, PartialEq, derive_more::Display, Debug, Allocative)]
 pub enum TypingBinOp {
     /// `+`.
     #[display("+")]
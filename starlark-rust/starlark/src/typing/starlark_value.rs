@@ -16,6 +16,7 @@
  */
 
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Debug;
 use std::fmt::Display;
@@ -28,6 +29,7 @@ use allocative::Allocative;
 use dupe::Dupe;
 use starlark_syntax::codemap::Span;
 
+use crate::docs::DocMember;
 use crate::typing::error::TypingError;
 use crate::typing::error::TypingNoContextError;
 use crate::typing::ty::TypeRenderConfig;
@@ -265,6 +267,27 @@ impl TyStarlarkValue {
         Err(TypingNoContextError)
     }
 
+    /// Names of the attributes provided by methods, with their documentation when available.
+    /// Attributes resolved by `attr_ty` cannot be listed.
+    pub(crate) fn attrs_with_docs(self) -> Vec<(String, Option<DocMember>)> {
+        let Some(methods) = (self.vtable.vtable.get_methods)() else {
+            return Vec::new();
+        };
+        let mut docs: HashMap<String, DocMember> = methods
+            .documentation(Ty::basic(TyBasic::StarlarkValue(self)))
+            .members
+            .into_iter()
+            .collect();
+        methods
+            .names()
+            .into_iter()
+            .map(|name| {
+                let doc = docs.remove(&name);
+                (name, doc)
+            })
+            .collect()
+    }
+
     pub(crate) fn attr(self, name: &str) -> Result<Ty, TypingNoContextError> {
         if let Ok(ty) = self.attr_from_methods(name) {
             return Ok(ty);
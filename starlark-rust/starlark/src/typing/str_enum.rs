@@ -0,0 +1,464 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Type of strings with a fixed set of values.
+
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use allocative::Allocative;
+
+use crate::typing::custom::TyCustomImpl;
+use crate::typing::error::TypingNoContextError;
+use crate::typing::error::TypingNoContextOrInternalError;
+use crate::typing::Ty;
+use crate::typing::TyBasic;
+use crate::typing::TyStarlarkValue;
+use crate::typing::TypingBinOp;
+use crate::typing::TypingOracleCtx;
+use crate::values::string::str_type::StarlarkStr;
+use crate::values::typing::type_compiled::alloc::TypeMatcherAlloc;
+use crate::values::typing::type_compiled::matcher::TypeMatcher;
+use crate::values::Value;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum TyStrEnumError {
+    #[error("Comparison of `{ty}` with `{value:?}` is always `{result}`")]
+    ImpossibleComparison {
+        ty: TyStrEnum,
+        value: String,
+        result: &'static str,
+    },
+    #[error("Value `{value:?}` is not one of `{ty}`")]
+    InvalidValue { ty: TyStrEnum, value: String },
+    #[error("`if` chain over `{ty}` does not handle {missing} and has no `else`")]
+    NonExhaustive { ty: TyStrEnum, missing: String },
+    #[error("Dict indexed with `{ty}` has no key {missing}")]
+    NonExhaustiveDict { ty: TyStrEnum, missing: String },
+}
+
+/// A string which is one of the given values, created with [`Ty::enum_of`].
+///
+/// At compile time it is a `str` with extra information: comparisons against string
+/// literals not in the set and non-exhaustive `if`/`elif` chains over it are reported.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Allocative)]
+pub struct TyStrEnum {
+    /// Sorted and deduplicated.
+    values: Vec<String>,
+}
+
+impl TyStrEnum {
+    pub(crate) fn new(values: impl IntoIterator<Item = impl Into<String>>) -> TyStrEnum {
+        let mut values: Vec<String> = values.into_iter().map(|v| v.into()).collect();
+        values.sort();
+        values.dedup();
+        TyStrEnum { values }
+    }
+
+    /// The possible values of the string, sorted.
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+
+    pub(crate) fn contains(&self, value: &str) -> bool {
+        self.values
+            .binary_search_by(|v| v.as_str().cmp(value))
+            .is_ok()
+    }
+
+    /// The string enum if the type is exactly one.
+    pub(crate) fn from_ty(ty: &Ty) -> Option<&TyStrEnum> {
+        match ty.iter_union() {
+            [basic] => Self::from_basic(basic),
+            _ => None,
+        }
+    }
+
+    fn from_basic(ty: &TyBasic) -> Option<&TyStrEnum> {
+        match ty {
+            TyBasic::Custom(custom) => custom.0.as_any().downcast_ref::<TyStrEnum>(),
+            _ => None,
+        }
+    }
+
+    /// Error if the string literal is not a valid value of this type.
+    pub(crate) fn check_value(&self, value: &str) -> Result<(), TyStrEnumError> {
+        if self.contains(value) {
+            Ok(())
+        } else {
+            Err(TyStrEnumError::InvalidValue {
+                ty: self.clone(),
+                value: value.to_owned(),
+            })
+        }
+    }
+
+    /// Error if comparing a value of this type to the string literal with `==` or `!=`
+    /// has a known result.
+    pub(crate) fn check_comparison(&self, value: &str, eq: bool) -> Result<(), TyStrEnumError> {
+        if self.contains(value) {
+            Ok(())
+        } else {
+            Err(TyStrEnumError::ImpossibleComparison {
+                ty: self.clone(),
+                value: value.to_owned(),
+                result: if eq { "False" } else { "True" },
+            })
+        }
+    }
+
+    /// The values not handled, formatted for an error, if any.
+    fn missing<'a>(&self, handled: impl IntoIterator<Item = &'a str>) -> Option<String> {
+        let handled: Vec<&str> = handled.into_iter().collect();
+        let missing: Vec<String> = self
+            .values
+            .iter()
+            .filter(|v| !handled.contains(&v.as_str()))
+            .map(|v| format!("{:?}", v))
+            .collect();
+        if missing.is_empty() {
+            None
+        } else {
+            Some(missing.join(", "))
+        }
+    }
+
+    /// Error if the values handled by a chain of comparisons without `else` are not all
+    /// the values of this type.
+    pub(crate) fn check_exhaustive<'a>(
+        &self,
+        handled: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), TyStrEnumError> {
+        match self.missing(handled) {
+            None => Ok(()),
+            Some(missing) => Err(TyStrEnumError::NonExhaustive {
+                ty: self.clone(),
+                missing,
+            }),
+        }
+    }
+
+    /// Error if a dict literal with the given keys, indexed with a value of this type,
+    /// has no key for some of the values.
+    pub(crate) fn check_dict_keys<'a>(
+        &self,
+        keys: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), TyStrEnumError> {
+        match self.missing(keys) {
+            None => Ok(()),
+            Some(missing) => Err(TyStrEnumError::NonExhaustiveDict {
+                ty: self.clone(),
+                missing,
+            }),
+        }
+    }
+
+    const fn base() -> TyStarlarkValue {
+        TyStarlarkValue::new::<StarlarkStr>()
+    }
+}
+
+impl TyCustomImpl for TyStrEnum {
+    fn as_name(&self) -> Option<&str> {
+        Some(Self::base().as_name())
+    }
+
+    fn bin_op(
+        &self,
+        bin_op: TypingBinOp,
+        rhs: &TyBasic,
+        _ctx: &TypingOracleCtx,
+    ) -> Result<Ty, TypingNoContextOrInternalError> {
+        // Other string enums are just strings for the purpose of operators.
+        let string = TyBasic::string();
+        let rhs = match Self::from_basic(rhs) {
+            Some(_) => &string,
+            None => rhs,
+        };
+        Ok(Self::base().bin_op(bin_op, rhs)?)
+    }
+
+    fn iter_item(&self) -> Result<Ty, TypingNoContextError> {
+        Self::base().iter_item()
+    }
+
+    fn index(
+        &self,
+        item: &TyBasic,
+        _ctx: &TypingOracleCtx,
+    ) -> Result<Ty, TypingNoContextOrInternalError> {
+        Ok(Self::base().index(item)?)
+    }
+
+    fn attribute(&self, attr: &str) -> Result<Ty, TypingNoContextError> {
+        Self::base().attr(attr)
+    }
+
+    fn attribute_names(&self) -> Vec<String> {
+        Self::base()
+            .attrs_with_docs()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    fn union2(x: Arc<Self>, other: Arc<Self>) -> Result<Arc<Self>, (Arc<Self>, Arc<Self>)> {
+        if x == other {
+            Ok(x)
+        } else {
+            Ok(Arc::new(TyStrEnum::new(
+                x.values.iter().chain(&other.values).cloned(),
+            )))
+        }
+    }
+
+    fn intersects(x: &Self, y: &Self) -> bool {
+        x.values.iter().any(|v| y.contains(v))
+    }
+
+    fn intersects_with(&self, other: &TyBasic) -> bool {
+        match other {
+            TyBasic::StarlarkValue(other) => other.is_str(),
+            _ => false,
+        }
+    }
+
+    fn matcher<T: TypeMatcherAlloc>(&self, factory: T) -> T::Result {
+        #[derive(Allocative, Debug, Clone)]
+        struct StrEnumMatcher(TyStrEnum);
+
+        impl TypeMatcher for StrEnumMatcher {
+            fn matches(&self, value: Value) -> bool {
+                value.unpack_str().is_some_and(|s| self.0.contains(s))
+            }
+        }
+
+        factory.alloc(StrEnumMatcher(self.clone()))
+    }
+}
+
+impl Display for TyStrEnum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        display_container::fmt_container(
+            f,
+            "enum_of(",
+            ")",
+            self.values.iter().map(|v| format!("{:?}", v)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use allocative::Allocative;
+    use starlark_derive::starlark_module;
+    use starlark_derive::starlark_value;
+    use starlark_derive::NoSerialize;
+    use starlark_derive::ProvidesStaticType;
+
+    use crate as starlark;
+    use crate::assert::Assert;
+    use crate::environment::GlobalsBuilder;
+    use crate::typing::Ty;
+    use crate::values::AllocValue;
+    use crate::values::Heap;
+    use crate::values::StarlarkValue;
+    use crate::values::Value;
+
+    #[derive(
+        Debug,
+        derive_more::Display,
+        ProvidesStaticType,
+        Allocative,
+        NoSerialize
+    )]
+    #[display("build_mode_type")]
+    struct BuildModeType;
+
+    impl<'v> AllocValue<'v> for BuildModeType {
+        fn alloc_value(self, heap: &'v Heap) -> Value<'v> {
+            heap.alloc_simple(self)
+        }
+    }
+
+    #[starlark_value(type = "build_mode_type")]
+    impl<'v> StarlarkValue<'v> for BuildModeType {
+        fn eval_type(&self) -> Option<Ty> {
+            Some(Ty::enum_of(["debug", "release"]))
+        }
+    }
+
+    #[starlark_module]
+    fn globals(globals: &mut GlobalsBuilder) {
+        fn build_mode_type() -> anyhow::Result<BuildModeType> {
+            Ok(BuildModeType)
+        }
+    }
+
+    fn assert() -> Assert<'static> {
+        let mut a = Assert::new();
+        a.globals_add(globals);
+        a
+    }
+
+    #[test]
+    fn test_str_enum_is_str() {
+        assert().pass(
+            r#"
+BuildMode = build_mode_type()
+
+def f(mode: BuildMode) -> str:
+    return mode.upper() + "_" + mode
+
+def g(mode: BuildMode):
+    f(mode)
+    f("debug")
+
+g("release")
+"#,
+        );
+    }
+
+    #[test]
+    fn test_str_enum_runtime_check() {
+        assert().fail(
+            r#"
+BuildMode = build_mode_type()
+
+def f(mode: BuildMode):
+    pass
+
+f("fastbuild")
+"#,
+            "does not match the type annotation",
+        );
+    }
+
+    #[test]
+    fn test_str_enum_impossible_comparison() {
+        let a = assert();
+        a.fail(
+            r#"
+BuildMode = build_mode_type()
+
+def f(mode: BuildMode) -> bool:
+    return mode == "relase"
+"#,
+            r#"Comparison of `enum_of("debug", "release")` with `"relase"` is always `False`"#,
+        );
+        a.fail(
+            r#"
+BuildMode = build_mode_type()
+
+def f(mode: BuildMode) -> bool:
+    return "dbg" != mode
+"#,
+            r#"Comparison of `enum_of("debug", "release")` with `"dbg"` is always `True`"#,
+        );
+    }
+
+    #[test]
+    fn test_str_enum_exhaustive() {
+        let a = assert();
+        a.pass(
+            r#"
+BuildMode = build_mode_type()
+
+def f(mode: BuildMode) -> int:
+    if mode == "debug":
+        return 1
+    elif "release" == mode:
+        return 2
+    return 3
+
+def g(mode: BuildMode) -> int:
+    if mode == "debug":
+        return 1
+    elif mode in ("release",):
+        return 2
+    return 3
+
+def h(mode: BuildMode, s: str) -> int:
+    if mode == "debug":
+        return 1
+    elif s == "x":
+        return 2
+    return 3
+
+def k(mode: BuildMode) -> int:
+    if mode == "debug":
+        return 1
+    elif mode == "debug":
+        return 2
+    else:
+        return 3
+"#,
+        );
+        a.fail(
+            r#"
+BuildMode = build_mode_type()
+
+def f(mode: BuildMode) -> int:
+    if mode == "debug":
+        return 1
+    elif mode == "debug" or mode == "debug":
+        return 2
+    return 3
+"#,
+            r#"`if` chain over `enum_of("debug", "release")` does not handle "release" and has no `else`"#,
+        );
+    }
+
+    #[test]
+    fn test_str_enum_dict_dispatch() {
+        let a = assert();
+        a.pass(
+            r#"
+BuildMode = build_mode_type()
+
+def f(mode: BuildMode) -> str:
+    return {"debug": "-O0", "release": "-O2"}[mode]
+"#,
+        );
+        a.fail(
+            r#"
+BuildMode = build_mode_type()
+
+def f(mode: BuildMode) -> str:
+    return {"debug": "-O0"}[mode]
+"#,
+            r#"Dict indexed with `enum_of("debug", "release")` has no key "release""#,
+        );
+    }
+
+    #[test]
+    fn test_typing_literal_exhaustive() {
+        assert().fail(
+            r#"
+def f(mode: typing.Literal["debug", "release", "profile"]) -> int:
+    if mode == "debug":
+        return 1
+    elif mode == "release":
+        return 2
+    return 3
+"#,
+            r#"`if` chain over `enum_of("debug", "profile", "release")` does not handle "profile" and has no `else`"#,
+        );
+    }
+}
@@ -35,9 +35,16 @@ use crate::util::arc_str::ArcStr;
 use crate::values::structs::StructRef;
 use crate::values::typing::type_compiled::alloc::TypeMatcherAlloc;
 use crate::values::typing::type_compiled::matcher::TypeMatcher;
+use crate::values::typing::type_compiled::matcher::TypeMatcherBox;
+use crate::values::typing::type_compiled::matcher::TypeMatcherBoxAlloc;
 use crate::values::Value;
 
 /// Struct type.
+///
+/// Struct types are structural: two struct types intersect if the types of their common fields
+/// intersect, and each has all the fields of the other, unless the other may have extra fields.
+/// So a struct type with `extra` is a protocol accepted by any struct providing at least its
+/// fields, and a struct type without `extra` is strict and rejects structs with other fields.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Allocative)]
 pub struct TyStruct {
     /// The fields that are definitely present in the struct, with their types.
@@ -89,6 +96,22 @@ impl TyCustomImpl for TyStruct {
         }
     }
 
+    fn intersects(x: &Self, y: &Self) -> bool {
+        for (name, x_ty) in &x.fields {
+            match y.fields.get(name) {
+                // Errors are internal, so be lenient.
+                Some(y_ty) => {
+                    if !x_ty.check_intersects(y_ty).unwrap_or(true) {
+                        return false;
+                    }
+                }
+                None if y.extra => {}
+                None => return false,
+            }
+        }
+        x.extra || y.fields.keys().all(|name| x.fields.contains_key(name))
+    }
+
     fn union2(a: Arc<Self>, b: Arc<Self>) -> Result<Arc<Self>, (Arc<Self>, Arc<Self>)> {
         if a == b {
             // Fast path.
@@ -118,7 +141,46 @@ impl TyCustomImpl for TyStruct {
             }
         }
 
-        factory.alloc(StructMatcher)
+        #[derive(Allocative, Debug, Clone)]
+        struct StructFieldsMatcher {
+            fields: Vec<(ArcStr, TypeMatcherBox)>,
+            extra: bool,
+        }
+
+        impl TypeMatcher for StructFieldsMatcher {
+            fn matches(&self, value: Value) -> bool {
+                let Some(value) = StructRef::from_value(value) else {
+                    return false;
+                };
+                let mut matched = 0;
+                for (name, field) in value.iter() {
+                    match self
+                        .fields
+                        .iter()
+                        .find(|(n, _)| n.as_str() == name.as_str())
+                    {
+                        Some((_, matcher)) if matcher.matches(field) => matched += 1,
+                        Some(_) => return false,
+                        None if self.extra => {}
+                        None => return false,
+                    }
+                }
+                matched == self.fields.len()
+            }
+        }
+
+        if self.fields.is_empty() && self.extra {
+            factory.alloc(StructMatcher)
+        } else {
+            factory.alloc(StructFieldsMatcher {
+                fields: self
+                    .fields
+                    .iter()
+                    .map(|(name, ty)| (name.dupe(), TypeMatcherBoxAlloc.ty(ty)))
+                    .collect(),
+                extra: self.extra,
+            })
+        }
     }
 }
 
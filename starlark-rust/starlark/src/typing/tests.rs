@@ -46,11 +46,19 @@ use crate::values::typing::StarlarkIter;
 use crate::values::Value;
 use crate::values::ValueOfUnchecked;
 
+mod any_args;
 mod call;
 mod callable;
+mod completion;
+mod coverage;
+mod hover;
+mod lambda;
 mod list;
+mod narrow;
+mod profile;
 mod special_function;
 mod tuple;
+mod type_var;
 mod types;
 
 #[derive(Default)]
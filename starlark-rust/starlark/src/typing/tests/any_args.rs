@@ -0,0 +1,71 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+
+use crate::environment::Globals;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+use crate::typing::Approximation;
+use crate::typing::AstModuleTypecheck;
+use crate::typing::TypecheckOptions;
+
+fn any_args(code: &str, report_any_args: bool) -> Vec<String> {
+    let ast = AstModule::parse(
+        "any_args.star",
+        code.to_owned(),
+        &Dialect::AllOptionsInternal,
+    )
+    .unwrap();
+    let (errors, _, _, approximations) = ast.typecheck_with_options(
+        &Globals::standard(),
+        &HashMap::new(),
+        &TypecheckOptions {
+            report_any_args,
+            ..TypecheckOptions::default()
+        },
+    );
+    assert!(errors.is_empty(), "{:?}", errors);
+    approximations
+        .into_iter()
+        .filter(|a| a.category == Approximation::ANY_ARG)
+        .map(|a| a.message)
+        .collect()
+}
+
+const CODE: &str = r#"def f(x: int, y: str, z):
+    pass
+def g(a, b):
+    f(a, y = b, z = a)
+    f(1, y = "", z = a)
+"#;
+
+#[test]
+fn test_any_args() {
+    assert_eq!(
+        vec![
+            "`x: int` at any_args.star:4:7-8".to_owned(),
+            "`y: str` at any_args.star:4:10-15".to_owned(),
+        ],
+        any_args(CODE, true)
+    );
+}
+
+#[test]
+fn test_any_args_not_requested() {
+    assert!(any_args(CODE, false).is_empty());
+}
@@ -142,3 +142,23 @@ def test():
 "#,
     );
 }
+
+#[test]
+fn test_call_star_args_kwargs() {
+    TypeCheck::new().check(
+        "call_star_args_kwargs",
+        r#"
+def foo(*args: str, **kwargs: int):
+    pass
+
+def bar(x, *, y):
+    pass
+
+def test(xs: list[int], d: dict[str, str]):
+    foo(*xs)
+    foo(**d)
+    bar(1, x = 2, y = 3)
+    bar(1, *xs)
+"#,
+    );
+}
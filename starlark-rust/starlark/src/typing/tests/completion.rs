@@ -0,0 +1,102 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+
+use crate::codemap::Pos;
+use crate::codemap::Span;
+use crate::environment::Globals;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+use crate::typing::AstModuleTypecheck;
+use crate::typing::AttributeCompletion;
+use crate::typing::ParamSpec;
+use crate::typing::Ty;
+
+/// Completions with the cursor on the first occurrence of `cursor` in `code`.
+fn attribute_completions(code: &str, cursor: &str) -> Vec<AttributeCompletion> {
+    let ast = AstModule::parse(
+        "completion.star",
+        code.to_owned(),
+        &Dialect::AllOptionsInternal,
+    )
+    .unwrap();
+    let (errors, typemap, _, _) = ast.typecheck(&Globals::standard(), &HashMap::new());
+    assert!(errors.is_empty(), "{:?}", errors);
+    let begin = code.find(cursor).unwrap() as u32;
+    typemap.attribute_completions(Span::new(
+        Pos::new(begin),
+        Pos::new(begin + cursor.len() as u32),
+    ))
+}
+
+#[test]
+fn test_attribute_completions_list() {
+    let completions = attribute_completions(
+        r#"
+def test(x: list[int]):
+    x.append(1)
+"#,
+        "append",
+    );
+    let names: Vec<&str> = completions.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(
+        vec![
+            "append", "clear", "extend", "index", "insert", "pop", "remove"
+        ],
+        names
+    );
+    let pop = completions.iter().find(|c| c.name == "pop").unwrap();
+    assert_eq!(
+        Ty::function(ParamSpec::pos_only([], [Ty::int()]), Ty::int()),
+        pop.ty
+    );
+    let append = completions.iter().find(|c| c.name == "append").unwrap();
+    assert!(append.docs.is_some());
+}
+
+#[test]
+fn test_attribute_completions_nested() {
+    let completions = attribute_completions(
+        r#"
+def test(x: dict[str, list[str]]):
+    x.values().pop().index("b")
+"#,
+        "index",
+    );
+    assert!(completions.iter().any(|c| c.name == "append"));
+    assert!(!completions.iter().any(|c| c.name == "keys"));
+    let index = completions.iter().find(|c| c.name == "index").unwrap();
+    assert_eq!(
+        Ty::function(ParamSpec::pos_only([Ty::string()], [Ty::int()]), Ty::int()),
+        index.ty
+    );
+}
+
+#[test]
+fn test_attribute_completions_not_attribute() {
+    assert!(
+        attribute_completions(
+            r#"
+def test(x: list[int]):
+    x.append(1)
+"#,
+            "test",
+        )
+        .is_empty()
+    );
+}
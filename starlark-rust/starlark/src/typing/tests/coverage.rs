@@ -0,0 +1,79 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+
+use crate::environment::Globals;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+use crate::typing::AstModuleTypecheck;
+use crate::typing::TypeCoverage;
+
+fn coverage(code: &str) -> TypeCoverage {
+    let ast = AstModule::parse(
+        "coverage.star",
+        code.to_owned(),
+        &Dialect::AllOptionsInternal,
+    )
+    .unwrap();
+    let (errors, typemap, _, _) = ast.typecheck(&Globals::standard(), &HashMap::new());
+    assert!(errors.is_empty(), "{:?}", errors);
+    typemap.coverage()
+}
+
+#[test]
+fn test_coverage_annotated() {
+    let coverage = coverage(
+        r#"
+def f(x: int) -> int:
+    return x + 1
+"#,
+    );
+    assert!(coverage.expressions > 0);
+    assert_eq!(0, coverage.any);
+    assert_eq!(0.0, coverage.any_fraction());
+}
+
+#[test]
+fn test_coverage_unannotated() {
+    let annotated = coverage(
+        r#"
+def f(x: int) -> int:
+    return x + 1
+"#,
+    );
+    let mut unannotated = coverage(
+        r#"
+def f(x):
+    return x + 1
+"#,
+    );
+    assert!(unannotated.any > 0);
+    assert!(unannotated.any_fraction() > 0.0);
+    assert!(unannotated.any_fraction() <= 1.0);
+
+    let any = unannotated.any;
+    unannotated += annotated;
+    assert_eq!(any, unannotated.any);
+    assert!(unannotated.expressions > annotated.expressions);
+}
+
+#[test]
+fn test_coverage_empty() {
+    assert_eq!(TypeCoverage::default(), coverage("x = 1"));
+    assert_eq!(0.0, TypeCoverage::default().any_fraction());
+}
@@ -0,0 +1,74 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+
+use crate::codemap::Pos;
+use crate::codemap::Span;
+use crate::environment::Globals;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+use crate::typing::AstModuleTypecheck;
+use crate::typing::Ty;
+
+/// Type at the first occurrence of `cursor` in `code`, and the text typed.
+fn type_at(code: &str, cursor: &str) -> Option<(String, Ty)> {
+    let ast =
+        AstModule::parse("hover.star", code.to_owned(), &Dialect::AllOptionsInternal).unwrap();
+    let (errors, typemap, _, _) = ast.typecheck(&Globals::standard(), &HashMap::new());
+    assert!(errors.is_empty(), "{:?}", errors);
+    let begin = code.find(cursor).unwrap() as u32;
+    let (span, ty) = typemap.type_at(Span::new(
+        Pos::new(begin),
+        Pos::new(begin + cursor.len() as u32),
+    ))?;
+    Some((
+        code[span.begin().get() as usize..span.end().get() as usize].to_owned(),
+        ty.clone(),
+    ))
+}
+
+#[test]
+fn test_type_at() {
+    let code = r#"
+def test(xs: dict[str, list[str]]):
+    ys = xs.values()
+    return len(ys[0])
+"#;
+    // Parameter.
+    assert_eq!(
+        Some((
+            "xs".to_owned(),
+            Ty::dict(Ty::string(), Ty::list(Ty::string()))
+        )),
+        type_at(code, "xs")
+    );
+    // Assignment.
+    assert_eq!(
+        Some(("ys".to_owned(), Ty::list(Ty::list(Ty::string())))),
+        type_at(code, "ys")
+    );
+    assert_eq!(
+        Some(("ys[0]".to_owned(), Ty::list(Ty::string()))),
+        type_at(code, "ys[0]")
+    );
+    assert_eq!(
+        Some(("len(ys[0])".to_owned(), Ty::int())),
+        type_at(code, "len(ys[0])")
+    );
+    assert_eq!(None, type_at(code, "\ndef"));
+}
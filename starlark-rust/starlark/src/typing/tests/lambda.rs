@@ -0,0 +1,71 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+//! Tests for lambdas and comprehension variables.
+
+use crate::assert::Assert;
+use crate::typing::tests::TypeCheck;
+
+#[test]
+fn test_lambda_and_comprehension_types() {
+    TypeCheck::new().ty("a").ty("b").ty("c").check(
+        "lambda_and_comprehension_types",
+        r#"
+def f(d: dict[str, int]):
+    g = lambda (x: int): str(x)
+    a = g(1)
+    b = [k for k, v in d.items()]
+    c = [v for k, v in d.items()]
+"#,
+    );
+}
+
+#[test]
+fn test_lambda_annotations() {
+    let a = Assert::new();
+    a.fail(
+        r#"
+def f():
+    g = lambda (x: int): x
+    g("a")
+"#,
+        "Expected type `int` but got `str`",
+    );
+    a.fail(
+        r#"
+def f():
+    g = lambda (x: int): x.upper()
+"#,
+        "The attribute `upper` is not available on the type `int`",
+    );
+    a.pass(
+        r#"
+g = lambda (x: int, y: str = "a"): y * x
+assert_eq("aa", g(2))
+"#,
+    );
+}
+
+#[test]
+fn test_comprehension_variable() {
+    Assert::new().fail(
+        r#"
+def f(d: dict[str, int]):
+    return [v.upper() for k, v in d.items()]
+"#,
+        "The attribute `upper` is not available on the type `int`",
+    );
+}
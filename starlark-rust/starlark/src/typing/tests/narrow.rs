@@ -0,0 +1,52 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Tests for narrowing of union types by conditions.
+
+use crate::typing::tests::TypeCheck;
+
+#[test]
+fn test_narrow_types() {
+    TypeCheck::new()
+        .ty("a")
+        .ty("b")
+        .ty("c")
+        .ty("d")
+        .ty("e")
+        .ty("g")
+        .check(
+            "narrow_types",
+            r#"
+def f(x: str | None, y: int | list[int], z: str | list[str]):
+    if x != None:
+        a = x
+    else:
+        b = x
+    if isinstance(y, int):
+        c = y
+    else:
+        d = y
+    if type(z) == "string":
+        e = z
+
+def h(x: str | None):
+    if x == None:
+        return
+    g = x
+"#,
+        );
+}
@@ -0,0 +1,84 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Tests for typing profiles.
+
+use crate::assert::Assert;
+use crate::typing::TypingProfile;
+
+fn assert(profile: TypingProfile) -> Assert<'static> {
+    let mut a = Assert::new();
+    a.setup_eval(move |eval| eval.set_typing_profile(profile));
+    a
+}
+
+#[test]
+fn test_lenient_allows_unannotated() {
+    assert(TypingProfile::Lenient).pass(
+        r#"
+def f(x, *args, **kwargs):
+    return x
+"#,
+    );
+}
+
+#[test]
+fn test_strict_requires_parameter_annotations() {
+    let a = assert(TypingProfile::Strict);
+    a.pass(
+        r#"
+def f(x: int, *args: str, y: str = "", **kwargs: int):
+    return x
+"#,
+    );
+    a.fail(
+        r#"
+def f(x: int, y = 1) -> int:
+    return x
+"#,
+        "Parameter `y` of function `f` has no type annotation",
+    );
+    a.fail(
+        r#"
+def f(*args) -> None:
+    pass
+"#,
+        "Parameter `args` of function `f` has no type annotation",
+    );
+}
+
+#[test]
+fn test_pedantic_requires_public_return_types() {
+    let a = assert(TypingProfile::Pedantic);
+    a.pass(
+        r#"
+def f(x: int) -> int:
+    return x
+
+def _private(x: int):
+    return x
+"#,
+    );
+    // Warnings are errors with the default soft error handler.
+    a.fail(
+        r#"
+def f(x: int):
+    return x
+"#,
+        "Public function `f` has no return type annotation",
+    );
+}
@@ -0,0 +1,41 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Tests for type variables.
+
+use crate::typing::tests::TypeCheck;
+
+#[test]
+fn test_type_var_call_result() {
+    TypeCheck::new().ty("a").ty("b").ty("e").check(
+        "type_var_call_result",
+        r#"
+T = typing.TypeVar("T")
+
+def first(xs: list[T]) -> T:
+    return xs[0]
+
+def get_or(value: T | None, default: T) -> T:
+    return default if value == None else value
+
+def test():
+    a = first([1, 2])
+    b = first(["x"])
+    e = get_or(None, 1)
+"#,
+    );
+}
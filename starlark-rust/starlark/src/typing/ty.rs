@@ -44,6 +44,7 @@ use crate::typing::function::TyCustomFunctionImpl;
 use crate::typing::function::TyFunction;
 use crate::typing::small_arc_vec::SmallArcVec1;
 use crate::typing::starlark_value::TyStarlarkValue;
+use crate::typing::str_enum::TyStrEnum;
 use crate::typing::structs::TyStruct;
 use crate::typing::tuple::TyTuple;
 use crate::typing::ParamSpec;
@@ -64,6 +65,10 @@ pub struct Approximation {
 }
 
 impl Approximation {
+    /// Category of the approximations reported with
+    /// [`TypecheckOptions::report_any_args`](crate::typing::TypecheckOptions::report_any_args).
+    pub const ANY_ARG: &'static str = "Any passed to annotated parameter";
+
     /// Create a new [`Approximation`].
     pub fn new(category: &'static str, message: impl Debug) -> Self {
         Self {
@@ -191,6 +196,11 @@ impl Ty {
         Ty::basic(TyBasic::string())
     }
 
+    /// Create a type of strings which can only be one of the given values.
+    pub fn enum_of(values: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Ty::custom(TyStrEnum::new(values))
+    }
+
     /// Create a list type.
     pub fn list(element: Ty) -> Self {
         Ty::basic(TyBasic::list(element))
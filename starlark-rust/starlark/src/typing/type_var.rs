@@ -0,0 +1,228 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Type variables, declared as `T = typing.TypeVar("T")`.
+
+use allocative::Allocative;
+use dupe::Dupe;
+use starlark_map::small_map::SmallMap;
+
+use crate::codemap::Span;
+use crate::typing::call_args::TyCallArgs;
+use crate::typing::callable::TyCallable;
+use crate::typing::custom::TyCustomImpl;
+use crate::typing::error::TypingNoContextError;
+use crate::typing::error::TypingNoContextOrInternalError;
+use crate::typing::error::TypingOrInternalError;
+use crate::typing::tuple::TyTuple;
+use crate::typing::Ty;
+use crate::typing::TyBasic;
+use crate::typing::TypingBinOp;
+use crate::typing::TypingOracleCtx;
+use crate::util::arc_str::ArcStr;
+use crate::values::typing::type_compiled::alloc::TypeMatcherAlloc;
+
+/// Type variable.
+///
+/// When a function signature mentions type variables, they are bound to the types
+/// of the arguments at each call site, and the result type of the call is the declared
+/// result type with the variables replaced by what they are bound to.
+/// Everywhere else, including inside the function body, a type variable is like `typing.Any`.
+#[derive(
+    Debug,
+    derive_more::Display,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Allocative
+)]
+#[display("{}", name)]
+pub(crate) struct TyTypeVar {
+    pub(crate) name: ArcStr,
+}
+
+impl TyCustomImpl for TyTypeVar {
+    fn as_name(&self) -> Option<&str> {
+        None
+    }
+
+    fn validate_call(
+        &self,
+        _span: Span,
+        _args: &TyCallArgs,
+        _oracle: TypingOracleCtx,
+    ) -> Result<Ty, TypingOrInternalError> {
+        Ok(Ty::any())
+    }
+
+    fn as_callable(&self) -> Option<TyCallable> {
+        Some(TyCallable::any())
+    }
+
+    fn bin_op(
+        &self,
+        _bin_op: TypingBinOp,
+        _rhs: &TyBasic,
+        _ctx: &TypingOracleCtx,
+    ) -> Result<Ty, TypingNoContextOrInternalError> {
+        Ok(Ty::any())
+    }
+
+    fn iter_item(&self) -> Result<Ty, TypingNoContextError> {
+        Ok(Ty::any())
+    }
+
+    fn index(
+        &self,
+        _item: &TyBasic,
+        _ctx: &TypingOracleCtx,
+    ) -> Result<Ty, TypingNoContextOrInternalError> {
+        Ok(Ty::any())
+    }
+
+    fn attribute(&self, _attr: &str) -> Result<Ty, TypingNoContextError> {
+        Ok(Ty::any())
+    }
+
+    fn intersects_with(&self, _other: &TyBasic) -> bool {
+        true
+    }
+
+    fn matcher<T: TypeMatcherAlloc>(&self, factory: T) -> T::Result {
+        factory.any()
+    }
+}
+
+fn as_type_var(ty: &TyBasic) -> Option<&TyTypeVar> {
+    match ty {
+        TyBasic::Custom(custom) => custom.0.as_any().downcast_ref::<TyTypeVar>(),
+        _ => None,
+    }
+}
+
+/// Whether the type mentions a type variable.
+pub(crate) fn contains_type_vars(ty: &Ty) -> bool {
+    ty.iter_union().iter().any(|basic| match basic {
+        TyBasic::Custom(_) => as_type_var(basic).is_some(),
+        TyBasic::List(item) | TyBasic::Iter(item) | TyBasic::Set(item) => contains_type_vars(item),
+        TyBasic::Dict(k, v) => contains_type_vars(k) || contains_type_vars(v),
+        TyBasic::Tuple(TyTuple::Elems(elems)) => elems.iter().any(contains_type_vars),
+        TyBasic::Tuple(TyTuple::Of(item)) => contains_type_vars(item),
+        TyBasic::Callable(c) => contains_type_vars(c.result()),
+        TyBasic::Any | TyBasic::StarlarkValue(_) | TyBasic::Type => false,
+    })
+}
+
+/// Bind the type variables in a parameter type to the matching parts of an argument type.
+/// A variable bound more than once is bound to the union of the types.
+pub(crate) fn bind_type_vars(param: &Ty, arg: &Ty, bindings: &mut SmallMap<ArcStr, Ty>) {
+    for arg in arg.iter_union() {
+        // For `T | None`, `None` does not bind `T`.
+        if param.iter_union().contains(arg) {
+            continue;
+        }
+        for param in param.iter_union() {
+            bind_type_vars_basic(param, arg, bindings);
+        }
+    }
+}
+
+fn bind_type_vars_basic(param: &TyBasic, arg: &TyBasic, bindings: &mut SmallMap<ArcStr, Ty>) {
+    if let Some(var) = as_type_var(param) {
+        let arg = Ty::basic(arg.clone());
+        match bindings.get_mut(&var.name) {
+            Some(bound) => *bound = Ty::union2(bound.clone(), arg),
+            None => {
+                bindings.insert(var.name.dupe(), arg);
+            }
+        }
+        return;
+    }
+    match (param, arg) {
+        (TyBasic::List(p), TyBasic::List(a)) | (TyBasic::Set(p), TyBasic::Set(a)) => {
+            bind_type_vars(p, a, bindings)
+        }
+        (
+            TyBasic::Iter(p),
+            TyBasic::List(a) | TyBasic::Set(a) | TyBasic::Iter(a) | TyBasic::Dict(a, _),
+        ) => bind_type_vars(p, a, bindings),
+        (TyBasic::Iter(p), TyBasic::Tuple(a)) => bind_type_vars(p, &a.item_ty(), bindings),
+        (TyBasic::Dict(pk, pv), TyBasic::Dict(ak, av)) => {
+            bind_type_vars(pk, ak, bindings);
+            bind_type_vars(pv, av, bindings);
+        }
+        (TyBasic::Tuple(TyTuple::Elems(p)), TyBasic::Tuple(TyTuple::Elems(a)))
+            if p.len() == a.len() =>
+        {
+            for (p, a) in p.iter().zip(a.iter()) {
+                bind_type_vars(p, a, bindings);
+            }
+        }
+        (TyBasic::Tuple(TyTuple::Of(p)), TyBasic::Tuple(a)) => {
+            bind_type_vars(p, &a.item_ty(), bindings)
+        }
+        (TyBasic::Callable(p), TyBasic::Callable(a)) => {
+            bind_type_vars(p.result(), a.result(), bindings)
+        }
+        (TyBasic::Callable(p), TyBasic::Custom(a)) => {
+            if let Some(a) = a.0.as_callable_dyn() {
+                bind_type_vars(p.result(), a.result(), bindings);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace type variables with the types they are bound to, or `typing.Any` if unbound.
+pub(crate) fn substitute_type_vars(ty: &Ty, bindings: &SmallMap<ArcStr, Ty>) -> Ty {
+    Ty::unions(
+        ty.iter_union()
+            .iter()
+            .map(|basic| substitute_type_vars_basic(basic, bindings))
+            .collect(),
+    )
+}
+
+fn substitute_type_vars_basic(ty: &TyBasic, bindings: &SmallMap<ArcStr, Ty>) -> Ty {
+    if let Some(var) = as_type_var(ty) {
+        return bindings.get(&var.name).cloned().unwrap_or_else(Ty::any);
+    }
+    match ty {
+        TyBasic::List(item) => Ty::list(substitute_type_vars(item, bindings)),
+        TyBasic::Iter(item) => Ty::iter(substitute_type_vars(item, bindings)),
+        TyBasic::Set(item) => Ty::set(substitute_type_vars(item, bindings)),
+        TyBasic::Dict(k, v) => Ty::dict(
+            substitute_type_vars(k, bindings),
+            substitute_type_vars(v, bindings),
+        ),
+        TyBasic::Tuple(TyTuple::Elems(elems)) => Ty::tuple(
+            elems
+                .iter()
+                .map(|elem| substitute_type_vars(elem, bindings))
+                .collect(),
+        ),
+        TyBasic::Tuple(TyTuple::Of(item)) => Ty::tuple_of(substitute_type_vars(item, bindings)),
+        TyBasic::Callable(c) => Ty::callable(
+            c.params().clone(),
+            substitute_type_vars(c.result(), bindings),
+        ),
+        _ => Ty::basic(ty.clone()),
+    }
+}
@@ -16,24 +16,35 @@
  */
 
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Display;
+use std::sync::Arc;
 
 use dupe::Dupe;
 use starlark_map::unordered_map::UnorderedMap;
 use starlark_syntax::slice_vec_ext::VecExt;
+use starlark_syntax::syntax::ast::ArgumentP;
+use starlark_syntax::syntax::ast::AssignP;
+use starlark_syntax::syntax::ast::AssignTargetP;
+use starlark_syntax::syntax::ast::DefP;
+use starlark_syntax::syntax::ast::ExprP;
+use starlark_syntax::syntax::ast::ParameterP;
 use starlark_syntax::syntax::ast::StmtP;
 use starlark_syntax::syntax::ast::Visibility;
 use starlark_syntax::syntax::module::AstModuleFields;
 use starlark_syntax::syntax::top_level_stmts::top_level_stmts_mut;
 
+use crate::analysis::Lint;
 use crate::codemap::CodeMap;
 use crate::codemap::FileSpanRef;
 use crate::codemap::Span;
 use crate::codemap::Spanned;
+use crate::docs::DocMember;
 use crate::environment::names::MutableNames;
 use crate::environment::Globals;
+use crate::eval::compiler::scope::payload::CstPayload;
 use crate::eval::compiler::scope::payload::CstStmt;
 use crate::eval::compiler::scope::scope_resolver_globals::ScopeResolverGlobals;
 use crate::eval::compiler::scope::BindingId;
@@ -41,16 +52,23 @@ use crate::eval::compiler::scope::BindingSource;
 use crate::eval::compiler::scope::ModuleScopes;
 use crate::syntax::AstModule;
 use crate::syntax::Dialect;
+use crate::typing::annotate::suggest_annotations;
+use crate::typing::annotate::AnnotationSuggestion;
+use crate::typing::bindings::unused_private;
 use crate::typing::bindings::Bindings;
 use crate::typing::bindings::BindingsCollect;
 use crate::typing::ctx::TypingContext;
+use crate::typing::deprecated::collect_deprecated;
+use crate::typing::deprecated::deprecated_uses;
 use crate::typing::error::InternalError;
 use crate::typing::error::TypingError;
 use crate::typing::fill_types_for_lint::fill_types_for_lint_typechecker;
 use crate::typing::fill_types_for_lint::ModuleVarTypes;
 use crate::typing::interface::Interface;
 use crate::typing::mode::TypecheckMode;
+use crate::typing::oracle::buck::OracleBuck;
 use crate::typing::oracle::ctx::TypingOracleCtx;
+use crate::typing::str_enum::TyStrEnum;
 use crate::typing::ty::Approximation;
 use crate::typing::ty::Ty;
 use crate::values::FrozenHeap;
@@ -60,7 +78,17 @@ pub(crate) fn solve_bindings(
     bindings: Bindings,
     oracle: TypingOracleCtx,
     module_var_types: &ModuleVarTypes,
-) -> Result<(Vec<TypingError>, HashMap<BindingId, Ty>, Vec<Approximation>), InternalError> {
+    options: &TypecheckOptions,
+) -> Result<
+    (
+        Vec<TypingError>,
+        HashMap<BindingId, Ty>,
+        HashMap<Span, Ty>,
+        HashMap<Span, Ty>,
+        Vec<Approximation>,
+    ),
+    InternalError,
+> {
     let mut types = bindings
         .expressions
         .keys()
@@ -77,6 +105,15 @@ pub(crate) fn solve_bindings(
         approximoations: RefCell::new(Vec::new()),
         types,
         module_var_types,
+        attr_objects: RefCell::new(HashMap::new()),
+        expr_types: RefCell::new(HashMap::new()),
+        any_args: options
+            .report_any_args
+            .then(|| RefCell::new(BTreeMap::new())),
+        narrowings: &bindings.narrowings,
+        buck: options.oracle.as_deref(),
+        rule_ctx: bindings.rule_ctx,
+        lambdas: &bindings.lambdas,
     };
     const ITERATIONS: usize = 100;
     for _iteration in 0..ITERATIONS {
@@ -120,9 +157,34 @@ pub(crate) fn solve_bindings(
             require,
         )?;
     }
+    for chain in &bindings.match_chains {
+        let ty = ctx.expression_type(chain.scrutinee)?;
+        if let Some(str_enum) = TyStrEnum::from_ty(&ty) {
+            if let Err(e) = str_enum.check_exhaustive(chain.values.iter().copied()) {
+                ctx.errors
+                    .borrow_mut()
+                    .push(ctx.oracle.mk_error(chain.span, e));
+            }
+        }
+    }
+    if let Some(any_args) = ctx.any_args {
+        ctx.approximoations
+            .borrow_mut()
+            .extend(
+                any_args
+                    .into_inner()
+                    .into_values()
+                    .map(|message| Approximation {
+                        category: Approximation::ANY_ARG,
+                        message,
+                    }),
+            );
+    }
     Ok((
         ctx.errors.into_inner(),
         ctx.types.into_hash_map(),
+        ctx.attr_objects.into_inner(),
+        ctx.expr_types.into_inner(),
         ctx.approximoations.into_inner(),
     ))
 }
@@ -132,6 +194,55 @@ pub(crate) fn solve_bindings(
 pub struct TypeMap {
     codemap: CodeMap,
     bindings: UnorderedMap<BindingId, (String, Span, Ty)>,
+    /// Types of the objects of attribute accesses, keyed by the span of the attribute name.
+    attr_objects: HashMap<Span, Ty>,
+    /// Types of the expressions and of the variables at their assignments, sorted by span
+    /// begin, to find those containing a position.
+    spans: Vec<(Span, Ty)>,
+    /// Uses of deprecated symbols loaded from other modules.
+    deprecated_uses: Vec<Lint>,
+    /// Module-private functions and variables which are never used.
+    unused_private: Vec<Lint>,
+    /// Annotations inferred for functions without them.
+    annotation_suggestions: Vec<AnnotationSuggestion>,
+}
+
+/// Number of expressions typed as `Any`, to measure the progress of adding type annotations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TypeCoverage {
+    /// Expressions and assigned variables typed.
+    pub expressions: usize,
+    /// Of those, the ones typed as `Any`.
+    pub any: usize,
+}
+
+impl TypeCoverage {
+    /// Fraction of the expressions typed as `Any`, `0` if there are no expressions.
+    pub fn any_fraction(&self) -> f64 {
+        if self.expressions == 0 {
+            0.0
+        } else {
+            self.any as f64 / self.expressions as f64
+        }
+    }
+}
+
+impl std::ops::AddAssign for TypeCoverage {
+    fn add_assign(&mut self, other: TypeCoverage) {
+        self.expressions += other.expressions;
+        self.any += other.any;
+    }
+}
+
+/// Attribute available on a value, for completion.
+#[derive(Debug, Clone)]
+pub struct AttributeCompletion {
+    /// Name of the attribute.
+    pub name: String,
+    /// Type of the attribute.
+    pub ty: Ty,
+    /// Documentation, for the methods and attributes of native types.
+    pub docs: Option<DocMember>,
 }
 
 impl Display for TypeMap {
@@ -154,6 +265,82 @@ impl Display for TypeMap {
 }
 
 impl TypeMap {
+    /// Warnings for the uses of symbols marked deprecated in the modules they are loaded from.
+    pub fn deprecated_uses(&self) -> &[Lint] {
+        &self.deprecated_uses
+    }
+
+    /// Warnings for the private (`_foo`) top-level functions and variables never used in the
+    /// module.
+    pub fn unused_private(&self) -> &[Lint] {
+        &self.unused_private
+    }
+
+    /// Edits adding the annotations inferred for the parameters and return types of functions
+    /// without them, sorted by position.
+    pub fn annotation_suggestions(&self) -> &[AnnotationSuggestion] {
+        &self.annotation_suggestions
+    }
+
+    /// Attributes available on the object of the attribute access at `cursor`:
+    /// with the cursor on `write` in `ctx.actions.write`, the attributes of `ctx.actions`.
+    ///
+    /// Only attribute accesses in functions are recorded.
+    pub fn attribute_completions(&self, cursor: Span) -> Vec<AttributeCompletion> {
+        let object = self
+            .attr_objects
+            .iter()
+            .filter(|(span, _)| span.begin() <= cursor.begin() && cursor.end() <= span.end())
+            .min_by_key(|(span, _)| (span.end().get() - span.begin().get(), **span));
+        let Some((_, ty)) = object else {
+            return Vec::new();
+        };
+        let oracle = TypingOracleCtx {
+            codemap: &self.codemap,
+        };
+        oracle
+            .attributes(ty)
+            .into_iter()
+            .map(|(name, ty, docs)| AttributeCompletion { name, ty, docs })
+            .collect()
+    }
+
+    /// Type of the innermost expression containing `cursor`, with the span of the expression,
+    /// for hover information. The variables assigned are typed at their assignments.
+    ///
+    /// Only expressions in functions are typed.
+    pub fn type_at(&self, cursor: Span) -> Option<(Span, &Ty)> {
+        // Only the spans beginning before the cursor can contain it.
+        let end = self
+            .spans
+            .partition_point(|(span, _)| span.begin() <= cursor.begin());
+        self.spans[..end]
+            .iter()
+            .filter(|(span, _)| cursor.end() <= span.end())
+            .min_by_key(|(span, _)| span.end().get() - span.begin().get())
+            .map(|(span, ty)| (*span, ty))
+    }
+
+    /// Number of the typed expressions, and of those typed as `Any`.
+    ///
+    /// Only expressions in functions are typed.
+    pub fn coverage(&self) -> TypeCoverage {
+        let mut coverage = TypeCoverage::default();
+        let mut previous = None;
+        for (span, ty) in &self.spans {
+            // Spans typed more than once are counted once.
+            if previous == Some(*span) {
+                continue;
+            }
+            previous = Some(*span);
+            coverage.expressions += 1;
+            if ty.is_any() {
+                coverage.any += 1;
+            }
+        }
+        coverage
+    }
+
     #[cfg(test)]
     pub(crate) fn find_bindings_by_name<'a>(&'a self, name: &str) -> Vec<&'a Ty> {
         self.bindings
@@ -176,21 +363,102 @@ impl TypeMap {
     }
 }
 
+/// Types of `ctx.attrs` of documented rules, or of the attributes declared by the `attrs`
+/// argument, by the name of their implementation, for top-level
+/// `name = rule(impl = f, ...)` statements.
+fn rule_impl_attrs(cst: &[&mut CstStmt], oracle: &OracleBuck) -> HashMap<String, Ty> {
+    // Top-level variables, for attributes declared in a variable.
+    let mut constants = HashMap::new();
+    for stmt in cst {
+        if let StmtP::Assign(AssignP { lhs, rhs, .. }) = &stmt.node {
+            if let AssignTargetP::Identifier(name) = &lhs.node {
+                constants.insert(name.ident.as_str(), rhs);
+            }
+        }
+    }
+
+    let mut res = HashMap::new();
+    for stmt in cst {
+        let StmtP::Assign(AssignP { lhs, rhs, .. }) = &stmt.node else {
+            continue;
+        };
+        let (AssignTargetP::Identifier(name), ExprP::Call(f, args)) = (&lhs.node, &rhs.node) else {
+            continue;
+        };
+        if !matches!(&f.node, ExprP::Identifier(f) if f.node.ident == "rule") {
+            continue;
+        }
+        let declared = || {
+            let attrs = args.args.iter().find_map(|arg| match &arg.node {
+                ArgumentP::Named(arg_name, value) if arg_name.node == "attrs" => Some(value),
+                _ => None,
+            })?;
+            let attrs = match &attrs.node {
+                ExprP::Identifier(x) => constants.get(x.node.ident.as_str()).copied()?,
+                _ => attrs,
+            };
+            oracle.declared_attrs(attrs)
+        };
+        // Documented rules are typed from their documentation.
+        let Some(attrs) = oracle.rule_attrs(&name.ident).or_else(declared) else {
+            continue;
+        };
+        for arg in &args.args {
+            if let ArgumentP::Named(arg_name, value) = &arg.node {
+                if let (true, ExprP::Identifier(f)) = (arg_name.node == "impl", &value.node) {
+                    res.insert(f.node.ident.clone(), attrs.clone());
+                }
+            }
+        }
+    }
+    res
+}
+
+/// The first parameter of a rule implementation.
+fn rule_impl_ctx(def: &DefP<CstPayload>, attrs: &Ty) -> Option<(BindingId, Ty)> {
+    match &def.params.first()?.node {
+        ParameterP::Normal(ctx, ..) => Some((ctx.payload?, attrs.clone())),
+        _ => None,
+    }
+}
+
+/// Options for [`AstModuleTypecheck::typecheck_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct TypecheckOptions {
+    /// Report each argument of type `Any` passed to a parameter with a type annotation,
+    /// as an [`Approximation`] with category [`Approximation::ANY_ARG`]. This measures
+    /// where untyped code flows into typed code.
+    pub report_any_args: bool,
+    /// Types of buck2 globals, provider fields and rule attributes from their documentation.
+    pub oracle: Option<Arc<OracleBuck>>,
+}
+
 /// Typecheck a module.
-pub trait AstModuleTypecheck {
+pub trait AstModuleTypecheck: Sized {
     /// Typecheck a module.
     fn typecheck(
         self,
         globals: &Globals,
         loads: &HashMap<String, Interface>,
+    ) -> (Vec<crate::Error>, TypeMap, Interface, Vec<Approximation>) {
+        self.typecheck_with_options(globals, loads, &TypecheckOptions::default())
+    }
+
+    /// Typecheck a module with options.
+    fn typecheck_with_options(
+        self,
+        globals: &Globals,
+        loads: &HashMap<String, Interface>,
+        options: &TypecheckOptions,
     ) -> (Vec<crate::Error>, TypeMap, Interface, Vec<Approximation>);
 }
 
 impl AstModuleTypecheck for AstModule {
-    fn typecheck(
+    fn typecheck_with_options(
         self,
         globals: &Globals,
         loads: &HashMap<String, Interface>,
+        options: &TypecheckOptions,
     ) -> (Vec<crate::Error>, TypeMap, Interface, Vec<Approximation>) {
         let (codemap, statement, _dialect, _) = self.into_parts();
         let names = MutableNames::new();
@@ -233,6 +501,11 @@ impl AstModuleTypecheck for AstModule {
                     TypeMap {
                         codemap,
                         bindings: UnorderedMap::new(),
+                        attr_objects: HashMap::new(),
+                        spans: Vec::new(),
+                        deprecated_uses: Vec::new(),
+                        unused_private: Vec::new(),
+                        annotation_suggestions: Vec::new(),
                     },
                     Interface::default(),
                     Vec::new(),
@@ -241,11 +514,20 @@ impl AstModuleTypecheck for AstModule {
         };
 
         let mut typemap = UnorderedMap::new();
+        let mut attr_objects = HashMap::new();
+        let mut spans = Vec::new();
         let mut all_solve_errors = Vec::new();
+        let rule_impls = match &options.oracle {
+            Some(oracle) => rule_impl_attrs(&cst, oracle),
+            None => HashMap::new(),
+        };
 
         for top in cst.iter_mut() {
-            if let StmtP::Def(_) = &mut top.node {
-                let bindings = match BindingsCollect::collect_one(
+            if let StmtP::Def(def) = &mut top.node {
+                let rule_ctx = rule_impls
+                    .get(def.name.ident.as_str())
+                    .and_then(|attrs| rule_impl_ctx(def, attrs));
+                let mut bindings = match BindingsCollect::collect_one(
                     top,
                     TypecheckMode::Lint,
                     &codemap,
@@ -258,30 +540,48 @@ impl AstModuleTypecheck for AstModule {
                             TypeMap {
                                 codemap,
                                 bindings: UnorderedMap::new(),
+                                attr_objects: HashMap::new(),
+                                spans: Vec::new(),
+                                deprecated_uses: Vec::new(),
+                                unused_private: Vec::new(),
+                                annotation_suggestions: Vec::new(),
+                            },
+                            Interface::default(),
+                            Vec::new(),
+                        );
+                    }
+                };
+                bindings.bindings.rule_ctx = rule_ctx;
+                let (
+                    solve_errors,
+                    types,
+                    solve_attr_objects,
+                    solve_expr_types,
+                    solve_approximations,
+                ) = match solve_bindings(bindings.bindings, oracle, &module_var_types, options) {
+                    Ok(x) => x,
+                    Err(e) => {
+                        return (
+                            vec![e.into_error()],
+                            TypeMap {
+                                codemap,
+                                bindings: UnorderedMap::new(),
+                                attr_objects: HashMap::new(),
+                                spans: Vec::new(),
+                                deprecated_uses: Vec::new(),
+                                unused_private: Vec::new(),
+                                annotation_suggestions: Vec::new(),
                             },
                             Interface::default(),
                             Vec::new(),
                         );
                     }
                 };
-                let (solve_errors, types, solve_approximations) =
-                    match solve_bindings(bindings.bindings, oracle, &module_var_types) {
-                        Ok(x) => x,
-                        Err(e) => {
-                            return (
-                                vec![e.into_error()],
-                                TypeMap {
-                                    codemap,
-                                    bindings: UnorderedMap::new(),
-                                },
-                                Interface::default(),
-                                Vec::new(),
-                            );
-                        }
-                    };
 
                 all_solve_errors.extend(solve_errors);
                 approximations.extend(solve_approximations);
+                attr_objects.extend(solve_attr_objects);
+                spans.extend(solve_expr_types);
 
                 for (id, ty) in &types {
                     let binding = scope_data.get_binding(*id);
@@ -295,8 +595,23 @@ impl AstModuleTypecheck for AstModule {
             }
         }
 
+        spans.extend(
+            typemap
+                .entries_unordered()
+                // Bindings from other modules have no span.
+                .filter(|(_, (_, span, _))| *span != Span::default())
+                .map(|(_, (_, span, ty))| (*span, ty.clone())),
+        );
+        spans.sort_by_key(|(span, _)| (span.begin(), span.end()));
+        let annotation_suggestions =
+            suggest_annotations(&cst, &codemap, &spans.iter().cloned().collect());
         let typemap = TypeMap {
             bindings: typemap,
+            attr_objects,
+            spans,
+            deprecated_uses: deprecated_uses(&cst, &codemap),
+            unused_private: unused_private(&cst, &codemap),
+            annotation_suggestions,
             codemap: codemap.dupe(),
         };
 
@@ -317,7 +632,9 @@ impl AstModuleTypecheck for AstModule {
                 res.insert(name.as_str().to_owned(), ty);
             }
         }
-        let interface = Interface::new(res);
+        let mut deprecated = collect_deprecated(&cst, &codemap);
+        deprecated.retain(|name, _| res.contains_key(name));
+        let interface = Interface::new(res).with_deprecated(deprecated);
 
         (errors, typemap, interface, approximations)
     }
@@ -33,6 +33,7 @@ use crate::typing::error::TypingOrInternalError;
 use crate::typing::starlark_value::TyStarlarkValue;
 use crate::typing::Ty;
 use crate::typing::TyBasic;
+use crate::typing::TypingBinOp;
 use crate::typing::TypingOracleCtx;
 use crate::values::types::type_instance_id::TypeInstanceId;
 use crate::values::typing::type_compiled::alloc::TypeMatcherAlloc;
@@ -59,6 +60,17 @@ pub struct TyUserIndex {
     pub(crate) result: Ty,
 }
 
+/// Signature of a binary operator with this type on the left-hand side.
+#[derive(Allocative, Debug)]
+pub struct TyUserBinOp {
+    /// The operator.
+    pub op: TypingBinOp,
+    /// Type of right-hand side.
+    pub rhs: Ty,
+    /// Type of result.
+    pub result: Ty,
+}
+
 /// Fields of the struct.
 #[derive(Allocative, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct TyUserFields {
@@ -111,6 +123,9 @@ pub struct TyUserParams {
     pub index: Option<TyUserIndex>,
     /// Set if more precise iter item is known than `base` provides.
     pub iter_item: Option<Ty>,
+    /// Binary operators with this type on the left-hand side.
+    /// Operators not listed here are typechecked with `base`.
+    pub bin_ops: Vec<TyUserBinOp>,
     /// This struct should only be constructed with `..default()`.
     pub _non_exhaustive: (),
 }
@@ -133,6 +148,8 @@ pub struct TyUser {
     index: Option<TyUserIndex>,
     /// Set if more precise iter item is known than `base` provides.
     iter_item: Option<Ty>,
+    /// Binary operators with this type on the left-hand side.
+    bin_ops: Vec<TyUserBinOp>,
 }
 
 impl TyUser {
@@ -150,6 +167,7 @@ impl TyUser {
             callable,
             index,
             iter_item,
+            bin_ops,
             _non_exhaustive: (),
         } = params;
         if callable.is_some() && !base.is_callable() {
@@ -171,6 +189,7 @@ impl TyUser {
             callable,
             index,
             iter_item,
+            bin_ops,
         })
     }
 }
@@ -208,22 +227,29 @@ impl TyCustomImpl for TyUser {
     }
 
     fn attribute(&self, attr: &str) -> Result<Ty, TypingNoContextError> {
-        if let Ok(ty) = self.base.attr_from_methods(attr) {
+        // Known fields are more precise than the types of the methods of the base.
+        if let Some(ty) = self.fields.known.get(attr) {
+            Ok(ty.dupe())
+        } else if let Ok(ty) = self.base.attr_from_methods(attr) {
             Ok(ty)
+        } else if self.fields.unknown {
+            Ok(Ty::any())
         } else {
-            match self.fields.known.get(attr) {
-                Some(ty) => Ok(ty.dupe()),
-                None => {
-                    if self.fields.unknown {
-                        Ok(Ty::any())
-                    } else {
-                        Err(TypingNoContextError)
-                    }
-                }
-            }
+            Err(TypingNoContextError)
         }
     }
 
+    fn attribute_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.fields.known.keys().cloned().collect();
+        names.extend(
+            self.base
+                .attrs_with_docs()
+                .into_iter()
+                .map(|(name, _)| name),
+        );
+        names
+    }
+
     fn index(
         &self,
         item: &TyBasic,
@@ -239,6 +265,29 @@ impl TyCustomImpl for TyUser {
         }
     }
 
+    fn bin_op(
+        &self,
+        bin_op: TypingBinOp,
+        rhs: &TyBasic,
+        ctx: &TypingOracleCtx,
+    ) -> Result<Ty, TypingNoContextOrInternalError> {
+        let mut declared = false;
+        let mut results = Vec::new();
+        for op in self.bin_ops.iter().filter(|op| op.op == bin_op) {
+            declared = true;
+            if ctx.intersects(&Ty::basic(rhs.dupe()), &op.rhs)? {
+                results.push(op.result.dupe());
+            }
+        }
+        if !declared {
+            Ok(self.base.bin_op(bin_op, rhs)?)
+        } else if results.is_empty() {
+            Err(TypingNoContextOrInternalError::Typing)
+        } else {
+            Ok(Ty::unions(results))
+        }
+    }
+
     fn iter_item(&self) -> Result<Ty, TypingNoContextError> {
         if let Some(iter_item) = &self.iter_item {
             Ok(iter_item.dupe())
@@ -248,7 +297,9 @@ impl TyCustomImpl for TyUser {
     }
 
     fn as_callable(&self) -> Option<TyCallable> {
-        if self.base.is_callable() {
+        if let Some(callable) = &self.callable {
+            Some(callable.dupe())
+        } else if self.base.is_callable() {
             Some(TyCallable::any())
         } else {
             None
@@ -297,6 +348,7 @@ mod tests {
     use starlark_derive::starlark_value;
     use starlark_derive::NoSerialize;
     use starlark_derive::ProvidesStaticType;
+    use starlark_map::sorted_map::SortedMap;
 
     use crate as starlark;
     use crate::assert::Assert;
@@ -309,6 +361,9 @@ mod tests {
     use crate::typing::Ty;
     use crate::typing::TyStarlarkValue;
     use crate::typing::TyUser;
+    use crate::typing::TyUserBinOp;
+    use crate::typing::TyUserFields;
+    use crate::typing::TypingBinOp;
     use crate::values::starlark_value_as_type::StarlarkValueAsType;
     use crate::values::typing::TypeInstanceId;
     use crate::values::AllocValue;
@@ -401,8 +456,67 @@ mod tests {
         }
     }
 
+    #[derive(
+        Debug,
+        derive_more::Display,
+        ProvidesStaticType,
+        Allocative,
+        NoSerialize
+    )]
+    #[display("path")]
+    struct OpaquePath;
+
+    #[starlark_value(type = "path")]
+    impl<'v> StarlarkValue<'v> for OpaquePath {}
+
+    #[derive(
+        Debug,
+        derive_more::Display,
+        ProvidesStaticType,
+        Allocative,
+        NoSerialize
+    )]
+    #[display("path_type")]
+    struct PathType {
+        ty_path: Ty,
+    }
+
+    impl<'v> AllocValue<'v> for PathType {
+        fn alloc_value(self, heap: &'v Heap) -> Value<'v> {
+            heap.alloc_simple(self)
+        }
+    }
+
+    #[starlark_value(type = "path_type")]
+    impl<'v> StarlarkValue<'v> for PathType {
+        fn eval_type(&self) -> Option<Ty> {
+            Some(self.ty_path.dupe())
+        }
+    }
+
     #[starlark_module]
     fn globals(globals: &mut GlobalsBuilder) {
+        fn path_type() -> anyhow::Result<PathType> {
+            let ty_path = Ty::custom(TyUser::new(
+                "path".to_owned(),
+                TyStarlarkValue::new::<OpaquePath>(),
+                TypeInstanceId::gen(),
+                TyUserParams {
+                    fields: TyUserFields {
+                        known: SortedMap::from_iter([("short_path".to_owned(), Ty::string())]),
+                        unknown: false,
+                    },
+                    bin_ops: vec![TyUserBinOp {
+                        op: TypingBinOp::Add,
+                        rhs: Ty::string(),
+                        result: Ty::string(),
+                    }],
+                    ..TyUserParams::default()
+                },
+            )?);
+            Ok(PathType { ty_path })
+        }
+
         fn fruit(name: String) -> anyhow::Result<FruitCallable> {
             let ty_fruit = Ty::custom(TyUser::new(
                 name.clone(),
@@ -439,6 +553,38 @@ mod tests {
         const Plant: StarlarkValueAsType<AbstractPlant> = StarlarkValueAsType::new();
     }
 
+    #[test]
+    fn test_opaque_type_attributes_and_bin_ops() {
+        let mut a = Assert::new();
+        a.globals_add(globals);
+        a.pass(
+            r#"
+Path = path_type()
+
+def join(p: Path) -> str:
+    return p + "/" + p.short_path
+"#,
+        );
+        a.fail(
+            r#"
+Path = path_type()
+
+def join(p: Path) -> str:
+    return p + 1
+"#,
+            "Binary operator `+` is not available on the types `path` and `int`",
+        );
+        a.fail(
+            r#"
+Path = path_type()
+
+def join(p: Path) -> str:
+    return p.long_path
+"#,
+            "The attribute `long_path` is not available on the type `path`",
+        );
+    }
+
     #[test]
     fn test_intersect_with_abstract_type() {
         let mut a = Assert::new();
@@ -32,6 +32,7 @@ use starlark_derive::Coerce;
 use starlark_derive::NoSerialize;
 use starlark_derive::Trace;
 use starlark_map::small_map::SmallMap;
+use starlark_map::sorted_map::SortedMap;
 use starlark_map::Equivalent;
 
 use crate as starlark;
@@ -44,6 +45,7 @@ use crate::eval::Evaluator;
 use crate::typing::callable::TyCallable;
 use crate::typing::starlark_value::TyStarlarkValue;
 use crate::typing::user::TyUser;
+use crate::typing::user::TyUserFields;
 use crate::typing::user::TyUserIndex;
 use crate::typing::user::TyUserParams;
 use crate::typing::ParamSpec;
@@ -303,12 +305,38 @@ where
         _eval: &mut Evaluator<'v, '_, '_>,
     ) -> crate::Result<()> {
         V::get_or_init_ty(&self.ty_enum_data, || {
+            let values: Vec<Value> = self
+                .elements()
+                .iter()
+                .map(|(_, enum_value)| {
+                    let enum_value: &EnumValueGen<_> = EnumValue::from_value(enum_value.to_value())
+                        .expect("known to be enum value");
+                    enum_value.value.to_value()
+                })
+                .collect();
+            let variants: Vec<Ty> = values.iter().map(|v| Ty::of_value(*v)).collect();
+            // Enums of strings, the most common, accept only their values.
+            let ty_value = match values
+                .iter()
+                .map(|v| v.unpack_str())
+                .collect::<Option<Vec<_>>>()
+            {
+                Some(values) => Ty::enum_of(values),
+                None => Ty::unions(variants.clone()),
+            };
             let ty_enum_value = Ty::custom(TyUser::new(
                 variable_name.to_owned(),
                 TyStarlarkValue::new::<EnumValue>(),
                 self.id,
                 TyUserParams {
                     matcher: Some(TypeMatcherFactory::new(EnumTypeMatcher { id: self.id })),
+                    fields: TyUserFields {
+                        known: SortedMap::from_iter([
+                            ("index".to_owned(), Ty::int()),
+                            ("value".to_owned(), ty_value.dupe()),
+                        ]),
+                        unknown: false,
+                    },
                     ..TyUserParams::default()
                 },
             )?);
@@ -323,13 +351,7 @@ where
                     }),
                     iter_item: Some(ty_enum_value.dupe()),
                     callable: Some(TyCallable::new(
-                        ParamSpec::pos_only(
-                            [
-                                // TODO(nga): we can do better parameter type.
-                                Ty::any(),
-                            ],
-                            [],
-                        ),
+                        ParamSpec::pos_only([ty_value], []),
                         ty_enum_value.dupe(),
                     )),
                     ..TyUserParams::default()
@@ -337,16 +359,7 @@ where
             )?);
             Ok(Arc::new(TyEnumData {
                 name: variable_name.to_owned(),
-                variants: self
-                    .elements()
-                    .iter()
-                    .map(|(_, enum_value)| {
-                        let enum_value: &EnumValueGen<_> =
-                            EnumValue::from_value(enum_value.to_value())
-                                .expect("known to be enum value");
-                        Ty::of_value(enum_value.value)
-                    })
-                    .collect(),
+                variants,
                 id: self.id,
                 ty_enum_value,
                 ty_enum_type,
@@ -530,4 +543,50 @@ def test():
             "Expected type `str` but got `Currency`",
         );
     }
+
+    #[test]
+    fn test_enum_call_invalid_value() {
+        assert::fail(
+            r#"
+Currency = enum("GBP", "USD", "EUR")
+
+def test():
+    Currency("YEN")
+"#,
+            r#"Value `"YEN"` is not one of `enum_of("EUR", "GBP", "USD")`"#,
+        );
+        assert::fail(
+            r#"
+Currency = enum("GBP", "USD", "EUR")
+
+def test():
+    Currency(1)
+"#,
+            r#"Expected type `enum_of("EUR", "GBP", "USD")` but got `int`"#,
+        );
+    }
+
+    #[test]
+    fn test_enum_value_field_types() {
+        assert::pass(
+            r#"
+Currency = enum("GBP", "USD", "EUR")
+
+def symbol(c: Currency) -> str:
+    return c.value.lower()
+
+def position(c: Currency) -> int:
+    return c.index + 1
+"#,
+        );
+        assert::fail(
+            r#"
+Currency = enum("GBP", "USD", "EUR")
+
+def is_yen(c: Currency) -> bool:
+    return c.value == "YEN"
+"#,
+            r#"Comparison of `enum_of("EUR", "GBP", "USD")` with `"YEN"` is always `False`"#,
+        );
+    }
 }
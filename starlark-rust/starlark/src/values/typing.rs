@@ -21,11 +21,14 @@ pub(crate) mod any;
 pub(crate) mod callable;
 pub(crate) mod globals;
 pub(crate) mod iter;
+pub(crate) mod literal;
 pub mod macro_refs;
 pub(crate) mod never;
+pub(crate) mod structs;
 pub(crate) mod ty;
 pub(crate) mod type_compiled;
 pub(crate) mod type_type;
+pub(crate) mod type_var;
 
 pub use crate::values::types::type_instance_id::TypeInstanceId;
 pub use crate::values::typing::callable::param::StarlarkCallableParamAny;
@@ -19,8 +19,11 @@ use crate::environment::GlobalsBuilder;
 use crate::values::typing::any::TypingAny;
 use crate::values::typing::callable::TypingCallable;
 use crate::values::typing::iter::TypingIterable;
+use crate::values::typing::literal::TypingLiteral;
 use crate::values::typing::never::TypingNever;
+use crate::values::typing::structs::register_typing_struct;
 use crate::values::typing::type_compiled::globals::register_eval_type;
+use crate::values::typing::type_var::register_type_var;
 
 pub(crate) fn register_typing(globals: &mut GlobalsBuilder) {
     register_eval_type(globals);
@@ -29,5 +32,8 @@ pub(crate) fn register_typing(globals: &mut GlobalsBuilder) {
         globals.set("Never", TypingNever);
         globals.set("Callable", TypingCallable);
         globals.set("Iterable", TypingIterable);
+        globals.set("Literal", TypingLiteral);
+        register_type_var(globals);
+        register_typing_struct(globals);
     });
 }
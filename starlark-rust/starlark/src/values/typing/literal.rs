@@ -0,0 +1,144 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! `typing.Literal`.
+
+use allocative::Allocative;
+use starlark_derive::NoSerialize;
+use starlark_derive::ProvidesStaticType;
+
+use crate as starlark;
+use crate::private::Private;
+use crate::typing::Ty;
+use crate::values::layout::avalue::alloc_static;
+use crate::values::layout::avalue::AValueBasic;
+use crate::values::layout::avalue::AValueImpl;
+use crate::values::layout::heap::repr::AValueRepr;
+use crate::values::starlark_value;
+use crate::values::tuple::TupleRef;
+use crate::values::typing::TypeCompiled;
+use crate::values::AllocFrozenValue;
+use crate::values::FrozenHeap;
+use crate::values::FrozenValue;
+use crate::values::Heap;
+use crate::values::StarlarkValue;
+use crate::values::Value;
+
+#[derive(Debug, thiserror::Error)]
+enum TypingLiteralError {
+    #[error("`typing.Literal[]` values must be strings, got `{0}`")]
+    NotString(String),
+}
+
+#[derive(
+    Debug,
+    derive_more::Display,
+    Allocative,
+    ProvidesStaticType,
+    NoSerialize
+)]
+#[display("{}", Self::TYPE)]
+pub(crate) struct TypingLiteral;
+
+impl TypingLiteral {
+    fn literal_type<'v>(values: &[Value<'v>], heap: &'v Heap) -> crate::Result<Value<'v>> {
+        let values = values
+            .iter()
+            .map(|v| match v.unpack_str() {
+                Some(s) => Ok(s),
+                None => Err(crate::Error::new_other(TypingLiteralError::NotString(
+                    v.to_repr(),
+                ))),
+            })
+            .collect::<crate::Result<Vec<&str>>>()?;
+        Ok(TypeCompiled::from_ty(&Ty::enum_of(values), heap).to_inner())
+    }
+}
+
+/// `typing.Literal["a", "b"]` is the type of strings which are one of the values.
+#[starlark_value(type = "typing.Literal")]
+impl<'v> StarlarkValue<'v> for TypingLiteral {
+    fn at(&self, index: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        match TupleRef::from_value(index) {
+            Some(tuple) => Self::literal_type(tuple.content(), heap),
+            None => Self::literal_type(&[index], heap),
+        }
+    }
+
+    fn at2(
+        &self,
+        index0: Value<'v>,
+        index1: Value<'v>,
+        heap: &'v Heap,
+        _private: Private,
+    ) -> crate::Result<Value<'v>> {
+        Self::literal_type(&[index0, index1], heap)
+    }
+}
+
+impl AllocFrozenValue for TypingLiteral {
+    fn alloc_frozen_value(self, _heap: &FrozenHeap) -> FrozenValue {
+        static LITERAL: AValueRepr<AValueImpl<'static, AValueBasic<TypingLiteral>>> =
+            alloc_static(TypingLiteral);
+
+        FrozenValue::new_repr(&LITERAL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_literal_runtime() {
+        assert::is_true(r#"isinstance("a", typing.Literal["a", "b"])"#);
+        assert::is_true(r#"isinstance("c", typing.Literal["a", "b", "c"])"#);
+        assert::is_true(r#"isinstance("a", typing.Literal["a"])"#);
+        assert::is_false(r#"isinstance("c", typing.Literal["a", "b"])"#);
+        assert::is_false(r#"isinstance(1, typing.Literal["a", "b"])"#);
+    }
+
+    #[test]
+    fn test_literal_annotation() {
+        assert::pass(
+            r#"
+def f(mode: typing.Literal["debug", "release", "test"]) -> str:
+    return mode
+
+f("test")
+"#,
+        );
+        assert::fail(
+            r#"
+def f(mode: typing.Literal["debug", "release"]):
+    pass
+
+def g():
+    f("fast")
+"#,
+            r#"Value `"fast"` is not one of `enum_of("debug", "release")`"#,
+        );
+    }
+
+    #[test]
+    fn test_literal_not_string() {
+        assert::fail(
+            r#"typing.Literal[1, 2]"#,
+            "`typing.Literal[]` values must be strings, got `1`",
+        );
+    }
+}
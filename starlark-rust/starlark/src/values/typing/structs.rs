@@ -0,0 +1,130 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use starlark_derive::starlark_module;
+use starlark_map::small_map::SmallMap;
+use starlark_map::sorted_map::SortedMap;
+
+use crate as starlark;
+use crate::environment::GlobalsBuilder;
+use crate::eval::Evaluator;
+use crate::typing::structs::TyStruct;
+use crate::typing::Ty;
+use crate::util::arc_str::ArcStr;
+use crate::values::typing::type_compiled::compiled::TypeCompiled;
+use crate::values::Heap;
+use crate::values::Value;
+
+/// The type of `typing.Struct(strict = strict, **fields)`.
+pub(crate) fn typing_struct_ty<'a, 'v>(
+    fields: impl IntoIterator<Item = (&'a str, Value<'v>)>,
+    strict: bool,
+    heap: &'v Heap,
+) -> anyhow::Result<Ty> {
+    let mut tys = Vec::new();
+    for (name, ty) in fields {
+        tys.push((
+            ArcStr::from(name),
+            TypeCompiled::new(ty, heap)?.as_ty().clone(),
+        ));
+    }
+    Ok(Ty::custom(TyStruct {
+        fields: SortedMap::from_iter(tys),
+        extra: !strict,
+    }))
+}
+
+#[starlark_module]
+pub(crate) fn register_typing_struct(globals: &mut GlobalsBuilder) {
+    /// Struct type with the given fields, like `typing.Struct(srcs = list[str])`.
+    ///
+    /// Structs are typed structurally: any struct providing at least these fields, with
+    /// compatible types, is accepted. With `strict = True`, structs with other fields are
+    /// rejected.
+    fn Struct<'v>(
+        #[starlark(require = named, default = false)] strict: bool,
+        #[starlark(kwargs)] fields: SmallMap<String, Value<'v>>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> anyhow::Result<TypeCompiled<Value<'v>>> {
+        let ty = typing_struct_ty(
+            fields.iter().map(|(k, v)| (k.as_str(), *v)),
+            strict,
+            eval.heap(),
+        )?;
+        Ok(TypeCompiled::from_ty(&ty, eval.heap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_struct_protocol_runtime() {
+        assert::pass(
+            r#"
+Srcs = typing.Struct(srcs = list[str])
+StrictSrcs = typing.Struct(srcs = list[str], strict = True)
+
+assert_true(isinstance(struct(srcs = ["a"]), Srcs))
+assert_true(isinstance(struct(srcs = ["a"], name = "x"), Srcs))
+assert_false(isinstance(struct(srcs = [1]), Srcs))
+assert_false(isinstance(struct(name = "x"), Srcs))
+assert_true(isinstance(struct(srcs = ["a"]), StrictSrcs))
+assert_false(isinstance(struct(srcs = ["a"], name = "x"), StrictSrcs))
+"#,
+        );
+    }
+
+    #[test]
+    fn test_struct_protocol_compile_time() {
+        assert::pass(
+            r#"
+Srcs = typing.Struct(srcs = list[str])
+
+def srcs(x: Srcs) -> list[str]:
+    return x.srcs
+
+def test():
+    srcs(struct(srcs = ["a"], name = "x"))
+"#,
+        );
+        assert::fail(
+            r#"
+Srcs = typing.Struct(srcs = list[str])
+
+def srcs(x: Srcs) -> list[str]:
+    return x.srcs
+
+def test():
+    srcs(struct(name = "x"))
+"#,
+            "Expected type",
+        );
+        assert::fail(
+            r#"
+StrictSrcs = typing.Struct(srcs = list[str], strict = True)
+
+def srcs(x: StrictSrcs) -> list[str]:
+    return x.srcs
+
+def test():
+    srcs(struct(srcs = ["a"], name = "x"))
+"#,
+            "Expected type",
+        );
+    }
+}
@@ -0,0 +1,115 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use allocative::Allocative;
+use starlark_derive::starlark_module;
+use starlark_derive::starlark_value;
+use starlark_derive::NoSerialize;
+use starlark_derive::ProvidesStaticType;
+
+use crate as starlark;
+use crate::environment::GlobalsBuilder;
+use crate::starlark_simple_value;
+use crate::typing::type_var::TyTypeVar;
+use crate::typing::Ty;
+use crate::util::arc_str::ArcStr;
+use crate::values::StarlarkValue;
+
+/// Value of `typing.TypeVar("T")`.
+#[derive(
+    Debug,
+    derive_more::Display,
+    Allocative,
+    ProvidesStaticType,
+    NoSerialize
+)]
+#[display("{}", name)]
+pub(crate) struct TypingTypeVar {
+    name: ArcStr,
+}
+
+starlark_simple_value!(TypingTypeVar);
+
+impl TypingTypeVar {
+    pub(crate) fn new(name: &str) -> TypingTypeVar {
+        TypingTypeVar {
+            name: ArcStr::from(name),
+        }
+    }
+}
+
+#[starlark_value(type = "typing.TypeVar")]
+impl<'v> StarlarkValue<'v> for TypingTypeVar {
+    fn eval_type(&self) -> Option<Ty> {
+        Some(Ty::custom(TyTypeVar {
+            name: self.name.clone(),
+        }))
+    }
+}
+
+#[starlark_module]
+pub(crate) fn register_type_var(globals: &mut GlobalsBuilder) {
+    /// Declare a type variable, to be used in function signatures like
+    /// `def first(xs: list[T]) -> T`.
+    ///
+    /// The typechecker binds type variables to the types of the arguments at each call site,
+    /// so `first([1, 2])` has type `int`. At runtime, a type variable matches any value.
+    #[starlark(speculative_exec_safe)]
+    fn TypeVar(#[starlark(require = pos)] name: &str) -> anyhow::Result<TypingTypeVar> {
+        Ok(TypingTypeVar::new(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_type_var_runtime() {
+        assert::pass(
+            r#"
+T = typing.TypeVar("T")
+
+def first(xs: list[T]) -> T:
+    return xs[0]
+
+assert_eq(1, first([1, 2]))
+assert_eq("x", first(["x"]))
+assert_eq("T", str(T))
+"#,
+        );
+    }
+
+    #[test]
+    fn test_type_var_compile_time_fail() {
+        assert::fail(
+            r#"
+T = typing.TypeVar("T")
+
+def first(xs: list[T]) -> T:
+    return xs[0]
+
+def takes_str(x: str):
+    pass
+
+def test():
+    takes_str(first([1, 2]))
+"#,
+            "Expected type `str` but got `int`",
+        );
+    }
+}
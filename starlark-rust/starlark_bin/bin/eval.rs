@@ -23,6 +23,7 @@ use std::iter;
 use std::path::Path;
 use std::path::PathBuf;
 
+use dupe::Dupe;
 use itertools::Either;
 use lsp_types::Url;
 use starlark::analysis::AstModuleLint;
@@ -368,4 +369,10 @@ impl LspContext for Context {
     fn get_environment(&self, _uri: &LspUrl) -> DocModule {
         DocModule::default()
     }
+
+    fn typecheck_globals(&self, _uri: &LspUrl) -> Option<Globals> {
+        // The typechecker does not know about the symbols of the prelude, and would report
+        // them as undefined.
+        self.prelude.is_empty().then(|| self.globals.dupe())
+    }
 }
@@ -15,10 +15,17 @@
  * limitations under the License.
  */
 
+use std::collections::HashMap;
+use std::path::Path;
+
 use lsp_types::NumberOrString;
 use lsp_types::Range;
 use starlark::analysis::EvalMessage;
 use starlark::analysis::EvalSeverity;
+use starlark::codemap::Span;
+use starlark::environment::Globals;
+use starlark::syntax::AstModule;
+use starlark::typing::AstModuleTypecheck;
 
 pub fn eval_message_to_lsp_diagnostic(eval_message: EvalMessage) -> lsp_types::Diagnostic {
     let range = match eval_message.span {
@@ -48,3 +55,32 @@ fn eval_severity_to_lsp_diagnostic_severity(
         EvalSeverity::Disabled => lsp_types::DiagnosticSeverity::INFORMATION,
     }
 }
+
+/// Typecheck a module against `globals`, and convert the type errors to diagnostics.
+///
+/// Places where the typechecker had to approximate types, and so may have missed errors,
+/// are reported as hints. Symbols loaded from other modules are not typechecked.
+pub fn typecheck_to_lsp_diagnostics(
+    ast: AstModule,
+    globals: &Globals,
+) -> Vec<lsp_types::Diagnostic> {
+    let path = ast.file_span(Span::default()).filename().to_owned();
+    let (errors, _, _, approximations) = ast.typecheck(globals, &HashMap::new());
+    let errors = errors.iter().map(|e| {
+        let mut message = EvalMessage::from_error(Path::new(&path), e);
+        message.name = "type-error".to_owned();
+        eval_message_to_lsp_diagnostic(message)
+    });
+    let approximations = approximations.iter().map(|a| {
+        lsp_types::Diagnostic::new(
+            Range::default(),
+            Some(lsp_types::DiagnosticSeverity::HINT),
+            Some(NumberOrString::String("type-approximation".to_owned())),
+            None,
+            a.to_string(),
+            None,
+            None,
+        )
+    });
+    errors.chain(approximations).collect()
+}
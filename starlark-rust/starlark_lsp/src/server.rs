@@ -93,7 +93,9 @@ use starlark::docs::markdown::render_doc_param;
 use starlark::docs::DocItem;
 use starlark::docs::DocMember;
 use starlark::docs::DocModule;
+use starlark::environment::Globals;
 use starlark::syntax::AstModule;
+use starlark::typing::AstModuleTypecheck;
 use starlark_syntax::codemap::ResolvedPos;
 use starlark_syntax::syntax::ast::AstPayload;
 use starlark_syntax::syntax::ast::LoadArgP;
@@ -105,6 +107,7 @@ use crate::definition::Definition;
 use crate::definition::DottedDefinition;
 use crate::definition::IdentifierDefinition;
 use crate::definition::LspModule;
+use crate::error::typecheck_to_lsp_diagnostics;
 use crate::inspect::AstModuleInspect;
 use crate::inspect::AutocompleteType;
 use crate::symbols::find_symbols_at_location;
@@ -349,6 +352,16 @@ pub trait LspContext {
     /// Get the preloaded environment for a particular file.
     fn get_environment(&self, uri: &LspUrl) -> DocModule;
 
+    /// Get the globals to typecheck a file against, if it should be typechecked. Type errors
+    /// are published as diagnostics along with the ones from `parse_file_with_contents`.
+    ///
+    /// The globals must include every symbol available to the file, or references to
+    /// missing symbols are reported as errors.
+    fn typecheck_globals(&self, uri: &LspUrl) -> Option<Globals> {
+        let _unused = uri;
+        None
+    }
+
     /// Get the LSPUrl for a global symbol if possible.
     ///
     /// The current file is provided in case different files have different global symbols
@@ -438,8 +451,13 @@ impl<T: LspContext> Backend<T> {
 
     fn validate(&self, uri: Url, version: Option<i64>, text: String) -> anyhow::Result<()> {
         let lsp_url = uri.clone().try_into()?;
-        let eval_result = self.context.parse_file_with_contents(&lsp_url, text);
+        let mut eval_result = self.context.parse_file_with_contents(&lsp_url, text);
         if let Some(ast) = eval_result.ast {
+            if let Some(globals) = self.context.typecheck_globals(&lsp_url) {
+                eval_result
+                    .diagnostics
+                    .extend(typecheck_to_lsp_diagnostics(ast.clone(), &globals));
+            }
             let module = Arc::new(LspModule::new(ast));
             let mut last_valid_parse = self.last_valid_parse.write().unwrap();
             last_valid_parse.insert(lsp_url, module);
@@ -1050,12 +1068,38 @@ impl<T: LspContext> Backend<T> {
                         )?
                     }
                 }
+                .or_else(|| self.get_hover_for_type(&uri, &document, line, character))
                 .unwrap_or(not_found)
             }
             None => not_found,
         })
     }
 
+    /// Hover information with the inferred type of the expression at the position, if the file
+    /// is typechecked.
+    fn get_hover_for_type(
+        &self,
+        uri: &LspUrl,
+        document: &LspModule,
+        line: u32,
+        character: u32,
+    ) -> Option<Hover> {
+        let globals = self.context.typecheck_globals(uri)?;
+        let codemap = document.ast.codemap();
+        let line_span = codemap.line_span_opt(line as usize)?;
+        let pos = std::cmp::min(line_span.begin() + character, line_span.end());
+        let (_, typemap, _, _) = document.ast.clone().typecheck(&globals, &HashMap::new());
+        // The character after the cursor.
+        let (span, ty) = typemap.type_at(Span::new(pos, pos + 1))?;
+        Some(Hover {
+            contents: HoverContents::Array(vec![MarkedString::LanguageString(LanguageString {
+                language: "python".to_owned(),
+                value: ty.to_string(),
+            })]),
+            range: Some(codemap.resolve_span(span).into()),
+        })
+    }
+
     fn get_hover_for_identifier_definition(
         &self,
         identifier_definition: IdentifierDefinition,
@@ -1374,16 +1418,26 @@ mod tests {
     use anyhow::Context;
     use lsp_server::Request;
     use lsp_server::RequestId;
+    use lsp_types::notification::PublishDiagnostics;
     use lsp_types::request::GotoDefinition;
+    use lsp_types::request::HoverRequest;
+    use lsp_types::DiagnosticSeverity;
     use lsp_types::GotoDefinitionParams;
     use lsp_types::GotoDefinitionResponse;
+    use lsp_types::Hover;
+    use lsp_types::HoverContents;
+    use lsp_types::HoverParams;
+    use lsp_types::LanguageString;
     use lsp_types::LocationLink;
+    use lsp_types::MarkedString;
+    use lsp_types::NumberOrString;
     use lsp_types::Position;
     use lsp_types::Range;
     use lsp_types::TextDocumentIdentifier;
     use lsp_types::TextDocumentPositionParams;
     use lsp_types::Url;
     use starlark::codemap::ResolvedSpan;
+    use starlark::environment::Globals;
     use starlark::wasm::is_wasm;
     use textwrap::dedent;
 
@@ -1482,6 +1536,77 @@ mod tests {
         path_to_load_string(&uri.to_file_path().unwrap())
     }
 
+    #[test]
+    fn publishes_type_errors() -> anyhow::Result<()> {
+        if is_wasm() {
+            return Ok(());
+        }
+
+        let uri = temp_file_uri("file.star");
+
+        let mut server = TestServer::new_with_typecheck(Globals::standard())?;
+        let contents = "def f(x: int):\n    pass\n\ndef g():\n    f(\"a\")\n";
+        server.change_file(uri.clone(), contents.to_owned())?;
+
+        let notification = server.get_notification::<PublishDiagnostics>()?;
+        assert_eq!(uri, notification.uri);
+        let type_errors: Vec<_> = notification
+            .diagnostics
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("type-error".to_owned())))
+            .collect();
+        assert_eq!(1, type_errors.len());
+        assert_eq!(Some(DiagnosticSeverity::ERROR), type_errors[0].severity);
+        assert_eq!(
+            Range::new(Position::new(4, 6), Position::new(4, 9)),
+            type_errors[0].range
+        );
+        assert!(
+            type_errors[0]
+                .message
+                .contains("Expected type `int` but got `str`"),
+            "{}",
+            type_errors[0].message
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn hover_shows_inferred_type() -> anyhow::Result<()> {
+        if is_wasm() {
+            return Ok(());
+        }
+
+        let uri = temp_file_uri("file.star");
+
+        let mut server = TestServer::new_with_typecheck(Globals::standard())?;
+        let contents = "def f(x: list[int]):\n    return x[0] + 1\n";
+        server.change_file(uri.clone(), contents.to_owned())?;
+
+        // On the `[` of `x[0]`.
+        let req = server.new_request::<HoverRequest>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(1, 12),
+            },
+            work_done_progress_params: Default::default(),
+        });
+        let request_id = server.send_request(req)?;
+        let hover = server.get_response::<Hover>(request_id)?;
+        assert_eq!(
+            HoverContents::Array(vec![MarkedString::LanguageString(LanguageString {
+                language: "python".to_owned(),
+                value: "int".to_owned(),
+            })]),
+            hover.contents
+        );
+        assert_eq!(
+            Some(Range::new(Position::new(1, 11), Position::new(1, 15))),
+            hover.range
+        );
+        Ok(())
+    }
+
     #[test]
     fn sends_empty_goto_definition_on_nonexistent_file() -> anyhow::Result<()> {
         if is_wasm() {
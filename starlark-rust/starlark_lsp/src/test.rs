@@ -61,6 +61,7 @@ use starlark::docs::DocFunction;
 use starlark::docs::DocItem;
 use starlark::docs::DocMember;
 use starlark::docs::DocModule;
+use starlark::environment::Globals;
 use starlark::errors::EvalMessage;
 use starlark::syntax::AstModule;
 use starlark::syntax::Dialect;
@@ -120,6 +121,7 @@ struct TestServerContext {
     dirs: Arc<RwLock<HashSet<PathBuf>>>,
     builtin_docs: Arc<HashMap<LspUrl, String>>,
     builtin_symbols: Arc<HashMap<String, LspUrl>>,
+    typecheck_globals: Option<Globals>,
 }
 
 impl LspContext for TestServerContext {
@@ -282,6 +284,10 @@ impl LspContext for TestServerContext {
         Ok(self.builtin_symbols.get(symbol).cloned())
     }
 
+    fn typecheck_globals(&self, _uri: &LspUrl) -> Option<Globals> {
+        self.typecheck_globals.dupe()
+    }
+
     fn get_environment(&self, _uri: &LspUrl) -> DocModule {
         DocModule {
             docs: None,
@@ -404,6 +410,18 @@ impl TestServer {
     /// initialization payload and makes sure that when the server is dropped, the threads
     /// are attempted to be stopped.
     pub(crate) fn new_with_settings(settings: Option<LspServerSettings>) -> anyhow::Result<Self> {
+        Self::start(settings, None)
+    }
+
+    /// Create and start a new LSP server that typechecks files against `globals`.
+    pub(crate) fn new_with_typecheck(globals: Globals) -> anyhow::Result<Self> {
+        Self::start(None, Some(globals))
+    }
+
+    fn start(
+        settings: Option<LspServerSettings>,
+        typecheck_globals: Option<Globals>,
+    ) -> anyhow::Result<Self> {
         let (server_connection, client_connection) = Connection::memory();
 
         let builtin = Self::testing_builtins(&std::env::current_dir()?)?;
@@ -434,6 +452,7 @@ impl TestServer {
             dirs: dirs.dupe(),
             builtin_docs: builtin_docs.dupe(),
             builtin_symbols,
+            typecheck_globals,
         };
 
         let server_thread = std::thread::spawn(|| {
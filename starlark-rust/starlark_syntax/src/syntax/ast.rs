@@ -531,8 +531,20 @@ impl Display for Expr {
                 body,
                 payload: _,
             }) => {
+                let typed = params.iter().any(|p| match &p.node {
+                    ParameterP::Normal(_, ty, _)
+                    | ParameterP::Args(_, ty)
+                    | ParameterP::KwArgs(_, ty) => ty.is_some(),
+                    ParameterP::Slash | ParameterP::NoArgs => false,
+                });
                 f.write_str("(lambda ")?;
+                if typed {
+                    f.write_str("(")?;
+                }
                 comma_separated_fmt(f, params, |x, f| write!(f, "{}", x.node), false)?;
+                if typed {
+                    f.write_str(")")?;
+                }
                 f.write_str(": ")?;
                 write!(f, "{}", body.node)?;
                 f.write_str(")")
@@ -215,6 +215,15 @@ fn test_tuples() {
     assert_eq!(parse("a = ()"), "a = ()\n");
 }
 
+#[test]
+fn test_index() {
+    assert_eq!(parse("a[1]"), "a[1]\n");
+    assert_eq!(parse("a[1, 2]"), "a[1, 2]\n");
+    // More than two indices are a tuple.
+    assert_eq!(parse("a[1, 2, 3]"), "a[(1, 2, 3)]\n");
+    assert_eq!(parse("a[1, 2, 3, 4,]"), "a[(1, 2, 3, 4)]\n");
+}
+
 #[test]
 fn test_return() {
     assert_eq!(parse("def fn(): return 1"), "def fn():\n  return 1\n");
@@ -300,6 +309,15 @@ fn test_lambda() {
     );
     assert_eq!(parse("lambda x: True"), "(lambda x: True)\n");
     assert_eq!(parse("lambda: True"), "(lambda : True)\n");
+    assert_eq!(
+        parse("f = lambda (x: int, y: str = 'a'): x"),
+        "f = (lambda (x: int, y: str = \"a\"): x)\n"
+    );
+    assert_eq!(
+        parse("lambda (x, *args: int): x"),
+        "(lambda (x, *args: int): x)\n"
+    );
+    assert_eq!(parse("lambda (x): x"), "(lambda x: x)\n");
     assert_eq!(
         parse("f(lambda x, y=1, *args, **kwargs: x + y + z)"),
         "f((lambda x, y = 1, *args, **kwargs: ((x + y) + z)))\n"
@@ -72,6 +72,8 @@ pub enum TypeExprUnpackP<'a, P: AstPayload> {
         Box<Spanned<TypeExprUnpackP<'a, P>>>,
         Box<Spanned<TypeExprUnpackP<'a, P>>>,
     ),
+    /// `typing.Literal["a", "b"]`: a path indexed with string literals.
+    Literal(Spanned<TypePathP<'a, P>>, Vec<Spanned<&'a str>>),
     /// List argument in `typing.Callable[[int], str]`.
     List(Vec<Spanned<TypeExprUnpackP<'a, P>>>),
     Union(Vec<Spanned<TypeExprUnpackP<'a, P>>>),
@@ -158,6 +160,20 @@ impl<'a, P: AstPayload> TypeExprUnpackP<'a, P> {
         }
     }
 
+    /// String literals, if all the expressions are.
+    fn unpack_str_literals(exprs: &[&'a AstExprP<P>]) -> Option<Vec<Spanned<&'a str>>> {
+        exprs
+            .iter()
+            .map(|expr| match &expr.node {
+                ExprP::Literal(AstLiteral::String(s)) => Some(Spanned {
+                    span: expr.span,
+                    node: s.node.as_str(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn unpack(
         expr: &'a AstExprP<P>,
         codemap: &CodeMap,
@@ -189,6 +205,17 @@ impl<'a, P: AstPayload> TypeExprUnpackP<'a, P> {
             ExprP::Call(..) => err("call"),
             ExprP::Index(a_i) => {
                 let (a, i) = &**a_i;
+                let items: Vec<&AstExprP<P>> = match &i.node {
+                    ExprP::Tuple(xs) => xs.iter().collect(),
+                    _ => vec![i],
+                };
+                if let Some(literals) = Self::unpack_str_literals(&items) {
+                    let path = Self::unpack_path(a, codemap)?;
+                    return Ok(Spanned {
+                        span,
+                        node: TypeExprUnpackP::Literal(path, literals),
+                    });
+                }
                 match &a.node {
                     ExprP::Identifier(ident) => {
                         let i = TypeExprUnpackP::unpack(i, codemap)?;
@@ -203,6 +230,12 @@ impl<'a, P: AstPayload> TypeExprUnpackP<'a, P> {
             ExprP::Index2(a_i0_i1) => {
                 let (a, i0, i1) = &**a_i0_i1;
                 let path = Self::unpack_path(a, codemap)?;
+                if let Some(literals) = Self::unpack_str_literals(&[i0, i1]) {
+                    return Ok(Spanned {
+                        span,
+                        node: TypeExprUnpackP::Literal(path, literals),
+                    });
+                }
                 let i0 = TypeExprUnpackP::unpack_argument(i0, codemap)?;
                 let i1 = TypeExprUnpackP::unpack_argument(i1, codemap)?;
                 Ok(Spanned {
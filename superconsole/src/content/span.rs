@@ -38,6 +38,9 @@ enum SpanError {
 pub struct Span {
     pub(crate) content: Cow<'static, str>,
     pub style: ContentStyle,
+    /// Target of an OSC 8 hyperlink around the content. The escape sequences are only written when
+    /// rendering, so they do not count towards the width of the span.
+    pub(crate) hyperlink: Option<Cow<'static, str>>,
 }
 
 /// Test whether a char is permissable to be inside a Span.
@@ -60,6 +63,7 @@ impl Span {
         Span {
             content: Cow::Borrowed("-"),
             style: ContentStyle::default(),
+            hyperlink: None,
         }
     }
 
@@ -74,6 +78,7 @@ impl Span {
         Span {
             content: Cow::Owned(content),
             style: ContentStyle::default(),
+            hyperlink: None,
         }
     }
 
@@ -81,11 +86,25 @@ impl Span {
         &self.content
     }
 
+    /// Make the span an OSC 8 hyperlink to `url`, which terminals that support them let users
+    /// open. Other terminals ignore it. Control characters are removed from the URL.
+    pub fn with_hyperlink<S: std::fmt::Display>(mut self, url: S) -> Self {
+        let mut url = url.to_string();
+        url.retain(|c| !c.is_control());
+        self.hyperlink = Some(Cow::Owned(url));
+        self
+    }
+
+    pub fn hyperlink(&self) -> Option<&str> {
+        self.hyperlink.as_deref()
+    }
+
     /// Create an unstyled span with the specified amount of whitespace padding.
     pub fn padding(amount: usize) -> Self {
         Self {
             content: Cow::Owned(format!("{:<width$}", "", width = amount)),
             style: ContentStyle::default(),
+            hyperlink: None,
         }
     }
 
@@ -97,6 +116,7 @@ impl Span {
             Ok(Self {
                 content: Cow::Owned(owned),
                 style: ContentStyle::default(),
+                hyperlink: None,
             })
         } else {
             Err(SpanError::InvalidWhitespace(owned).into())
@@ -108,6 +128,7 @@ impl Span {
         Self {
             content: Cow::Owned(content),
             style: ContentStyle::default(),
+            hyperlink: None,
         }
     }
 
@@ -118,6 +139,7 @@ impl Span {
             Ok(Self {
                 content: Cow::Owned(content.content().clone()),
                 style: *content.style(),
+                hyperlink: None,
             })
         } else {
             Err(SpanError::InvalidWhitespace(content.content().to_owned()).into())
@@ -130,6 +152,7 @@ impl Span {
         Self {
             content: Cow::Owned(content),
             style: *span.style(),
+            hyperlink: None,
         }
     }
 
@@ -168,7 +191,11 @@ impl Span {
     /// Because a `Grapheme` is represented as another string, the sub-`Span` is represented as a `Span`.
     /// This `panics` if it encounters unicode that it doesn't know how to deal with.
     pub fn iter(&self) -> impl Iterator<Item = Span> + '_ {
-        SpanIterator(&self.style, self.content.graphemes(true))
+        SpanIterator(
+            &self.style,
+            self.content.graphemes(true),
+            self.hyperlink.as_ref(),
+        )
     }
 
     pub(crate) fn render(&self, f: &mut impl fmt::Write) -> fmt::Result {
@@ -180,6 +207,10 @@ impl Span {
         let mut reset_foreground = false;
         let mut reset = false;
 
+        if let Some(url) = &self.hyperlink {
+            write!(f, "\x1b]8;;{}\x1b\\", url)?;
+        }
+
         if let Some(bg) = self.style.background_color {
             SetBackgroundColor(bg).write_ansi(f)?;
             reset_background = true;
@@ -206,6 +237,10 @@ impl Span {
             }
         }
 
+        if self.hyperlink.is_some() {
+            write!(f, "\x1b]8;;\x1b\\")?;
+        }
+
         Ok(())
     }
 
@@ -262,7 +297,8 @@ impl Span {
             fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
                 let style_is_default = self.0.style.foreground_color.is_none()
                     && self.0.style.background_color.is_none()
-                    && self.0.style.attributes.is_empty();
+                    && self.0.style.attributes.is_empty()
+                    && self.0.hyperlink.is_none();
                 if style_is_default {
                     write!(f, "{}", self.0.content)
                 } else {
@@ -285,6 +321,9 @@ impl Span {
                             write!(f, " unknown_attributes={:?}", a)?;
                         }
                     }
+                    if let Some(url) = &self.0.hyperlink {
+                        write!(f, " href={}", url)?;
+                    }
                     write!(f, ">")?;
                     write!(f, "{}", self.0.content)?;
                     write!(f, "</span>")?;
@@ -321,7 +360,11 @@ impl TryFrom<StyledContent<String>> for Span {
     }
 }
 
-pub(crate) struct SpanIterator<'a>(&'a ContentStyle, Graphemes<'a>);
+pub(crate) struct SpanIterator<'a>(
+    &'a ContentStyle,
+    Graphemes<'a>,
+    Option<&'a Cow<'static, str>>,
+);
 
 impl<'a> Iterator for SpanIterator<'a> {
     type Item = Span;
@@ -331,6 +374,7 @@ impl<'a> Iterator for SpanIterator<'a> {
         content.map(|content| Span {
             style: *self.0,
             content: Cow::Owned(content.to_owned()),
+            hyperlink: self.2.cloned(),
         })
     }
 }
@@ -391,6 +435,27 @@ mod tests {
         assert_eq!(lhs, rhs);
     }
 
+    #[test]
+    fn test_hyperlink() {
+        let span = Span::new_styled("foo".to_owned().red())
+            .unwrap()
+            .with_hyperlink("https://example.com/\x1b");
+        assert_eq!(span.hyperlink(), Some("https://example.com/"));
+        assert_eq!(span.len(), 3);
+
+        let mut rendered = String::new();
+        span.render(&mut rendered).unwrap();
+        assert!(rendered.starts_with("\x1b]8;;https://example.com/\x1b\\"));
+        assert!(rendered.ends_with("\x1b]8;;\x1b\\"));
+        assert_eq!(
+            "<span fg=red href=https://example.com/>foo</span>",
+            span.fmt_for_test().to_string()
+        );
+
+        // Graphemes of a hyperlink are still part of the hyperlink.
+        assert!(span.iter().all(|s| s.hyperlink() == span.hyperlink()));
+    }
+
     #[test]
     fn test_fmt_for_test() {
         let span = Span::new_styled(StyledContent::new(
@@ -44,6 +44,8 @@ pub struct SuperConsole {
     /// from the terminal. This generally is only used for testing
     /// situations.
     fallback_size: Option<Dimensions>,
+    /// Number of renders skipped because the output was not ready for another frame.
+    dropped_frames: u64,
     /// The terminal handle to write a buffer to the screen.
     /// All IO goes through this handle.
     pub(crate) output: Box<dyn SuperConsoleOutput>,
@@ -77,6 +79,7 @@ impl SuperConsole {
             canvas_contents: Lines::new(),
             to_emit: Lines::new(),
             fallback_size,
+            dropped_frames: 0,
             output,
         }
     }
@@ -102,6 +105,9 @@ impl SuperConsole {
         let mut has_rendered = false;
         while !has_rendered || (anything_emitted && !self.to_emit.is_empty()) {
             if !self.output.should_render() {
+                if !has_rendered {
+                    self.dropped_frames += 1;
+                }
                 break;
             }
 
@@ -114,6 +120,12 @@ impl SuperConsole {
         Ok(())
     }
 
+    /// Number of calls to [`render`](Self::render) which drew nothing because the output was still
+    /// busy with previous frames, e.g. because the terminal is slow or blocked.
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped_frames
+    }
+
     /// Perform a final render with [`DrawMode::Final`].
     /// Each component will have a chance to finalize themselves before the terminal is disposed of.
     pub fn finalize(self, root: &dyn Component) -> anyhow::Result<()> {
@@ -329,6 +341,7 @@ mod tests {
         console.emit(Lines(vec![vec!["line 1"].try_into()?]));
         console.render(&root)?;
         assert_eq!(console.test_output()?.frames.len(), 1);
+        assert_eq!(console.dropped_frames(), 2);
 
         Ok(())
     }
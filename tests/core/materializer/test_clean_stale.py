@@ -8,12 +8,13 @@
 # pyre-strict
 
 
+import json
 import re
 import shutil
 import time
 from datetime import datetime, timedelta
 from pathlib import Path
-from typing import List
+from typing import Any, Dict, List
 
 from buck2.tests.e2e_util.api.buck import Buck
 from buck2.tests.e2e_util.buck_workspace import buck_test, env
@@ -139,6 +140,50 @@ async def test_artifact_access_time_flushing_partial(buck: Buck) -> None:
     assert data[0] == "1"
 
 
+@buck_test()
+@env("BUCK_ACCESS_TIME_UPDATE_MAX_BUFFER_SIZE", "2")
+async def test_restart_keeps_materializer_state(buck: Buck) -> None:
+    # Only flush when the buffer is full, so that the access time of the second build is only
+    # written to the DB by the restart.
+    modify_acess_times_updates(buck, "partial")
+
+    async def status() -> Dict[str, Any]:
+        return json.loads((await buck.status()).stdout)
+
+    await buck.build("root//:copy")
+    time.sleep(1)
+    accessed = datetime.utcnow().replace(microsecond=0)
+    await buck.build("root//:copy")
+    before = await status()
+
+    with open(buck.cwd / ".buckconfig", "a") as f:
+        f.write("[buck2]\n")
+        f.write("daemon_buster = 1\n")
+
+    result = await buck.build("root//:copy")
+    assert "Startup config mismatch" in result.stderr
+    assert "Kept the materializer state of the previous daemon." in result.stderr
+    after = await status()
+
+    assert after["process_info"]["pid"] != before["process_info"]["pid"]
+    assert (
+        after["daemon_constraints"]["extra"]["materializer_state_identity"]
+        == before["daemon_constraints"]["extra"]["materializer_state_identity"]
+    )
+
+    materialized = [
+        line
+        for line in (await buck.audit("deferred-materializer", "list"))
+        .stdout.strip()
+        .splitlines()
+        if "\tmaterialized" in line
+    ]
+    assert len(materialized) == 1
+    match = re.search("\tmaterialized \\(ts=([^ ,]*)", materialized[0])
+    assert match
+    assert datetime.strptime(match.group(1), "%Y-%m-%dT%H:%M:%SZ") >= accessed
+
+
 @buck_test()
 @env("BUCK_LOG", "buck2_execute_impl::materializers=trace")
 @env("BUCK_ACCESS_TIME_UPDATE_MAX_BUFFER_SIZE", "0")
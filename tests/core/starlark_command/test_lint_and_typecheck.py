@@ -28,3 +28,13 @@ async def test_typecheck_fails(buck: Buck) -> None:
         buck.starlark("typecheck", "bad.bzl"),
         stderr_regex="Detected 2 errors",
     )
+
+
+@buck_test()
+async def test_typecheck_load_cycle(buck: Buck) -> None:
+    await buck.starlark("typecheck", "cycle_a.bzl")
+    await buck.starlark("typecheck", "--load-cycles=any", "cycle_a.bzl")
+    await expect_failure(
+        buck.starlark("typecheck", "--load-cycles=error", "cycle_a.bzl"),
+        stderr_regex="Load cycle",
+    )
@@ -126,6 +126,7 @@ export const sidebars: SidebarsConfig = {
             'users/advanced/restarter',
             'users/advanced/in_memory_cache',
             'users/advanced/external_cells',
+            'users/advanced/thin_client',
             isInternal() ? 'users/advanced/offline_build_archives' : null,
             isInternal() ? 'users/advanced/vpnless' : null,
           ].flatMap(x => x !== null ? [x] : []),